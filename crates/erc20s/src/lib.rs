@@ -7,6 +7,7 @@ pub struct ERC20 {
     pub asset_id: AssetId,
     pub chain_id: u64,
     pub symbol: &'static str,
+    pub name: &'static str,
     pub slot: u64,
     pub decimals: u8,
 }
@@ -22,6 +23,7 @@ pub const ERC20S: [ERC20; 3] = [
         ),
         chain_id: CHAIN_ID,
         symbol: "WETH",
+        name: "Wrapped Ether",
         slot: 3,
         decimals: 18,
     },
@@ -33,6 +35,7 @@ pub const ERC20S: [ERC20; 3] = [
         ),
         chain_id: CHAIN_ID,
         symbol: "DAI",
+        name: "Dai Stablecoin",
         slot: 2,
         decimals: 18,
     },
@@ -44,6 +47,7 @@ pub const ERC20S: [ERC20; 3] = [
         ),
         chain_id: CHAIN_ID,
         symbol: "GTC",
+        name: "Gitcoin",
         slot: 5,
         decimals: 18,
     },
@@ -0,0 +1,84 @@
+//! ABI error registry used to turn raw revert data into readable messages.
+
+use alloy::{
+    primitives::{Bytes, FixedBytes, U256, keccak256},
+    sol_types::{Panic, Revert, SolError},
+};
+use tlock_hdk::tlock_api::host::DecodedError;
+
+/// Well-known custom error signatures. Selectors are derived from these at
+/// lookup time so the table stays human-readable.
+const KNOWN_ERRORS: &[&str] = &[
+    // ERC-6093 ERC20 errors
+    "ERC20InsufficientBalance(address,uint256,uint256)",
+    "ERC20InvalidSender(address)",
+    "ERC20InvalidReceiver(address)",
+    "ERC20InsufficientAllowance(address,uint256,uint256)",
+    "ERC20InvalidApprover(address)",
+    "ERC20InvalidSpender(address)",
+    // ERC-6093 ERC721 errors
+    "ERC721InvalidOwner(address)",
+    "ERC721NonexistentToken(uint256)",
+    "ERC721IncorrectOwner(address,uint256,address)",
+    "ERC721InsufficientApproval(address,uint256)",
+    // OpenZeppelin access control
+    "OwnableUnauthorizedAccount(address)",
+    "AccessControlUnauthorizedAccount(address,bytes32)",
+    "ReentrancyGuardReentrantCall()",
+    "SafeERC20FailedOperation(address)",
+];
+
+/// Decodes ABI-encoded revert data.
+pub fn decode_error(data: &[u8]) -> DecodedError {
+    if data.is_empty() {
+        return DecodedError::Empty;
+    }
+
+    if let Ok(revert) = Revert::abi_decode(data) {
+        return DecodedError::Revert(revert.reason);
+    }
+
+    if let Ok(panic) = Panic::abi_decode(data) {
+        return DecodedError::Panic {
+            code: panic.code,
+            reason: panic_reason(panic.code).to_string(),
+        };
+    }
+
+    if data.len() < 4 {
+        return DecodedError::Custom {
+            selector: FixedBytes::ZERO,
+            signature: None,
+            data: Bytes::copy_from_slice(data),
+        };
+    }
+
+    let selector = FixedBytes::<4>::from_slice(&data[..4]);
+    let signature = KNOWN_ERRORS
+        .iter()
+        .find(|sig| keccak256(sig.as_bytes())[..4] == selector[..])
+        .map(|sig| sig.to_string());
+
+    DecodedError::Custom {
+        selector,
+        signature,
+        data: Bytes::copy_from_slice(&data[4..]),
+    }
+}
+
+/// https://docs.soliditylang.org/en/latest/control-structures.html#panic-via-assert-and-error-via-require
+fn panic_reason(code: U256) -> &'static str {
+    match code.saturating_to::<u64>() {
+        0x00 => "generic compiler panic",
+        0x01 => "assertion failed",
+        0x11 => "arithmetic overflow or underflow",
+        0x12 => "division or modulo by zero",
+        0x21 => "invalid enum value",
+        0x22 => "invalid storage byte array encoding",
+        0x31 => "pop on empty array",
+        0x32 => "array index out of bounds",
+        0x41 => "out of memory",
+        0x51 => "call to uninitialized function",
+        _ => "unknown panic code",
+    }
+}
@@ -0,0 +1,175 @@
+//! The address book of labeled accounts shared by the user and plugins.
+//!
+//! Forms offer saved contacts as recipients, so a plugin that could relabel
+//! one could pass its own address off as the user's. Each contact therefore
+//! records who saved it, and a plugin can only change or remove the contacts
+//! it saved itself. The user can change any of them.
+
+use std::sync::Mutex;
+
+use serde::{Deserialize, Serialize};
+use tlock_hdk::{
+    tlock_api::{addressbook::Contact, caip::AccountId},
+    wasmi_plugin_hdk::plugin_id::PluginId,
+};
+
+/// A contact and the plugin that saved it
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SavedContact {
+    #[serde(flatten)]
+    pub contact: Contact,
+    /// The plugin that saved the contact, or None if the user did
+    #[serde(default)]
+    pub owner: Option<PluginId>,
+}
+
+#[derive(Debug, Default)]
+pub struct AddressBook {
+    contacts: Mutex<Vec<SavedContact>>,
+}
+
+impl AddressBook {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn from_contacts(contacts: Vec<SavedContact>) -> Self {
+        Self {
+            contacts: Mutex::new(contacts),
+        }
+    }
+
+    pub fn contacts(&self) -> Vec<SavedContact> {
+        self.contacts.lock().unwrap().clone()
+    }
+
+    pub fn list(&self) -> Vec<Contact> {
+        let contacts = self.contacts.lock().unwrap();
+        contacts.iter().map(|c| c.contact.clone()).collect()
+    }
+
+    /// Saves a contact on behalf of `owner`, or the user if None. If the
+    /// account is already saved its label is replaced, which a plugin may
+    /// only do for contacts it saved.
+    pub fn add(&self, owner: Option<PluginId>, contact: Contact) -> Result<(), String> {
+        let mut contacts = self.contacts.lock().unwrap();
+        let existing = contacts
+            .iter_mut()
+            .find(|c| c.contact.account == contact.account);
+        match existing {
+            Some(existing) => {
+                check_owner(existing, owner)?;
+                existing.contact.label = contact.label;
+                existing.owner = owner;
+            }
+            None => contacts.push(SavedContact { contact, owner }),
+        }
+        Ok(())
+    }
+
+    /// Removes a saved account on behalf of `owner`, or the user if None,
+    /// which a plugin may only do for contacts it saved.
+    pub fn remove(&self, owner: Option<PluginId>, account: &AccountId) -> Result<(), String> {
+        let mut contacts = self.contacts.lock().unwrap();
+        let Some(index) = contacts.iter().position(|c| &c.contact.account == account) else {
+            return Ok(());
+        };
+        check_owner(&contacts[index], owner)?;
+        contacts.remove(index);
+        Ok(())
+    }
+
+    /// Moves a plugin's contacts to a new ID, e.g. when it's upgraded.
+    pub fn transfer(&self, old_id: &PluginId, new_id: PluginId) {
+        for contact in self.contacts.lock().unwrap().iter_mut() {
+            if contact.owner.as_ref() == Some(old_id) {
+                contact.owner = Some(new_id);
+            }
+        }
+    }
+}
+
+fn check_owner(contact: &SavedContact, owner: Option<PluginId>) -> Result<(), String> {
+    if owner.is_some() && contact.owner != owner {
+        return Err(format!(
+            "Contact {} wasn't saved by this plugin",
+            contact.contact.account
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use alloy::primitives::Address;
+
+    use super::*;
+
+    fn account(byte: u8) -> AccountId {
+        AccountId::new_evm(1, Address::repeat_byte(byte))
+    }
+
+    #[test]
+    fn plugins_only_change_their_own_contacts() {
+        let book = AddressBook::new();
+        let (plugin, other) = (PluginId::from(1u128), PluginId::from(2u128));
+        book.add(None, Contact::new("Savings", account(1))).unwrap();
+        book.add(Some(plugin), Contact::new("Exchange", account(2)))
+            .unwrap();
+
+        assert!(
+            book.add(Some(plugin), Contact::new("Mine", account(1)))
+                .is_err()
+        );
+        assert!(book.remove(Some(plugin), &account(1)).is_err());
+        assert!(
+            book.add(Some(other), Contact::new("Mine", account(2)))
+                .is_err()
+        );
+        assert!(book.remove(Some(other), &account(2)).is_err());
+
+        book.add(Some(plugin), Contact::new("Exchange 2", account(2)))
+            .unwrap();
+        let labels: Vec<_> = book.list().into_iter().map(|c| c.label).collect();
+        assert_eq!(labels, ["Savings", "Exchange 2"]);
+
+        book.remove(Some(plugin), &account(2)).unwrap();
+        assert_eq!(book.list().len(), 1);
+    }
+
+    #[test]
+    fn the_user_changes_any_contact_and_takes_it_over() {
+        let book = AddressBook::new();
+        let plugin = PluginId::from(1u128);
+        book.add(Some(plugin), Contact::new("Exchange", account(1)))
+            .unwrap();
+
+        book.add(None, Contact::new("Renamed", account(1))).unwrap();
+        assert_eq!(book.contacts()[0].owner, None);
+        assert!(book.remove(Some(plugin), &account(1)).is_err());
+        book.remove(None, &account(1)).unwrap();
+        assert!(book.list().is_empty());
+    }
+
+    #[test]
+    fn contacts_follow_their_plugin_through_upgrades() {
+        let book = AddressBook::new();
+        let (old, new) = (PluginId::from(1u128), PluginId::from(2u128));
+        book.add(Some(old), Contact::new("Exchange", account(1)))
+            .unwrap();
+
+        book.transfer(&old, new);
+        assert!(book.remove(Some(old), &account(1)).is_err());
+        book.remove(Some(new), &account(1)).unwrap();
+    }
+
+    #[test]
+    fn contacts_saved_before_owners_were_recorded_belong_to_the_user() {
+        let json = serde_json::json!({
+            "label": "Savings",
+            "account": account(1).to_string(),
+        });
+        let contact: SavedContact = serde_json::from_value(json).unwrap();
+        assert_eq!(contact.owner, None);
+    }
+}
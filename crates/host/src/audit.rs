@@ -0,0 +1,83 @@
+//! An append-only record of the value-moving calls plugins make.
+//!
+//! Unlike the event log, entries are never dropped and are persisted with the
+//! host's state, so the user can review every withdrawal, broadcast
+//! transaction, and coordinator proposal after the fact.
+
+use std::sync::Mutex;
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use tlock_hdk::wasmi_plugin_hdk::plugin_id::PluginId;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AuditEntry {
+    pub seq: u64,
+    pub timestamp: chrono::DateTime<chrono::Local>,
+    /// The plugin that made the call
+    pub plugin_id: PluginId,
+    /// The plugin's name when the call was made
+    pub plugin: String,
+    pub method: String,
+    /// Hex-encoded SHA-256 of the call's JSON-encoded params
+    pub params_hash: String,
+    pub outcome: AuditOutcome,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AuditOutcome {
+    Success,
+    Failed(String),
+}
+
+#[derive(Debug, Default)]
+pub struct AuditLog {
+    entries: Mutex<Vec<AuditEntry>>,
+}
+
+impl AuditLog {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn from_entries(entries: Vec<AuditEntry>) -> Self {
+        Self {
+            entries: Mutex::new(entries),
+        }
+    }
+
+    pub fn push(
+        &self,
+        plugin_id: PluginId,
+        plugin: String,
+        method: &str,
+        params: &impl Serialize,
+        outcome: AuditOutcome,
+    ) {
+        let mut entries = self.entries.lock().unwrap();
+        let seq = entries.last().map_or(0, |e| e.seq + 1);
+        entries.push(AuditEntry {
+            seq,
+            timestamp: chrono::Local::now(),
+            plugin_id,
+            plugin,
+            method: method.to_string(),
+            params_hash: params_hash(params),
+            outcome,
+        });
+    }
+
+    pub fn all(&self) -> Vec<AuditEntry> {
+        self.entries.lock().unwrap().clone()
+    }
+
+    pub fn export_json(&self) -> Result<String, serde_json::Error> {
+        serde_json::to_string_pretty(&*self.entries.lock().unwrap())
+    }
+}
+
+fn params_hash(params: &impl Serialize) -> String {
+    //? Params are host-constructed RPC types, so encoding can't fail in practice
+    let bytes = serde_json::to_vec(params).unwrap_or_default();
+    alloy::hex::encode(Sha256::digest(&bytes))
+}
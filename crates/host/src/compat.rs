@@ -0,0 +1,196 @@
+//! Translation for plugins built against older versions of the tlock API.
+//!
+//! The host only serves the latest version of each method. When a method's
+//! params or output change, `API_VERSION` is bumped, and calls from plugins
+//! targeting an older version are translated here: their params are upgraded
+//! before the call is handled, and the output is downgraded to the shape they
+//! were built against. Methods the host calls on plugins are given legacy
+//! definitions here, for calling plugins that serve an older version.
+
+use std::sync::Weak;
+
+use serde_json::{Value, json};
+use tlock_hdk::{
+    server::Next,
+    tlock_api::{
+        OptionalMethod, Permission, RpcMethod,
+        alloy::transports::BoxFuture,
+        caip::{AccountId, ChainId},
+        coordinator,
+        entities::CoordinatorId,
+        host, plugin,
+    },
+    wasmi_plugin_hdk::instance_id::InstanceId,
+    wasmi_plugin_pdk::rpc_message::RpcError,
+};
+
+use crate::host::Host;
+
+/// API version in which `host_fetch` started returning the response's status
+/// and headers alongside its body.
+pub const FETCH_RESPONSE: u32 = 2;
+/// API version in which entity requests started taking the capabilities the
+/// entity must serve.
+pub const ENTITY_CAPABILITIES: u32 = 3;
+/// API version in which `coordinator_get_session` started returning the
+/// session's TTL alongside its account.
+pub const SESSION_TTL: u32 = 4;
+/// API version in which `plugin_on_request_interrupted` started taking the
+/// request's ID and kind alongside its description.
+pub const REQUEST_KIND: u32 = 5;
+
+/// `coordinator_get_session` as served by coordinators targeting an API
+/// version before `SESSION_TTL`, which only return the session's account.
+pub struct LegacyGetSession;
+
+impl RpcMethod for LegacyGetSession {
+    type Params = (CoordinatorId, ChainId, Option<AccountId>);
+    type Output = AccountId;
+    const NAME: &'static str = coordinator::GetSession::NAME;
+    const PERMISSION: Permission = coordinator::GetSession::PERMISSION;
+}
+
+/// `plugin_on_request_interrupted` as served by plugins targeting an API
+/// version before `REQUEST_KIND`, which only take the request's description.
+pub struct LegacyOnRequestInterrupted;
+
+impl RpcMethod for LegacyOnRequestInterrupted {
+    type Params = String;
+    type Output = ();
+    const NAME: &'static str = plugin::OnRequestInterrupted::NAME;
+    const PERMISSION: Permission = plugin::OnRequestInterrupted::PERMISSION;
+}
+
+impl OptionalMethod for LegacyOnRequestInterrupted {}
+
+/// Middleware translating host calls from plugins targeting an older API
+/// version. Registered after the recording middleware, so recordings keep the
+/// shapes the plugin actually sent and received.
+pub fn translate_legacy_call<'a>(
+    host: &'a Weak<Host>,
+    instance: InstanceId,
+    method: &'a str,
+    params: Value,
+    next: Next<'a, Weak<Host>>,
+) -> BoxFuture<'a, Result<Value, RpcError>> {
+    Box::pin(async move {
+        let api_version = host
+            .upgrade()
+            .and_then(|host| host.get_api_version(&instance.plugin));
+        let Some(api_version) = api_version else {
+            return next.run(params).await;
+        };
+
+        let params = upgrade_params(api_version, method, params);
+        let output = next.run(params).await?;
+        Ok(downgrade_output(api_version, method, output))
+    })
+}
+
+/// Upgrades params sent by a plugin targeting `api_version` to the shape the
+/// host's method currently takes.
+pub fn upgrade_params(api_version: u32, method: &str, params: Value) -> Value {
+    //? Older plugins requested entities without capabilities, which matches
+    //? any entity serving the domain
+    if api_version < ENTITY_CAPABILITIES {
+        let capabilities = json!({});
+        if method == host::RequestEthProvider::NAME {
+            return json!([params, capabilities]);
+        }
+        if method == host::RequestVault::NAME {
+            return capabilities;
+        }
+    }
+    params
+}
+
+/// Downgrades a method's output to the shape a plugin targeting `api_version`
+/// expects.
+pub fn downgrade_output(api_version: u32, method: &str, output: Value) -> Value {
+    //? Older plugins only received the body, whatever the response's status
+    if api_version < FETCH_RESPONSE && method == host::Fetch::NAME {
+        return match output {
+            Value::Object(mut result) => match result.remove("Ok") {
+                Some(Value::Object(mut response)) => {
+                    json!({ "Ok": response.remove("body").unwrap_or_default() })
+                }
+                _ => Value::Object(result),
+            },
+            other => other,
+        };
+    }
+
+    if api_version < SESSION_TTL && method == coordinator::GetSession::NAME {
+        return match output {
+            Value::Object(mut session) => session.remove("account").unwrap_or_default(),
+            other => other,
+        };
+    }
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use tlock_hdk::tlock_api::{API_VERSION, host::EntityCapabilities};
+
+    use super::*;
+
+    #[test]
+    fn upgrades_entity_requests_without_capabilities() {
+        let params = upgrade_params(1, host::RequestEthProvider::NAME, json!("eip155:1"));
+        let (chain, capabilities): (Option<ChainId>, EntityCapabilities) =
+            serde_json::from_value(params).unwrap();
+        assert_eq!(chain, Some(ChainId::new_evm(1)));
+        assert_eq!(capabilities, EntityCapabilities::new());
+
+        let params = upgrade_params(2, host::RequestEthProvider::NAME, Value::Null);
+        let (chain, _): (Option<ChainId>, EntityCapabilities) =
+            serde_json::from_value(params).unwrap();
+        assert_eq!(chain, None);
+
+        let params = upgrade_params(2, host::RequestVault::NAME, Value::Null);
+        let capabilities: EntityCapabilities = serde_json::from_value(params).unwrap();
+        assert_eq!(capabilities, EntityCapabilities::new());
+    }
+
+    #[test]
+    fn leaves_current_params_alone() {
+        let params = json!(["eip155:1", { "chains": ["eip155:1"] }]);
+        let upgraded = upgrade_params(API_VERSION, host::RequestEthProvider::NAME, params.clone());
+        assert_eq!(upgraded, params);
+    }
+
+    #[test]
+    fn downgrades_fetch_to_its_body() {
+        let output = json!({ "Ok": { "status": 404, "headers": [], "body": [1, 2] } });
+        let downgraded = downgrade_output(1, host::Fetch::NAME, output.clone());
+        assert_eq!(downgraded, json!({ "Ok": [1, 2] }));
+        assert_eq!(
+            downgrade_output(FETCH_RESPONSE, host::Fetch::NAME, output.clone()),
+            output
+        );
+
+        let failed = json!({ "Err": "Unapproved origin" });
+        assert_eq!(
+            downgrade_output(1, host::Fetch::NAME, failed.clone()),
+            failed
+        );
+    }
+
+    #[test]
+    fn downgrades_session_to_its_account() {
+        let output = json!({
+            "account": "eip155:1:0x0000000000000000000000000000000000000001",
+            "ttl": { "secs": 60, "nanos": 0 },
+        });
+        let downgraded = downgrade_output(3, coordinator::GetSession::NAME, output.clone());
+        assert_eq!(
+            downgraded,
+            json!("eip155:1:0x0000000000000000000000000000000000000001")
+        );
+        assert_eq!(
+            downgrade_output(SESSION_TTL, coordinator::GetSession::NAME, output.clone()),
+            output
+        );
+    }
+}
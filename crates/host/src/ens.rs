@@ -0,0 +1,46 @@
+//! ENS contract bindings and name hashing used by the host's ENS methods.
+
+use alloy::{
+    hex,
+    primitives::{Address, B256, address, keccak256},
+    sol,
+};
+
+/// The ENS registry, deployed at the same address on mainnet and testnets.
+pub const ENS_REGISTRY: Address = address!("0x00000000000C2E074eC69A0dFb2997BA6C7d2e1e");
+
+sol! {
+    interface EnsRegistry {
+        function resolver(bytes32 node) external view returns (address);
+    }
+
+    interface EnsResolver {
+        function addr(bytes32 node) external view returns (address);
+        function name(bytes32 node) external view returns (string);
+    }
+}
+
+/// Computes the EIP-137 namehash of a name.
+///
+/// Names are lowercased but otherwise not normalized, so names outside of
+/// ASCII may not hash to their registered node.
+pub fn namehash(name: &str) -> B256 {
+    let name = name.to_lowercase();
+    let mut node = B256::ZERO;
+    if name.is_empty() {
+        return node;
+    }
+
+    for label in name.rsplit('.') {
+        let mut preimage = [0u8; 64];
+        preimage[..32].copy_from_slice(node.as_slice());
+        preimage[32..].copy_from_slice(keccak256(label.as_bytes()).as_slice());
+        node = keccak256(preimage);
+    }
+    node
+}
+
+/// Returns the reverse-resolution node for an address, `<addr>.addr.reverse`.
+pub fn reverse_node(address: Address) -> B256 {
+    namehash(&format!("{}.addr.reverse", hex::encode(address)))
+}
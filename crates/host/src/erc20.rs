@@ -0,0 +1,12 @@
+//! ERC20 contract bindings used by the host's deposit watches and simulation
+//! mode.
+
+use alloy::sol;
+
+sol! {
+    interface Erc20 {
+        function balanceOf(address owner) external view returns (uint256);
+
+        event Transfer(address indexed from, address indexed to, uint256 value);
+    }
+}
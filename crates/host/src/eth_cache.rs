@@ -0,0 +1,131 @@
+//! Caches idempotent reads from Ethereum providers.
+//!
+//! Nested plugin calls often ask the same provider for the same value many
+//! times while handling one interaction. The chain ID never changes for a
+//! provider, block number and gas price are kept briefly, and contract code
+//! is kept for the block it was read at.
+
+use std::{collections::HashMap, sync::Mutex};
+
+use alloy::{
+    eips::{BlockId, BlockNumberOrTag},
+    primitives::{Address, B256, Bytes, U256},
+};
+use chrono::{DateTime, Local};
+use tlock_hdk::tlock_api::entities::EthProviderId;
+
+/// Milliseconds a block number or gas price is reused before re-fetching.
+const SHORT_TTL_MS: i64 = 2000;
+/// Contract code entries kept before the cache is cleared.
+const MAX_CODE_ENTRIES: usize = 1024;
+
+/// The block a code read is pinned to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum CodeBlock {
+    Number(u64),
+    Hash(B256),
+}
+
+#[derive(Debug, Default)]
+pub struct EthCache {
+    chain_ids: Mutex<HashMap<EthProviderId, U256>>,
+    block_numbers: Mutex<HashMap<EthProviderId, (DateTime<Local>, u64)>>,
+    gas_prices: Mutex<HashMap<EthProviderId, (DateTime<Local>, u128)>>,
+    code: Mutex<HashMap<(EthProviderId, Address, CodeBlock), Bytes>>,
+}
+
+impl EthCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn chain_id(&self, provider_id: &EthProviderId) -> Option<U256> {
+        self.chain_ids.lock().unwrap().get(provider_id).copied()
+    }
+
+    pub fn set_chain_id(&self, provider_id: EthProviderId, chain_id: U256) {
+        self.chain_ids.lock().unwrap().insert(provider_id, chain_id);
+    }
+
+    pub fn block_number(&self, provider_id: &EthProviderId) -> Option<u64> {
+        fresh(&self.block_numbers.lock().unwrap(), provider_id)
+    }
+
+    pub fn set_block_number(&self, provider_id: EthProviderId, block_number: u64) {
+        let now = Local::now();
+        self.block_numbers
+            .lock()
+            .unwrap()
+            .insert(provider_id, (now, block_number));
+    }
+
+    pub fn gas_price(&self, provider_id: &EthProviderId) -> Option<u128> {
+        fresh(&self.gas_prices.lock().unwrap(), provider_id)
+    }
+
+    pub fn set_gas_price(&self, provider_id: EthProviderId, gas_price: u128) {
+        let now = Local::now();
+        self.gas_prices
+            .lock()
+            .unwrap()
+            .insert(provider_id, (now, gas_price));
+    }
+
+    pub fn code(
+        &self,
+        provider_id: EthProviderId,
+        address: Address,
+        block: BlockId,
+    ) -> Option<Bytes> {
+        let key = (provider_id, address, self.code_block(&provider_id, block)?);
+        self.code.lock().unwrap().get(&key).cloned()
+    }
+
+    pub fn set_code(
+        &self,
+        provider_id: EthProviderId,
+        address: Address,
+        block: BlockId,
+        code: Bytes,
+    ) {
+        let Some(block) = self.code_block(&provider_id, block) else {
+            return;
+        };
+
+        let mut entries = self.code.lock().unwrap();
+        if entries.len() >= MAX_CODE_ENTRIES {
+            entries.clear();
+        }
+        entries.insert((provider_id, address, block), code);
+    }
+
+    /// Drops everything cached for providers that no longer satisfy `keep`.
+    pub fn retain_providers(&self, mut keep: impl FnMut(&EthProviderId) -> bool) {
+        self.chain_ids.lock().unwrap().retain(|id, _| keep(id));
+        self.block_numbers.lock().unwrap().retain(|id, _| keep(id));
+        self.gas_prices.lock().unwrap().retain(|id, _| keep(id));
+        self.code.lock().unwrap().retain(|(id, _, _), _| keep(id));
+    }
+
+    /// Resolves a block ID to a specific block. `latest` resolves to the
+    /// cached block number while it's fresh, other tags aren't cached.
+    fn code_block(&self, provider_id: &EthProviderId, block: BlockId) -> Option<CodeBlock> {
+        match block {
+            BlockId::Hash(hash) => Some(CodeBlock::Hash(hash.block_hash)),
+            BlockId::Number(BlockNumberOrTag::Number(n)) => Some(CodeBlock::Number(n)),
+            BlockId::Number(BlockNumberOrTag::Latest) => {
+                self.block_number(provider_id).map(CodeBlock::Number)
+            }
+            BlockId::Number(_) => None,
+        }
+    }
+}
+
+fn fresh<T: Copy>(
+    entries: &HashMap<EthProviderId, (DateTime<Local>, T)>,
+    provider_id: &EthProviderId,
+) -> Option<T> {
+    let (fetched_at, value) = entries.get(provider_id)?;
+    let age = Local::now().signed_duration_since(*fetched_at);
+    (age.num_milliseconds() < SHORT_TTL_MS).then_some(*value)
+}
@@ -0,0 +1,80 @@
+//! Topic subscriptions for host-mediated pub/sub between plugins.
+
+use std::{
+    collections::{HashMap, HashSet},
+    sync::Mutex,
+};
+
+use tlock_hdk::wasmi_plugin_hdk::plugin_id::PluginId;
+
+/// Tracks which plugins are subscribed to which topics. Delivery is handled
+/// by the host, this only records subscriptions.
+#[derive(Debug, Default)]
+pub struct EventBus {
+    topics: Mutex<HashMap<String, HashSet<PluginId>>>,
+}
+
+impl EventBus {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn from_subscriptions(subscriptions: Vec<(String, PluginId)>) -> Self {
+        let bus = Self::new();
+        for (topic, plugin_id) in subscriptions {
+            bus.subscribe(topic, plugin_id);
+        }
+        bus
+    }
+
+    pub fn subscribe(&self, topic: String, plugin_id: PluginId) {
+        let mut topics = self.topics.lock().unwrap();
+        topics.entry(topic).or_default().insert(plugin_id);
+    }
+
+    pub fn unsubscribe(&self, topic: &str, plugin_id: &PluginId) {
+        let mut topics = self.topics.lock().unwrap();
+        if let Some(subscribers) = topics.get_mut(topic) {
+            subscribers.remove(plugin_id);
+            if subscribers.is_empty() {
+                topics.remove(topic);
+            }
+        }
+    }
+
+    /// Removes all of a plugin's subscriptions.
+    pub fn remove_plugin(&self, plugin_id: &PluginId) {
+        let mut topics = self.topics.lock().unwrap();
+        topics.retain(|_, subscribers| {
+            subscribers.remove(plugin_id);
+            !subscribers.is_empty()
+        });
+    }
+
+    /// Moves all of a plugin's subscriptions to another plugin, used when a
+    /// plugin is upgraded.
+    pub fn transfer(&self, from: &PluginId, to: PluginId) {
+        let mut topics = self.topics.lock().unwrap();
+        for subscribers in topics.values_mut() {
+            if subscribers.remove(from) {
+                subscribers.insert(to);
+            }
+        }
+    }
+
+    pub fn subscribers(&self, topic: &str) -> Vec<PluginId> {
+        let topics = self.topics.lock().unwrap();
+        topics
+            .get(topic)
+            .map(|subscribers| subscribers.iter().copied().collect())
+            .unwrap_or_default()
+    }
+
+    pub fn subscriptions(&self) -> Vec<(String, PluginId)> {
+        let topics = self.topics.lock().unwrap();
+        topics
+            .iter()
+            .flat_map(|(topic, subscribers)| subscribers.iter().map(|id| (topic.clone(), *id)))
+            .collect()
+    }
+}
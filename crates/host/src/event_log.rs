@@ -0,0 +1,136 @@
+//! A capped log of host and plugin events, shown in the frontend's events
+//! panel.
+
+use std::{collections::VecDeque, sync::Mutex};
+
+use tlock_hdk::{tlock_api::host::NotifyLevel, wasmi_plugin_hdk::plugin_id::PluginId};
+use uuid::Uuid;
+
+#[derive(Debug, Clone)]
+pub struct Event {
+    pub id: Uuid,
+    /// Position of the event in the log. Sequence numbers keep increasing
+    /// after old events are dropped, so they can be used as a cursor.
+    pub seq: u64,
+    pub timestamp: chrono::DateTime<chrono::Local>,
+    pub level: NotifyLevel,
+    pub plugin_id: Option<PluginId>,
+    /// The plugin's name when the event was logged
+    pub plugin: Option<String>,
+    /// The host method that produced the event, if any
+    pub method: Option<String>,
+    pub message: String,
+}
+
+/// Selects events from an `EventLog`. Unset fields match every event.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct EventFilter {
+    pub plugin_id: Option<PluginId>,
+    pub min_level: Option<NotifyLevel>,
+    pub method: Option<String>,
+    /// Only match events with a greater sequence number
+    pub after: Option<u64>,
+}
+
+impl EventFilter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_plugin(mut self, plugin_id: PluginId) -> Self {
+        self.plugin_id = Some(plugin_id);
+        self
+    }
+
+    pub fn with_min_level(mut self, level: NotifyLevel) -> Self {
+        self.min_level = Some(level);
+        self
+    }
+
+    pub fn with_method(mut self, method: impl Into<String>) -> Self {
+        self.method = Some(method.into());
+        self
+    }
+
+    pub fn after(mut self, seq: u64) -> Self {
+        self.after = Some(seq);
+        self
+    }
+
+    pub fn matches(&self, event: &Event) -> bool {
+        self.plugin_id.is_none_or(|id| event.plugin_id == Some(id))
+            && self
+                .min_level
+                .as_ref()
+                .is_none_or(|level| event.level >= *level)
+            && self
+                .method
+                .as_ref()
+                .is_none_or(|m| event.method.as_ref() == Some(m))
+            && self.after.is_none_or(|seq| event.seq > seq)
+    }
+}
+
+/// Keeps the most recent `capacity` events, dropping the oldest first.
+#[derive(Debug)]
+pub struct EventLog {
+    capacity: usize,
+    inner: Mutex<Inner>,
+}
+
+#[derive(Debug, Default)]
+struct Inner {
+    events: VecDeque<Event>,
+    next_seq: u64,
+}
+
+impl EventLog {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            inner: Mutex::new(Inner::default()),
+        }
+    }
+
+    pub fn push(
+        &self,
+        level: NotifyLevel,
+        plugin: Option<(PluginId, String)>,
+        method: Option<&str>,
+        message: impl Into<String>,
+    ) {
+        let mut inner = self.inner.lock().unwrap();
+        let seq = inner.next_seq;
+        inner.next_seq += 1;
+
+        let (plugin_id, plugin) = plugin.unzip();
+        inner.events.push_back(Event {
+            id: Uuid::new_v4(),
+            seq,
+            timestamp: chrono::Local::now(),
+            level,
+            plugin_id,
+            plugin,
+            method: method.map(str::to_string),
+            message: message.into(),
+        });
+        while inner.events.len() > self.capacity {
+            inner.events.pop_front();
+        }
+    }
+
+    /// Returns the matching events, oldest first.
+    pub fn query(&self, filter: &EventFilter) -> Vec<Event> {
+        let inner = self.inner.lock().unwrap();
+        inner
+            .events
+            .iter()
+            .filter(|event| filter.matches(event))
+            .cloned()
+            .collect()
+    }
+
+    pub fn all(&self) -> Vec<Event> {
+        self.inner.lock().unwrap().events.iter().cloned().collect()
+    }
+}
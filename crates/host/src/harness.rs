@@ -0,0 +1,222 @@
+//! A headless host for end-to-end plugin tests.
+//!
+//! `Harness` wraps a `Host` with no frontend attached. Anything driven through
+//! `Harness::run` has the user requests it raises answered from a `Script`,
+//! so a test can load compiled plugins, pick which vault or provider they're
+//! given, and then assert on the pages and state they leave behind. Requests
+//! the script has no answer for are denied, so a test fails rather than hangs.
+
+use std::{
+    collections::HashSet,
+    future::Future,
+    sync::{Arc, Mutex},
+};
+
+use futures::{
+    StreamExt,
+    channel::mpsc::{self, UnboundedReceiver},
+    future::{self, Either},
+    lock::Mutex as AsyncMutex,
+    pin_mut,
+};
+use tlock_hdk::{
+    tlock_api::{
+        component::Component,
+        entities::{CoordinatorId, EntityId, EthProviderId, PageId, SignerId, VaultId},
+    },
+    wasmi_plugin_hdk::plugin_id::PluginId,
+};
+use uuid::Uuid;
+
+use crate::{
+    host::{Host, PluginError, UserRequest},
+    host_state::PluginSource,
+};
+
+/// How the harness answers user requests. Plugin loads are always approved,
+/// including their manifests, network origins, and missing signatures.
+#[derive(Debug, Clone, Default)]
+pub struct Script {
+    pub eth_provider: Option<EthProviderId>,
+    pub vault: Option<VaultId>,
+    pub coordinator: Option<CoordinatorId>,
+    pub signer: Option<SignerId>,
+    /// Whether confirmations and value-moving calls are approved
+    pub approve: bool,
+    /// Index of the option picked for custom prompts
+    pub choice: Option<usize>,
+    /// Password the keystore is unlocked with
+    pub password: Option<String>,
+}
+
+pub struct Harness {
+    host: Arc<Host>,
+    script: Mutex<Script>,
+    /// Notified whenever the host's state changes. Subscribed once, so runs
+    /// don't each leave an observer behind.
+    updates: AsyncMutex<UnboundedReceiver<()>>,
+    /// Requests already answered, which stay listed until their caller wakes
+    handled: Mutex<HashSet<Uuid>>,
+}
+
+impl Default for Harness {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Harness {
+    pub fn new() -> Self {
+        Self::with_host(Arc::new(Host::new()))
+    }
+
+    /// Wraps an existing host, e.g. one restored with `Host::from_state`.
+    pub fn with_host(host: Arc<Host>) -> Self {
+        let (tx, rx) = mpsc::unbounded();
+        host.subscribe(tx);
+        Self {
+            host,
+            script: Mutex::new(Script::default()),
+            updates: AsyncMutex::new(rx),
+            handled: Mutex::new(HashSet::new()),
+        }
+    }
+
+    pub fn host(&self) -> &Arc<Host> {
+        &self.host
+    }
+
+    /// Changes how later requests are answered.
+    pub fn script(&self, edit: impl FnOnce(&mut Script)) {
+        edit(&mut self.script.lock().unwrap());
+    }
+
+    /// Loads a compiled plugin and initializes it.
+    pub async fn load(&self, wasm: Vec<u8>, name: &str) -> Result<PluginId, PluginError> {
+        let source = PluginSource::Embedded(wasm);
+        self.run(self.host.new_plugin(source, name)).await
+    }
+
+    /// Drives `fut` to completion, answering the user requests raised while
+    /// it runs.
+    pub async fn run<F: Future>(&self, fut: F) -> F::Output {
+        self.resolve_pending();
+
+        //? Concurrent runs share the one subscription, so only one of them
+        //? resolves requests at a time
+        let resolver = async {
+            let mut updates = self.updates.lock().await;
+            while updates.next().await.is_some() {
+                self.resolve_pending();
+            }
+        };
+        pin_mut!(fut, resolver);
+        match future::select(fut, resolver).await {
+            Either::Left((output, _)) => output,
+            Either::Right((_, fut)) => fut.await,
+        }
+    }
+
+    /// Answers every pending request from the script.
+    pub fn resolve_pending(&self) {
+        let script = self.script.lock().unwrap().clone();
+        for request in self.host.get_user_requests() {
+            let id = request.id();
+            if !self.handled.lock().unwrap().insert(id) {
+                continue;
+            }
+            if self.resolve(&script, &request).is_none() {
+                self.host.deny_user_request(id);
+            }
+        }
+    }
+
+    /// Answers a request from the script, or returns None if the script has
+    /// no answer for it.
+    fn resolve(&self, script: &Script, request: &UserRequest) -> Option<()> {
+        let host = &self.host;
+        let id = request.id();
+        match request {
+            UserRequest::ManifestApproval { .. } => host.approve_manifest_request(id),
+            UserRequest::OriginApproval { .. } => host.approve_origin_request(id),
+            UserRequest::UnsignedPlugin { .. } => host.approve_unsigned_plugin_request(id),
+            UserRequest::PluginUpgrade { .. } => host.approve_upgrade_request(id),
+            UserRequest::EthProviderSelection { .. } => {
+                host.resolve_eth_provider_request(id, script.eth_provider?, false)
+            }
+            UserRequest::VaultSelection { .. } => {
+                host.resolve_vault_request(id, script.vault?, false)
+            }
+            UserRequest::CoordinatorSelection { .. } => {
+                host.resolve_coordinator_request(id, script.coordinator?)
+            }
+            UserRequest::SignerSelection { .. } => host.resolve_signer_request(id, script.signer?),
+            UserRequest::CallApproval { .. } => {
+                script.approve.then_some(())?;
+                host.approve_call_request(id, None)
+            }
+            UserRequest::Confirmation { .. } => {
+                script.approve.then_some(())?;
+                host.confirm_request(id)
+            }
+            UserRequest::Custom { .. } => host.resolve_choice_request(id, script.choice?),
+            //? Unlocking resolves every pending unlock request
+            UserRequest::KeystoreUnlock { .. } => {
+                host.unlock_keystore(script.password.as_deref()?).ok()?
+            }
+            _ => return None,
+        }
+        Some(())
+    }
+
+    /// Entities registered by `plugin_id`.
+    pub fn entities(&self, plugin_id: &PluginId) -> Vec<EntityId> {
+        self.host
+            .get_entities()
+            .into_iter()
+            .filter(|e| self.host.get_entity_plugin_id(*e).as_ref() == Some(plugin_id))
+            .collect()
+    }
+
+    /// The first vault registered by `plugin_id`.
+    pub fn vault(&self, plugin_id: &PluginId) -> Option<VaultId> {
+        self.entities(plugin_id).into_iter().find_map(|e| match e {
+            EntityId::Vault(id) => Some(id),
+            _ => None,
+        })
+    }
+
+    /// The first Ethereum provider registered by `plugin_id`.
+    pub fn eth_provider(&self, plugin_id: &PluginId) -> Option<EthProviderId> {
+        self.entities(plugin_id).into_iter().find_map(|e| match e {
+            EntityId::EthProvider(id) => Some(id),
+            _ => None,
+        })
+    }
+
+    /// The first coordinator registered by `plugin_id`.
+    pub fn coordinator(&self, plugin_id: &PluginId) -> Option<CoordinatorId> {
+        self.entities(plugin_id).into_iter().find_map(|e| match e {
+            EntityId::Coordinator(id) => Some(id),
+            _ => None,
+        })
+    }
+
+    /// The first page registered by `plugin_id`.
+    pub fn page(&self, plugin_id: &PluginId) -> Option<PageId> {
+        self.entities(plugin_id).into_iter().find_map(|e| match e {
+            EntityId::Page(id) => Some(id),
+            _ => None,
+        })
+    }
+
+    /// The component a page last set with `host_set_page`.
+    pub fn interface(&self, page_id: PageId) -> Option<Component> {
+        self.host.get_interface(page_id)
+    }
+
+    /// The raw value a plugin has stored under `key`.
+    pub fn state(&self, plugin_id: &PluginId, key: &str) -> Option<Vec<u8>> {
+        self.host.get_state_value(plugin_id, key)
+    }
+}
@@ -0,0 +1,85 @@
+//! Vault history recorded by the host, and exports for accounting tools.
+
+use alloy::primitives::U256;
+use serde::Serialize;
+use tlock_hdk::tlock_api::{
+    caip::{AccountId, AssetId},
+    entities::VaultId,
+};
+use uuid::Uuid;
+
+/// A withdrawal routed through the host. Deposits and transfers made by a
+/// vault internally aren't visible to the host and are not recorded.
+#[derive(Debug, Clone)]
+pub struct VaultHistoryEntry {
+    pub id: Uuid,
+    pub vault_id: VaultId,
+    pub asset: AssetId,
+    pub amount: U256,
+    pub counterparty: AccountId,
+    pub timestamp: chrono::DateTime<chrono::Local>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    Csv,
+    Json,
+}
+
+impl ExportFormat {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ExportFormat::Csv => "csv",
+            ExportFormat::Json => "json",
+        }
+    }
+
+    pub fn mime_type(&self) -> &'static str {
+        match self {
+            ExportFormat::Csv => "text/csv",
+            ExportFormat::Json => "application/json",
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct HistoryRow {
+    timestamp: String,
+    kind: &'static str,
+    asset: String,
+    amount: String,
+    counterparty: String,
+}
+
+impl From<&VaultHistoryEntry> for HistoryRow {
+    fn from(entry: &VaultHistoryEntry) -> Self {
+        Self {
+            timestamp: entry.timestamp.to_rfc3339(),
+            kind: "withdrawal",
+            asset: entry.asset.to_string(),
+            amount: entry.amount.to_string(),
+            counterparty: entry.counterparty.to_string(),
+        }
+    }
+}
+
+pub fn export(
+    entries: &[VaultHistoryEntry],
+    format: ExportFormat,
+) -> Result<String, serde_json::Error> {
+    let rows: Vec<HistoryRow> = entries.iter().map(HistoryRow::from).collect();
+
+    match format {
+        ExportFormat::Json => serde_json::to_string_pretty(&rows),
+        ExportFormat::Csv => {
+            let mut csv = String::from("timestamp,kind,asset,amount,counterparty\n");
+            for row in rows {
+                csv.push_str(&format!(
+                    "{},{},{},{},{}\n",
+                    row.timestamp, row.kind, row.asset, row.amount, row.counterparty
+                ));
+            }
+            Ok(csv)
+        }
+    }
+}
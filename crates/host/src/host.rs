@@ -1,81 +1,339 @@
 use std::{
-    collections::HashMap,
+    collections::{HashMap, HashSet},
     hash::{DefaultHasher, Hash, Hasher},
     sync::{Arc, Mutex, Weak},
     time::Duration,
     usize,
 };
 
-use alloy::{primitives::U256, transports::http::reqwest};
+use alloy::{
+    consensus::Transaction as _,
+    eips::BlockId,
+    hex,
+    primitives::{Address, B256, Bytes, U256},
+    rpc::types::TransactionRequest,
+    sol_types::SolCall,
+    transports::http::reqwest,
+};
 use futures::channel::{mpsc::UnboundedSender, oneshot};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
 use thiserror::Error;
 use tlock_hdk::{
     impl_host_rpc, impl_host_rpc_no_id,
-    server::HostServer,
+    server::{HostServer, Next},
     tlock_api::{
-        RpcMethod,
+        API_VERSION, MIN_API_VERSION, OptionalMethod, Permission, RpcMethod, addressbook,
+        alloy::transports::BoxFuture,
+        btc,
         caip::{self, AccountId, AssetId},
-        component::Component,
+        component::{self, Component},
         coordinator,
         domains::Domain,
-        entities::{CoordinatorId, EntityId, EthProviderId, PageId, VaultId},
-        eth, global, host, page, plugin, state,
+        ens,
+        entities::{
+            BtcProviderId, BundlerProviderId, CoordinatorId, EntityId, EthProviderId,
+            NftProviderId, PageId, PriceProviderId, SignerId, SolProviderId, TokenListId, VaultId,
+        },
+        erc4337,
+        error::{DomainError, ErrorCode},
+        eth, fees, global, host, nft, notification, page, plugin, price, schema, signer, sol,
+        state, tokens, trace,
         vault::{self},
     },
+    trace::TraceId,
     wasmi_plugin_hdk::{self, instance_id::InstanceId, plugin::Plugin, plugin_id::PluginId},
     wasmi_plugin_pdk::rpc_message::{RpcError, RpcErrorContext},
 };
-use tracing::{info, warn};
+use tracing::{Instrument, info, warn};
 use uuid::Uuid;
 
-use crate::host_state::{HostState, PluginData, PluginSource};
+use crate::{
+    abi,
+    addressbook::AddressBook,
+    audit::{AuditEntry, AuditLog, AuditOutcome},
+    compat,
+    ens::{ENS_REGISTRY, EnsRegistry, EnsResolver, namehash, reverse_node},
+    erc20::Erc20,
+    eth_cache::EthCache,
+    event_bus::EventBus,
+    event_log::{Event, EventFilter, EventLog},
+    history::{self, ExportFormat, VaultHistoryEntry},
+    host_state::{
+        DepositWatch, EntityLabel, HOST_STATE_VERSION, HostState, PluginData, PluginSource,
+        ResourceLimits,
+    },
+    keystore::{Keystore, KeystoreError},
+    metrics::{MethodMetrics, Metrics},
+    nonces::NonceManager,
+    permissions::{Permissions, SessionGrant, SessionTerms},
+    rate_limit::{MethodClass, RateLimit, RateLimiter},
+    recording::{Direction, RecordedError, Recorder, Recording, Replayed, Replayer, call_raw},
+    scheduler::{CallStats, Scheduler},
+    signing::TrustedPublisher,
+    simulation::{SimulatedTransaction, Simulation},
+    state_store::{StateKey, StateStore, StateWrite, StoreError},
+    ticker::Ticker,
+    time,
+    transactions::{TrackedTransaction, TransactionTracker},
+    wasm_memory,
+    ws::WsConnections,
+};
+
+mod contacts;
+mod install;
+mod network;
+mod sessions;
+
+use network::FetchStream;
+use sessions::CoordinatorSession;
 
 pub struct Host {
     plugins: Mutex<HashMap<PluginId, Plugin>>,
     plugin_sources: Mutex<HashMap<PluginId, PluginSource>>,
+    /// SHA-256 of each plugin's loaded build
+    plugin_digests: Mutex<HashMap<PluginId, [u8; 32]>>,
+    /// Keys of the trusted publishers that signed each plugin's loaded build
+    plugin_publishers: Mutex<HashMap<PluginId, [u8; 32]>>,
+    manifests: Mutex<HashMap<PluginId, plugin::PluginManifest>>,
+    api_versions: Mutex<HashMap<PluginId, u32>>,
     entities: Mutex<HashMap<EntityId, PluginId>>,
+    entity_capabilities: Mutex<HashMap<EntityId, host::EntityCapabilities>>,
+    entity_labels: Mutex<HashMap<EntityId, EntityLabel>>,
 
-    // TODO: Restrict these to a max size / otherwise prevent plugins from abusing storage
     state: Mutex<HashMap<(PluginId, String), Vec<u8>>>,
+    /// Per-plugin state quotas overriding `DEFAULT_STATE_QUOTA_BYTES`
+    state_quotas: Mutex<HashMap<PluginId, usize>>,
+    /// Per-plugin overrides of the default `ResourceLimits`
+    resource_limits: Mutex<HashMap<PluginId, ResourceLimits>>,
     locks: Mutex<HashMap<(PluginId, String), (InstanceId, Arc<event_listener::Event>)>>,
+    state_store: Mutex<Option<Arc<dyn StateStore>>>,
+    /// State keys changed since the last `flush_state`
+    dirty_state: Mutex<HashSet<StateKey>>,
 
     interfaces: Mutex<HashMap<PageId, Component>>,
+    page_titles: Mutex<HashMap<PageId, String>>,
+    /// Pages opened in the frontend, with the current page last
+    page_stack: Mutex<Vec<PageId>>,
 
     // User requests awaiting user decisions
     user_requests: Mutex<Vec<UserRequest>>,
     user_request_senders: Mutex<HashMap<Uuid, oneshot::Sender<UserResponse>>>,
 
-    events: Mutex<Vec<Event>>,
+    events: EventLog,
+    vault_history: Mutex<Vec<VaultHistoryEntry>>,
+    active_chain: Mutex<caip::ChainId>,
+    notifications: Mutex<Vec<NotificationEntry>>,
+    /// Toasts the frontend hasn't shown yet
+    toasts: Mutex<Vec<(PluginId, host::Toast)>>,
+    address_book: AddressBook,
+    fetch_streams: Mutex<HashMap<host::FetchStreamId, FetchStream>>,
+    websockets: WsConnections,
+    keystore: Keystore,
+    alarms: Mutex<Vec<Alarm>>,
+    ticker: Ticker,
+    nonces: NonceManager,
+    transactions: TransactionTracker,
+    simulation: Simulation,
+    deposit_watches: Mutex<Vec<DepositWatch>>,
+    sessions: Mutex<Vec<CoordinatorSession>>,
+    event_bus: EventBus,
+    permissions: Permissions,
+    scheduler: Scheduler,
+    rate_limiter: RateLimiter,
+    metrics: Metrics,
+    /// Metrics for the host methods plugins call
+    host_call_metrics: Metrics,
+    recorder: Recorder,
+    /// Answers the host calls of a plugin being replayed
+    replayer: Mutex<Option<Replayer>>,
+    audit_log: AuditLog,
+    eth_cache: EthCache,
+    /// Per-method call deadlines overriding `PLUGIN_TIMEOUT_SECS`
+    call_timeouts: Mutex<HashMap<String, Duration>>,
+    /// Origins the user approved for `host_fetch` beyond those in manifests
+    fetch_origins: Mutex<Vec<(PluginId, String)>>,
+    /// Providers used for a chain's `host_request_eth_provider` calls instead
+    /// of prompting the user
+    default_eth_providers: Mutex<HashMap<caip::ChainId, EthProviderId>>,
+    /// Keys whose signatures let plugins load without an unsigned warning
+    trusted_publishers: Mutex<Vec<TrustedPublisher>>,
     observers: Mutex<Vec<UnboundedSender<()>>>,
 }
 
-#[derive(Debug, Clone, Eq, PartialEq)]
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
 pub enum UserRequest {
     EthProviderSelection {
         id: Uuid,
         plugin_id: PluginId,
         chain_id: caip::ChainId,
+        /// Capabilities the offered providers must satisfy, including the chain
+        required: host::EntityCapabilities,
     },
     VaultSelection {
         id: Uuid,
         plugin_id: PluginId,
+        required: host::EntityCapabilities,
     },
     CoordinatorSelection {
         id: Uuid,
         plugin_id: PluginId,
     },
+    SolProviderSelection {
+        id: Uuid,
+        plugin_id: PluginId,
+        chain_id: caip::ChainId,
+    },
+    BtcProviderSelection {
+        id: Uuid,
+        plugin_id: PluginId,
+        chain_id: caip::ChainId,
+    },
+    PriceProviderSelection {
+        id: Uuid,
+        plugin_id: PluginId,
+    },
+    SignerSelection {
+        id: Uuid,
+        plugin_id: PluginId,
+    },
+    BundlerProviderSelection {
+        id: Uuid,
+        plugin_id: PluginId,
+        chain_id: caip::ChainId,
+    },
+    TokenListSelection {
+        id: Uuid,
+        plugin_id: PluginId,
+    },
+    NftProviderSelection {
+        id: Uuid,
+        plugin_id: PluginId,
+    },
+    ManifestApproval {
+        id: Uuid,
+        plugin_id: PluginId,
+        manifest: plugin::PluginManifest,
+    },
+    OriginApproval {
+        id: Uuid,
+        plugin_id: PluginId,
+        origin: String,
+    },
+    /// A plugin being registered needs things that aren't installed yet
+    MissingDependencies {
+        id: Uuid,
+        plugin_id: PluginId,
+        missing: Vec<plugin::Dependency>,
+    },
+    /// A plugin about to be loaded isn't signed by a trusted publisher
+    UnsignedPlugin {
+        id: Uuid,
+        plugin_id: PluginId,
+        name: String,
+        url: String,
+    },
+    /// A new build would replace an installed plugin of the same name, and
+    /// isn't signed by the installed build's publisher
+    PluginUpgrade {
+        id: Uuid,
+        /// The installed plugin being replaced
+        plugin_id: PluginId,
+        name: String,
+    },
+    /// A plugin wants to make a value-moving call on an entity it has no
+    /// standing or session grant for
+    CallApproval {
+        id: Uuid,
+        plugin_id: PluginId,
+        entity_id: EntityId,
+        method: String,
+        /// The asset and amount the call spends, if any
+        spend: Option<(AssetId, U256)>,
+    },
+    /// A plugin needs a secret while the keystore is locked
+    KeystoreUnlock {
+        id: Uuid,
+        plugin_id: PluginId,
+        /// No password has been chosen yet, so the unlock sets it
+        new_password: bool,
+    },
+    Confirmation {
+        id: Uuid,
+        plugin_id: PluginId,
+        title: String,
+        body: String,
+        details: Component,
+    },
+    /// A plugin-defined question answered by picking one of `options`
+    Custom {
+        id: Uuid,
+        plugin_id: PluginId,
+        title: String,
+        body: Component,
+        options: Vec<String>,
+    },
+}
+
+/// A pending `host_set_alarm` call
+struct Alarm {
+    plugin_id: PluginId,
+    tag: String,
+    due: chrono::DateTime<chrono::Local>,
 }
 
 #[derive(Debug, Clone)]
-pub struct Event {
+pub struct NotificationEntry {
     pub id: Uuid,
-    pub message: String,
-    pub level: host::NotifyLevel,
+    pub plugin_id: PluginId,
+    pub plugin: String,
+    pub notification: notification::Notification,
     pub timestamp: chrono::DateTime<chrono::Local>,
-    pub plugin: Option<String>,
 }
 
+/// Deadline for a call into a plugin unless its method's deadline is
+/// overridden with `Host::set_call_timeout`.
 const PLUGIN_TIMEOUT_SECS: u64 = 300;
+/// Deadline for provider reads, which shouldn't wait on the user.
+const READ_TIMEOUT_SECS: u64 = 30;
+/// Bytes of state, keys included, each plugin may store unless its quota is
+/// overridden with `Host::set_state_quota`.
+const DEFAULT_STATE_QUOTA_BYTES: usize = 64 * 1024 * 1024;
+const SHUTDOWN_TIMEOUT_SECS: u64 = 10;
+/// Calls each plugin may have running at once. Further calls are queued.
+const MAX_CONCURRENT_CALLS: usize = 8;
+/// Events kept in the log before the oldest are dropped.
+const EVENT_LOG_CAPACITY: usize = 1000;
+
+/// Host methods every plugin may call regardless of its manifest.
+const CORE_METHODS: &[&str] = &[
+    global::Ping::NAME,
+    host::RegisterEntity::NAME,
+    host::RegisterEntityWithCapabilities::NAME,
+    host::SetPage::NAME,
+    host::SetPageTitle::NAME,
+    host::Navigate::NAME,
+    host::Notify::NAME,
+    host::ShowToast::NAME,
+    host::DecodeError::NAME,
+    host::GetActiveChain::NAME,
+    host::GetCapabilities::NAME,
+    host::GetTraceId::NAME,
+    host::Sleep::NAME,
+    notification::Push::NAME,
+    notification::Dismiss::NAME,
+    state::ReadKey::NAME,
+    state::LockKey::NAME,
+    state::SetKey::NAME,
+    state::UnlockKey::NAME,
+    state::DeleteKey::NAME,
+    state::ListKeys::NAME,
+    state::GetUsage::NAME,
+    host::SecretSet::NAME,
+    host::SecretGet::NAME,
+    host::SecretDelete::NAME,
+];
+const DEFAULT_CHAIN_ID: u64 = 1;
 
 impl UserRequest {
     pub fn id(&self) -> Uuid {
@@ -83,6 +341,22 @@ impl UserRequest {
             UserRequest::EthProviderSelection { id, .. } => id.clone(),
             UserRequest::VaultSelection { id, .. } => id.clone(),
             UserRequest::CoordinatorSelection { id, .. } => id.clone(),
+            UserRequest::SolProviderSelection { id, .. } => id.clone(),
+            UserRequest::BtcProviderSelection { id, .. } => id.clone(),
+            UserRequest::PriceProviderSelection { id, .. } => id.clone(),
+            UserRequest::SignerSelection { id, .. } => id.clone(),
+            UserRequest::BundlerProviderSelection { id, .. } => id.clone(),
+            UserRequest::TokenListSelection { id, .. } => id.clone(),
+            UserRequest::NftProviderSelection { id, .. } => id.clone(),
+            UserRequest::ManifestApproval { id, .. } => id.clone(),
+            UserRequest::OriginApproval { id, .. } => id.clone(),
+            UserRequest::MissingDependencies { id, .. } => id.clone(),
+            UserRequest::UnsignedPlugin { id, .. } => id.clone(),
+            UserRequest::PluginUpgrade { id, .. } => id.clone(),
+            UserRequest::CallApproval { id, .. } => id.clone(),
+            UserRequest::KeystoreUnlock { id, .. } => id.clone(),
+            UserRequest::Confirmation { id, .. } => id.clone(),
+            UserRequest::Custom { id, .. } => id.clone(),
         }
     }
 
@@ -91,6 +365,89 @@ impl UserRequest {
             UserRequest::EthProviderSelection { plugin_id, .. } => *plugin_id,
             UserRequest::VaultSelection { plugin_id, .. } => *plugin_id,
             UserRequest::CoordinatorSelection { plugin_id, .. } => *plugin_id,
+            UserRequest::SolProviderSelection { plugin_id, .. } => *plugin_id,
+            UserRequest::BtcProviderSelection { plugin_id, .. } => *plugin_id,
+            UserRequest::PriceProviderSelection { plugin_id, .. } => *plugin_id,
+            UserRequest::SignerSelection { plugin_id, .. } => *plugin_id,
+            UserRequest::BundlerProviderSelection { plugin_id, .. } => *plugin_id,
+            UserRequest::TokenListSelection { plugin_id, .. } => *plugin_id,
+            UserRequest::NftProviderSelection { plugin_id, .. } => *plugin_id,
+            UserRequest::ManifestApproval { plugin_id, .. } => *plugin_id,
+            UserRequest::OriginApproval { plugin_id, .. } => *plugin_id,
+            UserRequest::MissingDependencies { plugin_id, .. } => *plugin_id,
+            UserRequest::UnsignedPlugin { plugin_id, .. } => *plugin_id,
+            UserRequest::PluginUpgrade { plugin_id, .. } => *plugin_id,
+            UserRequest::CallApproval { plugin_id, .. } => *plugin_id,
+            UserRequest::KeystoreUnlock { plugin_id, .. } => *plugin_id,
+            UserRequest::Confirmation { plugin_id, .. } => *plugin_id,
+            UserRequest::Custom { plugin_id, .. } => *plugin_id,
+        }
+    }
+
+    pub fn kind(&self) -> plugin::RequestKind {
+        use plugin::RequestKind;
+
+        match self {
+            UserRequest::EthProviderSelection { .. } => {
+                RequestKind::EntitySelection(Domain::EthProvider)
+            }
+            UserRequest::VaultSelection { .. } => RequestKind::EntitySelection(Domain::Vault),
+            UserRequest::CoordinatorSelection { .. } => {
+                RequestKind::EntitySelection(Domain::Coordinator)
+            }
+            UserRequest::SolProviderSelection { .. } => {
+                RequestKind::EntitySelection(Domain::SolProvider)
+            }
+            UserRequest::BtcProviderSelection { .. } => {
+                RequestKind::EntitySelection(Domain::BtcProvider)
+            }
+            UserRequest::PriceProviderSelection { .. } => {
+                RequestKind::EntitySelection(Domain::PriceProvider)
+            }
+            UserRequest::SignerSelection { .. } => RequestKind::EntitySelection(Domain::Signer),
+            UserRequest::BundlerProviderSelection { .. } => {
+                RequestKind::EntitySelection(Domain::BundlerProvider)
+            }
+            UserRequest::TokenListSelection { .. } => {
+                RequestKind::EntitySelection(Domain::TokenList)
+            }
+            UserRequest::NftProviderSelection { .. } => {
+                RequestKind::EntitySelection(Domain::NftProvider)
+            }
+            UserRequest::ManifestApproval { .. } => RequestKind::ManifestApproval,
+            UserRequest::OriginApproval { .. } => RequestKind::OriginApproval,
+            UserRequest::MissingDependencies { .. } => RequestKind::MissingDependencies,
+            UserRequest::UnsignedPlugin { .. } => RequestKind::UnsignedPlugin,
+            UserRequest::PluginUpgrade { .. } => RequestKind::PluginUpgrade,
+            UserRequest::CallApproval { .. } => RequestKind::CallApproval,
+            UserRequest::KeystoreUnlock { .. } => RequestKind::KeystoreUnlock,
+            UserRequest::Confirmation { .. } => RequestKind::Confirmation,
+            UserRequest::Custom { .. } => RequestKind::Custom,
+        }
+    }
+
+    /// What the request asks the user for, e.g. "vault selection"
+    pub fn description(&self) -> &'static str {
+        match self {
+            UserRequest::EthProviderSelection { .. } => "Ethereum provider selection",
+            UserRequest::VaultSelection { .. } => "vault selection",
+            UserRequest::CoordinatorSelection { .. } => "coordinator selection",
+            UserRequest::SolProviderSelection { .. } => "Solana provider selection",
+            UserRequest::BtcProviderSelection { .. } => "Bitcoin provider selection",
+            UserRequest::PriceProviderSelection { .. } => "price provider selection",
+            UserRequest::SignerSelection { .. } => "signer selection",
+            UserRequest::BundlerProviderSelection { .. } => "bundler provider selection",
+            UserRequest::TokenListSelection { .. } => "token list selection",
+            UserRequest::NftProviderSelection { .. } => "NFT provider selection",
+            UserRequest::ManifestApproval { .. } => "manifest approval",
+            UserRequest::OriginApproval { .. } => "network access approval",
+            UserRequest::MissingDependencies { .. } => "missing dependencies",
+            UserRequest::UnsignedPlugin { .. } => "unsigned plugin approval",
+            UserRequest::PluginUpgrade { .. } => "plugin upgrade approval",
+            UserRequest::CallApproval { .. } => "call approval",
+            UserRequest::KeystoreUnlock { .. } => "keystore unlock",
+            UserRequest::Confirmation { .. } => "confirmation",
+            UserRequest::Custom { .. } => "custom prompt",
         }
     }
 }
@@ -100,6 +457,25 @@ pub enum UserResponse {
     EthProvider(EthProviderId),
     Vault(VaultId),
     Coordinator(CoordinatorId),
+    SolProvider(SolProviderId),
+    BtcProvider(BtcProviderId),
+    PriceProvider(PriceProviderId),
+    Signer(SignerId),
+    BundlerProvider(BundlerProviderId),
+    TokenList(TokenListId),
+    NftProvider(NftProviderId),
+    ManifestApproved,
+    OriginApproved,
+    /// The user has installed the missing dependencies and wants to retry
+    DependenciesRetried,
+    UnsignedPluginApproved,
+    UpgradeApproved,
+    /// The call was approved, opening a session grant on these terms if any
+    CallApproved(Option<SessionTerms>),
+    KeystoreUnlocked,
+    Confirmed,
+    /// Index of the option chosen for a `UserRequest::Custom`
+    Choice(usize),
 }
 
 #[derive(Error, Debug)]
@@ -110,6 +486,24 @@ pub enum PluginError {
     PdkError(#[from] wasmi_plugin_hdk::plugin::PluginError),
     #[error("Rpc error")]
     RpcError(#[from] RpcError),
+    #[error("Plugin '{0}' has a malformed signature")]
+    InvalidSignature(String),
+    #[error("Plugin '{0}' is not signed by a trusted publisher")]
+    Untrusted(String),
+    #[error("Couldn't fetch the signature of plugin '{0}': {1}")]
+    SignatureUnavailable(String, String),
+    #[error("Plugin '{0}' is missing dependencies: {1}")]
+    MissingDependencies(String, String),
+    #[error("Plugin '{0}' is a wasm component, which isn't supported yet")]
+    UnsupportedComponent(String),
+    #[error("Recording was made with a different build of plugin '{0}'")]
+    RecordingMismatch(String),
+    #[error("Plugin at {url} has SHA-256 {actual}, expected {expected}")]
+    DigestMismatch {
+        url: String,
+        expected: String,
+        actual: String,
+    },
 }
 
 impl Default for Host {
@@ -123,13 +517,54 @@ impl Host {
         Self {
             plugins: Mutex::new(HashMap::new()),
             plugin_sources: Mutex::new(HashMap::new()),
+            plugin_digests: Mutex::new(HashMap::new()),
+            plugin_publishers: Mutex::new(HashMap::new()),
+            manifests: Mutex::new(HashMap::new()),
+            api_versions: Mutex::new(HashMap::new()),
             entities: Mutex::new(HashMap::new()),
+            entity_capabilities: Mutex::new(HashMap::new()),
+            entity_labels: Mutex::new(HashMap::new()),
             state: Mutex::new(HashMap::new()),
+            state_quotas: Mutex::new(HashMap::new()),
+            resource_limits: Mutex::new(HashMap::new()),
             locks: Mutex::new(HashMap::new()),
+            state_store: Mutex::new(None),
+            dirty_state: Mutex::new(HashSet::new()),
             interfaces: Mutex::new(HashMap::new()),
+            page_titles: Mutex::new(HashMap::new()),
+            page_stack: Mutex::new(Vec::new()),
             user_requests: Mutex::new(Vec::new()),
             user_request_senders: Mutex::new(HashMap::new()),
-            events: Mutex::new(Vec::new()),
+            events: EventLog::new(EVENT_LOG_CAPACITY),
+            vault_history: Mutex::new(Vec::new()),
+            active_chain: Mutex::new(caip::ChainId::new_evm(DEFAULT_CHAIN_ID)),
+            notifications: Mutex::new(Vec::new()),
+            toasts: Mutex::new(Vec::new()),
+            address_book: AddressBook::new(),
+            fetch_streams: Mutex::new(HashMap::new()),
+            websockets: WsConnections::new(),
+            keystore: Keystore::new(),
+            alarms: Mutex::new(Vec::new()),
+            ticker: Ticker::new(),
+            nonces: NonceManager::new(),
+            transactions: TransactionTracker::new(),
+            simulation: Simulation::new(),
+            deposit_watches: Mutex::new(Vec::new()),
+            sessions: Mutex::new(Vec::new()),
+            event_bus: EventBus::new(),
+            permissions: Permissions::new(),
+            scheduler: Scheduler::new(MAX_CONCURRENT_CALLS),
+            rate_limiter: RateLimiter::new(),
+            metrics: Metrics::new(),
+            host_call_metrics: Metrics::new(),
+            recorder: Recorder::new(),
+            replayer: Mutex::new(None),
+            audit_log: AuditLog::new(),
+            eth_cache: EthCache::new(),
+            call_timeouts: Mutex::new(default_call_timeouts()),
+            fetch_origins: Mutex::new(Vec::new()),
+            default_eth_providers: Mutex::new(HashMap::new()),
+            trusted_publishers: Mutex::new(Vec::new()),
             observers: Mutex::new(Vec::new()),
         }
     }
@@ -137,32 +572,136 @@ impl Host {
     pub async fn from_state(host_state: HostState) -> Result<Arc<Self>, PluginError> {
         let entities: HashMap<EntityId, PluginId> = host_state.entities.into_iter().collect();
         let state: HashMap<(PluginId, String), Vec<u8>> = host_state.state.into_iter().collect();
+        let active_chain = host_state
+            .active_chain
+            .unwrap_or(caip::ChainId::new_evm(DEFAULT_CHAIN_ID));
 
         let host = Self {
             plugins: Mutex::new(HashMap::new()),
             plugin_sources: Mutex::new(HashMap::new()),
+            plugin_digests: Mutex::new(HashMap::new()),
+            plugin_publishers: Mutex::new(HashMap::new()),
+            manifests: Mutex::new(HashMap::new()),
+            api_versions: Mutex::new(HashMap::new()),
             entities: Mutex::new(entities),
+            entity_capabilities: Mutex::new(host_state.entity_capabilities.into_iter().collect()),
+            entity_labels: Mutex::new(host_state.entity_labels.into_iter().collect()),
             state: Mutex::new(state),
+            state_quotas: Mutex::new(host_state.state_quotas.into_iter().collect()),
+            resource_limits: Mutex::new(host_state.resource_limits.into_iter().collect()),
             locks: Mutex::new(HashMap::new()),
-            interfaces: Mutex::new(HashMap::new()),
+            state_store: Mutex::new(None),
+            dirty_state: Mutex::new(HashSet::new()),
+            interfaces: Mutex::new(host_state.interfaces.into_iter().collect()),
+            page_titles: Mutex::new(host_state.page_titles.into_iter().collect()),
+            page_stack: Mutex::new(Vec::new()),
             user_requests: Mutex::new(Vec::new()),
             user_request_senders: Mutex::new(HashMap::new()),
-            events: Mutex::new(Vec::new()),
+            events: EventLog::new(EVENT_LOG_CAPACITY),
+            vault_history: Mutex::new(Vec::new()),
+            active_chain: Mutex::new(active_chain),
+            notifications: Mutex::new(Vec::new()),
+            toasts: Mutex::new(Vec::new()),
+            address_book: AddressBook::from_contacts(host_state.address_book),
+            fetch_streams: Mutex::new(HashMap::new()),
+            websockets: WsConnections::new(),
+            keystore: Keystore::from_state(host_state.keystore),
+            alarms: Mutex::new(Vec::new()),
+            ticker: Ticker::from_intervals(host_state.tick_intervals),
+            nonces: NonceManager::new(),
+            transactions: TransactionTracker::new(),
+            simulation: Simulation::new(),
+            deposit_watches: Mutex::new(host_state.deposit_watches),
+            sessions: Mutex::new(Vec::new()),
+            event_bus: EventBus::from_subscriptions(host_state.subscriptions),
+            permissions: Permissions::from_grants(host_state.permission_grants),
+            scheduler: Scheduler::new(MAX_CONCURRENT_CALLS),
+            rate_limiter: RateLimiter::new(),
+            metrics: Metrics::new(),
+            host_call_metrics: Metrics::new(),
+            recorder: Recorder::new(),
+            replayer: Mutex::new(None),
+            audit_log: AuditLog::from_entries(host_state.audit_log),
+            eth_cache: EthCache::new(),
+            call_timeouts: Mutex::new(default_call_timeouts()),
+            fetch_origins: Mutex::new(host_state.fetch_origins),
+            default_eth_providers: Mutex::new(
+                host_state.default_eth_providers.into_iter().collect(),
+            ),
+            trusted_publishers: Mutex::new(host_state.trusted_publishers),
             observers: Mutex::new(Vec::new()),
         };
         let host = Arc::new(host);
 
+        //? Plugins are loaded with their saved ID rather than the hash of their
+        //? binary, since a hot-reloaded plugin keeps its original ID
         for plugin_data in host_state.plugins {
-            host.load_plugin(plugin_data.source, &plugin_data.name)
+            let PluginData {
+                id,
+                name,
+                source,
+                sha256,
+                publisher,
+            } = plugin_data;
+            let wasm_bytes = source.as_bytes().await?;
+            let publisher = host
+                .verify_restored(&source, &wasm_bytes, sha256, publisher, &name)
+                .await?;
+            host.load_plugin_bytes(id, source, wasm_bytes, publisher, &name)
                 .await?;
         }
+        host.fail_interrupted_requests(host_state.pending_requests)
+            .await;
 
         Ok(host)
     }
 
+    /// Tells plugins about the requests they were waiting on when the state
+    /// was saved. The calls that made them didn't survive, so rather than
+    /// prompting the user again each request fails with `OnRequestInterrupted`.
+    async fn fail_interrupted_requests(&self, requests: Vec<UserRequest>) {
+        for request in requests {
+            let Some(plugin) = self.get_plugin(&request.plugin_id()) else {
+                continue;
+            };
+            let event = format!("Interrupted {}", request.description());
+            self.log_event(&event, Some((plugin.id(), plugin.name())));
+
+            let interrupted = plugin::InterruptedRequest {
+                id: request.id(),
+                kind: request.kind(),
+                description: request.description().to_string(),
+            };
+            let api_version = self
+                .get_api_version(&plugin.id())
+                .unwrap_or(MIN_API_VERSION);
+            let result = if api_version < compat::REQUEST_KIND {
+                self.call_plugin_optional(
+                    plugin.clone(),
+                    compat::LegacyOnRequestInterrupted,
+                    interrupted.description,
+                )
+                .await
+            } else {
+                self.call_plugin_optional(plugin.clone(), plugin::OnRequestInterrupted, interrupted)
+                    .await
+            };
+            match result {
+                Ok(_) => {}
+                Err(e) => warn!(
+                    "Plugin {} failed to handle interrupted request: {}",
+                    plugin.id(),
+                    e
+                ),
+            }
+        }
+    }
+
     pub fn state(&self) -> HostState {
         let plugins = self.plugins.lock().unwrap();
         let plugin_sources = self.plugin_sources.lock().unwrap();
+        let plugin_digests = self.plugin_digests.lock().unwrap();
+        let plugin_publishers = self.plugin_publishers.lock().unwrap();
 
         let plugins_data = plugins
             .iter()
@@ -173,13 +712,64 @@ impl Host {
                     .get(id)
                     .cloned()
                     .expect("Plugin source not found"),
+                sha256: plugin_digests.get(id).copied(),
+                publisher: plugin_publishers.get(id).copied(),
             })
             .collect();
 
         HostState {
+            version: HOST_STATE_VERSION,
             plugins: plugins_data,
             entities: self.entities.lock().unwrap().clone().into_iter().collect(),
             state: self.state.lock().unwrap().clone().into_iter().collect(),
+            active_chain: Some(self.get_active_chain()),
+            address_book: self.address_book.contacts(),
+            subscriptions: self.event_bus.subscriptions(),
+            page_titles: self
+                .page_titles
+                .lock()
+                .unwrap()
+                .clone()
+                .into_iter()
+                .collect(),
+            entity_capabilities: self
+                .entity_capabilities
+                .lock()
+                .unwrap()
+                .clone()
+                .into_iter()
+                .collect(),
+            deposit_watches: self.deposit_watches.lock().unwrap().clone(),
+            state_quotas: self
+                .state_quotas
+                .lock()
+                .unwrap()
+                .clone()
+                .into_iter()
+                .collect(),
+            resource_limits: self
+                .resource_limits
+                .lock()
+                .unwrap()
+                .clone()
+                .into_iter()
+                .collect(),
+            keystore: self.keystore.state(),
+            pending_requests: self.get_user_requests(),
+            permission_grants: self.permissions.grants(),
+            fetch_origins: self.fetch_origins.lock().unwrap().clone(),
+            trusted_publishers: self.get_trusted_publishers(),
+            interfaces: self.get_interfaces().into_iter().collect(),
+            default_eth_providers: self.get_default_eth_providers(),
+            entity_labels: self
+                .entity_labels
+                .lock()
+                .unwrap()
+                .clone()
+                .into_iter()
+                .collect(),
+            audit_log: self.audit_log.all(),
+            tick_intervals: self.ticker.intervals(),
         }
     }
 
@@ -192,83 +782,518 @@ impl Host {
         observers.retain(|tx| tx.unbounded_send(()).is_ok());
     }
 
-    /// Creates a plugin from its source, register it, and calls its Init method
-    pub async fn new_plugin(
+    /// Swaps a loaded plugin's wasm module for a new build while keeping its
+    /// `PluginId`, so its entities, pages, grants, and stored state carry
+    /// over. Meant for plugin development, where re-adding the plugin after
+    /// every rebuild would mean setting it up again.
+    ///
+    /// The new build's `Migrate` is called if it implements it, and the user
+    /// is only prompted if the manifest changed. On failure the old build
+    /// stays loaded.
+    pub async fn reload_plugin(
         self: &Arc<Host>,
-        source: PluginSource,
-        name: &str,
-    ) -> Result<PluginId, PluginError> {
-        let plugin = self.load_plugin(source, name).await?;
-        info!("Initializing plugin {}", plugin.id());
+        plugin_id: PluginId,
+        wasm_bytes: Vec<u8>,
+    ) -> Result<(), PluginError> {
+        let Some(old_plugin) = self.get_plugin(&plugin_id) else {
+            return Err(RpcError::Custom(format!("Plugin {} is not loaded", plugin_id)).into());
+        };
+        let name = old_plugin.name().to_string();
 
-        let plugin_id = plugin.id();
-        match plugin::Init.call_async(plugin.clone(), ()).await {
-            Err(RpcError::MethodNotFound) => {
-                info!("Plugin {} does not implement Init, skipping", plugin.id());
-                self.log_event("Initialized", Some(name));
-                Ok(plugin_id)
+        info!("Reloading plugin '{}'...", name);
+        let publisher = match self.get_plugin_source(&plugin_id) {
+            Some(source) => self.verify_plugin(&source, &wasm_bytes, &name).await?,
+            None => None,
+        };
+        let (plugin, manifest, api_version) = self
+            .build_plugin(plugin_id, wasm_bytes.clone(), &name)
+            .await?;
+
+        let old_manifest = self.get_manifest(&plugin_id).unwrap_or_default();
+        let new_manifest = manifest.clone().unwrap_or_default();
+        if new_manifest != old_manifest {
+            self.request_manifest_approval(plugin_id, new_manifest.clone())
+                .await?;
+        }
+
+        let migrated = self
+            .migrate_state(&plugin, &name, plugin_id, &old_manifest, &new_manifest)
+            .await;
+        let migrated = match migrated {
+            Ok(state) => state,
+            Err(e) => {
+                warn!("Failed to migrate plugin '{}': {}", name, e);
+                return Err(e.into());
             }
-            Err(e) => Err(e.into()),
-            Ok(_) => {
-                info!("Plugin {} initialized", plugin.id());
-                self.log_event("Initialized", Some(name));
-                Ok(plugin_id)
+        };
+
+        self.shutdown_plugin(old_plugin).await;
+        {
+            let mut state = self.state.lock().unwrap();
+            let mut dirty = self.dirty_state.lock().unwrap();
+            state.retain(|key, _| {
+                if key.0 != plugin_id {
+                    return true;
+                }
+                dirty.insert(key.clone());
+                false
+            });
+            for (key, value) in migrated {
+                dirty.insert((plugin_id, key.clone()));
+                state.insert((plugin_id, key), value);
             }
         }
+        self.locks
+            .lock()
+            .unwrap()
+            .retain(|(id, _), _| *id != plugin_id);
+        {
+            //? The new build may point its providers somewhere else
+            let entities = self.entities.lock().unwrap();
+            self.eth_cache.retain_providers(|id| {
+                entities.get(&EntityId::EthProvider(*id)) != Some(&plugin_id)
+            });
+        }
+
+        //? URL sources are kept so the next reload fetches the latest build.
+        //? Restores are pinned to this build's digest, so they don't.
+        let sha256 = Sha256::digest(&wasm_bytes).into();
+        let source = match self.get_plugin_source(&plugin_id) {
+            Some(PluginSource::Url(url)) => PluginSource::Url(url),
+            _ => PluginSource::Embedded(wasm_bytes),
+        };
+        self.register_plugin(plugin, source, sha256, publisher, manifest, api_version);
+        self.notify_observers();
+
+        info!("Reloaded plugin '{}'", name);
+        self.log_event("Reloaded", Some((plugin_id, &name)));
+        Ok(())
+    }
+
+    /// Calls `Migrate` on a new build of a plugin with the old build's state,
+    /// returning the state to store for the new build. Builds that don't
+    /// implement `Migrate` keep the old state if the version is unchanged.
+    async fn migrate_state(
+        &self,
+        plugin: &Plugin,
+        name: &str,
+        old_id: PluginId,
+        old_manifest: &plugin::PluginManifest,
+        new_manifest: &plugin::PluginManifest,
+    ) -> Result<plugin::StateBlob, RpcError> {
+        let (old_version, new_version) = (old_manifest.version, new_manifest.version);
+        let old_state: plugin::StateBlob = self
+            .state
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|((id, _), _)| *id == old_id)
+            .map(|((_, key), value)| (key.clone(), value.clone()))
+            .collect();
+
+        match plugin::Migrate
+            .call_async(plugin.clone(), (old_version, old_state.clone()))
+            .await
+        {
+            Ok(state) => Ok(state),
+            Err(RpcError::MethodNotFound) if old_version == new_version => Ok(old_state),
+            Err(RpcError::MethodNotFound) => Err(RpcError::Custom(format!(
+                "Plugin '{}' does not implement Migrate from version {} to {}",
+                name, old_version, new_version
+            ))),
+            Err(e) => Err(e),
+        }
+    }
+
+    async fn request_manifest_approval(
+        &self,
+        plugin_id: PluginId,
+        manifest: plugin::PluginManifest,
+    ) -> Result<(), RpcError> {
+        let request = UserRequest::ManifestApproval {
+            id: Uuid::new_v4(),
+            plugin_id,
+            manifest,
+        };
+        self.create_user_request(request, |resp| match resp {
+            UserResponse::ManifestApproved => Some(()),
+            _ => None,
+        })
+        .await
     }
 
-    /// Loads a new plugin from its source and registers it
-    async fn load_plugin(
+    async fn load_plugin_bytes(
         self: &Arc<Host>,
+        id: PluginId,
         source: PluginSource,
+        wasm_bytes: Vec<u8>,
+        publisher: Option<[u8; 32]>,
         name: &str,
     ) -> Result<Plugin, PluginError> {
+        let sha256 = Sha256::digest(&wasm_bytes).into();
+        let (plugin, manifest, api_version) = self.build_plugin(id, wasm_bytes, name).await?;
+        self.register_plugin(
+            plugin.clone(),
+            source,
+            sha256,
+            publisher,
+            manifest,
+            api_version,
+        );
+        info!("Loaded plugin '{}'", name);
+        Ok(plugin)
+    }
+
+    /// Instantiates a plugin and reads its manifest and API version, without
+    /// registering it with the host.
+    async fn build_plugin(
+        self: &Arc<Host>,
+        id: PluginId,
+        wasm_bytes: Vec<u8>,
+        name: &str,
+    ) -> Result<(Plugin, Option<plugin::PluginManifest>, u32), PluginError> {
         let server = self.get_server();
         let server = Arc::new(server);
 
-        let wasm_bytes = source.as_bytes().await?;
-
         info!("Loading plugin '{}'...", name);
-        let mut s = DefaultHasher::new();
-        wasm_bytes.hash(&mut s);
-        let id: u128 = s.finish().into();
-        let id = PluginId::from(id);
-
+        // TODO: Load components alongside wasip1 modules once the plugin
+        // runtime supports the component model, serving the API through a
+        // WIT world instead of JSON-RPC over stdio.
+        if wasm_memory::is_component(&wasm_bytes) {
+            return Err(PluginError::UnsupportedComponent(name.to_string()));
+        }
+        let limits = self.get_resource_limits(&id);
+        let initial = wasm_memory::initial_memory_bytes(&wasm_bytes).unwrap_or(0);
+        if initial > limits.max_memory_bytes {
+            let message = format!(
+                "Plugin '{}' starts with {} bytes of memory, over its limit of {}",
+                name, initial, limits.max_memory_bytes
+            );
+            let error = DomainError::new(ErrorCode::RESOURCE_EXHAUSTED, message);
+            return Err(RpcError::from(error).into());
+        }
+        let Some(wasm_bytes) = wasm_memory::cap_memory(&wasm_bytes, limits.max_memory_bytes) else {
+            let message = format!(
+                "Plugin '{}' has memory that can't be held to its limit of {}",
+                name, limits.max_memory_bytes
+            );
+            let error = DomainError::new(ErrorCode::RESOURCE_EXHAUSTED, message);
+            return Err(RpcError::from(error).into());
+        };
+        // TODO: Fail calls whose guest ran out of memory with
+        // `ErrorCode::RESOURCE_EXHAUSTED`. A failed `memory.grow` makes the
+        // guest's allocator abort, and the framework reports that as a trap
+        // without saying why.
+        // TODO: Run plugins on wasmtime in the native host, keeping wasmi in
+        // the browser. The engine is chosen inside wasmi-plugin-framework's
+        // `Plugin`, so it needs a `WasmEngine` trait there for this builder
+        // to pick one by target.
         let plugin = Plugin::builder(name, wasm_bytes, server)
             .with_id(id)
             .with_timeout(Duration::from_secs(PLUGIN_TIMEOUT_SECS))
             .build()
             .await?;
 
-        self.plugins
+        let manifest = match plugin::GetManifest.call_async(plugin.clone(), ()).await {
+            Ok(manifest) => Some(manifest),
+            Err(RpcError::MethodNotFound) => {
+                info!("Plugin {} does not implement GetManifest", plugin.id());
+                None
+            }
+            Err(e) => return Err(e.into()),
+        };
+
+        let api_version = match plugin::GetApiVersion.call_async(plugin.clone(), ()).await {
+            Ok(version) => version,
+            Err(RpcError::MethodNotFound) => {
+                info!("Plugin {} does not implement GetApiVersion", plugin.id());
+                MIN_API_VERSION
+            }
+            Err(e) => return Err(e.into()),
+        };
+        if !(MIN_API_VERSION..=API_VERSION).contains(&api_version) {
+            return Err(RpcError::Custom(format!(
+                "Plugin '{}' targets unsupported API version {} (host supports {}-{})",
+                name, api_version, MIN_API_VERSION, API_VERSION
+            ))
+            .into());
+        }
+
+        Ok((plugin, manifest, api_version))
+    }
+
+    fn register_plugin(
+        &self,
+        plugin: Plugin,
+        source: PluginSource,
+        sha256: [u8; 32],
+        publisher: Option<[u8; 32]>,
+        manifest: Option<plugin::PluginManifest>,
+        api_version: u32,
+    ) {
+        let id = plugin.id();
+        match manifest {
+            Some(manifest) => self.manifests.lock().unwrap().insert(id, manifest),
+            None => self.manifests.lock().unwrap().remove(&id),
+        };
+        self.api_versions.lock().unwrap().insert(id, api_version);
+        self.plugins.lock().unwrap().insert(id, plugin);
+        self.plugin_sources.lock().unwrap().insert(id, source);
+        self.plugin_digests.lock().unwrap().insert(id, sha256);
+        match publisher {
+            Some(key) => self.plugin_publishers.lock().unwrap().insert(id, key),
+            None => self.plugin_publishers.lock().unwrap().remove(&id),
+        };
+    }
+
+    /// Shuts down a plugin, then removes it along with its entities. The
+    /// plugin's stored state is kept so it can be restored if re-added.
+    pub async fn remove_plugin(&self, plugin_id: &PluginId) {
+        let Some(plugin) = self.get_plugin(plugin_id) else {
+            return;
+        };
+
+        let name = plugin.name().to_string();
+        self.shutdown_plugin(plugin).await;
+
+        let mut removed_pages = Vec::new();
+        self.entities.lock().unwrap().retain(|entity_id, owner| {
+            if owner != plugin_id {
+                return true;
+            }
+            if let EntityId::Page(page_id) = entity_id {
+                removed_pages.push(*page_id);
+            }
+            false
+        });
+        {
+            let entities = self.entities.lock().unwrap();
+            self.entity_capabilities
+                .lock()
+                .unwrap()
+                .retain(|entity_id, _| entities.contains_key(entity_id));
+            self.permissions
+                .retain_entities(|entity_id| entities.contains_key(entity_id));
+            self.entity_labels
+                .lock()
+                .unwrap()
+                .retain(|entity_id, _| entities.contains_key(entity_id));
+            self.eth_cache
+                .retain_providers(|id| entities.contains_key(&EntityId::EthProvider(*id)));
+            self.default_eth_providers
+                .lock()
+                .unwrap()
+                .retain(|_, id| entities.contains_key(&EntityId::EthProvider(*id)));
+        }
+        self.interfaces
+            .lock()
+            .unwrap()
+            .retain(|page_id, _| !removed_pages.contains(page_id));
+        self.page_titles
+            .lock()
+            .unwrap()
+            .retain(|page_id, _| !removed_pages.contains(page_id));
+        self.page_stack
             .lock()
             .unwrap()
-            .insert(plugin.id(), plugin.clone());
+            .retain(|page_id| !removed_pages.contains(page_id));
 
-        self.plugin_sources.lock().unwrap().insert(id, source);
-        info!("Loaded plugin '{}'", name);
-        Ok(plugin)
+        self.unload_plugin(plugin_id);
+        self.log_event("Removed", Some((*plugin_id, &name)));
+    }
+
+    /// Removes a plugin like `remove_plugin`, then deletes its stored state
+    /// and quota and limit overrides so nothing is restored if it's added
+    /// again.
+    pub async fn uninstall_plugin(&self, plugin_id: &PluginId) {
+        self.remove_plugin(plugin_id).await;
+
+        {
+            let mut state = self.state.lock().unwrap();
+            let mut dirty = self.dirty_state.lock().unwrap();
+            state.retain(|key, _| {
+                if key.0 != *plugin_id {
+                    return true;
+                }
+                dirty.insert(key.clone());
+                false
+            });
+        }
+        self.locks
+            .lock()
+            .unwrap()
+            .retain(|(id, _), _| id != plugin_id);
+        self.state_quotas.lock().unwrap().remove(plugin_id);
+        self.resource_limits.lock().unwrap().remove(plugin_id);
+        self.keystore.remove_plugin(plugin_id);
+        self.notify_observers();
+    }
+
+    /// Shuts down every loaded plugin. Plugins stay registered, so the host's
+    /// state can still be saved afterwards.
+    pub async fn shutdown(&self) {
+        //? Fail pending requests so the calls waiting on them return an error
+        //? instead of hanging
+        self.user_request_senders.lock().unwrap().clear();
+
+        let plugins: Vec<Plugin> = self.plugins.lock().unwrap().values().cloned().collect();
+        let shutdowns = plugins
+            .into_iter()
+            .map(|plugin| self.shutdown_plugin(plugin));
+        futures::future::join_all(shutdowns).await;
+    }
+
+    async fn shutdown_plugin(&self, plugin: Plugin) {
+        info!("Shutting down plugin {}", plugin.id());
+        let timeout = Duration::from_secs(SHUTDOWN_TIMEOUT_SECS);
+        let shutdown = plugin::Shutdown.call_optional(plugin.clone(), ());
+        match time::timeout(timeout, shutdown).await {
+            Some(Ok(_)) => {}
+            Some(Err(e)) => warn!("Plugin {} failed to shut down: {}", plugin.id(), e),
+            None => warn!("Plugin {} timed out while shutting down", plugin.id()),
+        }
+    }
+
+    fn unload_plugin(&self, plugin_id: &PluginId) {
+        self.plugins.lock().unwrap().remove(plugin_id);
+        self.plugin_sources.lock().unwrap().remove(plugin_id);
+        self.plugin_digests.lock().unwrap().remove(plugin_id);
+        self.plugin_publishers.lock().unwrap().remove(plugin_id);
+        self.manifests.lock().unwrap().remove(plugin_id);
+        self.api_versions.lock().unwrap().remove(plugin_id);
+        self.fetch_streams
+            .lock()
+            .unwrap()
+            .retain(|_, s| &s.plugin_id != plugin_id);
+        self.websockets.remove_plugin(plugin_id);
+        self.alarms
+            .lock()
+            .unwrap()
+            .retain(|a| &a.plugin_id != plugin_id);
+        self.ticker.unregister(plugin_id);
+        self.toasts
+            .lock()
+            .unwrap()
+            .retain(|(id, _)| id != plugin_id);
+        self.deposit_watches
+            .lock()
+            .unwrap()
+            .retain(|w| &w.plugin_id != plugin_id);
+        //? Expire rather than drop the plugin's sessions so the next
+        //? `expire_sessions` cancels them with their coordinators
+        let now = chrono::Local::now();
+        for session in self.sessions.lock().unwrap().iter_mut() {
+            if &session.plugin_id == plugin_id {
+                session.expires = now;
+            }
+        }
+        self.event_bus.remove_plugin(plugin_id);
+        self.permissions.remove_plugin(plugin_id);
+        self.metrics.remove_plugin(plugin_id);
+        self.host_call_metrics.remove_plugin(plugin_id);
+        self.rate_limiter.remove_plugin(plugin_id);
+        self.nonces.remove_plugin(plugin_id);
+        self.simulation.nonces().remove_plugin(plugin_id);
+        self.fetch_origins
+            .lock()
+            .unwrap()
+            .retain(|(id, _)| id != plugin_id);
+        self.notify_observers();
     }
 
     pub fn get_server(self: &Arc<Host>) -> HostServer<Weak<Host>> {
         let weak_host = Arc::downgrade(self);
         HostServer::new(weak_host)
+            .with_guard(
+                |host: &Weak<Host>, instance: &InstanceId, method: &str, permission: Permission| {
+                    host.upgrade().is_some_and(|host| {
+                        host.is_method_permitted(&instance.plugin, method, permission)
+                    })
+                },
+            )
+            .with_rate_limit(|host: &Weak<Host>, instance: &InstanceId, method: &str| {
+                let Some(host) = host.upgrade() else {
+                    return false;
+                };
+                //? State writes are limited in their handlers instead, which
+                //? release the key's lock when they reject a write
+                if MethodClass::of(method) == Some(MethodClass::StateWrites) {
+                    return true;
+                }
+                let allowed = host.rate_limiter.check(instance.plugin, method);
+                if !allowed {
+                    warn!(
+                        "Plugin {} exceeded the rate limit for {}",
+                        instance.plugin, method
+                    );
+                }
+                allowed
+            })
+            .with_middleware(record_host_call)
+            .with_middleware(record_interaction)
+            .with_middleware(compat::translate_legacy_call)
             .with_method(global::Ping, ping)
             .with_method(host::RegisterEntity, register_entity)
+            .with_method(
+                host::RegisterEntityWithCapabilities,
+                register_entity_with_capabilities,
+            )
             .with_method(host::RequestEthProvider, request_eth_provider)
+            .with_method(host::GetActiveChain, active_chain)
+            .with_method(host::RequestConfirmation, request_confirmation)
+            .with_method(host::RequestUserChoice, request_user_choice)
             .with_method(host::RequestVault, request_vault)
             .with_method(host::RequestCoordinator, request_coordinator)
+            .with_method(host::RequestSolProvider, request_sol_provider)
+            .with_method(host::RequestBtcProvider, request_btc_provider)
+            .with_method(host::RequestPriceProvider, request_price_provider)
+            .with_method(host::RequestSigner, request_signer)
+            .with_method(host::RequestBundlerProvider, request_bundler_provider)
+            .with_method(host::RequestTokenList, request_token_list)
+            .with_method(host::RequestNftProvider, request_nft_provider)
+            .with_method(host::Publish, publish)
+            .with_method(host::Subscribe, subscribe_topic)
+            .with_method(host::Unsubscribe, unsubscribe_topic)
+            .with_method(host::SetAlarm, set_alarm)
+            .with_method(host::CancelAlarm, cancel_alarm)
+            .with_method(host::RegisterTick, register_tick)
+            .with_method(host::Sleep, sleep)
+            .with_method(host::UnregisterTick, unregister_tick)
+            .with_method(host::WatchDeposit, watch_deposit)
+            .with_method(host::UnwatchDeposit, unwatch_deposit)
+            .with_method(host::LeaseNonce, lease_nonce)
+            .with_method(host::ReleaseNonce, release_nonce)
             .with_method(host::Fetch, fetch)
+            .with_method(host::FetchStream, fetch_stream)
+            .with_method(host::FetchReadChunk, fetch_read_chunk)
+            .with_method(host::FetchClose, fetch_close)
+            .with_method(host::WsConnect, ws_connect)
+            .with_method(host::WsSend, ws_send)
+            .with_method(host::WsClose, ws_close)
+            .with_method(host::SecretSet, secret_set)
+            .with_method(host::SecretGet, secret_get)
+            .with_method(host::SecretDelete, secret_delete)
             .with_method(host::Notify, notify)
+            .with_method(host::ShowToast, show_toast)
+            .with_method(host::DecodeError, decode_error)
+            .with_method(notification::Push, notification_push)
+            .with_method(notification::Dismiss, notification_dismiss)
+            .with_method(addressbook::Add, addressbook_add)
+            .with_method(addressbook::Remove, addressbook_remove)
+            .with_method(addressbook::List, addressbook_list)
             .with_method(state::ReadKey, read_key)
             .with_method(state::LockKey, lock_key)
             .with_method(state::SetKey, set_key)
             .with_method(state::UnlockKey, unlock_key)
+            .with_method(state::DeleteKey, delete_key)
+            .with_method(state::ListKeys, list_keys)
+            .with_method(state::GetUsage, get_usage)
             .with_method(host::SetPage, set_interface)
+            .with_method(host::SetPageTitle, set_page_title)
+            .with_method(host::Navigate, navigate)
             .with_method(vault::GetAssets, vault_get_assets)
             .with_method(vault::Withdraw, vault_withdraw)
             .with_method(vault::GetDepositAddress, vault_get_deposit_address)
-            // .with_method(vault::OnDeposit, vault_on_deposit)
             .with_method(page::OnLoad, page_on_load)
             .with_method(page::OnUpdate, page_on_update)
             .with_method(eth::ChainId, eth_provider_chain_id)
@@ -276,17 +1301,52 @@ impl Host {
             .with_method(eth::Call, eth_provider_call)
             .with_method(eth::GetBalance, eth_provider_get_balance)
             .with_method(eth::GasPrice, eth_provider_gas_price)
+            .with_method(eth::MaxPriorityFeePerGas, eth_max_priority_fee_per_gas)
+            .with_method(eth::BlobBaseFee, eth_blob_base_fee)
             .with_method(eth::GetTransactionCount, eth_transaction_count)
             .with_method(eth::SendRawTransaction, eth_send_raw_transaction)
             .with_method(eth::EstimateGas, eth_estimate_gas)
+            .with_method(eth::CreateAccessList, eth_create_access_list)
             .with_method(eth::GetTransactionReceipt, eth_get_transaction_receipt)
             .with_method(eth::GetBlock, eth_get_block)
             .with_method(eth::GetCode, eth_get_code)
             .with_method(eth::GetStorageAt, eth_get_storage_at)
+            .with_method(eth::GetProof, eth_get_proof)
             .with_method(eth::FeeHistory, eth_fee_history)
+            .with_method(fees::Suggest, fees_suggest)
+            .with_method(sol::GetBalance, sol_get_balance)
+            .with_method(sol::GetAccountInfo, sol_get_account_info)
+            .with_method(sol::GetLatestBlockhash, sol_get_latest_blockhash)
+            .with_method(sol::SendTransaction, sol_send_transaction)
+            .with_method(btc::GetUtxos, btc_get_utxos)
+            .with_method(btc::BroadcastTx, btc_broadcast_tx)
+            .with_method(btc::EstimateFee, btc_estimate_fee)
+            .with_method(btc::GetAddressHistory, btc_get_address_history)
+            .with_method(ens::Resolve, ens_resolve)
+            .with_method(ens::Lookup, ens_lookup)
+            .with_method(price::Get, price_get)
+            .with_method(price::GetMany, price_get_many)
+            .with_method(signer::Sign, signer_sign)
+            .with_method(signer::GetAddress, signer_address)
+            .with_method(erc4337::BuildUserOp, erc4337_build_user_op)
+            .with_method(erc4337::EstimateUserOpGas, erc4337_estimate_user_op_gas)
+            .with_method(erc4337::SendUserOp, erc4337_send_user_op)
+            .with_method(erc4337::GetUserOpReceipt, erc4337_get_user_op_receipt)
+            .with_method(tokens::GetList, tokens_get_list)
+            .with_method(tokens::Search, tokens_search)
+            .with_method(nft::GetOwned, nft_get_owned)
+            .with_method(nft::GetMetadata, nft_get_metadata)
+            .with_method(trace::TraceTransaction, trace_transaction)
+            .with_method(trace::TraceCall, trace_call)
             .with_method(coordinator::GetAssets, coordinator_get_assets)
             .with_method(coordinator::GetSession, coordinator_get_session)
+            .with_method(coordinator::CancelSession, coordinator_cancel_session)
             .with_method(coordinator::Propose, coordinator_propose)
+            .with_method(coordinator::Simulate, coordinator_simulate)
+            .with_method(
+                coordinator::ProposeMultichain,
+                coordinator_propose_multichain,
+            )
     }
 
     pub fn get_entities(&self) -> Vec<EntityId> {
@@ -303,284 +1363,2061 @@ impl Host {
         self.plugins.lock().unwrap().get(plugin_id).cloned()
     }
 
-    pub fn get_entity_plugin_id(&self, entity_id: impl Into<EntityId>) -> Option<PluginId> {
-        let entity_id = entity_id.into();
-        let entities = self.entities.lock().unwrap();
-        entities.get(&entity_id).cloned()
+    fn find_plugin_by_name(&self, name: &str) -> Option<PluginId> {
+        let plugins = self.plugins.lock().unwrap();
+        plugins
+            .iter()
+            .find_map(|(id, plugin)| (plugin.name() == name).then_some(*id))
     }
 
-    pub fn get_entity_plugin(&self, entity_id: impl Into<EntityId>) -> Option<Plugin> {
-        let entity_id = entity_id.into();
-        let plugin_id = self.get_entity_plugin_id(entity_id)?;
-        self.get_plugin(&plugin_id)
+    pub fn get_plugin_source(&self, plugin_id: &PluginId) -> Option<PluginSource> {
+        self.plugin_sources.lock().unwrap().get(plugin_id).cloned()
     }
 
-    pub fn get_interfaces(&self) -> HashMap<PageId, Component> {
-        let interfaces = self.interfaces.lock().unwrap();
-        interfaces.clone()
+    /// Returns the plugin's manifest, or None if it doesn't declare one.
+    pub fn get_manifest(&self, plugin_id: &PluginId) -> Option<plugin::PluginManifest> {
+        self.manifests.lock().unwrap().get(plugin_id).cloned()
     }
 
-    pub fn get_interface(&self, page_id: PageId) -> Option<Component> {
-        let interfaces = self.interfaces.lock().unwrap();
-        interfaces.get(&page_id).cloned()
+    /// Returns the API version the plugin was built against.
+    pub fn get_api_version(&self, plugin_id: &PluginId) -> Option<u32> {
+        self.api_versions.lock().unwrap().get(plugin_id).copied()
     }
 
-    pub fn get_user_requests(&self) -> Vec<UserRequest> {
-        let requests = self.user_requests.lock().unwrap();
-        requests.clone()
-    }
+    fn is_method_permitted(
+        &self,
+        plugin_id: &PluginId,
+        method: &str,
+        permission: Permission,
+    ) -> bool {
+        //? Privileged methods must be declared even if they're listed as core
+        if permission != Permission::Privileged && CORE_METHODS.contains(&method) {
+            return true;
+        }
 
-    pub fn get_events(&self) -> Vec<Event> {
-        let events = self.events.lock().unwrap();
-        events.clone()
+        let manifests = self.manifests.lock().unwrap();
+        manifests
+            .get(plugin_id)
+            .is_some_and(|m| m.allows_method(method))
     }
 
-    pub fn resolve_eth_provider_request(&self, request_id: Uuid, provider_id: EthProviderId) {
-        self.resolve_user_request(request_id, UserResponse::EthProvider(provider_id));
+    pub fn get_entity_plugin_id(&self, entity_id: impl Into<EntityId>) -> Option<PluginId> {
+        let entity_id = entity_id.into();
+        let entities = self.entities.lock().unwrap();
+        entities.get(&entity_id).cloned()
     }
 
-    pub fn resolve_vault_request(&self, request_id: Uuid, vault_id: VaultId) {
-        self.resolve_user_request(request_id, UserResponse::Vault(vault_id));
+    /// Returns the capabilities the entity was registered with. Entities
+    /// registered without any are unrestricted.
+    pub fn get_entity_capabilities(
+        &self,
+        entity_id: impl Into<EntityId>,
+    ) -> host::EntityCapabilities {
+        let entity_id = entity_id.into();
+        let capabilities = self.entity_capabilities.lock().unwrap();
+        capabilities.get(&entity_id).cloned().unwrap_or_default()
     }
 
-    pub fn resolve_coordinator_request(&self, request_id: Uuid, coordinator_id: CoordinatorId) {
-        self.resolve_user_request(request_id, UserResponse::Coordinator(coordinator_id.into()));
+    pub fn get_entity_label(&self, entity_id: impl Into<EntityId>) -> Option<EntityLabel> {
+        let entity_id = entity_id.into();
+        self.entity_labels.lock().unwrap().get(&entity_id).cloned()
     }
 
-    pub fn deny_user_request(&self, request_id: Uuid) {
-        //? Drop the sender to cancel the request
-        self.user_request_senders
-            .lock()
-            .unwrap()
-            .remove(&request_id);
+    /// Returns each entity along with its label, if the user has set one.
+    pub fn get_labeled_entities(&self) -> Vec<(EntityId, Option<EntityLabel>)> {
+        let labels = self.entity_labels.lock().unwrap();
+        self.get_entities()
+            .into_iter()
+            .map(|id| (id, labels.get(&id).cloned()))
+            .collect()
     }
 
-    async fn create_user_request<T, F>(
+    /// Names an entity for display. An empty label removes the entity's
+    /// label and icon.
+    pub fn set_entity_label(&self, entity_id: EntityId, label: String) {
+        {
+            let mut labels = self.entity_labels.lock().unwrap();
+            if label.trim().is_empty() {
+                labels.remove(&entity_id);
+            } else {
+                labels.entry(entity_id).or_default().label = label.trim().to_string();
+            }
+        }
+        self.notify_observers();
+    }
+
+    /// Sets the icon shown before an entity's label. Icons are only shown
+    /// alongside a label, so this does nothing for unlabeled entities.
+    pub fn set_entity_icon(&self, entity_id: EntityId, icon: Option<String>) {
+        {
+            let mut labels = self.entity_labels.lock().unwrap();
+            if let Some(label) = labels.get_mut(&entity_id) {
+                label.icon = icon.filter(|icon| !icon.trim().is_empty());
+            }
+        }
+        self.notify_observers();
+    }
+
+    pub fn get_entity_plugin(&self, entity_id: impl Into<EntityId>) -> Option<Plugin> {
+        let entity_id = entity_id.into();
+        let plugin_id = self.get_entity_plugin_id(entity_id)?;
+        self.get_plugin(&plugin_id)
+    }
+
+    pub fn get_interfaces(&self) -> HashMap<PageId, Component> {
+        let interfaces = self.interfaces.lock().unwrap();
+        interfaces.clone()
+    }
+
+    pub fn get_page_title(&self, page_id: PageId) -> Option<String> {
+        self.page_titles.lock().unwrap().get(&page_id).cloned()
+    }
+
+    /// Returns the page currently open in the frontend, or None for the home
+    /// view.
+    pub fn current_page(&self) -> Option<PageId> {
+        self.page_stack.lock().unwrap().last().copied()
+    }
+
+    pub fn can_navigate_back(&self) -> bool {
+        self.page_stack.lock().unwrap().len() > 1
+    }
+
+    /// Opens a page, or the home view if None, clearing the page stack.
+    pub fn open_page(&self, page_id: Option<PageId>) {
+        *self.page_stack.lock().unwrap() = page_id.into_iter().collect();
+        self.notify_observers();
+    }
+
+    /// Opens a page on top of the current page.
+    pub fn push_page(&self, page_id: PageId) {
+        {
+            let mut stack = self.page_stack.lock().unwrap();
+            if stack.last() != Some(&page_id) {
+                stack.push(page_id);
+            }
+        }
+        self.notify_observers();
+    }
+
+    /// Returns to the previous page, if there is one.
+    pub fn navigate_back(&self) {
+        {
+            let mut stack = self.page_stack.lock().unwrap();
+            if stack.len() > 1 {
+                stack.pop();
+            }
+        }
+        self.notify_observers();
+    }
+
+    pub fn get_interface(&self, page_id: PageId) -> Option<Component> {
+        let interfaces = self.interfaces.lock().unwrap();
+        interfaces.get(&page_id).cloned()
+    }
+
+    pub fn get_user_requests(&self) -> Vec<UserRequest> {
+        let requests = self.user_requests.lock().unwrap();
+        requests.clone()
+    }
+
+    pub fn get_events(&self) -> Vec<Event> {
+        self.events.all()
+    }
+
+    /// Returns the logged events matching `filter`, oldest first.
+    pub fn query_events(&self, filter: &EventFilter) -> Vec<Event> {
+        self.events.query(filter)
+    }
+
+    pub fn get_notifications(&self) -> Vec<NotificationEntry> {
+        let notifications = self.notifications.lock().unwrap();
+        notifications.clone()
+    }
+
+    pub fn get_vault_history(&self, vault_id: VaultId) -> Vec<VaultHistoryEntry> {
+        let history = self.vault_history.lock().unwrap();
+        history
+            .iter()
+            .filter(|e| e.vault_id == vault_id)
+            .cloned()
+            .collect()
+    }
+
+    /// Exports the recorded history of a vault as CSV or JSON.
+    pub fn export_history(
         &self,
-        request: UserRequest,
-        extract_response: F,
-    ) -> Result<T, RpcError>
-    where
-        F: FnOnce(UserResponse) -> Option<T>,
-    {
-        let request_id = request.id();
+        vault_id: VaultId,
+        format: ExportFormat,
+    ) -> Result<String, serde_json::Error> {
+        history::export(&self.get_vault_history(vault_id), format)
+    }
 
-        // Insert the request
-        self.user_requests.lock().unwrap().push(request);
+    pub fn get_state_quota(&self, plugin_id: &PluginId) -> usize {
+        let quotas = self.state_quotas.lock().unwrap();
+        quotas
+            .get(plugin_id)
+            .copied()
+            .unwrap_or(DEFAULT_STATE_QUOTA_BYTES)
+    }
 
-        // Construct a receiver for the response and await it
-        let (sender, receiver) = oneshot::channel();
-        self.user_request_senders
-            .lock()
-            .unwrap()
-            .insert(request_id.clone(), sender);
+    /// Returns the value a plugin has stored under `key`, if any.
+    pub fn get_state_value(&self, plugin_id: &PluginId, key: &str) -> Option<Vec<u8>> {
+        let state = self.state.lock().unwrap();
+        state.get(&(*plugin_id, key.to_string())).cloned()
+    }
+
+    pub fn get_state_usage(&self, plugin_id: &PluginId) -> state::Usage {
+        let used = state_usage(&self.state.lock().unwrap(), plugin_id);
+        state::Usage {
+            used,
+            quota: self.get_state_quota(plugin_id),
+        }
+    }
 
+    /// Overrides a plugin's state quota, or restores the default if `None`.
+    /// Lowering the quota below the plugin's usage keeps its existing state
+    /// but rejects further writes that would grow it.
+    pub fn set_state_quota(&self, plugin_id: PluginId, quota: Option<usize>) {
+        {
+            let mut quotas = self.state_quotas.lock().unwrap();
+            match quota {
+                Some(quota) => quotas.insert(plugin_id, quota),
+                None => quotas.remove(&plugin_id),
+            };
+        }
         self.notify_observers();
-        let resp = receiver.await;
+    }
 
-        // Remove the request from the list
-        self.user_requests
+    pub fn get_resource_limits(&self, plugin_id: &PluginId) -> ResourceLimits {
+        let limits = self.resource_limits.lock().unwrap();
+        limits.get(plugin_id).copied().unwrap_or_default()
+    }
+
+    /// Overrides a plugin's resource limits, or restores the defaults if
+    /// `None`. Limits are applied when the plugin is built, so reload the
+    /// plugin for them to take effect.
+    pub fn set_resource_limits(&self, plugin_id: PluginId, limits: Option<ResourceLimits>) {
+        {
+            let mut overrides = self.resource_limits.lock().unwrap();
+            match limits {
+                Some(limits) => overrides.insert(plugin_id, limits),
+                None => overrides.remove(&plugin_id),
+            };
+        }
+        self.notify_observers();
+    }
+
+    pub fn get_active_chain(&self) -> caip::ChainId {
+        self.active_chain.lock().unwrap().clone()
+    }
+
+    /// Sets the active chain and notifies all plugins implementing
+    /// `OnChainChanged`.
+    pub async fn set_active_chain(&self, chain_id: caip::ChainId) {
+        *self.active_chain.lock().unwrap() = chain_id.clone();
+        self.log_event(&format!("Switched to chain {}", chain_id), None);
+        self.notify_observers();
+
+        let plugins: Vec<Plugin> = self.plugins.lock().unwrap().values().cloned().collect();
+        for plugin in plugins {
+            let params = chain_id.clone();
+            match self
+                .call_plugin_optional(plugin.clone(), plugin::OnChainChanged, params)
+                .await
+            {
+                Ok(_) => {}
+                Err(e) => warn!(
+                    "Plugin {} failed to handle chain change: {}",
+                    plugin.id(),
+                    e
+                ),
+            }
+        }
+        self.notify_observers();
+    }
+
+    /// Fires every alarm that's come due by calling the owning plugin's
+    /// `OnAlarm`. The frontend is expected to call this periodically.
+    pub async fn fire_due_alarms(&self) {
+        let now = chrono::Local::now();
+        let due: Vec<Alarm> = {
+            let mut alarms = self.alarms.lock().unwrap();
+            let (due, pending) = alarms.drain(..).partition(|a| a.due <= now);
+            *alarms = pending;
+            due
+        };
+
+        for alarm in due {
+            let Some(plugin) = self.get_plugin(&alarm.plugin_id) else {
+                continue;
+            };
+
+            match self
+                .call_plugin(plugin.clone(), plugin::OnAlarm, alarm.tag.clone())
+                .await
+            {
+                Ok(_) => {}
+                Err(RpcError::MethodNotFound) => {
+                    warn!("Plugin {} doesn't implement OnAlarm", plugin.id());
+                }
+                Err(e) => warn!(
+                    "Plugin {} failed to handle alarm '{}': {}",
+                    plugin.id(),
+                    alarm.tag,
+                    e
+                ),
+            }
+        }
+    }
+
+    /// Starts `OnTick` on every plugin whose tick has come due, without
+    /// waiting for them to finish, so one slow tick doesn't hold up the rest.
+    /// The frontend is expected to call this periodically.
+    pub async fn run_due_ticks(self: &Arc<Self>) {
+        for plugin_id in self.ticker.take_due() {
+            let Some(plugin) = self.get_plugin(&plugin_id) else {
+                self.ticker.unregister(&plugin_id);
+                continue;
+            };
+
+            let host = self.clone();
+            spawn(async move {
+                match host.call_plugin(plugin, plugin::OnTick, ()).await {
+                    Ok(_) => host.ticker.complete(&plugin_id, true),
+                    Err(RpcError::MethodNotFound) => {
+                        warn!("Plugin {} doesn't implement OnTick", plugin_id);
+                        host.ticker.unregister(&plugin_id);
+                    }
+                    Err(e) => {
+                        warn!("Plugin {} failed to handle tick: {}", plugin_id, e);
+                        host.ticker.complete(&plugin_id, false);
+                    }
+                }
+            });
+        }
+    }
+
+    /// Polls the balance of every watched deposit account and calls the
+    /// owning vault's `OnDepositDetected` for each balance that increased. The
+    /// frontend is expected to call this periodically.
+    pub async fn poll_deposits(&self) {
+        let watches: Vec<(PluginId, VaultId, EthProviderId, AccountId, AssetId)> = self
+            .deposit_watches
             .lock()
             .unwrap()
-            .retain(|req| req.id() != request_id);
+            .iter()
+            .map(|w| {
+                (
+                    w.plugin_id,
+                    w.vault_id,
+                    w.provider_id,
+                    w.account.clone(),
+                    w.asset.clone(),
+                )
+            })
+            .collect();
 
-        let Ok(resp) = resp else {
-            return Err(RpcError::Custom("Request Dropped".into()));
+        for (plugin_id, vault_id, provider_id, account, asset) in watches {
+            let balance = match self.deposit_balance(provider_id, &account, &asset).await {
+                Ok(balance) => balance,
+                Err(e) => {
+                    warn!("Failed to poll deposits for {}: {}", account, e);
+                    continue;
+                }
+            };
+
+            //? The watch may have been replaced or removed while polling
+            let previous = {
+                let mut watches = self.deposit_watches.lock().unwrap();
+                let Some(watch) = watches.iter_mut().find(|w| {
+                    w.plugin_id == plugin_id
+                        && w.vault_id == vault_id
+                        && w.account == account
+                        && w.asset == asset
+                }) else {
+                    continue;
+                };
+                std::mem::replace(&mut watch.balance, balance)
+            };
+
+            if balance <= previous {
+                continue;
+            }
+
+            let Some(plugin) = self.get_plugin(&plugin_id) else {
+                continue;
+            };
+            let amount = balance - previous;
+            let params = (vault_id, account.clone(), asset, amount);
+            match self
+                .call_plugin(plugin.clone(), vault::OnDepositDetected, params)
+                .await
+            {
+                Ok(_) => {}
+                Err(RpcError::MethodNotFound) => {
+                    warn!("Plugin {} doesn't implement OnDepositDetected", plugin.id());
+                }
+                Err(e) => warn!(
+                    "Plugin {} failed to handle deposit to {}: {}",
+                    plugin.id(),
+                    account,
+                    e
+                ),
+            }
+        }
+    }
+
+    /// Polls the receipt of every pending transaction and reports status
+    /// changes to the plugin that sent it. The frontend is expected to call
+    /// this periodically.
+    pub async fn poll_transactions(&self) {
+        for tx in self.transactions.pending() {
+            let status = match self
+                .eth_get_transaction_receipt((tx.provider_id, tx.hash))
+                .await
+            {
+                Ok(receipt) => {
+                    let block_number = receipt.block_number.unwrap_or_default();
+                    match receipt.status() {
+                        true => eth::TransactionStatus::Confirmed { block_number },
+                        false => eth::TransactionStatus::Failed { block_number },
+                    }
+                }
+                Err(_) => match self.transaction_replaced(&tx).await {
+                    true => eth::TransactionStatus::Replaced,
+                    false => continue,
+                },
+            };
+
+            let Some(plugin_id) = self.transactions.set_status(&tx.hash, status) else {
+                continue;
+            };
+            self.notify_observers();
+
+            let Some(plugin) = self.get_plugin(&plugin_id) else {
+                continue;
+            };
+            let params = (tx.hash, status);
+            let status = plugin::OnTransactionStatus;
+            match self
+                .call_plugin_optional(plugin.clone(), status, params)
+                .await
+            {
+                Ok(_) => {}
+                Err(e) => warn!(
+                    "Plugin {} failed to handle status of {}: {}",
+                    plugin.id(),
+                    tx.hash,
+                    e
+                ),
+            }
+        }
+    }
+
+    /// Whether a transaction without a receipt has had its nonce used by
+    /// another transaction from the same sender.
+    async fn transaction_replaced(&self, tx: &TrackedTransaction) -> bool {
+        let (sender, nonce) = match tx.sender {
+            Some(sender) => sender,
+            None => {
+                let Some(plugin) = self.get_entity_plugin(tx.provider_id) else {
+                    return false;
+                };
+                let params = (tx.provider_id, tx.hash);
+                let Ok(found) = self
+                    .call_plugin(plugin, eth::GetTransactionByHash, params)
+                    .await
+                else {
+                    return false;
+                };
+                let sender = found.inner.signer();
+                self.transactions
+                    .set_sender(&tx.hash, sender, found.nonce());
+                (sender, found.nonce())
+            }
         };
 
-        let Some(resp) = extract_response(resp) else {
-            return Err(RpcError::Custom("Unexpected Response Type".into()));
+        let params = (tx.provider_id, sender, BlockId::latest());
+        let Ok(count) = self.eth_transaction_count(params).await else {
+            return false;
         };
+        //? The transaction may have been mined since its receipt was checked
+        count > nonce
+            && self
+                .eth_get_transaction_receipt((tx.provider_id, tx.hash))
+                .await
+                .is_err()
+    }
 
-        Ok(resp)
+    /// Returns the transactions sent through `eth_sendRawTransaction` that are
+    /// pending, or finished recently, oldest first.
+    pub fn get_transactions(&self) -> Vec<TrackedTransaction> {
+        self.transactions.all()
     }
 
-    fn resolve_user_request(&self, request_id: Uuid, resp: UserResponse) {
-        let sender = self
-            .user_request_senders
+    pub fn clear_finished_transactions(&self) {
+        self.transactions.clear_finished();
+        self.notify_observers();
+    }
+
+    /// Starts simulation mode, rerouting EthProvider calls to `fork` instead
+    /// of broadcasting transactions.
+    pub fn enable_simulation(&self, fork: EthProviderId) -> Result<(), RpcError> {
+        self.get_entity_plugin_error(fork)?;
+        info!("Simulating against provider {}", fork);
+        self.simulation.enable(fork);
+        self.notify_observers();
+        Ok(())
+    }
+
+    pub fn disable_simulation(&self) {
+        info!("Simulation stopped");
+        self.simulation.disable();
+        self.notify_observers();
+    }
+
+    /// The fork provider calls are rerouted to, while simulating.
+    pub fn get_simulation_fork(&self) -> Option<EthProviderId> {
+        self.simulation.fork()
+    }
+
+    pub fn get_simulated_transactions(&self) -> Vec<SimulatedTransaction> {
+        self.simulation.transactions()
+    }
+
+    /// Fails broadcasts that simulation mode can't redirect. Only raw EVM
+    /// transactions have a fork to go to, so anything else would reach the
+    /// live chain.
+    fn reject_in_simulation(&self, method: &str) -> Result<(), RpcError> {
+        if self.simulation.fork().is_some() {
+            return Err(RpcError::Custom(format!(
+                "Simulation mode is on, and {} can't be simulated",
+                method
+            )));
+        }
+        Ok(())
+    }
+
+    /// Returns the provider a call meant for `provider_id` should go to. While
+    /// simulating that's the fork, as long as it serves the same chain.
+    async fn simulation_target(&self, provider_id: EthProviderId) -> EthProviderId {
+        let Some(fork) = self.simulation.fork() else {
+            return provider_id;
+        };
+        if fork == provider_id {
+            return fork;
+        }
+        if let Some(target) = self.simulation.target(provider_id) {
+            return target;
+        }
+
+        let (fork_chain, chain) = futures::join!(
+            self.eth_provider_chain_id(fork),
+            self.eth_provider_chain_id(provider_id)
+        );
+        match (fork_chain, chain) {
+            (Ok(fork_chain), Ok(chain)) => {
+                let target = if fork_chain == chain {
+                    fork
+                } else {
+                    provider_id
+                };
+                self.simulation.set_target(provider_id, target);
+                target
+            }
+            //? Not cached, so the chains are compared again once they're known
+            _ => provider_id,
+        }
+    }
+
+    /// Takes the toasts plugins have shown since the last call, oldest first.
+    pub fn take_toasts(&self) -> Vec<(PluginId, host::Toast)> {
+        std::mem::take(&mut *self.toasts.lock().unwrap())
+    }
+
+    pub fn dismiss_notification(&self, notification_id: Uuid) {
+        self.notifications
             .lock()
             .unwrap()
-            .remove(&request_id);
-        let Some(sender) = sender else {
-            warn!("No sender found for user request {}", request_id);
-            return;
-        };
+            .retain(|n| n.id != notification_id);
+        self.notify_observers();
+    }
+
+    /// Resolves an eth provider request. If `remember` is set, the requesting
+    /// plugin is granted the provider and isn't prompted for it again.
+    pub fn resolve_eth_provider_request(
+        &self,
+        request_id: Uuid,
+        provider_id: EthProviderId,
+        remember: bool,
+    ) {
+        if remember {
+            self.grant_requested_entity(request_id, provider_id.into());
+        }
+        self.resolve_user_request(request_id, UserResponse::EthProvider(provider_id));
+    }
+
+    /// Resolves a vault request. If `remember` is set, the requesting plugin
+    /// is granted the vault and isn't prompted for it again.
+    pub fn resolve_vault_request(&self, request_id: Uuid, vault_id: VaultId, remember: bool) {
+        if remember {
+            self.grant_requested_entity(request_id, vault_id.into());
+        }
+        self.resolve_user_request(request_id, UserResponse::Vault(vault_id));
+    }
+
+    pub fn resolve_coordinator_request(&self, request_id: Uuid, coordinator_id: CoordinatorId) {
+        self.resolve_user_request(request_id, UserResponse::Coordinator(coordinator_id.into()));
+    }
+
+    pub fn resolve_sol_provider_request(&self, request_id: Uuid, provider_id: SolProviderId) {
+        self.resolve_user_request(request_id, UserResponse::SolProvider(provider_id));
+    }
+
+    pub fn resolve_btc_provider_request(&self, request_id: Uuid, provider_id: BtcProviderId) {
+        self.resolve_user_request(request_id, UserResponse::BtcProvider(provider_id));
+    }
+
+    pub fn resolve_price_provider_request(&self, request_id: Uuid, provider_id: PriceProviderId) {
+        self.resolve_user_request(request_id, UserResponse::PriceProvider(provider_id));
+    }
+
+    pub fn resolve_signer_request(&self, request_id: Uuid, signer_id: SignerId) {
+        self.resolve_user_request(request_id, UserResponse::Signer(signer_id));
+    }
+
+    pub fn resolve_bundler_provider_request(
+        &self,
+        request_id: Uuid,
+        provider_id: BundlerProviderId,
+    ) {
+        self.resolve_user_request(request_id, UserResponse::BundlerProvider(provider_id));
+    }
+
+    pub fn resolve_token_list_request(&self, request_id: Uuid, token_list_id: TokenListId) {
+        self.resolve_user_request(request_id, UserResponse::TokenList(token_list_id));
+    }
+
+    pub fn resolve_nft_provider_request(&self, request_id: Uuid, provider_id: NftProviderId) {
+        self.resolve_user_request(request_id, UserResponse::NftProvider(provider_id));
+    }
+
+    pub fn approve_manifest_request(&self, request_id: Uuid) {
+        self.resolve_user_request(request_id, UserResponse::ManifestApproved);
+    }
+
+    pub fn approve_origin_request(&self, request_id: Uuid) {
+        self.resolve_user_request(request_id, UserResponse::OriginApproved);
+    }
+
+    pub fn retry_dependencies_request(&self, request_id: Uuid) {
+        self.resolve_user_request(request_id, UserResponse::DependenciesRetried);
+    }
+
+    pub fn approve_unsigned_plugin_request(&self, request_id: Uuid) {
+        self.resolve_user_request(request_id, UserResponse::UnsignedPluginApproved);
+    }
+
+    pub fn approve_upgrade_request(&self, request_id: Uuid) {
+        self.resolve_user_request(request_id, UserResponse::UpgradeApproved);
+    }
+
+    pub fn approve_call_request(&self, request_id: Uuid, session: Option<SessionTerms>) {
+        self.resolve_user_request(request_id, UserResponse::CallApproved(session));
+    }
+
+    pub fn is_keystore_unlocked(&self) -> bool {
+        self.keystore.is_unlocked()
+    }
+
+    /// Unlocks the keystore with the user's password, or sets the password if
+    /// none has been chosen yet. Resolves every pending unlock request.
+    pub fn unlock_keystore(&self, password: &str) -> Result<(), KeystoreError> {
+        self.keystore.unlock(password)?;
+        let pending: Vec<Uuid> = self
+            .user_requests
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|r| matches!(r, UserRequest::KeystoreUnlock { .. }))
+            .map(|r| r.id())
+            .collect();
+        for request_id in pending {
+            self.resolve_user_request(request_id, UserResponse::KeystoreUnlocked);
+        }
+        self.notify_observers();
+        Ok(())
+    }
+
+    pub fn lock_keystore(&self) {
+        self.keystore.lock();
+        self.notify_observers();
+    }
+
+    pub fn get_trusted_publishers(&self) -> Vec<TrustedPublisher> {
+        self.trusted_publishers.lock().unwrap().clone()
+    }
+
+    /// Trusts plugins signed by `key`, replacing any publisher with the same
+    /// key.
+    pub fn trust_publisher(&self, name: &str, key: [u8; 32]) {
+        {
+            let mut publishers = self.trusted_publishers.lock().unwrap();
+            publishers.retain(|p| p.key != key);
+            publishers.push(TrustedPublisher {
+                name: name.to_string(),
+                key,
+            });
+        }
+        self.notify_observers();
+    }
+
+    pub fn untrust_publisher(&self, key: &[u8; 32]) {
+        self.trusted_publishers
+            .lock()
+            .unwrap()
+            .retain(|p| &p.key != key);
+        self.notify_observers();
+    }
+
+    pub fn confirm_request(&self, request_id: Uuid) {
+        self.resolve_user_request(request_id, UserResponse::Confirmed);
+    }
+
+    pub fn resolve_choice_request(&self, request_id: Uuid, option: usize) {
+        self.resolve_user_request(request_id, UserResponse::Choice(option));
+    }
+
+    pub fn get_default_eth_providers(&self) -> Vec<(caip::ChainId, EthProviderId)> {
+        let defaults = self.default_eth_providers.lock().unwrap();
+        defaults.iter().map(|(c, p)| (c.clone(), *p)).collect()
+    }
+
+    pub fn get_default_eth_provider(&self, chain_id: &caip::ChainId) -> Option<EthProviderId> {
+        self.default_eth_providers
+            .lock()
+            .unwrap()
+            .get(chain_id)
+            .copied()
+    }
+
+    /// Sets the provider plugins get for `chain_id` without being prompted,
+    /// or clears it so the user is asked each time.
+    pub fn set_default_eth_provider(
+        &self,
+        chain_id: caip::ChainId,
+        provider_id: Option<EthProviderId>,
+    ) {
+        {
+            let mut defaults = self.default_eth_providers.lock().unwrap();
+            match provider_id {
+                Some(provider_id) => defaults.insert(chain_id, provider_id),
+                None => defaults.remove(&chain_id),
+            };
+        }
+        self.notify_observers();
+    }
+
+    pub fn get_permission_grants(&self) -> Vec<(PluginId, EntityId)> {
+        self.permissions.grants()
+    }
+
+    pub fn revoke_permission(&self, plugin_id: &PluginId, entity_id: &EntityId) {
+        self.permissions.revoke(plugin_id, entity_id);
+        self.notify_observers();
+    }
+
+    pub fn get_session_grants(&self) -> Vec<SessionGrant> {
+        self.permissions.sessions()
+    }
+
+    /// Lets a plugin call `method` on an entity for `ttl`, optionally spending
+    /// at most `limit` of an asset in total, without prompting the user.
+    pub fn grant_session(
+        &self,
+        plugin_id: PluginId,
+        entity_id: EntityId,
+        method: &str,
+        limit: Option<(AssetId, U256)>,
+        ttl: Duration,
+    ) -> Uuid {
+        let id = self
+            .permissions
+            .grant_session(plugin_id, entity_id, method, limit, ttl);
+        self.notify_observers();
+        id
+    }
+
+    pub fn revoke_session_grant(&self, id: &Uuid) {
+        self.permissions.revoke_session(id);
+        self.notify_observers();
+    }
+
+    /// Checks that a plugin may make a value-moving call on an entity, such as
+    /// a withdrawal, a transaction broadcast, or a coordinator proposal. Calls
+    /// are allowed from the entity's own plugin and when a session grant
+    /// covers them. Standing grants only let a plugin select the entity, so
+    /// otherwise the user is asked to approve the call, and may open a
+    /// session grant for further calls at the same time.
+    async fn authorize_call(
+        &self,
+        plugin_id: PluginId,
+        entity_id: EntityId,
+        method: &str,
+        spend: Option<(AssetId, U256)>,
+    ) -> Result<(), RpcError> {
+        if self.get_entity_plugin_id(entity_id) == Some(plugin_id) {
+            return Ok(());
+        }
+        let spent = spend.as_ref().map(|(asset, amount)| (asset, *amount));
+        if self
+            .permissions
+            .use_session(&plugin_id, &entity_id, method, spent)
+        {
+            return Ok(());
+        }
+
+        let request = UserRequest::CallApproval {
+            id: Uuid::new_v4(),
+            plugin_id,
+            entity_id,
+            method: method.to_string(),
+            spend: spend.clone(),
+        };
+        let terms = self
+            .create_user_request(request, |resp| match resp {
+                UserResponse::CallApproved(terms) => Some(terms),
+                _ => None,
+            })
+            .await?;
+        let Some(terms) = terms else {
+            return Ok(());
+        };
+
+        //? A limit can only be enforced on calls whose spend the host can
+        //? measure, so rather than open a session that's silently unlimited
+        //? the call fails
+        let limit = match (&spend, terms.limit) {
+            (Some((asset, _)), Some(max)) => Some((asset.clone(), max)),
+            (None, Some(_)) => {
+                return Err(RpcError::Custom(format!(
+                    "The host can't measure what {} spends, so its session can't be limited",
+                    method
+                )));
+            }
+            (_, None) => None,
+        };
+        self.grant_session(plugin_id, entity_id, method, limit, terms.ttl);
+        //? The approved call is the session's first, so it counts against the
+        //? limit the user just set
+        if !self
+            .permissions
+            .use_session(&plugin_id, &entity_id, method, spent)
+        {
+            return Err(RpcError::Custom("Call exceeds the session's limit".into()));
+        }
+        Ok(())
+    }
+
+    fn grant_requested_entity(&self, request_id: Uuid, entity_id: EntityId) {
+        let plugin_id = {
+            let requests = self.user_requests.lock().unwrap();
+            requests
+                .iter()
+                .find(|req| req.id() == request_id)
+                .map(|req| req.plugin_id())
+        };
+
+        if let Some(plugin_id) = plugin_id {
+            self.permissions.grant(plugin_id, entity_id);
+        }
+    }
+
+    /// Returns the oldest entity granted to a plugin that `filter_map` accepts
+    /// and that still satisfies `required`.
+    fn granted_entity<T>(
+        &self,
+        plugin_id: &PluginId,
+        required: &host::EntityCapabilities,
+        filter_map: impl Fn(EntityId) -> Option<T>,
+    ) -> Option<T> {
+        self.permissions
+            .granted(plugin_id)
+            .into_iter()
+            .find_map(|entity_id| {
+                self.get_entity_plugin_id(entity_id)?;
+                if !self.get_entity_capabilities(entity_id).satisfies(required) {
+                    return None;
+                }
+                filter_map(entity_id)
+            })
+    }
+
+    pub fn deny_user_request(&self, request_id: Uuid) {
+        //? Drop the sender to cancel the request
+        self.user_request_senders
+            .lock()
+            .unwrap()
+            .remove(&request_id);
+    }
+
+    async fn create_user_request<T, F>(
+        &self,
+        request: UserRequest,
+        extract_response: F,
+    ) -> Result<T, RpcError>
+    where
+        F: FnOnce(UserResponse) -> Option<T>,
+    {
+        let request_id = request.id();
+
+        // Insert the request
+        self.user_requests.lock().unwrap().push(request);
+
+        // Construct a receiver for the response and await it
+        let (sender, receiver) = oneshot::channel();
+        self.user_request_senders
+            .lock()
+            .unwrap()
+            .insert(request_id.clone(), sender);
+
+        self.notify_observers();
+        let resp = receiver.await;
+
+        // Remove the request from the list
+        self.user_requests
+            .lock()
+            .unwrap()
+            .retain(|req| req.id() != request_id);
+
+        let Ok(resp) = resp else {
+            return Err(RpcError::Custom("Request Dropped".into()));
+        };
+
+        let Some(resp) = extract_response(resp) else {
+            return Err(RpcError::Custom("Unexpected Response Type".into()));
+        };
+
+        Ok(resp)
+    }
+
+    fn resolve_user_request(&self, request_id: Uuid, resp: UserResponse) {
+        let sender = self
+            .user_request_senders
+            .lock()
+            .unwrap()
+            .remove(&request_id);
+        let Some(sender) = sender else {
+            warn!("No sender found for user request {}", request_id);
+            return;
+        };
+
+        if sender.send(resp).is_err() {
+            warn!("Failed to send response for user request {}", request_id);
+        }
+    }
+
+    /// Like `call_plugin`, for methods plugins may leave out. Returns None if
+    /// the plugin doesn't implement the method.
+    async fn call_plugin_optional<M: OptionalMethod>(
+        &self,
+        plugin: Plugin,
+        method: M,
+        params: M::Params,
+    ) -> Result<Option<M::Output>, RpcError> {
+        tlock_hdk::tlock_api::optional(self.call_plugin(plugin, method, params).await)
+    }
+
+    /// Calls a method on a plugin, waiting in the plugin's queue if it already
+    /// has `MAX_CONCURRENT_CALLS` calls running.
+    async fn call_plugin<M: RpcMethod>(
+        &self,
+        plugin: Plugin,
+        method: M,
+        params: M::Params,
+    ) -> Result<M::Output, RpcError> {
+        let plugin_id = plugin.id();
+        let name = plugin.name().to_string();
+        let timeout = self.get_call_timeout(M::NAME);
+        let Some(slot) = self.scheduler.acquire(plugin_id, timeout).await else {
+            warn!("Plugin {} was too busy to call {}", plugin_id, M::NAME);
+            let message = format!("{} waited {:?} for a free slot", M::NAME, timeout);
+            return Err(DomainError::new(ErrorCode::TIMEOUT, message).into());
+        };
+        let started = chrono::Local::now();
+
+        //? Host methods the plugin calls are handled inside this future, so
+        //? their spans nest under this one and inherit its trace
+        let trace_id = TraceId::current_or_new();
+        let span = tracing::info_span!(
+            "plugin_call",
+            plugin = %plugin_id,
+            method = M::NAME,
+            trace_id = %trace_id,
+        );
+        let _trace = tlock_hdk::trace::register(&span, trace_id);
+        let recording = self
+            .recorder
+            .begin(&plugin_id, Direction::ToPlugin)
+            .map(|pending| (pending, serde_json::to_value(&params).unwrap_or_default()));
+        let call = slot.hold(method.call_async(plugin, params).instrument(span));
+
+        //? Missing the deadline only stops the host waiting on the call. The
+        //? guest isn't interrupted, so it keeps running until it returns on
+        //? its own, and may still call the host meanwhile.
+        // TODO: Stop the guest once its deadline passes. The plugin framework
+        // has no way to interrupt a running instance yet, e.g. by suspending
+        // it after each slice of fuel, or to send it a cancellation notice, so
+        // this has to be added upstream in wasmi-plugin-framework first. A
+        // guest spinning in a loop never yields either, so in the browser the
+        // deadline can't fire until it returns and the tab freezes meanwhile.
+        let result = time::timeout(timeout, call).await;
+        let elapsed_ms = (chrono::Local::now() - started).num_milliseconds().max(0) as u64;
+        let ok = matches!(result, Some(Ok(_)));
+        self.metrics.record(plugin_id, M::NAME, elapsed_ms, ok);
+
+        let result = match result {
+            Some(result) => result,
+            None => {
+                warn!("Plugin {} timed out calling {}", plugin_id, M::NAME);
+                let message = format!("{} timed out after {:?}", M::NAME, timeout);
+                self.events.push(
+                    host::NotifyLevel::Warn,
+                    Some((plugin_id, name)),
+                    Some(M::NAME),
+                    message.clone(),
+                );
+                Err(DomainError::new(ErrorCode::TIMEOUT, message).into())
+            }
+        };
+        if let Some((pending, params)) = recording {
+            let recorded = result
+                .as_ref()
+                .map(|output| serde_json::to_value(output).unwrap_or_default())
+                .map_err(RecordedError::from);
+            self.recorder.finish(pending, M::NAME, params, recorded);
+        }
+        result
+    }
+
+    /// Returns how long a call to `method` may run before it's aborted.
+    pub fn get_call_timeout(&self, method: &str) -> Duration {
+        self.call_timeouts
+            .lock()
+            .unwrap()
+            .get(method)
+            .copied()
+            .unwrap_or(Duration::from_secs(PLUGIN_TIMEOUT_SECS))
+    }
+
+    /// The longest any call may run, across every method's deadline.
+    fn max_call_timeout(&self) -> Duration {
+        let timeouts = self.call_timeouts.lock().unwrap();
+        timeouts
+            .values()
+            .copied()
+            .fold(Duration::from_secs(PLUGIN_TIMEOUT_SECS), Duration::max)
+    }
+
+    /// Overrides the deadline for calls to `method`, or restores the default
+    /// if `timeout` is None.
+    pub fn set_call_timeout(&self, method: &str, timeout: Option<Duration>) {
+        let mut timeouts = self.call_timeouts.lock().unwrap();
+        match timeout {
+            Some(timeout) => timeouts.insert(method.to_string(), timeout),
+            None => timeouts.remove(method),
+        };
+    }
+
+    pub fn get_call_stats(&self, plugin_id: &PluginId) -> CallStats {
+        self.scheduler.stats(plugin_id)
+    }
+
+    pub fn get_rate_limit(&self, class: MethodClass) -> RateLimit {
+        self.rate_limiter.limit(class)
+    }
+
+    /// Overrides how often plugins may call a class of host methods.
+    pub fn set_rate_limit(&self, class: MethodClass, limit: RateLimit) {
+        self.rate_limiter.set_limit(class, limit);
+    }
+
+    /// Returns call metrics for every plugin method the host has called,
+    /// sorted by plugin then method.
+    pub fn get_metrics(&self) -> Vec<(PluginId, String, MethodMetrics)> {
+        self.metrics.snapshot()
+    }
+
+    /// Returns call metrics for every host method plugins have called, sorted
+    /// by plugin then method.
+    pub fn get_host_call_metrics(&self) -> Vec<(PluginId, String, MethodMetrics)> {
+        self.host_call_metrics.snapshot()
+    }
+
+    /// Starts recording a plugin's calls to and from the host, discarding any
+    /// recording in progress.
+    pub fn start_recording(&self, plugin_id: PluginId) {
+        self.recorder.start(plugin_id);
+        self.notify_observers();
+    }
+
+    /// Stops recording, returning the recording if there was one.
+    pub fn stop_recording(&self) -> Option<Recording> {
+        let recording = self.recorder.stop();
+        self.notify_observers();
+        recording
+    }
+
+    /// The plugin being recorded, if any.
+    pub fn get_recording_plugin(&self) -> Option<PluginId> {
+        self.recorder.recording()
+    }
+
+    /// Loads the recorded plugin and makes the host's recorded calls into it
+    /// again, one at a time, answering its host calls from the recording.
+    /// Returns each call's replayed result alongside the recorded one.
+    ///
+    /// Meant for a fresh host such as a `Harness`'s, since every host call the
+    /// plugin makes during the replay is answered from the recording.
+    pub async fn replay(
+        self: &Arc<Host>,
+        source: PluginSource,
+        name: &str,
+        recording: &Recording,
+    ) -> Result<Vec<Replayed>, PluginError> {
+        let wasm_bytes = source.as_bytes().await?;
+        let plugin_id = plugin_id_for(&wasm_bytes);
+        if plugin_id != recording.plugin_id {
+            return Err(PluginError::RecordingMismatch(name.to_string()));
+        }
+        let plugin = match self.get_plugin(&plugin_id) {
+            Some(plugin) => plugin,
+            None => {
+                self.load_plugin_bytes(plugin_id, source, wasm_bytes, None, name)
+                    .await?
+            }
+        };
+
+        *self.replayer.lock().unwrap() = Some(Replayer::new(recording));
+        let methods = schema::methods();
+        let mut replayed = Vec::new();
+        for call in recording.plugin_calls() {
+            let actual = match methods.iter().find(|m| m.name == call.method) {
+                Some(method) => call_raw(plugin.clone(), method.name, call.params.clone())
+                    .await
+                    .map_err(|e| RecordedError::from(&e)),
+                None => Err(RecordedError::MethodNotFound),
+            };
+            replayed.push(Replayed {
+                method: call.method.clone(),
+                expected: call.result.clone(),
+                actual,
+            });
+        }
+        *self.replayer.lock().unwrap() = None;
+        Ok(replayed)
+    }
+
+    pub fn reset_metrics(&self) {
+        self.metrics.reset();
+        self.host_call_metrics.reset();
+        self.notify_observers();
+    }
+
+    pub fn get_audit_log(&self) -> Vec<AuditEntry> {
+        self.audit_log.all()
+    }
+
+    /// Exports the audit log as pretty-printed JSON.
+    pub fn export_audit_log(&self) -> Result<String, serde_json::Error> {
+        self.audit_log.export_json()
+    }
+
+    /// Records the outcome of an audited call made by `plugin_id`.
+    fn audit<T>(
+        &self,
+        plugin_id: PluginId,
+        method: &str,
+        params: &impl Serialize,
+        result: &Result<T, RpcError>,
+    ) {
+        let name = match self.get_plugin(&plugin_id) {
+            Some(plugin) => plugin.name().to_string(),
+            None => "<unknown>".to_string(),
+        };
+        let outcome = match result {
+            Ok(_) => AuditOutcome::Success,
+            Err(e) => AuditOutcome::Failed(DomainError::describe(e)),
+        };
+        self.audit_log
+            .push(plugin_id, name, method, params, outcome);
+        self.notify_observers();
+    }
+
+    fn get_entity_plugin_error(&self, entity_id: impl Into<EntityId>) -> Result<Plugin, RpcError> {
+        let entity_id = entity_id.into();
+        let plugin = self
+            .get_entity_plugin(entity_id)
+            .context(format!("Entity {:?} not found", entity_id))?;
+
+        Ok(plugin)
+    }
+
+    pub async fn ping_plugin(&self, plugin_id: &PluginId) -> Result<String, RpcError> {
+        let plugin = self
+            .get_plugin(plugin_id)
+            .context(format!("Plugin {} not found", plugin_id))?;
+
+        let resp = self
+            .call_plugin(plugin, global::Ping, ())
+            .await
+            .context(format!("Error calling Ping on plugin {}", plugin_id))?;
+        Ok(resp)
+    }
+
+    pub fn log_event(&self, event: &str, plugin: Option<(PluginId, &str)>) {
+        let plugin = plugin.map(|(id, name)| (id, name.to_string()));
+        self.events
+            .push(host::NotifyLevel::Trace, plugin, None, event);
+    }
+
+    /// Logs a plugin's call to a host method.
+    pub fn log_call(&self, method: &str, plugin_id: PluginId) {
+        let name = match self.get_plugin(&plugin_id) {
+            Some(plugin) => plugin.name().to_string(),
+            None => "<unknown>".to_string(),
+        };
+        self.events.push(
+            host::NotifyLevel::Trace,
+            Some((plugin_id, name)),
+            Some(method),
+            method,
+        );
+    }
+}
+
+/// Shorter deadlines for provider reads, so a hung provider fails fast.
+fn default_call_timeouts() -> HashMap<String, Duration> {
+    let reads = [
+        eth::ChainId::NAME,
+        eth::BlockNumber::NAME,
+        eth::Call::NAME,
+        eth::GetBalance::NAME,
+        eth::GasPrice::NAME,
+        eth::GetTransactionCount::NAME,
+        eth::EstimateGas::NAME,
+    ];
+    let timeout = Duration::from_secs(READ_TIMEOUT_SECS);
+    reads
+        .into_iter()
+        .map(|m| (m.to_string(), timeout))
+        .collect()
+}
+
+/// Bytes of state stored by a plugin, keys included.
+fn state_usage(state: &HashMap<(PluginId, String), Vec<u8>>, plugin_id: &PluginId) -> usize {
+    state
+        .iter()
+        .filter(|((id, _), _)| id == plugin_id)
+        .map(|((_, key), value)| key.len() + value.len())
+        .sum()
+}
+
+/// Middleware recording the latency and outcome of every host method call.
+fn record_host_call<'a>(
+    host: &'a Weak<Host>,
+    instance: InstanceId,
+    method: &'a str,
+    params: serde_json::Value,
+    next: Next<'a, Weak<Host>>,
+) -> BoxFuture<'a, Result<serde_json::Value, RpcError>> {
+    Box::pin(async move {
+        let started = chrono::Local::now();
+        let result = next.run(params).await;
+        let elapsed_ms = (chrono::Local::now() - started).num_milliseconds().max(0) as u64;
+        if let Some(host) = host.upgrade() {
+            let metrics = &host.host_call_metrics;
+            metrics.record(instance.plugin, method, elapsed_ms, result.is_ok());
+        }
+        result
+    })
+}
+
+/// Middleware recording a plugin's host calls while it's being recorded, and
+/// answering them from the recording while it's being replayed.
+fn record_interaction<'a>(
+    host: &'a Weak<Host>,
+    instance: InstanceId,
+    method: &'a str,
+    params: serde_json::Value,
+    next: Next<'a, Weak<Host>>,
+) -> BoxFuture<'a, Result<serde_json::Value, RpcError>> {
+    Box::pin(async move {
+        let pending = match host.upgrade() {
+            Some(host) => {
+                let replayer = host.replayer.lock().unwrap();
+                let answer = replayer
+                    .as_ref()
+                    .and_then(|r| r.answer(&instance.plugin, method, &params));
+                if let Some(answer) = answer {
+                    return answer;
+                }
+                host.recorder.begin(&instance.plugin, Direction::ToHost)
+            }
+            None => None,
+        };
+        let Some(pending) = pending else {
+            return next.run(params).await;
+        };
+
+        let result = pending.within(next.run(params.clone())).await;
+        if let Some(host) = host.upgrade() {
+            let recorded = result.as_ref().cloned().map_err(RecordedError::from);
+            host.recorder.finish(pending, method, params, recorded);
+        }
+        result
+    })
+}
+
+/// Runs a future in the background.
+#[cfg(target_arch = "wasm32")]
+fn spawn<F: Future<Output = ()> + 'static>(fut: F) {
+    wasm_bindgen_futures::spawn_local(fut);
+}
+
+//? Natively the host runs inside a tokio runtime, which reqwest needs to
+//? drive requests made by the spawned future
+#[cfg(not(target_arch = "wasm32"))]
+fn spawn<F: Future<Output = ()> + Send + 'static>(fut: F) {
+    tokio::spawn(fut);
+}
+
+/// Plugins are identified by the hash of their wasm binary
+fn plugin_id_for(wasm_bytes: &[u8]) -> PluginId {
+    let mut s = DefaultHasher::new();
+    wasm_bytes.hash(&mut s);
+    let id: u128 = s.finish().into();
+    PluginId::from(id)
+}
+
+// TODO: Create a macro for these. It seens extremely possible, if a little
+// fiddly.
+impl Host {
+    pub async fn ping(&self, _instance_id: &InstanceId, _params: ()) -> Result<String, RpcError> {
+        Ok("Pong from host".to_string())
+    }
+
+    pub async fn register_entity(
+        &self,
+        instance_id: &InstanceId,
+        domain: Domain,
+    ) -> Result<EntityId, RpcError> {
+        self.register_entity_with_capabilities(
+            instance_id,
+            (domain, host::EntityCapabilities::default()),
+        )
+        .await
+    }
+
+    pub async fn register_entity_with_capabilities(
+        &self,
+        instance_id: &InstanceId,
+        params: (Domain, host::EntityCapabilities),
+    ) -> Result<EntityId, RpcError> {
+        let (domain, capabilities) = params;
+        //? Plugins without a manifest predate them, so keep letting them
+        //? register in any domain
+        let permitted = self
+            .get_manifest(&instance_id.plugin)
+            .is_none_or(|m| m.allows_domain(domain));
+        if !permitted {
+            return Err(RpcError::Custom(format!(
+                "Domain {} not permitted by plugin manifest",
+                domain
+            )));
+        }
+
+        let entity_id: EntityId = match domain {
+            Domain::EthProvider => EthProviderId::new().into(),
+            Domain::Page => PageId::new().into(),
+            Domain::Vault => VaultId::new().into(),
+            Domain::Coordinator => CoordinatorId::new().into(),
+            Domain::SolProvider => SolProviderId::new().into(),
+            Domain::BtcProvider => BtcProviderId::new().into(),
+            Domain::PriceProvider => PriceProviderId::new().into(),
+            Domain::Signer => SignerId::new().into(),
+            Domain::BundlerProvider => BundlerProviderId::new().into(),
+            Domain::TokenList => TokenListId::new().into(),
+            Domain::NftProvider => NftProviderId::new().into(),
+        };
+
+        self.entities
+            .lock()
+            .unwrap()
+            .insert(entity_id, instance_id.plugin);
+        if capabilities != host::EntityCapabilities::default() {
+            self.entity_capabilities
+                .lock()
+                .unwrap()
+                .insert(entity_id, capabilities);
+        }
+        Ok(entity_id)
+    }
+
+    pub async fn request_eth_provider(
+        &self,
+        instance_id: &InstanceId,
+        params: (Option<caip::ChainId>, host::EntityCapabilities),
+    ) -> Result<EthProviderId, RpcError> {
+        let (chain_id, required) = params;
+        let chain_id = chain_id.unwrap_or_else(|| self.get_active_chain());
+        let required = required.with_chain(chain_id.clone());
+        let granted = self.granted_entity(&instance_id.plugin, &required, |id| match id {
+            EntityId::EthProvider(provider_id) => Some(provider_id),
+            _ => None,
+        });
+        if let Some(provider_id) = granted {
+            return Ok(provider_id);
+        }
+
+        //? Defaults that no longer satisfy the request fall back to prompting
+        if let Some(provider_id) = self.get_default_eth_provider(&chain_id) {
+            let entity_id = EntityId::EthProvider(provider_id);
+            let loaded = self.get_entity_plugin_id(entity_id).is_some();
+            if loaded && self.get_entity_capabilities(entity_id).satisfies(&required) {
+                return Ok(provider_id);
+            }
+        }
+
+        let request = UserRequest::EthProviderSelection {
+            id: Uuid::new_v4(),
+            plugin_id: instance_id.plugin,
+            chain_id,
+            required,
+        };
+
+        self.create_user_request(request, |resp| match resp {
+            UserResponse::EthProvider(selected_provider) => Some(selected_provider),
+            _ => None,
+        })
+        .await
+    }
+
+    pub async fn active_chain(
+        &self,
+        _instance_id: &InstanceId,
+        _params: (),
+    ) -> Result<caip::ChainId, RpcError> {
+        Ok(self.get_active_chain())
+    }
+
+    pub async fn request_confirmation(
+        &self,
+        instance_id: &InstanceId,
+        params: (String, String, Component),
+    ) -> Result<bool, RpcError> {
+        let (title, body, details) = params;
+        let request = UserRequest::Confirmation {
+            id: Uuid::new_v4(),
+            plugin_id: instance_id.plugin,
+            title,
+            body,
+            details,
+        };
+
+        let resp = self
+            .create_user_request(request, |resp| match resp {
+                UserResponse::Confirmed => Some(true),
+                _ => None,
+            })
+            .await;
+
+        //? Denied requests are dropped, which the plugin sees as a refusal
+        Ok(resp.unwrap_or(false))
+    }
+
+    pub async fn request_user_choice(
+        &self,
+        instance_id: &InstanceId,
+        params: (String, Component, Vec<String>),
+    ) -> Result<Option<usize>, RpcError> {
+        let (title, body, options) = params;
+        if options.is_empty() {
+            return Err(RpcError::Custom("No options to choose from".into()));
+        }
+
+        let option_count = options.len();
+        let request = UserRequest::Custom {
+            id: Uuid::new_v4(),
+            plugin_id: instance_id.plugin,
+            title,
+            body,
+            options,
+        };
+
+        let resp = self
+            .create_user_request(request, |resp| match resp {
+                UserResponse::Choice(option) if option < option_count => Some(option),
+                _ => None,
+            })
+            .await;
+
+        //? A dismissed dialog isn't an error, the plugin just gets no choice
+        Ok(resp.ok())
+    }
+
+    pub async fn request_vault(
+        &self,
+        instance_id: &InstanceId,
+        required: host::EntityCapabilities,
+    ) -> Result<VaultId, RpcError> {
+        let granted = self.granted_entity(&instance_id.plugin, &required, |id| match id {
+            EntityId::Vault(vault_id) => Some(vault_id),
+            _ => None,
+        });
+        if let Some(vault_id) = granted {
+            return Ok(vault_id);
+        }
+
+        let request = UserRequest::VaultSelection {
+            id: Uuid::new_v4(),
+            plugin_id: instance_id.plugin,
+            required,
+        };
+
+        self.create_user_request(request, |resp| match resp {
+            UserResponse::Vault(selected_vault) => Some(selected_vault),
+            _ => None,
+        })
+        .await
+    }
+
+    pub async fn request_coordinator(
+        &self,
+        instance_id: &InstanceId,
+        _params: (),
+    ) -> Result<CoordinatorId, RpcError> {
+        let request = UserRequest::CoordinatorSelection {
+            id: Uuid::new_v4(),
+            plugin_id: instance_id.plugin,
+        };
+
+        self.create_user_request(request, |resp| match resp {
+            UserResponse::Coordinator(selected_coordinator) => Some(selected_coordinator),
+            _ => None,
+        })
+        .await
+    }
+
+    pub async fn request_sol_provider(
+        &self,
+        instance_id: &InstanceId,
+        chain_id: caip::ChainId,
+    ) -> Result<SolProviderId, RpcError> {
+        let request = UserRequest::SolProviderSelection {
+            id: Uuid::new_v4(),
+            plugin_id: instance_id.plugin,
+            chain_id,
+        };
+
+        self.create_user_request(request, |resp| match resp {
+            UserResponse::SolProvider(selected_provider) => Some(selected_provider),
+            _ => None,
+        })
+        .await
+    }
+
+    pub async fn request_btc_provider(
+        &self,
+        instance_id: &InstanceId,
+        chain_id: caip::ChainId,
+    ) -> Result<BtcProviderId, RpcError> {
+        let request = UserRequest::BtcProviderSelection {
+            id: Uuid::new_v4(),
+            plugin_id: instance_id.plugin,
+            chain_id,
+        };
+
+        self.create_user_request(request, |resp| match resp {
+            UserResponse::BtcProvider(selected_provider) => Some(selected_provider),
+            _ => None,
+        })
+        .await
+    }
+
+    pub async fn request_price_provider(
+        &self,
+        instance_id: &InstanceId,
+        _params: (),
+    ) -> Result<PriceProviderId, RpcError> {
+        let request = UserRequest::PriceProviderSelection {
+            id: Uuid::new_v4(),
+            plugin_id: instance_id.plugin,
+        };
+
+        self.create_user_request(request, |resp| match resp {
+            UserResponse::PriceProvider(selected_provider) => Some(selected_provider),
+            _ => None,
+        })
+        .await
+    }
+
+    pub async fn request_signer(
+        &self,
+        instance_id: &InstanceId,
+        _params: (),
+    ) -> Result<SignerId, RpcError> {
+        let request = UserRequest::SignerSelection {
+            id: Uuid::new_v4(),
+            plugin_id: instance_id.plugin,
+        };
+
+        self.create_user_request(request, |resp| match resp {
+            UserResponse::Signer(selected_signer) => Some(selected_signer),
+            _ => None,
+        })
+        .await
+    }
+
+    pub async fn request_bundler_provider(
+        &self,
+        instance_id: &InstanceId,
+        chain_id: caip::ChainId,
+    ) -> Result<BundlerProviderId, RpcError> {
+        let request = UserRequest::BundlerProviderSelection {
+            id: Uuid::new_v4(),
+            plugin_id: instance_id.plugin,
+            chain_id,
+        };
+
+        self.create_user_request(request, |resp| match resp {
+            UserResponse::BundlerProvider(selected_provider) => Some(selected_provider),
+            _ => None,
+        })
+        .await
+    }
+
+    pub async fn request_token_list(
+        &self,
+        instance_id: &InstanceId,
+        _params: (),
+    ) -> Result<TokenListId, RpcError> {
+        let request = UserRequest::TokenListSelection {
+            id: Uuid::new_v4(),
+            plugin_id: instance_id.plugin,
+        };
+
+        self.create_user_request(request, |resp| match resp {
+            UserResponse::TokenList(selected_list) => Some(selected_list),
+            _ => None,
+        })
+        .await
+    }
+
+    pub async fn request_nft_provider(
+        &self,
+        instance_id: &InstanceId,
+        _params: (),
+    ) -> Result<NftProviderId, RpcError> {
+        let request = UserRequest::NftProviderSelection {
+            id: Uuid::new_v4(),
+            plugin_id: instance_id.plugin,
+        };
+
+        self.create_user_request(request, |resp| match resp {
+            UserResponse::NftProvider(selected_provider) => Some(selected_provider),
+            _ => None,
+        })
+        .await
+    }
+
+    pub async fn publish(
+        &self,
+        instance_id: &InstanceId,
+        params: (String, Vec<u8>),
+    ) -> Result<(), RpcError> {
+        let (topic, payload) = params;
+        let subscribers: Vec<Plugin> = self
+            .event_bus
+            .subscribers(&topic)
+            .iter()
+            .filter(|id| **id != instance_id.plugin)
+            .filter_map(|id| self.get_plugin(id))
+            .collect();
+
+        let topic = &topic;
+        let deliveries = subscribers.into_iter().map(|plugin| {
+            let params = (topic.clone(), payload.clone());
+            async move {
+                if let Err(e) = self
+                    .call_plugin(plugin.clone(), plugin::OnEvent, params)
+                    .await
+                {
+                    warn!(
+                        "Plugin {} failed to handle event '{}': {}",
+                        plugin.id(),
+                        topic,
+                        e
+                    );
+                }
+            }
+        });
+        futures::future::join_all(deliveries).await;
+        Ok(())
+    }
+
+    pub async fn subscribe_topic(
+        &self,
+        instance_id: &InstanceId,
+        topic: String,
+    ) -> Result<(), RpcError> {
+        self.event_bus.subscribe(topic, instance_id.plugin);
+        Ok(())
+    }
+
+    pub async fn unsubscribe_topic(
+        &self,
+        instance_id: &InstanceId,
+        topic: String,
+    ) -> Result<(), RpcError> {
+        self.event_bus.unsubscribe(&topic, &instance_id.plugin);
+        Ok(())
+    }
+
+    pub async fn set_alarm(
+        &self,
+        instance_id: &InstanceId,
+        params: (Duration, String),
+    ) -> Result<(), RpcError> {
+        let (duration, tag) = params;
+        let due = chrono::Duration::from_std(duration)
+            .ok()
+            .and_then(|d| chrono::Local::now().checked_add_signed(d))
+            .context("Alarm duration is too long")?;
+
+        let mut alarms = self.alarms.lock().unwrap();
+        alarms.retain(|a| a.plugin_id != instance_id.plugin || a.tag != tag);
+        alarms.push(Alarm {
+            plugin_id: instance_id.plugin,
+            tag,
+            due,
+        });
+        Ok(())
+    }
+
+    pub async fn cancel_alarm(
+        &self,
+        instance_id: &InstanceId,
+        tag: String,
+    ) -> Result<(), RpcError> {
+        self.alarms
+            .lock()
+            .unwrap()
+            .retain(|a| a.plugin_id != instance_id.plugin || a.tag != tag);
+        Ok(())
+    }
+
+    pub async fn register_tick(
+        &self,
+        instance_id: &InstanceId,
+        interval: Duration,
+    ) -> Result<(), RpcError> {
+        self.ticker.register(instance_id.plugin, interval);
+        Ok(())
+    }
+
+    pub async fn unregister_tick(
+        &self,
+        instance_id: &InstanceId,
+        _params: (),
+    ) -> Result<(), RpcError> {
+        self.ticker.unregister(&instance_id.plugin);
+        Ok(())
+    }
+
+    pub async fn sleep(
+        &self,
+        _instance_id: &InstanceId,
+        duration: Duration,
+    ) -> Result<(), RpcError> {
+        //? The sleeping call can't outlast the longest deadline, so neither
+        //? should its sleep, which the host would otherwise keep a timer for
+        time::sleep(duration.min(self.max_call_timeout())).await;
+        Ok(())
+    }
 
-        if sender.send(resp).is_err() {
-            warn!("Failed to send response for user request {}", request_id);
+    pub async fn watch_deposit(
+        &self,
+        instance_id: &InstanceId,
+        params: (VaultId, EthProviderId, AccountId, AssetId),
+    ) -> Result<(), RpcError> {
+        let (vault_id, provider_id, account, asset) = params;
+        if self.get_entity_plugin_id(vault_id) != Some(instance_id.plugin) {
+            return Err(RpcError::custom(format!(
+                "Vault {} not owned by plugin",
+                vault_id
+            )));
         }
+
+        //? Start from the current balance so existing funds aren't reported
+        let balance = self.deposit_balance(provider_id, &account, &asset).await?;
+
+        let mut watches = self.deposit_watches.lock().unwrap();
+        watches.retain(|w| {
+            w.plugin_id != instance_id.plugin
+                || w.vault_id != vault_id
+                || w.account != account
+                || w.asset != asset
+        });
+        watches.push(DepositWatch {
+            plugin_id: instance_id.plugin,
+            vault_id,
+            provider_id,
+            account,
+            asset,
+            balance,
+        });
+        Ok(())
     }
 
-    /// ? Helper to get the plugin or return an RpcError if not found
-    fn get_entity_plugin_error(&self, entity_id: impl Into<EntityId>) -> Result<Plugin, RpcError> {
-        let entity_id = entity_id.into();
-        let plugin = self
-            .get_entity_plugin(entity_id)
-            .context(format!("Entity {:?} not found", entity_id))?;
+    pub async fn unwatch_deposit(
+        &self,
+        instance_id: &InstanceId,
+        params: (VaultId, AccountId, AssetId),
+    ) -> Result<(), RpcError> {
+        let (vault_id, account, asset) = params;
+        self.deposit_watches.lock().unwrap().retain(|w| {
+            w.plugin_id != instance_id.plugin
+                || w.vault_id != vault_id
+                || w.account != account
+                || w.asset != asset
+        });
+        Ok(())
+    }
 
-        Ok(plugin)
+    pub async fn lease_nonce(
+        &self,
+        instance_id: &InstanceId,
+        params: (EthProviderId, Address),
+    ) -> Result<u64, RpcError> {
+        let (provider_id, address) = params;
+        let provider_id = self.simulation_target(provider_id).await;
+        let chain_id = self.nonce_chain_id(provider_id).await?;
+        let pending = self
+            .eth_transaction_count((provider_id, address, BlockId::pending()))
+            .await?;
+        self.nonce_manager(provider_id)
+            .lease(instance_id.plugin, chain_id, address, pending)
+            .map_err(RpcError::Custom)
     }
 
-    pub async fn ping_plugin(&self, plugin_id: &PluginId) -> Result<String, RpcError> {
-        let plugin = self
-            .get_plugin(plugin_id)
-            .context(format!("Plugin {} not found", plugin_id))?;
+    pub async fn release_nonce(
+        &self,
+        instance_id: &InstanceId,
+        params: (EthProviderId, Address, u64),
+    ) -> Result<(), RpcError> {
+        let (provider_id, address, nonce) = params;
+        let provider_id = self.simulation_target(provider_id).await;
+        let chain_id = self.nonce_chain_id(provider_id).await?;
+        self.nonce_manager(provider_id)
+            .release(instance_id.plugin, chain_id, address, nonce)
+            .map_err(RpcError::Custom)
+    }
 
-        let resp = global::Ping
-            .call_async(plugin, ())
-            .await
-            .context(format!("Error calling Ping on plugin {}", plugin_id))?;
-        Ok(resp)
+    /// The nonces leased for transactions sent through `provider_id`. The
+    /// simulation fork has its own, so simulated transactions don't advance
+    /// the live chain's nonces.
+    fn nonce_manager(&self, provider_id: EthProviderId) -> &NonceManager {
+        if self.simulation.fork() == Some(provider_id) {
+            self.simulation.nonces()
+        } else {
+            &self.nonces
+        }
     }
 
-    pub fn log_event(&self, event: &str, plugin: Option<&str>) {
-        let mut log = self.events.lock().unwrap();
-        log.push(Event {
-            id: Uuid::new_v4(),
-            message: event.to_string(),
-            level: host::NotifyLevel::Trace,
-            timestamp: chrono::Local::now(),
-            plugin: plugin.map(|p| p.to_string()),
-        });
+    /// The chain nonces leased through `provider_id` are tracked under.
+    async fn nonce_chain_id(&self, provider_id: EthProviderId) -> Result<caip::ChainId, RpcError> {
+        let chain_id = self.eth_provider_chain_id(provider_id).await?;
+        let chain_id = u64::try_from(chain_id).context("Chain ID out of range")?;
+        Ok(caip::ChainId::Evm(Some(chain_id)))
     }
-}
 
-// TODO: Create a macro for these. It seens extremely possible, if a little
-// fiddly.
-impl Host {
-    pub async fn ping(&self, _instance_id: &InstanceId, _params: ()) -> Result<String, RpcError> {
-        Ok("Pong from host".to_string())
+    /// Gets an account's balance of a native or ERC20 asset.
+    async fn deposit_balance(
+        &self,
+        provider_id: EthProviderId,
+        account: &AccountId,
+        asset: &AssetId,
+    ) -> Result<U256, RpcError> {
+        let owner = account.try_into_evm_address().map_err(RpcError::custom)?;
+        match &asset.asset {
+            caip::AssetType::Slip44(_) => {
+                self.eth_provider_get_balance((provider_id, owner, BlockId::latest()))
+                    .await
+            }
+            caip::AssetType::Erc20(token) => {
+                let data = Erc20::balanceOfCall { owner }.abi_encode();
+                let tx = TransactionRequest::default()
+                    .to(*token)
+                    .input(Bytes::from(data).into());
+                let output = self
+                    .eth_provider_call((provider_id, tx, BlockId::latest(), None, None))
+                    .await?;
+                let balance = Erc20::balanceOfCall::abi_decode_returns(&output)
+                    .context("Invalid balanceOf response")?;
+                Ok(balance)
+            }
+            _ => Err(RpcError::custom(format!(
+                "Deposits of {} can't be watched",
+                asset
+            ))),
+        }
     }
 
-    pub async fn register_entity(
+    pub async fn secret_set(
         &self,
         instance_id: &InstanceId,
-        domain: Domain,
-    ) -> Result<EntityId, RpcError> {
-        let entity_id: EntityId = match domain {
-            Domain::EthProvider => EthProviderId::new().into(),
-            Domain::Page => PageId::new().into(),
-            Domain::Vault => VaultId::new().into(),
-            Domain::Coordinator => CoordinatorId::new().into(),
-        };
+        (name, value): (String, Vec<u8>),
+    ) -> Result<(), RpcError> {
+        self.ensure_keystore_unlocked(instance_id.plugin).await?;
+        self.keystore
+            .set(instance_id.plugin, &name, &value)
+            .map_err(|e| RpcError::Custom(e.to_string()))?;
+        self.notify_observers();
+        Ok(())
+    }
 
-        let mut entities = self.entities.lock().unwrap();
-        entities.insert(entity_id, instance_id.plugin);
-        Ok(entity_id)
+    pub async fn secret_get(
+        &self,
+        instance_id: &InstanceId,
+        name: String,
+    ) -> Result<Option<Vec<u8>>, RpcError> {
+        self.ensure_keystore_unlocked(instance_id.plugin).await?;
+        self.keystore
+            .get(instance_id.plugin, &name)
+            .map_err(|e| RpcError::Custom(e.to_string()))
     }
 
-    pub async fn request_eth_provider(
+    pub async fn secret_delete(
         &self,
         instance_id: &InstanceId,
-        chain_id: caip::ChainId,
-    ) -> Result<EthProviderId, RpcError> {
-        let request = UserRequest::EthProviderSelection {
+        name: String,
+    ) -> Result<(), RpcError> {
+        self.keystore.delete(instance_id.plugin, &name);
+        self.notify_observers();
+        Ok(())
+    }
+
+    /// Waits for the user to unlock the keystore if it's locked.
+    async fn ensure_keystore_unlocked(&self, plugin_id: PluginId) -> Result<(), RpcError> {
+        if self.keystore.is_unlocked() {
+            return Ok(());
+        }
+
+        let request = UserRequest::KeystoreUnlock {
             id: Uuid::new_v4(),
-            plugin_id: instance_id.plugin,
-            chain_id,
+            plugin_id,
+            new_password: !self.keystore.is_initialized(),
         };
-
         self.create_user_request(request, |resp| match resp {
-            UserResponse::EthProvider(selected_provider) => Some(selected_provider),
+            UserResponse::KeystoreUnlocked => Some(()),
             _ => None,
         })
         .await
     }
 
-    pub async fn request_vault(
+    pub async fn notify(
         &self,
         instance_id: &InstanceId,
-        _params: (),
-    ) -> Result<VaultId, RpcError> {
-        let request = UserRequest::VaultSelection {
-            id: Uuid::new_v4(),
-            plugin_id: instance_id.plugin,
-        };
+        params: (host::NotifyLevel, String),
+    ) -> Result<(), RpcError> {
+        {
+            let (level, message) = params;
 
-        self.create_user_request(request, |resp| match resp {
-            UserResponse::Vault(selected_vault) => Some(selected_vault),
-            _ => None,
-        })
-        .await
+            let plugin_name = match self.get_plugin(&instance_id.plugin) {
+                Some(plugin) => plugin.name().to_string(),
+                None => "Unknown Plugin".to_string(),
+            };
+
+            let plugin = Some((instance_id.plugin, plugin_name));
+            self.events
+                .push(level, plugin, Some(host::Notify::NAME), message);
+        }
+
+        self.notify_observers();
+        Ok(())
     }
 
-    pub async fn request_coordinator(
+    pub async fn show_toast(
         &self,
         instance_id: &InstanceId,
-        _params: (),
-    ) -> Result<CoordinatorId, RpcError> {
-        let request = UserRequest::CoordinatorSelection {
-            id: Uuid::new_v4(),
-            plugin_id: instance_id.plugin,
+        toast: host::Toast,
+    ) -> Result<(), RpcError> {
+        let plugin_name = match self.get_plugin(&instance_id.plugin) {
+            Some(plugin) => plugin.name().to_string(),
+            None => "Unknown Plugin".to_string(),
         };
-
-        self.create_user_request(request, |resp| match resp {
-            UserResponse::Coordinator(selected_coordinator) => Some(selected_coordinator),
-            _ => None,
-        })
-        .await
+        //? Traced so the event log keeps a record without toasting it again
+        let plugin = Some((instance_id.plugin, plugin_name));
+        let message = toast.message.clone();
+        self.events.push(
+            host::NotifyLevel::Trace,
+            plugin,
+            Some(host::ShowToast::NAME),
+            message,
+        );
+
+        self.toasts
+            .lock()
+            .unwrap()
+            .push((instance_id.plugin, toast));
+        self.notify_observers();
+        Ok(())
     }
 
-    pub async fn fetch(
+    pub async fn notification_push(
         &self,
-        _instance_id: &InstanceId,
-        req: host::Request,
-    ) -> Result<Result<Vec<u8>, String>, RpcError> {
-        let mut headers = reqwest::header::HeaderMap::new();
-        for (key, value) in req.headers.iter() {
-            if let (Ok(name), Ok(val)) = (
-                reqwest::header::HeaderName::from_bytes(key.as_bytes()),
-                reqwest::header::HeaderValue::from_bytes(value),
-            ) {
-                headers.insert(name, val);
+        instance_id: &InstanceId,
+        notification: notification::Notification,
+    ) -> Result<(), RpcError> {
+        //? Plugins may only route actions to their own pages
+        for action in &notification.actions {
+            if self.get_entity_plugin_id(action.page_id) != Some(instance_id.plugin) {
+                return Err(RpcError::custom(format!(
+                    "Notification action targets page {} not owned by plugin",
+                    action.page_id
+                )));
             }
         }
 
-        let client = reqwest::Client::new();
-        let body = req.body.clone().unwrap_or_default();
-        let request = match req.method.to_lowercase().as_str() {
-            "get" => client.get(req.url.clone()).headers(headers),
-            "post" => client
-                .post(req.url.clone())
-                .headers(headers)
-                .body(body.clone()),
-            _ => {
-                warn!("Unsupported HTTP method: {}", req.method);
-                return Ok(Err("Unsupported HTTP method".to_string()));
-            }
+        let plugin_name = match self.get_plugin(&instance_id.plugin) {
+            Some(plugin) => plugin.name().to_string(),
+            None => "Unknown Plugin".to_string(),
         };
 
-        // TODO: Handle errors properly
-        let resp = request
-            .send()
-            .await
-            .context("Failed to send HTTP request")?;
-        let bytes = resp
-            .bytes()
-            .await
-            .context("Failed to read response bytes")?;
-        Ok(Ok(bytes.to_vec()))
-    }
-
-    pub async fn notify(
-        &self,
-        instance_id: &InstanceId,
-        params: (host::NotifyLevel, String),
-    ) -> Result<(), RpcError> {
         {
-            let (level, message) = params;
+            let mut notifications = self.notifications.lock().unwrap();
+            if let Some(key) = &notification.dedupe_key {
+                notifications.retain(|n| {
+                    n.plugin_id != instance_id.plugin
+                        || n.notification.dedupe_key.as_ref() != Some(key)
+                });
+            }
 
-            let plugin_name = match self.get_plugin(&instance_id.plugin) {
-                Some(plugin) => plugin.name().to_string(),
-                None => "Unknown Plugin".to_string(),
-            };
+            self.events.push(
+                notification.level.clone(),
+                Some((instance_id.plugin, plugin_name.clone())),
+                Some(notification::Push::NAME),
+                notification.message.clone(),
+            );
 
-            self.events.lock().unwrap().push(Event {
+            notifications.push(NotificationEntry {
                 id: Uuid::new_v4(),
-                message,
-                level,
+                plugin_id: instance_id.plugin,
+                plugin: plugin_name,
+                notification,
                 timestamp: chrono::Local::now(),
-                plugin: Some(plugin_name),
             });
         }
 
@@ -588,6 +3425,28 @@ impl Host {
         Ok(())
     }
 
+    pub async fn notification_dismiss(
+        &self,
+        instance_id: &InstanceId,
+        dedupe_key: String,
+    ) -> Result<(), RpcError> {
+        self.notifications.lock().unwrap().retain(|n| {
+            n.plugin_id != instance_id.plugin
+                || n.notification.dedupe_key.as_ref() != Some(&dedupe_key)
+        });
+
+        self.notify_observers();
+        Ok(())
+    }
+
+    pub async fn decode_error(
+        &self,
+        _instance_id: &InstanceId,
+        data: Bytes,
+    ) -> Result<host::DecodedError, RpcError> {
+        Ok(abi::decode_error(&data))
+    }
+
     pub async fn read_key(
         &self,
         instance_id: &InstanceId,
@@ -674,12 +3533,139 @@ impl Host {
                 _ => return Ok(Err(state::SetError::KeyNotLocked)),
             }
         }
+        self.check_state_write(state::SetKey::NAME, &state_key)?;
 
         let mut state = self.state.lock().unwrap();
+        let previous = state
+            .get(&state_key)
+            .map_or(0, |v| state_key.1.len() + v.len());
+        let used = state_usage(&state, &instance_id.plugin) - previous;
+        if used + state_key.1.len() + value.len() > self.get_state_quota(&instance_id.plugin) {
+            return Ok(Err(state::SetError::QuotaExceeded));
+        }
+
+        self.dirty_state.lock().unwrap().insert(state_key.clone());
         state.insert(state_key, value);
         Ok(Ok(()))
     }
 
+    pub async fn delete_key(
+        &self,
+        instance_id: &InstanceId,
+        key: String,
+    ) -> Result<Result<(), state::SetError>, RpcError> {
+        let state_key = (instance_id.plugin, key);
+
+        {
+            let locks = self.locks.lock().unwrap();
+            match locks.get(&state_key) {
+                Some((holder, _)) if holder == instance_id => {}
+                _ => return Ok(Err(state::SetError::KeyNotLocked)),
+            }
+        }
+        self.check_state_write(state::DeleteKey::NAME, &state_key)?;
+
+        self.state.lock().unwrap().remove(&state_key);
+        self.dirty_state.lock().unwrap().insert(state_key);
+        Ok(Ok(()))
+    }
+
+    /// Takes a write to a locked key from the plugin's rate limit. A rejected
+    /// write also releases the lock, so other instances waiting on the key
+    /// aren't held up by a plugin that's been limited.
+    fn check_state_write(&self, method: &str, state_key: &StateKey) -> Result<(), RpcError> {
+        if self.rate_limiter.check(state_key.0, method) {
+            return Ok(());
+        }
+
+        warn!(
+            "Plugin {} exceeded the rate limit for {}",
+            state_key.0, method
+        );
+        if let Some((_, event)) = self.locks.lock().unwrap().remove(state_key) {
+            event.notify(usize::MAX);
+        }
+        let message = format!("Rate limit exceeded for {}", method);
+        Err(DomainError::new(ErrorCode::RATE_LIMITED, message).into())
+    }
+
+    pub async fn list_keys(
+        &self,
+        instance_id: &InstanceId,
+        prefix: String,
+    ) -> Result<Vec<String>, RpcError> {
+        let state = self.state.lock().unwrap();
+        let mut keys: Vec<String> = state
+            .keys()
+            .filter(|(id, key)| *id == instance_id.plugin && key.starts_with(&prefix))
+            .map(|(_, key)| key.clone())
+            .collect();
+        keys.sort();
+        Ok(keys)
+    }
+
+    pub async fn get_usage(
+        &self,
+        instance_id: &InstanceId,
+        _params: (),
+    ) -> Result<state::Usage, RpcError> {
+        Ok(self.get_state_usage(&instance_id.plugin))
+    }
+
+    /// Attaches a durable store for plugin state. Stored entries are loaded
+    /// unless the host already holds the key, and everything the host holds
+    /// is written out on the next `flush_state`.
+    pub async fn attach_state_store(&self, store: Arc<dyn StateStore>) -> Result<(), StoreError> {
+        let stored = store.load().await?;
+        {
+            let mut state = self.state.lock().unwrap();
+            self.dirty_state
+                .lock()
+                .unwrap()
+                .extend(state.keys().cloned());
+            for (key, value) in stored {
+                state.entry(key).or_insert(value);
+            }
+        }
+
+        *self.state_store.lock().unwrap() = Some(store);
+        self.notify_observers();
+        Ok(())
+    }
+
+    pub fn has_state_store(&self) -> bool {
+        self.state_store.lock().unwrap().is_some()
+    }
+
+    /// Writes state changed since the last flush to the attached store. Failed
+    /// writes are retried on the next flush. The frontend is expected to call
+    /// this periodically.
+    pub async fn flush_state(&self) {
+        let Some(store) = self.state_store.lock().unwrap().clone() else {
+            return;
+        };
+
+        let keys: Vec<StateKey> = self.dirty_state.lock().unwrap().drain().collect();
+        if keys.is_empty() {
+            return;
+        }
+
+        let writes: Vec<StateWrite> = {
+            let state = self.state.lock().unwrap();
+            keys.iter()
+                .map(|key| match state.get(key) {
+                    Some(value) => StateWrite::Put(key.clone(), value.clone()),
+                    None => StateWrite::Delete(key.clone()),
+                })
+                .collect()
+        };
+
+        if let Err(e) = store.apply(writes).await {
+            warn!("Failed to persist plugin state: {}", e);
+            self.dirty_state.lock().unwrap().extend(keys);
+        }
+    }
+
     pub async fn unlock_key(
         &self,
         instance_id: &InstanceId,
@@ -721,12 +3707,59 @@ impl Host {
 
     pub async fn set_interface(
         &self,
-        _instance_id: &InstanceId,
+        instance_id: &InstanceId,
         params: (PageId, Component),
     ) -> Result<(), RpcError> {
         let (page_id, component) = params;
+        self.check_page_owner(instance_id, page_id)?;
+        //? Links open their target without going through `host_navigate`, so
+        //? they're held to the same rule
+        for target in component.link_targets() {
+            self.check_page_owner(instance_id, target)?;
+        }
+
+        let shows_progress = component.shows_progress();
         self.interfaces.lock().unwrap().insert(page_id, component);
         self.notify_observers();
+        //? Plugins showing progress set their page several times in one
+        //? handler, so let the frontend draw each update before they carry on
+        if shows_progress {
+            time::yield_now().await;
+        }
+        Ok(())
+    }
+
+    pub async fn set_page_title(
+        &self,
+        instance_id: &InstanceId,
+        params: (PageId, String),
+    ) -> Result<(), RpcError> {
+        let (page_id, title) = params;
+        self.check_page_owner(instance_id, page_id)?;
+
+        self.page_titles.lock().unwrap().insert(page_id, title);
+        self.notify_observers();
+        Ok(())
+    }
+
+    pub async fn navigate(
+        &self,
+        instance_id: &InstanceId,
+        page_id: PageId,
+    ) -> Result<(), RpcError> {
+        self.check_page_owner(instance_id, page_id)?;
+        self.push_page(page_id);
+        Ok(())
+    }
+
+    fn check_page_owner(&self, instance_id: &InstanceId, page_id: PageId) -> Result<(), RpcError> {
+        let owner = self.entities.lock().unwrap().get(&page_id.into()).copied();
+        if owner != Some(instance_id.plugin) {
+            return Err(RpcError::Custom(format!(
+                "Page {} is not owned by this plugin",
+                page_id
+            )));
+        }
         Ok(())
     }
 
@@ -736,8 +3769,8 @@ impl Host {
     ) -> Result<Vec<(AssetId, U256)>, RpcError> {
         let plugin = self.get_entity_plugin_error(vault_id)?;
 
-        let balance = vault::GetAssets
-            .call_async(plugin, vault_id)
+        let balance = self
+            .call_plugin(plugin, vault::GetAssets, vault_id)
             .await
             .context("Error calling BalanceOf")?;
         Ok(balance)
@@ -745,15 +3778,44 @@ impl Host {
 
     pub async fn vault_withdraw(
         &self,
+        instance_id: &InstanceId,
         params: (VaultId, AccountId, AssetId, U256),
     ) -> Result<(), RpcError> {
-        let (vault_id, to, asset, amount) = params;
+        self.log_call(vault::Withdraw::NAME, instance_id.plugin);
+        let (vault_id, to, asset, amount) = params.clone();
         let plugin = self.get_entity_plugin_error(vault_id)?;
 
-        vault::Withdraw
-            .call_async(plugin, (vault_id, to, asset, amount))
-            .await
-            .context("Error calling Withdraw")?;
+        let spend = Some((asset.clone(), amount));
+        let authorized = self
+            .authorize_call(
+                instance_id.plugin,
+                vault_id.into(),
+                vault::Withdraw::NAME,
+                spend,
+            )
+            .await;
+        let result = match authorized {
+            Ok(()) => self
+                .call_plugin(
+                    plugin,
+                    vault::Withdraw,
+                    (vault_id, to.clone(), asset.clone(), amount),
+                )
+                .await
+                .context("Error calling Withdraw"),
+            Err(e) => Err(e),
+        };
+        self.audit(instance_id.plugin, vault::Withdraw::NAME, &params, &result);
+        result?;
+
+        self.vault_history.lock().unwrap().push(VaultHistoryEntry {
+            id: Uuid::new_v4(),
+            vault_id,
+            asset,
+            amount,
+            counterparty: to,
+            timestamp: chrono::Local::now(),
+        });
         Ok(())
     }
 
@@ -764,18 +3826,77 @@ impl Host {
         let (vault_id, asset) = params;
         let plugin = self.get_entity_plugin_error(vault_id)?;
 
-        let result = vault::GetDepositAddress
-            .call_async(plugin, (vault_id, asset))
-            .await
-            .context("Error calling GetDepositAddress")?;
-        Ok(result)
+        let result = self
+            .call_plugin(
+                plugin.clone(),
+                vault::GetDepositAddress,
+                (vault_id, asset.clone()),
+            )
+            .await
+            .context("Error calling GetDepositAddress")?;
+
+        //? Depositors can't be trusted to report their deposits, so the host
+        //? watches every address it hands out
+        self.track_deposit_address(plugin.id(), vault_id, &result, asset)
+            .await;
+        Ok(result)
+    }
+
+    /// Starts watching a deposit address through its chain's default eth
+    /// provider, unless the vault already watches it. Addresses that can't be
+    /// watched are skipped.
+    async fn track_deposit_address(
+        &self,
+        plugin_id: PluginId,
+        vault_id: VaultId,
+        account: &AccountId,
+        asset: AssetId,
+    ) {
+        let watched = |w: &DepositWatch| {
+            w.plugin_id == plugin_id
+                && w.vault_id == vault_id
+                && &w.account == account
+                && w.asset == asset
+        };
+        if self.deposit_watches.lock().unwrap().iter().any(watched) {
+            return;
+        }
+
+        let Some(provider_id) = self.get_default_eth_provider(account.chain_id()) else {
+            info!(
+                "No default eth provider for {}, not watching {}",
+                account.chain_id(),
+                account
+            );
+            return;
+        };
+        let balance = match self.deposit_balance(provider_id, account, &asset).await {
+            Ok(balance) => balance,
+            Err(e) => {
+                info!("Not watching deposits to {}: {}", account, e);
+                return;
+            }
+        };
+
+        //? The address may have been watched while reading its balance
+        let mut watches = self.deposit_watches.lock().unwrap();
+        if watches.iter().any(watched) {
+            return;
+        }
+        watches.push(DepositWatch {
+            plugin_id,
+            vault_id,
+            provider_id,
+            account: account.clone(),
+            asset,
+            balance,
+        });
     }
 
     pub async fn page_on_load(&self, page_id: PageId) -> Result<(), RpcError> {
         let plugin = self.get_entity_plugin_error(page_id)?;
 
-        page::OnLoad
-            .call_async(plugin, page_id)
+        self.call_plugin(plugin, page::OnLoad, page_id)
             .await
             .context("Error calling OnPageLoad")?;
         Ok(())
@@ -785,8 +3906,7 @@ impl Host {
         let (page_id, event) = params;
         let plugin = self.get_entity_plugin_error(page_id)?;
 
-        page::OnUpdate
-            .call_async(plugin, (page_id, event))
+        self.call_plugin(plugin, page::OnUpdate, (page_id, event))
             .await
             .context("Error calling OnPageUpdate")?;
         Ok(())
@@ -796,12 +3916,16 @@ impl Host {
         &self,
         provider_id: EthProviderId,
     ) -> Result<U256, RpcError> {
+        if let Some(chain_id) = self.eth_cache.chain_id(&provider_id) {
+            return Ok(chain_id);
+        }
         let plugin = self.get_entity_plugin_error(provider_id)?;
 
-        let chain_id = eth::ChainId
-            .call_async(plugin, provider_id)
+        let chain_id = self
+            .call_plugin(plugin, eth::ChainId, provider_id)
             .await
             .context("Error calling ChainId")?;
+        self.eth_cache.set_chain_id(provider_id, chain_id);
         Ok(chain_id)
     }
 
@@ -809,23 +3933,29 @@ impl Host {
         &self,
         provider_id: EthProviderId,
     ) -> Result<u64, RpcError> {
+        let provider_id = self.simulation_target(provider_id).await;
+        if let Some(block_number) = self.eth_cache.block_number(&provider_id) {
+            return Ok(block_number);
+        }
         let plugin = self.get_entity_plugin_error(provider_id)?;
 
-        let block_number = eth::BlockNumber
-            .call_async(plugin, provider_id)
+        let block_number = self
+            .call_plugin(plugin, eth::BlockNumber, provider_id)
             .await
             .context("Error calling BlockNumber")?;
+        self.eth_cache.set_block_number(provider_id, block_number);
         Ok(block_number)
     }
 
     pub async fn eth_provider_call(
         &self,
-        params: <eth::Call as RpcMethod>::Params,
+        mut params: <eth::Call as RpcMethod>::Params,
     ) -> Result<<eth::Call as RpcMethod>::Output, RpcError> {
+        params.0 = self.simulation_target(params.0).await;
         let plugin = self.get_entity_plugin_error(params.0)?;
 
-        let resp = eth::Call
-            .call_async(plugin, params)
+        let resp = self
+            .call_plugin(plugin, eth::Call, params)
             .await
             .context("Error calling Call")?;
         Ok(resp)
@@ -833,12 +3963,13 @@ impl Host {
 
     pub async fn eth_provider_get_balance(
         &self,
-        params: <eth::GetBalance as RpcMethod>::Params,
+        mut params: <eth::GetBalance as RpcMethod>::Params,
     ) -> Result<<eth::GetBalance as RpcMethod>::Output, RpcError> {
+        params.0 = self.simulation_target(params.0).await;
         let plugin = self.get_entity_plugin_error(params.0)?;
 
-        let resp = eth::GetBalance
-            .call_async(plugin, params)
+        let resp = self
+            .call_plugin(plugin, eth::GetBalance, params)
             .await
             .context("Error calling GetBalance")?;
         Ok(resp)
@@ -848,23 +3979,29 @@ impl Host {
         &self,
         provider_id: EthProviderId,
     ) -> Result<u128, RpcError> {
+        let provider_id = self.simulation_target(provider_id).await;
+        if let Some(gas_price) = self.eth_cache.gas_price(&provider_id) {
+            return Ok(gas_price);
+        }
         let plugin = self.get_entity_plugin_error(provider_id)?;
 
-        let gas_price = eth::GasPrice
-            .call_async(plugin, provider_id)
+        let gas_price = self
+            .call_plugin(plugin, eth::GasPrice, provider_id)
             .await
             .context("Error calling GasPrice")?;
+        self.eth_cache.set_gas_price(provider_id, gas_price);
         Ok(gas_price)
     }
 
     pub async fn eth_transaction_count(
         &self,
-        params: <eth::GetTransactionCount as RpcMethod>::Params,
+        mut params: <eth::GetTransactionCount as RpcMethod>::Params,
     ) -> Result<<eth::GetTransactionCount as RpcMethod>::Output, RpcError> {
+        params.0 = self.simulation_target(params.0).await;
         let plugin = self.get_entity_plugin_error(params.0)?;
 
-        let resp = eth::GetTransactionCount
-            .call_async(plugin, params)
+        let resp = self
+            .call_plugin(plugin, eth::GetTransactionCount, params)
             .await
             .context("Error calling GetTransactionCount")?;
         Ok(resp)
@@ -872,25 +4009,91 @@ impl Host {
 
     pub async fn eth_send_raw_transaction(
         &self,
+        instance_id: &InstanceId,
         params: <eth::SendRawTransaction as RpcMethod>::Params,
     ) -> Result<<eth::SendRawTransaction as RpcMethod>::Output, RpcError> {
+        self.log_call(eth::SendRawTransaction::NAME, instance_id.plugin);
+        if let Some(fork) = self.simulation.fork() {
+            return self
+                .simulate_raw_transaction(instance_id.plugin, fork, params)
+                .await;
+        }
         let plugin = self.get_entity_plugin_error(params.0)?;
 
-        let tx_hash = eth::SendRawTransaction
-            .call_async(plugin, params)
+        let authorized = self
+            .authorize_call(
+                instance_id.plugin,
+                params.0.into(),
+                eth::SendRawTransaction::NAME,
+                None,
+            )
+            .await;
+        let result = match authorized {
+            Ok(()) => self
+                .call_plugin(plugin, eth::SendRawTransaction, params.clone())
+                .await
+                .context("Error calling SendRawTransaction"),
+            Err(e) => Err(e),
+        };
+        if let Ok(tx_hash) = &result {
+            self.transactions
+                .track(*tx_hash, instance_id.plugin, params.0);
+        }
+        self.audit(
+            instance_id.plugin,
+            eth::SendRawTransaction::NAME,
+            &params,
+            &result,
+        );
+        result
+    }
+
+    /// Applies a transaction to the simulation fork instead of broadcasting
+    /// it. Fails rather than broadcast if the fork serves a different chain.
+    async fn simulate_raw_transaction(
+        &self,
+        plugin_id: PluginId,
+        fork: EthProviderId,
+        params: <eth::SendRawTransaction as RpcMethod>::Params,
+    ) -> Result<<eth::SendRawTransaction as RpcMethod>::Output, RpcError> {
+        let (provider_id, raw_tx) = params;
+        if self.simulation_target(provider_id).await != fork {
+            return Err(RpcError::Custom(
+                "Simulation mode is on, but the simulation fork serves a different chain"
+                    .to_string(),
+            ));
+        }
+        let plugin = self.get_entity_plugin_error(fork)?;
+
+        let hash = self
+            .call_plugin(plugin.clone(), eth::SendRawTransaction, (fork, raw_tx))
             .await
             .context("Error calling SendRawTransaction")?;
-        Ok(tx_hash)
+        let receipt = self
+            .call_plugin(plugin, eth::GetTransactionReceipt, (fork, hash))
+            .await
+            .ok();
+        info!("Simulated transaction {} from plugin {}", hash, plugin_id);
+        self.simulation.record(SimulatedTransaction {
+            hash,
+            plugin_id,
+            provider_id,
+            submitted: chrono::Local::now(),
+            receipt,
+        });
+        self.notify_observers();
+        Ok(hash)
     }
 
     pub async fn eth_estimate_gas(
         &self,
-        params: <eth::EstimateGas as RpcMethod>::Params,
+        mut params: <eth::EstimateGas as RpcMethod>::Params,
     ) -> Result<<eth::EstimateGas as RpcMethod>::Output, RpcError> {
+        params.0 = self.simulation_target(params.0).await;
         let plugin = self.get_entity_plugin_error(params.0)?;
 
-        let gas_estimate = eth::EstimateGas
-            .call_async(plugin, params)
+        let gas_estimate = self
+            .call_plugin(plugin, eth::EstimateGas, params)
             .await
             .context("Error calling EstimateGas")?;
         Ok(gas_estimate)
@@ -898,12 +4101,13 @@ impl Host {
 
     pub async fn eth_get_transaction_receipt(
         &self,
-        params: <eth::GetTransactionReceipt as RpcMethod>::Params,
+        mut params: <eth::GetTransactionReceipt as RpcMethod>::Params,
     ) -> Result<<eth::GetTransactionReceipt as RpcMethod>::Output, RpcError> {
+        params.0 = self.simulation_target(params.0).await;
         let plugin = self.get_entity_plugin_error(params.0)?;
 
-        let receipt = eth::GetTransactionReceipt
-            .call_async(plugin, params)
+        let receipt = self
+            .call_plugin(plugin, eth::GetTransactionReceipt, params)
             .await
             .context("Error calling GetTransactionReceipt")?;
         Ok(receipt)
@@ -911,12 +4115,13 @@ impl Host {
 
     pub async fn eth_get_block(
         &self,
-        params: <eth::GetBlock as RpcMethod>::Params,
+        mut params: <eth::GetBlock as RpcMethod>::Params,
     ) -> Result<<eth::GetBlock as RpcMethod>::Output, RpcError> {
+        params.0 = self.simulation_target(params.0).await;
         let plugin = self.get_entity_plugin_error(params.0)?;
 
-        let block = eth::GetBlock
-            .call_async(plugin, params)
+        let block = self
+            .call_plugin(plugin, eth::GetBlock, params)
             .await
             .context("Error calling GetBlock")?;
         Ok(block)
@@ -926,78 +4131,625 @@ impl Host {
         &self,
         params: <eth::GetCode as RpcMethod>::Params,
     ) -> Result<<eth::GetCode as RpcMethod>::Output, RpcError> {
-        let plugin = self.get_entity_plugin_error(params.0)?;
+        let (provider_id, address, block) = params;
+        let provider_id = self.simulation_target(provider_id).await;
+        if let Some(code) = self.eth_cache.code(provider_id, address, block) {
+            return Ok(code);
+        }
+        let plugin = self.get_entity_plugin_error(provider_id)?;
 
-        let code = eth::GetCode
-            .call_async(plugin, params)
+        let code = self
+            .call_plugin(plugin, eth::GetCode, (provider_id, address, block))
             .await
             .context("Error calling GetCode")?;
+        self.eth_cache
+            .set_code(provider_id, address, block, code.clone());
         Ok(code)
     }
 
     pub async fn eth_get_storage_at(
         &self,
-        params: <eth::GetStorageAt as RpcMethod>::Params,
+        mut params: <eth::GetStorageAt as RpcMethod>::Params,
     ) -> Result<<eth::GetStorageAt as RpcMethod>::Output, RpcError> {
+        params.0 = self.simulation_target(params.0).await;
         let plugin = self.get_entity_plugin_error(params.0)?;
 
-        let storage = eth::GetStorageAt
-            .call_async(plugin, params)
+        let storage = self
+            .call_plugin(plugin, eth::GetStorageAt, params)
             .await
             .context("Error calling GetStorageAt")?;
         Ok(storage)
     }
 
+    pub async fn eth_max_priority_fee_per_gas(
+        &self,
+        provider_id: EthProviderId,
+    ) -> Result<u128, RpcError> {
+        let provider_id = self.simulation_target(provider_id).await;
+        let plugin = self.get_entity_plugin_error(provider_id)?;
+
+        let fee = self
+            .call_plugin(plugin, eth::MaxPriorityFeePerGas, provider_id)
+            .await
+            .context("Error calling MaxPriorityFeePerGas")?;
+        Ok(fee)
+    }
+
+    pub async fn eth_blob_base_fee(&self, provider_id: EthProviderId) -> Result<u128, RpcError> {
+        let provider_id = self.simulation_target(provider_id).await;
+        let plugin = self.get_entity_plugin_error(provider_id)?;
+
+        let fee = self
+            .call_plugin(plugin, eth::BlobBaseFee, provider_id)
+            .await
+            .context("Error calling BlobBaseFee")?;
+        Ok(fee)
+    }
+
+    pub async fn eth_create_access_list(
+        &self,
+        mut params: <eth::CreateAccessList as RpcMethod>::Params,
+    ) -> Result<<eth::CreateAccessList as RpcMethod>::Output, RpcError> {
+        params.0 = self.simulation_target(params.0).await;
+        let plugin = self.get_entity_plugin_error(params.0)?;
+
+        let access_list = self
+            .call_plugin(plugin, eth::CreateAccessList, params)
+            .await
+            .context("Error calling CreateAccessList")?;
+        Ok(access_list)
+    }
+
+    pub async fn eth_get_proof(
+        &self,
+        mut params: <eth::GetProof as RpcMethod>::Params,
+    ) -> Result<<eth::GetProof as RpcMethod>::Output, RpcError> {
+        params.0 = self.simulation_target(params.0).await;
+        let plugin = self.get_entity_plugin_error(params.0)?;
+
+        let proof = self
+            .call_plugin(plugin, eth::GetProof, params)
+            .await
+            .context("Error calling GetProof")?;
+        Ok(proof)
+    }
+
+    pub async fn sol_get_balance(
+        &self,
+        params: <sol::GetBalance as RpcMethod>::Params,
+    ) -> Result<<sol::GetBalance as RpcMethod>::Output, RpcError> {
+        let plugin = self.get_entity_plugin_error(params.0)?;
+
+        let balance = self
+            .call_plugin(plugin, sol::GetBalance, params)
+            .await
+            .context("Error calling GetBalance")?;
+        Ok(balance)
+    }
+
+    pub async fn sol_get_account_info(
+        &self,
+        params: <sol::GetAccountInfo as RpcMethod>::Params,
+    ) -> Result<<sol::GetAccountInfo as RpcMethod>::Output, RpcError> {
+        let plugin = self.get_entity_plugin_error(params.0)?;
+
+        let account = self
+            .call_plugin(plugin, sol::GetAccountInfo, params)
+            .await
+            .context("Error calling GetAccountInfo")?;
+        Ok(account)
+    }
+
+    pub async fn sol_get_latest_blockhash(
+        &self,
+        params: <sol::GetLatestBlockhash as RpcMethod>::Params,
+    ) -> Result<<sol::GetLatestBlockhash as RpcMethod>::Output, RpcError> {
+        let plugin = self.get_entity_plugin_error(params.0)?;
+
+        let blockhash = self
+            .call_plugin(plugin, sol::GetLatestBlockhash, params)
+            .await
+            .context("Error calling GetLatestBlockhash")?;
+        Ok(blockhash)
+    }
+
+    pub async fn sol_send_transaction(
+        &self,
+        instance_id: &InstanceId,
+        params: <sol::SendTransaction as RpcMethod>::Params,
+    ) -> Result<<sol::SendTransaction as RpcMethod>::Output, RpcError> {
+        self.log_call(sol::SendTransaction::NAME, instance_id.plugin);
+        self.reject_in_simulation(sol::SendTransaction::NAME)?;
+        let plugin = self.get_entity_plugin_error(params.0)?;
+        self.authorize_call(
+            instance_id.plugin,
+            params.0.into(),
+            sol::SendTransaction::NAME,
+            None,
+        )
+        .await?;
+
+        let signature = self
+            .call_plugin(plugin, sol::SendTransaction, params)
+            .await
+            .context("Error calling SendTransaction")?;
+        Ok(signature)
+    }
+
+    pub async fn btc_get_utxos(
+        &self,
+        params: <btc::GetUtxos as RpcMethod>::Params,
+    ) -> Result<<btc::GetUtxos as RpcMethod>::Output, RpcError> {
+        let plugin = self.get_entity_plugin_error(params.0)?;
+
+        let utxos = self
+            .call_plugin(plugin, btc::GetUtxos, params)
+            .await
+            .context("Error calling GetUtxos")?;
+        Ok(utxos)
+    }
+
+    pub async fn btc_broadcast_tx(
+        &self,
+        instance_id: &InstanceId,
+        params: <btc::BroadcastTx as RpcMethod>::Params,
+    ) -> Result<<btc::BroadcastTx as RpcMethod>::Output, RpcError> {
+        self.log_call(btc::BroadcastTx::NAME, instance_id.plugin);
+        self.reject_in_simulation(btc::BroadcastTx::NAME)?;
+        let plugin = self.get_entity_plugin_error(params.0)?;
+        self.authorize_call(
+            instance_id.plugin,
+            params.0.into(),
+            btc::BroadcastTx::NAME,
+            None,
+        )
+        .await?;
+
+        let txid = self
+            .call_plugin(plugin, btc::BroadcastTx, params)
+            .await
+            .context("Error calling BroadcastTx")?;
+        Ok(txid)
+    }
+
+    pub async fn btc_estimate_fee(
+        &self,
+        params: <btc::EstimateFee as RpcMethod>::Params,
+    ) -> Result<<btc::EstimateFee as RpcMethod>::Output, RpcError> {
+        let plugin = self.get_entity_plugin_error(params.0)?;
+
+        let fee_rate = self
+            .call_plugin(plugin, btc::EstimateFee, params)
+            .await
+            .context("Error calling EstimateFee")?;
+        Ok(fee_rate)
+    }
+
+    pub async fn btc_get_address_history(
+        &self,
+        params: <btc::GetAddressHistory as RpcMethod>::Params,
+    ) -> Result<<btc::GetAddressHistory as RpcMethod>::Output, RpcError> {
+        let plugin = self.get_entity_plugin_error(params.0)?;
+
+        let history = self
+            .call_plugin(plugin, btc::GetAddressHistory, params)
+            .await
+            .context("Error calling GetAddressHistory")?;
+        Ok(history)
+    }
+
+    pub async fn ens_resolve(
+        &self,
+        params: <ens::Resolve as RpcMethod>::Params,
+    ) -> Result<<ens::Resolve as RpcMethod>::Output, RpcError> {
+        let (provider_id, name) = params;
+        let node = namehash(&name);
+        let resolver = self
+            .ens_resolver(provider_id, node)
+            .await?
+            .context(format!("No resolver set for {}", name))?;
+
+        let data = EnsResolver::addrCall { node }.abi_encode();
+        let output = self.ens_call(provider_id, resolver, data).await?;
+        let address =
+            EnsResolver::addrCall::abi_decode_returns(&output).context("Invalid addr response")?;
+        if address == Address::ZERO {
+            return Err(RpcError::Custom(format!("No address set for {}", name)));
+        }
+        Ok(address)
+    }
+
+    pub async fn ens_lookup(
+        &self,
+        params: <ens::Lookup as RpcMethod>::Params,
+    ) -> Result<<ens::Lookup as RpcMethod>::Output, RpcError> {
+        let (provider_id, address) = params;
+        let node = reverse_node(address);
+        let Some(resolver) = self.ens_resolver(provider_id, node).await? else {
+            return Ok(None);
+        };
+
+        let data = EnsResolver::nameCall { node }.abi_encode();
+        let output = self.ens_call(provider_id, resolver, data).await?;
+        let name =
+            EnsResolver::nameCall::abi_decode_returns(&output).context("Invalid name response")?;
+        if name.is_empty() {
+            return Ok(None);
+        }
+
+        //? Anyone can set a reverse record pointing at any name, so only trust
+        //? names that resolve back to the address
+        match self.ens_resolve((provider_id, name.clone())).await {
+            Ok(resolved) if resolved == address => Ok(Some(name)),
+            _ => Ok(None),
+        }
+    }
+
+    async fn ens_resolver(
+        &self,
+        provider_id: EthProviderId,
+        node: B256,
+    ) -> Result<Option<Address>, RpcError> {
+        let data = EnsRegistry::resolverCall { node }.abi_encode();
+        let output = self.ens_call(provider_id, ENS_REGISTRY, data).await?;
+        let resolver = EnsRegistry::resolverCall::abi_decode_returns(&output)
+            .context("Invalid resolver response")?;
+        Ok((resolver != Address::ZERO).then_some(resolver))
+    }
+
+    async fn ens_call(
+        &self,
+        provider_id: EthProviderId,
+        to: Address,
+        data: Vec<u8>,
+    ) -> Result<Bytes, RpcError> {
+        let tx = TransactionRequest::default()
+            .to(to)
+            .input(Bytes::from(data).into());
+        self.eth_provider_call((provider_id, tx, BlockId::latest(), None, None))
+            .await
+    }
+
+    pub async fn price_get(
+        &self,
+        params: <price::Get as RpcMethod>::Params,
+    ) -> Result<<price::Get as RpcMethod>::Output, RpcError> {
+        let plugin = self.get_entity_plugin_error(params.0)?;
+
+        let price = self
+            .call_plugin(plugin, price::Get, params)
+            .await
+            .context("Error calling Get")?;
+        Ok(price)
+    }
+
+    pub async fn price_get_many(
+        &self,
+        params: <price::GetMany as RpcMethod>::Params,
+    ) -> Result<<price::GetMany as RpcMethod>::Output, RpcError> {
+        let plugin = self.get_entity_plugin_error(params.0)?;
+
+        let prices = self
+            .call_plugin(plugin, price::GetMany, params)
+            .await
+            .context("Error calling GetMany")?;
+        Ok(prices)
+    }
+
+    /// Forwards a signing request to the signer once the user has approved
+    /// it. The prompt shows the signer's address alongside the message.
+    pub async fn signer_sign(
+        &self,
+        instance_id: &InstanceId,
+        params: <signer::Sign as RpcMethod>::Params,
+    ) -> Result<<signer::Sign as RpcMethod>::Output, RpcError> {
+        let (signer_id, message) = params;
+        let plugin = self.get_entity_plugin_error(signer_id)?;
+
+        let address = self
+            .call_plugin(plugin.clone(), signer::GetAddress, signer_id)
+            .await
+            .context("Error calling GetAddress")?;
+        let preview = match std::str::from_utf8(&message) {
+            Ok(text) => component::text(text),
+            Err(_) => component::hex(&message),
+        };
+        let details = component::container(vec![component::address(address, None), preview]);
+
+        let title = "Sign message".to_string();
+        let body = format!("Sign this message with {}?", signer_id);
+        if !self
+            .request_confirmation(instance_id, (title, body, details))
+            .await?
+        {
+            return Err(RpcError::custom("User rejected the signature request"));
+        }
+
+        let signature = self
+            .call_plugin(plugin, signer::Sign, (signer_id, message))
+            .await
+            .context("Error calling Sign")?;
+        Ok(signature)
+    }
+
+    pub async fn signer_address(
+        &self,
+        params: <signer::GetAddress as RpcMethod>::Params,
+    ) -> Result<<signer::GetAddress as RpcMethod>::Output, RpcError> {
+        let plugin = self.get_entity_plugin_error(params)?;
+
+        let address = self
+            .call_plugin(plugin, signer::GetAddress, params)
+            .await
+            .context("Error calling GetAddress")?;
+        Ok(address)
+    }
+
+    pub async fn erc4337_build_user_op(
+        &self,
+        params: <erc4337::BuildUserOp as RpcMethod>::Params,
+    ) -> Result<<erc4337::BuildUserOp as RpcMethod>::Output, RpcError> {
+        let plugin = self.get_entity_plugin_error(params.0)?;
+
+        let user_op = self
+            .call_plugin(plugin, erc4337::BuildUserOp, params)
+            .await
+            .context("Error calling BuildUserOp")?;
+        Ok(user_op)
+    }
+
+    pub async fn erc4337_estimate_user_op_gas(
+        &self,
+        params: <erc4337::EstimateUserOpGas as RpcMethod>::Params,
+    ) -> Result<<erc4337::EstimateUserOpGas as RpcMethod>::Output, RpcError> {
+        let plugin = self.get_entity_plugin_error(params.0)?;
+
+        let estimation = self
+            .call_plugin(plugin, erc4337::EstimateUserOpGas, params)
+            .await
+            .context("Error calling EstimateUserOpGas")?;
+        Ok(estimation)
+    }
+
+    pub async fn erc4337_send_user_op(
+        &self,
+        instance_id: &InstanceId,
+        params: <erc4337::SendUserOp as RpcMethod>::Params,
+    ) -> Result<<erc4337::SendUserOp as RpcMethod>::Output, RpcError> {
+        self.log_call(erc4337::SendUserOp::NAME, instance_id.plugin);
+        self.reject_in_simulation(erc4337::SendUserOp::NAME)?;
+        let plugin = self.get_entity_plugin_error(params.0)?;
+        self.authorize_call(
+            instance_id.plugin,
+            params.0.into(),
+            erc4337::SendUserOp::NAME,
+            None,
+        )
+        .await?;
+
+        let user_op_hash = self
+            .call_plugin(plugin, erc4337::SendUserOp, params)
+            .await
+            .context("Error calling SendUserOp")?;
+        Ok(user_op_hash)
+    }
+
+    pub async fn erc4337_get_user_op_receipt(
+        &self,
+        params: <erc4337::GetUserOpReceipt as RpcMethod>::Params,
+    ) -> Result<<erc4337::GetUserOpReceipt as RpcMethod>::Output, RpcError> {
+        let plugin = self.get_entity_plugin_error(params.0)?;
+
+        let receipt = self
+            .call_plugin(plugin, erc4337::GetUserOpReceipt, params)
+            .await
+            .context("Error calling GetUserOpReceipt")?;
+        Ok(receipt)
+    }
+
+    pub async fn tokens_get_list(
+        &self,
+        params: <tokens::GetList as RpcMethod>::Params,
+    ) -> Result<<tokens::GetList as RpcMethod>::Output, RpcError> {
+        let plugin = self.get_entity_plugin_error(params.0)?;
+
+        let tokens = self
+            .call_plugin(plugin, tokens::GetList, params)
+            .await
+            .context("Error calling GetList")?;
+        Ok(tokens)
+    }
+
+    pub async fn tokens_search(
+        &self,
+        params: <tokens::Search as RpcMethod>::Params,
+    ) -> Result<<tokens::Search as RpcMethod>::Output, RpcError> {
+        let plugin = self.get_entity_plugin_error(params.0)?;
+
+        let tokens = self
+            .call_plugin(plugin, tokens::Search, params)
+            .await
+            .context("Error calling Search")?;
+        Ok(tokens)
+    }
+
+    pub async fn nft_get_owned(
+        &self,
+        params: <nft::GetOwned as RpcMethod>::Params,
+    ) -> Result<<nft::GetOwned as RpcMethod>::Output, RpcError> {
+        let plugin = self.get_entity_plugin_error(params.0)?;
+
+        let owned = self
+            .call_plugin(plugin, nft::GetOwned, params)
+            .await
+            .context("Error calling GetOwned")?;
+        Ok(owned)
+    }
+
+    pub async fn nft_get_metadata(
+        &self,
+        params: <nft::GetMetadata as RpcMethod>::Params,
+    ) -> Result<<nft::GetMetadata as RpcMethod>::Output, RpcError> {
+        let plugin = self.get_entity_plugin_error(params.0)?;
+
+        let metadata = self
+            .call_plugin(plugin, nft::GetMetadata, params)
+            .await
+            .context("Error calling GetMetadata")?;
+        Ok(metadata)
+    }
+
+    pub async fn trace_transaction(
+        &self,
+        mut params: <trace::TraceTransaction as RpcMethod>::Params,
+    ) -> Result<<trace::TraceTransaction as RpcMethod>::Output, RpcError> {
+        params.0 = self.simulation_target(params.0).await;
+        let plugin = self.get_entity_plugin_error(params.0)?;
+
+        let trace = self
+            .call_plugin(plugin, trace::TraceTransaction, params)
+            .await
+            .context("Error calling TraceTransaction")?;
+        Ok(trace)
+    }
+
+    pub async fn trace_call(
+        &self,
+        mut params: <trace::TraceCall as RpcMethod>::Params,
+    ) -> Result<<trace::TraceCall as RpcMethod>::Output, RpcError> {
+        params.0 = self.simulation_target(params.0).await;
+        let plugin = self.get_entity_plugin_error(params.0)?;
+
+        let trace = self
+            .call_plugin(plugin, trace::TraceCall, params)
+            .await
+            .context("Error calling TraceCall")?;
+        Ok(trace)
+    }
+
     pub async fn eth_fee_history(
         &self,
-        params: <eth::FeeHistory as RpcMethod>::Params,
+        mut params: <eth::FeeHistory as RpcMethod>::Params,
     ) -> Result<<eth::FeeHistory as RpcMethod>::Output, RpcError> {
+        params.0 = self.simulation_target(params.0).await;
         let plugin = self.get_entity_plugin_error(params.0)?;
 
-        let history = eth::FeeHistory
-            .call_async(plugin, params)
+        let history = self
+            .call_plugin(plugin, eth::FeeHistory, params)
             .await
             .context("Error calling FeeHistory")?;
         Ok(history)
     }
 
+    pub async fn fees_suggest(
+        &self,
+        provider_id: EthProviderId,
+    ) -> Result<<fees::Suggest as RpcMethod>::Output, RpcError> {
+        let provider_id = self.simulation_target(provider_id).await;
+        let plugin = self.get_entity_plugin_error(provider_id)?;
+
+        let suggestion = self
+            .call_plugin(plugin, fees::Suggest, provider_id)
+            .await
+            .context("Error calling Suggest")?;
+        Ok(suggestion)
+    }
+
     pub async fn coordinator_get_assets(
         &self,
         params: <coordinator::GetAssets as RpcMethod>::Params,
     ) -> Result<<coordinator::GetAssets as RpcMethod>::Output, RpcError> {
         let plugin = self.get_entity_plugin_error(params.0)?;
 
-        let assets = coordinator::GetAssets
-            .call_async(plugin, params)
+        let assets = self
+            .call_plugin(plugin, coordinator::GetAssets, params)
             .await
             .context("Error calling GetAssets")?;
         Ok(assets)
     }
 
-    pub async fn coordinator_get_session(
+    pub async fn coordinator_propose(
         &self,
-        params: <coordinator::GetSession as RpcMethod>::Params,
-    ) -> Result<<coordinator::GetSession as RpcMethod>::Output, RpcError> {
+        instance_id: &InstanceId,
+        params: <coordinator::Propose as RpcMethod>::Params,
+    ) -> Result<<coordinator::Propose as RpcMethod>::Output, RpcError> {
+        self.log_call(coordinator::Propose::NAME, instance_id.plugin);
         let plugin = self.get_entity_plugin_error(params.0)?;
 
-        let session = coordinator::GetSession
-            .call_async(plugin, params)
-            .await
-            .context("Error calling GetSession")?;
-        Ok(session)
+        //? Proposing closes the session whether or not it's accepted
+        self.close_session(params.0, &params.1);
+        let authorized = self
+            .authorize_call(
+                instance_id.plugin,
+                params.0.into(),
+                coordinator::Propose::NAME,
+                None,
+            )
+            .await;
+        let result = match authorized {
+            Ok(()) => self
+                .call_plugin(plugin, coordinator::Propose, params.clone())
+                .await
+                .context("Error calling Propose"),
+            Err(e) => Err(e),
+        };
+        self.audit(
+            instance_id.plugin,
+            coordinator::Propose::NAME,
+            &params,
+            &result,
+        );
+        result
     }
 
-    pub async fn coordinator_propose(
+    pub async fn coordinator_simulate(
         &self,
-        params: <coordinator::Propose as RpcMethod>::Params,
-    ) -> Result<<coordinator::Propose as RpcMethod>::Output, RpcError> {
+        params: <coordinator::Simulate as RpcMethod>::Params,
+    ) -> Result<<coordinator::Simulate as RpcMethod>::Output, RpcError> {
         let plugin = self.get_entity_plugin_error(params.0)?;
 
-        let result = coordinator::Propose
-            .call_async(plugin, params)
+        let result = self
+            .call_plugin_optional(plugin, coordinator::Simulate, params)
             .await
-            .context("Error calling Propose")?;
-        Ok(result)
+            .context("Error calling Simulate")?;
+        //? Pass MethodNotFound through so callers can tell the bundle wasn't
+        //? simulated
+        result.ok_or(RpcError::MethodNotFound)
+    }
+
+    pub async fn coordinator_propose_multichain(
+        &self,
+        instance_id: &InstanceId,
+        params: <coordinator::ProposeMultichain as RpcMethod>::Params,
+    ) -> Result<<coordinator::ProposeMultichain as RpcMethod>::Output, RpcError> {
+        self.log_call(coordinator::ProposeMultichain::NAME, instance_id.plugin);
+        let plugin = self.get_entity_plugin_error(params.0)?;
+
+        for (account, _) in &params.1 {
+            self.close_session(params.0, account);
+        }
+        let authorized = self
+            .authorize_call(
+                instance_id.plugin,
+                params.0.into(),
+                coordinator::ProposeMultichain::NAME,
+                None,
+            )
+            .await;
+        let result = match authorized {
+            Ok(()) => self
+                .call_plugin(plugin, coordinator::ProposeMultichain, params.clone())
+                .await
+                .context("Error calling ProposeMultichain"),
+            Err(e) => Err(e),
+        };
+        self.audit(
+            instance_id.plugin,
+            coordinator::ProposeMultichain::NAME,
+            &params,
+            &result,
+        );
+        result
     }
 }
 
@@ -1009,20 +4761,68 @@ impl Host {
 // pass a dummy ID.
 impl_host_rpc!(Host, global::Ping, ping);
 impl_host_rpc!(Host, host::RegisterEntity, register_entity);
+impl_host_rpc!(
+    Host,
+    host::RegisterEntityWithCapabilities,
+    register_entity_with_capabilities
+);
 impl_host_rpc!(Host, host::RequestEthProvider, request_eth_provider);
+impl_host_rpc!(Host, host::GetActiveChain, active_chain);
+impl_host_rpc!(Host, host::RequestConfirmation, request_confirmation);
+impl_host_rpc!(Host, host::RequestUserChoice, request_user_choice);
 impl_host_rpc!(Host, host::RequestVault, request_vault);
 impl_host_rpc!(Host, host::RequestCoordinator, request_coordinator);
+impl_host_rpc!(Host, host::RequestSolProvider, request_sol_provider);
+impl_host_rpc!(Host, host::RequestBtcProvider, request_btc_provider);
+impl_host_rpc!(Host, host::RequestPriceProvider, request_price_provider);
+impl_host_rpc!(Host, host::RequestSigner, request_signer);
+impl_host_rpc!(Host, host::RequestBundlerProvider, request_bundler_provider);
+impl_host_rpc!(Host, host::RequestTokenList, request_token_list);
+impl_host_rpc!(Host, host::RequestNftProvider, request_nft_provider);
+impl_host_rpc!(Host, signer::Sign, signer_sign);
+impl_host_rpc!(Host, host::Publish, publish);
+impl_host_rpc!(Host, host::Subscribe, subscribe_topic);
+impl_host_rpc!(Host, host::Unsubscribe, unsubscribe_topic);
+impl_host_rpc!(Host, host::SetAlarm, set_alarm);
+impl_host_rpc!(Host, host::CancelAlarm, cancel_alarm);
+impl_host_rpc!(Host, host::RegisterTick, register_tick);
+impl_host_rpc!(Host, host::Sleep, sleep);
+impl_host_rpc!(Host, host::UnregisterTick, unregister_tick);
+impl_host_rpc!(Host, host::WatchDeposit, watch_deposit);
+impl_host_rpc!(Host, host::UnwatchDeposit, unwatch_deposit);
+impl_host_rpc!(Host, host::LeaseNonce, lease_nonce);
+impl_host_rpc!(Host, host::ReleaseNonce, release_nonce);
 impl_host_rpc!(Host, host::Fetch, fetch);
+impl_host_rpc!(Host, host::FetchStream, fetch_stream);
+impl_host_rpc!(Host, host::FetchReadChunk, fetch_read_chunk);
+impl_host_rpc!(Host, host::FetchClose, fetch_close);
+impl_host_rpc!(Host, host::WsConnect, ws_connect);
+impl_host_rpc!(Host, host::WsSend, ws_send);
+impl_host_rpc!(Host, host::WsClose, ws_close);
+impl_host_rpc!(Host, host::SecretSet, secret_set);
+impl_host_rpc!(Host, host::SecretGet, secret_get);
+impl_host_rpc!(Host, host::SecretDelete, secret_delete);
 impl_host_rpc!(Host, state::ReadKey, read_key);
 impl_host_rpc!(Host, state::LockKey, lock_key);
 impl_host_rpc!(Host, state::SetKey, set_key);
 impl_host_rpc!(Host, state::UnlockKey, unlock_key);
+impl_host_rpc!(Host, state::DeleteKey, delete_key);
+impl_host_rpc!(Host, state::ListKeys, list_keys);
+impl_host_rpc!(Host, state::GetUsage, get_usage);
 impl_host_rpc!(Host, host::SetPage, set_interface);
+impl_host_rpc!(Host, host::SetPageTitle, set_page_title);
+impl_host_rpc!(Host, host::Navigate, navigate);
 impl_host_rpc!(Host, host::Notify, notify);
+impl_host_rpc!(Host, host::ShowToast, show_toast);
+impl_host_rpc!(Host, host::DecodeError, decode_error);
+impl_host_rpc!(Host, notification::Push, notification_push);
+impl_host_rpc!(Host, notification::Dismiss, notification_dismiss);
+impl_host_rpc!(Host, addressbook::Add, addressbook_add);
+impl_host_rpc!(Host, addressbook::Remove, addressbook_remove);
+impl_host_rpc!(Host, addressbook::List, addressbook_list);
 impl_host_rpc_no_id!(Host, vault::GetAssets, vault_get_assets);
-impl_host_rpc_no_id!(Host, vault::Withdraw, vault_withdraw);
+impl_host_rpc!(Host, vault::Withdraw, vault_withdraw);
 impl_host_rpc_no_id!(Host, vault::GetDepositAddress, vault_get_deposit_address);
-// impl_host_rpc_no_id!(Host, vault::OnDeposit, vault_on_deposit);
 impl_host_rpc_no_id!(Host, page::OnLoad, page_on_load);
 impl_host_rpc_no_id!(Host, page::OnUpdate, page_on_update);
 impl_host_rpc_no_id!(Host, eth::ChainId, eth_provider_chain_id);
@@ -1031,7 +4831,7 @@ impl_host_rpc_no_id!(Host, eth::Call, eth_provider_call);
 impl_host_rpc_no_id!(Host, eth::GetBalance, eth_provider_get_balance);
 impl_host_rpc_no_id!(Host, eth::GasPrice, eth_provider_gas_price);
 impl_host_rpc_no_id!(Host, eth::GetTransactionCount, eth_transaction_count);
-impl_host_rpc_no_id!(Host, eth::SendRawTransaction, eth_send_raw_transaction);
+impl_host_rpc!(Host, eth::SendRawTransaction, eth_send_raw_transaction);
 impl_host_rpc_no_id!(Host, eth::EstimateGas, eth_estimate_gas);
 impl_host_rpc_no_id!(
     Host,
@@ -1041,7 +4841,50 @@ impl_host_rpc_no_id!(
 impl_host_rpc_no_id!(Host, eth::GetBlock, eth_get_block);
 impl_host_rpc_no_id!(Host, eth::GetCode, eth_get_code);
 impl_host_rpc_no_id!(Host, eth::GetStorageAt, eth_get_storage_at);
+impl_host_rpc_no_id!(Host, eth::GetProof, eth_get_proof);
+impl_host_rpc_no_id!(Host, eth::CreateAccessList, eth_create_access_list);
+impl_host_rpc_no_id!(
+    Host,
+    eth::MaxPriorityFeePerGas,
+    eth_max_priority_fee_per_gas
+);
+impl_host_rpc_no_id!(Host, eth::BlobBaseFee, eth_blob_base_fee);
 impl_host_rpc_no_id!(Host, eth::FeeHistory, eth_fee_history);
+impl_host_rpc_no_id!(Host, fees::Suggest, fees_suggest);
+impl_host_rpc_no_id!(Host, sol::GetBalance, sol_get_balance);
+impl_host_rpc_no_id!(Host, sol::GetAccountInfo, sol_get_account_info);
+impl_host_rpc_no_id!(Host, sol::GetLatestBlockhash, sol_get_latest_blockhash);
+impl_host_rpc!(Host, sol::SendTransaction, sol_send_transaction);
+impl_host_rpc_no_id!(Host, btc::GetUtxos, btc_get_utxos);
+impl_host_rpc!(Host, btc::BroadcastTx, btc_broadcast_tx);
+impl_host_rpc_no_id!(Host, btc::EstimateFee, btc_estimate_fee);
+impl_host_rpc_no_id!(Host, btc::GetAddressHistory, btc_get_address_history);
+impl_host_rpc_no_id!(Host, ens::Resolve, ens_resolve);
+impl_host_rpc_no_id!(Host, ens::Lookup, ens_lookup);
+impl_host_rpc_no_id!(Host, price::Get, price_get);
+impl_host_rpc_no_id!(Host, price::GetMany, price_get_many);
+impl_host_rpc_no_id!(Host, signer::GetAddress, signer_address);
+impl_host_rpc_no_id!(Host, erc4337::BuildUserOp, erc4337_build_user_op);
+impl_host_rpc_no_id!(
+    Host,
+    erc4337::EstimateUserOpGas,
+    erc4337_estimate_user_op_gas
+);
+impl_host_rpc!(Host, erc4337::SendUserOp, erc4337_send_user_op);
+impl_host_rpc_no_id!(Host, erc4337::GetUserOpReceipt, erc4337_get_user_op_receipt);
+impl_host_rpc_no_id!(Host, tokens::GetList, tokens_get_list);
+impl_host_rpc_no_id!(Host, tokens::Search, tokens_search);
+impl_host_rpc_no_id!(Host, nft::GetOwned, nft_get_owned);
+impl_host_rpc_no_id!(Host, nft::GetMetadata, nft_get_metadata);
+impl_host_rpc_no_id!(Host, trace::TraceTransaction, trace_transaction);
+impl_host_rpc_no_id!(Host, trace::TraceCall, trace_call);
 impl_host_rpc_no_id!(Host, coordinator::GetAssets, coordinator_get_assets);
-impl_host_rpc_no_id!(Host, coordinator::GetSession, coordinator_get_session);
-impl_host_rpc_no_id!(Host, coordinator::Propose, coordinator_propose);
+impl_host_rpc!(Host, coordinator::GetSession, coordinator_get_session);
+impl_host_rpc!(Host, coordinator::CancelSession, coordinator_cancel_session);
+impl_host_rpc!(Host, coordinator::Propose, coordinator_propose);
+impl_host_rpc_no_id!(Host, coordinator::Simulate, coordinator_simulate);
+impl_host_rpc!(
+    Host,
+    coordinator::ProposeMultichain,
+    coordinator_propose_multichain
+);
@@ -0,0 +1,60 @@
+//! The address book's user API and host methods. Contacts and who saved them
+//! are kept by `AddressBook`.
+
+use super::*;
+
+impl Host {
+    pub fn get_address_book(&self) -> Vec<addressbook::Contact> {
+        self.address_book.list()
+    }
+
+    /// Saves a contact for the user, replacing the label of an already-saved
+    /// account even if a plugin saved it.
+    pub fn add_contact(&self, contact: addressbook::Contact) {
+        //? The user may change any contact
+        let _ = self.address_book.add(None, contact);
+        self.notify_observers();
+    }
+
+    pub fn remove_contact(&self, account: &AccountId) {
+        let _ = self.address_book.remove(None, account);
+        self.notify_observers();
+    }
+
+    pub async fn addressbook_add(
+        &self,
+        instance_id: &InstanceId,
+        contact: addressbook::Contact,
+    ) -> Result<(), RpcError> {
+        self.address_book
+            .add(Some(instance_id.plugin), contact)
+            .map_err(RpcError::Custom)?;
+        self.notify_observers();
+        Ok(())
+    }
+
+    pub async fn addressbook_remove(
+        &self,
+        instance_id: &InstanceId,
+        account: AccountId,
+    ) -> Result<(), RpcError> {
+        self.address_book
+            .remove(Some(instance_id.plugin), &account)
+            .map_err(RpcError::Custom)?;
+        self.notify_observers();
+        Ok(())
+    }
+
+    pub async fn addressbook_list(
+        &self,
+        _instance_id: &InstanceId,
+        chain_id: Option<caip::ChainId>,
+    ) -> Result<Vec<addressbook::Contact>, RpcError> {
+        let contacts = self
+            .get_address_book()
+            .into_iter()
+            .filter(|c| chain_id.as_ref().is_none_or(|id| &c.account.chain_id == id))
+            .collect();
+        Ok(contacts)
+    }
+}
@@ -0,0 +1,484 @@
+//! Installing and upgrading plugins: verifying their builds and signatures,
+//! and resolving the dependencies their manifests declare.
+
+use super::{
+    network::{fetch_response, origin_of},
+    *,
+};
+use crate::signing::{self, SIGNATURE_SUFFIX, Verification};
+
+const SIGNATURE_TIMEOUT_SECS: u64 = 30;
+
+impl Host {
+    /// Creates a plugin from its source, register it, and calls its Init method.
+    ///
+    /// If a plugin with the same name is already loaded, it is upgraded instead.
+    pub async fn new_plugin(
+        self: &Arc<Host>,
+        source: PluginSource,
+        name: &str,
+    ) -> Result<PluginId, PluginError> {
+        let wasm_bytes = source.as_bytes().await?;
+        let publisher = self.verify_plugin(&source, &wasm_bytes, name).await?;
+        self.add_plugin(source, wasm_bytes, publisher, name).await
+    }
+
+    /// Registers a verified build, signed by `publisher` if any, and calls its
+    /// Init method, or upgrades the loaded plugin with the same name to it.
+    async fn add_plugin(
+        self: &Arc<Host>,
+        source: PluginSource,
+        wasm_bytes: Vec<u8>,
+        publisher: Option<[u8; 32]>,
+        name: &str,
+    ) -> Result<PluginId, PluginError> {
+        if let Some(old_id) = self.find_plugin_by_name(name) {
+            return self
+                .upgrade_plugin(old_id, source, wasm_bytes, publisher, name)
+                .await;
+        }
+
+        let plugin_id = plugin_id_for(&wasm_bytes);
+        let plugin = self
+            .load_plugin_bytes(plugin_id, source, wasm_bytes, publisher, name)
+            .await?;
+        self.approve_manifest(plugin_id).await?;
+        self.resolve_dependencies(plugin_id, name).await?;
+
+        info!("Initializing plugin {}", plugin.id());
+        match plugin::Init.call_async(plugin.clone(), ()).await {
+            Err(RpcError::MethodNotFound) => {
+                info!("Plugin {} does not implement Init, skipping", plugin.id());
+                self.log_event("Initialized", Some((plugin_id, name)));
+                Ok(plugin_id)
+            }
+            Err(e) => Err(e.into()),
+            Ok(_) => {
+                info!("Plugin {} initialized", plugin.id());
+                self.log_event("Initialized", Some((plugin_id, name)));
+                Ok(plugin_id)
+            }
+        }
+    }
+
+    /// Downloads a plugin and installs it if its SHA-256 digest matches
+    /// `expected_sha256`. The verified bytes are stored as the plugin's
+    /// source, so later loads can't pick up a different build from the URL.
+    ///
+    /// The plugin is named after the last segment of the URL's path.
+    pub async fn install_plugin_from_url(
+        self: &Arc<Host>,
+        url: &str,
+        expected_sha256: [u8; 32],
+    ) -> Result<PluginId, PluginError> {
+        self.install_plugin_as(url, expected_sha256, &plugin_name_for(url))
+            .await
+    }
+
+    async fn install_plugin_as(
+        self: &Arc<Host>,
+        url: &str,
+        expected_sha256: [u8; 32],
+        name: &str,
+    ) -> Result<PluginId, PluginError> {
+        let source = PluginSource::Url(url.to_string());
+        info!("Installing plugin '{}' from {}", name, url);
+
+        let wasm_bytes = source.as_bytes().await?;
+        let digest: [u8; 32] = Sha256::digest(&wasm_bytes).into();
+        if digest != expected_sha256 {
+            return Err(PluginError::DigestMismatch {
+                url: url.to_string(),
+                expected: hex::encode(expected_sha256),
+                actual: hex::encode(digest),
+            });
+        }
+
+        let publisher = self.verify_plugin(&source, &wasm_bytes, name).await?;
+        let source = PluginSource::Embedded(wasm_bytes.clone());
+        self.add_plugin(source, wasm_bytes, publisher, name).await
+    }
+
+    /// Replaces a loaded plugin with a new build of it, migrating the old
+    /// build's state, secrets, entities, and grants to the new one.
+    ///
+    /// Builds signed by the old build's publisher are swapped in without
+    /// asking. Anything else could be an unrelated plugin that happens to
+    /// share the name, so the user must confirm the replacement first.
+    ///
+    /// The new build is only swapped in once `Migrate` succeeds. On failure the
+    /// new build is unloaded and the old one stays registered.
+    async fn upgrade_plugin(
+        self: &Arc<Host>,
+        old_id: PluginId,
+        source: PluginSource,
+        wasm_bytes: Vec<u8>,
+        publisher: Option<[u8; 32]>,
+        name: &str,
+    ) -> Result<PluginId, PluginError> {
+        if plugin_id_for(&wasm_bytes) == old_id {
+            info!("Plugin '{}' is already loaded", name);
+            return Ok(old_id);
+        }
+
+        info!("Upgrading plugin '{}'...", name);
+        let old_publisher = self.plugin_publishers.lock().unwrap().get(&old_id).copied();
+        if publisher.is_none() || publisher != old_publisher {
+            warn!("Plugin '{}' is not signed by its installed publisher", name);
+            let request = UserRequest::PluginUpgrade {
+                id: Uuid::new_v4(),
+                plugin_id: old_id,
+                name: name.to_string(),
+            };
+            self.create_user_request(request, |resp| match resp {
+                UserResponse::UpgradeApproved => Some(()),
+                _ => None,
+            })
+            .await?;
+        }
+
+        let new_id = plugin_id_for(&wasm_bytes);
+        let plugin = self
+            .load_plugin_bytes(new_id, source, wasm_bytes, publisher, name)
+            .await?;
+        self.approve_manifest(new_id).await?;
+        self.resolve_dependencies(new_id, name).await?;
+
+        let old_manifest = self.get_manifest(&old_id).unwrap_or_default();
+        let new_manifest = self.get_manifest(&new_id).unwrap_or_default();
+        let migrated = self
+            .migrate_state(&plugin, name, old_id, &old_manifest, &new_manifest)
+            .await;
+        let migrated = match migrated {
+            Ok(state) => state,
+            Err(e) => {
+                warn!("Failed to migrate plugin '{}': {}", name, e);
+                self.unload_plugin(&new_id);
+                return Err(e.into());
+            }
+        };
+
+        //? Migration succeeded, so swap the registration over to the new build
+        if let Some(old_plugin) = self.get_plugin(&old_id) {
+            self.shutdown_plugin(old_plugin).await;
+        }
+        {
+            let mut state = self.state.lock().unwrap();
+            let mut dirty = self.dirty_state.lock().unwrap();
+            state.retain(|key, _| {
+                let keep = key.0 != old_id && key.0 != new_id;
+                if !keep {
+                    dirty.insert(key.clone());
+                }
+                keep
+            });
+            for (key, value) in migrated {
+                dirty.insert((new_id, key.clone()));
+                state.insert((new_id, key), value);
+            }
+        }
+        self.locks
+            .lock()
+            .unwrap()
+            .retain(|(id, _), _| *id != old_id);
+        for owner in self.entities.lock().unwrap().values_mut() {
+            if *owner == old_id {
+                *owner = new_id;
+            }
+        }
+        for watch in self.deposit_watches.lock().unwrap().iter_mut() {
+            if watch.plugin_id == old_id {
+                watch.plugin_id = new_id;
+            }
+        }
+        {
+            let mut quotas = self.state_quotas.lock().unwrap();
+            if let Some(quota) = quotas.remove(&old_id) {
+                quotas.insert(new_id, quota);
+            }
+        }
+        {
+            let mut limits = self.resource_limits.lock().unwrap();
+            if let Some(limit) = limits.remove(&old_id) {
+                limits.insert(new_id, limit);
+            }
+        }
+        self.event_bus.transfer(&old_id, new_id);
+        self.permissions.transfer(&old_id, new_id);
+        self.ticker.transfer(&old_id, new_id);
+        self.keystore.transfer(&old_id, new_id);
+        self.address_book.transfer(&old_id, new_id);
+        for (plugin_id, _) in self.fetch_origins.lock().unwrap().iter_mut() {
+            if *plugin_id == old_id {
+                *plugin_id = new_id;
+            }
+        }
+        self.unload_plugin(&old_id);
+
+        info!("Upgraded plugin '{}' from {} to {}", name, old_id, new_id);
+        self.log_event("Upgraded", Some((new_id, name)));
+        Ok(new_id)
+    }
+
+    /// Prompts the user to approve a loaded plugin's manifest, unloading the
+    /// plugin if it's denied.
+    async fn approve_manifest(&self, plugin_id: PluginId) -> Result<(), PluginError> {
+        let manifest = self.get_manifest(&plugin_id).unwrap_or_default();
+        let approval = self.request_manifest_approval(plugin_id, manifest).await;
+        if let Err(e) = approval {
+            info!("Manifest for plugin {} was not approved", plugin_id);
+            self.unload_plugin(&plugin_id);
+            return Err(e.into());
+        }
+
+        Ok(())
+    }
+
+    /// Checks a registered plugin's dependencies, installing missing plugins
+    /// that have a release and prompting the user to install the rest until
+    /// they're all met. The plugin is unloaded if the user gives up.
+    async fn resolve_dependencies(
+        self: &Arc<Host>,
+        plugin_id: PluginId,
+        name: &str,
+    ) -> Result<(), PluginError> {
+        let manifest = self.get_manifest(&plugin_id).unwrap_or_default();
+        self.install_dependencies(&manifest).await;
+        loop {
+            let missing = self.missing_dependencies(&manifest);
+            if missing.is_empty() {
+                return Ok(());
+            }
+
+            info!(
+                "Plugin '{}' is missing {} dependencies",
+                name,
+                missing.len()
+            );
+            let request = UserRequest::MissingDependencies {
+                id: Uuid::new_v4(),
+                plugin_id,
+                missing: missing.clone(),
+            };
+            let retried = self
+                .create_user_request(request, |resp| match resp {
+                    UserResponse::DependenciesRetried => Some(()),
+                    _ => None,
+                })
+                .await;
+            if retried.is_err() {
+                self.unload_plugin(&plugin_id);
+                let missing: Vec<String> = missing.iter().map(|d| d.to_string()).collect();
+                return Err(PluginError::MissingDependencies(
+                    name.to_string(),
+                    missing.join(", "),
+                ));
+            }
+        }
+    }
+
+    /// Installs the missing plugin dependencies in `manifest` that have a
+    /// release. Failures are logged and left for the user to resolve.
+    ///
+    /// Only plugins that aren't installed at all are installed. Installing
+    /// over one whose version is too low would upgrade it, handing its state
+    /// and secrets to a build named by someone else's manifest, so that's left
+    /// for the user to resolve too.
+    async fn install_dependencies(self: &Arc<Host>, manifest: &plugin::PluginManifest) {
+        for dependency in self.missing_dependencies(manifest) {
+            let plugin::Dependency::Plugin {
+                name,
+                release: Some(release),
+                ..
+            } = dependency
+            else {
+                continue;
+            };
+            if self.find_plugin_by_name(&name).is_some() {
+                info!("Dependency '{}' is installed but out of date", name);
+                continue;
+            }
+
+            info!("Installing dependency '{}' from {}", name, release.url);
+            //? Boxed since installing a dependency resolves its own dependencies
+            let installed = Box::pin(self.install_plugin_as(&release.url, release.sha256, &name));
+            if let Err(e) = installed.await {
+                warn!("Failed to install dependency '{}': {}", name, e);
+            }
+        }
+    }
+
+    /// Returns the dependencies in `manifest` that aren't met by the plugins
+    /// and entities currently registered.
+    pub fn missing_dependencies(
+        &self,
+        manifest: &plugin::PluginManifest,
+    ) -> Vec<plugin::Dependency> {
+        manifest
+            .dependencies
+            .iter()
+            .filter(|dependency| !self.has_dependency(dependency))
+            .cloned()
+            .collect()
+    }
+
+    fn has_dependency(&self, dependency: &plugin::Dependency) -> bool {
+        match dependency {
+            plugin::Dependency::Entity { domain, chain_id } => {
+                let mut required = host::EntityCapabilities::new();
+                if let Some(chain_id) = chain_id {
+                    required = required.with_chain(chain_id.clone());
+                }
+                self.get_entities().into_iter().any(|entity_id| {
+                    entity_id.domain() == *domain
+                        && self.get_entity_capabilities(entity_id).satisfies(&required)
+                })
+            }
+            plugin::Dependency::Plugin {
+                name, min_version, ..
+            } => self
+                .find_plugin_by_name(name)
+                .and_then(|id| self.get_manifest(&id))
+                .is_some_and(|manifest| manifest.version >= *min_version),
+        }
+    }
+
+    /// Fetches the detached signature served next to a plugin's URL, the same
+    /// way the plugin's own fetches are made: counted against its rate limit,
+    /// and without following redirects, so the signature can only come from
+    /// the plugin's origin.
+    ///
+    /// Only a 200 response is taken as a signature, and a 404 as there being
+    /// none. Any other response fails, rather than passing an error page or
+    /// an empty body off as the signature.
+    async fn fetch_signature(
+        &self,
+        plugin_id: PluginId,
+        url: &str,
+    ) -> Result<Option<Vec<u8>>, String> {
+        if !self.rate_limiter.check(plugin_id, host::Fetch::NAME) {
+            return Err("Too many requests".to_string());
+        }
+
+        let req = host::Request {
+            url: format!("{}{}", url, SIGNATURE_SUFFIX),
+            method: "GET".to_string(),
+            timeout: Some(Duration::from_secs(SIGNATURE_TIMEOUT_SECS)),
+            redirect: host::RedirectPolicy::None,
+            ..Default::default()
+        };
+        //? Appending the suffix can move a bare origin to another host, e.g.
+        //? `https://example.com` to `https://example.com.sig`
+        let origin = origin_of(url).filter(|origin| origin_of(&req.url).as_ref() == Some(origin));
+        let Some(origin) = origin else {
+            return Err(format!("Invalid signature URL: {}", req.url));
+        };
+
+        let response = fetch_response(&req, &[origin]).await?;
+        match response.status {
+            200 => Ok(Some(response.body)),
+            404 => Ok(None),
+            status => Err(format!("Unexpected response status {}", status)),
+        }
+    }
+
+    /// Checks a URL plugin's detached signature against the trusted
+    /// publishers, returning the key of the publisher that signed it. Plugins
+    /// without a trusted signature are only loaded if the user explicitly
+    /// accepts them.
+    pub(super) async fn verify_plugin(
+        &self,
+        source: &PluginSource,
+        wasm_bytes: &[u8],
+        name: &str,
+    ) -> Result<Option<[u8; 32]>, PluginError> {
+        let PluginSource::Url(url) = source else {
+            return Ok(None);
+        };
+
+        match self.check_signature(url, wasm_bytes, name).await? {
+            Verification::Trusted(publisher) => {
+                info!("Plugin '{}' is signed by '{}'", name, publisher.name);
+                Ok(Some(publisher.key))
+            }
+            Verification::Invalid => Err(PluginError::InvalidSignature(name.to_string())),
+            Verification::Untrusted => {
+                warn!("Plugin '{}' is not signed by a trusted publisher", name);
+                let request = UserRequest::UnsignedPlugin {
+                    id: Uuid::new_v4(),
+                    plugin_id: plugin_id_for(wasm_bytes),
+                    name: name.to_string(),
+                    url: url.clone(),
+                };
+                self.create_user_request(request, |resp| match resp {
+                    UserResponse::UnsignedPluginApproved => Some(()),
+                    _ => None,
+                })
+                .await?;
+                Ok(None)
+            }
+        }
+    }
+
+    /// Checks that a URL plugin being restored still serves the build that was
+    /// verified when it was loaded, returning the publisher that signed it.
+    /// States saved before builds were pinned have no digest, so their plugins
+    /// must be signed by a trusted publisher instead. There's no one to prompt
+    /// while restoring, so anything else fails the restore.
+    pub(super) async fn verify_restored(
+        &self,
+        source: &PluginSource,
+        wasm_bytes: &[u8],
+        sha256: Option<[u8; 32]>,
+        publisher: Option<[u8; 32]>,
+        name: &str,
+    ) -> Result<Option<[u8; 32]>, PluginError> {
+        let PluginSource::Url(url) = source else {
+            return Ok(publisher);
+        };
+
+        let digest: [u8; 32] = Sha256::digest(wasm_bytes).into();
+        match sha256 {
+            Some(expected) if expected == digest => Ok(publisher),
+            Some(expected) => Err(PluginError::DigestMismatch {
+                url: url.clone(),
+                expected: hex::encode(expected),
+                actual: hex::encode(digest),
+            }),
+            None => match self.check_signature(url, wasm_bytes, name).await? {
+                Verification::Trusted(publisher) => Ok(Some(publisher.key)),
+                Verification::Invalid => Err(PluginError::InvalidSignature(name.to_string())),
+                Verification::Untrusted => Err(PluginError::Untrusted(name.to_string())),
+            },
+        }
+    }
+
+    /// Fetches a URL plugin's detached signature and checks it against the
+    /// trusted publishers.
+    async fn check_signature(
+        &self,
+        url: &str,
+        wasm_bytes: &[u8],
+        name: &str,
+    ) -> Result<Verification, PluginError> {
+        let signature = self
+            .fetch_signature(plugin_id_for(wasm_bytes), url)
+            .await
+            .map_err(|e| PluginError::SignatureUnavailable(name.to_string(), e))?;
+        let trusted = self.get_trusted_publishers();
+        Ok(signing::verify(wasm_bytes, signature.as_deref(), &trusted))
+    }
+}
+
+/// Names a plugin after the last segment of its URL's path, without the
+/// `.wasm` extension.
+fn plugin_name_for(url: &str) -> String {
+    let path = url.split(['?', '#']).next().unwrap_or(url);
+    let file = path.rsplit('/').next().unwrap_or(path);
+    let name = file.strip_suffix(".wasm").unwrap_or(file);
+    if name.is_empty() {
+        "unknown_plugin".to_string()
+    } else {
+        name.to_string()
+    }
+}
@@ -0,0 +1,368 @@
+//! Plugins' HTTP requests and WebSockets, which can only reach the origins
+//! the user approved for them.
+
+use super::*;
+use crate::ws::WsEvent;
+
+const FETCH_CHUNK_SIZE: usize = 64 * 1024;
+
+/// A buffered response body being read by a plugin in chunks
+pub(super) struct FetchStream {
+    pub(super) plugin_id: PluginId,
+    body: Vec<u8>,
+    offset: usize,
+}
+
+impl Host {
+    /// Delivers messages received on plugins' WebSockets, in the order they
+    /// arrived. The frontend is expected to call this frequently.
+    pub async fn deliver_ws_messages(&self) {
+        for (plugin_id, ws_id, event) in self.websockets.take_events() {
+            let Some(plugin) = self.get_plugin(&plugin_id) else {
+                continue;
+            };
+
+            let result = match event {
+                WsEvent::Message(message) => {
+                    let params = (ws_id, message);
+                    self.call_plugin(plugin.clone(), plugin::OnWsMessage, params)
+                        .await
+                }
+                WsEvent::Closed(reason) => {
+                    let params = (ws_id, reason);
+                    self.call_plugin(plugin.clone(), plugin::OnWsClosed, params)
+                        .await
+                }
+            };
+            if let Err(e) = result {
+                warn!(
+                    "Plugin {} failed to handle WebSocket event: {}",
+                    plugin.id(),
+                    e
+                );
+            }
+        }
+    }
+
+    /// Checks that a plugin may fetch from a URL's origin. Origins that aren't
+    /// in the plugin's manifest or approved before prompt the user.
+    async fn approve_origin(&self, plugin_id: PluginId, url: &str) -> Result<(), String> {
+        let origin = origin_of(url).ok_or_else(|| format!("Invalid URL: {}", url))?;
+        let manifest = self.get_manifest(&plugin_id).unwrap_or_default();
+        let declared = manifest
+            .origins
+            .iter()
+            .any(|o| origin_of(o).as_ref() == Some(&origin));
+        let key = (plugin_id, origin);
+        if declared || self.fetch_origins.lock().unwrap().contains(&key) {
+            return Ok(());
+        }
+
+        let request = UserRequest::OriginApproval {
+            id: Uuid::new_v4(),
+            plugin_id,
+            origin: key.1.clone(),
+        };
+        let approval = self
+            .create_user_request(request, |resp| match resp {
+                UserResponse::OriginApproved => Some(()),
+                _ => None,
+            })
+            .await;
+        if approval.is_err() {
+            return Err(format!("Fetching from {} was not approved", key.1));
+        }
+
+        let mut origins = self.fetch_origins.lock().unwrap();
+        if !origins.contains(&key) {
+            origins.push(key);
+        }
+        Ok(())
+    }
+
+    /// Origins the plugin may fetch from without prompting: those in its
+    /// manifest and those the user has approved. Redirects are only followed
+    /// to these, since there's no way to prompt partway through a request.
+    fn approved_origins(&self, plugin_id: PluginId) -> Vec<String> {
+        let manifest = self.get_manifest(&plugin_id).unwrap_or_default();
+        let approved = self.fetch_origins.lock().unwrap();
+        manifest
+            .origins
+            .iter()
+            .filter_map(|o| origin_of(o))
+            .chain(
+                approved
+                    .iter()
+                    .filter(|(id, _)| *id == plugin_id)
+                    .map(|(_, origin)| origin.clone()),
+            )
+            .collect()
+    }
+
+    pub async fn fetch(
+        &self,
+        instance_id: &InstanceId,
+        req: host::Request,
+    ) -> Result<Result<host::Response, String>, RpcError> {
+        if let Err(e) = self.approve_origin(instance_id.plugin, &req.url).await {
+            return Ok(Err(e));
+        }
+        let origins = self.approved_origins(instance_id.plugin);
+        Ok(fetch_response(&req, &origins).await)
+    }
+
+    pub async fn fetch_stream(
+        &self,
+        instance_id: &InstanceId,
+        req: host::Request,
+    ) -> Result<Result<host::ResponseHead, String>, RpcError> {
+        if let Err(e) = self.approve_origin(instance_id.plugin, &req.url).await {
+            return Ok(Err(e));
+        }
+
+        // TODO: Stream the body from the network instead of buffering it. The
+        // response can't be held across host calls on wasm since it isn't
+        // `Send`, so for now only the plugin side is chunked.
+        let host::Response {
+            status,
+            headers,
+            body,
+        } = match fetch_response(&req, &self.approved_origins(instance_id.plugin)).await {
+            Ok(resp) => resp,
+            Err(e) => return Ok(Err(e)),
+        };
+
+        let stream_id = host::FetchStreamId(Uuid::new_v4());
+        self.fetch_streams.lock().unwrap().insert(
+            stream_id,
+            FetchStream {
+                plugin_id: instance_id.plugin,
+                body,
+                offset: 0,
+            },
+        );
+
+        Ok(Ok(host::ResponseHead {
+            stream_id,
+            status,
+            headers,
+        }))
+    }
+
+    pub async fn fetch_read_chunk(
+        &self,
+        instance_id: &InstanceId,
+        stream_id: host::FetchStreamId,
+    ) -> Result<Result<Option<Vec<u8>>, String>, RpcError> {
+        let mut streams = self.fetch_streams.lock().unwrap();
+        let Some(stream) = streams
+            .get_mut(&stream_id)
+            .filter(|s| s.plugin_id == instance_id.plugin)
+        else {
+            return Ok(Err(format!("Fetch stream {:?} not found", stream_id)));
+        };
+
+        if stream.offset >= stream.body.len() {
+            streams.remove(&stream_id);
+            return Ok(Ok(None));
+        }
+
+        let end = (stream.offset + FETCH_CHUNK_SIZE).min(stream.body.len());
+        let chunk = stream.body[stream.offset..end].to_vec();
+        stream.offset = end;
+        Ok(Ok(Some(chunk)))
+    }
+
+    pub async fn fetch_close(
+        &self,
+        instance_id: &InstanceId,
+        stream_id: host::FetchStreamId,
+    ) -> Result<(), RpcError> {
+        let mut streams = self.fetch_streams.lock().unwrap();
+        if streams
+            .get(&stream_id)
+            .is_some_and(|s| s.plugin_id == instance_id.plugin)
+        {
+            streams.remove(&stream_id);
+        }
+        Ok(())
+    }
+
+    pub async fn ws_connect(
+        &self,
+        instance_id: &InstanceId,
+        url: String,
+    ) -> Result<Result<host::WsId, String>, RpcError> {
+        if let Err(e) = self.approve_origin(instance_id.plugin, &url).await {
+            return Ok(Err(e));
+        }
+        Ok(self.websockets.connect(instance_id.plugin, &url))
+    }
+
+    pub async fn ws_send(
+        &self,
+        instance_id: &InstanceId,
+        (ws_id, message): (host::WsId, host::WsMessage),
+    ) -> Result<Result<(), String>, RpcError> {
+        Ok(self.websockets.send(instance_id.plugin, ws_id, message))
+    }
+
+    pub async fn ws_close(
+        &self,
+        instance_id: &InstanceId,
+        ws_id: host::WsId,
+    ) -> Result<(), RpcError> {
+        self.websockets.close(instance_id.plugin, ws_id);
+        Ok(())
+    }
+}
+
+/// Returns a URL's origin as `scheme://host[:port]`, or `None` if it isn't a
+/// valid URL with a host.
+pub(super) fn origin_of(url: &str) -> Option<String> {
+    let origin = reqwest::Url::parse(url).ok()?.origin();
+    origin.is_tuple().then(|| origin.ascii_serialization())
+}
+
+/// Sends a request and reads its whole body, failing if that takes longer
+/// than the request's timeout. Redirects are only followed to `origins`.
+pub(super) async fn fetch_response(
+    req: &host::Request,
+    origins: &[String],
+) -> Result<host::Response, String> {
+    let fetch = async {
+        let resp = send_request(req, origins).await?;
+        let status = resp.status().as_u16();
+        let headers = response_headers(&resp);
+        let body = resp
+            .bytes()
+            .await
+            .map_err(|e| format!("Failed to read response body: {}", e))?;
+        Ok(host::Response {
+            status,
+            headers,
+            body: body.to_vec(),
+        })
+    };
+
+    match req.timeout {
+        Some(timeout) => time::timeout(timeout, fetch)
+            .await
+            .unwrap_or_else(|| Err(format!("Request timed out after {:?}", timeout))),
+        None => fetch.await,
+    }
+}
+
+/// Sends a plugin's network request, returning an error message if it
+/// couldn't be made or was redirected outside `origins`.
+async fn send_request(
+    req: &host::Request,
+    origins: &[String],
+) -> Result<reqwest::Response, String> {
+    let mut headers = reqwest::header::HeaderMap::new();
+    for (key, value) in req.headers.iter() {
+        if let (Ok(name), Ok(val)) = (
+            reqwest::header::HeaderName::from_bytes(key.as_bytes()),
+            reqwest::header::HeaderValue::from_bytes(value),
+        ) {
+            headers.insert(name, val);
+        }
+    }
+
+    let method = reqwest::Method::from_bytes(req.method.to_uppercase().as_bytes())
+        .map_err(|_| format!("Unsupported HTTP method: {}", req.method))?;
+    let mut request = http_client(req.redirect, origins)?
+        .request(method, req.url.clone())
+        .headers(headers);
+    if let Some(body) = &req.body {
+        request = request.body(body.clone());
+    }
+
+    let resp = request
+        .send()
+        .await
+        .map_err(|e| format!("Failed to send HTTP request: {}", e))?;
+    check_redirects(req, &resp, origins)?;
+    Ok(resp)
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn http_client(
+    redirect: host::RedirectPolicy,
+    origins: &[String],
+) -> Result<reqwest::Client, String> {
+    let policy = match redirect {
+        host::RedirectPolicy::Follow(max) => {
+            let origins = origins.to_vec();
+            reqwest::redirect::Policy::custom(move |attempt| {
+                if attempt.previous().len() >= max as usize {
+                    return attempt.error(format!("More than {} redirects", max));
+                }
+                match origin_of(attempt.url().as_str()) {
+                    Some(origin) if origins.contains(&origin) => attempt.follow(),
+                    _ => {
+                        let error = format!("Redirected to unapproved URL {}", attempt.url());
+                        attempt.error(error)
+                    }
+                }
+            })
+        }
+        host::RedirectPolicy::None => reqwest::redirect::Policy::none(),
+    };
+    reqwest::Client::builder()
+        .redirect(policy)
+        .build()
+        .map_err(|e| format!("Failed to build HTTP client: {}", e))
+}
+
+//? The browser's fetch follows redirects itself, so they're checked once the
+//? response arrives in `check_redirects` instead
+#[cfg(target_arch = "wasm32")]
+fn http_client(
+    _redirect: host::RedirectPolicy,
+    _origins: &[String],
+) -> Result<reqwest::Client, String> {
+    Ok(reqwest::Client::new())
+}
+
+//? Natively the client's redirect policy has already checked every hop
+#[cfg(not(target_arch = "wasm32"))]
+fn check_redirects(
+    _req: &host::Request,
+    _resp: &reqwest::Response,
+    _origins: &[String],
+) -> Result<(), String> {
+    Ok(())
+}
+
+/// Fails a response the browser reached through a redirect the request's
+/// policy doesn't allow. The redirect has already been followed by then, but
+/// its response doesn't reach the plugin. The number of redirects isn't
+/// visible to the host, so `Follow`'s limit can't be applied.
+#[cfg(target_arch = "wasm32")]
+fn check_redirects(
+    req: &host::Request,
+    resp: &reqwest::Response,
+    origins: &[String],
+) -> Result<(), String> {
+    if reqwest::Url::parse(&req.url).ok().as_ref() == Some(resp.url()) {
+        return Ok(());
+    }
+    match (req.redirect, origin_of(resp.url().as_str())) {
+        (host::RedirectPolicy::None, _) => Err(format!(
+            "Redirected to {}, but redirects can't be turned off in the browser",
+            resp.url()
+        )),
+        (host::RedirectPolicy::Follow(_), Some(origin)) if origins.contains(&origin) => Ok(()),
+        (host::RedirectPolicy::Follow(_), _) => {
+            Err(format!("Redirected to unapproved URL {}", resp.url()))
+        }
+    }
+}
+
+fn response_headers(resp: &reqwest::Response) -> Vec<(String, Vec<u8>)> {
+    resp.headers()
+        .iter()
+        .map(|(name, value)| (name.to_string(), value.as_bytes().to_vec()))
+        .collect()
+}
@@ -0,0 +1,134 @@
+//! Coordinator sessions the host tracks, so they're cancelled once their TTL
+//! elapses or the plugin that opened them goes away.
+
+use super::*;
+
+/// Longest a coordinator session is tracked before the host cancels it,
+/// regardless of the TTL the coordinator returned
+const MAX_SESSION_TTL: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// An open coordinator session, tracked so it can be cancelled once it expires
+pub(super) struct CoordinatorSession {
+    pub(super) plugin_id: PluginId,
+    pub(super) coordinator_id: CoordinatorId,
+    pub(super) account: AccountId,
+    pub(super) expires: chrono::DateTime<chrono::Local>,
+}
+
+impl Host {
+    pub async fn coordinator_get_session(
+        &self,
+        instance_id: &InstanceId,
+        params: <coordinator::GetSession as RpcMethod>::Params,
+    ) -> Result<<coordinator::GetSession as RpcMethod>::Output, RpcError> {
+        let coordinator_id = params.0;
+        let plugin = self.get_entity_plugin_error(coordinator_id)?;
+
+        let api_version = self.get_api_version(&plugin.id()).unwrap_or(API_VERSION);
+        let session = if api_version < compat::SESSION_TTL {
+            //? Older coordinators hold sessions until they're cancelled
+            let account = self
+                .call_plugin(plugin, compat::LegacyGetSession, params)
+                .await
+                .context("Error calling GetSession")?;
+            coordinator::Session {
+                account,
+                ttl: MAX_SESSION_TTL,
+            }
+        } else {
+            self.call_plugin(plugin, coordinator::GetSession, params)
+                .await
+                .context("Error calling GetSession")?
+        };
+
+        let ttl = session.ttl.min(MAX_SESSION_TTL);
+        let expires = chrono::Local::now() + chrono::Duration::seconds(ttl.as_secs() as i64);
+
+        let mut sessions = self.sessions.lock().unwrap();
+        sessions.retain(|s| s.coordinator_id != coordinator_id || s.account != session.account);
+        sessions.push(CoordinatorSession {
+            plugin_id: instance_id.plugin,
+            coordinator_id,
+            account: session.account.clone(),
+            expires,
+        });
+        Ok(session)
+    }
+
+    pub async fn coordinator_cancel_session(
+        &self,
+        instance_id: &InstanceId,
+        params: <coordinator::CancelSession as RpcMethod>::Params,
+    ) -> Result<<coordinator::CancelSession as RpcMethod>::Output, RpcError> {
+        self.log_call(coordinator::CancelSession::NAME, instance_id.plugin);
+        let (coordinator_id, account) = params.clone();
+        let plugin = self.get_entity_plugin_error(coordinator_id)?;
+
+        //? Only the plugin that opened the session, or one the user granted
+        //? the coordinator to, may cancel it
+        let owns_session = self.sessions.lock().unwrap().iter().any(|s| {
+            s.coordinator_id == coordinator_id
+                && s.account == account
+                && s.plugin_id == instance_id.plugin
+        });
+        let granted = self
+            .permissions
+            .granted(&instance_id.plugin)
+            .contains(&coordinator_id.into());
+        if !owns_session && !granted {
+            return Err(RpcError::Custom(format!(
+                "Plugin {} doesn't own the session for {}",
+                instance_id.plugin, account
+            )));
+        }
+
+        self.call_plugin(plugin, coordinator::CancelSession, params)
+            .await
+            .context("Error calling CancelSession")?;
+        self.close_session(coordinator_id, &account);
+        Ok(())
+    }
+
+    /// Cancels every coordinator session whose TTL has elapsed. The frontend
+    /// is expected to call this periodically.
+    pub async fn expire_sessions(&self) {
+        let now = chrono::Local::now();
+        let expired: Vec<CoordinatorSession> = {
+            let mut sessions = self.sessions.lock().unwrap();
+            let (expired, open) = sessions.drain(..).partition(|s| s.expires <= now);
+            *sessions = open;
+            expired
+        };
+
+        for session in expired {
+            let Some(plugin) = self.get_entity_plugin(session.coordinator_id) else {
+                continue;
+            };
+
+            let params = (session.coordinator_id, session.account.clone());
+            match self
+                .call_plugin(plugin.clone(), coordinator::CancelSession, params)
+                .await
+            {
+                Ok(_) => info!("Expired session for {}", session.account),
+                Err(RpcError::MethodNotFound) => {
+                    warn!("Plugin {} doesn't implement CancelSession", plugin.id());
+                }
+                Err(e) => warn!(
+                    "Plugin {} failed to cancel session for {}: {}",
+                    plugin.id(),
+                    session.account,
+                    e
+                ),
+            }
+        }
+    }
+
+    /// Stops tracking a session the coordinator has closed.
+    pub(super) fn close_session(&self, coordinator_id: CoordinatorId, account: &AccountId) {
+        self.sessions
+            .lock()
+            .unwrap()
+            .retain(|s| s.coordinator_id != coordinator_id || &s.account != account);
+    }
+}
@@ -1,12 +1,164 @@
-use alloy::transports::http::reqwest;
+use std::time::Duration;
+
+use alloy::{primitives::U256, transports::http::reqwest};
 use serde::{Deserialize, Serialize};
-use tlock_hdk::{tlock_api::entities::EntityId, wasmi_plugin_hdk::plugin_id::PluginId};
+use serde_json::{Map, Value};
+use thiserror::Error;
+use tlock_hdk::{
+    tlock_api::{
+        caip::{AccountId, AssetId, ChainId},
+        component::Component,
+        entities::{EntityId, EthProviderId, PageId, VaultId},
+        host::EntityCapabilities,
+    },
+    wasmi_plugin_hdk::plugin_id::PluginId,
+};
+
+use crate::{
+    addressbook::SavedContact, audit::AuditEntry, host::UserRequest, keystore::KeystoreState,
+    signing::TrustedPublisher,
+};
+
+/// Version of the `HostState` schema written by this build. Bump it and add a
+/// step to `MIGRATIONS` whenever a change needs more than a serde default.
+pub const HOST_STATE_VERSION: u32 = 2;
+
+/// Steps migrating a serialized `HostState` from each version to the next,
+/// starting from version 1.
+const MIGRATIONS: &[fn(&mut Map<String, Value>)] = &[migrate_v1_to_v2];
+
+#[derive(Error, Debug)]
+pub enum StateError {
+    #[error("Unsupported state version {0}")]
+    UnsupportedVersion(u32),
+    #[error("State is not a JSON object")]
+    NotAnObject,
+    #[error("Invalid state JSON")]
+    Json(#[from] serde_json::Error),
+}
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct HostState {
+    /// Schema version, missing from states saved before versioning
+    #[serde(default = "unversioned")]
+    pub version: u32,
     pub plugins: Vec<PluginData>,
     pub entities: Vec<(EntityId, PluginId)>,
     pub state: Vec<((PluginId, String), Vec<u8>)>,
+    #[serde(default)]
+    pub active_chain: Option<ChainId>,
+    #[serde(default)]
+    pub address_book: Vec<SavedContact>,
+    #[serde(default)]
+    pub subscriptions: Vec<(String, PluginId)>,
+    #[serde(default)]
+    pub page_titles: Vec<(PageId, String)>,
+    #[serde(default)]
+    pub entity_capabilities: Vec<(EntityId, EntityCapabilities)>,
+    #[serde(default)]
+    pub deposit_watches: Vec<DepositWatch>,
+    #[serde(default)]
+    pub state_quotas: Vec<(PluginId, usize)>,
+    #[serde(default)]
+    pub permission_grants: Vec<(PluginId, EntityId)>,
+    #[serde(default)]
+    pub fetch_origins: Vec<(PluginId, String)>,
+    #[serde(default)]
+    pub trusted_publishers: Vec<TrustedPublisher>,
+    #[serde(default)]
+    pub interfaces: Vec<(PageId, Component)>,
+    #[serde(default)]
+    pub default_eth_providers: Vec<(ChainId, EthProviderId)>,
+    #[serde(default)]
+    pub entity_labels: Vec<(EntityId, EntityLabel)>,
+    #[serde(default)]
+    pub audit_log: Vec<AuditEntry>,
+    #[serde(default)]
+    pub tick_intervals: Vec<(PluginId, Duration)>,
+    #[serde(default)]
+    pub resource_limits: Vec<(PluginId, ResourceLimits)>,
+    #[serde(default)]
+    pub keystore: Option<KeystoreState>,
+    /// User requests that were pending when the state was saved
+    #[serde(default)]
+    pub pending_requests: Vec<UserRequest>,
+}
+
+impl HostState {
+    pub fn from_slice(bytes: &[u8]) -> Result<Self, StateError> {
+        Self::from_json(serde_json::from_slice(bytes)?)
+    }
+
+    /// Migrates a serialized state to `HOST_STATE_VERSION` and decodes it.
+    pub fn from_json(value: Value) -> Result<Self, StateError> {
+        let Value::Object(mut state) = value else {
+            return Err(StateError::NotAnObject);
+        };
+
+        let mut version = match state.get("version") {
+            Some(version) => serde_json::from_value(version.clone())?,
+            None => unversioned(),
+        };
+        if version == 0 || version > HOST_STATE_VERSION {
+            return Err(StateError::UnsupportedVersion(version));
+        }
+
+        for migration in &MIGRATIONS[(version - 1) as usize..] {
+            migration(&mut state);
+            version += 1;
+        }
+        state.insert("version".to_string(), version.into());
+
+        Ok(serde_json::from_value(Value::Object(state))?)
+    }
+}
+
+fn unversioned() -> u32 {
+    1
+}
+
+//? v2 snapshots include page interfaces, so v1 pages start without one until
+//? their plugin sets it again
+fn migrate_v1_to_v2(state: &mut Map<String, Value>) {
+    state
+        .entry("interfaces")
+        .or_insert_with(|| Value::Array(Vec::new()));
+}
+
+/// A user-assigned name and icon shown in place of an entity's ID
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EntityLabel {
+    pub label: String,
+    /// A short string, typically an emoji, shown before the label
+    #[serde(default)]
+    pub icon: Option<String>,
+}
+
+/// Caps on the resources a plugin's instances may use
+// TODO: Add a fuel budget per call once the plugin runtime can meter fuel
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ResourceLimits {
+    /// Largest size, in bytes, an instance's linear memory may grow to
+    pub max_memory_bytes: u64,
+}
+
+impl Default for ResourceLimits {
+    fn default() -> Self {
+        Self {
+            max_memory_bytes: 256 * 1024 * 1024,
+        }
+    }
+}
+
+/// A `host_watch_deposit` registration and the last balance seen for it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DepositWatch {
+    pub plugin_id: PluginId,
+    pub vault_id: VaultId,
+    pub provider_id: EthProviderId,
+    pub account: AccountId,
+    pub asset: AssetId,
+    pub balance: U256,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
@@ -14,6 +166,13 @@ pub struct PluginData {
     pub id: PluginId,
     pub name: String,
     pub source: PluginSource,
+    /// SHA-256 of the build that was loaded, which a URL source must still
+    /// serve when the state is restored
+    #[serde(default)]
+    pub sha256: Option<[u8; 32]>,
+    /// Key of the trusted publisher that signed the build, if any
+    #[serde(default)]
+    pub publisher: Option<[u8; 32]>,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
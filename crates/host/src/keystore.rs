@@ -0,0 +1,295 @@
+//! Encrypted storage for plugin secrets, such as private keys.
+//!
+//! Secrets are sealed with XChaCha20-Poly1305 under a key derived from the
+//! user's password with Argon2id. The password is never stored, and the
+//! derived key is only held in memory while the keystore is unlocked. A
+//! verifier, a known value sealed under the same key, lets the host reject a
+//! wrong password before it's used.
+
+use std::{collections::HashMap, sync::Mutex};
+
+use argon2::Argon2;
+use chacha20poly1305::{
+    KeyInit, XChaCha20Poly1305, XNonce,
+    aead::{Aead, Payload},
+};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use tlock_hdk::wasmi_plugin_hdk::plugin_id::PluginId;
+use uuid::Uuid;
+use zeroize::Zeroizing;
+
+const VERIFIER: &[u8] = b"tlock keystore";
+
+#[derive(Debug, Error)]
+pub enum KeystoreError {
+    #[error("Keystore is locked")]
+    Locked,
+    #[error("Incorrect password")]
+    IncorrectPassword,
+    #[error("Failed to derive key: {0}")]
+    KeyDerivation(String),
+    #[error("Secret could not be decrypted")]
+    Corrupted,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Sealed {
+    pub(crate) nonce: [u8; 24],
+    pub(crate) ciphertext: Vec<u8>,
+}
+
+/// The keystore as persisted with the host's state. Secrets stay sealed.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KeystoreState {
+    salt: [u8; 16],
+    verifier: Sealed,
+    secrets: Vec<(PluginId, String, Sealed)>,
+}
+
+pub(crate) type Key = Zeroizing<[u8; 32]>;
+
+#[derive(Default)]
+pub struct Keystore {
+    /// The salt and verifier, set once the user first chooses a password
+    header: Mutex<Option<([u8; 16], Sealed)>>,
+    secrets: Mutex<HashMap<(PluginId, String), Sealed>>,
+    key: Mutex<Option<Key>>,
+}
+
+impl Keystore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn from_state(state: Option<KeystoreState>) -> Self {
+        let Some(state) = state else {
+            return Self::new();
+        };
+        let secrets = state
+            .secrets
+            .into_iter()
+            .map(|(plugin_id, name, sealed)| ((plugin_id, name), sealed))
+            .collect();
+        Self {
+            header: Mutex::new(Some((state.salt, state.verifier))),
+            secrets: Mutex::new(secrets),
+            key: Mutex::new(None),
+        }
+    }
+
+    pub fn state(&self) -> Option<KeystoreState> {
+        let (salt, verifier) = self.header.lock().unwrap().clone()?;
+        let secrets = self
+            .secrets
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|((plugin_id, name), sealed)| (*plugin_id, name.clone(), sealed.clone()))
+            .collect();
+        Some(KeystoreState {
+            salt,
+            verifier,
+            secrets,
+        })
+    }
+
+    /// Whether the user has chosen a password yet.
+    pub fn is_initialized(&self) -> bool {
+        self.header.lock().unwrap().is_some()
+    }
+
+    pub fn is_unlocked(&self) -> bool {
+        self.key.lock().unwrap().is_some()
+    }
+
+    /// Unlocks the keystore. The first unlock sets the password.
+    pub fn unlock(&self, password: &str) -> Result<(), KeystoreError> {
+        let mut header = self.header.lock().unwrap();
+        let key = match &*header {
+            Some((salt, verifier)) => {
+                let key = derive_key(password, salt)?;
+                open(&key, verifier, b"").map_err(|_| KeystoreError::IncorrectPassword)?;
+                key
+            }
+            None => {
+                let salt = random_bytes();
+                let key = derive_key(password, &salt)?;
+                *header = Some((salt, seal(&key, VERIFIER, b"")));
+                key
+            }
+        };
+        *self.key.lock().unwrap() = Some(key);
+        Ok(())
+    }
+
+    /// Forgets the derived key until the keystore is next unlocked.
+    pub fn lock(&self) {
+        *self.key.lock().unwrap() = None;
+    }
+
+    pub fn set(&self, plugin_id: PluginId, name: &str, value: &[u8]) -> Result<(), KeystoreError> {
+        let key = self.key.lock().unwrap();
+        let key = key.as_ref().ok_or(KeystoreError::Locked)?;
+        let sealed = seal(key, value, name.as_bytes());
+        self.secrets
+            .lock()
+            .unwrap()
+            .insert((plugin_id, name.to_string()), sealed);
+        Ok(())
+    }
+
+    pub fn get(&self, plugin_id: PluginId, name: &str) -> Result<Option<Vec<u8>>, KeystoreError> {
+        let key = self.key.lock().unwrap();
+        let key = key.as_ref().ok_or(KeystoreError::Locked)?;
+        let secrets = self.secrets.lock().unwrap();
+        let Some(sealed) = secrets.get(&(plugin_id, name.to_string())) else {
+            return Ok(None);
+        };
+        open(key, sealed, name.as_bytes()).map(Some)
+    }
+
+    pub fn delete(&self, plugin_id: PluginId, name: &str) {
+        let mut secrets = self.secrets.lock().unwrap();
+        secrets.remove(&(plugin_id, name.to_string()));
+    }
+
+    /// Moves a plugin's secrets to a new ID, e.g. when it's upgraded.
+    pub fn transfer(&self, old_id: &PluginId, new_id: PluginId) {
+        let mut secrets = self.secrets.lock().unwrap();
+        let moved: Vec<_> = secrets
+            .keys()
+            .filter(|(id, _)| id == old_id)
+            .cloned()
+            .collect();
+        for key in moved {
+            if let Some(sealed) = secrets.remove(&key) {
+                secrets.insert((new_id, key.1), sealed);
+            }
+        }
+    }
+
+    pub fn remove_plugin(&self, plugin_id: &PluginId) {
+        self.secrets
+            .lock()
+            .unwrap()
+            .retain(|(id, _), _| id != plugin_id);
+    }
+}
+
+pub(crate) fn derive_key(password: &str, salt: &[u8; 16]) -> Result<Key, KeystoreError> {
+    let mut key = Zeroizing::new([0u8; 32]);
+    Argon2::default()
+        .hash_password_into(password.as_bytes(), salt, &mut key[..])
+        .map_err(|e| KeystoreError::KeyDerivation(e.to_string()))?;
+    Ok(key)
+}
+
+/// Seals `value`, binding it to `aad` so it can't be passed off as a
+/// different secret.
+pub(crate) fn seal(key: &Key, value: &[u8], aad: &[u8]) -> Sealed {
+    let cipher = XChaCha20Poly1305::new((&**key).into());
+    let nonce: [u8; 24] = random_bytes();
+    let payload = Payload { msg: value, aad };
+    //? Encryption only fails for messages of hundreds of gigabytes
+    let ciphertext = cipher
+        .encrypt(XNonce::from_slice(&nonce), payload)
+        .expect("secret too large to encrypt");
+    Sealed { nonce, ciphertext }
+}
+
+pub(crate) fn open(key: &Key, sealed: &Sealed, aad: &[u8]) -> Result<Vec<u8>, KeystoreError> {
+    let cipher = XChaCha20Poly1305::new((&**key).into());
+    let payload = Payload {
+        msg: &sealed.ciphertext,
+        aad,
+    };
+    cipher
+        .decrypt(XNonce::from_slice(&sealed.nonce), payload)
+        .map_err(|_| KeystoreError::Corrupted)
+}
+
+/// Returns `N` random bytes.
+pub(crate) fn random_bytes<const N: usize>() -> [u8; N] {
+    //? v4 UUIDs are drawn from the platform's secure RNG, which saves pulling
+    //? in one for wasm. Each contributes 122 random bits.
+    let mut bytes = [0u8; N];
+    for chunk in bytes.chunks_mut(16) {
+        let id = Uuid::new_v4();
+        chunk.copy_from_slice(&id.as_bytes()[..chunk.len()]);
+    }
+    bytes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn secrets_survive_a_save_and_restore() {
+        let keystore = Keystore::new();
+        let plugin = PluginId::from(1u128);
+        assert!(matches!(
+            keystore.set(plugin, "key", b"secret"),
+            Err(KeystoreError::Locked)
+        ));
+
+        keystore.unlock("password").unwrap();
+        keystore.set(plugin, "key", b"secret").unwrap();
+
+        let restored = Keystore::from_state(keystore.state());
+        assert!(restored.is_initialized());
+        assert!(matches!(
+            restored.get(plugin, "key"),
+            Err(KeystoreError::Locked)
+        ));
+        restored.unlock("password").unwrap();
+        assert_eq!(restored.get(plugin, "key").unwrap().unwrap(), b"secret");
+        assert_eq!(restored.get(plugin, "other").unwrap(), None);
+    }
+
+    #[test]
+    fn a_wrong_password_is_rejected() {
+        let keystore = Keystore::new();
+        keystore.unlock("password").unwrap();
+        keystore.lock();
+
+        assert!(matches!(
+            keystore.unlock("wrong"),
+            Err(KeystoreError::IncorrectPassword)
+        ));
+        assert!(!keystore.is_unlocked());
+        keystore.unlock("password").unwrap();
+    }
+
+    #[test]
+    fn secrets_follow_their_plugin_through_upgrades() {
+        let keystore = Keystore::new();
+        let (old, new) = (PluginId::from(1u128), PluginId::from(2u128));
+        keystore.unlock("password").unwrap();
+        keystore.set(old, "key", b"secret").unwrap();
+
+        keystore.transfer(&old, new);
+        assert_eq!(keystore.get(old, "key").unwrap(), None);
+        assert_eq!(keystore.get(new, "key").unwrap().unwrap(), b"secret");
+    }
+
+    #[test]
+    fn a_secret_only_opens_under_its_own_name() {
+        let keystore = Keystore::new();
+        let plugin = PluginId::from(1u128);
+        keystore.unlock("password").unwrap();
+        keystore.set(plugin, "spending", b"secret").unwrap();
+
+        //? Swap the sealed secret to a different name, as a tampered state
+        //? could
+        let mut state = keystore.state().unwrap();
+        state.secrets[0].1 = "viewing".to_string();
+        let restored = Keystore::from_state(Some(state));
+        restored.unlock("password").unwrap();
+        assert!(matches!(
+            restored.get(plugin, "viewing"),
+            Err(KeystoreError::Corrupted)
+        ));
+    }
+}
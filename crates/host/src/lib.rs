@@ -1,2 +1,30 @@
+pub mod abi;
+pub mod addressbook;
+pub mod audit;
+pub mod compat;
+pub mod ens;
+pub mod erc20;
+pub mod eth_cache;
+pub mod event_bus;
+pub mod event_log;
+#[cfg(feature = "test-harness")]
+pub mod harness;
+pub mod history;
 pub mod host;
 pub mod host_state;
+pub mod keystore;
+pub mod metrics;
+pub mod nonces;
+pub mod permissions;
+pub mod rate_limit;
+pub mod recording;
+pub mod scheduler;
+pub mod signing;
+pub mod simulation;
+pub mod state_crypto;
+pub mod state_store;
+pub mod ticker;
+mod time;
+pub mod transactions;
+pub mod wasm_memory;
+pub mod ws;
@@ -0,0 +1,93 @@
+//! Per-plugin, per-method statistics for calls the host makes into plugins.
+
+use std::{collections::HashMap, sync::Mutex};
+
+use tlock_hdk::wasmi_plugin_hdk::plugin_id::PluginId;
+
+/// Upper bounds, in milliseconds, of the latency histogram's buckets. Calls
+/// slower than the last bound are counted in a final overflow bucket.
+pub const LATENCY_BUCKETS_MS: [u64; 8] = [1, 5, 10, 50, 100, 500, 1000, 5000];
+
+// TODO: Record wasm fuel consumed once the plugin runtime reports it per call
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MethodMetrics {
+    pub calls: u64,
+    pub errors: u64,
+    pub total_ms: u64,
+    pub max_ms: u64,
+    /// Call counts per `LATENCY_BUCKETS_MS` bucket, plus the overflow bucket
+    pub latency_histogram: [u64; LATENCY_BUCKETS_MS.len() + 1],
+}
+
+impl MethodMetrics {
+    pub fn mean_ms(&self) -> f64 {
+        if self.calls == 0 {
+            return 0.0;
+        }
+        self.total_ms as f64 / self.calls as f64
+    }
+
+    /// Fraction of calls that returned an error or timed out.
+    pub fn error_rate(&self) -> f64 {
+        if self.calls == 0 {
+            return 0.0;
+        }
+        self.errors as f64 / self.calls as f64
+    }
+
+    fn record(&mut self, elapsed_ms: u64, ok: bool) {
+        self.calls += 1;
+        if !ok {
+            self.errors += 1;
+        }
+        self.total_ms += elapsed_ms;
+        self.max_ms = self.max_ms.max(elapsed_ms);
+
+        let bucket = LATENCY_BUCKETS_MS
+            .iter()
+            .position(|bound| elapsed_ms <= *bound)
+            .unwrap_or(LATENCY_BUCKETS_MS.len());
+        self.latency_histogram[bucket] += 1;
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct Metrics {
+    methods: Mutex<HashMap<(PluginId, String), MethodMetrics>>,
+}
+
+impl Metrics {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn record(&self, plugin_id: PluginId, method: &str, elapsed_ms: u64, ok: bool) {
+        let mut methods = self.methods.lock().unwrap();
+        methods
+            .entry((plugin_id, method.to_string()))
+            .or_default()
+            .record(elapsed_ms, ok);
+    }
+
+    /// Returns every recorded method's metrics, sorted by plugin then method.
+    pub fn snapshot(&self) -> Vec<(PluginId, String, MethodMetrics)> {
+        let methods = self.methods.lock().unwrap();
+        let mut snapshot: Vec<_> = methods
+            .iter()
+            .map(|((plugin_id, method), metrics)| (*plugin_id, method.clone(), metrics.clone()))
+            .collect();
+        snapshot.sort_by(|a, b| (a.0.to_string(), &a.1).cmp(&(b.0.to_string(), &b.1)));
+        snapshot
+    }
+
+    pub fn remove_plugin(&self, plugin_id: &PluginId) {
+        self.methods
+            .lock()
+            .unwrap()
+            .retain(|(p, _), _| p != plugin_id);
+    }
+
+    pub fn reset(&self) {
+        self.methods.lock().unwrap().clear();
+    }
+}
@@ -0,0 +1,198 @@
+//! Leases EVM account nonces to plugins, so plugins sending from the same
+//! account concurrently don't build transactions with the same nonce.
+//!
+//! The provider's pending transaction count is the floor for every lease. On
+//! top of it the host tracks the next nonce it hasn't leased, and nonces that
+//! were leased but released unused, which are handed out again first so they
+//! don't leave a gap that blocks later transactions.
+//!
+//! Each lease records the plugin that took it, and only that plugin can
+//! release it. Any plugin can lease for any address, so a plugin can hold at
+//! most `MAX_LEASES` unused nonces per account, limiting how far it can push
+//! another plugin's sends past a gap it never fills.
+
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap},
+    sync::Mutex,
+};
+
+use alloy::primitives::Address;
+use tlock_hdk::{tlock_api::caip::ChainId, wasmi_plugin_hdk::plugin_id::PluginId};
+
+/// Most nonces a plugin can hold leased and unused for a single account.
+pub const MAX_LEASES: usize = 16;
+
+#[derive(Debug, Default)]
+struct AccountNonces {
+    next: u64,
+    released: BTreeSet<u64>,
+    leases: BTreeMap<u64, PluginId>,
+}
+
+impl AccountNonces {
+    fn release(&mut self, nonce: u64) {
+        self.released.insert(nonce);
+        while self.next > 0 && self.released.remove(&(self.next - 1)) {
+            self.next -= 1;
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct NonceManager {
+    accounts: Mutex<HashMap<(ChainId, Address), AccountNonces>>,
+}
+
+impl NonceManager {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Leases a nonce for the account to `plugin_id`, given the account's
+    /// pending transaction count.
+    pub fn lease(
+        &self,
+        plugin_id: PluginId,
+        chain_id: ChainId,
+        address: Address,
+        pending: u64,
+    ) -> Result<u64, String> {
+        let mut accounts = self.accounts.lock().unwrap();
+        let nonces = accounts.entry((chain_id, address)).or_default();
+
+        //? Nonces below the pending count have been used, by their lease or
+        //? elsewhere
+        nonces.released.retain(|n| *n >= pending);
+        nonces.leases.retain(|n, _| *n >= pending);
+
+        let held = nonces.leases.values().filter(|p| **p == plugin_id).count();
+        if held >= MAX_LEASES {
+            return Err(format!(
+                "Plugin already holds {} unused nonces for {}",
+                held, address
+            ));
+        }
+
+        let nonce = match nonces.released.pop_first() {
+            Some(nonce) => nonce,
+            None => {
+                let nonce = nonces.next.max(pending);
+                nonces.next = nonce + 1;
+                nonce
+            }
+        };
+        nonces.leases.insert(nonce, plugin_id);
+        Ok(nonce)
+    }
+
+    /// Forgets every lease.
+    pub fn clear(&self) {
+        self.accounts.lock().unwrap().clear();
+    }
+
+    /// Returns a nonce leased to `plugin_id` that won't be used, so it can be
+    /// leased again.
+    pub fn release(
+        &self,
+        plugin_id: PluginId,
+        chain_id: ChainId,
+        address: Address,
+        nonce: u64,
+    ) -> Result<(), String> {
+        let mut accounts = self.accounts.lock().unwrap();
+        let nonces = accounts.get_mut(&(chain_id, address));
+        let Some(nonces) = nonces.filter(|n| n.leases.get(&nonce) == Some(&plugin_id)) else {
+            return Err(format!(
+                "Nonce {} for {} isn't leased to this plugin",
+                nonce, address
+            ));
+        };
+
+        nonces.leases.remove(&nonce);
+        nonces.release(nonce);
+        Ok(())
+    }
+
+    /// Releases every nonce leased to a plugin, e.g. when it's unloaded.
+    pub fn remove_plugin(&self, plugin_id: &PluginId) {
+        for nonces in self.accounts.lock().unwrap().values_mut() {
+            let leased: Vec<u64> = nonces
+                .leases
+                .iter()
+                .filter(|(_, p)| *p == plugin_id)
+                .map(|(n, _)| *n)
+                .collect();
+            for nonce in leased {
+                nonces.leases.remove(&nonce);
+                nonces.release(nonce);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lease(nonces: &NonceManager, plugin_id: PluginId, pending: u64) -> Result<u64, String> {
+        nonces.lease(
+            plugin_id,
+            ChainId::new_evm(1),
+            Address::repeat_byte(1),
+            pending,
+        )
+    }
+
+    fn release(nonces: &NonceManager, plugin_id: PluginId, nonce: u64) -> Result<(), String> {
+        nonces.release(
+            plugin_id,
+            ChainId::new_evm(1),
+            Address::repeat_byte(1),
+            nonce,
+        )
+    }
+
+    #[test]
+    fn only_the_leasing_plugin_releases_a_nonce() {
+        let nonces = NonceManager::new();
+        let (plugin, other) = (PluginId::from(1u128), PluginId::from(2u128));
+        assert_eq!(lease(&nonces, plugin, 3).unwrap(), 3);
+        assert_eq!(lease(&nonces, plugin, 3).unwrap(), 4);
+
+        assert!(release(&nonces, other, 3).is_err());
+        assert!(release(&nonces, plugin, 5).is_err());
+        release(&nonces, plugin, 3).unwrap();
+        assert!(release(&nonces, plugin, 3).is_err());
+
+        //? The released nonce is handed out again before any higher one
+        assert_eq!(lease(&nonces, other, 3).unwrap(), 3);
+        assert_eq!(lease(&nonces, other, 3).unwrap(), 5);
+    }
+
+    #[test]
+    fn plugins_hold_a_limited_number_of_unused_nonces() {
+        let nonces = NonceManager::new();
+        let (plugin, other) = (PluginId::from(1u128), PluginId::from(2u128));
+        for _ in 0..MAX_LEASES {
+            lease(&nonces, plugin, 0).unwrap();
+        }
+        assert!(lease(&nonces, plugin, 0).is_err());
+        assert!(lease(&nonces, other, 0).is_ok());
+
+        //? Nonces below the pending count were used, freeing their leases
+        assert!(lease(&nonces, plugin, 1).is_ok());
+    }
+
+    #[test]
+    fn unloading_a_plugin_releases_its_nonces() {
+        let nonces = NonceManager::new();
+        let (plugin, other) = (PluginId::from(1u128), PluginId::from(2u128));
+        assert_eq!(lease(&nonces, plugin, 0).unwrap(), 0);
+        assert_eq!(lease(&nonces, other, 0).unwrap(), 1);
+        assert_eq!(lease(&nonces, plugin, 0).unwrap(), 2);
+
+        nonces.remove_plugin(&plugin);
+        assert_eq!(lease(&nonces, other, 0).unwrap(), 0);
+        assert_eq!(lease(&nonces, other, 0).unwrap(), 2);
+    }
+}
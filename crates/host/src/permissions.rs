@@ -0,0 +1,276 @@
+//! Remembered user decisions letting a plugin use an entity without being
+//! prompted again.
+//!
+//! Besides standing grants, which last until revoked, the user can open a
+//! session grant: a time-boxed permission to call one method on an entity,
+//! optionally capped to a total amount of one asset. Session grants aren't
+//! persisted.
+
+use std::{sync::Mutex, time::Duration};
+
+use alloy::primitives::U256;
+use chrono::{DateTime, Local};
+use tlock_hdk::{
+    tlock_api::{caip::AssetId, entities::EntityId},
+    wasmi_plugin_hdk::plugin_id::PluginId,
+};
+use uuid::Uuid;
+
+/// Longest a session grant can last
+const MAX_SESSION_TTL: Duration = Duration::from_secs(24 * 60 * 60);
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SessionGrant {
+    pub id: Uuid,
+    pub plugin_id: PluginId,
+    pub entity_id: EntityId,
+    pub method: String,
+    pub limit: Option<SpendLimit>,
+    pub expires: DateTime<Local>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SpendLimit {
+    pub asset: AssetId,
+    pub max: U256,
+    /// Amount spent through the grant so far
+    pub spent: U256,
+}
+
+/// The terms the user approved a session grant with
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SessionTerms {
+    pub ttl: Duration,
+    /// Most of the call's asset that may be spent through the grant, or None
+    /// for no limit
+    pub limit: Option<U256>,
+}
+
+/// Tracks which entities each plugin has been granted. Grants are kept in the
+/// order they were made, so the oldest matching grant is used first.
+#[derive(Debug, Default)]
+pub struct Permissions {
+    grants: Mutex<Vec<(PluginId, EntityId)>>,
+    sessions: Mutex<Vec<SessionGrant>>,
+}
+
+impl Permissions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn from_grants(grants: Vec<(PluginId, EntityId)>) -> Self {
+        let permissions = Self::new();
+        for (plugin_id, entity_id) in grants {
+            permissions.grant(plugin_id, entity_id);
+        }
+        permissions
+    }
+
+    pub fn grant(&self, plugin_id: PluginId, entity_id: EntityId) {
+        let mut grants = self.grants.lock().unwrap();
+        if !grants.contains(&(plugin_id, entity_id)) {
+            grants.push((plugin_id, entity_id));
+        }
+    }
+
+    pub fn revoke(&self, plugin_id: &PluginId, entity_id: &EntityId) {
+        let mut grants = self.grants.lock().unwrap();
+        grants.retain(|(p, e)| p != plugin_id || e != entity_id);
+    }
+
+    /// Removes every grant held by a plugin.
+    pub fn remove_plugin(&self, plugin_id: &PluginId) {
+        let mut grants = self.grants.lock().unwrap();
+        grants.retain(|(p, _)| p != plugin_id);
+        let mut sessions = self.sessions.lock().unwrap();
+        sessions.retain(|s| &s.plugin_id != plugin_id);
+    }
+
+    /// Removes every grant to entities that no longer satisfy `keep`.
+    pub fn retain_entities(&self, mut keep: impl FnMut(&EntityId) -> bool) {
+        let mut grants = self.grants.lock().unwrap();
+        grants.retain(|(_, e)| keep(e));
+        let mut sessions = self.sessions.lock().unwrap();
+        sessions.retain(|s| keep(&s.entity_id));
+    }
+
+    /// Moves all of a plugin's grants to another plugin, used when a plugin
+    /// is upgraded.
+    pub fn transfer(&self, from: &PluginId, to: PluginId) {
+        let mut grants = self.grants.lock().unwrap();
+        for (plugin_id, _) in grants.iter_mut() {
+            if plugin_id == from {
+                *plugin_id = to;
+            }
+        }
+        let mut sessions = self.sessions.lock().unwrap();
+        for session in sessions.iter_mut() {
+            if &session.plugin_id == from {
+                session.plugin_id = to;
+            }
+        }
+    }
+
+    /// Opens a session grant letting a plugin call `method` on an entity
+    /// until `ttl`, at most a day, has passed. With a limit, the grant only
+    /// covers calls spending `asset`, up to `max` in total.
+    pub fn grant_session(
+        &self,
+        plugin_id: PluginId,
+        entity_id: EntityId,
+        method: &str,
+        limit: Option<(AssetId, U256)>,
+        ttl: Duration,
+    ) -> Uuid {
+        let id = Uuid::new_v4();
+        let ttl = chrono::Duration::from_std(ttl.min(MAX_SESSION_TTL)).unwrap_or_default();
+        let expires = Local::now() + ttl;
+        self.sessions.lock().unwrap().push(SessionGrant {
+            id,
+            plugin_id,
+            entity_id,
+            method: method.to_string(),
+            limit: limit.map(|(asset, max)| SpendLimit {
+                asset,
+                max,
+                spent: U256::ZERO,
+            }),
+            expires,
+        });
+        id
+    }
+
+    pub fn revoke_session(&self, id: &Uuid) {
+        self.sessions.lock().unwrap().retain(|s| &s.id != id);
+    }
+
+    /// Session grants that haven't expired, oldest first.
+    pub fn sessions(&self) -> Vec<SessionGrant> {
+        let mut sessions = self.sessions.lock().unwrap();
+        let now = Local::now();
+        sessions.retain(|s| s.expires > now);
+        sessions.clone()
+    }
+
+    /// Checks the call against the plugin's session grants. If one covers it,
+    /// the call's spend is counted against that grant and true is returned.
+    pub fn use_session(
+        &self,
+        plugin_id: &PluginId,
+        entity_id: &EntityId,
+        method: &str,
+        spend: Option<(&AssetId, U256)>,
+    ) -> bool {
+        let mut sessions = self.sessions.lock().unwrap();
+        let now = Local::now();
+        sessions.retain(|s| s.expires > now);
+
+        let covering = sessions.iter_mut().find(|s| {
+            if &s.plugin_id != plugin_id || &s.entity_id != entity_id || s.method != method {
+                return false;
+            }
+            let Some(limit) = &s.limit else {
+                return true;
+            };
+            spend.is_some_and(|(asset, amount)| {
+                asset == &limit.asset
+                    && limit
+                        .spent
+                        .checked_add(amount)
+                        .is_some_and(|total| total <= limit.max)
+            })
+        });
+        let Some(session) = covering else {
+            return false;
+        };
+
+        if let (Some(limit), Some((_, amount))) = (session.limit.as_mut(), spend) {
+            limit.spent += amount;
+        }
+        true
+    }
+
+    /// Entities granted to a plugin, oldest grant first.
+    pub fn granted(&self, plugin_id: &PluginId) -> Vec<EntityId> {
+        let grants = self.grants.lock().unwrap();
+        grants
+            .iter()
+            .filter(|(p, _)| p == plugin_id)
+            .map(|(_, e)| *e)
+            .collect()
+    }
+
+    pub fn grants(&self) -> Vec<(PluginId, EntityId)> {
+        self.grants.lock().unwrap().clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloy::primitives::Address;
+    use tlock_hdk::tlock_api::entities::VaultId;
+
+    use super::*;
+
+    const METHOD: &str = "vault_withdraw";
+    const TTL: Duration = Duration::from_secs(60);
+
+    #[test]
+    fn limited_sessions_count_spends_against_their_cap() {
+        let permissions = Permissions::new();
+        let vault = EntityId::Vault(VaultId::new());
+        let plugin = PluginId::from(1u128);
+        let eth = AssetId::eth(1);
+        let limit = (eth.clone(), U256::from(10));
+        permissions.grant_session(plugin, vault, METHOD, Some(limit), TTL);
+
+        let spend = |amount: u64| Some((&eth, U256::from(amount)));
+        assert!(permissions.use_session(&plugin, &vault, METHOD, spend(6)));
+        assert!(!permissions.use_session(&plugin, &vault, METHOD, spend(5)));
+        assert!(permissions.use_session(&plugin, &vault, METHOD, spend(4)));
+        assert!(!permissions.use_session(&plugin, &vault, METHOD, spend(1)));
+
+        let spent = permissions.sessions()[0].limit.as_ref().unwrap().spent;
+        assert_eq!(spent, U256::from(10));
+    }
+
+    #[test]
+    fn limited_sessions_only_cover_their_asset() {
+        let permissions = Permissions::new();
+        let vault = EntityId::Vault(VaultId::new());
+        let plugin = PluginId::from(1u128);
+        let limit = (AssetId::eth(1), U256::from(10));
+        permissions.grant_session(plugin, vault, METHOD, Some(limit), TTL);
+
+        let token = AssetId::erc20(1, Address::repeat_byte(1));
+        let spend = Some((&token, U256::from(1)));
+        assert!(!permissions.use_session(&plugin, &vault, METHOD, spend));
+        assert!(!permissions.use_session(&plugin, &vault, METHOD, None));
+    }
+
+    #[test]
+    fn sessions_only_cover_their_plugin_entity_and_method() {
+        let permissions = Permissions::new();
+        let vault = EntityId::Vault(VaultId::new());
+        let (plugin, other) = (PluginId::from(1u128), PluginId::from(2u128));
+        permissions.grant_session(plugin, vault, METHOD, None, TTL);
+
+        assert!(permissions.use_session(&plugin, &vault, METHOD, None));
+        assert!(!permissions.use_session(&other, &vault, METHOD, None));
+        let other_vault = EntityId::Vault(VaultId::new());
+        assert!(!permissions.use_session(&plugin, &other_vault, METHOD, None));
+        assert!(!permissions.use_session(&plugin, &vault, "eth_sendRawTransaction", None));
+    }
+
+    #[test]
+    fn expired_sessions_cover_nothing() {
+        let permissions = Permissions::new();
+        let vault = EntityId::Vault(VaultId::new());
+        let plugin = PluginId::from(1u128);
+        permissions.grant_session(plugin, vault, METHOD, None, Duration::ZERO);
+
+        assert!(!permissions.use_session(&plugin, &vault, METHOD, None));
+        assert!(permissions.sessions().is_empty());
+    }
+}
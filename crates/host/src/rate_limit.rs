@@ -0,0 +1,220 @@
+//! Token-bucket rate limits on the host methods plugins call.
+//!
+//! Methods are grouped into classes, and each plugin gets its own bucket per
+//! class. A bucket refills at the class's rate up to its burst size, and each
+//! call takes one token. Calls to methods outside every class aren't limited.
+
+use std::{collections::HashMap, sync::Mutex};
+
+use chrono::{DateTime, Local};
+use tlock_hdk::{
+    tlock_api::{RpcMethod, btc, erc4337, eth, host, sol, state},
+    wasmi_plugin_hdk::plugin_id::PluginId,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MethodClass {
+    /// Outbound HTTP requests and WebSocket messages
+    Fetch,
+    /// Transactions broadcast to a chain
+    Transactions,
+    /// Writes to the plugin's stored state
+    StateWrites,
+}
+
+impl MethodClass {
+    pub const ALL: [MethodClass; 3] = [
+        MethodClass::Fetch,
+        MethodClass::Transactions,
+        MethodClass::StateWrites,
+    ];
+
+    pub fn of(method: &str) -> Option<Self> {
+        match method {
+            m if m == host::Fetch::NAME
+                || m == host::FetchStream::NAME
+                || m == host::WsConnect::NAME
+                || m == host::WsSend::NAME =>
+            {
+                Some(Self::Fetch)
+            }
+            m if m == eth::SendRawTransaction::NAME
+                || m == sol::SendTransaction::NAME
+                || m == btc::BroadcastTx::NAME
+                || m == erc4337::SendUserOp::NAME =>
+            {
+                Some(Self::Transactions)
+            }
+            m if m == state::SetKey::NAME || m == state::DeleteKey::NAME => Some(Self::StateWrites),
+            _ => None,
+        }
+    }
+
+    pub fn default_limit(self) -> RateLimit {
+        match self {
+            MethodClass::Fetch => RateLimit::new(20.0, 50.0),
+            MethodClass::Transactions => RateLimit::new(1.0, 5.0),
+            MethodClass::StateWrites => RateLimit::new(100.0, 200.0),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RateLimit {
+    /// Tokens added to the bucket each second
+    pub per_second: f64,
+    /// Most tokens the bucket can hold, i.e. the largest burst of calls
+    pub burst: f64,
+}
+
+impl RateLimit {
+    pub fn new(per_second: f64, burst: f64) -> Self {
+        Self { per_second, burst }
+    }
+}
+
+#[derive(Debug)]
+struct Bucket {
+    tokens: f64,
+    updated: DateTime<Local>,
+}
+
+#[derive(Debug)]
+pub struct RateLimiter {
+    limits: Mutex<HashMap<MethodClass, RateLimit>>,
+    buckets: Mutex<HashMap<(PluginId, MethodClass), Bucket>>,
+}
+
+impl Default for RateLimiter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl RateLimiter {
+    pub fn new() -> Self {
+        let limits = MethodClass::ALL
+            .into_iter()
+            .map(|class| (class, class.default_limit()))
+            .collect();
+        Self {
+            limits: Mutex::new(limits),
+            buckets: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Takes a token for the call if one is available. Returns false if the
+    /// plugin has exceeded the method's rate limit.
+    pub fn check(&self, plugin_id: PluginId, method: &str) -> bool {
+        self.check_at(plugin_id, method, Local::now())
+    }
+
+    fn check_at(&self, plugin_id: PluginId, method: &str, now: DateTime<Local>) -> bool {
+        let Some(class) = MethodClass::of(method) else {
+            return true;
+        };
+        let limit = self.limit(class);
+
+        let mut buckets = self.buckets.lock().unwrap();
+        let bucket = buckets.entry((plugin_id, class)).or_insert(Bucket {
+            tokens: limit.burst,
+            updated: now,
+        });
+
+        let elapsed = (now - bucket.updated).num_milliseconds().max(0) as f64 / 1000.0;
+        bucket.tokens = (bucket.tokens + elapsed * limit.per_second).min(limit.burst);
+        bucket.updated = now;
+
+        if bucket.tokens < 1.0 {
+            return false;
+        }
+        bucket.tokens -= 1.0;
+        true
+    }
+
+    pub fn limit(&self, class: MethodClass) -> RateLimit {
+        let limits = self.limits.lock().unwrap();
+        limits
+            .get(&class)
+            .copied()
+            .unwrap_or_else(|| class.default_limit())
+    }
+
+    pub fn set_limit(&self, class: MethodClass, limit: RateLimit) {
+        self.limits.lock().unwrap().insert(class, limit);
+    }
+
+    pub fn remove_plugin(&self, plugin_id: &PluginId) {
+        self.buckets
+            .lock()
+            .unwrap()
+            .retain(|(id, _), _| id != plugin_id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Duration;
+    use tlock_hdk::tlock_api::global;
+
+    use super::*;
+
+    #[test]
+    fn broadcasts_on_every_chain_are_transactions() {
+        for method in [
+            eth::SendRawTransaction::NAME,
+            sol::SendTransaction::NAME,
+            btc::BroadcastTx::NAME,
+            erc4337::SendUserOp::NAME,
+        ] {
+            assert_eq!(MethodClass::of(method), Some(MethodClass::Transactions));
+        }
+        assert_eq!(
+            MethodClass::of(host::WsSend::NAME),
+            Some(MethodClass::Fetch)
+        );
+        assert_eq!(
+            MethodClass::of(state::SetKey::NAME),
+            Some(MethodClass::StateWrites)
+        );
+        assert_eq!(MethodClass::of(global::Ping::NAME), None);
+    }
+
+    #[test]
+    fn buckets_refill_at_the_class_rate_up_to_the_burst() {
+        let limiter = RateLimiter::new();
+        limiter.set_limit(MethodClass::Transactions, RateLimit::new(1.0, 2.0));
+        let plugin = PluginId::from(1u128);
+        let method = btc::BroadcastTx::NAME;
+        let start = Local::now();
+
+        assert!(limiter.check_at(plugin, method, start));
+        assert!(limiter.check_at(plugin, method, start));
+        assert!(!limiter.check_at(plugin, method, start));
+
+        //? Half a second refills half a token, which isn't enough for a call
+        let later = start + Duration::milliseconds(500);
+        assert!(!limiter.check_at(plugin, method, later));
+        let later = start + Duration::milliseconds(1000);
+        assert!(limiter.check_at(plugin, method, later));
+
+        //? A long pause refills no more than the burst
+        let later = start + Duration::seconds(60);
+        assert!(limiter.check_at(plugin, method, later));
+        assert!(limiter.check_at(plugin, method, later));
+        assert!(!limiter.check_at(plugin, method, later));
+    }
+
+    #[test]
+    fn plugins_have_their_own_buckets() {
+        let limiter = RateLimiter::new();
+        limiter.set_limit(MethodClass::Transactions, RateLimit::new(1.0, 1.0));
+        let (plugin, other) = (PluginId::from(1u128), PluginId::from(2u128));
+        let now = Local::now();
+
+        assert!(limiter.check_at(plugin, eth::SendRawTransaction::NAME, now));
+        assert!(!limiter.check_at(plugin, sol::SendTransaction::NAME, now));
+        assert!(limiter.check_at(other, eth::SendRawTransaction::NAME, now));
+        assert!(limiter.check_at(plugin, global::Ping::NAME, now));
+    }
+}
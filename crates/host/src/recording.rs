@@ -0,0 +1,396 @@
+//! Recordings of the traffic crossing a plugin's transport, for reproducing
+//! bugs offline.
+//!
+//! While a plugin is being recorded, the host logs every call it makes into
+//! the plugin and every host method the plugin calls, with their params and
+//! results. `Host::replay` loads the same plugin into a fresh host and makes
+//! the recorded calls into it again, answering its host calls from the
+//! recording, so a flow that failed against live providers and other plugins
+//! can be stepped through without them.
+//!
+//! Calls are replayed one at a time in the order they started. Flows where the
+//! host called the plugin concurrently are replayed, but the host calls each
+//! one makes are answered in the order they were recorded, per method, which
+//! may not match the order they're made in.
+
+use std::{
+    cell::Cell,
+    collections::{HashMap, VecDeque},
+    future::{Future, poll_fn},
+    pin::pin,
+    sync::Mutex,
+};
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tlock_hdk::{
+    wasmi_plugin_hdk::plugin_id::PluginId,
+    wasmi_plugin_pdk::{rpc_message::RpcError, transport::AsyncTransport},
+};
+use tracing::warn;
+use uuid::Uuid;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Direction {
+    /// The host called one of the plugin's methods
+    ToPlugin,
+    /// The plugin called a host method
+    ToHost,
+}
+
+/// An `RpcError` as recorded. Errors other than these two variants only keep
+/// their message.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum RecordedError {
+    MethodNotFound,
+    InvalidParams,
+    Custom(String),
+}
+
+impl From<&RpcError> for RecordedError {
+    fn from(err: &RpcError) -> Self {
+        match err {
+            RpcError::MethodNotFound => RecordedError::MethodNotFound,
+            RpcError::InvalidParams => RecordedError::InvalidParams,
+            RpcError::Custom(msg) => RecordedError::Custom(msg.clone()),
+            other => RecordedError::Custom(other.to_string()),
+        }
+    }
+}
+
+impl From<RecordedError> for RpcError {
+    fn from(err: RecordedError) -> Self {
+        match err {
+            RecordedError::MethodNotFound => RpcError::MethodNotFound,
+            RecordedError::InvalidParams => RpcError::InvalidParams,
+            RecordedError::Custom(msg) => RpcError::Custom(msg),
+        }
+    }
+}
+
+pub type RecordedResult = Result<Value, RecordedError>;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Interaction {
+    /// Order the call started in
+    pub seq: u64,
+    pub direction: Direction,
+    /// Whether the call was made while handling one of the plugin's host
+    /// calls, so it was made on the plugin's behalf rather than by the user
+    pub nested: bool,
+    pub method: String,
+    pub params: Value,
+    pub result: RecordedResult,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Recording {
+    pub plugin_id: PluginId,
+    /// Interactions in the order they started
+    pub interactions: Vec<Interaction>,
+}
+
+impl Recording {
+    pub fn to_json(&self) -> Result<String, serde_json::Error> {
+        serde_json::to_string_pretty(self)
+    }
+
+    pub fn from_json(json: &str) -> Result<Self, serde_json::Error> {
+        serde_json::from_str(json)
+    }
+
+    /// The calls the host made into the plugin on its own account, which a
+    /// replay makes again.
+    pub fn plugin_calls(&self) -> impl Iterator<Item = &Interaction> {
+        self.interactions
+            .iter()
+            .filter(|i| i.direction == Direction::ToPlugin && !i.nested)
+    }
+}
+
+/// A replayed call into the plugin, with the result it had when recorded.
+#[derive(Debug, Clone)]
+pub struct Replayed {
+    pub method: String,
+    pub expected: RecordedResult,
+    pub actual: RecordedResult,
+}
+
+impl Replayed {
+    pub fn matches(&self) -> bool {
+        self.expected == self.actual
+    }
+}
+
+thread_local! {
+    /// The session whose plugin's host call is being polled on this thread.
+    /// Calls made while it's polled belong to that host call's chain, unlike
+    /// calls the host started concurrently.
+    static HOST_CALL: Cell<Option<Uuid>> = const { Cell::new(None) };
+}
+
+struct Session {
+    id: Uuid,
+    plugin_id: PluginId,
+    next_seq: u64,
+    interactions: Vec<Interaction>,
+}
+
+/// A call that started while recording, to be passed back to `finish`.
+pub struct Pending {
+    session: Uuid,
+    seq: u64,
+    direction: Direction,
+    nested: bool,
+}
+
+impl Pending {
+    /// Runs `fut`, the handling of a host call, marking the calls it makes as
+    /// nested in it.
+    pub async fn within<F: Future>(&self, fut: F) -> F::Output {
+        let mut fut = pin!(fut);
+        poll_fn(|cx| {
+            let outer = HOST_CALL.replace(Some(self.session));
+            let poll = fut.as_mut().poll(cx);
+            HOST_CALL.set(outer);
+            poll
+        })
+        .await
+    }
+}
+
+/// Records one plugin's interactions at a time.
+#[derive(Default)]
+pub struct Recorder {
+    session: Mutex<Option<Session>>,
+}
+
+impl Recorder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Starts recording a plugin, discarding any recording in progress.
+    pub fn start(&self, plugin_id: PluginId) {
+        *self.session.lock().unwrap() = Some(Session {
+            id: Uuid::new_v4(),
+            plugin_id,
+            next_seq: 0,
+            interactions: Vec::new(),
+        });
+    }
+
+    /// Stops recording, returning what was recorded. Calls still running are
+    /// left out.
+    pub fn stop(&self) -> Option<Recording> {
+        let session = self.session.lock().unwrap().take()?;
+        let mut interactions = session.interactions;
+        interactions.sort_by_key(|i| i.seq);
+        Some(Recording {
+            plugin_id: session.plugin_id,
+            interactions,
+        })
+    }
+
+    pub fn recording(&self) -> Option<PluginId> {
+        self.session.lock().unwrap().as_ref().map(|s| s.plugin_id)
+    }
+
+    /// Notes the start of a call, if the plugin is being recorded.
+    pub fn begin(&self, plugin_id: &PluginId, direction: Direction) -> Option<Pending> {
+        let mut session = self.session.lock().unwrap();
+        let session = session.as_mut().filter(|s| &s.plugin_id == plugin_id)?;
+        let pending = Pending {
+            session: session.id,
+            seq: session.next_seq,
+            direction,
+            nested: HOST_CALL.get() == Some(session.id),
+        };
+        session.next_seq += 1;
+        Some(pending)
+    }
+
+    pub fn finish(&self, pending: Pending, method: &str, params: Value, result: RecordedResult) {
+        let mut session = self.session.lock().unwrap();
+        let Some(session) = session.as_mut().filter(|s| s.id == pending.session) else {
+            return;
+        };
+        session.interactions.push(Interaction {
+            seq: pending.seq,
+            direction: pending.direction,
+            nested: pending.nested,
+            method: method.to_string(),
+            params,
+            result,
+        });
+    }
+}
+
+/// Answers a replayed plugin's host calls from its recording.
+pub struct Replayer {
+    plugin_id: PluginId,
+    responses: Mutex<HashMap<String, VecDeque<Interaction>>>,
+}
+
+impl Replayer {
+    pub fn new(recording: &Recording) -> Self {
+        let mut responses: HashMap<String, VecDeque<Interaction>> = HashMap::new();
+        for interaction in &recording.interactions {
+            if interaction.direction == Direction::ToHost {
+                let queue = responses.entry(interaction.method.clone()).or_default();
+                queue.push_back(interaction.clone());
+            }
+        }
+        Self {
+            plugin_id: recording.plugin_id,
+            responses: Mutex::new(responses),
+        }
+    }
+
+    /// Answers a host call with the next recorded result for its method, or
+    /// returns None for calls from other plugins.
+    pub fn answer(
+        &self,
+        plugin_id: &PluginId,
+        method: &str,
+        params: &Value,
+    ) -> Option<Result<Value, RpcError>> {
+        if plugin_id != &self.plugin_id {
+            return None;
+        }
+
+        let mut responses = self.responses.lock().unwrap();
+        let Some(recorded) = responses.get_mut(method).and_then(|q| q.pop_front()) else {
+            let message = format!("Replay has no recorded {} call left", method);
+            return Some(Err(RpcError::Custom(message)));
+        };
+        if &recorded.params != params {
+            warn!(
+                "Replayed {} call has different params than recorded",
+                method
+            );
+        }
+        Some(recorded.result.map_err(Into::into))
+    }
+}
+
+/// Calls a plugin method by name, with JSON params.
+pub(crate) async fn call_raw<T, E>(
+    transport: T,
+    method: &'static str,
+    params: Value,
+) -> Result<Value, RpcError>
+where
+    T: AsyncTransport<E> + Send + Sync + 'static,
+    E: Into<RpcError>,
+{
+    let resp = transport
+        .call_async(method, params)
+        .await
+        .map_err(Into::into)?;
+    Ok(resp.result)
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::executor::block_on;
+    use serde_json::json;
+
+    use super::*;
+
+    fn finish(recorder: &Recorder, pending: Pending, method: &str, result: Value) {
+        recorder.finish(pending, method, json!([method]), Ok(result));
+    }
+
+    #[test]
+    fn marks_calls_nested_per_host_call() {
+        let recorder = Recorder::new();
+        let plugin = PluginId::from(1u128);
+        recorder.start(plugin);
+
+        let call = recorder.begin(&plugin, Direction::ToPlugin).unwrap();
+        let host_call = recorder.begin(&plugin, Direction::ToHost).unwrap();
+        //? Started while the host call runs, but not by it
+        let concurrent = recorder.begin(&plugin, Direction::ToPlugin).unwrap();
+        let nested = block_on(
+            host_call.within(async { recorder.begin(&plugin, Direction::ToPlugin).unwrap() }),
+        );
+
+        finish(&recorder, nested, "page_on_load", json!(null));
+        finish(&recorder, host_call, "host_set_page", json!(null));
+        finish(&recorder, concurrent, "page_on_update", json!(null));
+        finish(&recorder, call, "plugin_init", json!(null));
+
+        let recording = recorder.stop().unwrap();
+        let nested: Vec<_> = recording.interactions.iter().map(|i| i.nested).collect();
+        assert_eq!(nested, [false, false, false, true]);
+        let replayed: Vec<_> = recording
+            .plugin_calls()
+            .map(|i| i.method.as_str())
+            .collect();
+        assert_eq!(replayed, ["plugin_init", "page_on_update"]);
+    }
+
+    #[test]
+    fn only_records_the_started_session() {
+        let recorder = Recorder::new();
+        let plugin = PluginId::from(1u128);
+        assert!(recorder.begin(&plugin, Direction::ToPlugin).is_none());
+
+        recorder.start(plugin);
+        assert!(
+            recorder
+                .begin(&PluginId::from(2u128), Direction::ToPlugin)
+                .is_none()
+        );
+        let stale = recorder.begin(&plugin, Direction::ToPlugin).unwrap();
+
+        recorder.start(plugin);
+        finish(&recorder, stale, "plugin_init", json!(null));
+        assert!(recorder.stop().unwrap().interactions.is_empty());
+    }
+
+    #[test]
+    fn replays_host_calls_from_a_recording() {
+        let recorder = Recorder::new();
+        let plugin = PluginId::from(1u128);
+        recorder.start(plugin);
+        let call = recorder.begin(&plugin, Direction::ToPlugin).unwrap();
+        for block in [1, 2] {
+            let host_call = recorder.begin(&plugin, Direction::ToHost).unwrap();
+            finish(&recorder, host_call, "eth_blockNumber", json!(block));
+        }
+        let failed = recorder.begin(&plugin, Direction::ToHost).unwrap();
+        recorder.finish(
+            failed,
+            "host_fetch",
+            json!(["host_fetch"]),
+            Err(RecordedError::MethodNotFound),
+        );
+        finish(&recorder, call, "plugin_init", json!(null));
+
+        let json = recorder.stop().unwrap().to_json().unwrap();
+        let recording = Recording::from_json(&json).unwrap();
+        assert_eq!(recording.plugin_id, plugin);
+        assert_eq!(recording.interactions.len(), 4);
+
+        let replayer = Replayer::new(&recording);
+        let params = json!(["eth_blockNumber"]);
+        let answer = |method: &str| replayer.answer(&plugin, method, &params).unwrap();
+        assert_eq!(answer("eth_blockNumber").unwrap(), json!(1));
+        assert_eq!(answer("eth_blockNumber").unwrap(), json!(2));
+        assert!(matches!(
+            answer("eth_blockNumber"),
+            Err(RpcError::Custom(_))
+        ));
+        assert!(matches!(
+            answer("host_fetch"),
+            Err(RpcError::MethodNotFound)
+        ));
+        assert!(
+            replayer
+                .answer(&PluginId::from(2u128), "host_fetch", &params)
+                .is_none()
+        );
+    }
+}
@@ -0,0 +1,230 @@
+//! Limits how many calls into each plugin run at once.
+//!
+//! Every call into a plugin spawns a new wasm instance, so without a limit a
+//! page spamming calls could spawn unbounded runtimes. Calls beyond the limit
+//! wait in a queue until a running call finishes, or give up once they've
+//! waited too long.
+//!
+//! A call chain that comes back into a plugin it's already running in, e.g. a
+//! page calling a coordinator that calls back into the page, shares the slot
+//! its outer call holds. Waiting on a second slot could otherwise deadlock
+//! once every slot is held by a call waiting on itself.
+
+use std::{
+    cell::RefCell,
+    collections::HashMap,
+    pin::Pin,
+    sync::Mutex,
+    task::{Context, Poll},
+    time::Duration,
+};
+
+use event_listener::Event;
+use tlock_hdk::wasmi_plugin_hdk::plugin_id::PluginId;
+
+use crate::time;
+
+thread_local! {
+    /// Plugins holding a slot for the call chain currently being polled,
+    /// innermost last.
+    static HELD: RefCell<Vec<PluginId>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Calls running and waiting for a plugin.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CallStats {
+    pub active: usize,
+    pub queued: usize,
+    /// The most calls that have been queued at once
+    pub peak_queued: usize,
+}
+
+#[derive(Debug)]
+pub struct Scheduler {
+    max_concurrent: usize,
+    stats: Mutex<HashMap<PluginId, CallStats>>,
+    released: Event,
+}
+
+/// A running call's slot, released when dropped.
+pub struct Slot<'a> {
+    scheduler: &'a Scheduler,
+    plugin_id: PluginId,
+    /// Whether the slot belongs to an outer call in the same chain
+    shared: bool,
+}
+
+/// A call run while holding its plugin's slot, so calls it makes back into
+/// the plugin share the slot.
+pub struct Holding<F> {
+    plugin_id: PluginId,
+    inner: Pin<Box<F>>,
+}
+
+/// Pops the innermost held slot when a `Holding` finishes polling.
+struct Release;
+
+/// Removes a queued call from the stats if it's dropped before it runs.
+struct Waiting<'a> {
+    scheduler: &'a Scheduler,
+    plugin_id: PluginId,
+}
+
+impl Scheduler {
+    pub fn new(max_concurrent: usize) -> Self {
+        Self {
+            max_concurrent,
+            stats: Mutex::new(HashMap::new()),
+            released: Event::new(),
+        }
+    }
+
+    /// Takes a slot for a call into the plugin, waiting while it has
+    /// `max_concurrent` calls running. Returns None if no slot freed up within
+    /// `timeout`. Calls made inside a `Holding` call into the same plugin
+    /// share its slot without waiting.
+    pub async fn acquire(&self, plugin_id: PluginId, timeout: Duration) -> Option<Slot<'_>> {
+        if is_held(&plugin_id) {
+            return Some(Slot {
+                scheduler: self,
+                plugin_id,
+                shared: true,
+            });
+        }
+        time::timeout(timeout, self.wait_for_slot(plugin_id)).await
+    }
+
+    async fn wait_for_slot(&self, plugin_id: PluginId) -> Slot<'_> {
+        let mut waiting = None;
+        loop {
+            let listener = {
+                let mut stats = self.stats.lock().unwrap();
+                let entry = stats.entry(plugin_id).or_default();
+                if entry.active < self.max_concurrent {
+                    //? Leave the queue here rather than in `Waiting::drop`,
+                    //? which would need the lock we're holding
+                    if let Some(waiting) = waiting.take() {
+                        std::mem::forget::<Waiting>(waiting);
+                        entry.queued -= 1;
+                    }
+                    entry.active += 1;
+                    return Slot {
+                        scheduler: self,
+                        plugin_id,
+                        shared: false,
+                    };
+                }
+
+                if waiting.is_none() {
+                    entry.queued += 1;
+                    entry.peak_queued = entry.peak_queued.max(entry.queued);
+                    waiting = Some(Waiting {
+                        scheduler: self,
+                        plugin_id,
+                    });
+                }
+                self.released.listen()
+            };
+            listener.await;
+        }
+    }
+
+    pub fn stats(&self, plugin_id: &PluginId) -> CallStats {
+        let stats = self.stats.lock().unwrap();
+        stats.get(plugin_id).copied().unwrap_or_default()
+    }
+
+    fn release(&self, plugin_id: &PluginId) {
+        if let Some(entry) = self.stats.lock().unwrap().get_mut(plugin_id) {
+            entry.active -= 1;
+        }
+        self.released.notify(usize::MAX);
+    }
+}
+
+impl Slot<'_> {
+    /// Runs `call` while holding this slot.
+    pub fn hold<F: Future>(&self, call: F) -> Holding<F> {
+        Holding {
+            plugin_id: self.plugin_id,
+            inner: Box::pin(call),
+        }
+    }
+}
+
+impl Drop for Slot<'_> {
+    fn drop(&mut self) {
+        if !self.shared {
+            self.scheduler.release(&self.plugin_id);
+        }
+    }
+}
+
+impl<F: Future> Future for Holding<F> {
+    type Output = F::Output;
+
+    //? The slot is only marked held while the call is being polled, since
+    //? that's when any nested calls it makes are polled too
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<F::Output> {
+        HELD.with(|held| held.borrow_mut().push(self.plugin_id));
+        let _release = Release;
+        self.inner.as_mut().poll(cx)
+    }
+}
+
+impl Drop for Release {
+    fn drop(&mut self) {
+        HELD.with(|held| held.borrow_mut().pop());
+    }
+}
+
+fn is_held(plugin_id: &PluginId) -> bool {
+    HELD.with(|held| held.borrow().contains(plugin_id))
+}
+
+impl Drop for Waiting<'_> {
+    fn drop(&mut self) {
+        let mut stats = self.scheduler.stats.lock().unwrap();
+        if let Some(entry) = stats.get_mut(&self.plugin_id) {
+            entry.queued -= 1;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::executor::block_on;
+
+    use super::*;
+
+    const TIMEOUT: Duration = Duration::from_millis(50);
+
+    #[test]
+    fn nested_calls_share_the_slot() {
+        let scheduler = Scheduler::new(1);
+        let plugin_id = PluginId::from(1u128);
+
+        block_on(async {
+            let slot = scheduler.acquire(plugin_id, TIMEOUT).await.unwrap();
+            let nested = slot.hold(async {
+                let nested = scheduler.acquire(plugin_id, TIMEOUT).await;
+                assert!(nested.is_some());
+            });
+            nested.await;
+            assert_eq!(scheduler.stats(&plugin_id).active, 1);
+        });
+        assert_eq!(scheduler.stats(&plugin_id).active, 0);
+    }
+
+    #[test]
+    fn waiting_times_out() {
+        let scheduler = Scheduler::new(1);
+        let plugin_id = PluginId::from(1u128);
+
+        block_on(async {
+            let _slot = scheduler.acquire(plugin_id, TIMEOUT).await.unwrap();
+            assert!(scheduler.acquire(plugin_id, TIMEOUT).await.is_none());
+        });
+        assert_eq!(scheduler.stats(&plugin_id).queued, 0);
+    }
+}
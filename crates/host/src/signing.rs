@@ -0,0 +1,56 @@
+//! Ed25519 signatures over plugin wasm bundles.
+//!
+//! A plugin served from `<url>` may be accompanied by a detached signature at
+//! `<url>.sig`, holding the 64-byte signature of the wasm bytes. Signatures
+//! are checked against the publisher keys the user trusts.
+
+use ed25519_dalek::{Signature, Verifier, VerifyingKey};
+use serde::{Deserialize, Serialize};
+
+/// Suffix appended to a plugin's URL to find its detached signature.
+pub const SIGNATURE_SUFFIX: &str = ".sig";
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TrustedPublisher {
+    pub name: String,
+    /// The publisher's ed25519 public key
+    pub key: [u8; 32],
+}
+
+/// How a plugin's bundle checked out against the trusted publishers.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Verification {
+    /// Signed by a trusted publisher
+    Trusted(TrustedPublisher),
+    /// No signature was found, or it isn't from a trusted publisher
+    Untrusted,
+    /// The signature is malformed, so it or the bundle has been corrupted
+    Invalid,
+}
+
+/// Checks a detached signature over `wasm_bytes`.
+///
+/// A well-formed signature that no trusted key accepts is `Untrusted` rather
+/// than `Invalid`, since it may come from a publisher the user hasn't added.
+pub fn verify(
+    wasm_bytes: &[u8],
+    signature: Option<&[u8]>,
+    trusted: &[TrustedPublisher],
+) -> Verification {
+    let Some(signature) = signature else {
+        return Verification::Untrusted;
+    };
+    let Ok(signature) = Signature::from_slice(signature) else {
+        return Verification::Invalid;
+    };
+
+    for publisher in trusted {
+        let Ok(key) = VerifyingKey::from_bytes(&publisher.key) else {
+            continue;
+        };
+        if key.verify(wasm_bytes, &signature).is_ok() {
+            return Verification::Trusted(publisher.clone());
+        }
+    }
+    Verification::Untrusted
+}
@@ -0,0 +1,118 @@
+//! Host-wide simulation mode.
+//!
+//! While it's on, every EthProvider call that reads or changes chain state is
+//! rerouted to a fork provider, such as revm-provider, whenever the fork
+//! serves the same chain as the provider the plugin asked for. Transactions
+//! are applied to the fork instead of being broadcast, and are recorded here
+//! with the receipt the fork returned so the user can inspect what they did.
+//! Their nonces are leased separately from the live chain's.
+
+use std::{collections::HashMap, sync::Mutex};
+
+use alloy::{
+    primitives::{Address, TxHash, U256},
+    rpc::types::TransactionReceipt,
+    sol_types::SolEvent,
+};
+use tlock_hdk::{tlock_api::entities::EthProviderId, wasmi_plugin_hdk::plugin_id::PluginId};
+
+use crate::{erc20::Erc20, nonces::NonceManager};
+
+#[derive(Debug, Clone)]
+pub struct SimulatedTransaction {
+    pub hash: TxHash,
+    /// The plugin that sent the transaction
+    pub plugin_id: PluginId,
+    /// The provider the plugin sent the transaction to
+    pub provider_id: EthProviderId,
+    pub submitted: chrono::DateTime<chrono::Local>,
+    /// The fork's receipt, if it returned one
+    pub receipt: Option<TransactionReceipt>,
+}
+
+impl SimulatedTransaction {
+    /// The ERC20 transfers the transaction emitted.
+    pub fn transfers(&self) -> Vec<Transfer> {
+        let Some(receipt) = &self.receipt else {
+            return Vec::new();
+        };
+        receipt
+            .inner
+            .logs()
+            .iter()
+            .filter_map(|log| Erc20::Transfer::decode_log(&log.inner).ok())
+            .map(|log| Transfer {
+                token: log.address,
+                from: log.from,
+                to: log.to,
+                value: log.value,
+            })
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Transfer {
+    pub token: Address,
+    pub from: Address,
+    pub to: Address,
+    pub value: U256,
+}
+
+#[derive(Debug, Default)]
+pub struct Simulation {
+    fork: Mutex<Option<EthProviderId>>,
+    /// Where calls meant for each provider go, once the provider's chain has
+    /// been compared with the fork's
+    targets: Mutex<HashMap<EthProviderId, EthProviderId>>,
+    /// Nonces leased for transactions applied to the fork
+    nonces: NonceManager,
+    transactions: Mutex<Vec<SimulatedTransaction>>,
+}
+
+impl Simulation {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The fork provider calls are rerouted to, while simulating.
+    pub fn fork(&self) -> Option<EthProviderId> {
+        *self.fork.lock().unwrap()
+    }
+
+    /// Starts simulating against `fork`, forgetting any earlier simulation.
+    pub fn enable(&self, fork: EthProviderId) {
+        *self.fork.lock().unwrap() = Some(fork);
+        self.targets.lock().unwrap().clear();
+        self.nonces.clear();
+        self.transactions.lock().unwrap().clear();
+    }
+
+    /// Stops simulating. Recorded transactions are kept until the next
+    /// simulation starts, so they can still be inspected.
+    pub fn disable(&self) {
+        *self.fork.lock().unwrap() = None;
+        self.targets.lock().unwrap().clear();
+    }
+
+    pub fn target(&self, provider_id: EthProviderId) -> Option<EthProviderId> {
+        self.targets.lock().unwrap().get(&provider_id).copied()
+    }
+
+    pub fn set_target(&self, provider_id: EthProviderId, target: EthProviderId) {
+        self.targets.lock().unwrap().insert(provider_id, target);
+    }
+
+    pub fn nonces(&self) -> &NonceManager {
+        &self.nonces
+    }
+
+    pub fn record(&self, transaction: SimulatedTransaction) {
+        self.transactions.lock().unwrap().push(transaction);
+    }
+
+    /// Returns every transaction applied to the fork, oldest first.
+    pub fn transactions(&self) -> Vec<SimulatedTransaction> {
+        self.transactions.lock().unwrap().clone()
+    }
+}
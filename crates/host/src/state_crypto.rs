@@ -0,0 +1,65 @@
+//! Password-based encryption of exported host state.
+//!
+//! Exports are sealed the same way as keystore secrets: a key derived from the
+//! password with Argon2id encrypts the state with XChaCha20-Poly1305. An
+//! encrypted export is `MAGIC`, then the salt, the nonce, and the ciphertext.
+
+use thiserror::Error;
+
+use crate::keystore::{self, Sealed};
+
+const MAGIC: &[u8] = b"tlock-encrypted-state-v1\n";
+const SALT_LEN: usize = 16;
+const NONCE_LEN: usize = 24;
+
+#[derive(Debug, Error)]
+pub enum StateCryptoError {
+    #[error("State is not encrypted")]
+    NotEncrypted,
+    #[error("Encrypted state is truncated")]
+    Truncated,
+    #[error("Incorrect password")]
+    IncorrectPassword,
+    #[error("Failed to derive key: {0}")]
+    KeyDerivation(String),
+}
+
+/// Whether `data` is an export made by `encrypt`.
+pub fn is_encrypted(data: &[u8]) -> bool {
+    data.starts_with(MAGIC)
+}
+
+pub fn encrypt(plaintext: &[u8], password: &str) -> Result<Vec<u8>, StateCryptoError> {
+    let salt: [u8; SALT_LEN] = keystore::random_bytes();
+    let key = keystore::derive_key(password, &salt)
+        .map_err(|e| StateCryptoError::KeyDerivation(e.to_string()))?;
+    let sealed = keystore::seal(&key, plaintext, MAGIC);
+
+    let mut data = Vec::with_capacity(MAGIC.len() + SALT_LEN + NONCE_LEN + sealed.ciphertext.len());
+    data.extend_from_slice(MAGIC);
+    data.extend_from_slice(&salt);
+    data.extend_from_slice(&sealed.nonce);
+    data.extend_from_slice(&sealed.ciphertext);
+    Ok(data)
+}
+
+pub fn decrypt(data: &[u8], password: &str) -> Result<Vec<u8>, StateCryptoError> {
+    let rest = data
+        .strip_prefix(MAGIC)
+        .ok_or(StateCryptoError::NotEncrypted)?;
+    if rest.len() < SALT_LEN + NONCE_LEN {
+        return Err(StateCryptoError::Truncated);
+    }
+    let (salt, rest) = rest.split_at(SALT_LEN);
+    let (nonce, ciphertext) = rest.split_at(NONCE_LEN);
+
+    //? The lengths were checked above, so these conversions can't fail
+    let salt: [u8; SALT_LEN] = salt.try_into().unwrap();
+    let sealed = Sealed {
+        nonce: nonce.try_into().unwrap(),
+        ciphertext: ciphertext.to_vec(),
+    };
+    let key = keystore::derive_key(password, &salt)
+        .map_err(|e| StateCryptoError::KeyDerivation(e.to_string()))?;
+    keystore::open(&key, &sealed, MAGIC).map_err(|_| StateCryptoError::IncorrectPassword)
+}
@@ -0,0 +1,88 @@
+//! Browser state store backed by IndexedDB.
+
+use async_trait::async_trait;
+use js_sys::Uint8Array;
+use rexie::{ObjectStore, Rexie, TransactionMode};
+use wasm_bindgen::JsValue;
+
+use super::{StateKey, StateStore, StateWrite, StoreError, decode_key, encode_key};
+
+const STORE_NAME: &str = "state";
+
+/// Stores each state key as a separate IndexedDB record. The database is
+/// opened per operation since IndexedDB handles can't be shared across
+/// threads.
+pub struct IndexedDbStore {
+    db_name: String,
+}
+
+impl IndexedDbStore {
+    pub fn new(db_name: impl Into<String>) -> Self {
+        Self {
+            db_name: db_name.into(),
+        }
+    }
+
+    async fn open(&self) -> Result<Rexie, StoreError> {
+        Rexie::builder(&self.db_name)
+            .version(1)
+            .add_object_store(ObjectStore::new(STORE_NAME))
+            .build()
+            .await
+            .map_err(backend_error)
+    }
+}
+
+#[async_trait(?Send)]
+impl StateStore for IndexedDbStore {
+    async fn load(&self) -> Result<Vec<(StateKey, Vec<u8>)>, StoreError> {
+        let db = self.open().await?;
+        let tx = db
+            .transaction(&[STORE_NAME], TransactionMode::ReadOnly)
+            .map_err(backend_error)?;
+        let store = tx.store(STORE_NAME).map_err(backend_error)?;
+
+        let records = store
+            .scan(None, None, None, None)
+            .await
+            .map_err(backend_error)?;
+        records
+            .into_iter()
+            .map(|(key, value)| {
+                let key = key
+                    .as_string()
+                    .ok_or_else(|| StoreError::Backend("Non-string key".into()))?;
+                Ok((decode_key(&key)?, Uint8Array::new(&value).to_vec()))
+            })
+            .collect()
+    }
+
+    async fn apply(&self, writes: Vec<StateWrite>) -> Result<(), StoreError> {
+        let db = self.open().await?;
+        let tx = db
+            .transaction(&[STORE_NAME], TransactionMode::ReadWrite)
+            .map_err(backend_error)?;
+        let store = tx.store(STORE_NAME).map_err(backend_error)?;
+
+        for write in writes {
+            match write {
+                StateWrite::Put(key, value) => {
+                    let key = JsValue::from_str(&encode_key(&key)?);
+                    let value: JsValue = Uint8Array::from(value.as_slice()).into();
+                    store.put(&value, Some(&key)).await.map_err(backend_error)?;
+                }
+                StateWrite::Delete(key) => {
+                    let key = JsValue::from_str(&encode_key(&key)?);
+                    store.delete(key).await.map_err(backend_error)?;
+                }
+            }
+        }
+
+        tx.done().await.map_err(backend_error)?;
+        Ok(())
+    }
+}
+
+fn backend_error(err: rexie::Error) -> StoreError {
+    StoreError::Backend(err.to_string())
+}
@@ -0,0 +1,74 @@
+//! Durable backends for plugin state.
+//!
+//! The host serves `state_*` calls from its in-memory map and records which
+//! keys changed. `Host::flush_state` later writes those keys to the attached
+//! `StateStore`, so plugins never wait on storage.
+
+use async_trait::async_trait;
+use thiserror::Error;
+use tlock_hdk::wasmi_plugin_hdk::plugin_id::PluginId;
+
+#[cfg(feature = "indexeddb")]
+mod indexeddb;
+#[cfg(feature = "sled")]
+mod sled;
+
+#[cfg(feature = "indexeddb")]
+pub use indexeddb::IndexedDbStore;
+#[cfg(feature = "sled")]
+pub use sled::SledStore;
+
+/// A plugin's state key, as stored by the host
+pub type StateKey = (PluginId, String);
+
+#[derive(Debug, Clone)]
+pub enum StateWrite {
+    Put(StateKey, Vec<u8>),
+    Delete(StateKey),
+}
+
+#[derive(Error, Debug)]
+pub enum StoreError {
+    #[error("Storage backend error: {0}")]
+    Backend(String),
+    #[error("Invalid stored key: {0}")]
+    Key(#[from] serde_json::Error),
+}
+
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+pub trait StateStore: Send + Sync {
+    /// Reads every stored entry.
+    async fn load(&self) -> Result<Vec<(StateKey, Vec<u8>)>, StoreError>;
+
+    /// Applies a batch of writes. Backends SHOULD apply the batch atomically.
+    async fn apply(&self, writes: Vec<StateWrite>) -> Result<(), StoreError>;
+}
+
+/// Keeps nothing, so state only lives in the host's memory. Useful for tests
+/// and for frontends without a durable backend.
+#[derive(Debug, Default)]
+pub struct MemoryStore;
+
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+impl StateStore for MemoryStore {
+    async fn load(&self) -> Result<Vec<(StateKey, Vec<u8>)>, StoreError> {
+        Ok(Vec::new())
+    }
+
+    async fn apply(&self, _writes: Vec<StateWrite>) -> Result<(), StoreError> {
+        Ok(())
+    }
+}
+
+/// Encodes a state key for backends that only support flat keys.
+#[cfg(any(feature = "indexeddb", feature = "sled"))]
+fn encode_key(key: &StateKey) -> Result<String, StoreError> {
+    Ok(serde_json::to_string(key)?)
+}
+
+#[cfg(any(feature = "indexeddb", feature = "sled"))]
+fn decode_key(key: &str) -> Result<StateKey, StoreError> {
+    Ok(serde_json::from_str(key)?)
+}
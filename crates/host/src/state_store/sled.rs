@@ -0,0 +1,51 @@
+//! Native state store backed by a sled database.
+
+use std::path::Path;
+
+use async_trait::async_trait;
+
+use super::{StateKey, StateStore, StateWrite, StoreError, decode_key, encode_key};
+
+pub struct SledStore {
+    db: ::sled::Db,
+}
+
+impl SledStore {
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, StoreError> {
+        let db = ::sled::open(path).map_err(backend_error)?;
+        Ok(Self { db })
+    }
+}
+
+#[async_trait]
+impl StateStore for SledStore {
+    async fn load(&self) -> Result<Vec<(StateKey, Vec<u8>)>, StoreError> {
+        self.db
+            .iter()
+            .map(|entry| {
+                let (key, value) = entry.map_err(backend_error)?;
+                let key =
+                    std::str::from_utf8(&key).map_err(|e| StoreError::Backend(e.to_string()))?;
+                Ok((decode_key(key)?, value.to_vec()))
+            })
+            .collect()
+    }
+
+    async fn apply(&self, writes: Vec<StateWrite>) -> Result<(), StoreError> {
+        let mut batch = ::sled::Batch::default();
+        for write in writes {
+            match write {
+                StateWrite::Put(key, value) => batch.insert(encode_key(&key)?.as_bytes(), value),
+                StateWrite::Delete(key) => batch.remove(encode_key(&key)?.as_bytes()),
+            }
+        }
+
+        self.db.apply_batch(batch).map_err(backend_error)?;
+        self.db.flush_async().await.map_err(backend_error)?;
+        Ok(())
+    }
+}
+
+fn backend_error(err: ::sled::Error) -> StoreError {
+    StoreError::Backend(err.to_string())
+}
@@ -0,0 +1,131 @@
+//! Periodic `plugin_on_tick` calls scheduled with `host_register_tick`.
+//!
+//! Each tick is delayed by up to `JITTER_FRACTION` of its interval, so plugins
+//! registered at the same moment don't all wake together. While a plugin's
+//! ticks keep failing, its interval doubles up to `MAX_BACKOFF_FACTOR` times
+//! the registered one, and resets once a tick succeeds.
+
+use std::{collections::HashMap, sync::Mutex, time::Duration};
+
+use chrono::{DateTime, Local};
+use tlock_hdk::wasmi_plugin_hdk::plugin_id::PluginId;
+use uuid::Uuid;
+
+/// Shortest interval a plugin can register
+pub const MIN_TICK_INTERVAL: Duration = Duration::from_secs(60);
+/// Longest interval a plugin can register
+pub const MAX_TICK_INTERVAL: Duration = Duration::from_secs(7 * 24 * 60 * 60);
+/// Largest fraction of the interval added to each tick as jitter
+const JITTER_FRACTION: f64 = 0.1;
+/// Most the interval is stretched by while ticks fail
+const MAX_BACKOFF_FACTOR: u32 = 32;
+
+#[derive(Debug)]
+struct Schedule {
+    interval: Duration,
+    /// When the next tick is due, or None while a tick is running
+    due: Option<DateTime<Local>>,
+    /// Consecutive failed ticks
+    failures: u32,
+}
+
+impl Schedule {
+    fn new(interval: Duration, now: DateTime<Local>) -> Self {
+        let mut schedule = Self {
+            interval: interval.clamp(MIN_TICK_INTERVAL, MAX_TICK_INTERVAL),
+            due: None,
+            failures: 0,
+        };
+        schedule.reschedule(now);
+        schedule
+    }
+
+    fn reschedule(&mut self, now: DateTime<Local>) {
+        let factor = 2u32.saturating_pow(self.failures).min(MAX_BACKOFF_FACTOR);
+        let delay = self.interval.saturating_mul(factor).mul_f64(1.0 + jitter());
+        let delay = chrono::Duration::from_std(delay).unwrap_or(chrono::Duration::MAX);
+        self.due = now.checked_add_signed(delay);
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct Ticker {
+    schedules: Mutex<HashMap<PluginId, Schedule>>,
+}
+
+impl Ticker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn from_intervals(intervals: Vec<(PluginId, Duration)>) -> Self {
+        let now = Local::now();
+        let schedules = intervals
+            .into_iter()
+            .map(|(plugin_id, interval)| (plugin_id, Schedule::new(interval, now)))
+            .collect();
+        Self {
+            schedules: Mutex::new(schedules),
+        }
+    }
+
+    /// Registered intervals, for persisting with the host state.
+    pub fn intervals(&self) -> Vec<(PluginId, Duration)> {
+        let schedules = self.schedules.lock().unwrap();
+        schedules.iter().map(|(id, s)| (*id, s.interval)).collect()
+    }
+
+    pub fn register(&self, plugin_id: PluginId, interval: Duration) {
+        let schedule = Schedule::new(interval, Local::now());
+        self.schedules.lock().unwrap().insert(plugin_id, schedule);
+    }
+
+    pub fn unregister(&self, plugin_id: &PluginId) {
+        self.schedules.lock().unwrap().remove(plugin_id);
+    }
+
+    /// Moves a plugin's schedule to a new ID, e.g. when it's upgraded.
+    pub fn transfer(&self, old_id: &PluginId, new_id: PluginId) {
+        let mut schedules = self.schedules.lock().unwrap();
+        if let Some(schedule) = schedules.remove(old_id) {
+            schedules.insert(new_id, schedule);
+        }
+    }
+
+    /// Returns the plugins whose tick has come due. Each must be reported back
+    /// with `complete` to schedule its next tick.
+    pub fn take_due(&self) -> Vec<PluginId> {
+        let now = Local::now();
+        let mut schedules = self.schedules.lock().unwrap();
+        let mut due = Vec::new();
+        for (plugin_id, schedule) in schedules.iter_mut() {
+            if schedule.due.is_none_or(|d| d > now) {
+                continue;
+            }
+            //? Clear the deadline so a slow tick isn't fired again before it
+            //? completes
+            schedule.due = None;
+            due.push(*plugin_id);
+        }
+        due
+    }
+
+    pub fn complete(&self, plugin_id: &PluginId, ok: bool) {
+        let mut schedules = self.schedules.lock().unwrap();
+        let Some(schedule) = schedules.get_mut(plugin_id) else {
+            return;
+        };
+        schedule.failures = match ok {
+            true => 0,
+            false => schedule.failures.saturating_add(1),
+        };
+        schedule.reschedule(Local::now());
+    }
+}
+
+/// Returns a random fraction in `[0, JITTER_FRACTION)`.
+fn jitter() -> f64 {
+    //? Borrows uuid's randomness rather than pulling in an RNG for wasm
+    let bits = (Uuid::new_v4().as_u128() >> 75) as u64;
+    bits as f64 / (1u64 << 53) as f64 * JITTER_FRACTION
+}
@@ -0,0 +1,41 @@
+use std::time::Duration;
+
+use futures::future::{self, Either};
+
+/// Runs `fut` to completion, or returns None if it doesn't finish within
+/// `duration`.
+pub async fn timeout<F: Future>(duration: Duration, fut: F) -> Option<F::Output> {
+    let fut = std::pin::pin!(fut);
+    let timer = std::pin::pin!(sleep(duration));
+    match future::select(fut, timer).await {
+        Either::Left((output, _)) => Some(output),
+        Either::Right(_) => None,
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+pub async fn sleep(duration: Duration) {
+    gloo_timers::future::sleep(duration).await;
+}
+
+//? No async runtime is available natively, so park a thread for the duration
+#[cfg(not(target_arch = "wasm32"))]
+pub async fn sleep(duration: Duration) {
+    let (tx, rx) = futures::channel::oneshot::channel();
+    std::thread::spawn(move || {
+        std::thread::sleep(duration);
+        let _ = tx.send(());
+    });
+    let _ = rx.await;
+}
+
+/// Yields to the browser's event loop, giving it a chance to render before
+/// the caller continues.
+#[cfg(target_arch = "wasm32")]
+pub async fn yield_now() {
+    gloo_timers::future::TimeoutFuture::new(0).await;
+}
+
+//? Nothing renders natively, so there's nothing to yield to
+#[cfg(not(target_arch = "wasm32"))]
+pub async fn yield_now() {}
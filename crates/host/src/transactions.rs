@@ -0,0 +1,106 @@
+//! Tracks transactions sent through `eth_sendRawTransaction` until they're
+//! confirmed, fail, or are replaced.
+
+use std::{collections::VecDeque, sync::Mutex};
+
+use alloy::primitives::{Address, TxHash};
+use tlock_hdk::{
+    tlock_api::{entities::EthProviderId, eth::TransactionStatus},
+    wasmi_plugin_hdk::plugin_id::PluginId,
+};
+
+/// Most finished transactions kept for the pending transactions panel
+const MAX_FINISHED: usize = 100;
+
+#[derive(Debug, Clone)]
+pub struct TrackedTransaction {
+    pub hash: TxHash,
+    /// The plugin that sent the transaction
+    pub plugin_id: PluginId,
+    pub provider_id: EthProviderId,
+    pub submitted: chrono::DateTime<chrono::Local>,
+    pub status: TransactionStatus,
+    /// The transaction's sender and nonce, once the provider has reported them
+    pub sender: Option<(Address, u64)>,
+}
+
+#[derive(Debug, Default)]
+pub struct TransactionTracker {
+    transactions: Mutex<VecDeque<TrackedTransaction>>,
+}
+
+impl TransactionTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn track(&self, hash: TxHash, plugin_id: PluginId, provider_id: EthProviderId) {
+        let mut transactions = self.transactions.lock().unwrap();
+        transactions.retain(|t| t.hash != hash);
+        transactions.push_back(TrackedTransaction {
+            hash,
+            plugin_id,
+            provider_id,
+            submitted: chrono::Local::now(),
+            status: TransactionStatus::Pending,
+            sender: None,
+        });
+    }
+
+    /// Returns every tracked transaction, oldest first.
+    pub fn all(&self) -> Vec<TrackedTransaction> {
+        self.transactions.lock().unwrap().iter().cloned().collect()
+    }
+
+    pub fn pending(&self) -> Vec<TrackedTransaction> {
+        let transactions = self.transactions.lock().unwrap();
+        transactions
+            .iter()
+            .filter(|t| t.status == TransactionStatus::Pending)
+            .cloned()
+            .collect()
+    }
+
+    pub fn set_sender(&self, hash: &TxHash, sender: Address, nonce: u64) {
+        let mut transactions = self.transactions.lock().unwrap();
+        if let Some(t) = transactions.iter_mut().find(|t| &t.hash == hash) {
+            t.sender = Some((sender, nonce));
+        }
+    }
+
+    /// Moves a pending transaction to `status`. Returns the plugin that sent
+    /// it if the status changed.
+    pub fn set_status(&self, hash: &TxHash, status: TransactionStatus) -> Option<PluginId> {
+        let mut transactions = self.transactions.lock().unwrap();
+        let t = transactions
+            .iter_mut()
+            .find(|t| &t.hash == hash && t.status == TransactionStatus::Pending)?;
+        if t.status == status {
+            return None;
+        }
+        t.status = status;
+        let plugin_id = t.plugin_id;
+
+        //? Drop the oldest finished transactions beyond the cap
+        let mut finished = transactions
+            .iter()
+            .filter(|t| t.status != TransactionStatus::Pending)
+            .count();
+        transactions.retain(|t| {
+            if finished <= MAX_FINISHED || t.status == TransactionStatus::Pending {
+                return true;
+            }
+            finished -= 1;
+            false
+        });
+        Some(plugin_id)
+    }
+
+    /// Forgets every transaction that's no longer pending.
+    pub fn clear_finished(&self) {
+        self.transactions
+            .lock()
+            .unwrap()
+            .retain(|t| t.status == TransactionStatus::Pending);
+    }
+}
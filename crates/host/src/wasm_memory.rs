@@ -0,0 +1,329 @@
+//! Reads and caps the linear memory a wasm module declares, so plugins that
+//! would start out over their memory limit are rejected before they're
+//! instantiated, and plugins can't grow past it afterwards. Also recognizes
+//! component binaries, which the plugin runtime can't load.
+
+/// Size of a wasm memory page, in bytes
+const PAGE_SIZE: u64 = 64 * 1024;
+const IMPORT_SECTION: u8 = 2;
+const MEMORY_SECTION: u8 = 5;
+const MEMORY_IMPORT: u8 = 2;
+/// Set in a memory's limits flags when it declares a maximum
+const HAS_MAXIMUM: u64 = 1;
+
+/// Whether `wasm` is a component, built for the component model, rather than
+/// a core module. Components share the magic number but mark a different
+/// layer in their header.
+pub fn is_component(wasm: &[u8]) -> bool {
+    wasm.starts_with(b"\0asm") && wasm.get(6..8).is_some_and(|layer| layer == [1, 0])
+}
+
+/// A memory the module defines or imports, in pages.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Memory {
+    initial: u64,
+    maximum: Option<u64>,
+    imported: bool,
+}
+
+/// Returns the bytes of linear memory the module's memories start with,
+/// imported ones included, or None if the module can't be parsed.
+pub fn initial_memory_bytes(wasm: &[u8]) -> Option<u64> {
+    let total = memories(wasm)?
+        .iter()
+        .fold(0u64, |total, memory| total.saturating_add(memory.initial));
+    Some(total.saturating_mul(PAGE_SIZE))
+}
+
+/// Rewrites the module so its memories can't grow past `max_bytes` in total.
+/// Memories the module defines are given a maximum, with the room left over
+/// after every memory's initial size going to the first of them. Returns
+/// None if the module can't be parsed, or if it imports a memory that
+/// doesn't declare a maximum within the limit, since the engine can only
+/// hold imported memories to the maximum the module declares for them.
+pub fn cap_memory(wasm: &[u8], max_bytes: u64) -> Option<Vec<u8>> {
+    let memories = memories(wasm)?;
+    let initial = memories
+        .iter()
+        .fold(0u64, |total, memory| total.saturating_add(memory.initial));
+    let mut room = (max_bytes / PAGE_SIZE).checked_sub(initial)?;
+    for memory in memories.iter().filter(|memory| memory.imported) {
+        room = room.checked_sub(memory.maximum?.checked_sub(memory.initial)?)?;
+    }
+
+    let mut capped = wasm.get(..8)?.to_vec();
+    let mut reader = Reader(wasm.get(8..)?);
+    while !reader.0.is_empty() {
+        let id = reader.byte()?;
+        let size = reader.leb()? as usize;
+        let section = reader.take(size)?;
+        if id != MEMORY_SECTION {
+            capped.push(id);
+            write_leb(&mut capped, size as u64);
+            capped.extend_from_slice(section);
+            continue;
+        }
+
+        let mut section = Reader(section);
+        let count = section.leb()?;
+        let mut limits = Vec::new();
+        write_leb(&mut limits, count);
+        for _ in 0..count {
+            let (flags, memory) = section.limits(false)?;
+            let growth = match memory.maximum {
+                Some(maximum) => maximum.checked_sub(memory.initial)?.min(room),
+                None => room,
+            };
+            room -= growth;
+            write_leb(&mut limits, flags | HAS_MAXIMUM);
+            write_leb(&mut limits, memory.initial);
+            write_leb(&mut limits, memory.initial + growth);
+        }
+        capped.push(id);
+        write_leb(&mut capped, limits.len() as u64);
+        capped.extend_from_slice(&limits);
+    }
+    Some(capped)
+}
+
+/// Returns the memories the module imports, then those it defines.
+fn memories(wasm: &[u8]) -> Option<Vec<Memory>> {
+    if wasm.get(..4)? != b"\0asm" {
+        return None;
+    }
+    let mut memories = Vec::new();
+    let mut reader = Reader(wasm.get(8..)?);
+    while !reader.0.is_empty() {
+        let id = reader.byte()?;
+        let size = reader.leb()? as usize;
+        let mut section = Reader(reader.take(size)?);
+        match id {
+            IMPORT_SECTION => {
+                for _ in 0..section.leb()? {
+                    if let Some(memory) = section.import()? {
+                        memories.push(memory);
+                    }
+                }
+            }
+            MEMORY_SECTION => {
+                for _ in 0..section.leb()? {
+                    memories.push(section.limits(false)?.1);
+                }
+            }
+            _ => {}
+        }
+    }
+    Some(memories)
+}
+
+fn write_leb(out: &mut Vec<u8>, mut value: u64) {
+    loop {
+        let byte = (value & 0x7f) as u8;
+        value >>= 7;
+        if value == 0 {
+            out.push(byte);
+            return;
+        }
+        out.push(byte | 0x80);
+    }
+}
+
+struct Reader<'a>(&'a [u8]);
+
+impl<'a> Reader<'a> {
+    fn byte(&mut self) -> Option<u8> {
+        let (&byte, rest) = self.0.split_first()?;
+        self.0 = rest;
+        Some(byte)
+    }
+
+    fn take(&mut self, len: usize) -> Option<&'a [u8]> {
+        if len > self.0.len() {
+            return None;
+        }
+        let (taken, rest) = self.0.split_at(len);
+        self.0 = rest;
+        Some(taken)
+    }
+
+    /// Reads an unsigned LEB128 integer.
+    fn leb(&mut self) -> Option<u64> {
+        let mut value: u64 = 0;
+        for shift in (0..64).step_by(7) {
+            let byte = self.byte()?;
+            value |= u64::from(byte & 0x7f) << shift;
+            if byte & 0x80 == 0 {
+                return Some(value);
+            }
+        }
+        None
+    }
+
+    /// Reads a memory's or table's limits, returning their flags too.
+    fn limits(&mut self, imported: bool) -> Option<(u64, Memory)> {
+        let flags = self.leb()?;
+        let initial = self.leb()?;
+        let maximum = match flags & HAS_MAXIMUM {
+            0 => None,
+            _ => Some(self.leb()?),
+        };
+        let memory = Memory {
+            initial,
+            maximum,
+            imported,
+        };
+        Some((flags, memory))
+    }
+
+    /// Reads an import, returning it if it's a memory.
+    fn import(&mut self) -> Option<Option<Memory>> {
+        for _ in 0..2 {
+            let len = self.leb()? as usize;
+            self.take(len)?;
+        }
+        match self.byte()? {
+            0 => {
+                self.leb()?;
+            }
+            1 => {
+                self.byte()?;
+                self.limits(true)?;
+            }
+            MEMORY_IMPORT => return Some(Some(self.limits(true)?.1)),
+            3 => {
+                self.take(2)?;
+            }
+            4 => {
+                self.byte()?;
+                self.leb()?;
+            }
+            _ => return None,
+        }
+        Some(None)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MIB: u64 = 1024 * 1024;
+
+    fn module(sections: &[(u8, Vec<u8>)]) -> Vec<u8> {
+        let mut wasm = b"\0asm\x01\0\0\0".to_vec();
+        for (id, section) in sections {
+            wasm.push(*id);
+            write_leb(&mut wasm, section.len() as u64);
+            wasm.extend_from_slice(section);
+        }
+        wasm
+    }
+
+    /// A memory section defining memories with these initial and maximum
+    /// page counts.
+    fn memory_section(memories: &[(u64, Option<u64>)]) -> (u8, Vec<u8>) {
+        let mut section = Vec::new();
+        write_leb(&mut section, memories.len() as u64);
+        for (initial, maximum) in memories {
+            write_leb(&mut section, u64::from(maximum.is_some()));
+            write_leb(&mut section, *initial);
+            if let Some(maximum) = maximum {
+                write_leb(&mut section, *maximum);
+            }
+        }
+        (MEMORY_SECTION, section)
+    }
+
+    /// An import section importing a function, then a memory with these
+    /// initial and maximum page counts.
+    fn import_section(initial: u64, maximum: Option<u64>) -> (u8, Vec<u8>) {
+        let mut section = vec![2];
+        section.extend_from_slice(b"\x03env\x04func\x00\x00");
+        section.extend_from_slice(b"\x03env\x06memory");
+        section.push(MEMORY_IMPORT);
+        write_leb(&mut section, u64::from(maximum.is_some()));
+        write_leb(&mut section, initial);
+        if let Some(maximum) = maximum {
+            write_leb(&mut section, maximum);
+        }
+        (IMPORT_SECTION, section)
+    }
+
+    fn memories_of(wasm: &[u8]) -> Vec<(u64, Option<u64>)> {
+        memories(wasm)
+            .unwrap()
+            .iter()
+            .map(|memory| (memory.initial, memory.maximum))
+            .collect()
+    }
+
+    #[test]
+    fn sums_every_memory() {
+        let wasm = module(&[memory_section(&[(16, None), (4, Some(8))])]);
+        assert_eq!(initial_memory_bytes(&wasm), Some(20 * PAGE_SIZE));
+
+        let wasm = module(&[import_section(2, Some(4)), memory_section(&[(16, None)])]);
+        assert_eq!(initial_memory_bytes(&wasm), Some(18 * PAGE_SIZE));
+
+        assert_eq!(initial_memory_bytes(&module(&[])), Some(0));
+    }
+
+    #[test]
+    fn rejects_truncated_modules() {
+        let wasm = module(&[memory_section(&[(16, Some(32))])]);
+        for len in [0, 3, 9, wasm.len() - 1] {
+            assert_eq!(initial_memory_bytes(&wasm[..len]), None, "length {}", len);
+        }
+        assert_eq!(initial_memory_bytes(b"\0wasm\x01\0\0\0"), None);
+    }
+
+    #[test]
+    fn rejects_oversized_values() {
+        //? The section claims more bytes than the module has left
+        let mut wasm = module(&[memory_section(&[(16, None)])]);
+        wasm[9] += 1;
+        assert_eq!(initial_memory_bytes(&wasm), None);
+
+        //? An initial size encoded in more bytes than a u64 needs
+        let mut section = vec![1, 0];
+        section.extend_from_slice(&[0xff; 10]);
+        section.push(0x01);
+        let wasm = module(&[(MEMORY_SECTION, section)]);
+        assert_eq!(initial_memory_bytes(&wasm), None);
+
+        //? Page counts past u64 when converted to bytes saturate
+        let wasm = module(&[memory_section(&[
+            (u64::MAX / 2, None),
+            (u64::MAX / 2, None),
+        ])]);
+        assert_eq!(initial_memory_bytes(&wasm), Some(u64::MAX));
+    }
+
+    #[test]
+    fn caps_growth_to_the_limit() {
+        let custom = (0, b"\x04name".to_vec());
+        let wasm = module(&[custom.clone(), memory_section(&[(16, None), (4, Some(8))])]);
+
+        let capped = cap_memory(&wasm, 64 * MIB).unwrap();
+        assert_eq!(memories_of(&capped), [(16, Some(1020)), (4, Some(4))]);
+        assert!(capped.starts_with(&module(&[custom])));
+
+        let capped = cap_memory(&wasm, 2 * MIB).unwrap();
+        assert_eq!(memories_of(&capped), [(16, Some(28)), (4, Some(4))]);
+
+        assert_eq!(cap_memory(&wasm, MIB), None);
+    }
+
+    #[test]
+    fn caps_imported_memories_by_their_maximum() {
+        let wasm = module(&[import_section(2, Some(4)), memory_section(&[(16, None)])]);
+        let capped = cap_memory(&wasm, 2 * MIB).unwrap();
+        assert_eq!(memories_of(&capped), [(2, Some(4)), (16, Some(28))]);
+
+        let wasm = module(&[import_section(2, None)]);
+        assert_eq!(cap_memory(&wasm, 2 * MIB), None);
+
+        let wasm = module(&[import_section(2, Some(64))]);
+        assert_eq!(cap_memory(&wasm, 2 * MIB), None);
+    }
+}
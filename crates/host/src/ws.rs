@@ -0,0 +1,158 @@
+//! WebSocket connections plugins open through `host_ws_connect`.
+//!
+//! Each socket is driven by a background task that forwards messages the
+//! plugin sends and queues the ones it receives. The host drains the queue in
+//! `Host::deliver_ws_messages`, calling the owning plugin's `OnWsMessage` and
+//! `OnWsClosed` in the order the events arrived.
+
+use std::{
+    collections::{HashMap, VecDeque},
+    sync::{Arc, Mutex},
+};
+
+use futures::channel::mpsc;
+use tlock_hdk::{
+    tlock_api::host::{WsId, WsMessage},
+    wasmi_plugin_hdk::plugin_id::PluginId,
+};
+use uuid::Uuid;
+
+#[derive(Debug, Clone)]
+pub enum WsEvent {
+    Message(WsMessage),
+    /// The socket closed, with the error that closed it if any
+    Closed(Option<String>),
+}
+
+struct Socket {
+    plugin_id: PluginId,
+    outgoing: Outgoing,
+}
+
+type Inbox = Arc<Mutex<VecDeque<(WsId, WsEvent)>>>;
+type Outgoing = mpsc::UnboundedSender<WsMessage>;
+
+#[derive(Default)]
+pub struct WsConnections {
+    sockets: Mutex<HashMap<WsId, Socket>>,
+    inbox: Inbox,
+}
+
+impl WsConnections {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn connect(&self, plugin_id: PluginId, url: &str) -> Result<WsId, String> {
+        let id = WsId(Uuid::new_v4());
+        let outgoing = open(id, url, self.inbox.clone())?;
+        let socket = Socket {
+            plugin_id,
+            outgoing,
+        };
+        self.sockets.lock().unwrap().insert(id, socket);
+        Ok(id)
+    }
+
+    pub fn send(&self, plugin_id: PluginId, id: WsId, message: WsMessage) -> Result<(), String> {
+        let sockets = self.sockets.lock().unwrap();
+        let Some(socket) = sockets.get(&id).filter(|s| s.plugin_id == plugin_id) else {
+            return Err(format!("WebSocket {:?} not found", id));
+        };
+        socket
+            .outgoing
+            .unbounded_send(message)
+            .map_err(|_| format!("WebSocket {:?} is closed", id))
+    }
+
+    /// Closes a socket. Its remaining events are discarded.
+    pub fn close(&self, plugin_id: PluginId, id: WsId) {
+        let mut sockets = self.sockets.lock().unwrap();
+        if sockets.get(&id).is_some_and(|s| s.plugin_id == plugin_id) {
+            sockets.remove(&id);
+        }
+    }
+
+    pub fn remove_plugin(&self, plugin_id: &PluginId) {
+        self.sockets
+            .lock()
+            .unwrap()
+            .retain(|_, s| &s.plugin_id != plugin_id);
+    }
+
+    /// Takes every queued event, along with the plugin that owns its socket.
+    /// Events for sockets that were closed by their plugin are dropped.
+    pub fn take_events(&self) -> Vec<(PluginId, WsId, WsEvent)> {
+        let events: Vec<_> = self.inbox.lock().unwrap().drain(..).collect();
+        let mut sockets = self.sockets.lock().unwrap();
+        let mut owned = Vec::with_capacity(events.len());
+        for (id, event) in events {
+            let Some(plugin_id) = sockets.get(&id).map(|s| s.plugin_id) else {
+                continue;
+            };
+            if matches!(event, WsEvent::Closed(_)) {
+                sockets.remove(&id);
+            }
+            owned.push((plugin_id, id, event));
+        }
+        owned
+    }
+}
+
+/// Opens a socket and spawns the task driving it. Dropping the returned
+/// sender closes the socket.
+#[cfg(target_arch = "wasm32")]
+fn open(id: WsId, url: &str, inbox: Inbox) -> Result<Outgoing, String> {
+    use futures::{SinkExt, StreamExt, future};
+    use gloo_net::websocket::{Message, futures::WebSocket};
+
+    let socket = WebSocket::open(url).map_err(|e| format!("Failed to open WebSocket: {}", e))?;
+    let (mut write, mut read) = socket.split();
+    let (outgoing, mut rx) = mpsc::unbounded::<WsMessage>();
+
+    let writer = async move {
+        while let Some(message) = rx.next().await {
+            let message = match message {
+                WsMessage::Text(text) => Message::Text(text),
+                WsMessage::Binary(bytes) => Message::Bytes(bytes),
+            };
+            if let Err(e) = write.send(message).await {
+                return Some(e.to_string());
+            }
+        }
+        let _ = write.close().await;
+        None
+    };
+
+    let reader_inbox = inbox.clone();
+    let reader = async move {
+        while let Some(message) = read.next().await {
+            let message = match message {
+                Ok(Message::Text(text)) => WsMessage::Text(text),
+                Ok(Message::Bytes(bytes)) => WsMessage::Binary(bytes),
+                Err(e) => return Some(e.to_string()),
+            };
+            let event = WsEvent::Message(message);
+            reader_inbox.lock().unwrap().push_back((id, event));
+        }
+        None
+    };
+
+    wasm_bindgen_futures::spawn_local(async move {
+        let reason = match future::select(Box::pin(reader), Box::pin(writer)).await {
+            future::Either::Left((reason, _)) | future::Either::Right((reason, _)) => reason,
+        };
+        inbox
+            .lock()
+            .unwrap()
+            .push_back((id, WsEvent::Closed(reason)));
+    });
+    Ok(outgoing)
+}
+
+// TODO: Support native hosts. There's no async runtime natively to drive the
+// socket, so it'd need a thread per connection like `time::sleep`.
+#[cfg(not(target_arch = "wasm32"))]
+fn open(_id: WsId, _url: &str, _inbox: Inbox) -> Result<Outgoing, String> {
+    Err("WebSockets are only supported in the browser".to_string())
+}
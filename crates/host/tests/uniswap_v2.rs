@@ -0,0 +1,77 @@
+//! Drives uniswap-v2 end to end, against revm-provider and the EOA vault and
+//! coordinator. Needs the plugins built first, e.g. with `make plugins`, and
+//! network access for revm-provider's fork. The host fetches with reqwest,
+//! which natively needs a tokio runtime.
+
+#![cfg(feature = "test-harness")]
+
+use std::collections::HashMap;
+
+use host::harness::Harness;
+use serde_json::Value;
+use tlock_hdk::tlock_api::page::PageEvent;
+
+fn plugin(name: &str) -> Vec<u8> {
+    let path = format!(
+        "{}/../../target/wasm32-wasip1/debug/{}.wasm",
+        env!("CARGO_MANIFEST_DIR"),
+        name
+    );
+    std::fs::read(&path).unwrap_or_else(|_| panic!("{} is built, e.g. with `make plugins`", path))
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn quotes_a_swap() {
+    let harness = Harness::new();
+
+    let revm = harness
+        .load(plugin("revm-provider"), "revm-provider")
+        .await
+        .unwrap();
+    let eth_provider = harness.eth_provider(&revm);
+    harness.script(|script| script.eth_provider = eth_provider);
+
+    let vault = harness
+        .load(plugin("eoa-vault"), "eoa-vault")
+        .await
+        .unwrap();
+    let vault = harness.vault(&vault);
+    harness.script(|script| script.vault = vault);
+
+    let coordinator = harness
+        .load(plugin("eoa-coordinator"), "eoa-coordinator")
+        .await
+        .unwrap();
+    let coordinator = harness.coordinator(&coordinator);
+    harness.script(|script| script.coordinator = coordinator);
+
+    let uniswap = harness
+        .load(plugin("uniswap-v2"), "uniswap-v2")
+        .await
+        .unwrap();
+    let page = harness.page(&uniswap).expect("uniswap-v2 registers a page");
+
+    harness
+        .run(harness.host().page_on_load(page))
+        .await
+        .unwrap();
+    let interface = serde_json::to_string(&harness.interface(page).unwrap()).unwrap();
+    assert!(interface.contains("\"swap_form\""));
+
+    let form = HashMap::from([
+        ("from_token".to_string(), "0".to_string()),
+        ("to_token".to_string(), "1".to_string()),
+        ("amount".to_string(), "1.0".to_string()),
+    ]);
+    let event = PageEvent::FormSubmitted("swap_form".to_string(), form);
+    harness
+        .run(harness.host().page_on_update((page, event)))
+        .await
+        .unwrap();
+
+    let state = harness.state(&uniswap, "").unwrap();
+    let state: Value = serde_json::from_slice(&state).unwrap();
+    assert!(!state["quote"].is_null(), "quoted: {}", state);
+    let interface = serde_json::to_string(&harness.interface(page).unwrap()).unwrap();
+    assert!(interface.contains("Quote"));
+}
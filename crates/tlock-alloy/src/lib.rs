@@ -2,6 +2,7 @@ use std::task::Poll;
 
 use alloy::{
     eips::BlockId,
+    primitives::U256,
     rpc::{
         client::RpcClient,
         json_rpc::{RequestPacket, Response, ResponsePacket, ResponsePayload, SerializedRequest},
@@ -12,8 +13,11 @@ use alloy::{
 use serde::Deserialize;
 use serde_json::value::to_raw_value;
 use tlock_pdk::{
-    tlock_api::{RpcMethod, entities::EthProviderId, eth},
-    wasmi_plugin_pdk::transport::Transport,
+    tlock_api::{RpcMethod, entities::EthProviderId, eth, plugin::PluginManifest, trace},
+    wasmi_plugin_pdk::{
+        rpc_message::RpcError,
+        transport::{AsyncTransport, SyncManyTransport, Transport},
+    },
 };
 use tower_service::Service;
 use tracing::error;
@@ -49,6 +53,30 @@ impl AlloyBridge {
         };
         RpcClient::new(transport, false)
     }
+
+    /// Adds the eth methods the bridge may call to a plugin manifest.
+    pub fn manifest(manifest: PluginManifest) -> PluginManifest {
+        manifest
+            .with_method(eth::ChainId)
+            .with_method(eth::BlockNumber)
+            .with_method(eth::Call)
+            .with_method(eth::GetBalance)
+            .with_method(eth::GasPrice)
+            .with_method(eth::MaxPriorityFeePerGas)
+            .with_method(eth::BlobBaseFee)
+            .with_method(eth::EstimateGas)
+            .with_method(eth::GetTransactionCount)
+            .with_method(eth::SendRawTransaction)
+            .with_method(eth::GetTransactionReceipt)
+            .with_method(eth::GetBlock)
+            .with_method(eth::GetCode)
+            .with_method(eth::GetStorageAt)
+            .with_method(eth::GetProof)
+            .with_method(eth::CreateAccessList)
+            .with_method(eth::FeeHistory)
+            .with_method(trace::TraceTransaction)
+            .with_method(trace::TraceCall)
+    }
 }
 
 impl Service<RequestPacket> for AlloyBridge {
@@ -66,19 +94,10 @@ impl Service<RequestPacket> for AlloyBridge {
                 RequestPacket::Batch(rs) => rs,
             };
 
-            let mut responses = Vec::with_capacity(reqs.len());
-
-            for req in reqs {
-                let response = call_req(&req, transport.clone(), provider_id).await;
-                let response = match response {
-                    Ok(resp) => resp,
-                    Err(e) => {
-                        error!("Error handling request: req={:?}, error={:?}", req, e);
-                        return Err(e);
-                    }
-                };
-                responses.push(response);
-            }
+            let responses = match reqs.len() {
+                1 => vec![call_single(&reqs[0], &transport, provider_id).await?],
+                _ => call_batch(&reqs, &transport, provider_id)?,
+            };
 
             let packet = match responses.len() {
                 1 => ResponsePacket::Single(responses.into_iter().next().unwrap()),
@@ -97,13 +116,76 @@ impl Service<RequestPacket> for AlloyBridge {
     }
 }
 
-async fn call_req(
+/// Sends a single request to the provider.
+async fn call_single(
     req: &SerializedRequest,
-    transport: Transport,
+    transport: &Transport,
     provider_id: EthProviderId,
 ) -> Result<Response, TransportError> {
-    let id = req.id().clone();
+    let (method, params) = to_call(req, provider_id)?;
+    let result = match transport.call_async(method, params).await {
+        Ok(resp) => resp.result,
+        //? Map failed receipt lookups to null since that's what's expected for getTransactionReceipt
+        // TODO: Consider instead returning a Result<Option<Receipt>> to make this more explicit / correct.
+        Err(_) if method == eth::GetTransactionReceipt::NAME => serde_json::Value::Null,
+        Err(e) => {
+            let e: RpcError = e.into();
+            error!("Error handling request: req={:?}, error={:?}", req, e);
+            return Err(TransportErrorKind::custom_str(&e.to_string()));
+        }
+    };
+
+    to_response(req, &result)
+}
+
+/// Packs a batch of requests into a single `call_many` round trip.
+//? Batches aren't retried request by request on failure, since that could
+//? resend transactions that already went through.
+fn call_batch(
+    reqs: &[SerializedRequest],
+    transport: &Transport,
+    provider_id: EthProviderId,
+) -> Result<Vec<Response>, TransportError> {
+    let calls = reqs
+        .iter()
+        .map(|req| to_call(req, provider_id))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let resps = transport.call_many(calls).map_err(|e| {
+        let e: RpcError = e.into();
+        error!("Error handling batch: reqs={:?}, error={:?}", reqs, e);
+        TransportErrorKind::custom_str(&e.to_string())
+    })?;
+
+    if resps.len() != reqs.len() {
+        return Err(TransportErrorKind::custom_str(&format!(
+            "Expected {} responses in batch, got {}",
+            reqs.len(),
+            resps.len()
+        )));
+    }
+
+    reqs.iter()
+        .zip(resps)
+        .map(|(req, resp)| to_response(req, &resp.result))
+        .collect()
+}
+
+fn to_response(
+    req: &SerializedRequest,
+    result: &serde_json::Value,
+) -> Result<Response, TransportError> {
+    Ok(Response {
+        id: req.id().clone(),
+        payload: ResponsePayload::Success(to_raw_value(result).map_err(TransportError::ser_err)?),
+    })
+}
 
+/// Translates an alloy request into the matching tlock method call.
+fn to_call(
+    req: &SerializedRequest,
+    provider_id: EthProviderId,
+) -> Result<(&'static str, serde_json::Value), TransportError> {
     // Ensure params field always exists for deserialization
     let method = req.meta().method.clone();
     let default_params = serde_json::value::RawValue::from_string("[]".to_string()).unwrap();
@@ -116,55 +198,28 @@ async fn call_req(
 
     let req = EthRequest::deserialize(&json_with_params).map_err(TransportError::ser_err)?;
 
-    let resp = match req {
-        EthRequest::EthChainId(()) => {
-            let resp = eth::ChainId
-                .call_async(transport.clone(), provider_id)
-                .await
-                .map_err(|e| TransportErrorKind::custom_str(&e.to_string()))?;
-            serde_json::to_value(resp).map_err(TransportError::ser_err)?
-        }
-        EthRequest::EthBlockNumber(()) => {
-            let resp = eth::BlockNumber
-                .call_async(transport.clone(), provider_id)
-                .await
-                .map_err(|e| TransportErrorKind::custom_str(&e.to_string()))?;
-            serde_json::to_value(resp).map_err(TransportError::ser_err)?
-        }
+    let call = match req {
+        EthRequest::EthChainId(()) => eth::ChainId.request(provider_id),
+        EthRequest::EthBlockNumber(()) => eth::BlockNumber.request(provider_id),
         EthRequest::EthCall(tx_request, block_id, state_override, block_override) => {
             let tx_request = tx_request.inner;
             let block_id = block_id.unwrap_or(BlockId::latest());
             let block_override = block_override.map(|b| *b);
-            let resp = eth::Call
-                .call_async(
-                    transport.clone(),
-                    (
-                        provider_id,
-                        tx_request,
-                        block_id,
-                        state_override,
-                        block_override,
-                    ),
-                )
-                .await
-                .map_err(|e| TransportErrorKind::custom_str(&e.to_string()))?;
-            serde_json::to_value(resp).map_err(TransportError::ser_err)?
+            eth::Call.request((
+                provider_id,
+                tx_request,
+                block_id,
+                state_override,
+                block_override,
+            ))
         }
         EthRequest::EthGetBalance(address, block_id) => {
             let block_id = block_id.unwrap_or(BlockId::latest());
-            let resp = eth::GetBalance
-                .call_async(transport.clone(), (provider_id, address, block_id))
-                .await
-                .map_err(|e| TransportErrorKind::custom_str(&e.to_string()))?;
-            serde_json::to_value(resp).map_err(TransportError::ser_err)?
-        }
-        EthRequest::EthGasPrice(_) => {
-            let resp = eth::GasPrice
-                .call_async(transport.clone(), provider_id)
-                .await
-                .map_err(|e| TransportErrorKind::custom_str(&e.to_string()))?;
-            serde_json::to_value(resp).map_err(TransportError::ser_err)?
+            eth::GetBalance.request((provider_id, address, block_id))
         }
+        EthRequest::EthGasPrice(_) => eth::GasPrice.request(provider_id),
+        EthRequest::EthMaxPriorityFeePerGas(_) => eth::MaxPriorityFeePerGas.request(provider_id),
+        EthRequest::EthBlobBaseFee(_) => eth::BlobBaseFee.request(provider_id),
         EthRequest::EthEstimateGas(
             transaction_request,
             block_id,
@@ -174,47 +229,23 @@ async fn call_req(
             let transaction_request = transaction_request.inner;
             let block_id = block_id.unwrap_or(BlockId::latest());
             let block_override = block_override.map(|b| *b);
-            let resp = eth::EstimateGas
-                .call_async(
-                    transport.clone(),
-                    (
-                        provider_id,
-                        transaction_request,
-                        block_id,
-                        state_override,
-                        block_override,
-                    ),
-                )
-                .await
-                .map_err(|e| TransportErrorKind::custom_str(&e.to_string()))?;
-            serde_json::to_value(resp).map_err(TransportError::ser_err)?
+            eth::EstimateGas.request((
+                provider_id,
+                transaction_request,
+                block_id,
+                state_override,
+                block_override,
+            ))
         }
         EthRequest::EthGetTransactionCount(address, block_id) => {
             let block_id = block_id.unwrap_or(BlockId::latest());
-            let resp = eth::GetTransactionCount
-                .call_async(transport.clone(), (provider_id, address, block_id))
-                .await
-                .map_err(|e| TransportErrorKind::custom_str(&e.to_string()))?;
-            serde_json::to_value(resp).map_err(TransportError::ser_err)?
+            eth::GetTransactionCount.request((provider_id, address, block_id))
         }
         EthRequest::EthSendRawTransaction(bytes) => {
-            let resp = eth::SendRawTransaction
-                .call_async(transport.clone(), (provider_id, bytes))
-                .await
-                .map_err(|e| TransportErrorKind::custom_str(&e.to_string()))?;
-            serde_json::to_value(resp).map_err(TransportError::ser_err)?
+            eth::SendRawTransaction.request((provider_id, bytes))
         }
         EthRequest::EthGetTransactionReceipt(txhash) => {
-            let resp = eth::GetTransactionReceipt
-                .call_async(transport.clone(), (provider_id, txhash))
-                .await
-                .map_err(|e| TransportErrorKind::custom_str(&e.to_string()));
-            //? Map None responses to null JSON value since that's what's expected for getTransactionReceipt
-            // TODO: Consider instead returning a Result<Option<Receipt>> to make this more explicit / correct.
-            match resp {
-                Ok(r) => serde_json::to_value(r).map_err(TransportError::ser_err)?,
-                Err(_) => serde_json::to_value(None::<()>).map_err(TransportError::ser_err)?,
-            }
+            eth::GetTransactionReceipt.request((provider_id, txhash))
         }
         EthRequest::EthGetBlockByNumber(block_number, full) => {
             let transactions_kind = if full {
@@ -222,41 +253,37 @@ async fn call_req(
             } else {
                 BlockTransactionsKind::Hashes
             };
-            let resp = eth::GetBlock
-                .call_async(
-                    transport.clone(),
-                    (provider_id, block_number.into(), transactions_kind),
-                )
-                .await
-                .map_err(|e| TransportErrorKind::custom_str(&e.to_string()))?;
-            serde_json::to_value(resp).map_err(TransportError::ser_err)?
+            eth::GetBlock.request((provider_id, block_number.into(), transactions_kind))
         }
         EthRequest::EthGetCodeAt(address, block_id) => {
             let block_id = block_id.unwrap_or(BlockId::latest());
-            let resp = eth::GetCode
-                .call_async(transport.clone(), (provider_id, address, block_id))
-                .await
-                .map_err(|e| TransportErrorKind::custom_str(&e.to_string()))?;
-            serde_json::to_value(resp).map_err(TransportError::ser_err)?
+            eth::GetCode.request((provider_id, address, block_id))
         }
         EthRequest::EthGetStorageAt(address, slot, block_id) => {
             let block_id = block_id.unwrap_or(BlockId::latest());
-            let resp = eth::GetStorageAt
-                .call_async(transport.clone(), (provider_id, address, slot, block_id))
-                .await
-                .map_err(|e| TransportErrorKind::custom_str(&e.to_string()))?;
-            serde_json::to_value(resp).map_err(TransportError::ser_err)?
+            eth::GetStorageAt.request((provider_id, address, slot, block_id))
+        }
+        EthRequest::EthCreateAccessList(tx_request, block_id) => {
+            let tx_request = tx_request.inner;
+            let block_id = block_id.unwrap_or(BlockId::latest());
+            eth::CreateAccessList.request((provider_id, tx_request, block_id))
+        }
+        EthRequest::EthGetProof(address, keys, block_id) => {
+            let block_id = block_id.unwrap_or(BlockId::latest());
+            let keys = keys.into_iter().map(|k| U256::from_be_bytes(k.0)).collect();
+            eth::GetProof.request((provider_id, address, keys, block_id))
         }
         EthRequest::EthFeeHistory(block_count, newest_block, reward_percentiles) => {
             let block_count: u64 = block_count.saturating_to();
-            let resp = eth::FeeHistory
-                .call_async(
-                    transport.clone(),
-                    (provider_id, block_count, newest_block, reward_percentiles),
-                )
-                .await
-                .map_err(|e| TransportErrorKind::custom_str(&e.to_string()))?;
-            serde_json::to_value(resp).map_err(TransportError::ser_err)?
+            eth::FeeHistory.request((provider_id, block_count, newest_block, reward_percentiles))
+        }
+        EthRequest::DebugTraceTransaction(tx_hash, opts) => {
+            trace::TraceTransaction.request((provider_id, tx_hash, opts.tracing_options))
+        }
+        EthRequest::DebugTraceCall(tx_request, block_id, opts) => {
+            let tx_request = tx_request.inner;
+            let block_id = block_id.unwrap_or(BlockId::latest());
+            trace::TraceCall.request((provider_id, tx_request, block_id, opts))
         }
         _ => {
             return Err(TransportErrorKind::custom_str(
@@ -269,10 +296,5 @@ async fn call_req(
         }
     };
 
-    let response = Response {
-        id,
-        payload: ResponsePayload::Success(to_raw_value(&resp).map_err(TransportError::ser_err)?),
-    };
-
-    Ok(response)
+    call.map_err(|e| TransportErrorKind::custom_str(&e.to_string()))
 }
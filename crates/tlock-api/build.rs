@@ -0,0 +1,53 @@
+//! Lists every method `src/lib.rs` declares with `rpc_method!`, so
+//! `schema::methods` describes each of them without being kept by hand.
+
+use std::{env, fs, path::Path};
+
+fn main() {
+    println!("cargo::rerun-if-changed=src/lib.rs");
+    let source = fs::read_to_string("src/lib.rs").expect("src/lib.rs is readable");
+    let lines: Vec<&str> = source.lines().collect();
+
+    let mut module = None;
+    let mut methods = Vec::new();
+    for (i, line) in lines.iter().enumerate() {
+        if let Some(name) = line
+            .strip_prefix("pub mod ")
+            .and_then(|rest| rest.strip_suffix(" {"))
+        {
+            module = Some(name);
+            continue;
+        }
+        if line.trim() != "rpc_method!(" {
+            continue;
+        }
+
+        //? The name and struct come first, after any comments and attributes,
+        //? and may each be on their own line
+        let mut declaration = String::new();
+        for line in lines[i + 1..].iter().map(|line| line.trim()) {
+            if line.starts_with("//") || line.starts_with("#[") {
+                continue;
+            }
+            declaration.push_str(line);
+            if declaration.matches(',').count() >= 2 {
+                break;
+            }
+        }
+        //? Skip the macro's own recursive invocations
+        if declaration.starts_with('$') {
+            continue;
+        }
+        let module = module.expect("methods are declared inside a namespace module");
+        let name = declaration.split(',').nth(1).expect("method has a struct");
+        methods.push(format!(
+            "of::<crate::{}::{}>(generator)",
+            module,
+            name.trim()
+        ));
+    }
+
+    let out = Path::new(&env::var("OUT_DIR").expect("OUT_DIR is set")).join("methods.rs");
+    fs::write(out, format!("vec![\n    {},\n]\n", methods.join(",\n    ")))
+        .expect("OUT_DIR is writable");
+}
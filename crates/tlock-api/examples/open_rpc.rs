@@ -0,0 +1,11 @@
+//! Prints the API's OpenRPC document.
+//!
+//! `cargo run -p tlock-api --example open_rpc > tlock.openrpc.json`
+
+fn main() {
+    let doc = tlock_api::schema::open_rpc();
+    println!(
+        "{}",
+        serde_json::to_string_pretty(&doc).expect("document is valid JSON")
+    );
+}
@@ -1,13 +1,23 @@
-use std::{fmt::Display, str::FromStr};
+use std::{borrow::Cow, fmt::Display, str::FromStr};
 
-use alloy::primitives::Address;
+use alloy::primitives::{Address, U256};
+use schemars::{JsonSchema, Schema, SchemaGenerator, json_schema};
 use serde::{Deserialize, Serialize};
 
 // ---------- ChainId ----------
 
+/// CAIP-4 reference for Bitcoin mainnet (first 32 hex chars of the genesis hash)
+pub const BIP122_BITCOIN_MAINNET: &str = "000000000019d6689c085ae165831e93";
+/// CAIP-4 reference for Bitcoin testnet3
+pub const BIP122_BITCOIN_TESTNET: &str = "000000000933ea01ad0ba521a0c8e048";
+
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum ChainId {
     Evm(Option<u64>),
+    /// BIP-122 chain, referenced by its truncated genesis block hash.
+    ///
+    /// https://github.com/ChainAgnostic/CAIPs/blob/main/CAIPs/caip-4.md
+    Bip122(Option<String>),
     Custom {
         namespace: String,
         reference: Option<String>,
@@ -20,6 +30,9 @@ impl ChainId {
             let chain_id = reference.and_then(|r| if r == "_" { None } else { r.parse().ok() });
             return Self::Evm(chain_id);
         }
+        if namespace == "bip122" {
+            return Self::Bip122(reference.filter(|r| r != "_"));
+        }
         Self::Custom {
             namespace,
             reference,
@@ -30,9 +43,14 @@ impl ChainId {
         Self::Evm(Some(chain_id))
     }
 
+    pub fn new_bip122(genesis_hash: &str) -> Self {
+        Self::Bip122(Some(genesis_hash.to_string()))
+    }
+
     pub fn namespace(&self) -> &str {
         match self {
             Self::Evm(_) => "eip155",
+            Self::Bip122(_) => "bip122",
             Self::Custom { namespace, .. } => namespace,
         }
     }
@@ -41,6 +59,8 @@ impl ChainId {
         match self {
             Self::Evm(Some(id)) => Some(id.to_string()),
             Self::Evm(None) => Some("_".to_string()),
+            Self::Bip122(Some(hash)) => Some(hash.clone()),
+            Self::Bip122(None) => Some("_".to_string()),
             Self::Custom { reference, .. } => reference.clone(),
         }
     }
@@ -64,6 +84,14 @@ impl FromStr for ChainId {
                 };
                 Ok(Self::Evm(chain_id))
             }
+            ["bip122", reference] => {
+                let genesis_hash = if *reference == "_" {
+                    None
+                } else {
+                    Some(reference.to_string())
+                };
+                Ok(Self::Bip122(genesis_hash))
+            }
             [namespace, reference] => {
                 let reference = if *reference == "_" {
                     None
@@ -88,6 +116,8 @@ impl Display for ChainId {
         match self {
             Self::Evm(Some(id)) => write!(f, "eip155:{}", id),
             Self::Evm(None) => write!(f, "eip155:_"),
+            Self::Bip122(Some(hash)) => write!(f, "bip122:{}", hash),
+            Self::Bip122(None) => write!(f, "bip122:_"),
             Self::Custom {
                 namespace,
                 reference,
@@ -121,6 +151,19 @@ impl<'de> Deserialize<'de> for ChainId {
     }
 }
 
+impl JsonSchema for ChainId {
+    fn schema_name() -> Cow<'static, str> {
+        "ChainId".into()
+    }
+
+    fn json_schema(_: &mut SchemaGenerator) -> Schema {
+        json_schema!({
+            "type": "string",
+            "description": "A CAIP-2 chain ID, e.g. `eip155:1`",
+        })
+    }
+}
+
 // ---------- AccountId ----------
 
 /// CAIP-10 Account ID.
@@ -153,6 +196,13 @@ impl AccountId {
         }
     }
 
+    pub fn new_bip122(genesis_hash: &str, address: String) -> Self {
+        Self {
+            chain_id: ChainId::new_bip122(genesis_hash),
+            address: AccountAddress::Custom(address),
+        }
+    }
+
     pub fn chain_id(&self) -> &ChainId {
         &self.chain_id
     }
@@ -236,6 +286,19 @@ impl<'de> Deserialize<'de> for AccountId {
     }
 }
 
+impl JsonSchema for AccountId {
+    fn schema_name() -> Cow<'static, str> {
+        "AccountId".into()
+    }
+
+    fn json_schema(_: &mut SchemaGenerator) -> Schema {
+        json_schema!({
+            "type": "string",
+            "description": "A CAIP-10 account ID, e.g. `eip155:1:0xab16...`",
+        })
+    }
+}
+
 // ---------- AssetId ----------
 
 /// CAIP-19 Asset ID
@@ -251,6 +314,16 @@ pub struct AssetId {
 pub enum AssetType {
     Slip44(u32),
     Erc20(Address),
+    /// A single ERC-721 token, formatted as `erc721:<contract>/<token_id>`.
+    Erc721 {
+        contract: Address,
+        token_id: U256,
+    },
+    /// A single ERC-1155 token ID, formatted as `erc1155:<contract>/<token_id>`.
+    Erc1155 {
+        contract: Address,
+        token_id: U256,
+    },
     Custom {
         namespace: String,
         reference: String,
@@ -271,6 +344,16 @@ impl AssetId {
                     }
                 }
             }
+            "erc721" | "erc1155" => match parse_nft_reference(&reference) {
+                Ok((contract, token_id)) if namespace == "erc721" => {
+                    AssetType::Erc721 { contract, token_id }
+                }
+                Ok((contract, token_id)) => AssetType::Erc1155 { contract, token_id },
+                Err(_) => AssetType::Custom {
+                    namespace,
+                    reference,
+                },
+            },
             _ => AssetType::Custom {
                 namespace,
                 reference,
@@ -293,6 +376,28 @@ impl AssetId {
         }
     }
 
+    pub const fn erc721(chain_id: u64, contract: Address, token_id: U256) -> Self {
+        Self {
+            chain_id: ChainId::Evm(Some(chain_id)),
+            asset: AssetType::Erc721 { contract, token_id },
+        }
+    }
+
+    pub const fn erc1155(chain_id: u64, contract: Address, token_id: U256) -> Self {
+        Self {
+            chain_id: ChainId::Evm(Some(chain_id)),
+            asset: AssetType::Erc1155 { contract, token_id },
+        }
+    }
+
+    /// Native BTC on a BIP-122 chain
+    pub fn btc(genesis_hash: &str) -> Self {
+        Self {
+            chain_id: ChainId::new_bip122(genesis_hash),
+            asset: AssetType::Slip44(0),
+        }
+    }
+
     pub fn chain_id(&self) -> &ChainId {
         &self.chain_id
     }
@@ -301,6 +406,8 @@ impl AssetId {
         match &self.asset {
             AssetType::Slip44(_) => "slip44",
             AssetType::Erc20(_) => "erc20",
+            AssetType::Erc721 { .. } => "erc721",
+            AssetType::Erc1155 { .. } => "erc1155",
             AssetType::Custom { namespace, .. } => namespace,
         }
     }
@@ -309,10 +416,23 @@ impl AssetId {
         match &self.asset {
             AssetType::Slip44(coin) => coin.to_string(),
             AssetType::Erc20(addr) => format!("{:#x}", addr),
+            AssetType::Erc721 { contract, .. } | AssetType::Erc1155 { contract, .. } => {
+                format!("{:#x}", contract)
+            }
             AssetType::Custom { reference, .. } => reference.clone(),
         }
     }
 
+    /// Token ID for NFT assets, or None for fungible assets.
+    pub fn token_id(&self) -> Option<U256> {
+        match &self.asset {
+            AssetType::Erc721 { token_id, .. } | AssetType::Erc1155 { token_id, .. } => {
+                Some(*token_id)
+            }
+            _ => None,
+        }
+    }
+
     pub fn try_into_erc20_address(&self) -> Result<Address, String> {
         match &self.chain_id {
             ChainId::Evm(_) => match &self.asset {
@@ -357,6 +477,14 @@ impl FromStr for AssetId {
                     .map_err(|e| format!("Invalid ERC20 address: {}", e))?;
                 AssetType::Erc20(addr)
             }
+            "erc721" => {
+                let (contract, token_id) = parse_nft_reference(asset_parts[1])?;
+                AssetType::Erc721 { contract, token_id }
+            }
+            "erc1155" => {
+                let (contract, token_id) = parse_nft_reference(asset_parts[1])?;
+                AssetType::Erc1155 { contract, token_id }
+            }
             namespace => AssetType::Custom {
                 namespace: namespace.to_string(),
                 reference: asset_parts[1].to_string(),
@@ -367,6 +495,19 @@ impl FromStr for AssetId {
     }
 }
 
+/// Parses a `<contract>/<token_id>` NFT asset reference.
+fn parse_nft_reference(reference: &str) -> Result<(Address, U256), String> {
+    let (contract, token_id) = reference
+        .split_once('/')
+        .ok_or_else(|| format!("Missing token ID in NFT asset: {}", reference))?;
+    let contract = contract
+        .parse::<Address>()
+        .map_err(|e| format!("Invalid NFT contract address: {}", e))?;
+    let token_id =
+        U256::from_str_radix(token_id, 10).map_err(|e| format!("Invalid NFT token ID: {}", e))?;
+    Ok((contract, token_id))
+}
+
 impl Display for AssetId {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "{}/{}", self.chain_id, self.asset)
@@ -392,11 +533,30 @@ impl<'de> Deserialize<'de> for AssetId {
     }
 }
 
+impl JsonSchema for AssetId {
+    fn schema_name() -> Cow<'static, str> {
+        "AssetId".into()
+    }
+
+    fn json_schema(_: &mut SchemaGenerator) -> Schema {
+        json_schema!({
+            "type": "string",
+            "description": "A CAIP-19 asset ID, e.g. `eip155:1/slip44:60`",
+        })
+    }
+}
+
 impl Display for AssetType {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             AssetType::Slip44(coin) => write!(f, "slip44:{}", coin),
             AssetType::Erc20(addr) => write!(f, "erc20:{:#x}", addr),
+            AssetType::Erc721 { contract, token_id } => {
+                write!(f, "erc721:{:#x}/{}", contract, token_id)
+            }
+            AssetType::Erc1155 { contract, token_id } => {
+                write!(f, "erc1155:{:#x}/{}", contract, token_id)
+            }
             AssetType::Custom {
                 namespace,
                 reference,
@@ -455,4 +615,36 @@ mod tests {
         let parsed: ChainId = "eip155:_".parse().unwrap();
         assert_eq!(parsed, chain);
     }
+
+    #[test]
+    fn test_bip122_asset_id_serde() {
+        let asset = AssetId::btc(BIP122_BITCOIN_MAINNET);
+
+        let json = serde_json::to_string(&asset).unwrap();
+        assert_eq!(json, "\"bip122:000000000019d6689c085ae165831e93/slip44:0\"");
+
+        let parsed: AssetId = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed, asset);
+    }
+
+    #[test]
+    fn test_erc721_asset_id_serde() {
+        let asset = AssetId::erc721(
+            1,
+            "0x06012c8cf97bead5deae237070f9587f8e7a266d"
+                .parse()
+                .unwrap(),
+            U256::from(771769),
+        );
+
+        let json = serde_json::to_string(&asset).unwrap();
+        assert_eq!(
+            json,
+            "\"eip155:1/erc721:0x06012c8cf97bead5deae237070f9587f8e7a266d/771769\""
+        );
+
+        let parsed: AssetId = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed, asset);
+        assert_eq!(parsed.token_id(), Some(U256::from(771769)));
+    }
 }
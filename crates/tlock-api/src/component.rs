@@ -1,10 +1,11 @@
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
 use crate::caip::{AccountId, AssetId, ChainId};
 
 /// Basic UI component templates that can be used by plugins to build pages
 /// and other UI custom elements.
-#[derive(PartialEq, Clone, Serialize, Deserialize, Debug)]
+#[derive(PartialEq, Eq, Clone, Serialize, Deserialize, JsonSchema, Debug)]
 pub enum Component {
     Container {
         children: Vec<Component>,
@@ -40,6 +41,14 @@ pub enum Component {
         selected: Option<String>,
         id: String,
     },
+    /// A dropdown of accounts, shown by label. The selected account is
+    /// submitted in its CAIP-10 form.
+    AccountDropdownInput {
+        label: String,
+        options: Vec<(String, AccountId)>,
+        selected: Option<AccountId>,
+        id: String,
+    },
     SubmitInput {
         text: String,
     },
@@ -49,8 +58,15 @@ pub enum Component {
     Account {
         id: AccountId,
     },
+    /// An EVM address, displayed by its ENS name if one is given.
+    Address {
+        #[schemars(with = "crate::schema::Hex")]
+        address: alloy::primitives::Address,
+        name: Option<String>,
+    },
     Asset {
         id: AssetId,
+        #[schemars(with = "Option<crate::schema::Hex>")]
         balance: Option<alloy::primitives::U256>,
     },
     EntityId {
@@ -59,12 +75,86 @@ pub enum Component {
     Hex {
         data: Vec<u8>,
     },
+    /// Opens a page on top of the current page when clicked.
+    Link {
+        text: String,
+        page_id: crate::entities::PageId,
+    },
+    /// Shows one of several labeled sections at a time, starting with the
+    /// first. Switching tabs sends a `PageEvent::TabChanged`.
+    Tabs {
+        items: Vec<(String, Component)>,
+        id: String,
+    },
+    /// A dialog shown above the page for as long as it's part of the page.
+    /// Plugins open it by setting a page that includes it, and close it by
+    /// setting one that doesn't. Dismissing it sends a
+    /// `PageEvent::ModalDismissed`.
+    Modal {
+        title: String,
+        children: Vec<Component>,
+        id: String,
+    },
+    /// A progress bar, `percent` of the way full.
+    Progress {
+        label: String,
+        percent: u8,
+    },
+    /// An activity indicator for work of unknown length.
+    Spinner {
+        label: String,
+    },
 }
 
 impl Component {
     pub fn empty() -> Self {
         Component::Container { children: vec![] }
     }
+
+    /// Returns the pages this component and its children link to.
+    pub fn link_targets(&self) -> Vec<crate::entities::PageId> {
+        let mut targets = Vec::new();
+        self.collect_link_targets(&mut targets);
+        targets
+    }
+
+    /// Whether this component or any of its children shows progress, with a
+    /// `Progress` or `Spinner`.
+    pub fn shows_progress(&self) -> bool {
+        match self {
+            Component::Progress { .. } | Component::Spinner { .. } => true,
+            Component::Container { children }
+            | Component::Form {
+                fields: children, ..
+            }
+            | Component::Modal { children, .. } => children.iter().any(Component::shows_progress),
+            Component::UnorderedList { items } | Component::Tabs { items, .. } => {
+                items.iter().any(|(_, child)| child.shows_progress())
+            }
+            _ => false,
+        }
+    }
+
+    fn collect_link_targets(&self, targets: &mut Vec<crate::entities::PageId>) {
+        match self {
+            Component::Link { page_id, .. } => targets.push(*page_id),
+            Component::Container { children }
+            | Component::Form {
+                fields: children, ..
+            }
+            | Component::Modal { children, .. } => {
+                for child in children {
+                    child.collect_link_targets(targets);
+                }
+            }
+            Component::UnorderedList { items } | Component::Tabs { items, .. } => {
+                for (_, child) in items {
+                    child.collect_link_targets(targets);
+                }
+            }
+            _ => {}
+        }
+    }
 }
 
 impl From<&str> for Component {
@@ -111,6 +201,43 @@ where
     Component::UnorderedList { items }
 }
 
+pub fn tabs<I, S>(id: impl Into<String>, items: I) -> Component
+where
+    I: IntoIterator<Item = (S, Component)>,
+    S: Into<String>,
+{
+    let items = items.into_iter().map(|(k, v)| (k.into(), v)).collect();
+    Component::Tabs {
+        id: id.into(),
+        items,
+    }
+}
+
+pub fn modal<I>(id: impl Into<String>, title: impl Into<String>, children: I) -> Component
+where
+    I: IntoIterator<Item = Component>,
+{
+    Component::Modal {
+        id: id.into(),
+        title: title.into(),
+        children: children.into_iter().collect(),
+    }
+}
+
+/// Builds a progress bar. `percent` is capped at 100.
+pub fn progress(label: impl Into<String>, percent: u8) -> Component {
+    Component::Progress {
+        label: label.into(),
+        percent: percent.min(100),
+    }
+}
+
+pub fn spinner(label: impl Into<String>) -> Component {
+    Component::Spinner {
+        label: label.into(),
+    }
+}
+
 pub fn button_input(id: impl Into<String>, text: impl Into<String>) -> Component {
     Component::ButtonInput {
         id: id.into(),
@@ -164,6 +291,27 @@ where
     }
 }
 
+/// Builds an account dropdown, typically from the user's address book
+/// contacts.
+pub fn account_dropdown<I, S>(
+    id: impl Into<String>,
+    label: impl Into<String>,
+    options: I,
+    selected: Option<AccountId>,
+) -> Component
+where
+    I: IntoIterator<Item = (S, AccountId)>,
+    S: Into<String>,
+{
+    let options = options.into_iter().map(|(l, a)| (l.into(), a)).collect();
+    Component::AccountDropdownInput {
+        id: id.into(),
+        label: label.into(),
+        options,
+        selected,
+    }
+}
+
 pub fn chain(id: ChainId) -> Component {
     Component::Chain { id }
 }
@@ -172,6 +320,10 @@ pub fn account(id: AccountId) -> Component {
     Component::Account { id }
 }
 
+pub fn address(address: alloy::primitives::Address, name: Option<String>) -> Component {
+    Component::Address { address, name }
+}
+
 pub fn asset(id: AssetId, balance: Option<alloy::primitives::U256>) -> Component {
     Component::Asset { id, balance }
 }
@@ -180,8 +332,48 @@ pub fn entity_id(id: crate::entities::EntityId) -> Component {
     Component::EntityId { id }
 }
 
+pub fn link(text: impl Into<String>, page_id: crate::entities::PageId) -> Component {
+    Component::Link {
+        text: text.into(),
+        page_id,
+    }
+}
+
 pub fn hex(data: &[u8]) -> Component {
     Component::Hex {
         data: data.to_vec(),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::entities::PageId;
+
+    #[test]
+    fn link_targets_include_nested_links() {
+        let (first, second, third) = (PageId::new(), PageId::new(), PageId::new());
+        let page = container([
+            link("First", first),
+            tabs("tabs", [("Tab", container([link("Second", second)]))]),
+            modal("modal", "Modal", [link("Third", third)]),
+            text("No link"),
+        ]);
+        assert_eq!(page.link_targets(), vec![first, second, third]);
+        assert!(text("No link").link_targets().is_empty());
+    }
+
+    #[test]
+    fn shows_nested_progress() {
+        let page = container([text("Status"), modal("modal", "Modal", [spinner("")])]);
+        assert!(page.shows_progress());
+        assert!(!container([text("Idle")]).shows_progress());
+        assert_eq!(
+            progress("Done", 150),
+            Component::Progress {
+                label: "Done".into(),
+                percent: 100,
+            }
+        );
+    }
+}
@@ -1,11 +1,12 @@
 use std::fmt::{self, Display};
 
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
 /// Domains represent the different possible semantic categories of entities.
 /// All entities from a given domain must share a common interface, but may
 /// have different internal implementations and behaviors.
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "lowercase")]
 pub enum Domain {
     /// Vaults can hold, transfer, and receive assets.
@@ -16,6 +17,20 @@ pub enum Domain {
     EthProvider,
     /// Coordinator domain for coordinating on-chain actions securely.
     Coordinator,
+    /// SolProviders can provide Solana-style RPC access.
+    SolProvider,
+    /// BtcProviders can provide UTXO-based Bitcoin access.
+    BtcProvider,
+    /// PriceProviders can quote asset prices in fiat currencies.
+    PriceProvider,
+    /// Signers can sign raw messages on behalf of other plugins.
+    Signer,
+    /// BundlerProviders can build and submit ERC-4337 user operations.
+    BundlerProvider,
+    /// TokenLists can list and search the tokens available on a chain.
+    TokenList,
+    /// NftProviders can list and describe ERC-721 and ERC-1155 tokens.
+    NftProvider,
 }
 
 impl Display for Domain {
@@ -25,6 +40,13 @@ impl Display for Domain {
             Domain::Page => write!(f, "page"),
             Domain::EthProvider => write!(f, "ethprovider"),
             Domain::Coordinator => write!(f, "coordinator"),
+            Domain::SolProvider => write!(f, "solprovider"),
+            Domain::BtcProvider => write!(f, "btcprovider"),
+            Domain::PriceProvider => write!(f, "priceprovider"),
+            Domain::Signer => write!(f, "signer"),
+            Domain::BundlerProvider => write!(f, "bundlerprovider"),
+            Domain::TokenList => write!(f, "tokenlist"),
+            Domain::NftProvider => write!(f, "nftprovider"),
         }
     }
 }
@@ -1,29 +1,61 @@
 use std::{
+    borrow::Cow,
     fmt::{self, Display},
     str::FromStr,
 };
 
+use schemars::{JsonSchema, Schema, SchemaGenerator, json_schema};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
-#[derive(Serialize, Deserialize, Debug, Copy, Clone, PartialEq, Eq, Hash)]
+use crate::domains::Domain;
+
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Copy, Clone, PartialEq, Eq, Hash)]
 pub struct VaultId(Uuid);
 
-#[derive(Serialize, Deserialize, Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Copy, Clone, PartialEq, Eq, Hash)]
 pub struct PageId(Uuid);
 
-#[derive(Serialize, Deserialize, Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Copy, Clone, PartialEq, Eq, Hash)]
 pub struct EthProviderId(Uuid);
 
-#[derive(Serialize, Deserialize, Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Copy, Clone, PartialEq, Eq, Hash)]
 pub struct CoordinatorId(Uuid);
 
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct SolProviderId(Uuid);
+
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct BtcProviderId(Uuid);
+
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct PriceProviderId(Uuid);
+
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct SignerId(Uuid);
+
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct BundlerProviderId(Uuid);
+
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct TokenListId(Uuid);
+
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct NftProviderId(Uuid);
+
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
 pub enum EntityId {
     Vault(VaultId),
     Page(PageId),
     EthProvider(EthProviderId),
     Coordinator(CoordinatorId),
+    SolProvider(SolProviderId),
+    BtcProvider(BtcProviderId),
+    PriceProvider(PriceProviderId),
+    Signer(SignerId),
+    BundlerProvider(BundlerProviderId),
+    TokenList(TokenListId),
+    NftProvider(NftProviderId),
 }
 
 impl Display for EntityId {
@@ -33,6 +65,31 @@ impl Display for EntityId {
             EntityId::Page(page_id) => Display::fmt(page_id, f),
             EntityId::EthProvider(eth_provider_id) => Display::fmt(eth_provider_id, f),
             EntityId::Coordinator(coordinator_id) => Display::fmt(coordinator_id, f),
+            EntityId::SolProvider(sol_provider_id) => Display::fmt(sol_provider_id, f),
+            EntityId::BtcProvider(btc_provider_id) => Display::fmt(btc_provider_id, f),
+            EntityId::PriceProvider(price_provider_id) => Display::fmt(price_provider_id, f),
+            EntityId::Signer(signer_id) => Display::fmt(signer_id, f),
+            EntityId::BundlerProvider(bundler_provider_id) => Display::fmt(bundler_provider_id, f),
+            EntityId::TokenList(token_list_id) => Display::fmt(token_list_id, f),
+            EntityId::NftProvider(nft_provider_id) => Display::fmt(nft_provider_id, f),
+        }
+    }
+}
+
+impl EntityId {
+    pub fn domain(&self) -> Domain {
+        match self {
+            EntityId::Vault(_) => Domain::Vault,
+            EntityId::Page(_) => Domain::Page,
+            EntityId::EthProvider(_) => Domain::EthProvider,
+            EntityId::Coordinator(_) => Domain::Coordinator,
+            EntityId::SolProvider(_) => Domain::SolProvider,
+            EntityId::BtcProvider(_) => Domain::BtcProvider,
+            EntityId::PriceProvider(_) => Domain::PriceProvider,
+            EntityId::Signer(_) => Domain::Signer,
+            EntityId::BundlerProvider(_) => Domain::BundlerProvider,
+            EntityId::TokenList(_) => Domain::TokenList,
+            EntityId::NftProvider(_) => Domain::NftProvider,
         }
     }
 }
@@ -65,6 +122,27 @@ impl<'de> Deserialize<'de> for EntityId {
         if let Ok(coordinator_id) = CoordinatorId::from_str(&s) {
             return Ok(EntityId::Coordinator(coordinator_id));
         }
+        if let Ok(provider_id) = SolProviderId::from_str(&s) {
+            return Ok(EntityId::SolProvider(provider_id));
+        }
+        if let Ok(provider_id) = BtcProviderId::from_str(&s) {
+            return Ok(EntityId::BtcProvider(provider_id));
+        }
+        if let Ok(provider_id) = PriceProviderId::from_str(&s) {
+            return Ok(EntityId::PriceProvider(provider_id));
+        }
+        if let Ok(signer_id) = SignerId::from_str(&s) {
+            return Ok(EntityId::Signer(signer_id));
+        }
+        if let Ok(provider_id) = BundlerProviderId::from_str(&s) {
+            return Ok(EntityId::BundlerProvider(provider_id));
+        }
+        if let Ok(token_list_id) = TokenListId::from_str(&s) {
+            return Ok(EntityId::TokenList(token_list_id));
+        }
+        if let Ok(nft_provider_id) = NftProviderId::from_str(&s) {
+            return Ok(EntityId::NftProvider(nft_provider_id));
+        }
 
         Err(serde::de::Error::custom(format!(
             "Invalid EntityId string: {}",
@@ -73,6 +151,19 @@ impl<'de> Deserialize<'de> for EntityId {
     }
 }
 
+impl JsonSchema for EntityId {
+    fn schema_name() -> Cow<'static, str> {
+        "EntityId".into()
+    }
+
+    fn json_schema(_: &mut SchemaGenerator) -> Schema {
+        json_schema!({
+            "type": "string",
+            "description": "An entity's domain and UUID, e.g. `vault:<uuid>`",
+        })
+    }
+}
+
 // TODO: Setup macros for these repetitive impls
 
 impl VaultId {
@@ -231,10 +322,296 @@ impl From<CoordinatorId> for EntityId {
     }
 }
 
+impl SolProviderId {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl Default for SolProviderId {
+    fn default() -> Self {
+        SolProviderId(Uuid::new_v4())
+    }
+}
+
+impl Display for SolProviderId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if f.alternate() {
+            write!(f, "sol_provider:{}", self.0) // full: {:#}
+        } else {
+            let uuid_str = self.0.as_simple().to_string();
+            write!(f, "sol_provider:{}", &uuid_str[..6]) // short: {}
+        }
+    }
+}
+
+impl FromStr for SolProviderId {
+    type Err = uuid::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.strip_prefix("sol_provider:").unwrap_or(s);
+        let uuid = Uuid::from_str(s)?;
+        Ok(SolProviderId(uuid))
+    }
+}
+
+impl From<SolProviderId> for EntityId {
+    fn from(sol_provider_id: SolProviderId) -> Self {
+        EntityId::SolProvider(sol_provider_id)
+    }
+}
+
+impl BtcProviderId {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl Default for BtcProviderId {
+    fn default() -> Self {
+        BtcProviderId(Uuid::new_v4())
+    }
+}
+
+impl Display for BtcProviderId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if f.alternate() {
+            write!(f, "btc_provider:{}", self.0) // full: {:#}
+        } else {
+            let uuid_str = self.0.as_simple().to_string();
+            write!(f, "btc_provider:{}", &uuid_str[..6]) // short: {}
+        }
+    }
+}
+
+impl FromStr for BtcProviderId {
+    type Err = uuid::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.strip_prefix("btc_provider:").unwrap_or(s);
+        let uuid = Uuid::from_str(s)?;
+        Ok(BtcProviderId(uuid))
+    }
+}
+
+impl From<BtcProviderId> for EntityId {
+    fn from(btc_provider_id: BtcProviderId) -> Self {
+        EntityId::BtcProvider(btc_provider_id)
+    }
+}
+
+impl PriceProviderId {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl Default for PriceProviderId {
+    fn default() -> Self {
+        PriceProviderId(Uuid::new_v4())
+    }
+}
+
+impl Display for PriceProviderId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if f.alternate() {
+            write!(f, "price_provider:{}", self.0) // full: {:#}
+        } else {
+            let uuid_str = self.0.as_simple().to_string();
+            write!(f, "price_provider:{}", &uuid_str[..6]) // short: {}
+        }
+    }
+}
+
+impl FromStr for PriceProviderId {
+    type Err = uuid::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.strip_prefix("price_provider:").unwrap_or(s);
+        let uuid = Uuid::from_str(s)?;
+        Ok(PriceProviderId(uuid))
+    }
+}
+
+impl From<PriceProviderId> for EntityId {
+    fn from(price_provider_id: PriceProviderId) -> Self {
+        EntityId::PriceProvider(price_provider_id)
+    }
+}
+
+impl SignerId {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl Default for SignerId {
+    fn default() -> Self {
+        SignerId(Uuid::new_v4())
+    }
+}
+
+impl Display for SignerId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if f.alternate() {
+            write!(f, "signer:{}", self.0) // full: {:#}
+        } else {
+            let uuid_str = self.0.as_simple().to_string();
+            write!(f, "signer:{}", &uuid_str[..6]) // short: {}
+        }
+    }
+}
+
+impl FromStr for SignerId {
+    type Err = uuid::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.strip_prefix("signer:").unwrap_or(s);
+        let uuid = Uuid::from_str(s)?;
+        Ok(SignerId(uuid))
+    }
+}
+
+impl From<SignerId> for EntityId {
+    fn from(signer_id: SignerId) -> Self {
+        EntityId::Signer(signer_id)
+    }
+}
+
+impl BundlerProviderId {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl Default for BundlerProviderId {
+    fn default() -> Self {
+        BundlerProviderId(Uuid::new_v4())
+    }
+}
+
+impl Display for BundlerProviderId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if f.alternate() {
+            write!(f, "bundler_provider:{}", self.0) // full: {:#}
+        } else {
+            let uuid_str = self.0.as_simple().to_string();
+            write!(f, "bundler_provider:{}", &uuid_str[..6]) // short: {}
+        }
+    }
+}
+
+impl FromStr for BundlerProviderId {
+    type Err = uuid::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.strip_prefix("bundler_provider:").unwrap_or(s);
+        let uuid = Uuid::from_str(s)?;
+        Ok(BundlerProviderId(uuid))
+    }
+}
+
+impl From<BundlerProviderId> for EntityId {
+    fn from(bundler_provider_id: BundlerProviderId) -> Self {
+        EntityId::BundlerProvider(bundler_provider_id)
+    }
+}
+
+impl TokenListId {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl Default for TokenListId {
+    fn default() -> Self {
+        TokenListId(Uuid::new_v4())
+    }
+}
+
+impl Display for TokenListId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if f.alternate() {
+            write!(f, "token_list:{}", self.0) // full: {:#}
+        } else {
+            let uuid_str = self.0.as_simple().to_string();
+            write!(f, "token_list:{}", &uuid_str[..6]) // short: {}
+        }
+    }
+}
+
+impl FromStr for TokenListId {
+    type Err = uuid::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.strip_prefix("token_list:").unwrap_or(s);
+        let uuid = Uuid::from_str(s)?;
+        Ok(TokenListId(uuid))
+    }
+}
+
+impl From<TokenListId> for EntityId {
+    fn from(token_list_id: TokenListId) -> Self {
+        EntityId::TokenList(token_list_id)
+    }
+}
+
+impl NftProviderId {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl Default for NftProviderId {
+    fn default() -> Self {
+        NftProviderId(Uuid::new_v4())
+    }
+}
+
+impl Display for NftProviderId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if f.alternate() {
+            write!(f, "nft_provider:{}", self.0) // full: {:#}
+        } else {
+            let uuid_str = self.0.as_simple().to_string();
+            write!(f, "nft_provider:{}", &uuid_str[..6]) // short: {}
+        }
+    }
+}
+
+impl FromStr for NftProviderId {
+    type Err = uuid::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.strip_prefix("nft_provider:").unwrap_or(s);
+        let uuid = Uuid::from_str(s)?;
+        Ok(NftProviderId(uuid))
+    }
+}
+
+impl From<NftProviderId> for EntityId {
+    fn from(nft_provider_id: NftProviderId) -> Self {
+        EntityId::NftProvider(nft_provider_id)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn entity_id_domain() {
+        assert_eq!(EntityId::Vault(VaultId::new()).domain(), Domain::Vault);
+        assert_eq!(
+            EntityId::EthProvider(EthProviderId::new()).domain(),
+            Domain::EthProvider
+        );
+        assert_eq!(
+            EntityId::NftProvider(NftProviderId::new()).domain(),
+            Domain::NftProvider
+        );
+    }
+
     #[test]
     fn entity_id_vault_roundtrip() {
         let id = EntityId::Vault(VaultId::new());
@@ -282,4 +659,88 @@ mod tests {
         let deserialized: EntityId = serde_json::from_str(&serialized.to_string()).unwrap();
         assert_eq!(id, deserialized);
     }
+
+    #[test]
+    fn entity_id_sol_provider_roundtrip() {
+        let id = EntityId::SolProvider(SolProviderId::new());
+        let serialized = serde_json::to_value(&id).unwrap();
+        assert!(
+            serialized.is_string(),
+            "EntityId should serialize as a string"
+        );
+        let deserialized: EntityId = serde_json::from_str(&serialized.to_string()).unwrap();
+        assert_eq!(id, deserialized);
+    }
+
+    #[test]
+    fn entity_id_btc_provider_roundtrip() {
+        let id = EntityId::BtcProvider(BtcProviderId::new());
+        let serialized = serde_json::to_value(&id).unwrap();
+        assert!(
+            serialized.is_string(),
+            "EntityId should serialize as a string"
+        );
+        let deserialized: EntityId = serde_json::from_str(&serialized.to_string()).unwrap();
+        assert_eq!(id, deserialized);
+    }
+
+    #[test]
+    fn entity_id_price_provider_roundtrip() {
+        let id = EntityId::PriceProvider(PriceProviderId::new());
+        let serialized = serde_json::to_value(&id).unwrap();
+        assert!(
+            serialized.is_string(),
+            "EntityId should serialize as a string"
+        );
+        let deserialized: EntityId = serde_json::from_str(&serialized.to_string()).unwrap();
+        assert_eq!(id, deserialized);
+    }
+
+    #[test]
+    fn entity_id_signer_roundtrip() {
+        let id = EntityId::Signer(SignerId::new());
+        let serialized = serde_json::to_value(&id).unwrap();
+        assert!(
+            serialized.is_string(),
+            "EntityId should serialize as a string"
+        );
+        let deserialized: EntityId = serde_json::from_str(&serialized.to_string()).unwrap();
+        assert_eq!(id, deserialized);
+    }
+
+    #[test]
+    fn entity_id_bundler_provider_roundtrip() {
+        let id = EntityId::BundlerProvider(BundlerProviderId::new());
+        let serialized = serde_json::to_value(&id).unwrap();
+        assert!(
+            serialized.is_string(),
+            "EntityId should serialize as a string"
+        );
+        let deserialized: EntityId = serde_json::from_str(&serialized.to_string()).unwrap();
+        assert_eq!(id, deserialized);
+    }
+
+    #[test]
+    fn entity_id_token_list_roundtrip() {
+        let id = EntityId::TokenList(TokenListId::new());
+        let serialized = serde_json::to_value(&id).unwrap();
+        assert!(
+            serialized.is_string(),
+            "EntityId should serialize as a string"
+        );
+        let deserialized: EntityId = serde_json::from_str(&serialized.to_string()).unwrap();
+        assert_eq!(id, deserialized);
+    }
+
+    #[test]
+    fn entity_id_nft_provider_roundtrip() {
+        let id = EntityId::NftProvider(NftProviderId::new());
+        let serialized = serde_json::to_value(&id).unwrap();
+        assert!(
+            serialized.is_string(),
+            "EntityId should serialize as a string"
+        );
+        let deserialized: EntityId = serde_json::from_str(&serialized.to_string()).unwrap();
+        assert_eq!(id, deserialized);
+    }
 }
@@ -0,0 +1,183 @@
+//! Structured errors for domain methods.
+//!
+//! `RpcError` only carries a free-form message across the wasm transport, so
+//! a `DomainError` is encoded into that message as tagged JSON. Callers
+//! recover it with `DomainError::from_rpc` instead of matching on strings.
+//! Decoding still works after the message has been wrapped with extra context
+//! by the host or an intermediate plugin.
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use wasmi_plugin_pdk::rpc_message::RpcError;
+
+/// Marker preceding the JSON encoding of a `DomainError` in an error message.
+const TAG: &str = "tlock_error:";
+
+/// Machine-readable error code shared across plugins.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(transparent)]
+pub struct ErrorCode(pub u32);
+
+impl ErrorCode {
+    /// The entity ID passed to the method isn't served by this plugin.
+    pub const INVALID_ENTITY: ErrorCode = ErrorCode(1000);
+    /// The account isn't managed by this entity.
+    pub const INVALID_ACCOUNT: ErrorCode = ErrorCode(1001);
+    /// The chain isn't supported by this entity.
+    pub const UNSUPPORTED_CHAIN: ErrorCode = ErrorCode(1002);
+    /// The asset isn't supported by this entity.
+    pub const UNSUPPORTED_ASSET: ErrorCode = ErrorCode(1003);
+    /// The entity doesn't hold enough of an asset to satisfy the request.
+    pub const INSUFFICIENT_FUNDS: ErrorCode = ErrorCode(1004);
+    /// The plugin hasn't been set up to serve this request yet.
+    pub const NOT_CONFIGURED: ErrorCode = ErrorCode(1005);
+    /// The account doesn't have an open coordinator session.
+    pub const NO_SESSION: ErrorCode = ErrorCode(1006);
+    /// The call didn't finish before its deadline, and the caller stopped
+    /// waiting on it.
+    pub const TIMEOUT: ErrorCode = ErrorCode(1007);
+    /// The plugin made too many calls to a method in a short time.
+    pub const RATE_LIMITED: ErrorCode = ErrorCode(1008);
+    /// The plugin needs more memory than its resource limits allow.
+    pub const RESOURCE_EXHAUSTED: ErrorCode = ErrorCode(1009);
+}
+
+impl fmt::Display for ErrorCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+/// An error with a code, a human-readable message, and optional structured
+/// data describing the failure.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct DomainError {
+    pub code: ErrorCode,
+    pub message: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub data: Option<Value>,
+}
+
+impl DomainError {
+    pub fn new(code: ErrorCode, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            message: message.into(),
+            data: None,
+        }
+    }
+
+    /// Attaches structured data to the error. Data that fails to serialize is
+    /// dropped.
+    pub fn with_data(mut self, data: impl Serialize) -> Self {
+        self.data = serde_json::to_value(data).ok();
+        self
+    }
+
+    /// Decodes the `DomainError` carried by an `RpcError`, if any.
+    pub fn from_rpc(err: &RpcError) -> Option<Self> {
+        let RpcError::Custom(msg) = err else {
+            return None;
+        };
+
+        let (_, encoded) = msg.split_once(TAG)?;
+        serde_json::Deserializer::from_str(encoded)
+            .into_iter::<DomainError>()
+            .next()?
+            .ok()
+    }
+
+    /// Returns the error code carried by an `RpcError`, if any.
+    pub fn code_of(err: &RpcError) -> Option<ErrorCode> {
+        Self::from_rpc(err).map(|e| e.code)
+    }
+
+    /// Formats an `RpcError` for showing to the user, with any `DomainError`
+    /// it carries replaced by its message. Context wrapped around the error
+    /// is kept.
+    pub fn describe(err: &RpcError) -> String {
+        let RpcError::Custom(msg) = err else {
+            return err.to_string();
+        };
+        let Some((context, encoded)) = msg.split_once(TAG) else {
+            return msg.clone();
+        };
+
+        let mut errors = serde_json::Deserializer::from_str(encoded).into_iter::<DomainError>();
+        match errors.next() {
+            Some(Ok(domain)) => {
+                let rest = &encoded[errors.byte_offset()..];
+                format!("{}{}{}", context, domain, rest)
+            }
+            _ => msg.clone(),
+        }
+    }
+}
+
+impl fmt::Display for DomainError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} (code {})", self.message, self.code)
+    }
+}
+
+impl std::error::Error for DomainError {}
+
+impl From<DomainError> for RpcError {
+    fn from(err: DomainError) -> Self {
+        match serde_json::to_string(&err) {
+            Ok(encoded) => RpcError::Custom(format!("{}{}", TAG, encoded)),
+            Err(_) => RpcError::Custom(err.to_string()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_domain_error_roundtrip() {
+        let err = DomainError::new(ErrorCode::INSUFFICIENT_FUNDS, "Insufficient assets")
+            .with_data(serde_json::json!({ "available": "10", "required": "20" }));
+
+        let rpc_err: RpcError = err.clone().into();
+        assert_eq!(DomainError::from_rpc(&rpc_err), Some(err));
+    }
+
+    #[test]
+    fn test_domain_error_wrapped_context() {
+        let err = DomainError::new(ErrorCode::UNSUPPORTED_ASSET, "Unsupported asset");
+        let RpcError::Custom(msg) = RpcError::from(err.clone()) else {
+            panic!("Expected a custom error");
+        };
+
+        let wrapped = RpcError::Custom(format!("Error calling vault_withdraw: {}", msg));
+        assert_eq!(
+            DomainError::code_of(&wrapped),
+            Some(ErrorCode::UNSUPPORTED_ASSET)
+        );
+        assert_eq!(
+            DomainError::code_of(&RpcError::Custom("Other".into())),
+            None
+        );
+    }
+
+    #[test]
+    fn test_domain_error_describe() {
+        let err = DomainError::new(ErrorCode::NO_SESSION, "No open session");
+        let RpcError::Custom(msg) = RpcError::from(err) else {
+            panic!("Expected a custom error");
+        };
+
+        let wrapped = RpcError::Custom(format!("Error calling propose: {}", msg));
+        assert_eq!(
+            DomainError::describe(&wrapped),
+            "Error calling propose: No open session (code 1006)"
+        );
+        assert_eq!(
+            DomainError::describe(&RpcError::Custom("Other".into())),
+            "Other"
+        );
+    }
+}
@@ -1,16 +1,35 @@
 use async_trait::async_trait;
-use serde::{Serialize, de::DeserializeOwned};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
 use wasmi_plugin_pdk::rpc_message::{RpcError, RpcErrorContext};
 
 pub mod caip;
 pub mod component;
 pub mod domains;
 pub mod entities;
+pub mod error;
 pub use alloy;
 pub mod rpc_batch;
-
-// TODO: Add a signer trait just for signing raw messages? Not sure if it'd work
-// - we might end up with too many types requiring user authentication.
+pub mod schema;
+
+/// Version of the tlock API this crate implements. Bumped whenever a method
+/// changes incompatibly.
+pub const API_VERSION: u32 = 5;
+/// Oldest plugin API version the host can still serve.
+pub const MIN_API_VERSION: u32 = 1;
+
+/// What a method can do on the caller's behalf, which decides how the host
+/// gates plugins calling it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, JsonSchema)]
+pub enum Permission {
+    /// Only reads or changes the plugin's own state or what the user sees.
+    /// The host lets plugins call its core methods of this class without
+    /// declaring them.
+    Standard,
+    /// Acts beyond the plugin, e.g. moving funds, signing, or reaching the
+    /// network. Plugins must always declare these in their manifest.
+    Privileged,
+}
 
 // TODO: Consider adding a `mod sealed::Sealed {}` to prevent external impl,
 // forcing plugins to only use provided methods. That's already somewhat
@@ -28,6 +47,24 @@ pub trait RpcMethod: Send + Sync {
     type Output: DeserializeOwned + Serialize + Send + Sync;
 
     const NAME: &'static str;
+    /// The Rust types of `Params` and `Output`, as written in the method's
+    /// declaration, or empty if not given. Used to describe the method in
+    /// `schema`.
+    const PARAMS_TYPE: &'static str = "";
+    const OUTPUT_TYPE: &'static str = "";
+    /// Version of this method's params and output. Bumped whenever either
+    /// changes incompatibly.
+    const VERSION: u32 = 1;
+    /// What this method can do when a plugin calls it on the host. Methods
+    /// are privileged unless they declare otherwise.
+    const PERMISSION: Permission = Permission::Privileged;
+
+    /// Encodes a call to this method as a `(name, params)` pair, for packing
+    /// several calls into one `call_many` batch.
+    fn request(&self, params: Self::Params) -> Result<(&'static str, serde_json::Value), RpcError> {
+        let raw_params = serde_json::to_value(params).map_err(|_| RpcError::InvalidParams)?;
+        Ok((Self::NAME, raw_params))
+    }
 
     fn call<T, E>(&self, transport: T, params: Self::Params) -> Result<Self::Output, RpcError>
     where
@@ -51,8 +88,7 @@ pub trait RpcMethod: Send + Sync {
     {
         let raw_params: Vec<(&str, serde_json::Value)> = params
             .into_iter()
-            .map(|p| serde_json::to_value(p).map_err(|_| RpcError::InvalidParams))
-            .map(|res| res.map(|v| (Self::NAME, v)))
+            .map(|p| self.request(p))
             .collect::<Result<_, _>>()?;
         let responses = transport.call_many(raw_params).map_err(Into::into)?;
         let mut results = Vec::with_capacity(responses.len());
@@ -82,20 +118,81 @@ pub trait RpcMethod: Send + Sync {
     }
 }
 
+/// A method implementers may leave out, such as a lifecycle hook. A runner
+/// answers methods it wasn't given a handler for with `MethodNotFound`, so
+/// implementers don't need to stub these; callers use `call_optional`, which
+/// treats that as the method being absent.
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+pub trait OptionalMethod: RpcMethod {
+    /// Calls the method, returning None if the callee doesn't implement it.
+    async fn call_optional<T, E>(
+        &self,
+        transport: T,
+        params: Self::Params,
+    ) -> Result<Option<Self::Output>, RpcError>
+    where
+        T: wasmi_plugin_pdk::transport::AsyncTransport<E> + Send + Sync + 'static,
+        E: Into<RpcError>,
+    {
+        optional(self.call_async(transport, params).await)
+    }
+}
+
+/// Treats a result failing with `MethodNotFound` as the method being absent.
+pub fn optional<T>(result: Result<T, RpcError>) -> Result<Option<T>, RpcError> {
+    match result {
+        Ok(output) => Ok(Some(output)),
+        Err(RpcError::MethodNotFound) => Ok(None),
+        Err(e) => Err(e),
+    }
+}
+
 macro_rules! rpc_method {
     (
         $(#[$meta:meta])*
         $name:ident, $struct_name:ident, $params:ty, $output:ty
+    ) => {
+        rpc_method!($(#[$meta])* $name, $struct_name, $params, $output, version = 1);
+    };
+    (
+        $(#[$meta:meta])*
+        $name:ident, $struct_name:ident, $params:ty, $output:ty, version = $version:literal
+    ) => {
+        rpc_method!(
+            $(#[$meta])* $name, $struct_name, $params, $output,
+            version = $version, permission = Privileged
+        );
+    };
+    (
+        $(#[$meta:meta])*
+        $name:ident, $struct_name:ident, $params:ty, $output:ty, permission = $permission:ident
+    ) => {
+        rpc_method!(
+            $(#[$meta])* $name, $struct_name, $params, $output,
+            version = 1, permission = $permission
+        );
+    };
+    (
+        $(#[$meta:meta])*
+        $name:ident, $struct_name:ident, $params:ty, $output:ty,
+        version = $version:literal, permission = $permission:ident
     ) => {
         $(#[$meta])*
         #[doc = concat!("**Params:** `", stringify!($params), "`")]
         #[doc = concat!("**Output:** `", stringify!($output), "`")]
+        #[doc = concat!("**Version:** `", stringify!($version), "`")]
+        #[doc = concat!("**Permission:** `", stringify!($permission), "`")]
         pub struct $struct_name;
 
         impl $crate::RpcMethod for $struct_name {
             type Params = $params;
             type Output = $output;
             const NAME: &'static str = stringify!($name);
+            const PARAMS_TYPE: &'static str = stringify!($params);
+            const OUTPUT_TYPE: &'static str = stringify!($output);
+            const VERSION: u32 = $version;
+            const PERMISSION: $crate::Permission = $crate::Permission::$permission;
         }
     };
 }
@@ -105,40 +202,188 @@ macro_rules! rpc_method {
 pub mod global {
     rpc_method!(
         /// Simple health check
-        tlock_ping, Ping, (), String
+        tlock_ping, Ping, (), String, permission = Standard
     );
 }
 
 /// The host namespace contains methods for interacting with the host and
 /// performing privileged operations.
 pub mod host {
-    use std::fmt;
+    use std::{fmt, time::Duration};
 
+    use alloy::primitives::{Address, Bytes, FixedBytes, U256};
+    use schemars::JsonSchema;
     use serde::{Deserialize, Serialize};
+    use uuid::Uuid;
 
     use crate::{
-        caip::ChainId,
+        RpcMethod,
+        caip::{AccountId, AssetId, ChainId},
         component::Component,
         domains::Domain,
-        entities::{CoordinatorId, EntityId, EthProviderId, PageId, VaultId},
+        entities::{
+            BtcProviderId, BundlerProviderId, CoordinatorId, EntityId, EthProviderId,
+            NftProviderId, PageId, PriceProviderId, SignerId, SolProviderId, TokenListId, VaultId,
+        },
     };
 
-    #[derive(Serialize, Deserialize, Clone)]
+    #[derive(Serialize, Deserialize, JsonSchema, Clone, Default)]
     pub struct Request {
         pub url: String,
+        /// Any standard HTTP method, e.g. `GET`, `POST`, `PUT`, `DELETE`,
+        /// `PATCH`, or `HEAD`. Case-insensitive.
         pub method: String,
         pub headers: Vec<(String, Vec<u8>)>,
         pub body: Option<Vec<u8>>,
+        /// Fails the request if the response hasn't been fully received in
+        /// time. Without one, the request is only bounded by the call timeout.
+        #[serde(default)]
+        pub timeout: Option<Duration>,
+        #[serde(default)]
+        pub redirect: RedirectPolicy,
+    }
+
+    /// How a `Request` handles HTTP redirects. Redirects are only followed to
+    /// origins the plugin is already allowed to fetch from. Hosts running in a
+    /// browser can't stop redirects from being followed, so they fail a
+    /// redirected request under `None` instead of returning the redirect, and
+    /// can't limit how many are followed.
+    #[derive(Serialize, Deserialize, JsonSchema, Clone, Copy, Debug, PartialEq, Eq)]
+    pub enum RedirectPolicy {
+        /// Follow up to this many redirects, then fail
+        Follow(u32),
+        /// Return redirect responses as they are
+        None,
+    }
+
+    impl Default for RedirectPolicy {
+        fn default() -> Self {
+            RedirectPolicy::Follow(10)
+        }
+    }
+
+    #[derive(Serialize, Deserialize, JsonSchema, Clone, Debug)]
+    pub struct Response {
+        pub status: u16,
+        pub headers: Vec<(String, Vec<u8>)>,
+        pub body: Vec<u8>,
+    }
+
+    impl Response {
+        /// Returns true if the status is in the 2xx range.
+        pub fn is_success(&self) -> bool {
+            (200..300).contains(&self.status)
+        }
+    }
+
+    /// Handle to a streamed response body, valid until the body is fully read
+    /// or the stream is closed.
+    #[derive(Serialize, Deserialize, JsonSchema, Debug, Copy, Clone, PartialEq, Eq, Hash)]
+    pub struct FetchStreamId(pub Uuid);
+
+    #[derive(Serialize, Deserialize, JsonSchema, Clone, Debug)]
+    pub struct ResponseHead {
+        pub stream_id: FetchStreamId,
+        pub status: u16,
+        pub headers: Vec<(String, Vec<u8>)>,
+    }
+
+    /// Handle to a WebSocket opened with `host_ws_connect`, valid until the
+    /// socket closes.
+    #[derive(Serialize, Deserialize, JsonSchema, Debug, Copy, Clone, PartialEq, Eq, Hash)]
+    pub struct WsId(pub Uuid);
+
+    #[derive(Serialize, Deserialize, JsonSchema, Clone, Debug, PartialEq, Eq)]
+    pub enum WsMessage {
+        Text(String),
+        Binary(Vec<u8>),
     }
 
     /// TODO: Consider adding more levels based on android/iOS notification levels
-    #[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+    /// Levels are ordered from least to most severe.
+    #[derive(Debug, Serialize, Deserialize, JsonSchema, Clone, PartialEq, Eq, PartialOrd, Ord)]
     pub enum NotifyLevel {
         Trace,
         Info,
+        Warn,
+        Error,
+    }
+
+    #[derive(Debug, Serialize, Deserialize, JsonSchema, Clone, Copy, PartialEq, Eq)]
+    pub enum ToastLevel {
+        Info,
+        Success,
+        Warning,
         Error,
     }
 
+    /// A short-lived message the frontend shows over the page, e.g. the
+    /// outcome of an action, which is dismissed on its own.
+    #[derive(Debug, Serialize, Deserialize, JsonSchema, Clone, PartialEq, Eq)]
+    pub struct Toast {
+        pub level: ToastLevel,
+        pub message: String,
+        /// How long the toast is shown, or the frontend's default if None
+        pub duration: Option<Duration>,
+    }
+
+    impl Toast {
+        pub fn new(level: ToastLevel, message: impl Into<String>) -> Self {
+            Self {
+                level,
+                message: message.into(),
+                duration: None,
+            }
+        }
+
+        pub fn with_duration(mut self, duration: Duration) -> Self {
+            self.duration = Some(duration);
+            self
+        }
+    }
+
+    /// A human-readable decoding of ABI-encoded revert data.
+    #[derive(Debug, Serialize, Deserialize, JsonSchema, Clone, PartialEq, Eq)]
+    #[non_exhaustive]
+    pub enum DecodedError {
+        /// The revert carried no data.
+        Empty,
+        /// A standard `Error(string)` revert.
+        Revert(String),
+        /// A solidity `Panic(uint256)`, with a description of the panic code.
+        Panic {
+            #[schemars(with = "crate::schema::Hex")]
+            code: U256,
+            reason: String,
+        },
+        /// A custom error. `signature` is populated if the selector is known
+        /// to the host's registry.
+        Custom {
+            #[schemars(with = "crate::schema::Hex")]
+            selector: FixedBytes<4>,
+            signature: Option<String>,
+            #[schemars(with = "crate::schema::Hex")]
+            data: Bytes,
+        },
+    }
+
+    impl fmt::Display for DecodedError {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            match self {
+                DecodedError::Empty => write!(f, "Reverted without data"),
+                DecodedError::Revert(reason) => write!(f, "{}", reason),
+                DecodedError::Panic { code, reason } => write!(f, "Panic({:#x}): {}", code, reason),
+                DecodedError::Custom {
+                    signature: Some(signature),
+                    ..
+                } => write!(f, "{}", signature),
+                DecodedError::Custom { selector, data, .. } => {
+                    write!(f, "Unknown error {} (data: {})", selector, data)
+                }
+            }
+        }
+    }
+
     impl fmt::Debug for Request {
         fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
             let headers_debug: Vec<_> = self
@@ -158,110 +403,901 @@ pub mod host {
         }
     }
 
+    /// What an entity supports, advertised when it's registered. Entity
+    /// requests can require capabilities so the user is only offered
+    /// entities that fit.
+    ///
+    /// Empty `chains` or `assets` mean the entity doesn't restrict them.
+    /// Optional methods, such as the `trace` namespace, are only matched if
+    /// they're listed.
+    #[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+    pub struct EntityCapabilities {
+        #[serde(default)]
+        pub chains: Vec<ChainId>,
+        #[serde(default)]
+        pub assets: Vec<AssetId>,
+        /// Optional methods the entity serves, by name
+        #[serde(default)]
+        pub methods: Vec<String>,
+    }
+
+    impl EntityCapabilities {
+        pub fn new() -> Self {
+            Self::default()
+        }
+
+        pub fn with_chain(mut self, chain_id: ChainId) -> Self {
+            if !self.chains.contains(&chain_id) {
+                self.chains.push(chain_id);
+            }
+            self
+        }
+
+        pub fn with_asset(mut self, asset_id: AssetId) -> Self {
+            if !self.assets.contains(&asset_id) {
+                self.assets.push(asset_id);
+            }
+            self
+        }
+
+        pub fn with_method<M: RpcMethod>(mut self, _: M) -> Self {
+            if !self.methods.iter().any(|m| m == M::NAME) {
+                self.methods.push(M::NAME.to_string());
+            }
+            self
+        }
+
+        /// Whether an entity with these capabilities meets every requirement
+        /// in `required`.
+        pub fn satisfies(&self, required: &EntityCapabilities) -> bool {
+            let chains =
+                self.chains.is_empty() || required.chains.iter().all(|c| self.chains.contains(c));
+            let assets =
+                self.assets.is_empty() || required.assets.iter().all(|a| self.assets.contains(a));
+            let methods = required.methods.iter().all(|m| self.methods.contains(m));
+            chains && assets && methods
+        }
+    }
+
     rpc_method!(
         /// Request the host registers a new entity with the given ID and this
         /// plugin as its owner.
-        host_register_entity, RegisterEntity, Domain, EntityId
+        host_register_entity, RegisterEntity, Domain, EntityId, permission = Standard
+    );
+
+    rpc_method!(
+        /// Registers a new entity like `RegisterEntity`, advertising what it
+        /// supports so entity requests can filter on it.
+        host_register_entity_with_capabilities,
+        RegisterEntityWithCapabilities,
+        (Domain, EntityCapabilities),
+        EntityId,
+        permission = Standard
     );
 
     // TODO: Consider turning the host_request_* into their own domain? Makes it
     // more obvious they're all related and can share docs.
     rpc_method!(
-        /// Request the host to provide an EthProvider for this plugin
+        /// Request the host to provide an EthProvider for this plugin. If no
+        /// chain is given, the host's active chain is used.
+        ///
+        /// The user is only offered providers that support the chain and
+        /// satisfy the required capabilities. If the user previously chose to
+        /// remember a matching provider, it's returned without prompting.
         host_request_eth_provider,
         RequestEthProvider,
+        (Option<ChainId>, EntityCapabilities),
+        EthProviderId,
+        version = 2
+    );
+
+    rpc_method!(
+        /// Request the host to provide a SolProvider for this plugin on the
+        /// given Solana chain.
+        host_request_sol_provider,
+        RequestSolProvider,
+        ChainId,
+        SolProviderId
+    );
+
+    rpc_method!(
+        /// Request the host to provide a BtcProvider for this plugin on the
+        /// given BIP-122 chain.
+        host_request_btc_provider,
+        RequestBtcProvider,
+        ChainId,
+        BtcProviderId
+    );
+
+    rpc_method!(
+        /// Request the host to provide a PriceProvider for this plugin
+        host_request_price_provider,
+        RequestPriceProvider,
+        (),
+        PriceProviderId
+    );
+
+    rpc_method!(
+        /// Request the host to provide a Signer for this plugin
+        host_request_signer,
+        RequestSigner,
+        (),
+        SignerId
+    );
+
+    rpc_method!(
+        /// Request the host to provide a BundlerProvider for this plugin on
+        /// the given EVM chain.
+        host_request_bundler_provider,
+        RequestBundlerProvider,
         ChainId,
-        EthProviderId
+        BundlerProviderId
+    );
+
+    rpc_method!(
+        /// Request the host to provide a TokenList for this plugin
+        host_request_token_list,
+        RequestTokenList,
+        (),
+        TokenListId
+    );
+
+    rpc_method!(
+        /// Request the host to provide an NftProvider for this plugin
+        host_request_nft_provider,
+        RequestNftProvider,
+        (),
+        NftProviderId
+    );
+
+    rpc_method!(
+        /// Get the chain currently selected by the user.
+        host_get_active_chain, GetActiveChain, (), ChainId, permission = Standard
+    );
+
+    #[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+    pub struct Capabilities {
+        /// API version the host implements
+        pub api_version: u32,
+        /// Oldest plugin API version the host still serves
+        pub min_api_version: u32,
+        /// Host methods, by name, and their versions
+        pub methods: Vec<(String, u32)>,
+    }
+
+    impl Capabilities {
+        /// Returns the version of the host's method, or None if the host
+        /// doesn't serve it.
+        pub fn method_version(&self, method: &str) -> Option<u32> {
+            self.methods
+                .iter()
+                .find_map(|(name, version)| (name == method).then_some(*version))
+        }
+    }
+
+    rpc_method!(
+        /// Get the API version and methods supported by the host. Plugins
+        /// can use this to check for optional methods before calling them.
+        host_get_capabilities, GetCapabilities, (), Capabilities, permission = Standard
+    );
+
+    rpc_method!(
+        /// Get the ID of the host's trace for the call being handled, so spans
+        /// the plugin emits can be correlated with the host's. None if the
+        /// host isn't tracing the call.
+        host_get_trace_id, GetTraceId, (), Option<String>, permission = Standard
+    );
+
+    rpc_method!(
+        /// Ask the user to confirm an action in a host-rendered dialog, with
+        /// a title, a body, and a component describing the action's details.
+        /// Returns true only if the user confirmed.
+        ///
+        /// Unlike confirmations rendered on a plugin's own page, these can't be
+        /// spoofed or auto-accepted by the plugin.
+        host_request_confirmation, RequestConfirmation, (String, String, Component), bool
+    );
+
+    rpc_method!(
+        /// Ask the user to pick one of several options in a host-rendered
+        /// dialog, with a title and a component explaining the choice.
+        /// Returns the index of the chosen option, or None if the user
+        /// dismissed the dialog.
+        host_request_user_choice, RequestUserChoice, (String, Component, Vec<String>), Option<usize>
+    );
+
+    rpc_method!(
+        /// Request the host to provide a Vault for this plugin. The user is
+        /// only offered vaults that satisfy the required capabilities, e.g.
+        /// vaults supporting a particular asset. If the user previously chose
+        /// to remember a matching vault, it's returned without prompting.
+        host_request_vault, RequestVault, EntityCapabilities, VaultId, version = 2
+    );
+
+    rpc_method!(
+        /// Requests the host to provide a Coordinator for this plugin
+        host_request_coordinator,
+        RequestCoordinator,
+        (),
+        CoordinatorId
+    );
+
+    rpc_method!(
+        /// Publishes a payload to every plugin subscribed to the topic, other
+        /// than the publisher. Returns once all subscribers have handled it.
+        host_publish, Publish, (String, Vec<u8>), ()
+    );
+
+    rpc_method!(
+        /// Subscribes this plugin to a topic. Published payloads are delivered
+        /// through `plugin_on_event`. Subscriptions persist until removed or
+        /// the plugin is unloaded.
+        host_subscribe, Subscribe, String, ()
+    );
+
+    rpc_method!(
+        /// Unsubscribes this plugin from a topic.
+        host_unsubscribe, Unsubscribe, String, ()
+    );
+
+    rpc_method!(
+        /// Schedules `plugin_on_alarm` to be called with the tag once the
+        /// duration has elapsed, even if none of the plugin's pages are open.
+        /// Setting an alarm with an existing tag replaces it.
+        ///
+        /// Alarms fire once. Plugins that need to be woken periodically
+        /// SHOULD set the alarm again from `plugin_on_alarm`.
+        host_set_alarm, SetAlarm, (Duration, String), ()
+    );
+
+    rpc_method!(
+        /// Cancels this plugin's alarm with the given tag, if one is set.
+        host_cancel_alarm, CancelAlarm, String, ()
+    );
+
+    rpc_method!(
+        /// Schedules `plugin_on_tick` to be called repeatedly, roughly once per
+        /// interval, even if none of the plugin's pages are open. Registering
+        /// again replaces the previous interval.
+        ///
+        /// The host adds jitter to each tick and backs off while ticks fail,
+        /// so plugins MUST NOT rely on exact timing. Intervals shorter than
+        /// the host's minimum are rounded up to it.
+        host_register_tick, RegisterTick, Duration, ()
+    );
+
+    rpc_method!(
+        /// Stops this plugin's `plugin_on_tick` calls, if any are scheduled.
+        host_unregister_tick, UnregisterTick, (), ()
+    );
+
+    rpc_method!(
+        /// Resolves after the given duration. wasm32-wasip1 plugins have no
+        /// timers of their own, so this is how they wait without busy-looping.
+        /// The wait counts toward the deadline of the call making it.
+        ///
+        /// The host clamps the duration to the longest deadline any call may
+        /// have, 300 seconds unless the host raises it, since the calling
+        /// plugin can't still be waiting once that's passed.
+        host_sleep, Sleep, Duration, (), permission = Standard
+    );
+
+    rpc_method!(
+        /// Watches one of this plugin's vault accounts for deposits of an asset.
+        /// The host polls the account's balance through the eth provider and
+        /// calls `vault_on_deposit_detected` whenever it increases. Only the
+        /// native asset and ERC20 tokens can be watched.
+        ///
+        /// Watches persist with the host state until removed or the plugin is
+        /// unloaded. Watching an already watched account and asset replaces
+        /// the existing watch.
+        host_watch_deposit, WatchDeposit, (VaultId, EthProviderId, AccountId, AssetId), ()
+    );
+
+    rpc_method!(
+        /// Stops watching an account for deposits of an asset.
+        host_unwatch_deposit, UnwatchDeposit, (VaultId, AccountId, AssetId), ()
+    );
+
+    rpc_method!(
+        /// Leases the nonce for the next transaction sent from an address on
+        /// the provider's chain.
+        ///
+        /// The host hands out each nonce once, starting from the provider's
+        /// pending transaction count, so plugins sending from the same account
+        /// concurrently don't collide. Plugins MUST set the leased nonce on
+        /// the transaction and SHOULD release it with `host_release_nonce` if
+        /// the transaction isn't broadcast. A plugin can hold at most 16
+        /// unused nonces per account; leasing more fails.
+        host_lease_nonce, LeaseNonce, (EthProviderId, Address), u64
+    );
+
+    rpc_method!(
+        /// Returns an unused nonce leased with `host_lease_nonce`, so it's
+        /// leased again before any higher nonce. Only the plugin that leased
+        /// the nonce can release it.
+        host_release_nonce, ReleaseNonce, (EthProviderId, Address, u64), ()
     );
 
     rpc_method!(
-        /// Request the host to provide a Vault for this plugin
-        host_request_vault, RequestVault, (), VaultId
+        /// Make a network request. Returns an error only if the request
+        /// couldn't be made or timed out, HTTP error statuses are returned as
+        /// responses.
+        ///
+        /// Requests to origins not listed in the plugin's manifest prompt the
+        /// user the first time, and fail if the user doesn't approve them.
+        ///
+        /// The whole body is returned at once. Use `FetchStream` for large
+        /// responses.
+        host_fetch, Fetch, Request, Result<Response, String>,
+        version = 2, permission = Privileged
+    );
+
+    rpc_method!(
+        /// Make a network request, returning the response's status and
+        /// headers. The body is read in chunks with `FetchReadChunk`.
+        host_fetch_stream, FetchStream, Request, Result<ResponseHead, String>,
+        permission = Privileged
+    );
+
+    rpc_method!(
+        /// Read the next chunk of a streamed response body. Returns None once
+        /// the body has been fully read, after which the stream is closed.
+        host_fetch_read_chunk, FetchReadChunk, FetchStreamId, Result<Option<Vec<u8>>, String>
+    );
+
+    rpc_method!(
+        /// Close a streamed response before its body has been fully read.
+        host_fetch_close, FetchClose, FetchStreamId, ()
+    );
+
+    rpc_method!(
+        /// Open a WebSocket to the given `ws://` or `wss://` URL. Messages
+        /// received on it are delivered to the plugin's `OnWsMessage`, and
+        /// `OnWsClosed` is called once when it closes.
+        ///
+        /// Origins are approved the same way as `host_fetch`.
+        host_ws_connect, WsConnect, String, Result<WsId, String>, permission = Privileged
+    );
+
+    rpc_method!(
+        /// Send a message on a WebSocket opened with `host_ws_connect`.
+        host_ws_send, WsSend, (WsId, WsMessage), Result<(), String>
+    );
+
+    rpc_method!(
+        /// Close a WebSocket. `OnWsClosed` isn't called for sockets the plugin
+        /// closes itself.
+        host_ws_close, WsClose, WsId, ()
+    );
+
+    rpc_method!(
+        /// Store a secret, such as a private key, under the given name. Secrets
+        /// are encrypted with the user's keystore password and kept apart from
+        /// plugin state. Prompts the user to unlock the keystore if it's
+        /// locked.
+        host_secret_set, SecretSet, (String, Vec<u8>), (), permission = Standard
+    );
+
+    rpc_method!(
+        /// Read a secret stored with `host_secret_set`, or None if there isn't
+        /// one under the name. Prompts the user to unlock the keystore if it's
+        /// locked.
+        host_secret_get, SecretGet, String, Option<Vec<u8>>, permission = Standard
+    );
+
+    rpc_method!(
+        /// Delete a secret stored with `host_secret_set`.
+        host_secret_delete, SecretDelete, String, (), permission = Standard
+    );
+
+    rpc_method!(
+        /// Sets a specific page to the given component.
+        host_set_page, SetPage, (PageId, Component), (), permission = Standard
+    );
+
+    rpc_method!(
+        /// Sets the title shown for one of this plugin's pages in the
+        /// frontend's navigation.
+        host_set_page_title, SetPageTitle, (PageId, String), (), permission = Standard
+    );
+
+    rpc_method!(
+        /// Opens one of this plugin's pages on top of the current page. The
+        /// user can navigate back to the previous page.
+        host_navigate, Navigate, PageId, (), permission = Standard
+    );
+
+    rpc_method!(
+        /// Sends a notification to the host to be displayed
+        host_notify, Notify, (NotifyLevel, String), (), permission = Standard
+    );
+
+    rpc_method!(
+        /// Shows a toast over the page. Unlike `host_notify`, the message
+        /// isn't kept beyond the event log once the toast is dismissed.
+        host_show_toast, ShowToast, Toast, (), permission = Standard
+    );
+
+    rpc_method!(
+        /// Decodes raw revert data into a readable error using the host's
+        /// registry of known error selectors.
+        host_decode_error, DecodeError, Bytes, DecodedError, permission = Standard
+    );
+}
+
+/// The notification namespace allows plugins to push persistent notifications
+/// to the host's notification center.
+pub mod notification {
+    use schemars::JsonSchema;
+    use serde::{Deserialize, Serialize};
+
+    use crate::{entities::PageId, host::NotifyLevel};
+
+    #[derive(Debug, Serialize, Deserialize, JsonSchema, Clone, PartialEq, Eq)]
+    pub struct Notification {
+        pub level: NotifyLevel,
+        pub message: String,
+        /// Notifications sharing a dedupe key replace one another rather than
+        /// stacking in the notification center.
+        pub dedupe_key: Option<String>,
+        pub actions: Vec<NotificationAction>,
+    }
+
+    /// A button attached to a notification. When clicked, the host sends
+    /// `PageEvent::NotificationAction(id)` to the given page.
+    #[derive(Debug, Serialize, Deserialize, JsonSchema, Clone, PartialEq, Eq)]
+    pub struct NotificationAction {
+        pub id: String,
+        pub label: String,
+        pub page_id: PageId,
+    }
+
+    impl Notification {
+        pub fn new(level: NotifyLevel, message: impl Into<String>) -> Self {
+            Self {
+                level,
+                message: message.into(),
+                dedupe_key: None,
+                actions: vec![],
+            }
+        }
+
+        pub fn with_dedupe_key(mut self, key: impl Into<String>) -> Self {
+            self.dedupe_key = Some(key.into());
+            self
+        }
+
+        pub fn with_action(
+            mut self,
+            id: impl Into<String>,
+            label: impl Into<String>,
+            page_id: PageId,
+        ) -> Self {
+            self.actions.push(NotificationAction {
+                id: id.into(),
+                label: label.into(),
+                page_id,
+            });
+            self
+        }
+    }
+
+    rpc_method!(
+        /// Pushes a notification to the host's notification center.
+        ///
+        /// Action buttons MUST target pages owned by the calling plugin, the
+        /// host will reject the notification otherwise.
+        notification_push, Push, Notification, (), permission = Standard
+    );
+
+    rpc_method!(
+        /// Dismisses this plugin's notification with the given dedupe key, if
+        /// one exists.
+        notification_dismiss, Dismiss, String, (), permission = Standard
+    );
+}
+
+/// The state namespace allows plugins to manage their persistent state
+/// stored by the host.
+pub mod state {
+    use schemars::JsonSchema;
+    use serde::{Deserialize, Serialize};
+    use thiserror::Error;
+
+    #[derive(Debug, Error, Serialize, Deserialize, JsonSchema)]
+    #[non_exhaustive]
+    pub enum SetError {
+        #[error("Key is not locked")]
+        KeyNotLocked,
+        #[error("Plugin state quota exceeded")]
+        QuotaExceeded,
+    }
+
+    #[derive(Debug, Error, Serialize, Deserialize, JsonSchema)]
+    #[non_exhaustive]
+    pub enum UnlockError {
+        #[error("Key is not locked")]
+        KeyNotLocked,
+    }
+
+    rpc_method!(
+        /// Reads a key from this plugin's state.
+        ///
+        /// If the key is already locked, the host will block until it is
+        /// unlocked.
+        state_read_key, ReadKey, String, Vec<u8>, permission = Standard
+    );
+
+    rpc_method!(
+        /// Locks a key from this plugin's state. The key will remain
+        /// locked until either `state_unlock_key` is called, or the plugin
+        /// instance is terminated.
+        ///
+        /// If the key is already locked, the host will block until it is
+        /// unlocked.
+        state_lock_key, LockKey, String, Vec<u8>, permission = Standard
+    );
+
+    rpc_method!(
+        /// Sets a key in this plugin's state. If the key is not locked,
+        /// returns an error.
+        state_set_key, SetKey, (String, Vec<u8>), Result<(), SetError>, permission = Standard
+    );
+
+    rpc_method!(
+        /// Unlocks a previously locked key in this plugin's state. If the key
+        /// is not locked, returns an error.
+        state_unlock_key, UnlockKey, String, Result<(), UnlockError>, permission = Standard
+    );
+
+    rpc_method!(
+        /// Deletes a key from this plugin's state. Like `state_set_key`, the
+        /// key must be locked, and stays locked until `state_unlock_key` is
+        /// called.
+        state_delete_key, DeleteKey, String, Result<(), SetError>, permission = Standard
+    );
+
+    rpc_method!(
+        /// Lists the keys in this plugin's state that start with the given
+        /// prefix. Keys are returned in lexicographic order.
+        state_list_keys, ListKeys, String, Vec<String>, permission = Standard
+    );
+
+    /// Bytes of state stored by a plugin, counting both keys and values.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+    pub struct Usage {
+        pub used: usize,
+        pub quota: usize,
+    }
+
+    rpc_method!(
+        /// Gets this plugin's state usage and quota. `state_set_key` returns
+        /// `SetError::QuotaExceeded` for writes that would exceed the quota.
+        state_get_usage, GetUsage, (), Usage, permission = Standard
+    );
+}
+
+/// The addressbook namespace gives plugins access to the user's saved
+/// accounts, stored by the host and shared between all plugins.
+pub mod addressbook {
+    use schemars::JsonSchema;
+    use serde::{Deserialize, Serialize};
+
+    use crate::caip::{AccountId, ChainId};
+
+    #[derive(Debug, Serialize, Deserialize, JsonSchema, Clone, PartialEq, Eq)]
+    pub struct Contact {
+        pub label: String,
+        pub account: AccountId,
+    }
+
+    impl Contact {
+        pub fn new(label: impl Into<String>, account: AccountId) -> Self {
+            Self {
+                label: label.into(),
+                account,
+            }
+        }
+    }
+
+    rpc_method!(
+        /// Saves a contact to the address book. If the account is already
+        /// saved, its label is replaced. Fails if the contact was saved by
+        /// the user or another plugin, since only they may change it.
+        addressbook_add, Add, Contact, ()
+    );
+
+    rpc_method!(
+        /// Removes an account from the address book, if it's saved. Fails if
+        /// the contact was saved by the user or another plugin.
+        addressbook_remove, Remove, AccountId, ()
+    );
+
+    rpc_method!(
+        /// Lists saved contacts. If a chain is given, only contacts on that
+        /// chain are returned.
+        addressbook_list, List, Option<ChainId>, Vec<Contact>
+    );
+}
+
+/// The plugin namespace contains methods implemented by plugins, used by the
+/// host for lifecycle management.
+pub mod plugin {
+    use std::fmt;
+
+    use schemars::JsonSchema;
+    use serde::{Deserialize, Serialize};
+    use uuid::Uuid;
+
+    use crate::{RpcMethod, caip::ChainId, domains::Domain};
+
+    /// Capabilities a plugin declares up-front. The host prompts the user to
+    /// approve the manifest when the plugin is registered, and rejects calls
+    /// to any host method not declared here.
+    ///
+    /// Core methods (ping, state, pages, notifications, entity registration)
+    /// are always permitted and don't need to be declared.
+    #[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+    pub struct PluginManifest {
+        /// Version of the plugin's stored state, passed to `Migrate` when the
+        /// plugin is upgraded.
+        #[serde(default)]
+        pub version: u32,
+        /// Host methods the plugin may call, by name.
+        pub methods: Vec<String>,
+        /// Domains the plugin may register entities in.
+        pub domains: Vec<Domain>,
+        /// Network origins the plugin may fetch from without prompting, e.g.
+        /// `https://1rpc.io`.
+        #[serde(default)]
+        pub origins: Vec<String>,
+        /// What the plugin needs before it's initialized. The host checks
+        /// these when the plugin is registered and prompts the user to provide
+        /// any that are missing.
+        #[serde(default)]
+        pub dependencies: Vec<Dependency>,
+    }
+
+    /// Something a plugin needs from other plugins to work.
+    #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+    pub enum Dependency {
+        /// An entity in `domain`, registered by any plugin. If a chain is
+        /// given, the entity must serve it.
+        Entity {
+            domain: Domain,
+            chain_id: Option<ChainId>,
+        },
+        /// A plugin with this name whose manifest version is at least
+        /// `min_version`. If a release is given, the host installs it when
+        /// the plugin is missing.
+        Plugin {
+            name: String,
+            min_version: u32,
+            #[serde(default)]
+            release: Option<PluginRelease>,
+        },
+    }
+
+    /// A build of a plugin the host can download and install.
+    #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+    pub struct PluginRelease {
+        pub url: String,
+        /// SHA-256 digest the downloaded wasm binary must match
+        pub sha256: [u8; 32],
+    }
+
+    impl fmt::Display for Dependency {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            match self {
+                Dependency::Entity {
+                    domain,
+                    chain_id: None,
+                } => write!(f, "A {} entity", domain),
+                Dependency::Entity {
+                    domain,
+                    chain_id: Some(chain_id),
+                } => write!(f, "A {} entity for {}", domain, chain_id),
+                Dependency::Plugin {
+                    name, min_version, ..
+                } => {
+                    write!(f, "Plugin '{}' version {} or later", name, min_version)
+                }
+            }
+        }
+    }
+
+    /// A plugin's stored state, as `(key, value)` pairs.
+    pub type StateBlob = Vec<(String, Vec<u8>)>;
+
+    /// A user request a plugin was waiting on when the host's state was saved.
+    #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+    pub struct InterruptedRequest {
+        pub id: Uuid,
+        pub kind: RequestKind,
+        /// What the request asked the user for, e.g. "vault selection"
+        pub description: String,
+    }
+
+    /// What a user request asked the user for.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+    pub enum RequestKind {
+        /// Picking an entity in this domain
+        EntitySelection(Domain),
+        ManifestApproval,
+        OriginApproval,
+        MissingDependencies,
+        UnsignedPlugin,
+        PluginUpgrade,
+        CallApproval,
+        KeystoreUnlock,
+        Confirmation,
+        Custom,
+    }
+
+    impl PluginManifest {
+        pub fn new() -> Self {
+            Self::default()
+        }
+
+        pub fn with_version(mut self, version: u32) -> Self {
+            self.version = version;
+            self
+        }
+
+        pub fn with_method<M: RpcMethod>(mut self, _: M) -> Self {
+            if !self.allows_method(M::NAME) {
+                self.methods.push(M::NAME.to_string());
+            }
+            self
+        }
+
+        pub fn with_domain(mut self, domain: Domain) -> Self {
+            if !self.allows_domain(domain) {
+                self.domains.push(domain);
+            }
+            self
+        }
+
+        pub fn with_origin(mut self, origin: impl Into<String>) -> Self {
+            let origin = origin.into();
+            if !self.origins.contains(&origin) {
+                self.origins.push(origin);
+            }
+            self
+        }
+
+        pub fn with_dependency(mut self, dependency: Dependency) -> Self {
+            if !self.dependencies.contains(&dependency) {
+                self.dependencies.push(dependency);
+            }
+            self
+        }
+
+        pub fn allows_method(&self, method: &str) -> bool {
+            self.methods.iter().any(|m| m == method)
+        }
+
+        pub fn allows_domain(&self, domain: Domain) -> bool {
+            self.domains.contains(&domain)
+        }
+    }
+
+    rpc_method!(
+        /// Get the plugin's manifest of requested capabilities. Called by the
+        /// host before `Init`. Plugins that don't implement this are granted
+        /// only the core methods, and may register entities in any domain.
+        plugin_get_manifest, GetManifest, (), PluginManifest
     );
 
     rpc_method!(
-        /// Requests the host to provide a Coordinator for this plugin
-        host_request_coordinator,
-        RequestCoordinator,
-        (),
-        CoordinatorId
+        /// Get the tlock API version the plugin was built against. Called by
+        /// the host when the plugin is loaded. Plugins that don't implement
+        /// this are assumed to target `MIN_API_VERSION`.
+        plugin_get_api_version, GetApiVersion, (), u32
     );
 
     rpc_method!(
-        /// Make a network request
-        host_fetch, Fetch, Request, Result<Vec<u8>, String>
+        /// Initialize the plugin, called by the host the first time a new plugin
+        /// is registered. Will only ever be called once per plugin.
+        plugin_init, Init, (), ()
     );
 
     rpc_method!(
-        /// Sets a specific page to the given component.
-        host_set_page, SetPage, (PageId, Component), ()
+        /// Migrate state stored by a previous version of the plugin. Called by
+        /// the host when a plugin is upgraded, before the new version replaces
+        /// the old registration.
+        ///
+        /// Receives the old manifest's `version` and the old state, and returns
+        /// the state to store for the new version. If this call fails the
+        /// upgrade is aborted and the old version stays registered.
+        ///
+        /// Plugins that don't implement this may only be upgraded between
+        /// builds with the same manifest `version`.
+        plugin_migrate, Migrate, (u32, StateBlob), StateBlob
     );
 
     rpc_method!(
-        /// Sends a notification to the host to be displayed
-        host_notify, Notify, (NotifyLevel, String), ()
+        /// Called by the host before the plugin is unloaded, either because it
+        /// was removed or because the host is shutting down. Plugins SHOULD
+        /// flush pending state and revoke any open sessions.
+        ///
+        /// The host only waits a bounded amount of time for this call, after
+        /// which the plugin and its entities are removed regardless.
+        plugin_shutdown, Shutdown, (), ()
     );
-}
 
-/// The state namespace allows plugins to manage their persistent state
-/// stored by the host.
-pub mod state {
-    use serde::{Deserialize, Serialize};
-    use thiserror::Error;
+    rpc_method!(
+        /// Called by the host when the user switches the active chain, so
+        /// chain-sensitive plugins can refresh their state.
+        plugin_on_chain_changed, OnChainChanged, crate::caip::ChainId, ()
+    );
 
-    #[derive(Debug, Error, Serialize, Deserialize)]
-    #[non_exhaustive]
-    pub enum SetError {
-        #[error("Key is not locked")]
-        KeyNotLocked,
-    }
+    rpc_method!(
+        /// Called by the host when an alarm set with `host_set_alarm` fires,
+        /// with the alarm's tag.
+        plugin_on_alarm, OnAlarm, String, ()
+    );
 
-    #[derive(Debug, Error, Serialize, Deserialize)]
-    #[non_exhaustive]
-    pub enum UnlockError {
-        #[error("Key is not locked")]
-        KeyNotLocked,
-    }
+    rpc_method!(
+        /// Called by the host on the interval registered with
+        /// `host_register_tick`.
+        plugin_on_tick, OnTick, (), ()
+    );
 
     rpc_method!(
-        /// Reads a key from this plugin's state.
-        ///
-        /// If the key is already locked, the host will block until it is
-        /// unlocked.
-        state_read_key, ReadKey, String, Vec<u8>
+        /// Called by the host when a payload is published to a topic this
+        /// plugin subscribed to with `host_subscribe`.
+        plugin_on_event, OnEvent, (String, Vec<u8>), ()
     );
 
     rpc_method!(
-        /// Locks a key from this plugin's state. The key will remain
-        /// locked until either `state_unlock_key` is called, or the plugin
-        /// instance is terminated.
-        ///
-        /// If the key is already locked, the host will block until it is
-        /// unlocked.
-        state_lock_key, LockKey, String, Vec<u8>
+        /// Called by the host when a transaction this plugin sent with
+        /// `eth_sendRawTransaction` changes status. Each transaction reports
+        /// one final status: confirmed, failed, or replaced.
+        plugin_on_transaction_status,
+        OnTransactionStatus,
+        (alloy::primitives::TxHash, crate::eth::TransactionStatus),
+        ()
     );
 
     rpc_method!(
-        /// Sets a key in this plugin's state. If the key is not locked,
-        /// returns an error.
-        state_set_key, SetKey, (String, Vec<u8>), Result<(), SetError>
+        /// Called by the host after restoring its state, once for each user
+        /// request this plugin was waiting on when the state was saved. The
+        /// call that made the request didn't survive, so the plugin should make
+        /// it again if it still needs it.
+        plugin_on_request_interrupted,
+        OnRequestInterrupted,
+        InterruptedRequest,
+        (),
+        version = 2
     );
 
     rpc_method!(
-        /// Unlocks a previously locked key in this plugin's state. If the key
-        /// is not locked, returns an error.
-        state_unlock_key, UnlockKey, String, Result<(), UnlockError>
+        /// Called by the host with each message received on a WebSocket this
+        /// plugin opened with `host_ws_connect`.
+        plugin_on_ws_message, OnWsMessage, (crate::host::WsId, crate::host::WsMessage), ()
     );
-}
 
-/// The plugin namespace contains methods implemented by plugins, used by the
-/// host for lifecycle management.
-pub mod plugin {
     rpc_method!(
-        /// Initialize the plugin, called by the host the first time a new plugin
-        /// is registered. Will only ever be called once per plugin.
-        plugin_init, Init, (), ()
+        /// Called by the host when a WebSocket this plugin opened closes, with
+        /// the error that closed it if any.
+        plugin_on_ws_closed, OnWsClosed, (crate::host::WsId, Option<String>), ()
     );
+
+    impl crate::OptionalMethod for GetManifest {}
+    impl crate::OptionalMethod for GetApiVersion {}
+    impl crate::OptionalMethod for Init {}
+    impl crate::OptionalMethod for Migrate {}
+    impl crate::OptionalMethod for Shutdown {}
+    impl crate::OptionalMethod for OnChainChanged {}
+    impl crate::OptionalMethod for OnAlarm {}
+    impl crate::OptionalMethod for OnTick {}
+    impl crate::OptionalMethod for OnEvent {}
+    impl crate::OptionalMethod for OnTransactionStatus {}
+    impl crate::OptionalMethod for OnRequestInterrupted {}
+    impl crate::OptionalMethod for OnWsMessage {}
+    impl crate::OptionalMethod for OnWsClosed {}
 }
 
 /// The eth namespace contains methods for interacting with EVM chains.
@@ -271,13 +1307,30 @@ pub mod eth {
         eips::{BlockId, BlockNumberOrTag},
         primitives::{Address, Bytes, TxHash, U256},
         rpc::types::{
-            Block, BlockOverrides, BlockTransactionsKind, Filter, Log, Transaction,
-            TransactionReceipt, TransactionRequest, state::StateOverride,
+            AccessListResult, Block, BlockOverrides, BlockTransactionsKind,
+            EIP1186AccountProofResponse, Filter, Log, Transaction, TransactionReceipt,
+            TransactionRequest, state::StateOverride,
         },
     };
+    use schemars::JsonSchema;
+    use serde::{Deserialize, Serialize};
 
     use crate::entities::EthProviderId;
 
+    /// Status of a transaction sent with `eth_sendRawTransaction`, as tracked
+    /// by the host.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+    pub enum TransactionStatus {
+        /// Broadcast but not yet included in a block
+        Pending,
+        /// Included in a block and executed successfully
+        Confirmed { block_number: u64 },
+        /// Included in a block but reverted
+        Failed { block_number: u64 },
+        /// Another transaction from the same sender used its nonce
+        Replaced,
+    }
+
     rpc_method!(
         /// Get the current block number.
         eth_blockNumber, BlockNumber, EthProviderId, u64
@@ -327,6 +1380,22 @@ pub mod eth {
         eth_getStorageAt, GetStorageAt, (EthProviderId, Address, U256, BlockId), U256
     );
 
+    rpc_method!(
+        /// Gets the account and storage values of an account, including the
+        /// EIP-1186 merkle proofs, for the given storage keys.
+        eth_getProof, GetProof, (EthProviderId, Address, Vec<U256>, BlockId), EIP1186AccountProofResponse
+    );
+
+    rpc_method!(
+        /// Returns the current maxPriorityFeePerGas per gas in wei.
+        eth_maxPriorityFeePerGas, MaxPriorityFeePerGas, EthProviderId, u128
+    );
+
+    rpc_method!(
+        /// Returns the base fee per blob gas in wei.
+        eth_blobBaseFee, BlobBaseFee, EthProviderId, u128
+    );
+
     rpc_method!(
         /// Returns the historic gas fee for a given block range
         eth_feeHistory, FeeHistory, (EthProviderId, u64, BlockNumberOrTag, Vec<f64>), alloy::rpc::types::FeeHistory
@@ -352,6 +1421,12 @@ pub mod eth {
         eth_estimateGas, EstimateGas, (EthProviderId, TransactionRequest, BlockId, Option<StateOverride>, Option<BlockOverrides>), u64
     );
 
+    rpc_method!(
+        /// Creates an EIP-2930 access list for a transaction, listing the
+        /// accounts and storage slots it touches.
+        eth_createAccessList, CreateAccessList, (EthProviderId, TransactionRequest, BlockId), AccessListResult
+    );
+
     // TODO: Consider making this a different domain and having a distinction
     // between "eth-read" and "eth-write" methods. Would also make it easier to
     // add custom send methods (IE to private pool, or forwarding to devp2p, etc).
@@ -360,7 +1435,384 @@ pub mod eth {
         /// signed prior to calling this method. If the transaction is invalid,
         /// the plugin MUST return an error. Callers SHOULD NOT assume that
         /// the plugin
-        eth_sendRawTransaction, SendRawTransaction, (EthProviderId, Bytes), TxHash
+        eth_sendRawTransaction, SendRawTransaction, (EthProviderId, Bytes), TxHash,
+        permission = Privileged
+    );
+}
+
+/// The sol namespace contains methods for interacting with Solana chains.
+/// Methods mirror the standard Solana JSON-RPC API, with keys, hashes, and
+/// signatures passed in their base58 string encodings.
+pub mod sol {
+    use schemars::JsonSchema;
+    use serde::{Deserialize, Serialize};
+
+    use crate::entities::SolProviderId;
+
+    /// Base58-encoded public key
+    pub type Pubkey = String;
+    /// Base58-encoded transaction signature
+    pub type Signature = String;
+
+    #[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, Copy, PartialEq, Eq, Default)]
+    #[serde(rename_all = "lowercase")]
+    pub enum Commitment {
+        Processed,
+        Confirmed,
+        #[default]
+        Finalized,
+    }
+
+    #[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, PartialEq, Eq)]
+    pub struct AccountInfo {
+        pub lamports: u64,
+        pub owner: Pubkey,
+        pub data: Vec<u8>,
+        pub executable: bool,
+        pub rent_epoch: u64,
+    }
+
+    #[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, PartialEq, Eq)]
+    pub struct LatestBlockhash {
+        /// Base58-encoded blockhash
+        pub blockhash: String,
+        pub last_valid_block_height: u64,
+    }
+
+    rpc_method!(
+        /// Get the lamport balance of an account.
+        sol_getBalance, GetBalance, (SolProviderId, Pubkey, Commitment), u64
+    );
+
+    rpc_method!(
+        /// Get an account's info, or None if the account does not exist.
+        sol_getAccountInfo, GetAccountInfo, (SolProviderId, Pubkey, Commitment), Option<AccountInfo>
+    );
+
+    rpc_method!(
+        /// Get the latest blockhash, used when building transactions.
+        sol_getLatestBlockhash, GetLatestBlockhash, (SolProviderId, Commitment), LatestBlockhash
+    );
+
+    rpc_method!(
+        /// Submit a fully-signed, wire-encoded transaction. Returns the
+        /// transaction's first signature.
+        sol_sendTransaction, SendTransaction, (SolProviderId, Vec<u8>), Signature,
+        permission = Privileged
+    );
+}
+
+/// The btc namespace contains methods for interacting with UTXO-based Bitcoin
+/// chains. Amounts are denominated in satoshis and txids use their hex display
+/// encoding.
+pub mod btc {
+    use schemars::JsonSchema;
+    use serde::{Deserialize, Serialize};
+
+    use crate::entities::BtcProviderId;
+
+    /// Encoded Bitcoin address (e.g. bech32)
+    pub type Address = String;
+    /// Hex-encoded transaction ID
+    pub type Txid = String;
+
+    #[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, PartialEq, Eq)]
+    pub struct Utxo {
+        pub txid: Txid,
+        pub vout: u32,
+        pub value: u64,
+        pub script_pubkey: Vec<u8>,
+        /// Height of the block containing the output, or None if unconfirmed.
+        pub block_height: Option<u64>,
+    }
+
+    #[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, PartialEq, Eq)]
+    pub struct HistoryEntry {
+        pub txid: Txid,
+        /// Height of the block containing the transaction, or None if unconfirmed.
+        pub block_height: Option<u64>,
+        /// Net change in the address's balance from this transaction
+        pub delta: i64,
+    }
+
+    rpc_method!(
+        /// Get the unspent outputs locked to an address, including unconfirmed
+        /// outputs.
+        btc_getUtxos, GetUtxos, (BtcProviderId, Address), Vec<Utxo>
+    );
+
+    rpc_method!(
+        /// Broadcast a fully-signed, consensus-encoded transaction. Returns its
+        /// txid.
+        btc_broadcastTx, BroadcastTx, (BtcProviderId, Vec<u8>), Txid
+    );
+
+    rpc_method!(
+        /// Estimate the fee rate, in sat/vB, required for a transaction to
+        /// confirm within the given number of blocks.
+        btc_estimateFee, EstimateFee, (BtcProviderId, u32), f64
+    );
+
+    rpc_method!(
+        /// Get the transaction history of an address, most recent first.
+        btc_getAddressHistory, GetAddressHistory, (BtcProviderId, Address), Vec<HistoryEntry>
+    );
+}
+
+/// The trace namespace contains optional debugging methods for EthProviders.
+/// Providers that don't support tracing SHOULD return `MethodNotFound`.
+pub mod trace {
+    use alloy::{
+        eips::BlockId,
+        primitives::TxHash,
+        rpc::types::{
+            TransactionRequest,
+            trace::geth::{GethDebugTracingCallOptions, GethDebugTracingOptions, GethTrace},
+        },
+    };
+
+    use crate::entities::EthProviderId;
+
+    rpc_method!(
+        /// Replays a mined transaction and returns its execution trace.
+        debug_traceTransaction, TraceTransaction, (EthProviderId, TxHash, GethDebugTracingOptions), GethTrace
+    );
+
+    rpc_method!(
+        /// Executes a call without creating a transaction and returns its
+        /// execution trace.
+        debug_traceCall, TraceCall, (EthProviderId, TransactionRequest, BlockId, GethDebugTracingCallOptions), GethTrace
+    );
+}
+
+/// The fees namespace contains EIP-1559 fee suggestions, served by
+/// EthProviders. Providers derive them from `eth_feeHistory`, and
+/// `tlock_pdk::fees` implements the shared percentile math.
+pub mod fees {
+    use schemars::JsonSchema;
+    use serde::{Deserialize, Serialize};
+
+    use crate::entities::EthProviderId;
+
+    /// EIP-1559 fee parameters for a transaction, in wei.
+    #[derive(Serialize, Deserialize, JsonSchema, Clone, Copy, Debug, PartialEq, Eq)]
+    pub struct FeeEstimate {
+        pub max_fee_per_gas: u128,
+        pub max_priority_fee_per_gas: u128,
+    }
+
+    #[derive(Serialize, Deserialize, JsonSchema, Clone, Copy, Debug, PartialEq, Eq)]
+    pub struct FeeSuggestion {
+        pub slow: FeeEstimate,
+        pub normal: FeeEstimate,
+        pub fast: FeeEstimate,
+    }
+
+    rpc_method!(
+        /// Suggests fees for a transaction included in the next few blocks,
+        /// with faster tiers paying a higher priority fee.
+        fees_suggest, Suggest, EthProviderId, FeeSuggestion
+    );
+}
+
+/// The ens namespace contains methods for resolving ENS names. These are
+/// served by the host, which reads the ENS contracts through the given
+/// EthProvider.
+pub mod ens {
+    use alloy::primitives::Address;
+
+    use crate::entities::EthProviderId;
+
+    rpc_method!(
+        /// Resolve an ENS name to the address it points to. Returns an error if
+        /// the name has no resolver or no address set.
+        ens_resolve, Resolve, (EthProviderId, String), Address
+    );
+
+    rpc_method!(
+        /// Look up the primary ENS name of an address. Returns None if the
+        /// address has no primary name, or if that name doesn't resolve back
+        /// to the address.
+        ens_lookup, Lookup, (EthProviderId, Address), Option<String>
+    );
+}
+
+/// The price namespace contains methods for quoting asset prices. Quote
+/// currencies are lowercase ISO 4217 codes (e.g. `usd`, `eur`).
+pub mod price {
+    pub use rust_decimal::Decimal;
+
+    use crate::{caip::AssetId, entities::PriceProviderId};
+
+    /// Lowercase ISO 4217 currency code
+    pub type Currency = String;
+
+    rpc_method!(
+        /// Get the price of one whole unit of an asset in the quote currency.
+        /// Providers MUST return an error if they don't know the asset's
+        /// price.
+        price_get, Get, (PriceProviderId, AssetId, Currency), Decimal
+    );
+
+    rpc_method!(
+        /// Get the prices of several assets in the quote currency. Assets
+        /// without a known price are omitted from the result.
+        price_get_many, GetMany, (PriceProviderId, Vec<AssetId>, Currency), Vec<(AssetId, Decimal)>
+    );
+}
+
+/// The erc4337 namespace contains methods for building and submitting
+/// ERC-4337 user operations through a bundler.
+///
+/// User operations use the EntryPoint v0.7 field layout. Methods that talk to
+/// the bundler take the EntryPoint address explicitly, matching the bundler
+/// JSON-RPC API.
+pub mod erc4337 {
+    use alloy::primitives::{Address, B256, Bytes};
+    pub use alloy::rpc::types::erc4337::{
+        PackedUserOperation, UserOperationGasEstimation, UserOperationReceipt,
+    };
+    use schemars::JsonSchema;
+    use serde::{Deserialize, Serialize};
+
+    use crate::entities::BundlerProviderId;
+
+    /// The parts of a user operation chosen by the caller. Everything else is
+    /// filled in by the bundler provider.
+    #[derive(Debug, Serialize, Deserialize, JsonSchema, Clone, PartialEq, Eq)]
+    pub struct UserOpRequest {
+        #[schemars(with = "crate::schema::Hex")]
+        pub entry_point: Address,
+        #[schemars(with = "crate::schema::Hex")]
+        pub sender: Address,
+        #[schemars(with = "crate::schema::Hex")]
+        pub call_data: Bytes,
+        /// Factory and init data, if the sender hasn't been deployed yet.
+        #[schemars(with = "Option<(crate::schema::Hex, crate::schema::Hex)>")]
+        pub factory: Option<(Address, Bytes)>,
+    }
+
+    rpc_method!(
+        /// Build an unsigned user operation, filling in the nonce, gas fees,
+        /// and gas limits. The signature field is left as a dummy signature
+        /// suitable for estimation, and MUST be replaced before sending.
+        erc4337_build_user_op, BuildUserOp, (BundlerProviderId, UserOpRequest), PackedUserOperation
+    );
+
+    rpc_method!(
+        /// Estimate the gas limits for a user operation.
+        erc4337_estimate_user_op_gas,
+        EstimateUserOpGas,
+        (BundlerProviderId, PackedUserOperation, Address),
+        UserOperationGasEstimation
+    );
+
+    rpc_method!(
+        /// Submit a signed user operation to the bundler, returning its hash.
+        erc4337_send_user_op, SendUserOp, (BundlerProviderId, PackedUserOperation, Address), B256,
+        permission = Privileged
+    );
+
+    rpc_method!(
+        /// Get the receipt for a user operation, or None if it hasn't been
+        /// included yet.
+        erc4337_get_user_op_receipt,
+        GetUserOpReceipt,
+        (BundlerProviderId, B256),
+        Option<UserOperationReceipt>
+    );
+}
+
+/// The tokens namespace contains methods for discovering the tokens available
+/// on a chain, so UIs don't need to hardcode their own token lists.
+pub mod tokens {
+    use schemars::JsonSchema;
+    use serde::{Deserialize, Serialize};
+
+    use crate::{
+        caip::{AssetId, ChainId},
+        entities::TokenListId,
+    };
+
+    #[derive(Debug, Serialize, Deserialize, JsonSchema, Clone, PartialEq, Eq)]
+    pub struct TokenInfo {
+        pub asset_id: AssetId,
+        pub symbol: String,
+        pub name: String,
+        pub decimals: u8,
+        pub logo_url: Option<String>,
+    }
+
+    rpc_method!(
+        /// Get every token the list knows about on a chain.
+        tokens_get_list, GetList, (TokenListId, ChainId), Vec<TokenInfo>
+    );
+
+    rpc_method!(
+        /// Search for tokens across all chains by symbol, name, or address.
+        /// Matching is case-insensitive. Lists SHOULD order results by
+        /// relevance.
+        tokens_search, Search, (TokenListId, String), Vec<TokenInfo>
+    );
+}
+
+/// The nft namespace contains methods for discovering ERC-721 and ERC-1155
+/// tokens. NFTs are identified by `AssetType::Erc721` / `AssetType::Erc1155`
+/// asset IDs, which vaults can also report through `vault_get_assets`.
+pub mod nft {
+    use alloy::primitives::U256;
+    use schemars::JsonSchema;
+    use serde::{Deserialize, Serialize};
+
+    use crate::{
+        caip::{AccountId, AssetId},
+        entities::NftProviderId,
+    };
+
+    /// Token metadata, following the ERC-721 metadata JSON schema.
+    #[derive(Debug, Serialize, Deserialize, JsonSchema, Clone, PartialEq, Eq, Default)]
+    pub struct NftMetadata {
+        pub name: Option<String>,
+        pub description: Option<String>,
+        /// Image URL, with `ipfs://` URLs resolved through a gateway.
+        pub image: Option<String>,
+        /// `(trait_type, value)` pairs
+        pub attributes: Vec<(String, String)>,
+    }
+
+    rpc_method!(
+        /// Get the NFTs owned by an account, with the owned amount of each.
+        /// ERC-721 amounts are always 1.
+        nft_get_owned, GetOwned, (NftProviderId, AccountId), Vec<(AssetId, U256)>
+    );
+
+    rpc_method!(
+        /// Get the metadata for an NFT. Providers MUST return an error for
+        /// non-NFT assets.
+        nft_get_metadata, GetMetadata, (NftProviderId, AssetId), NftMetadata
+    );
+}
+
+/// The signer namespace contains methods for signing raw messages without
+/// the calling plugin holding a private key.
+///
+/// The host asks the user to approve every `Sign` request before forwarding
+/// it to the signer, so plugins SHOULD only request signatures in response to
+/// user actions.
+pub mod signer {
+    use alloy::primitives::{Address, Bytes, Signature};
+
+    use crate::entities::SignerId;
+
+    rpc_method!(
+        /// Sign a message with EIP-191 `personal_sign` semantics. Signers MUST
+        /// prefix the message themselves, so callers pass the raw message.
+        signer_sign, Sign, (SignerId, Bytes), Signature, permission = Privileged
+    );
+
+    rpc_method!(
+        /// Get the address that signatures from this signer recover to.
+        signer_address, GetAddress, SignerId, Address
     );
 }
 
@@ -395,7 +1847,8 @@ pub mod vault {
         /// or for any other reason.
         ///
         /// Vaults MUST reject requests if they cannot fufill them.
-        vault_withdraw, Withdraw, (VaultId, AccountId, AssetId, U256), ()
+        vault_withdraw, Withdraw, (VaultId, AccountId, AssetId, U256), (),
+        permission = Privileged
     );
 
     rpc_method!(
@@ -412,18 +1865,21 @@ pub mod vault {
         vault_get_deposit_address, GetDepositAddress, (VaultId, AssetId), AccountId
     );
 
-    // TODO: Whether this method makes sense. We can't guarantee it will be
-    // called on every deposit, so vaults will need to reconcile deposits
-    // themselves anyway. It may be better to add callbacks vaults can
-    // register for when deposits are made rather than trusting depositors
-    // to call this method. rpc_method!(
-    //     /// Callback for when an amount is deposited in an account.
-    //     ///
-    //     /// Acts as a hint to the vault plugin that it should handle the
-    // deposit     /// and update its internal state accordingly. The vault
-    // cannot assume     /// that this method will always be called for
-    // every deposit.     vault_on_deposit, OnDeposit, (VaultId, AccountId,
-    // AssetId), () );
+    rpc_method!(
+        /// Called by the host when a watched account's balance of an asset
+        /// increases, with the amount it increased by. Watches are registered
+        /// with `host_watch_deposit`, and the host also watches every address
+        /// returned from `vault_get_deposit_address` whose chain has a default
+        /// eth provider.
+        ///
+        /// Balances are polled, so several deposits may be reported as a single
+        /// increase and withdrawals made between polls may hide a deposit.
+        /// Vaults SHOULD treat this as a hint to reconcile their state rather
+        /// than as a record of every transfer.
+        vault_on_deposit_detected, OnDepositDetected, (VaultId, AccountId, AssetId, U256), ()
+    );
+
+    impl crate::OptionalMethod for OnDepositDetected {}
 }
 
 /// Coordinators act as intermediaries between plugins and vaults. They provide
@@ -438,28 +1894,60 @@ pub mod vault {
 /// in the event of an error or failure. Coordinators abstract away this complexity and
 /// handle all vault interactions on behalf of plugins.
 pub mod coordinator {
-    use alloy::primitives::{Address, U256};
+    use std::time::Duration;
+
+    use alloy::primitives::{Address, I256, U256};
 
     use crate::{
         caip::{AccountId, AssetId, ChainId},
         entities::CoordinatorId,
     };
 
-    #[derive(serde::Serialize, serde::Deserialize, Clone, Debug)]
+    #[derive(serde::Serialize, serde::Deserialize, schemars::JsonSchema, Clone, Debug)]
     pub struct EvmBundle {
+        #[schemars(with = "Vec<(AssetId, crate::schema::Hex)>")]
         pub inputs: Vec<(AssetId, U256)>,
         // TODO: Consider something like railgun's hasNonDeterministicOutputs flag?
         pub outputs: Vec<AssetId>,
         pub operations: Vec<EvmOperation>,
     }
 
-    #[derive(serde::Serialize, serde::Deserialize, Clone, Debug)]
+    #[derive(serde::Serialize, serde::Deserialize, schemars::JsonSchema, Clone, Debug)]
     pub struct EvmOperation {
+        #[schemars(with = "crate::schema::Hex")]
         pub to: Address,
+        #[schemars(with = "crate::schema::Hex")]
         pub value: U256,
         pub data: Vec<u8>,
     }
 
+    #[derive(
+        serde::Serialize, serde::Deserialize, schemars::JsonSchema, Clone, Debug, PartialEq,
+    )]
+    pub struct Session {
+        pub account: AccountId,
+        /// How long the session stays open. Once it elapses the coordinator
+        /// MUST release any assets held for the session and reject proposals
+        /// from the account until a new session is requested.
+        pub ttl: Duration,
+    }
+
+    #[derive(serde::Serialize, serde::Deserialize, schemars::JsonSchema, Clone, Debug)]
+    pub struct SimulationResult {
+        /// Expected net change in each bundled asset once the bundle has
+        /// executed and any outstanding assets have been returned.
+        #[schemars(with = "Vec<(AssetId, crate::schema::SignedInteger)>")]
+        pub asset_deltas: Vec<(AssetId, I256)>,
+        /// Total gas used by the bundle's operations
+        pub gas_used: u64,
+        /// Expected gas cost, denominated in the chain's native asset
+        #[schemars(with = "crate::schema::Hex")]
+        pub gas_cost: U256,
+        /// Reason the first failing operation reverted, or None if every
+        /// operation succeeded.
+        pub revert_reason: Option<String>,
+    }
+
     rpc_method!(
         /// Gets the coordinator to start a new session.
         ///
@@ -468,7 +1956,26 @@ pub mod coordinator {
         /// return an error.
         ///
         /// If None(accountID) the coordinator may start a new session with any account.
-        coordinator_get_session, GetSession, (CoordinatorId, ChainId, Option<AccountId>), AccountId
+        ///
+        /// Resuming a session restarts its TTL.
+        coordinator_get_session,
+        GetSession,
+        (CoordinatorId, ChainId, Option<AccountId>),
+        Session,
+        version = 2
+    );
+
+    rpc_method!(
+        /// Cancels the account's session, releasing any assets held for it.
+        ///
+        /// Plugins SHOULD cancel sessions they no longer intend to propose
+        /// from. The host also cancels sessions once their TTL (at most a day)
+        /// has elapsed, or when the plugin that requested them is unloaded.
+        /// Cancelling a session that is already closed is not an error.
+        ///
+        /// Only the plugin that requested the session, or one that has been
+        /// granted the coordinator, may cancel it.
+        coordinator_cancel_session, CancelSession, (CoordinatorId, AccountId), ()
     );
 
     rpc_method!(
@@ -495,22 +2002,70 @@ pub mod coordinator {
         coordinator_propose_evm,
         Propose,
         (CoordinatorId, AccountId, EvmBundle),
-        ()
+        (),
+        permission = Privileged
     );
+
+    rpc_method!(
+        /// Simulate a set of EVM operations without executing them.
+        ///
+        /// Accepts the same bundle as `Propose`. The coordinator MUST NOT
+        /// modify any on-chain or vault state while simulating, and the
+        /// account's session remains open afterwards.
+        ///
+        /// A reverting bundle is not an error. The coordinator SHOULD return
+        /// a result with `revert_reason` set instead.
+        ///
+        /// Coordinators MAY leave this out, so callers MUST treat
+        /// `MethodNotFound` as the bundle not having been simulated.
+        coordinator_simulate,
+        Simulate,
+        (CoordinatorId, AccountId, EvmBundle),
+        SimulationResult
+    );
+
+    rpc_method!(
+        /// Propose EVM operations spanning multiple chains as a single proposal.
+        ///
+        /// Each entry pairs a bundle with the account that executes it. The
+        /// chain for each bundle is the chain of its account, and a session
+        /// MUST have been requested for every account prior to calling this
+        /// method.
+        ///
+        /// The proposal is approved or rejected as a whole. The coordinator
+        /// MUST validate every bundle before executing any of them, and MUST
+        /// reject the proposal if it cannot execute one of the bundles.
+        ///
+        /// Bundles are executed in order, so outputs of an earlier bundle (e.g.
+        /// bridged assets) MAY be used as inputs of a later one.
+        ///
+        /// After calling this method, all involved sessions are considered closed.
+        coordinator_propose_multichain,
+        ProposeMultichain,
+        (CoordinatorId, Vec<(AccountId, EvmBundle)>),
+        (),
+        permission = Privileged
+    );
+
+    impl crate::OptionalMethod for Simulate {}
 }
 
 pub mod page {
     use std::collections::HashMap;
 
+    use schemars::JsonSchema;
     use serde::{Deserialize, Serialize};
 
     use crate::entities::PageId;
 
     #[non_exhaustive]
-    #[derive(Serialize, Deserialize, Debug)]
+    #[derive(Serialize, Deserialize, JsonSchema, Debug)]
     pub enum PageEvent {
         ButtonClicked(String),                          // (button_id)
         FormSubmitted(String, HashMap<String, String>), // (form_id, form_values)
+        NotificationAction(String),                     // (action_id)
+        TabChanged(String, usize),                      // (tabs_id, tab_index)
+        ModalDismissed(String),                         // (modal_id)
     }
 
     rpc_method!(
@@ -0,0 +1,474 @@
+//! A machine-readable description of every method in the API, for tooling
+//! and plugin SDKs in other languages.
+//!
+//! `open_rpc` describes the methods as an OpenRPC document, with their params
+//! and outputs described by JSON Schema. The methods are listed by the build
+//! script from their `rpc_method!` declarations, so every method is covered.
+//!
+//! The API's own types derive their schemas with `schemars`. Alloy's types
+//! don't provide one, so hex-encoded values like addresses and `U256` are
+//! described as hex strings, and its RPC types, like `Block`, only by name.
+
+// TODO: Describe alloy's RPC types in full, once alloy provides schemas for
+// them or they're worth maintaining by hand.
+
+use std::{borrow::Cow, time::Duration};
+
+use alloy::{
+    eips::{BlockId, BlockNumberOrTag},
+    primitives::{Address, Bytes, FixedBytes, Signature, U256},
+    rpc::types::{
+        AccessListResult, Block, BlockOverrides, BlockTransactionsKind,
+        EIP1186AccountProofResponse, FeeHistory, Filter, Log, Transaction, TransactionReceipt,
+        TransactionRequest,
+        erc4337::{PackedUserOperation, UserOperationGasEstimation, UserOperationReceipt},
+        state::StateOverride,
+        trace::geth::{GethDebugTracingCallOptions, GethDebugTracingOptions, GethTrace},
+    },
+};
+use rust_decimal::Decimal;
+use schemars::{JsonSchema, Schema, SchemaGenerator, generate::SchemaSettings, json_schema};
+use serde::Serialize;
+use serde_json::{Value, json};
+
+use crate::{
+    API_VERSION, Permission, RpcMethod, addressbook, btc, caip, component, coordinator, domains,
+    entities, erc4337, eth, fees, host, nft, notification, page, plugin, sol, state, tokens,
+};
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct MethodSchema {
+    pub name: &'static str,
+    pub version: u32,
+    pub permission: Permission,
+    pub params: Schema,
+    pub output: Schema,
+    /// The Rust type of the method's params, as written in `tlock-api`, or
+    /// empty if the method doesn't say
+    pub params_type: &'static str,
+    /// The Rust type of the method's output, as written in `tlock-api`, or
+    /// empty if the method doesn't say
+    pub output_type: &'static str,
+}
+
+/// Describes `M`, adding the schemas its params and output refer to to
+/// `generator`'s definitions.
+pub fn of<M>(generator: &mut SchemaGenerator) -> MethodSchema
+where
+    M: RpcMethod,
+    M::Params: ApiSchema,
+    M::Output: ApiSchema,
+{
+    MethodSchema {
+        name: M::NAME,
+        version: M::VERSION,
+        permission: M::PERMISSION,
+        params: M::Params::api_schema(generator),
+        output: M::Output::api_schema(generator),
+        params_type: M::PARAMS_TYPE,
+        output_type: M::OUTPUT_TYPE,
+    }
+}
+
+/// A generator whose definitions are referred to where an OpenRPC document
+/// keeps them.
+pub fn generator() -> SchemaGenerator {
+    SchemaSettings::draft2020_12()
+        .with(|settings| settings.definitions_path = "/components/schemas".into())
+        .into_generator()
+}
+
+/// Every method in the API, in the order they're declared.
+pub fn methods() -> Vec<MethodSchema> {
+    methods_with(&mut generator())
+}
+
+/// Every method in the API, in the order they're declared, adding the
+/// schemas they refer to to `generator`'s definitions.
+pub fn methods_with(generator: &mut SchemaGenerator) -> Vec<MethodSchema> {
+    include!(concat!(env!("OUT_DIR"), "/methods.rs"))
+}
+
+/// Describes every method as an OpenRPC document.
+pub fn open_rpc() -> Value {
+    let mut generator = generator();
+    let methods: Vec<Value> = methods_with(&mut generator)
+        .into_iter()
+        .map(|method| {
+            json!({
+                "name": method.name,
+                "paramStructure": "by-position",
+                "params": [descriptor("params", &method.params, method.params_type)],
+                "result": descriptor("result", &method.output, method.output_type),
+                "x-version": method.version,
+                "x-permission": method.permission,
+            })
+        })
+        .collect();
+
+    json!({
+        "openrpc": "1.2.6",
+        "info": {
+            "title": "tlock",
+            "version": API_VERSION.to_string(),
+        },
+        "methods": methods,
+        "components": {
+            "schemas": generator.definitions(),
+        },
+    })
+}
+
+/// An OpenRPC content descriptor, naming the Rust type it describes if known.
+fn descriptor(name: &str, schema: &Schema, rust_type: &str) -> Value {
+    let mut descriptor = json!({
+        "name": name,
+        "required": true,
+        "schema": schema,
+    });
+    if !rust_type.is_empty() {
+        descriptor["x-rust-type"] = rust_type.into();
+    }
+    descriptor
+}
+
+/// A type with a JSON Schema describing how it's serialized in the API.
+///
+/// Implemented through `schemars` for the API's own types, and here for
+/// containers of them and for the alloy and std types the API uses, which
+/// `schemars` can't be implemented for outside their crates.
+pub trait ApiSchema {
+    fn api_schema(generator: &mut SchemaGenerator) -> Schema;
+}
+
+macro_rules! via_schemars {
+    ($($ty:ty),* $(,)?) => {
+        $(
+            impl ApiSchema for $ty {
+                fn api_schema(generator: &mut SchemaGenerator) -> Schema {
+                    generator.subschema_for::<$ty>()
+                }
+            }
+        )*
+    };
+}
+
+via_schemars!(
+    (),
+    bool,
+    u8,
+    u16,
+    u32,
+    u64,
+    u128,
+    usize,
+    f64,
+    String,
+    Duration,
+    Decimal,
+    caip::AccountId,
+    caip::AssetId,
+    caip::ChainId,
+    component::Component,
+    domains::Domain,
+    entities::BtcProviderId,
+    entities::BundlerProviderId,
+    entities::CoordinatorId,
+    entities::EntityId,
+    entities::EthProviderId,
+    entities::NftProviderId,
+    entities::PageId,
+    entities::PriceProviderId,
+    entities::SignerId,
+    entities::SolProviderId,
+    entities::TokenListId,
+    entities::VaultId,
+    addressbook::Contact,
+    btc::HistoryEntry,
+    btc::Utxo,
+    coordinator::EvmBundle,
+    coordinator::Session,
+    coordinator::SimulationResult,
+    erc4337::UserOpRequest,
+    eth::TransactionStatus,
+    fees::FeeSuggestion,
+    host::Capabilities,
+    host::DecodedError,
+    host::EntityCapabilities,
+    host::FetchStreamId,
+    host::NotifyLevel,
+    host::Request,
+    host::Response,
+    host::ResponseHead,
+    host::Toast,
+    host::WsId,
+    host::WsMessage,
+    nft::NftMetadata,
+    notification::Notification,
+    page::PageEvent,
+    plugin::InterruptedRequest,
+    plugin::PluginManifest,
+    sol::AccountInfo,
+    sol::Commitment,
+    sol::LatestBlockhash,
+    state::SetError,
+    state::UnlockError,
+    state::Usage,
+    tokens::TokenInfo,
+);
+
+impl<T: ApiSchema> ApiSchema for Option<T> {
+    fn api_schema(generator: &mut SchemaGenerator) -> Schema {
+        let some = T::api_schema(generator);
+        json_schema!({ "anyOf": [some, { "type": "null" }] })
+    }
+}
+
+impl<T: ApiSchema> ApiSchema for Vec<T> {
+    fn api_schema(generator: &mut SchemaGenerator) -> Schema {
+        let items = T::api_schema(generator);
+        json_schema!({ "type": "array", "items": items })
+    }
+}
+
+impl<T: ApiSchema, E: ApiSchema> ApiSchema for Result<T, E> {
+    fn api_schema(generator: &mut SchemaGenerator) -> Schema {
+        let ok = T::api_schema(generator);
+        let err = E::api_schema(generator);
+        json_schema!({
+            "oneOf": [
+                {
+                    "type": "object",
+                    "properties": { "Ok": ok },
+                    "required": ["Ok"],
+                    "additionalProperties": false,
+                },
+                {
+                    "type": "object",
+                    "properties": { "Err": err },
+                    "required": ["Err"],
+                    "additionalProperties": false,
+                },
+            ]
+        })
+    }
+}
+
+macro_rules! tuples {
+    ($($len:literal => ($($item:ident),+)),* $(,)?) => {
+        $(
+            impl<$($item: ApiSchema),+> ApiSchema for ($($item,)+) {
+                fn api_schema(generator: &mut SchemaGenerator) -> Schema {
+                    let items = [$($item::api_schema(generator)),+];
+                    json_schema!({
+                        "type": "array",
+                        "prefixItems": items,
+                        "minItems": $len,
+                        "maxItems": $len,
+                    })
+                }
+            }
+        )*
+    };
+}
+
+tuples!(
+    2 => (A, B),
+    3 => (A, B, C),
+    4 => (A, B, C, D),
+    5 => (A, B, C, D, E),
+);
+
+/// Schema for alloy's hex-encoded values, e.g. `Address`, `U256`, and
+/// `Bytes`. API types use it for their alloy fields with
+/// `#[schemars(with = "Hex")]`.
+pub struct Hex;
+
+impl JsonSchema for Hex {
+    fn inline_schema() -> bool {
+        true
+    }
+
+    fn schema_name() -> Cow<'static, str> {
+        "Hex".into()
+    }
+
+    fn json_schema(_: &mut SchemaGenerator) -> Schema {
+        json_schema!({ "type": "string", "pattern": "^0x[0-9a-fA-F]*$" })
+    }
+}
+
+/// Schema for alloy's signed integers, like `I256`, which are serialized as
+/// decimal strings.
+pub struct SignedInteger;
+
+impl JsonSchema for SignedInteger {
+    fn inline_schema() -> bool {
+        true
+    }
+
+    fn schema_name() -> Cow<'static, str> {
+        "SignedInteger".into()
+    }
+
+    fn json_schema(_: &mut SchemaGenerator) -> Schema {
+        json_schema!({ "type": "string", "pattern": "^-?[0-9]+$" })
+    }
+}
+
+macro_rules! hex {
+    ($($ty:ty),* $(,)?) => {
+        $(
+            impl ApiSchema for $ty {
+                fn api_schema(generator: &mut SchemaGenerator) -> Schema {
+                    generator.subschema_for::<Hex>()
+                }
+            }
+        )*
+    };
+}
+
+hex!(Address, U256, Bytes);
+
+impl<const N: usize> ApiSchema for FixedBytes<N> {
+    fn api_schema(generator: &mut SchemaGenerator) -> Schema {
+        generator.subschema_for::<Hex>()
+    }
+}
+
+/// Describes alloy's RPC types by name only, since alloy doesn't provide
+/// their schemas.
+macro_rules! opaque {
+    ($($ty:ident),* $(,)?) => {
+        $(
+            impl ApiSchema for $ty {
+                fn api_schema(_: &mut SchemaGenerator) -> Schema {
+                    json_schema!({
+                        "title": stringify!($ty),
+                        "description": concat!(
+                            "Alloy's `", stringify!($ty), "`, serialized as in the Ethereum ",
+                            "JSON-RPC API",
+                        ),
+                    })
+                }
+            }
+        )*
+    };
+}
+
+opaque!(
+    AccessListResult,
+    Block,
+    BlockId,
+    BlockNumberOrTag,
+    BlockOverrides,
+    BlockTransactionsKind,
+    EIP1186AccountProofResponse,
+    FeeHistory,
+    Filter,
+    GethDebugTracingCallOptions,
+    GethDebugTracingOptions,
+    GethTrace,
+    Log,
+    PackedUserOperation,
+    Signature,
+    StateOverride,
+    Transaction,
+    TransactionReceipt,
+    TransactionRequest,
+    UserOperationGasEstimation,
+    UserOperationReceipt,
+);
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::*;
+
+    fn find(methods: &[MethodSchema], name: &str) -> MethodSchema {
+        methods
+            .iter()
+            .find(|m| m.name == name)
+            .unwrap_or_else(|| panic!("{} is listed", name))
+            .clone()
+    }
+
+    /// Collects every `$ref` in `value`.
+    fn refs<'a>(value: &'a Value, found: &mut Vec<&'a str>) {
+        match value {
+            Value::Object(object) => {
+                if let Some(Value::String(reference)) = object.get("$ref") {
+                    found.push(reference);
+                }
+                object.values().for_each(|value| refs(value, found));
+            }
+            Value::Array(items) => items.iter().for_each(|value| refs(value, found)),
+            _ => {}
+        }
+    }
+
+    #[test]
+    fn test_method_names_are_unique() {
+        let methods = methods();
+        let names: HashSet<_> = methods.iter().map(|m| m.name).collect();
+        assert_eq!(names.len(), methods.len());
+    }
+
+    #[test]
+    fn test_methods_are_described() {
+        let methods = methods();
+        assert_eq!(methods[0].name, crate::global::Ping::NAME);
+
+        let ping = find(&methods, crate::global::Ping::NAME);
+        assert_eq!(ping.permission, Permission::Standard);
+        assert_eq!(ping.output, json_schema!({ "type": "string" }));
+
+        let fetch = find(&methods, host::Fetch::NAME);
+        assert_eq!(fetch.permission, Permission::Privileged);
+        assert_eq!(fetch.version, 2);
+        assert_eq!(fetch.params_type, "Request");
+
+        let logs = find(&methods, eth::GetLogs::NAME);
+        assert_eq!(logs.output.get("type"), Some(&json!("array")));
+        find(&methods, page::OnUpdate::NAME);
+    }
+
+    #[test]
+    fn test_open_rpc_refs_resolve() {
+        let doc = open_rpc();
+        assert_eq!(doc["methods"][0]["name"], "tlock_ping");
+        assert_eq!(doc["methods"][0]["result"]["x-rust-type"], "String");
+
+        let mut found = Vec::new();
+        refs(&doc, &mut found);
+        assert!(!found.is_empty());
+        for reference in found {
+            let name = reference
+                .strip_prefix("#/components/schemas/")
+                .unwrap_or_else(|| panic!("{} points into the components", reference));
+            assert!(
+                doc["components"]["schemas"].get(name).is_some(),
+                "{} is defined",
+                reference
+            );
+        }
+    }
+
+    #[test]
+    fn test_alloy_fields_are_hex() {
+        let mut generator = generator();
+        generator.subschema_for::<host::DecodedError>();
+        let decoded = &generator.definitions()["DecodedError"];
+        let custom = decoded["oneOf"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .find_map(|variant| variant["properties"].get("Custom"))
+            .unwrap();
+        assert_eq!(
+            custom["properties"]["selector"]["pattern"],
+            "^0x[0-9a-fA-F]*$"
+        );
+    }
+}
@@ -3,6 +3,7 @@ pub use tracing;
 pub use wasmi_plugin_hdk;
 pub use wasmi_plugin_pdk;
 pub mod server;
+pub mod trace;
 
 #[macro_export]
 macro_rules! __impl_host_rpc_base {
@@ -40,12 +41,16 @@ macro_rules! impl_host_rpc {
             |host: ::std::sync::Arc<$host_ty>,
              instance_id: $crate::wasmi_plugin_hdk::instance_id::InstanceId,
              params: <$method as $crate::tlock_api::RpcMethod>::Params| async move {
-                let span = ::tracing::info_span!(
+                use $crate::tracing::Instrument;
+
+                let trace_id = $crate::trace::TraceId::current_or_new();
+                let span = $crate::tracing::info_span!(
                     <$method>::NAME,
                     plugin = %instance_id.plugin,
+                    trace_id = %trace_id,
                 );
-                let _enter = span.enter();
-                host.$host_fn(&instance_id, params).await
+                let _trace = $crate::trace::register(&span, trace_id);
+                host.$host_fn(&instance_id, params).instrument(span).await
             }
         );
     };
@@ -61,15 +66,18 @@ macro_rules! impl_host_rpc_no_id {
             |host: ::std::sync::Arc<$host_ty>,
              instance_id: $crate::wasmi_plugin_hdk::instance_id::InstanceId,
              params: <$method as $crate::tlock_api::RpcMethod>::Params| async move {
+                use $crate::tracing::Instrument;
+
                 let plugin_id = &instance_id.plugin;
-                let span = ::tracing::info_span!(
+                let trace_id = $crate::trace::TraceId::current_or_new();
+                let span = $crate::tracing::info_span!(
                     <$method>::NAME,
                     plugin = %plugin_id,
+                    trace_id = %trace_id,
                 );
-                let _enter = span.enter();
-                let plugin_name = host.get_plugin(plugin_id).map(|p| p.name().to_string()).unwrap_or("<unknown>".to_string());
-                host.log_event(<$method>::NAME, Some(&plugin_name));
-                host.$host_fn(params).await
+                let _trace = $crate::trace::register(&span, trace_id);
+                host.log_call(<$method>::NAME, *plugin_id);
+                host.$host_fn(params).instrument(span).await
             }
         );
     };
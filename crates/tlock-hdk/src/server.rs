@@ -1,18 +1,61 @@
+use std::sync::Arc;
+
 use serde_json::Value;
-use tlock_api::{RpcMethod, alloy::transports::BoxFuture};
+use tlock_api::{
+    API_VERSION, MIN_API_VERSION, Permission, RpcMethod,
+    alloy::transports::BoxFuture,
+    error::{DomainError, ErrorCode},
+    host::{Capabilities, GetCapabilities, GetTraceId},
+};
 use wasmi_plugin_hdk::{host_handler::HostHandler, instance_id::InstanceId};
 use wasmi_plugin_pdk::{router::MaybeSend, rpc_message::RpcError};
 
+type Guard<S> = Arc<dyn Fn(&S, &InstanceId, &str) -> bool + Send + Sync>;
+type PermissionGuard<S> = Arc<dyn Fn(&S, &InstanceId, &str, Permission) -> bool + Send + Sync>;
+type Middleware<S> = Arc<
+    dyn for<'a> Fn(&'a S, InstanceId, &'a str, Value, Next<'a, S>) -> BoxFuture<'a, RpcResult>
+        + Send
+        + Sync,
+>;
+type RpcResult = Result<Value, RpcError>;
+
 /// Lightweight HostServer wrapper that provides a typed interface for
 /// registering RPC methods from tlock_api.
 pub struct HostServer<S: Clone + Send + Sync + 'static> {
     inner: wasmi_plugin_hdk::server::HostServer<S>,
+    state: S,
+    guard: Option<PermissionGuard<S>>,
+    rate_limit: Option<Guard<S>>,
+    middleware: Vec<Middleware<S>>,
+    methods: Vec<(&'static str, u32, Permission)>,
+}
+
+/// The rest of a call's middleware chain, ending with the method's handler.
+pub struct Next<'a, S: Clone + Send + Sync + 'static> {
+    server: &'a HostServer<S>,
+    instance: InstanceId,
+    method: &'a str,
+    index: usize,
+}
+
+impl<'a, S: Clone + Send + Sync + 'static> Next<'a, S> {
+    /// Passes the call on, with `params`, to the next middleware or the
+    /// handler.
+    pub fn run(self, params: Value) -> BoxFuture<'a, RpcResult> {
+        self.server
+            .dispatch(self.instance, self.method, params, self.index)
+    }
 }
 
 impl<S: Default + Clone + Send + Sync + 'static> Default for HostServer<S> {
     fn default() -> Self {
         Self {
             inner: wasmi_plugin_hdk::server::HostServer::default(),
+            state: S::default(),
+            guard: None,
+            rate_limit: None,
+            middleware: Vec::new(),
+            methods: Vec::new(),
         }
     }
 }
@@ -20,7 +63,12 @@ impl<S: Default + Clone + Send + Sync + 'static> Default for HostServer<S> {
 impl<S: Clone + Send + Sync + 'static> HostServer<S> {
     pub fn new(state: S) -> Self {
         Self {
-            inner: wasmi_plugin_hdk::server::HostServer::new(state),
+            inner: wasmi_plugin_hdk::server::HostServer::new(state.clone()),
+            state,
+            guard: None,
+            rate_limit: None,
+            middleware: Vec::new(),
+            methods: Vec::new(),
         }
     }
 
@@ -31,8 +79,121 @@ impl<S: Clone + Send + Sync + 'static> HostServer<S> {
         Fut: Future<Output = Result<M::Output, RpcError>> + MaybeSend + 'static,
     {
         self.inner = self.inner.with_method(M::NAME, func);
+        self.methods.push((M::NAME, M::VERSION, M::PERMISSION));
+        self
+    }
+
+    /// Sets a guard that is checked before every call. Calls for which the
+    /// guard returns false are rejected without reaching the method handler.
+    ///
+    /// The guard is given the method's declared `Permission`, so its checks
+    /// can't be skipped by forgetting to list a new method. Unknown methods
+    /// are treated as privileged.
+    pub fn with_guard<F>(mut self, guard: F) -> Self
+    where
+        F: Fn(&S, &InstanceId, &str, Permission) -> bool + Send + Sync + 'static,
+    {
+        self.guard = Some(Arc::new(guard));
+        self
+    }
+
+    /// Sets a rate limit that is checked after the guard on every call.
+    /// Calls for which it returns false are rejected with a `RATE_LIMITED`
+    /// error.
+    pub fn with_rate_limit<F>(mut self, rate_limit: F) -> Self
+    where
+        F: Fn(&S, &InstanceId, &str) -> bool + Send + Sync + 'static,
+    {
+        self.rate_limit = Some(Arc::new(rate_limit));
         self
     }
+
+    /// Adds a middleware that wraps every call that passes the guard and rate
+    /// limit. It's given the call's params and the rest of the chain as
+    /// `next`, and can inspect or change either before running `next`, or
+    /// answer without running it. Middleware runs in the order it was added.
+    pub fn with_middleware<F>(mut self, middleware: F) -> Self
+    where
+        F: for<'a> Fn(&'a S, InstanceId, &'a str, Value, Next<'a, S>) -> BoxFuture<'a, RpcResult>
+            + Send
+            + Sync
+            + 'static,
+    {
+        self.middleware.push(Arc::new(middleware));
+        self
+    }
+
+    /// Runs the middleware at `index` onwards, then the method's handler.
+    fn dispatch<'a>(
+        &'a self,
+        instance: InstanceId,
+        method: &'a str,
+        params: Value,
+        index: usize,
+    ) -> BoxFuture<'a, RpcResult> {
+        let Some(middleware) = self.middleware.get(index) else {
+            return self.call(instance, method, params);
+        };
+        let next = Next {
+            server: self,
+            instance,
+            method,
+            index: index + 1,
+        };
+        middleware(&self.state, instance, method, params, next)
+    }
+
+    fn call<'a>(
+        &'a self,
+        instance: InstanceId,
+        method: &'a str,
+        params: Value,
+    ) -> BoxFuture<'a, RpcResult> {
+        if method == GetCapabilities::NAME {
+            let capabilities = serde_json::to_value(self.capabilities())
+                .map_err(|e| RpcError::Custom(e.to_string()));
+            return Box::pin(async move { capabilities });
+        }
+
+        //? Host methods are handled while the plugin call's span is current
+        if method == GetTraceId::NAME {
+            let trace_id = crate::trace::current().map(|id| id.to_string());
+            let trace_id =
+                serde_json::to_value(trace_id).map_err(|e| RpcError::Custom(e.to_string()));
+            return Box::pin(async move { trace_id });
+        }
+
+        self.inner.handle(instance, method, params)
+    }
+
+    /// Returns the permission `method` was declared with.
+    pub fn permission_of(&self, method: &str) -> Permission {
+        //? Methods answered by the server itself aren't registered
+        if method == GetCapabilities::NAME {
+            return GetCapabilities::PERMISSION;
+        }
+        if method == GetTraceId::NAME {
+            return GetTraceId::PERMISSION;
+        }
+        self.methods
+            .iter()
+            .find(|(name, _, _)| *name == method)
+            .map_or(Permission::Privileged, |(_, _, permission)| *permission)
+    }
+
+    /// Returns the API version and registered methods, as served to plugins
+    /// by `host_get_capabilities`.
+    pub fn capabilities(&self) -> Capabilities {
+        Capabilities {
+            api_version: API_VERSION,
+            min_api_version: MIN_API_VERSION,
+            methods: self
+                .methods
+                .iter()
+                .map(|(name, version, _)| (name.to_string(), *version))
+                .collect(),
+        }
+    }
 }
 
 impl<S: Clone + Send + Sync + 'static> HostHandler for HostServer<S> {
@@ -42,6 +203,30 @@ impl<S: Clone + Send + Sync + 'static> HostHandler for HostServer<S> {
         method: &'a str,
         params: Value,
     ) -> BoxFuture<'a, Result<Value, RpcError>> {
-        self.inner.handle(instance, method, params)
+        let permitted = self
+            .guard
+            .as_ref()
+            .is_none_or(|guard| guard(&self.state, &instance, method, self.permission_of(method)));
+        if !permitted {
+            let err = RpcError::Custom(format!(
+                "Method {} not permitted by plugin manifest",
+                method
+            ));
+            return Box::pin(async move { Err(err) });
+        }
+
+        let within_limit = self
+            .rate_limit
+            .as_ref()
+            .is_none_or(|rate_limit| rate_limit(&self.state, &instance, method));
+        if !within_limit {
+            let err = DomainError::new(
+                ErrorCode::RATE_LIMITED,
+                format!("Rate limit exceeded for {}", method),
+            );
+            return Box::pin(async move { Err(err.into()) });
+        }
+
+        self.dispatch(instance, method, params, 0)
     }
 }
@@ -0,0 +1,85 @@
+//! Correlates the spans of nested plugin calls into a single trace.
+//!
+//! A user action often fans out through several plugins, e.g. page →
+//! coordinator → vault → provider. Every call into a plugin and every host
+//! method a plugin calls gets a span carrying a `trace_id`. Spans created
+//! while another traced span is current inherit its trace ID, so filtering on
+//! one ID shows the whole chain.
+//!
+//! Traces are only tracked while a subscriber has enabled the spans, since
+//! disabled spans have no ID to key them by.
+
+use std::{
+    collections::HashMap,
+    fmt,
+    sync::{
+        Mutex,
+        atomic::{AtomicU64, Ordering},
+    },
+};
+
+use tracing::{Span, span};
+
+static NEXT_TRACE_ID: AtomicU64 = AtomicU64::new(1);
+static TRACES: Mutex<Option<HashMap<span::Id, TraceId>>> = Mutex::new(None);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct TraceId(u64);
+
+impl TraceId {
+    /// Returns a trace ID unique within this process.
+    pub fn new() -> Self {
+        Self(NEXT_TRACE_ID.fetch_add(1, Ordering::Relaxed))
+    }
+
+    /// Returns the trace of the current span, or starts a new trace.
+    pub fn current_or_new() -> Self {
+        current().unwrap_or_default()
+    }
+}
+
+impl Default for TraceId {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl fmt::Display for TraceId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:016x}", self.0)
+    }
+}
+
+/// Returns the trace of the current span, if it belongs to one.
+pub fn current() -> Option<TraceId> {
+    let id = Span::current().id()?;
+    let traces = TRACES.lock().unwrap();
+    traces.as_ref()?.get(&id).copied()
+}
+
+/// Associates a span with a trace until the returned guard is dropped.
+/// Spans created while `span` is current will inherit the trace.
+#[must_use]
+pub fn register(span: &Span, trace_id: TraceId) -> TraceGuard {
+    let id = span.id();
+    if let Some(id) = &id {
+        let mut traces = TRACES.lock().unwrap();
+        traces.get_or_insert_default().insert(id.clone(), trace_id);
+    }
+    TraceGuard { id }
+}
+
+pub struct TraceGuard {
+    id: Option<span::Id>,
+}
+
+impl Drop for TraceGuard {
+    fn drop(&mut self) {
+        let Some(id) = &self.id else {
+            return;
+        };
+        if let Some(traces) = TRACES.lock().unwrap().as_mut() {
+            traces.remove(id);
+        }
+    }
+}
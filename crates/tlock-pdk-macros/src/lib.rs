@@ -0,0 +1,185 @@
+//! Derive macros re-exported by `tlock-pdk`.
+
+use proc_macro::TokenStream;
+use quote::{ToTokens, quote};
+use syn::{
+    DeriveInput, Generics, Ident, ImplItem, Item, ItemImpl, LitInt, LitStr, Path, Token,
+    parse_macro_input, punctuated::Punctuated,
+};
+
+/// Derives `tlock_pdk::versioned::PluginState`.
+///
+/// ```ignore
+/// #[derive(Serialize, Deserialize, PluginState)]
+/// #[plugin_state(key = "settings", version = 2, previous = SettingsV1)]
+/// struct Settings { ... }
+///
+/// impl From<SettingsV1> for Settings { ... }
+/// ```
+///
+/// `key` defaults to the plugin's main state key and `version` to 1. With
+/// `previous`, state stored at an older version is decoded as that type and
+/// converted with `From`, recursively down to the first version.
+#[proc_macro_derive(PluginState, attributes(plugin_state))]
+pub fn derive_plugin_state(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    match plugin_state(&input) {
+        Ok(tokens) => tokens.into(),
+        Err(err) => err.to_compile_error().into(),
+    }
+}
+
+fn plugin_state(input: &DeriveInput) -> syn::Result<proc_macro2::TokenStream> {
+    let mut key = String::new();
+    let mut version: u32 = 1;
+    let mut previous: Option<Path> = None;
+    for attr in input
+        .attrs
+        .iter()
+        .filter(|a| a.path().is_ident("plugin_state"))
+    {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("key") {
+                key = meta.value()?.parse::<LitStr>()?.value();
+            } else if meta.path.is_ident("version") {
+                version = meta.value()?.parse::<LitInt>()?.base10_parse()?;
+            } else if meta.path.is_ident("previous") {
+                previous = Some(meta.value()?.parse()?);
+            } else {
+                return Err(meta.error("expected `key`, `version`, or `previous`"));
+            }
+            Ok(())
+        })?;
+    }
+
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    let migrate = previous.map(|previous| {
+        quote! {
+            fn migrate(
+                version: u32,
+                state: ::tlock_pdk::versioned::Value,
+            ) -> ::std::result::Result<Self, ::tlock_pdk::state::LockError> {
+                ::tlock_pdk::versioned::migrate_from::<#previous, Self>(version, state)
+            }
+        }
+    });
+
+    let plugin_state = quote!(::tlock_pdk::versioned::PluginState);
+    Ok(quote! {
+        impl #impl_generics #plugin_state for #name #ty_generics #where_clause {
+            const KEY: &'static str = #key;
+            const VERSION: u32 = #version;
+            #migrate
+        }
+    })
+}
+
+/// Builds a plugin's `PluginRunner` from its impls of the
+/// `tlock_pdk::handlers` traits.
+///
+/// ```ignore
+/// #[tlock_plugin(LifecyclePlugin, VaultPlugin)]
+/// struct MyVault;
+///
+/// #[tlock_plugin]
+/// impl VaultPlugin for MyVault {
+///     async fn get_assets(transport: Transport, id: VaultId) -> Result<..., RpcError> { ... }
+/// }
+///
+/// fn main() {
+///     MyVault::runner().run();
+/// }
+/// ```
+///
+/// On a trait impl, each fn the impl defines is registered as the handler
+/// for the trait's method of that name. On the plugin's type, the macro
+/// lists the traits it implements and generates `runner()`, which registers
+/// the handlers of each.
+#[proc_macro_attribute]
+pub fn tlock_plugin(attr: TokenStream, item: TokenStream) -> TokenStream {
+    let result = match parse_macro_input!(item as Item) {
+        Item::Impl(input) if attr.is_empty() => handlers_impl(input),
+        Item::Impl(input) => Err(syn::Error::new_spanned(
+            input.impl_token,
+            "`tlock_plugin` takes no arguments on an impl",
+        )),
+        Item::Struct(input) => {
+            let traits =
+                parse_macro_input!(attr with Punctuated::<Path, Token![,]>::parse_terminated);
+            Ok(runner_impl(
+                &input.ident,
+                &input.generics,
+                traits,
+                input.to_token_stream(),
+            ))
+        }
+        Item::Enum(input) => {
+            let traits =
+                parse_macro_input!(attr with Punctuated::<Path, Token![,]>::parse_terminated);
+            Ok(runner_impl(
+                &input.ident,
+                &input.generics,
+                traits,
+                input.to_token_stream(),
+            ))
+        }
+        item => Err(syn::Error::new_spanned(
+            item,
+            "expected a trait impl, or the plugin's struct or enum",
+        )),
+    };
+    match result {
+        Ok(tokens) => tokens.into(),
+        Err(err) => err.to_compile_error().into(),
+    }
+}
+
+fn handlers_impl(mut input: ItemImpl) -> syn::Result<proc_macro2::TokenStream> {
+    let Some((_, trait_path, _)) = input.trait_.clone() else {
+        return Err(syn::Error::new_spanned(
+            input.self_ty,
+            "expected `impl Trait for Type`, with a trait from `tlock_pdk::handlers`",
+        ));
+    };
+    let defined: Vec<String> = input
+        .items
+        .iter()
+        .filter_map(|item| match item {
+            ImplItem::Fn(func) => Some(func.sig.ident.to_string()),
+            _ => None,
+        })
+        .collect();
+
+    let runner = quote!(::tlock_pdk::runner::PluginRunner);
+    input.items.push(syn::parse_quote! {
+        fn register(runner: #runner) -> #runner {
+            <Self as #trait_path>::register_defined(runner, &[#(#defined),*])
+        }
+    });
+    Ok(input.to_token_stream())
+}
+
+fn runner_impl(
+    name: &Ident,
+    generics: &Generics,
+    traits: Punctuated<Path, Token![,]>,
+    item: proc_macro2::TokenStream,
+) -> proc_macro2::TokenStream {
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    let runner = traits.iter().fold(
+        quote!(::tlock_pdk::runner::PluginRunner::new()),
+        |runner, trait_path| quote!(<Self as #trait_path>::register(#runner)),
+    );
+    quote! {
+        #item
+
+        impl #impl_generics #name #ty_generics #where_clause {
+            /// A runner with the handlers of every trait this plugin
+            /// implements registered.
+            pub fn runner() -> ::tlock_pdk::runner::PluginRunner {
+                #runner
+            }
+        }
+    }
+}
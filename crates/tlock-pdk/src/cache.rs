@@ -0,0 +1,30 @@
+//! Request-scoped cache of raw state values.
+//!
+//! `StateHandle` reads go through here, so a handler that reads the same key
+//! several times only asks the host once. Writes through the PDK update the
+//! cached value. `PluginRunner` clears the cache at the start of every call,
+//! so values never outlive the request that read them. Writes made by other
+//! instances during a request aren't seen until the next one; lock the key
+//! to read its latest value.
+
+use std::{cell::RefCell, collections::HashMap};
+
+thread_local! {
+    static CACHE: RefCell<HashMap<String, Vec<u8>>> = RefCell::new(HashMap::new());
+}
+
+pub(crate) fn get(key: &str) -> Option<Vec<u8>> {
+    CACHE.with(|cache| cache.borrow().get(key).cloned())
+}
+
+pub(crate) fn insert(key: &str, data: Vec<u8>) {
+    CACHE.with(|cache| cache.borrow_mut().insert(key.to_string(), data));
+}
+
+pub(crate) fn remove(key: &str) {
+    CACHE.with(|cache| cache.borrow_mut().remove(key));
+}
+
+pub(crate) fn clear() {
+    CACHE.with(|cache| cache.borrow_mut().clear());
+}
@@ -0,0 +1,48 @@
+//! Shared implementation of `fees_suggest` for EthProviders.
+//!
+//! Providers fetch `eth_feeHistory` for `FEE_HISTORY_BLOCKS` blocks with
+//! `REWARD_PERCENTILES` and pass the result to `suggest`.
+use tlock_api::{
+    alloy::rpc::types::FeeHistory,
+    fees::{FeeEstimate, FeeSuggestion},
+};
+
+/// Number of recent blocks to sample priority fees from.
+pub const FEE_HISTORY_BLOCKS: u64 = 10;
+
+/// Priority fee percentiles for the slow, normal, and fast tiers.
+pub const REWARD_PERCENTILES: [f64; 3] = [10.0, 50.0, 90.0];
+
+/// Computes a fee suggestion from a fee history fetched with
+/// `REWARD_PERCENTILES`. Returns None if the history is missing the next
+/// block's base fee or the priority fee rewards.
+///
+/// Each tier's priority fee is the median across blocks of that tier's reward
+/// percentile. The max fee allows for the base fee doubling before inclusion.
+pub fn suggest(history: &FeeHistory) -> Option<FeeSuggestion> {
+    let base_fee = *history.base_fee_per_gas.last()?;
+    let rewards = history.reward.as_ref()?;
+    if rewards.is_empty() {
+        return None;
+    }
+
+    let estimate = |tier: usize| -> Option<FeeEstimate> {
+        let mut tier_rewards = rewards
+            .iter()
+            .map(|block| block.get(tier).copied())
+            .collect::<Option<Vec<u128>>>()?;
+        tier_rewards.sort_unstable();
+        let priority_fee = tier_rewards[tier_rewards.len() / 2];
+
+        Some(FeeEstimate {
+            max_fee_per_gas: base_fee.saturating_mul(2).saturating_add(priority_fee),
+            max_priority_fee_per_gas: priority_fee,
+        })
+    };
+
+    Some(FeeSuggestion {
+        slow: estimate(0)?,
+        normal: estimate(1)?,
+        fast: estimate(2)?,
+    })
+}
@@ -0,0 +1,203 @@
+//! Traits grouping the methods a plugin can serve by namespace, for use with
+//! `#[tlock_plugin]`.
+//!
+//! Each trait has one fn per method in its namespace, typed by the method's
+//! params and output. Plugins implement the fns for the methods they serve
+//! and leave the rest out; `#[tlock_plugin]` only registers the ones the
+//! impl defines, so the runner answers the others with `MethodNotFound`.
+
+use tlock_api::{
+    RpcMethod, btc, coordinator, erc4337, eth, fees, global, nft, page, plugin, price, signer, sol,
+    tokens, trace, vault,
+};
+use wasmi_plugin_pdk::{router::MaybeSend, rpc_message::RpcError, transport::Transport};
+
+use crate::runner::PluginRunner;
+
+macro_rules! handlers {
+    (
+        $(#[$meta:meta])*
+        $trait_name:ident { $($method:path => $func:ident),* $(,)? }
+    ) => {
+        $(#[$meta])*
+        pub trait $trait_name: Sized + 'static {
+            $(
+                #[doc = concat!("Handles `", stringify!($method), "`.")]
+                fn $func(
+                    _: Transport,
+                    _: <$method as RpcMethod>::Params,
+                ) -> impl Future<Output = Result<<$method as RpcMethod>::Output, RpcError>>
+                       + MaybeSend
+                       + 'static {
+                    async { Err(RpcError::MethodNotFound) }
+                }
+            )*
+
+            /// Registers the handlers this impl defines. Generated by
+            /// `#[tlock_plugin]`.
+            #[doc(hidden)]
+            fn register(runner: PluginRunner) -> PluginRunner {
+                runner
+            }
+
+            /// Registers the handlers named in `defined`.
+            #[doc(hidden)]
+            fn register_defined(mut runner: PluginRunner, defined: &[&str]) -> PluginRunner {
+                $(
+                    if defined.contains(&stringify!($func)) {
+                        runner = runner.with_method($method, Self::$func);
+                    }
+                )*
+                runner
+            }
+        }
+    };
+}
+
+handlers!(
+    /// `tlock_ping`, served by every plugin that wants to answer it.
+    GlobalPlugin {
+        global::Ping => ping,
+    }
+);
+
+handlers!(
+    /// The `plugin_*` methods the host calls over a plugin's lifetime.
+    /// `plugin_get_api_version` is answered by `PluginRunner` itself.
+    LifecyclePlugin {
+        plugin::GetManifest => get_manifest,
+        plugin::Init => init,
+        plugin::Migrate => migrate,
+        plugin::Shutdown => shutdown,
+        plugin::OnChainChanged => on_chain_changed,
+        plugin::OnAlarm => on_alarm,
+        plugin::OnTick => on_tick,
+        plugin::OnEvent => on_event,
+        plugin::OnTransactionStatus => on_transaction_status,
+        plugin::OnRequestInterrupted => on_request_interrupted,
+        plugin::OnWsMessage => on_ws_message,
+        plugin::OnWsClosed => on_ws_closed,
+    }
+);
+
+handlers!(
+    PagePlugin {
+        page::OnLoad => on_load,
+        page::OnUpdate => on_update,
+    }
+);
+
+handlers!(
+    VaultPlugin {
+        vault::GetAssets => get_assets,
+        vault::Withdraw => withdraw,
+        vault::GetDepositAddress => get_deposit_address,
+        vault::OnDepositDetected => on_deposit_detected,
+    }
+);
+
+handlers!(
+    CoordinatorPlugin {
+        coordinator::GetSession => get_session,
+        coordinator::CancelSession => cancel_session,
+        coordinator::GetAssets => get_assets,
+        coordinator::Propose => propose,
+        coordinator::Simulate => simulate,
+        coordinator::ProposeMultichain => propose_multichain,
+    }
+);
+
+handlers!(
+    EthProviderPlugin {
+        eth::BlockNumber => block_number,
+        eth::ChainId => chain_id,
+        eth::Call => call,
+        eth::GasPrice => gas_price,
+        eth::GetBalance => get_balance,
+        eth::GetBlock => get_block,
+        eth::GetBlockReceipts => get_block_receipts,
+        eth::GetLogs => get_logs,
+        eth::GetCode => get_code,
+        eth::GetStorageAt => get_storage_at,
+        eth::GetProof => get_proof,
+        eth::MaxPriorityFeePerGas => max_priority_fee_per_gas,
+        eth::BlobBaseFee => blob_base_fee,
+        eth::FeeHistory => fee_history,
+        eth::GetTransactionByHash => get_transaction_by_hash,
+        eth::GetTransactionReceipt => get_transaction_receipt,
+        eth::GetTransactionCount => get_transaction_count,
+        eth::EstimateGas => estimate_gas,
+        eth::CreateAccessList => create_access_list,
+        eth::SendRawTransaction => send_raw_transaction,
+    }
+);
+
+handlers!(
+    /// The `debug_*` tracing methods, served by EthProviders.
+    TracePlugin {
+        trace::TraceTransaction => trace_transaction,
+        trace::TraceCall => trace_call,
+    }
+);
+
+handlers!(
+    /// Fee suggestions, served by EthProviders.
+    FeesPlugin {
+        fees::Suggest => suggest,
+    }
+);
+
+handlers!(
+    SolProviderPlugin {
+        sol::GetBalance => get_balance,
+        sol::GetAccountInfo => get_account_info,
+        sol::GetLatestBlockhash => get_latest_blockhash,
+        sol::SendTransaction => send_transaction,
+    }
+);
+
+handlers!(
+    BtcProviderPlugin {
+        btc::GetUtxos => get_utxos,
+        btc::BroadcastTx => broadcast_tx,
+        btc::EstimateFee => estimate_fee,
+        btc::GetAddressHistory => get_address_history,
+    }
+);
+
+handlers!(
+    PriceProviderPlugin {
+        price::Get => get,
+        price::GetMany => get_many,
+    }
+);
+
+handlers!(
+    BundlerProviderPlugin {
+        erc4337::BuildUserOp => build_user_op,
+        erc4337::EstimateUserOpGas => estimate_user_op_gas,
+        erc4337::SendUserOp => send_user_op,
+        erc4337::GetUserOpReceipt => get_user_op_receipt,
+    }
+);
+
+handlers!(
+    TokenListPlugin {
+        tokens::GetList => get_list,
+        tokens::Search => search,
+    }
+);
+
+handlers!(
+    NftProviderPlugin {
+        nft::GetOwned => get_owned,
+        nft::GetMetadata => get_metadata,
+    }
+);
+
+handlers!(
+    SignerPlugin {
+        signer::Sign => sign,
+        signer::GetAddress => get_address,
+    }
+);
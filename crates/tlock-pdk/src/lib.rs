@@ -2,5 +2,14 @@ pub use async_trait;
 pub use futures;
 pub use tlock_api;
 pub use wasmi_plugin_pdk;
+mod cache;
+pub mod fees;
+pub mod handlers;
+pub mod retry;
 pub mod runner;
 pub mod state;
+pub mod time;
+pub mod trace;
+pub mod versioned;
+
+pub use tlock_pdk_macros::{PluginState, tlock_plugin};
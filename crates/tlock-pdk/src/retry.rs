@@ -0,0 +1,141 @@
+//! Retries for flaky host and provider calls, with exponential backoff and
+//! jitter between attempts.
+
+use std::{
+    collections::hash_map::RandomState,
+    hash::{BuildHasher, Hasher},
+    time::Duration,
+};
+
+use tlock_api::error::{DomainError, ErrorCode};
+use wasmi_plugin_pdk::{rpc_message::RpcError, transport::Transport};
+
+use crate::time;
+
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    /// Most attempts made, including the first
+    pub max_attempts: u32,
+    /// Delay before the first retry. Doubles after each further attempt.
+    pub initial_delay: Duration,
+    /// Longest delay between two attempts
+    pub max_delay: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 4,
+            initial_delay: Duration::from_millis(250),
+            max_delay: Duration::from_secs(5),
+        }
+    }
+}
+
+impl RetryPolicy {
+    pub fn new(max_attempts: u32) -> Self {
+        Self {
+            max_attempts,
+            ..Self::default()
+        }
+    }
+
+    pub fn with_initial_delay(mut self, delay: Duration) -> Self {
+        self.initial_delay = delay;
+        self
+    }
+
+    pub fn with_max_delay(mut self, delay: Duration) -> Self {
+        self.max_delay = delay;
+        self
+    }
+
+    /// Delay after the given failed attempt, counting from 1. Somewhere
+    /// between half and all of the backoff, so plugins retrying the same
+    /// failure don't all retry together.
+    fn delay(&self, attempt: u32) -> Duration {
+        let factor = 2u32.saturating_pow(attempt.saturating_sub(1));
+        let backoff = self
+            .initial_delay
+            .saturating_mul(factor)
+            .min(self.max_delay);
+        backoff.mul_f64(0.5 + jitter() * 0.5)
+    }
+}
+
+/// Whether a failed call may succeed if made again. Only timeouts and rate
+/// limits are; other errors, like invalid params or insufficient funds,
+/// would fail the same way again.
+pub fn is_retryable(err: &RpcError) -> bool {
+    matches!(
+        DomainError::code_of(err),
+        Some(ErrorCode::TIMEOUT | ErrorCode::RATE_LIMITED)
+    )
+}
+
+/// Calls `f` until it succeeds, fails with an error that isn't
+/// `is_retryable`, or runs out of attempts, returning its last result.
+///
+/// ```ignore
+/// let block = retry(transport.clone(), &RetryPolicy::default(), || {
+///     eth::BlockNumber.call_async(transport.clone(), provider_id)
+/// })
+/// .await?;
+/// ```
+///
+/// Only retry calls that are safe to repeat. A call that timed out may still
+/// have taken effect.
+pub async fn retry<T, F, Fut>(
+    transport: Transport,
+    policy: &RetryPolicy,
+    f: F,
+) -> Result<T, RpcError>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T, RpcError>>,
+{
+    let should_retry = |result: &Result<T, RpcError>| match result {
+        Ok(_) => false,
+        Err(err) => is_retryable(err),
+    };
+    retry_when(transport, policy, should_retry, f).await
+}
+
+/// Like `retry`, but calls `f` again whenever `should_retry` approves of its
+/// result. Useful when the failure is in the output, like a 503 response.
+pub async fn retry_when<T, F, Fut>(
+    transport: Transport,
+    policy: &RetryPolicy,
+    should_retry: impl Fn(&T) -> bool,
+    mut f: F,
+) -> T
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = T>,
+{
+    let mut attempt = 1;
+    loop {
+        let result = f().await;
+        if attempt >= policy.max_attempts || !should_retry(&result) {
+            return result;
+        }
+
+        //? Without a host timer retrying immediately would only hammer the
+        //? failing service, so give up instead
+        if time::sleep(transport.clone(), policy.delay(attempt))
+            .await
+            .is_err()
+        {
+            return result;
+        }
+        attempt += 1;
+    }
+}
+
+/// Returns a random fraction in `[0, 1)`.
+fn jitter() -> f64 {
+    //? `RandomState` is seeded from WASI's random source, which saves pulling
+    //? in an RNG
+    let bits = RandomState::new().build_hasher().finish() >> 11;
+    bits as f64 / (1u64 << 53) as f64
+}
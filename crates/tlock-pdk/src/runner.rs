@@ -1,6 +1,9 @@
-use tlock_api::RpcMethod;
+use tlock_api::{API_VERSION, RpcMethod, plugin};
+use tracing::Instrument;
 use wasmi_plugin_pdk::{router::MaybeSend, rpc_message::RpcError, transport::Transport};
 
+use crate::{cache, trace};
+
 /// Lightweight Runner wrapper that provides a typed interface for registering
 /// RPC methods from tlock_api.
 pub struct PluginRunner {
@@ -8,23 +11,49 @@ pub struct PluginRunner {
 }
 
 impl PluginRunner {
+    /// Creates a new runner. The runner answers `plugin_get_api_version`
+    /// with the API version this crate was built against.
     pub fn new() -> Self {
-        Self {
+        let runner = Self {
             inner: wasmi_plugin_pdk::runner::PluginRunner::new(),
-        }
+        };
+        runner.with_method(plugin::GetApiVersion, get_api_version)
     }
 
+    // TODO: Add a `with_streaming_method` whose handler yields its result in
+    // chunks, with a matching `call_streaming`, once the framework's transport
+    // has a streaming frame type.
+    /// Registers a handler for `M`. Each call runs inside a span tagged with
+    /// the host's trace ID for it, and starts with an empty state cache.
     pub fn with_method<M, F, Fut>(mut self, _: M, func: F) -> Self
     where
         M: RpcMethod + 'static,
         F: Fn(Transport, M::Params) -> Fut + Send + Sync + 'static,
         Fut: Future<Output = Result<M::Output, RpcError>> + MaybeSend + 'static,
     {
-        self.inner = self.inner.with_method(M::NAME, func);
+        let func = std::sync::Arc::new(func);
+        self.inner = self
+            .inner
+            .with_method(M::NAME, move |transport: Transport, params| {
+                let func = func.clone();
+                async move {
+                    cache::clear();
+                    let span = trace::call_span(transport.clone(), M::NAME).await;
+                    func(transport, params).instrument(span).await
+                }
+            });
         self
     }
 
+    // TODO: Serve requests concurrently once the framework can frame many of
+    // them over one instance's stdin: match responses to requests by ID and
+    // spawn a task per request, with its own state cache, so a slow call
+    // doesn't hold up unrelated ones.
     pub fn run(self) {
         self.inner.run()
     }
 }
+
+async fn get_api_version(_transport: Transport, _params: ()) -> Result<u32, RpcError> {
+    Ok(API_VERSION)
+}
@@ -13,6 +13,11 @@ use tlock_api::{
 use tracing::error;
 use wasmi_plugin_pdk::{rpc_message::RpcError, transport::SyncTransport};
 
+use crate::{
+    cache,
+    versioned::{self, PluginState},
+};
+
 #[derive(Debug, Error)]
 pub enum LockError {
     #[error("RPC error: {0}")]
@@ -23,6 +28,8 @@ pub enum LockError {
     Empty(String),
     #[error("Set key error: {0}")]
     SetError(#[from] SetError),
+    #[error("Unsupported state version: {0}")]
+    UnsupportedVersion(u32),
 }
 
 impl From<LockError> for RpcError {
@@ -121,7 +128,7 @@ where
         key: impl Into<String>,
     ) -> Result<V, LockError> {
         let key = key.into();
-        let data = state::ReadKey.call(self.transport.clone(), key.clone())?;
+        let data = self.read_raw(&key)?;
         if data.is_empty() {
             return Err(LockError::Empty(key));
         }
@@ -138,7 +145,7 @@ where
         default: impl FnOnce() -> V,
     ) -> Result<V, LockError> {
         let key = key.into();
-        let data = state::ReadKey.call(self.transport.clone(), key.clone())?;
+        let data = self.read_raw(&key)?;
         if data.is_empty() {
             return Ok(default());
         }
@@ -164,11 +171,81 @@ where
         //? Lock the key so the host lets us write to it
         let (_guard, _data) = LockGuard::acquire(self.transport.clone(), key.clone())?;
         let data = serde_json::to_vec(&value)?;
-        state::SetKey.call(self.transport.clone(), (key.clone(), data))??;
+        set_key(self.transport.clone(), &key, data)?;
         let _ = state::UnlockKey.call(self.transport.clone(), key)?;
         Ok(())
     }
 
+    /// Reads a versioned state from `V::KEY`, migrating it if it was stored by
+    /// an older version. Returns None if the key is empty.
+    pub fn load<V: PluginState>(&self) -> Result<Option<V>, LockError> {
+        self.load_key(V::KEY)
+    }
+
+    /// Like `load`, but reads from `key` instead of `V::KEY`.
+    pub fn load_key<V: PluginState>(&self, key: impl Into<String>) -> Result<Option<V>, LockError> {
+        let data = self.read_raw(&key.into())?;
+        if data.is_empty() {
+            return Ok(None);
+        }
+        versioned::decode(&data).map(Some)
+    }
+
+    /// Writes a versioned state to `V::KEY`, tagged with `V::VERSION`. The same
+    /// caveats as `write_key` apply.
+    pub fn save<V: PluginState>(&self, value: &V) -> Result<(), LockError> {
+        self.save_key(V::KEY, value)
+    }
+
+    /// Like `save`, but writes to `key` instead of `V::KEY`.
+    pub fn save_key<V: PluginState>(
+        &self,
+        key: impl Into<String>,
+        value: &V,
+    ) -> Result<(), LockError> {
+        let key = key.into();
+
+        //? Lock the key so the host lets us write to it
+        let (_guard, _data) = LockGuard::acquire(self.transport.clone(), key.clone())?;
+        let data = versioned::encode(value)?;
+        set_key(self.transport.clone(), &key, data)?;
+        let _ = state::UnlockKey.call(self.transport.clone(), key)?;
+        Ok(())
+    }
+
+    /// Deletes the state at `key`, waiting for any other instance holding its
+    /// lock to release it first.
+    pub fn delete_key(&self, key: impl Into<String>) -> Result<(), LockError> {
+        let key = key.into();
+
+        let (_guard, _data) = LockGuard::acquire(self.transport.clone(), key.clone())?;
+        cache::remove(&key);
+        state::DeleteKey.call(self.transport.clone(), key.clone())??;
+        cache::insert(&key, Vec::new());
+        Ok(())
+    }
+
+    /// Reads the raw value at `key`, from the request's cache if it's been read
+    /// or written already.
+    fn read_raw(&self, key: &str) -> Result<Vec<u8>, LockError> {
+        if let Some(data) = cache::get(key) {
+            return Ok(data);
+        }
+        let data = state::ReadKey.call(self.transport.clone(), key.to_string())?;
+        cache::insert(key, data.clone());
+        Ok(data)
+    }
+
+    /// Lists all keys starting with `prefix`, in lexicographic order.
+    pub fn list_keys(&self, prefix: impl Into<String>) -> Result<Vec<String>, LockError> {
+        Ok(state::ListKeys.call(self.transport.clone(), prefix.into())?)
+    }
+
+    /// Returns the plugin's state usage and quota, in bytes.
+    pub fn usage(&self) -> Result<state::Usage, LockError> {
+        Ok(state::GetUsage.call(self.transport.clone(), ())?)
+    }
+
     /// Lock the key, initializing with default if empty.
     pub fn lock_key<V: Serialize + DeserializeOwned + Default>(
         &self,
@@ -297,6 +374,7 @@ where
 {
     pub fn acquire(transport: T, key: String) -> Result<(Self, Vec<u8>), LockError> {
         let data = state::LockKey.call(transport.clone(), key.clone())?;
+        cache::insert(&key, data.clone());
         Ok((
             Self {
                 transport,
@@ -308,22 +386,9 @@ where
     }
 
     pub fn set(&self, data: Vec<u8>) {
-        let result = state::SetKey.call(self.transport.clone(), (self.key.clone(), data));
-        let result = match result {
-            Ok(res) => res,
-            Err(err) => {
-                error!("Failed to set key '{}': {}", self.key, err);
-                return;
-            }
-        };
-
-        match result {
-            Ok(_) => {}
-            Err(err) => {
-                error!("Failed to set key '{}': {}", self.key, err);
-                return;
-            }
-        };
+        if let Err(err) = set_key(self.transport.clone(), &self.key, data) {
+            error!("Failed to set key '{}': {}", self.key, err);
+        }
     }
 }
 
@@ -336,3 +401,16 @@ where
         let _ = state::UnlockKey.call(self.transport.clone(), self.key.clone());
     }
 }
+
+/// Writes `data` to `key`, keeping the request's cache in step. The cached
+/// value is dropped first, so a failed write isn't mistaken for a stored one.
+fn set_key<T, E>(transport: T, key: &str, data: Vec<u8>) -> Result<(), LockError>
+where
+    T: SyncTransport<E> + Clone,
+    E: Into<RpcError>,
+{
+    cache::remove(key);
+    state::SetKey.call(transport, (key.to_string(), data.clone()))??;
+    cache::insert(key, data);
+    Ok(())
+}
@@ -0,0 +1,30 @@
+//! Timers for plugins. wasm32-wasip1 has no timer a plugin can await, so
+//! sleeping is delegated to the host through `host_sleep`.
+
+use std::{pin::pin, time::Duration};
+
+use futures::future::{self, Either};
+use tlock_api::{RpcMethod, host};
+use wasmi_plugin_pdk::{rpc_message::RpcError, transport::Transport};
+
+/// Waits for `duration` without blocking the plugin's other tasks.
+pub async fn sleep(transport: Transport, duration: Duration) -> Result<(), RpcError> {
+    host::Sleep.call_async(transport, duration).await
+}
+
+/// Runs `fut` to completion, or returns None if it doesn't finish within
+/// `duration`.
+pub async fn timeout<F: Future>(
+    transport: Transport,
+    duration: Duration,
+    fut: F,
+) -> Option<F::Output> {
+    let fut = pin!(fut);
+    let timer = pin!(sleep(transport, duration));
+    match future::select(fut, timer).await {
+        Either::Left((output, _)) => Some(output),
+        Either::Right((Ok(()), _)) => None,
+        //? Without a timer there's no deadline to enforce, so just wait
+        Either::Right((Err(_), fut)) => Some(fut.await),
+    }
+}
@@ -0,0 +1,31 @@
+//! Links the spans a plugin emits to the host's trace of the call.
+//!
+//! The host tags each call into a plugin with a trace ID, shared by every
+//! nested call made for the same user action. `PluginRunner` fetches it at
+//! the start of each call and runs the handler inside a span carrying it, so
+//! the plugin's own events can be filtered by the same ID as the host's.
+
+use tlock_api::{RpcMethod, host};
+use tracing::{Level, Span};
+use wasmi_plugin_pdk::transport::Transport;
+
+/// Returns the host's trace ID for the call being handled, if it has one.
+pub async fn trace_id(transport: Transport) -> Option<String> {
+    host::GetTraceId
+        .call_async(transport, ())
+        .await
+        .ok()
+        .flatten()
+}
+
+/// Returns a span for a call to `method`, tagged with the host's trace ID.
+pub async fn call_span(transport: Transport, method: &'static str) -> Span {
+    //? Skip the round trip when the span wouldn't be recorded anyway
+    if !tracing::enabled!(Level::INFO) {
+        return Span::none();
+    }
+    match trace_id(transport).await {
+        Some(trace_id) => tracing::info_span!("call", method, trace_id = %trace_id),
+        None => tracing::info_span!("call", method),
+    }
+}
@@ -0,0 +1,193 @@
+//! Plugin state tagged with a schema version, so older stored state can be
+//! migrated forward instead of failing to deserialize after a struct change.
+//!
+//! Values are stored as `{ "version": N, "state": ... }`. State written
+//! before it was versioned counts as version 1. Implement `PluginState` with
+//! `#[derive(PluginState)]` and read and write it with `StateHandle::load`
+//! and `StateHandle::save`.
+
+use std::cmp::Ordering;
+
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
+pub use serde_json::Value;
+
+use crate::state::LockError;
+
+pub trait PluginState: Serialize + DeserializeOwned {
+    /// The state key the value is stored under.
+    const KEY: &'static str;
+    /// The current schema version. Bump it, and keep the old struct as the
+    /// `previous` version, whenever the struct changes incompatibly.
+    const VERSION: u32;
+
+    /// Decodes state stored at an older `version`, migrating it to this one.
+    fn migrate(version: u32, _state: Value) -> Result<Self, LockError> {
+        Err(LockError::UnsupportedVersion(version))
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Versioned<T> {
+    version: u32,
+    state: T,
+}
+
+pub(crate) fn encode<V: PluginState>(value: &V) -> Result<Vec<u8>, LockError> {
+    let versioned = Versioned {
+        version: V::VERSION,
+        state: value,
+    };
+    Ok(serde_json::to_vec(&versioned)?)
+}
+
+pub(crate) fn decode<V: PluginState>(data: &[u8]) -> Result<V, LockError> {
+    let value: Value = serde_json::from_slice(data)?;
+    let (version, state) = match serde_json::from_value::<Versioned<Value>>(value.clone()) {
+        Ok(versioned) => (versioned.version, versioned.state),
+        Err(_) => (1, value),
+    };
+    match version.cmp(&V::VERSION) {
+        Ordering::Equal => Ok(serde_json::from_value(state)?),
+        Ordering::Less => V::migrate(version, state),
+        Ordering::Greater => Err(LockError::UnsupportedVersion(version)),
+    }
+}
+
+/// Decodes state stored at `version` as `P`, migrating it first if it's
+/// older still, then converts it to `V`. Called by the `migrate` that
+/// `#[derive(PluginState)]` generates for `previous = P`.
+pub fn migrate_from<P, V>(version: u32, state: Value) -> Result<V, LockError>
+where
+    P: PluginState,
+    V: From<P>,
+{
+    let previous = match version.cmp(&P::VERSION) {
+        Ordering::Equal => serde_json::from_value(state)?,
+        Ordering::Less => P::migrate(version, state)?,
+        Ordering::Greater => return Err(LockError::UnsupportedVersion(version)),
+    };
+    Ok(V::from(previous))
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct CounterV1 {
+        count: u32,
+    }
+
+    impl PluginState for CounterV1 {
+        const KEY: &'static str = "counter";
+        const VERSION: u32 = 1;
+    }
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct CounterV2 {
+        count: u64,
+    }
+
+    impl PluginState for CounterV2 {
+        const KEY: &'static str = "counter";
+        const VERSION: u32 = 2;
+
+        fn migrate(version: u32, state: Value) -> Result<Self, LockError> {
+            migrate_from::<CounterV1, Self>(version, state)
+        }
+    }
+
+    impl From<CounterV1> for CounterV2 {
+        fn from(v1: CounterV1) -> Self {
+            Self {
+                count: v1.count.into(),
+            }
+        }
+    }
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct CounterV3 {
+        count: u64,
+        label: String,
+    }
+
+    impl PluginState for CounterV3 {
+        const KEY: &'static str = "counter";
+        const VERSION: u32 = 3;
+
+        fn migrate(version: u32, state: Value) -> Result<Self, LockError> {
+            migrate_from::<CounterV2, Self>(version, state)
+        }
+    }
+
+    impl From<CounterV2> for CounterV3 {
+        fn from(v2: CounterV2) -> Self {
+            Self {
+                count: v2.count,
+                label: "counter".to_string(),
+            }
+        }
+    }
+
+    fn bytes(value: Value) -> Vec<u8> {
+        serde_json::to_vec(&value).unwrap()
+    }
+
+    #[test]
+    fn round_trips_the_current_version() {
+        let state = CounterV3 {
+            count: 7,
+            label: "seven".to_string(),
+        };
+        let encoded = encode(&state).unwrap();
+        assert_eq!(decode::<CounterV3>(&encoded).unwrap(), state);
+    }
+
+    #[test]
+    fn migrates_through_every_version() {
+        let data = bytes(json!({ "version": 1, "state": { "count": 5 } }));
+        let expected = CounterV3 {
+            count: 5,
+            label: "counter".to_string(),
+        };
+        assert_eq!(decode::<CounterV3>(&data).unwrap(), expected);
+
+        let data = bytes(json!({ "version": 2, "state": { "count": 5 } }));
+        assert_eq!(decode::<CounterV3>(&data).unwrap(), expected);
+    }
+
+    #[test]
+    fn treats_untagged_state_as_version_1() {
+        let data = bytes(json!({ "count": 5 }));
+        assert_eq!(decode::<CounterV3>(&data).unwrap().count, 5);
+        assert_eq!(decode::<CounterV1>(&data).unwrap(), CounterV1 { count: 5 });
+
+        //? Unknown fields next to the tag mean it isn't a tag
+        let data = bytes(json!({ "version": 3, "state": {}, "count": 5 }));
+        assert_eq!(decode::<CounterV1>(&data).unwrap(), CounterV1 { count: 5 });
+    }
+
+    #[test]
+    fn rejects_unknown_versions() {
+        let data = bytes(json!({ "version": 4, "state": { "count": 5 } }));
+        assert!(matches!(
+            decode::<CounterV3>(&data),
+            Err(LockError::UnsupportedVersion(4))
+        ));
+
+        //? Versions older than the oldest struct kept can't be migrated
+        let data = bytes(json!({ "version": 0, "state": { "count": 5 } }));
+        assert!(matches!(
+            decode::<CounterV3>(&data),
+            Err(LockError::UnsupportedVersion(0))
+        ));
+
+        assert!(matches!(
+            migrate_from::<CounterV2, CounterV3>(3, json!({ "count": 5 })),
+            Err(LockError::UnsupportedVersion(3))
+        ));
+    }
+}
@@ -0,0 +1,86 @@
+//! Signs plugin wasm bundles for verification by the host.
+//!
+//! ```text
+//! tlock-sign keygen <key-file>        Write a new signing key, print its public key
+//! tlock-sign pubkey <key-file>        Print the public key for a signing key
+//! tlock-sign sign <key-file> <wasm>   Write a detached signature to <wasm>.sig
+//! tlock-sign verify <pubkey> <wasm>   Check <wasm>.sig against a public key
+//! ```
+//!
+//! Signing keys are stored as hex-encoded 32-byte seeds.
+
+use std::{env, fs};
+
+use anyhow::{Context, anyhow, bail};
+use ed25519_dalek::{Signature, Signer, SigningKey, Verifier, VerifyingKey};
+
+const SIGNATURE_SUFFIX: &str = ".sig";
+
+fn main() -> anyhow::Result<()> {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let args: Vec<&str> = args.iter().map(String::as_str).collect();
+
+    match args.as_slice() {
+        ["keygen", key_file] => keygen(key_file),
+        ["pubkey", key_file] => {
+            let key = read_signing_key(key_file)?;
+            println!("{}", hex::encode(key.verifying_key().as_bytes()));
+            Ok(())
+        }
+        ["sign", key_file, wasm_file] => sign(key_file, wasm_file),
+        ["verify", pubkey, wasm_file] => verify(pubkey, wasm_file),
+        _ => bail!(
+            "Usage: tlock-sign keygen <key-file> | pubkey <key-file> | \
+             sign <key-file> <wasm> | verify <pubkey> <wasm>"
+        ),
+    }
+}
+
+fn keygen(key_file: &str) -> anyhow::Result<()> {
+    if fs::exists(key_file)? {
+        bail!("{} already exists", key_file);
+    }
+
+    let key = SigningKey::from_bytes(&rand::random());
+    fs::write(key_file, hex::encode(key.to_bytes()))
+        .with_context(|| format!("Failed to write {}", key_file))?;
+    println!("{}", hex::encode(key.verifying_key().as_bytes()));
+    Ok(())
+}
+
+fn sign(key_file: &str, wasm_file: &str) -> anyhow::Result<()> {
+    let key = read_signing_key(key_file)?;
+    let wasm = fs::read(wasm_file).with_context(|| format!("Failed to read {}", wasm_file))?;
+
+    let sig_file = format!("{}{}", wasm_file, SIGNATURE_SUFFIX);
+    fs::write(&sig_file, key.sign(&wasm).to_bytes())
+        .with_context(|| format!("Failed to write {}", sig_file))?;
+    println!("Wrote {}", sig_file);
+    Ok(())
+}
+
+fn verify(pubkey: &str, wasm_file: &str) -> anyhow::Result<()> {
+    let key: [u8; 32] = hex::decode(pubkey.trim())?
+        .try_into()
+        .map_err(|_| anyhow!("Public key must be 32 bytes"))?;
+    let key = VerifyingKey::from_bytes(&key)?;
+
+    let wasm = fs::read(wasm_file).with_context(|| format!("Failed to read {}", wasm_file))?;
+    let sig_file = format!("{}{}", wasm_file, SIGNATURE_SUFFIX);
+    let signature = fs::read(&sig_file).with_context(|| format!("Failed to read {}", sig_file))?;
+    let signature = Signature::from_slice(&signature)?;
+
+    key.verify(&wasm, &signature)
+        .map_err(|_| anyhow!("Signature does not match"))?;
+    println!("Signature OK");
+    Ok(())
+}
+
+fn read_signing_key(key_file: &str) -> anyhow::Result<SigningKey> {
+    let seed =
+        fs::read_to_string(key_file).with_context(|| format!("Failed to read {}", key_file))?;
+    let seed: [u8; 32] = hex::decode(seed.trim())?
+        .try_into()
+        .map_err(|_| anyhow!("Signing key must be 32 bytes"))?;
+    Ok(SigningKey::from_bytes(&seed))
+}
@@ -0,0 +1,306 @@
+//! An in-memory host for unit testing plugin code natively.
+//!
+//! `MockTransport` implements wasmi-plugin-pdk's `SyncTransport` and
+//! `AsyncTransport`, so it can stand in for the host anywhere plugin code is
+//! generic over them: `RpcMethod::call` and `call_async`, `StateHandle`, and
+//! helpers written the same way. Host methods answer with whatever the test
+//! programmed with `MockTransport::on`, and the state methods are served from
+//! an in-memory store. Every call is recorded, so tests can check what the
+//! code asked the host for.
+//!
+//! ```rust,ignore
+//! let host = MockTransport::new();
+//! host.on(eth::BlockNumber, |_| Ok(100));
+//! assert_eq!(eth::BlockNumber.call(host.clone(), provider_id)?, 100);
+//! assert_eq!(host.calls_to(eth::BlockNumber), vec![provider_id]);
+//! ```
+
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    sync::{Arc, Mutex},
+};
+
+use async_trait::async_trait;
+use serde_json::{Value, json};
+use tlock_api::{
+    RpcMethod,
+    state::{self, SetError, UnlockError, Usage},
+};
+use wasmi_plugin_pdk::{
+    rpc_message::{RpcError, RpcErrorContext, RpcResponse},
+    transport::{AsyncTransport, SyncTransport},
+};
+
+/// State quota reported by `state_get_usage`, the same as the host's default
+pub const STATE_QUOTA_BYTES: usize = 64 * 1024 * 1024;
+
+type Handler = Arc<dyn Fn(Value) -> Result<Value, RpcError> + Send + Sync>;
+
+#[derive(Debug, Default)]
+struct MockState {
+    values: BTreeMap<String, Vec<u8>>,
+    locked: HashSet<String>,
+}
+
+impl MockState {
+    fn usage(&self) -> usize {
+        self.values.iter().map(|(k, v)| k.len() + v.len()).sum()
+    }
+}
+
+#[derive(Default)]
+struct Inner {
+    handlers: Mutex<HashMap<String, Handler>>,
+    calls: Mutex<Vec<(String, Value)>>,
+    state: Arc<Mutex<MockState>>,
+}
+
+/// A host that answers plugin calls in memory. Clones share the same
+/// handlers, state, and call log.
+#[derive(Clone)]
+pub struct MockTransport {
+    inner: Arc<Inner>,
+}
+
+impl Default for MockTransport {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MockTransport {
+    /// Creates a host that serves the state methods and answers every other
+    /// method with `MethodNotFound` until it's programmed with `on`.
+    pub fn new() -> Self {
+        let transport = Self {
+            inner: Arc::new(Inner::default()),
+        };
+        transport.serve_state();
+        transport
+    }
+
+    /// Answers calls to `method` with `handler`, replacing any earlier
+    /// handler for it, including the built-in state handlers.
+    pub fn on<M, F>(&self, _method: M, handler: F) -> &Self
+    where
+        M: RpcMethod,
+        F: Fn(M::Params) -> Result<M::Output, RpcError> + Send + Sync + 'static,
+    {
+        let handler: Handler = Arc::new(move |params| {
+            let params = serde_json::from_value(params).map_err(|_| RpcError::InvalidParams)?;
+            let output = handler(params)?;
+            serde_json::to_value(output).context("Serialization Error")
+        });
+        self.inner
+            .handlers
+            .lock()
+            .unwrap()
+            .insert(M::NAME.to_string(), handler);
+        self
+    }
+
+    /// Answers every call to `method` with `output`.
+    pub fn respond<M>(&self, method: M, output: M::Output) -> &Self
+    where
+        M: RpcMethod,
+        M::Output: Clone + 'static,
+    {
+        self.on(method, move |_| Ok(output.clone()))
+    }
+
+    /// Every call made so far, in order, with its raw params.
+    pub fn calls(&self) -> Vec<(String, Value)> {
+        self.inner.calls.lock().unwrap().clone()
+    }
+
+    /// The params of every call made to `method` so far, in order.
+    pub fn calls_to<M: RpcMethod>(&self, _method: M) -> Vec<M::Params> {
+        self.calls()
+            .into_iter()
+            .filter(|(name, _)| name == M::NAME)
+            .filter_map(|(_, params)| serde_json::from_value(params).ok())
+            .collect()
+    }
+
+    /// Stores a raw value, as if the plugin had written it.
+    ///
+    /// `StateHandle` caches values for the rest of the request once it's
+    /// read them, so values set after the code under test has read the key
+    /// aren't seen, the same as writes by other instances.
+    pub fn store(&self, key: impl Into<String>, value: Vec<u8>) {
+        let mut state = self.inner.state.lock().unwrap();
+        state.values.insert(key.into(), value);
+    }
+
+    /// The raw value stored at `key`, if any.
+    pub fn stored(&self, key: &str) -> Option<Vec<u8>> {
+        self.inner.state.lock().unwrap().values.get(key).cloned()
+    }
+
+    /// Whether the plugin holds the lock on `key`.
+    pub fn is_locked(&self, key: &str) -> bool {
+        self.inner.state.lock().unwrap().locked.contains(key)
+    }
+
+    fn serve_state(&self) {
+        let store = self.inner.state.clone();
+        self.on(state::ReadKey, move |key| {
+            let state = store.lock().unwrap();
+            Ok(state.values.get(&key).cloned().unwrap_or_default())
+        });
+
+        //? The host would block until the lock is released, which would hang
+        //? a single-threaded test, so it fails instead
+        let store = self.inner.state.clone();
+        self.on(state::LockKey, move |key| {
+            let mut state = store.lock().unwrap();
+            if !state.locked.insert(key.clone()) {
+                return Err(RpcError::Custom(format!("Key '{}' is already locked", key)));
+            }
+            Ok(state.values.get(&key).cloned().unwrap_or_default())
+        });
+
+        let store = self.inner.state.clone();
+        self.on(state::SetKey, move |(key, value)| {
+            let mut state = store.lock().unwrap();
+            if !state.locked.contains(&key) {
+                return Ok(Err(SetError::KeyNotLocked));
+            }
+            let old = state.values.get(&key).map_or(0, |v| key.len() + v.len());
+            if state.usage() - old + key.len() + value.len() > STATE_QUOTA_BYTES {
+                return Ok(Err(SetError::QuotaExceeded));
+            }
+            state.values.insert(key, value);
+            Ok(Ok(()))
+        });
+
+        let store = self.inner.state.clone();
+        self.on(state::UnlockKey, move |key| {
+            let mut state = store.lock().unwrap();
+            if !state.locked.remove(&key) {
+                return Ok(Err(UnlockError::KeyNotLocked));
+            }
+            Ok(Ok(()))
+        });
+
+        let store = self.inner.state.clone();
+        self.on(state::DeleteKey, move |key| {
+            let mut state = store.lock().unwrap();
+            if !state.locked.contains(&key) {
+                return Ok(Err(SetError::KeyNotLocked));
+            }
+            state.values.remove(&key);
+            Ok(Ok(()))
+        });
+
+        let store = self.inner.state.clone();
+        self.on(state::ListKeys, move |prefix| {
+            let state = store.lock().unwrap();
+            let keys = state.values.keys().filter(|k| k.starts_with(&prefix));
+            Ok(keys.cloned().collect())
+        });
+
+        let store = self.inner.state.clone();
+        self.on(state::GetUsage, move |()| {
+            let state = store.lock().unwrap();
+            Ok(Usage {
+                used: state.usage(),
+                quota: STATE_QUOTA_BYTES,
+            })
+        });
+    }
+
+    fn dispatch(&self, method: &str, params: Value) -> Result<Value, RpcError> {
+        self.inner
+            .calls
+            .lock()
+            .unwrap()
+            .push((method.to_string(), params.clone()));
+
+        //? Cloned out so a handler can call back into the transport
+        let handler = self.inner.handlers.lock().unwrap().get(method).cloned();
+        match handler {
+            Some(handler) => handler(params),
+            None => Err(RpcError::MethodNotFound),
+        }
+    }
+}
+
+/// Wraps a result the way the framework's transport would deliver it.
+fn response(result: Value) -> Result<RpcResponse, RpcError> {
+    let response = json!({ "jsonrpc": "2.0", "id": 0, "result": result });
+    serde_json::from_value(response).context("Deserialization Error")
+}
+
+impl SyncTransport<RpcError> for MockTransport {
+    fn call(&self, method: &str, params: Value) -> Result<RpcResponse, RpcError> {
+        response(self.dispatch(method, params)?)
+    }
+}
+
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+impl AsyncTransport<RpcError> for MockTransport {
+    async fn call_async(&self, method: &str, params: Value) -> Result<RpcResponse, RpcError> {
+        response(self.dispatch(method, params)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::executor::block_on;
+    use tlock_api::{entities::EthProviderId, eth, global};
+    use tlock_pdk::state::StateExt;
+
+    use super::*;
+
+    #[test]
+    fn programmed_methods_answer_and_are_recorded() {
+        let host = MockTransport::new();
+        let provider_id = EthProviderId::new();
+        host.respond(eth::BlockNumber, 100);
+
+        assert_eq!(
+            eth::BlockNumber.call(host.clone(), provider_id).unwrap(),
+            100
+        );
+        let number = block_on(eth::BlockNumber.call_async(host.clone(), provider_id));
+        assert_eq!(number.unwrap(), 100);
+        assert_eq!(host.calls_to(eth::BlockNumber), vec![provider_id; 2]);
+    }
+
+    #[test]
+    fn unprogrammed_methods_are_not_found() {
+        let host = MockTransport::new();
+        assert!(matches!(
+            global::Ping.call(host.clone(), ()),
+            Err(RpcError::MethodNotFound)
+        ));
+        assert_eq!(host.calls().len(), 1);
+    }
+
+    #[test]
+    fn state_handles_read_and_write_the_mock_state() {
+        let host = MockTransport::new();
+        host.state().write_key("count", 5u32).unwrap();
+
+        let stored = host.stored("count").unwrap();
+        assert_eq!(serde_json::from_slice::<u32>(&stored).unwrap(), 5);
+        assert!(!host.is_locked("count"));
+        let keys = state::ListKeys.call(host.clone(), String::new()).unwrap();
+        assert_eq!(keys, ["count"]);
+    }
+
+    #[test]
+    fn writes_need_the_key_locked() {
+        let host = MockTransport::new();
+        let set = state::SetKey.call(host.clone(), ("key".into(), vec![1]));
+        assert!(matches!(set.unwrap(), Err(SetError::KeyNotLocked)));
+
+        state::LockKey.call(host.clone(), "key".into()).unwrap();
+        assert!(state::LockKey.call(host.clone(), "key".into()).is_err());
+        let set = state::SetKey.call(host.clone(), ("key".into(), vec![1]));
+        assert!(set.unwrap().is_ok());
+        assert_eq!(host.stored("key"), Some(vec![1]));
+    }
+}
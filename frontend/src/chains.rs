@@ -0,0 +1,53 @@
+use tlock_hdk::tlock_api::caip::ChainId;
+
+pub struct ChainInfo {
+    pub id: u64,
+    pub name: &'static str,
+    pub explorer: &'static str,
+}
+
+/// Chains selectable from the network switcher.
+pub static CHAINS: [ChainInfo; 5] = [
+    ChainInfo {
+        id: 1,
+        name: "Ethereum",
+        explorer: "https://etherscan.io",
+    },
+    ChainInfo {
+        id: 11155111,
+        name: "Sepolia",
+        explorer: "https://sepolia.etherscan.io",
+    },
+    ChainInfo {
+        id: 10,
+        name: "Optimism",
+        explorer: "https://optimistic.etherscan.io",
+    },
+    ChainInfo {
+        id: 8453,
+        name: "Base",
+        explorer: "https://basescan.org",
+    },
+    ChainInfo {
+        id: 42161,
+        name: "Arbitrum One",
+        explorer: "https://arbiscan.io",
+    },
+];
+
+pub fn get_chain_info(chain_id: &ChainId) -> Option<&'static ChainInfo> {
+    match chain_id {
+        ChainId::Evm(Some(id)) => CHAINS.iter().find(|c| c.id == *id),
+        _ => None,
+    }
+}
+
+pub fn chain_name(chain_id: &ChainId) -> String {
+    get_chain_info(chain_id)
+        .map(|c| c.name.to_string())
+        .unwrap_or_else(|| chain_id.to_string())
+}
+
+pub fn explorer_address_url(chain_id: &ChainId, address: &str) -> Option<String> {
+    get_chain_info(chain_id).map(|c| format!("{}/address/{}", c.explorer, address))
+}
@@ -1,12 +1,17 @@
 use alloy::primitives::U256;
 use dioxus::prelude::*;
 use tlock_hdk::tlock_api::{
-    caip::{AccountAddress, AssetType},
+    caip::{AccountAddress, AssetType, ChainId},
     component::Component,
     page::PageEvent,
 };
 use web_sys::js_sys::eval;
 
+use crate::{
+    chains::{chain_name, explorer_address_url},
+    contexts::host::HostContext,
+};
+
 fn format_balance(amount: U256, decimals: u8) -> String {
     let amount_f64 = amount.to_string().parse::<f64>().unwrap_or(0.0);
     format!("{:.4}", amount_f64 / 10_f64.powi(decimals as i32))
@@ -22,8 +27,20 @@ fn get_asset_info(asset_type: &AssetType) -> (String, u8) {
         AssetType::Slip44(n) => (format!("slip44:{}", n), 18),
         AssetType::Erc20(addr) => erc20s::get_erc20_by_address(addr)
             .map(|t| (t.symbol.to_string(), t.decimals))
-            .unwrap_or_else(|| (format!("erc20:{}", shorten_addr(&format!("{:?}", addr))), 18)),
-        AssetType::Custom { namespace, reference } => (
+            .unwrap_or_else(|| {
+                (
+                    format!("erc20:{}", shorten_addr(&format!("{:?}", addr))),
+                    18,
+                )
+            }),
+        AssetType::Erc721 { contract, token_id } | AssetType::Erc1155 { contract, token_id } => (
+            format!("{}#{}", shorten_addr(&format!("{:?}", contract)), token_id),
+            0,
+        ),
+        AssetType::Custom {
+            namespace,
+            reference,
+        } => (
             format!(
                 "{}:{}...{}",
                 namespace,
@@ -35,6 +52,15 @@ fn get_asset_info(asset_type: &AssetType) -> (String, u8) {
     }
 }
 
+//? Highlights chain-specific components that aren't on the active chain
+fn chain_class(chain_id: &ChainId, active_chain: &ChainId) -> &'static str {
+    if chain_id == active_chain {
+        ""
+    } else {
+        "text-warning"
+    }
+}
+
 #[derive(PartialEq, Clone, Props)]
 pub struct ComponentProps {
     component: Component,
@@ -43,6 +69,9 @@ pub struct ComponentProps {
 
 #[component]
 pub fn RenderComponent(props: ComponentProps) -> Element {
+    let ctx: HostContext = use_context();
+    let active_chain = ctx.active_chain();
+
     let component = props.component;
     match component {
         Component::Container { children } => {
@@ -163,11 +192,39 @@ pub fn RenderComponent(props: ComponentProps) -> Element {
                 }
             }
         }
+        Component::AccountDropdownInput {
+            label,
+            options,
+            selected,
+            id,
+        } => {
+            rsx! {
+                fieldset { class: "fieldset",
+                    label { class: "label", "{label}" }
+                    select { class: "select w-full", name: "{id}",
+                        {
+                            options
+                                .iter()
+                                .map(|(option_label, account)| {
+                                    let is_selected = selected.as_ref() == Some(account);
+                                    rsx! {
+                                        option { value: "{account}", selected: is_selected, "{option_label}" }
+                                    }
+                                })
+                        }
+                    }
+                }
+            }
+        }
         Component::Chain { id } => {
+            let chain_class = chain_class(&id, &active_chain);
+
             rsx! {
                 div { class: "join border border-base-300 rounded-lg",
-                    div { class: "join-item px-3 py-1 font-mono text-sm flex items-center",
-                        "{id}"
+                    div {
+                        class: "join-item px-3 py-1 font-mono text-sm flex items-center tooltip cursor-help {chain_class}",
+                        "data-tip": "{id}",
+                        "{chain_name(&id)}"
                     }
                     button {
                         class: "join-item btn btn-ghost btn-sm border-l border-base-300",
@@ -185,16 +242,29 @@ pub fn RenderComponent(props: ComponentProps) -> Element {
                 AccountAddress::Custom(s) => s.clone(),
             };
 
+            let chain_class = chain_class(&id.chain_id, &active_chain);
+            let explorer_url = explorer_address_url(&id.chain_id, &addr);
+
             rsx! {
                 div { class: "join border border-base-300 rounded-lg",
-                    div { class: "join-item px-3 py-1 font-mono text-sm flex items-center",
-                        "{id.chain_id.namespace()}:{id.chain_id.reference().unwrap_or_else(|| \"_\".to_string())}"
+                    div {
+                        class: "join-item px-3 py-1 font-mono text-sm flex items-center tooltip cursor-help {chain_class}",
+                        "data-tip": "{id.chain_id}",
+                        "{chain_name(&id.chain_id)}"
                     }
                     div {
                         class: "join-item px-3 py-1 font-mono text-sm flex items-center tooltip cursor-help before:max-w-md",
                         "data-tip": "{addr}",
                         "{shorten_addr(&addr)}"
                     }
+                    if let Some(url) = explorer_url {
+                        a {
+                            class: "join-item btn btn-ghost btn-sm border-l border-base-300",
+                            href: "{url}",
+                            target: "_blank",
+                            "Explorer"
+                        }
+                    }
                     button {
                         class: "join-item btn btn-ghost btn-sm border-l border-base-300",
                         onclick: move |_| {
@@ -205,13 +275,46 @@ pub fn RenderComponent(props: ComponentProps) -> Element {
                 }
             }
         }
+        Component::Address { address, name } => {
+            let addr = format!("{:?}", address);
+            let label = name.unwrap_or_else(|| shorten_addr(&addr));
+
+            rsx! {
+                div { class: "join border border-base-300 rounded-lg",
+                    div {
+                        class: "join-item px-3 py-1 font-mono text-sm flex items-center tooltip cursor-help before:max-w-md",
+                        "data-tip": "{addr}",
+                        "{label}"
+                    }
+                    button {
+                        class: "join-item btn btn-ghost btn-sm border-l border-base-300",
+                        onclick: move |_| {
+                            let _ = eval(&format!("navigator.clipboard.writeText('{}')", addr));
+                        },
+                        "Copy"
+                    }
+                }
+            }
+        }
         Component::Asset { id, balance } => {
             let (asset_display, decimals) = get_asset_info(&id.asset);
+            let chain_class = chain_class(&id.chain_id, &active_chain);
+            let explorer_url = match &id.asset {
+                AssetType::Erc20(addr) => {
+                    explorer_address_url(&id.chain_id, &format!("{:?}", addr))
+                }
+                AssetType::Erc721 { contract, .. } | AssetType::Erc1155 { contract, .. } => {
+                    explorer_address_url(&id.chain_id, &format!("{:?}", contract))
+                }
+                _ => None,
+            };
 
             rsx! {
                 div { class: "join border border-base-300 rounded-lg",
-                    div { class: "join-item px-3 py-1 font-mono text-sm flex items-center",
-                        "{id.chain_id.namespace()}:{id.chain_id.reference().unwrap_or_else(|| \"_\".to_string())}"
+                    div {
+                        class: "join-item px-3 py-1 font-mono text-sm flex items-center tooltip cursor-help {chain_class}",
+                        "data-tip": "{id.chain_id}",
+                        "{chain_name(&id.chain_id)}"
                     }
                     div {
                         class: "join-item px-3 py-1 font-mono text-sm flex items-center tooltip cursor-help before:max-w-md",
@@ -223,6 +326,14 @@ pub fn RenderComponent(props: ComponentProps) -> Element {
                             {format_balance(bal, decimals)}
                         }
                     }
+                    if let Some(url) = explorer_url {
+                        a {
+                            class: "join-item btn btn-ghost btn-sm border-l border-base-300",
+                            href: "{url}",
+                            target: "_blank",
+                            "Explorer"
+                        }
+                    }
                     button {
                         class: "join-item btn btn-ghost btn-sm border-l border-base-300",
                         onclick: move |_| {
@@ -255,6 +366,67 @@ pub fn RenderComponent(props: ComponentProps) -> Element {
                 }
             }
         }
+        Component::Link { text, page_id } => {
+            let mut ctx = ctx;
+            rsx! {
+                button {
+                    class: "link link-primary text-left",
+                    onclick: move |_| ctx.push_page(page_id),
+                    "{text}"
+                }
+            }
+        }
+        Component::Tabs { items, id } => {
+            rsx! {
+                RenderTabs { items, id, on_event: props.on_event }
+            }
+        }
+        Component::Modal {
+            title,
+            children,
+            id,
+        } => {
+            rsx! {
+                dialog { class: "modal modal-open",
+                    div { class: "modal-box flex flex-col items-start gap-2",
+                        h3 { class: "font-bold text-lg", "{title}" }
+                        {children.iter().map(|child| rsx! {
+                            RenderComponent { component: child.clone(), on_event: props.on_event }
+                        })}
+                    }
+                    form {
+                        method: "dialog",
+                        class: "modal-backdrop",
+                        onmousedown: move |_| {
+                            props.on_event.call(PageEvent::ModalDismissed(id.clone()));
+                        },
+                        button { "Close" }
+                    }
+                }
+            }
+        }
+        Component::Progress { label, percent } => {
+            rsx! {
+                div { class: "flex flex-col gap-1 w-full",
+                    span { class: "text-sm", "{label}" }
+                    progress {
+                        class: "progress progress-primary w-full",
+                        value: "{percent}",
+                        max: "100",
+                    }
+                }
+            }
+        }
+        Component::Spinner { label } => {
+            rsx! {
+                div { class: "flex items-center gap-2",
+                    span { class: "loading loading-spinner loading-sm" }
+                    if !label.is_empty() {
+                        span { class: "text-sm", "{label}" }
+                    }
+                }
+            }
+        }
         Component::Hex { data } => {
             let hex_str = format!("0x{}", hex::encode(&data));
             rsx! {
@@ -274,3 +446,44 @@ pub fn RenderComponent(props: ComponentProps) -> Element {
         }
     }
 }
+
+#[derive(PartialEq, Clone, Props)]
+struct TabsProps {
+    items: Vec<(String, Component)>,
+    id: String,
+    on_event: Callback<PageEvent, ()>,
+}
+
+//? A component of its own so the active tab's signal survives page updates
+#[component]
+fn RenderTabs(props: TabsProps) -> Element {
+    let mut active = use_signal(|| 0usize);
+    let current = active().min(props.items.len().saturating_sub(1));
+
+    rsx! {
+        div { class: "flex flex-col items-start gap-2 w-full",
+            div { role: "tablist", class: "tabs tabs-border",
+                for (i , (label , _)) in props.items.iter().enumerate() {
+                    button {
+                        role: "tab",
+                        class: if i == current { "tab tab-active" } else { "tab" },
+                        key: "{i}",
+                        onclick: {
+                            let id = props.id.clone();
+                            move |_| {
+                                if active() != i {
+                                    active.set(i);
+                                    props.on_event.call(PageEvent::TabChanged(id.clone(), i));
+                                }
+                            }
+                        },
+                        "{label}"
+                    }
+                }
+            }
+            if let Some((_, content)) = props.items.get(current) {
+                RenderComponent { component: content.clone(), on_event: props.on_event }
+            }
+        }
+    }
+}
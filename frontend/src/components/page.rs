@@ -1,5 +1,5 @@
 use dioxus::prelude::*;
-use tlock_hdk::tlock_api::{entities::PageId, page::PageEvent};
+use tlock_hdk::tlock_api::{entities::PageId, error::DomainError, page::PageEvent};
 
 use crate::{
     components::component::RenderComponent,
@@ -18,7 +18,7 @@ pub fn Page(id: PageId) -> Element {
             if let Err(err) = ctx.page_on_load(id).await {
                 info!("OnPageLoad error: {}", err);
                 toast.push(
-                    format!("Error loading page: {}", err),
+                    format!("Error loading page: {}", DomainError::describe(&err)),
                     crate::contexts::toast::ToastKind::Error,
                 );
             }
@@ -32,7 +32,7 @@ pub fn Page(id: PageId) -> Element {
                 Err(err) => {
                     info!("OnPageUpdate error: {}", err);
                     toast.push(
-                        format!("Error updating page: {}", err),
+                        format!("Error updating page: {}", DomainError::describe(&err)),
                         crate::contexts::toast::ToastKind::Error,
                     );
                 }
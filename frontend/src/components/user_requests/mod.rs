@@ -1,10 +1,12 @@
-use std::fmt::Debug;
+use std::{fmt::Debug, time::Duration};
 
+use alloy::primitives::U256;
 use dioxus::prelude::*;
-use host::host::UserRequest;
-use tlock_hdk::tlock_api::entities::EntityId;
+use host::{host::UserRequest, permissions::SessionTerms};
+use tlock_hdk::tlock_api::{caip::AssetId, entities::EntityId, host::EntityCapabilities};
+use uuid::Uuid;
 
-use crate::contexts::host::HostContext;
+use crate::{components::component::RenderComponent, contexts::host::HostContext};
 
 #[component]
 pub fn UserRequestComponent(request: UserRequest) -> Element {
@@ -18,27 +20,44 @@ pub fn UserRequestComponent(request: UserRequest) -> Element {
         .unwrap_or("Unknown Plugin".to_string());
 
     match request {
-        UserRequest::EthProviderSelection { id, chain_id, .. } => rsx! {
+        UserRequest::EthProviderSelection {
+            id,
+            chain_id,
+            required,
+            ..
+        } => rsx! {
             SelectionWrapper { title: "Ethereum Provider ({chain_id})", plugin_name,
                 EntitySelection {
                     filter_map: |eid| match eid {
                         EntityId::EthProvider(i) => Some(i),
                         _ => None,
                     },
+                    required,
                     on_deny: move |_| ctx.deny_user_request(id),
-                    on_select: move |selected_id| ctx.resolve_eth_provider_request(id, selected_id),
+                    on_select: move |selected_id| {
+                        ctx.resolve_eth_provider_request(id, selected_id, false)
+                    },
+                    on_remember: move |selected_id| {
+                        ctx.resolve_eth_provider_request(id, selected_id, true)
+                    },
                 }
             }
         },
-        UserRequest::VaultSelection { id, .. } => rsx! {
+        UserRequest::VaultSelection { id, required, .. } => rsx! {
             SelectionWrapper { title: "Vault", plugin_name,
                 EntitySelection {
                     filter_map: |eid| match eid {
                         EntityId::Vault(i) => Some(i),
                         _ => None,
                     },
+                    required,
                     on_deny: move |_| ctx.deny_user_request(id),
-                    on_select: move |selected_id| ctx.resolve_vault_request(id, selected_id),
+                    on_select: move |selected_id| {
+                        ctx.resolve_vault_request(id, selected_id, false)
+                    },
+                    on_remember: move |selected_id| {
+                        ctx.resolve_vault_request(id, selected_id, true)
+                    },
                 }
             }
         },
@@ -54,6 +73,272 @@ pub fn UserRequestComponent(request: UserRequest) -> Element {
                 }
             }
         },
+        UserRequest::SolProviderSelection { id, chain_id, .. } => rsx! {
+            SelectionWrapper { title: "Solana Provider ({chain_id})", plugin_name,
+                EntitySelection {
+                    filter_map: |eid| match eid {
+                        EntityId::SolProvider(i) => Some(i),
+                        _ => None,
+                    },
+                    on_deny: move |_| ctx.deny_user_request(id),
+                    on_select: move |selected_id| ctx.resolve_sol_provider_request(id, selected_id),
+                }
+            }
+        },
+        UserRequest::BtcProviderSelection { id, chain_id, .. } => rsx! {
+            SelectionWrapper { title: "Bitcoin Provider ({chain_id})", plugin_name,
+                EntitySelection {
+                    filter_map: |eid| match eid {
+                        EntityId::BtcProvider(i) => Some(i),
+                        _ => None,
+                    },
+                    on_deny: move |_| ctx.deny_user_request(id),
+                    on_select: move |selected_id| ctx.resolve_btc_provider_request(id, selected_id),
+                }
+            }
+        },
+        UserRequest::PriceProviderSelection { id, .. } => rsx! {
+            SelectionWrapper { title: "Price Provider", plugin_name,
+                EntitySelection {
+                    filter_map: |eid| match eid {
+                        EntityId::PriceProvider(i) => Some(i),
+                        _ => None,
+                    },
+                    on_deny: move |_| ctx.deny_user_request(id),
+                    on_select: move |selected_id| ctx.resolve_price_provider_request(id, selected_id),
+                }
+            }
+        },
+        UserRequest::SignerSelection { id, .. } => rsx! {
+            SelectionWrapper { title: "Signer", plugin_name,
+                EntitySelection {
+                    filter_map: |eid| match eid {
+                        EntityId::Signer(i) => Some(i),
+                        _ => None,
+                    },
+                    on_deny: move |_| ctx.deny_user_request(id),
+                    on_select: move |selected_id| ctx.resolve_signer_request(id, selected_id),
+                }
+            }
+        },
+        UserRequest::BundlerProviderSelection { id, chain_id, .. } => rsx! {
+            SelectionWrapper { title: "Bundler Provider ({chain_id})", plugin_name,
+                EntitySelection {
+                    filter_map: |eid| match eid {
+                        EntityId::BundlerProvider(i) => Some(i),
+                        _ => None,
+                    },
+                    on_deny: move |_| ctx.deny_user_request(id),
+                    on_select: move |selected_id| ctx.resolve_bundler_provider_request(id, selected_id),
+                }
+            }
+        },
+        UserRequest::TokenListSelection { id, .. } => rsx! {
+            SelectionWrapper { title: "Token List", plugin_name,
+                EntitySelection {
+                    filter_map: |eid| match eid {
+                        EntityId::TokenList(i) => Some(i),
+                        _ => None,
+                    },
+                    on_deny: move |_| ctx.deny_user_request(id),
+                    on_select: move |selected_id| ctx.resolve_token_list_request(id, selected_id),
+                }
+            }
+        },
+        UserRequest::NftProviderSelection { id, .. } => rsx! {
+            SelectionWrapper { title: "NFT Provider", plugin_name,
+                EntitySelection {
+                    filter_map: |eid| match eid {
+                        EntityId::NftProvider(i) => Some(i),
+                        _ => None,
+                    },
+                    on_deny: move |_| ctx.deny_user_request(id),
+                    on_select: move |selected_id| ctx.resolve_nft_provider_request(id, selected_id),
+                }
+            }
+        },
+        UserRequest::ManifestApproval { id, manifest, .. } => rsx! {
+            SelectionWrapper { title: "Permissions", plugin_name,
+                ul {
+                    if manifest.methods.is_empty() && manifest.domains.is_empty()
+                        && manifest.origins.is_empty() && manifest.dependencies.is_empty()
+                    {
+                        p { class: "px-3 py-1.5", "No additional permissions" }
+                    }
+                    for domain in manifest.domains.iter() {
+                        p { key: "domain-{domain}", class: "px-3 py-1.5 font-mono text-sm",
+                            "Register {domain} entities"
+                        }
+                    }
+                    for origin in manifest.origins.iter() {
+                        p { key: "origin-{origin}", class: "px-3 py-1.5 font-mono text-sm",
+                            "Fetch from {origin}"
+                        }
+                    }
+                    for method in manifest.methods.iter() {
+                        p { key: "method-{method}", class: "px-3 py-1.5 font-mono text-sm",
+                            "{method}"
+                        }
+                    }
+                    for dependency in manifest.dependencies.iter() {
+                        p { key: "dependency-{dependency}", class: "px-3 py-1.5 font-mono text-sm",
+                            "Requires: {dependency}"
+                        }
+                    }
+                    div { class: "divider" }
+                    li {
+                        button { onclick: move |_| ctx.approve_manifest_request(id), "Approve" }
+                    }
+                    li {
+                        button { class: "text-error", onclick: move |_| ctx.deny_user_request(id), "Deny Request" }
+                    }
+                }
+            }
+        },
+        UserRequest::OriginApproval { id, origin, .. } => rsx! {
+            SelectionWrapper { title: "Network Access", plugin_name,
+                p { class: "px-3 py-1.5", "Allow requests to "
+                    span { class: "font-mono", "{origin}" }
+                    "?"
+                }
+                ul {
+                    div { class: "divider" }
+                    li {
+                        button { onclick: move |_| ctx.approve_origin_request(id), "Allow" }
+                    }
+                    li {
+                        button { class: "text-error", onclick: move |_| ctx.deny_user_request(id), "Deny" }
+                    }
+                }
+            }
+        },
+        UserRequest::MissingDependencies { id, missing, .. } => rsx! {
+            SelectionWrapper { title: "Missing Dependencies", plugin_name,
+                div { class: "flex flex-col gap-2 px-3 py-1.5",
+                    p { "This plugin needs the following before it can be set up. Install them, then retry." }
+                    for dependency in missing.iter() {
+                        p { key: "{dependency}", class: "font-mono text-sm", "{dependency}" }
+                    }
+                }
+                ul {
+                    div { class: "divider" }
+                    li {
+                        button { onclick: move |_| ctx.retry_dependencies_request(id), "Retry" }
+                    }
+                    li {
+                        button { class: "text-error", onclick: move |_| ctx.deny_user_request(id), "Cancel" }
+                    }
+                }
+            }
+        },
+        UserRequest::UnsignedPlugin { id, name, url, .. } => rsx! {
+            SelectionWrapper { title: "Unsigned Plugin", plugin_name: name.clone(),
+                div { class: "flex flex-col gap-2 px-3 py-1.5",
+                    p { class: "text-warning",
+                        "{name} isn't signed by a publisher you trust. Only load it if you trust its source."
+                    }
+                    p { class: "font-mono text-sm break-all", "{url}" }
+                }
+                ul {
+                    div { class: "divider" }
+                    li {
+                        button { onclick: move |_| ctx.approve_unsigned_plugin_request(id), "Load Anyway" }
+                    }
+                    li {
+                        button { class: "text-error", onclick: move |_| ctx.deny_user_request(id), "Cancel" }
+                    }
+                }
+            }
+        },
+        UserRequest::PluginUpgrade { id, name, .. } => rsx! {
+            SelectionWrapper { title: "Replace Plugin", plugin_name: name.clone(),
+                div { class: "flex flex-col gap-2 px-3 py-1.5",
+                    p { class: "text-warning",
+                        "This build would replace the installed {name}, but isn't signed by the same publisher."
+                    }
+                    p {
+                        "Its stored data, secrets, entities, and permissions would all be handed to the new build. Only continue if you meant to upgrade {name}."
+                    }
+                }
+                ul {
+                    div { class: "divider" }
+                    li {
+                        button { onclick: move |_| ctx.approve_upgrade_request(id), "Replace {name}" }
+                    }
+                    li {
+                        button { class: "text-error", onclick: move |_| ctx.deny_user_request(id), "Cancel" }
+                    }
+                }
+            }
+        },
+        UserRequest::CallApproval {
+            id,
+            entity_id,
+            method,
+            spend,
+            ..
+        } => rsx! {
+            SelectionWrapper { title: "Call Approval", plugin_name,
+                CallApproval { id, entity_id, method, spend }
+            }
+        },
+        UserRequest::KeystoreUnlock {
+            id, new_password, ..
+        } => rsx! {
+            SelectionWrapper { title: "Keystore Unlock", plugin_name,
+                KeystoreUnlock { id, new_password }
+            }
+        },
+        UserRequest::Confirmation {
+            id,
+            title,
+            body,
+            details,
+            ..
+        } => rsx! {
+            SelectionWrapper { title: "Confirmation", plugin_name,
+                div { class: "flex flex-col gap-2 px-3 py-1.5",
+                    h4 { class: "font-bold", "{title}" }
+                    p { "{body}" }
+                    //? Details are display-only, so events from them are ignored
+                    RenderComponent { component: details, on_event: move |_| {} }
+                }
+                ul {
+                    div { class: "divider" }
+                    li {
+                        button { onclick: move |_| ctx.confirm_request(id), "Confirm" }
+                    }
+                    li {
+                        button { class: "text-error", onclick: move |_| ctx.deny_user_request(id), "Cancel" }
+                    }
+                }
+            }
+        },
+        UserRequest::Custom {
+            id,
+            title,
+            body,
+            options,
+            ..
+        } => rsx! {
+            SelectionWrapper { title: "{title}", plugin_name,
+                div { class: "px-3 py-1.5",
+                    //? The body is display-only, so events from it are ignored
+                    RenderComponent { component: body, on_event: move |_| {} }
+                }
+                ul {
+                    for (i, option) in options.into_iter().enumerate() {
+                        li { key: "{i}",
+                            button { onclick: move |_| ctx.resolve_choice_request(id, i), "{option}" }
+                        }
+                    }
+                    div { class: "divider" }
+                    li {
+                        button { class: "text-error", onclick: move |_| ctx.deny_user_request(id), "Dismiss" }
+                    }
+                }
+            }
+        },
     }
 }
 
@@ -70,19 +355,158 @@ fn SelectionWrapper(title: String, plugin_name: String, children: Element) -> El
     }
 }
 
+#[component]
+fn CallApproval(
+    id: Uuid,
+    entity_id: EntityId,
+    method: String,
+    spend: Option<(AssetId, U256)>,
+) -> Element {
+    let mut ctx: HostContext = use_context();
+    let mut minutes = use_signal(|| "10".to_string());
+    let initial_limit = spend.as_ref().map(|(_, amount)| amount.to_string());
+    let mut limit = use_signal(|| initial_limit.unwrap_or_default());
+    let mut error = use_signal(|| None::<String>);
+
+    rsx! {
+        div { class: "flex flex-col gap-2 px-3 py-1.5",
+            p { "Call "
+                span { class: "font-mono", "{method}" }
+                " on "
+                span { class: "font-mono", "{entity_id}" }
+                "?"
+            }
+            if let Some((asset, amount)) = &spend {
+                p { class: "font-mono text-sm break-all", "Spends {amount} of {asset}" }
+            }
+            div { class: "divider my-0", "Allow for a session" }
+            div { class: "flex items-center gap-1",
+                input {
+                    class: "input input-xs w-20",
+                    r#type: "number",
+                    placeholder: "Minutes",
+                    value: "{minutes}",
+                    oninput: move |e| minutes.set(e.value()),
+                }
+                "minutes"
+            }
+            if spend.is_some() {
+                input {
+                    class: "input input-xs w-full",
+                    r#type: "number",
+                    placeholder: "Total limit in base units (blank for unlimited)",
+                    value: "{limit}",
+                    oninput: move |e| limit.set(e.value()),
+                }
+            } else {
+                p { class: "text-sm", "This call's spend can't be measured, so the session can't be limited" }
+            }
+            if let Some(error) = error() {
+                p { class: "text-error text-sm", "{error}" }
+            }
+        }
+        ul {
+            div { class: "divider" }
+            li {
+                button { onclick: move |_| ctx.approve_call_request(id, None), "Allow Once" }
+            }
+            li {
+                button {
+                    onclick: move |_| {
+                        let Ok(mins) = minutes.read().trim().parse::<u64>() else {
+                            error.set(Some("Minutes must be a whole number".to_string()));
+                            return;
+                        };
+                        let limit = match limit.read().trim() {
+                            "" => None,
+                            raw => match raw.parse::<U256>() {
+                                Ok(limit) => Some(limit),
+                                Err(_) => {
+                                    error.set(Some("Limit must be a whole number".to_string()));
+                                    return;
+                                }
+                            },
+                        };
+                        let terms = SessionTerms {
+                            ttl: Duration::from_secs(mins * 60),
+                            limit,
+                        };
+                        ctx.approve_call_request(id, Some(terms));
+                    },
+                    "Allow for Session"
+                }
+            }
+            li {
+                button { class: "text-error", onclick: move |_| ctx.deny_user_request(id), "Deny" }
+            }
+        }
+    }
+}
+
+#[component]
+fn KeystoreUnlock(id: Uuid, new_password: bool) -> Element {
+    let mut ctx: HostContext = use_context();
+    let mut password = use_signal(String::new);
+    let mut error = use_signal(|| None::<String>);
+
+    rsx! {
+        div { class: "flex flex-col gap-2 px-3 py-1.5",
+            if new_password {
+                p { "Choose a password to encrypt plugin secrets with. It can't be recovered if forgotten." }
+            } else {
+                p { "Enter your keystore password to unlock plugin secrets." }
+            }
+            input {
+                class: "input input-sm w-full",
+                r#type: "password",
+                placeholder: "Password",
+                value: "{password}",
+                oninput: move |e| password.set(e.value()),
+            }
+            if let Some(error) = error() {
+                p { class: "text-error text-sm", "{error}" }
+            }
+        }
+        ul {
+            div { class: "divider" }
+            li {
+                button {
+                    onclick: move |_| {
+                        let result = ctx.unlock_keystore(&password.read());
+                        match result {
+                            Ok(()) => password.set(String::new()),
+                            Err(e) => error.set(Some(e.to_string())),
+                        }
+                    },
+                    "Unlock"
+                }
+            }
+            li {
+                button { class: "text-error", onclick: move |_| ctx.deny_user_request(id), "Cancel" }
+            }
+        }
+    }
+}
+
 #[component]
 fn EntitySelection<T>(
     filter_map: Callback<EntityId, Option<T>>,
+    #[props(default)] required: EntityCapabilities,
     on_select: EventHandler<T>,
+    /// Called instead of `on_select` when the user asks to remember their
+    /// choice. The option is only offered if this is set.
+    on_remember: Option<EventHandler<T>>,
     on_deny: EventHandler<()>,
 ) -> Element
 where
     T: PartialEq + Debug + Copy + 'static,
 {
     let ctx: HostContext = use_context();
+    let mut remember = use_signal(|| false);
     let entities = ctx.entity_ids();
     let entities: Vec<(EntityId, T)> = entities
         .iter()
+        .filter(|entity_id| ctx.entity_capabilities(**entity_id).satisfies(&required))
         .filter_map(|entity_id| filter_map.call(*entity_id).map(|t| (*entity_id, t)))
         .collect();
 
@@ -93,8 +517,22 @@ where
                     SelectableEntity {
                         id: entity.0,
                         entity: entity.1,
-                        on_select: on_select.clone(),
+                        on_select: move |selected| match on_remember {
+                            Some(on_remember) if *remember.read() => on_remember.call(selected),
+                            _ => on_select.call(selected),
+                        },
+                    }
+                }
+            }
+            if on_remember.is_some() {
+                label { class: "label cursor-pointer gap-2 px-3 py-1.5",
+                    input {
+                        r#type: "checkbox",
+                        class: "checkbox checkbox-sm",
+                        checked: *remember.read(),
+                        onchange: move |e| remember.set(e.checked()),
                     }
+                    "Remember this choice"
                 }
             }
             div { class: "divider" }
@@ -118,6 +556,6 @@ where
         .unwrap_or("Unknown Plugin");
 
     rsx!(
-        button { onclick: move |_| on_select.call(entity), "{id} (plugin: {plugin_name})" }
+        button { onclick: move |_| on_select.call(entity), "{ctx.entity_display_name(id)} (plugin: {plugin_name})" }
     )
 }
@@ -1,25 +1,46 @@
 use std::sync::Arc;
 
 use dioxus::{
-    hooks::{UnboundedReceiver, use_coroutine, use_coroutine_handle, use_signal},
+    hooks::{UnboundedReceiver, use_coroutine, use_coroutine_handle, use_future, use_signal},
+    logger::tracing::warn,
     signals::{ReadableExt, Signal, WritableExt},
 };
 use futures::StreamExt;
 use host::{
-    host::{Event, Host, PluginError, UserRequest},
-    host_state::{HostState, PluginSource},
+    event_log::{Event, EventFilter},
+    history::ExportFormat,
+    host::{Host, NotificationEntry, PluginError, UserRequest},
+    host_state::{EntityLabel, HostState, PluginSource, ResourceLimits},
+    keystore::KeystoreError,
+    metrics::MethodMetrics,
+    permissions::{SessionGrant, SessionTerms},
+    signing::TrustedPublisher,
+    simulation::SimulatedTransaction,
+    transactions::TrackedTransaction,
 };
 use tlock_hdk::{
     tlock_api::{
+        caip::ChainId,
         component::Component,
-        entities::{CoordinatorId, EntityId, EthProviderId, PageId, VaultId},
+        entities::{
+            BtcProviderId, BundlerProviderId, CoordinatorId, EntityId, EthProviderId,
+            NftProviderId, PageId, PriceProviderId, SignerId, SolProviderId, TokenListId, VaultId,
+        },
+        host::{EntityCapabilities, Toast},
         page::PageEvent,
+        state::Usage,
     },
     wasmi_plugin_hdk::{plugin::Plugin, plugin_id::PluginId},
     wasmi_plugin_pdk::rpc_message::RpcError,
 };
 use uuid::Uuid;
 
+const ALARM_POLL_MS: u32 = 1000;
+const DEPOSIT_POLL_MS: u32 = 15_000;
+const TRANSACTION_POLL_MS: u32 = 4_000;
+const WS_POLL_MS: u32 = 50;
+const STATE_FLUSH_MS: u32 = 500;
+
 #[derive(Copy, Clone)]
 pub struct HostContext {
     host: Signal<Arc<Host>>,
@@ -40,6 +61,56 @@ impl HostContext {
         let tx = use_coroutine_handle::<()>().tx();
         host_sig.read().subscribe(tx);
 
+        //? Alarms, ticks, and session expiries are polled rather than scheduled
+        //? individually so they keep firing against whichever host is current
+        //? after a state load
+        use_future(move || async move {
+            loop {
+                gloo_timers::future::TimeoutFuture::new(ALARM_POLL_MS).await;
+                let host = host_sig.read().clone();
+                host.fire_due_alarms().await;
+                host.run_due_ticks().await;
+                host.expire_sessions().await;
+            }
+        });
+
+        use_future(move || async move {
+            loop {
+                gloo_timers::future::TimeoutFuture::new(DEPOSIT_POLL_MS).await;
+                let host = host_sig.read().clone();
+                host.poll_deposits().await;
+            }
+        });
+
+        use_future(move || async move {
+            loop {
+                gloo_timers::future::TimeoutFuture::new(TRANSACTION_POLL_MS).await;
+                let host = host_sig.read().clone();
+                host.poll_transactions().await;
+            }
+        });
+
+        use_future(move || async move {
+            loop {
+                gloo_timers::future::TimeoutFuture::new(WS_POLL_MS).await;
+                let host = host_sig.read().clone();
+                host.deliver_ws_messages().await;
+            }
+        });
+
+        //? Plugin state is written behind the host's in-memory map. A store is
+        //? attached to whichever host is current, including after a state load
+        use_future(move || async move {
+            loop {
+                gloo_timers::future::TimeoutFuture::new(STATE_FLUSH_MS).await;
+                let host = host_sig.read().clone();
+                if !host.has_state_store() {
+                    attach_state_store(&host).await;
+                }
+                host.flush_state().await;
+            }
+        });
+
         Self {
             host: host_sig,
             revision,
@@ -84,6 +155,126 @@ impl HostContext {
         self.host.read().get_entity_plugin(entity_id)
     }
 
+    pub fn entity_label(&self, entity_id: EntityId) -> Option<EntityLabel> {
+        let _ = self.revision.read();
+        self.host.read().get_entity_label(entity_id)
+    }
+
+    /// The entity's icon and label if the user has set one, otherwise its ID.
+    pub fn entity_display_name(&self, entity_id: EntityId) -> String {
+        match self.entity_label(entity_id) {
+            Some(EntityLabel {
+                label,
+                icon: Some(icon),
+            }) => format!("{} {}", icon, label),
+            Some(EntityLabel { label, icon: None }) => label,
+            None => entity_id.to_string(),
+        }
+    }
+
+    pub fn set_resource_limits(&mut self, plugin_id: PluginId, limits: Option<ResourceLimits>) {
+        let host = self.host.read().clone();
+        host.set_resource_limits(plugin_id, limits);
+        self.notify();
+    }
+
+    pub fn set_entity_label(&mut self, entity_id: EntityId, label: String, icon: Option<String>) {
+        let host = self.host.read().clone();
+        host.set_entity_label(entity_id, label);
+        host.set_entity_icon(entity_id, icon);
+        self.notify();
+    }
+
+    pub fn entity_capabilities(&self, entity_id: EntityId) -> EntityCapabilities {
+        let _ = self.revision.read();
+        self.host.read().get_entity_capabilities(entity_id)
+    }
+
+    pub fn state_usage(&self, plugin_id: PluginId) -> Usage {
+        let _ = self.revision.read();
+        self.host.read().get_state_usage(&plugin_id)
+    }
+
+    pub fn resource_limits(&self, plugin_id: PluginId) -> ResourceLimits {
+        let _ = self.revision.read();
+        self.host.read().get_resource_limits(&plugin_id)
+    }
+
+    pub fn permission_grants(&self) -> Vec<(PluginId, EntityId)> {
+        let _ = self.revision.read();
+        self.host.read().get_permission_grants()
+    }
+
+    pub fn session_grants(&self) -> Vec<SessionGrant> {
+        let _ = self.revision.read();
+        self.host.read().get_session_grants()
+    }
+
+    pub fn metrics(&self) -> Vec<(PluginId, String, MethodMetrics)> {
+        let _ = self.revision.read();
+        self.host.read().get_metrics()
+    }
+
+    pub fn reset_metrics(&mut self) {
+        let host = self.host.read().clone();
+        host.reset_metrics();
+        self.notify();
+    }
+
+    pub fn transactions(&self) -> Vec<TrackedTransaction> {
+        let _ = self.revision.read();
+        self.host.read().get_transactions()
+    }
+
+    pub fn clear_finished_transactions(&mut self) {
+        let host = self.host.read().clone();
+        host.clear_finished_transactions();
+        self.notify();
+    }
+
+    pub fn simulation_fork(&self) -> Option<EthProviderId> {
+        let _ = self.revision.read();
+        self.host.read().get_simulation_fork()
+    }
+
+    pub fn simulated_transactions(&self) -> Vec<SimulatedTransaction> {
+        let _ = self.revision.read();
+        self.host.read().get_simulated_transactions()
+    }
+
+    pub fn enable_simulation(&mut self, fork: EthProviderId) -> Result<(), RpcError> {
+        let host = self.host.read().clone();
+        host.enable_simulation(fork)?;
+        self.notify();
+        Ok(())
+    }
+
+    pub fn disable_simulation(&mut self) {
+        let host = self.host.read().clone();
+        host.disable_simulation();
+        self.notify();
+    }
+
+    pub fn default_eth_provider(&self, chain_id: &ChainId) -> Option<EthProviderId> {
+        let _ = self.revision.read();
+        self.host.read().get_default_eth_provider(chain_id)
+    }
+
+    pub fn set_default_eth_provider(
+        &mut self,
+        chain_id: ChainId,
+        provider_id: Option<EthProviderId>,
+    ) {
+        let host = self.host.read().clone();
+        host.set_default_eth_provider(chain_id, provider_id);
+        self.notify();
+    }
+
+    pub fn trusted_publishers(&self) -> Vec<TrustedPublisher> {
+        let _ = self.revision.read();
+        self.host.read().get_trusted_publishers()
+    }
+
     pub fn page_ids(&self) -> Vec<PageId> {
         let entity_ids = self.entity_ids();
         entity_ids
@@ -110,6 +301,61 @@ impl HostContext {
         self.host.read().get_events()
     }
 
+    pub fn query_events(&self, filter: &EventFilter) -> Vec<Event> {
+        let _ = self.revision.read();
+        self.host.read().query_events(filter)
+    }
+
+    /// Takes the toasts plugins have shown since the last call.
+    pub fn take_toasts(&self) -> Vec<(PluginId, Toast)> {
+        let _ = self.revision.read();
+        self.host.read().take_toasts()
+    }
+
+    pub fn notifications(&self) -> Vec<NotificationEntry> {
+        let _ = self.revision.read();
+        self.host.read().get_notifications()
+    }
+
+    pub fn page_title(&self, page_id: PageId) -> Option<String> {
+        let _ = self.revision.read();
+        self.host.read().get_page_title(page_id)
+    }
+
+    pub fn current_page(&self) -> Option<PageId> {
+        let _ = self.revision.read();
+        self.host.read().current_page()
+    }
+
+    pub fn can_navigate_back(&self) -> bool {
+        let _ = self.revision.read();
+        self.host.read().can_navigate_back()
+    }
+
+    pub fn active_chain(&self) -> ChainId {
+        let _ = self.revision.read();
+        self.host.read().get_active_chain()
+    }
+
+    pub fn export_history(
+        &self,
+        vault_id: VaultId,
+        format: ExportFormat,
+    ) -> Result<String, serde_json::Error> {
+        let _ = self.revision.read();
+        self.host.read().export_history(vault_id, format)
+    }
+
+    pub fn export_audit_log(&self) -> Result<String, serde_json::Error> {
+        let _ = self.revision.read();
+        self.host.read().export_audit_log()
+    }
+
+    pub fn is_keystore_unlocked(&self) -> bool {
+        let _ = self.revision.read();
+        self.host.read().is_keystore_unlocked()
+    }
+
     //? --- Actions ---
     pub fn set_host(&mut self, host: Arc<Host>) {
         self.host.set(host);
@@ -127,6 +373,47 @@ impl HostContext {
         Ok(id)
     }
 
+    pub async fn install_plugin_from_url(
+        &mut self,
+        url: &str,
+        expected_sha256: [u8; 32],
+    ) -> Result<PluginId, PluginError> {
+        let host = self.host.read().clone();
+        let id = host.install_plugin_from_url(url, expected_sha256).await?;
+        self.notify();
+        Ok(id)
+    }
+
+    /// Reloads a plugin from its source, keeping its ID, entities, and state.
+    pub async fn reload_plugin(&mut self, plugin_id: PluginId) -> Result<(), PluginError> {
+        let host = self.host.read().clone();
+        let Some(source) = host.get_plugin_source(&plugin_id) else {
+            return Ok(());
+        };
+
+        let wasm_bytes = source.as_bytes().await?;
+        host.reload_plugin(plugin_id, wasm_bytes).await?;
+        self.notify();
+        Ok(())
+    }
+
+    pub async fn remove_plugin(&mut self, plugin_id: PluginId) {
+        let host = self.host.read().clone();
+        host.remove_plugin(&plugin_id).await;
+        self.notify();
+    }
+
+    pub async fn uninstall_plugin(&mut self, plugin_id: PluginId) {
+        let host = self.host.read().clone();
+        host.uninstall_plugin(&plugin_id).await;
+        self.notify();
+    }
+
+    pub async fn shutdown(&mut self) {
+        let host = self.host.read().clone();
+        host.shutdown().await;
+    }
+
     pub async fn page_on_load(&mut self, page_id: PageId) -> Result<(), RpcError> {
         let host = self.host.read().clone();
         host.page_on_load(page_id).await?;
@@ -145,15 +432,74 @@ impl HostContext {
         Ok(())
     }
 
-    pub fn resolve_eth_provider_request(&mut self, request_id: Uuid, provider_id: EthProviderId) {
+    pub async fn set_active_chain(&mut self, chain_id: ChainId) {
+        let host = self.host.read().clone();
+        host.set_active_chain(chain_id).await;
+        self.notify();
+    }
+
+    pub fn open_page(&mut self, page_id: Option<PageId>) {
+        let host = self.host.read().clone();
+        host.open_page(page_id);
+        self.notify();
+    }
+
+    pub fn push_page(&mut self, page_id: PageId) {
+        let host = self.host.read().clone();
+        host.push_page(page_id);
+        self.notify();
+    }
+
+    pub fn navigate_back(&mut self) {
+        let host = self.host.read().clone();
+        host.navigate_back();
+        self.notify();
+    }
+
+    pub fn dismiss_notification(&mut self, notification_id: Uuid) {
+        let host = self.host.read().clone();
+        host.dismiss_notification(notification_id);
+        self.notify();
+    }
+
+    pub fn resolve_eth_provider_request(
+        &mut self,
+        request_id: Uuid,
+        provider_id: EthProviderId,
+        remember: bool,
+    ) {
+        let host = self.host.read().clone();
+        host.resolve_eth_provider_request(request_id, provider_id, remember);
+        self.notify();
+    }
+
+    pub fn resolve_vault_request(&mut self, request_id: Uuid, vault_id: VaultId, remember: bool) {
+        let host = self.host.read().clone();
+        host.resolve_vault_request(request_id, vault_id, remember);
+        self.notify();
+    }
+
+    pub fn revoke_permission(&mut self, plugin_id: PluginId, entity_id: EntityId) {
+        let host = self.host.read().clone();
+        host.revoke_permission(&plugin_id, &entity_id);
+        self.notify();
+    }
+
+    pub fn revoke_session_grant(&mut self, id: Uuid) {
+        let host = self.host.read().clone();
+        host.revoke_session_grant(&id);
+        self.notify();
+    }
+
+    pub fn trust_publisher(&mut self, name: &str, key: [u8; 32]) {
         let host = self.host.read().clone();
-        host.resolve_eth_provider_request(request_id, provider_id);
+        host.trust_publisher(name, key);
         self.notify();
     }
 
-    pub fn resolve_vault_request(&mut self, request_id: Uuid, vault_id: VaultId) {
+    pub fn untrust_publisher(&mut self, key: [u8; 32]) {
         let host = self.host.read().clone();
-        host.resolve_vault_request(request_id, vault_id);
+        host.untrust_publisher(&key);
         self.notify();
     }
 
@@ -163,9 +509,148 @@ impl HostContext {
         self.notify();
     }
 
+    pub fn resolve_sol_provider_request(&mut self, request_id: Uuid, provider_id: SolProviderId) {
+        let host = self.host.read().clone();
+        host.resolve_sol_provider_request(request_id, provider_id);
+        self.notify();
+    }
+
+    pub fn resolve_btc_provider_request(&mut self, request_id: Uuid, provider_id: BtcProviderId) {
+        let host = self.host.read().clone();
+        host.resolve_btc_provider_request(request_id, provider_id);
+        self.notify();
+    }
+
+    pub fn resolve_price_provider_request(
+        &mut self,
+        request_id: Uuid,
+        provider_id: PriceProviderId,
+    ) {
+        let host = self.host.read().clone();
+        host.resolve_price_provider_request(request_id, provider_id);
+        self.notify();
+    }
+
+    pub fn resolve_signer_request(&mut self, request_id: Uuid, signer_id: SignerId) {
+        let host = self.host.read().clone();
+        host.resolve_signer_request(request_id, signer_id);
+        self.notify();
+    }
+
+    pub fn resolve_bundler_provider_request(
+        &mut self,
+        request_id: Uuid,
+        provider_id: BundlerProviderId,
+    ) {
+        let host = self.host.read().clone();
+        host.resolve_bundler_provider_request(request_id, provider_id);
+        self.notify();
+    }
+
+    pub fn resolve_token_list_request(&mut self, request_id: Uuid, token_list_id: TokenListId) {
+        let host = self.host.read().clone();
+        host.resolve_token_list_request(request_id, token_list_id);
+        self.notify();
+    }
+
+    pub fn resolve_nft_provider_request(&mut self, request_id: Uuid, provider_id: NftProviderId) {
+        let host = self.host.read().clone();
+        host.resolve_nft_provider_request(request_id, provider_id);
+        self.notify();
+    }
+
+    pub fn approve_manifest_request(&mut self, request_id: Uuid) {
+        let host = self.host.read().clone();
+        host.approve_manifest_request(request_id);
+        self.notify();
+    }
+
+    pub fn approve_origin_request(&mut self, request_id: Uuid) {
+        let host = self.host.read().clone();
+        host.approve_origin_request(request_id);
+        self.notify();
+    }
+
+    pub fn approve_call_request(&mut self, request_id: Uuid, session: Option<SessionTerms>) {
+        let host = self.host.read().clone();
+        host.approve_call_request(request_id, session);
+        self.notify();
+    }
+
+    pub fn unlock_keystore(&mut self, password: &str) -> Result<(), KeystoreError> {
+        let host = self.host.read().clone();
+        host.unlock_keystore(password)?;
+        self.notify();
+        Ok(())
+    }
+
+    pub fn lock_keystore(&mut self) {
+        let host = self.host.read().clone();
+        host.lock_keystore();
+        self.notify();
+    }
+
+    pub fn retry_dependencies_request(&mut self, request_id: Uuid) {
+        let host = self.host.read().clone();
+        host.retry_dependencies_request(request_id);
+        self.notify();
+    }
+
+    pub fn approve_unsigned_plugin_request(&mut self, request_id: Uuid) {
+        let host = self.host.read().clone();
+        host.approve_unsigned_plugin_request(request_id);
+        self.notify();
+    }
+
+    pub fn approve_upgrade_request(&mut self, request_id: Uuid) {
+        let host = self.host.read().clone();
+        host.approve_upgrade_request(request_id);
+        self.notify();
+    }
+
+    pub fn confirm_request(&mut self, request_id: Uuid) {
+        let host = self.host.read().clone();
+        host.confirm_request(request_id);
+        self.notify();
+    }
+
+    pub fn resolve_choice_request(&mut self, request_id: Uuid, option: usize) {
+        let host = self.host.read().clone();
+        host.resolve_choice_request(request_id, option);
+        self.notify();
+    }
+
     pub fn deny_user_request(&mut self, request_id: Uuid) {
         let host = self.host.read().clone();
         host.deny_user_request(request_id);
         self.notify();
     }
 }
+
+#[cfg(feature = "web")]
+async fn attach_state_store(host: &Host) {
+    let store = Arc::new(host::state_store::IndexedDbStore::new("tlock"));
+    if let Err(e) = host.attach_state_store(store).await {
+        warn!("Failed to open plugin state store: {}", e);
+    }
+}
+
+#[cfg(feature = "desktop")]
+async fn attach_state_store(host: &Host) {
+    let store = match host::state_store::SledStore::open("tlock-state") {
+        Ok(store) => Arc::new(store),
+        Err(e) => {
+            warn!("Failed to open plugin state store: {}", e);
+            return;
+        }
+    };
+    if let Err(e) = host.attach_state_store(store).await {
+        warn!("Failed to open plugin state store: {}", e);
+    }
+}
+
+#[cfg(not(any(feature = "web", feature = "desktop")))]
+async fn attach_state_store(host: &Host) {
+    let store = Arc::new(host::state_store::MemoryStore);
+    let _ = host.attach_state_store(store).await;
+}
@@ -11,6 +11,7 @@ pub struct Toast {
 pub enum ToastKind {
     Info,
     Success,
+    Warning,
     Error,
 }
 
@@ -25,6 +26,11 @@ impl ToastContext {
     }
 
     pub fn push(&self, message: impl Into<String>, kind: ToastKind) {
+        self.push_for(message, kind, TOAST_TIMEOUT_MS);
+    }
+
+    /// Pushes a toast that's dismissed after `timeout_ms`.
+    pub fn push_for(&self, message: impl Into<String>, kind: ToastKind, timeout_ms: u32) {
         let mut storage = self.0;
         let id = uuid::Uuid::new_v4();
         let msg = message.into();
@@ -36,7 +42,7 @@ impl ToastContext {
         });
 
         spawn(async move {
-            gloo_timers::future::TimeoutFuture::new(TOAST_TIMEOUT_MS).await;
+            gloo_timers::future::TimeoutFuture::new(timeout_ms).await;
             storage.write().retain(|t| t.id != id);
         });
     }
@@ -57,6 +63,7 @@ pub fn toast_container() -> Element {
                     key: "{toast.id}",
                     class: match toast.kind {
                         ToastKind::Success => "alert alert-success",
+                        ToastKind::Warning => "alert alert-warning",
                         ToastKind::Error => "alert alert-error",
                         _ => "alert alert-info",
                     },
@@ -1,3 +1,4 @@
+pub mod chains;
 pub mod components;
 pub mod contexts;
 pub mod download_util;
@@ -5,25 +5,44 @@ use std::sync::Arc;
 use anyhow::anyhow;
 use dioxus::{logger::tracing::info, prelude::*};
 use frontend::{
+    chains::{CHAINS, chain_name},
     components::{page::Page, user_requests::UserRequestComponent},
     contexts::{
         host::HostContext,
         toast::{ToastContext, ToastKind, toast_container},
     },
+    download_util::download_bytes,
     focus_helper::blur_active_element,
 };
-use host::{host::Host, host_state::PluginSource};
-use tlock_hdk::tlock_api::{
-    entities::{EntityId, PageId},
-    host::NotifyLevel,
+use host::{
+    event_log::EventFilter,
+    history::ExportFormat,
+    host::Host,
+    host_state::{HostState, PluginSource, ResourceLimits},
+    state_crypto,
+};
+use tlock_hdk::{
+    tlock_api::{
+        caip::ChainId,
+        entities::{EntityId, EthProviderId, VaultId},
+        error::DomainError,
+        eth::TransactionStatus,
+        host::{EntityCapabilities, NotifyLevel, ToastLevel},
+        page::PageEvent,
+    },
+    wasmi_plugin_hdk::plugin_id::PluginId,
 };
 
 #[derive(Copy, Clone)]
 struct UiContext {
     show_request_sidebar: Signal<bool>,
     show_events_sidebar: Signal<bool>,
+    show_metrics_sidebar: Signal<bool>,
+    show_transactions_sidebar: Signal<bool>,
+    show_simulation_sidebar: Signal<bool>,
+    show_notifications_sidebar: Signal<bool>,
     show_plugin_registry_sidebar: Signal<bool>,
-    selected_page: Signal<Option<PageId>>,
+    show_state_sidebar: Signal<bool>,
 
     new_events: Signal<bool>,
 }
@@ -43,8 +62,12 @@ fn app() -> Element {
     let ui_signals = UiContext {
         show_request_sidebar: use_signal(|| false),
         show_events_sidebar: use_signal(|| false),
+        show_metrics_sidebar: use_signal(|| false),
+        show_transactions_sidebar: use_signal(|| false),
+        show_simulation_sidebar: use_signal(|| false),
+        show_notifications_sidebar: use_signal(|| false),
         show_plugin_registry_sidebar: use_signal(|| false),
-        selected_page: use_signal(|| None),
+        show_state_sidebar: use_signal(|| false),
         new_events: use_signal(|| false),
     };
     use_context_provider(|| ui_signals);
@@ -90,6 +113,11 @@ fn app() -> Element {
             toast_container {}
             requests_modal {}
             events_modal {}
+            metrics_modal {}
+            transactions_modal {}
+            simulation_modal {}
+            state_modal {}
+            notifications_modal {}
             plugins_modal {}
             events_toast_handler {}
             div { class: "drawer md:drawer-open bg-base-300",
@@ -132,12 +160,27 @@ fn app() -> Element {
 
 #[component]
 fn sidebar_component() -> Element {
-    let ctx: HostContext = use_context();
+    let mut ctx: HostContext = use_context();
+    let toast_ctx: ToastContext = use_context();
     let mut show_requests = use_context::<UiContext>().show_request_sidebar;
     let mut show_events = use_context::<UiContext>().show_events_sidebar;
-    let mut selected_page = use_context::<UiContext>().selected_page;
+    let mut show_metrics = use_context::<UiContext>().show_metrics_sidebar;
+    let mut show_transactions = use_context::<UiContext>().show_transactions_sidebar;
+    let mut show_simulation = use_context::<UiContext>().show_simulation_sidebar;
+    let mut show_notifications = use_context::<UiContext>().show_notifications_sidebar;
     let mut show_plugin_registry = use_context::<UiContext>().show_plugin_registry_sidebar;
+    let mut show_state = use_context::<UiContext>().show_state_sidebar;
     let new_events = use_context::<UiContext>().new_events;
+    let pending_transactions = ctx
+        .transactions()
+        .iter()
+        .filter(|tx| tx.status == TransactionStatus::Pending)
+        .count();
+    let mut editing_entity = use_signal(|| None::<EntityId>);
+    let mut entity_label = use_signal(String::new);
+    let mut entity_icon = use_signal(String::new);
+    let mut editing_limits = use_signal(|| None::<PluginId>);
+    let mut memory_mib = use_signal(String::new);
 
     let named_pages = use_memo(move || {
         let pages = ctx.page_ids();
@@ -148,8 +191,9 @@ fn sidebar_component() -> Element {
                     .entity_plugin(EntityId::Page(id))
                     .map(|p| p.name().to_string())
                     .unwrap_or("Unknown Plugin".to_string());
+                let title = ctx.page_title(id).unwrap_or_else(|| id.to_string());
 
-                (id, name)
+                (id, name, title)
             })
             .collect();
 
@@ -187,25 +231,26 @@ fn sidebar_component() -> Element {
                 h1 { class: "menu-title text-2xl text-primary ps-0 font-heading", "Lodgelock Demo" }
             }
             states_dropdown {}
+            chain_switcher {}
             div { class: "divider" }
             h2 { class: "menu-title", "Pages" }
             ul {
                 li { key: "home",
                     button {
-                        class: if selected_page.read().is_none() { "menu-active" },
+                        class: if ctx.current_page().is_none() { "menu-active" },
                         class: "py-1.5",
-                        onclick: move |_| selected_page.set(None),
+                        onclick: move |_| ctx.open_page(None),
                         "Home"
                     }
                 }
-                for (page_id , plugin_name) in named_pages() {
+                for (page_id , plugin_name , title) in named_pages() {
                     li { key: "page-{page_id}",
                         button {
                             class: "py-1.5 tooltip",
-                            class: if selected_page.read().as_ref() == Some(&page_id) { "menu-active" },
+                            class: if ctx.current_page() == Some(page_id) { "menu-active" },
                             "data-tip": "plugin: {plugin_name}",
-                            onclick: move |_| selected_page.set(Some(page_id)),
-                            "{page_id}"
+                            onclick: move |_| ctx.open_page(Some(page_id)),
+                            "{title}"
                         }
                     }
                 }
@@ -213,19 +258,210 @@ fn sidebar_component() -> Element {
             h2 { class: "menu-title", "Entities" }
             ul { class: "px-3",
                 for (entity_id , plugin_name) in named_entities() {
-                    p {
-                        key: "entity-{entity_id}",
-                        class: "py-1.5 w-full tooltip",
-                        "data-tip": "plugin: {plugin_name}",
-                        "{entity_id}"
+                    if *editing_entity.read() == Some(entity_id) {
+                        div { key: "entity-{entity_id}", class: "flex items-center gap-1 py-1",
+                            input {
+                                class: "input input-xs w-10",
+                                placeholder: "🙂",
+                                value: "{entity_icon}",
+                                oninput: move |e| entity_icon.set(e.value()),
+                            }
+                            input {
+                                class: "input input-xs grow",
+                                placeholder: "Label",
+                                value: "{entity_label}",
+                                oninput: move |e| entity_label.set(e.value()),
+                            }
+                            button {
+                                class: "btn btn-ghost btn-xs",
+                                onclick: move |_| {
+                                    let icon = Some(entity_icon.read().clone());
+                                    ctx.set_entity_label(entity_id, entity_label.read().clone(), icon);
+                                    editing_entity.set(None);
+                                },
+                                "Save"
+                            }
+                        }
+                    } else {
+                        div { key: "entity-{entity_id}", class: "flex items-center gap-1",
+                            p {
+                                class: "py-1.5 w-full tooltip",
+                                "data-tip": "{entity_id} (plugin: {plugin_name})",
+                                "{ctx.entity_display_name(entity_id)}"
+                            }
+                            button {
+                                class: "btn btn-ghost btn-xs",
+                                onclick: move |_| {
+                                    let label = ctx.entity_label(entity_id).unwrap_or_default();
+                                    entity_label.set(label.label);
+                                    entity_icon.set(label.icon.unwrap_or_default());
+                                    editing_entity.set(Some(entity_id));
+                                },
+                                "Rename"
+                            }
+                            if let EntityId::Vault(vault_id) = entity_id {
+                                button {
+                                    class: "btn btn-ghost btn-xs",
+                                    onclick: move |_| export_vault_history(vault_id, ExportFormat::Csv),
+                                    "CSV"
+                                }
+                                button {
+                                    class: "btn btn-ghost btn-xs",
+                                    onclick: move |_| export_vault_history(vault_id, ExportFormat::Json),
+                                    "JSON"
+                                }
+                            }
+                        }
                     }
                 }
             }
             h2 { class: "menu-title", "Plugins" }
             ul { class: "px-3",
                 for plugin in ctx.plugins() {
-                    p { key: "plugin-{plugin.id()}", class: "py-1.5 w-full",
-                        "{plugin.name()} [{plugin.id()}]"
+                    {
+                        let plugin_id = plugin.id();
+                        let usage = ctx.state_usage(plugin_id);
+                        let used = format_bytes(usage.used);
+                        let quota = format_bytes(usage.quota);
+                        let limits = ctx.resource_limits(plugin_id);
+                        let memory = format_bytes(limits.max_memory_bytes as usize);
+                        rsx! {
+                            if *editing_limits.read() == Some(plugin_id) {
+                                div { key: "limits-{plugin_id}", class: "flex items-center gap-1 py-1",
+                                    input {
+                                        class: "input input-xs w-20",
+                                        r#type: "number",
+                                        placeholder: "Memory MiB",
+                                        value: "{memory_mib}",
+                                        oninput: move |e| memory_mib.set(e.value()),
+                                    }
+                                    button {
+                                        class: "btn btn-ghost btn-xs",
+                                        onclick: move |_| {
+                                            let Ok(mib) = memory_mib.read().trim().parse::<u64>() else {
+                                                toast_ctx.push("Memory must be a whole number of MiB", ToastKind::Error);
+                                                return;
+                                            };
+                                            let limits = ResourceLimits {
+                                                max_memory_bytes: mib.saturating_mul(1024 * 1024),
+                                            };
+                                            ctx.set_resource_limits(plugin_id, Some(limits));
+                                            editing_limits.set(None);
+                                        },
+                                        "Save"
+                                    }
+                                    button {
+                                        class: "btn btn-ghost btn-xs",
+                                        onclick: move |_| {
+                                            ctx.set_resource_limits(plugin_id, None);
+                                            editing_limits.set(None);
+                                        },
+                                        "Default"
+                                    }
+                                }
+                            }
+                            div {
+                                key: "plugin-{plugin_id}",
+                                class: "py-1.5 w-full flex items-center justify-between gap-2",
+                                div {
+                                    p { "{plugin.name()} [{plugin_id}]" }
+                                    p { class: "text-xs opacity-60", "Storage: {used} / {quota}" }
+                                    p { class: "text-xs opacity-60", "Memory cap: {memory}" }
+                                }
+                                div { class: "flex gap-1",
+                                    button {
+                                        class: "btn btn-ghost btn-xs tooltip",
+                                        "data-tip": "Largest memory the plugin may grow to, applied on reload",
+                                        onclick: move |_| {
+                                            let limits = ctx.resource_limits(plugin_id);
+                                            memory_mib.set((limits.max_memory_bytes / (1024 * 1024)).to_string());
+                                            editing_limits.set(Some(plugin_id));
+                                        },
+                                        "Limits"
+                                    }
+                                    button {
+                                        class: "btn btn-ghost btn-xs tooltip",
+                                        "data-tip": "Loads the latest build, keeping its entities and data",
+                                        onclick: move |_| async move {
+                                            if let Err(e) = ctx.reload_plugin(plugin_id).await {
+                                                toast_ctx.push(format!("Failed to reload plugin: {:?}", e), ToastKind::Error);
+                                            }
+                                        },
+                                        "Reload"
+                                    }
+                                    button {
+                                        class: "btn btn-ghost btn-xs tooltip",
+                                        "data-tip": "Keeps the plugin's stored data",
+                                        onclick: move |_| async move {
+                                            ctx.remove_plugin(plugin_id).await;
+                                        },
+                                        "Remove"
+                                    }
+                                    button {
+                                        class: "btn btn-ghost btn-xs text-error tooltip",
+                                        "data-tip": "Also deletes the plugin's stored data",
+                                        onclick: move |_| async move {
+                                            ctx.uninstall_plugin(plugin_id).await;
+                                        },
+                                        "Uninstall"
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+            if !ctx.permission_grants().is_empty() || !ctx.session_grants().is_empty() {
+                h2 { class: "menu-title", "Permissions" }
+                ul { class: "px-3",
+                    for session in ctx.session_grants() {
+                        {
+                            let plugin_name = ctx
+                                .plugin(session.plugin_id)
+                                .map(|p| p.name().to_string())
+                                .unwrap_or("Unknown Plugin".to_string());
+                            let session_id = session.id;
+                            let expires = session.expires.format("%H:%M");
+                            let limit = session
+                                .limit
+                                .as_ref()
+                                .map(|l| format!(", {} of {} left", l.max - l.spent, l.asset))
+                                .unwrap_or_default();
+                            rsx! {
+                                div {
+                                    key: "session-{session_id}",
+                                    class: "py-1.5 w-full flex items-center justify-between gap-2",
+                                    p {
+                                        "{plugin_name} may call {session.method} on {session.entity_id} until {expires}{limit}"
+                                    }
+                                    button {
+                                        class: "btn btn-ghost btn-xs text-error",
+                                        onclick: move |_| ctx.revoke_session_grant(session_id),
+                                        "Revoke"
+                                    }
+                                }
+                            }
+                        }
+                    }
+                    for (plugin_id , entity_id) in ctx.permission_grants() {
+                        {
+                            let plugin_name = ctx
+                                .plugin(plugin_id)
+                                .map(|p| p.name().to_string())
+                                .unwrap_or("Unknown Plugin".to_string());
+                            rsx! {
+                                div {
+                                    key: "grant-{plugin_id}-{entity_id}",
+                                    class: "py-1.5 w-full flex items-center justify-between gap-2",
+                                    p { "{plugin_name} may use {entity_id}" }
+                                    button {
+                                        class: "btn btn-ghost btn-xs text-error",
+                                        onclick: move |_| ctx.revoke_permission(plugin_id, entity_id),
+                                        "Revoke"
+                                    }
+                                }
+                            }
+                        }
                     }
                 }
             }
@@ -245,6 +481,19 @@ fn sidebar_component() -> Element {
                         }
                     }
                 }
+                li {
+                    button {
+                        class: "indicator w-full justify-between",
+                        onclick: move |_| show_notifications.set(true),
+                        "Notifications"
+
+                        if !ctx.notifications().is_empty() {
+                            span { class: "indicator-item badge badge-secondary badge-xl mr-4",
+                                "{ctx.notifications().len()}"
+                            }
+                        }
+                    }
+                }
                 li {
                     button {
                         class: "indicator w-full justify-between",
@@ -256,9 +505,47 @@ fn sidebar_component() -> Element {
                         }
                     }
                 }
+                li {
+                    button { onclick: move |_| show_metrics.set(true), "Metrics" }
+                }
+                li {
+                    button {
+                        class: "indicator w-full justify-between",
+                        onclick: move |_| show_transactions.set(true),
+                        "Transactions"
+
+                        if pending_transactions > 0 {
+                            span { class: "indicator-item badge badge-secondary badge-xl mr-4",
+                                "{pending_transactions}"
+                            }
+                        }
+                    }
+                }
+                li {
+                    button {
+                        class: "indicator w-full justify-between",
+                        onclick: move |_| show_simulation.set(true),
+                        "Simulation"
+
+                        if ctx.simulation_fork().is_some() {
+                            span { class: "indicator-item badge badge-warning badge-xl mr-4", "On" }
+                        }
+                    }
+                }
+                li {
+                    button { onclick: move |_| export_audit_log(), "Export Audit Log" }
+                }
+                if ctx.is_keystore_unlocked() {
+                    li {
+                        button { onclick: move |_| ctx.lock_keystore(), "Lock Keystore" }
+                    }
+                }
                 li {
                     button { onclick: move |_| show_plugin_registry.set(true), "Load Plugin" }
                 }
+                li {
+                    button { onclick: move |_| show_state.set(true), "Host State" }
+                }
                 li {
                     a {
                         href: "https://github.com/Robert-MacWha/lodgelock",
@@ -266,19 +553,6 @@ fn sidebar_component() -> Element {
                         "Github"
                     }
                 }
-                        // li {
-            //     button {
-            //         onclick: move |_| {
-            //             let state = ctx.state();
-            //             if let Ok(data) = serde_json::to_vec_pretty(&state) {
-            //                 if let Err(e) = download_bytes(&data, "state.json", "application/json") {
-            //                     error!("Failed to download state: {:?}", e);
-            //                 }
-            //             }
-            //         },
-            //         "Download State"
-            //     }
-            // }
             }
         }
     }
@@ -286,13 +560,13 @@ fn sidebar_component() -> Element {
 
 #[component]
 fn main_component() -> Element {
-    let ctx: HostContext = use_context();
-    let selected_page = use_context::<UiContext>().selected_page;
+    let mut ctx: HostContext = use_context();
+    let current_page = ctx.current_page();
     let pages = ctx.page_ids();
 
     //? If a page is selected, only show that page
-    let pages = match selected_page.read().as_ref() {
-        Some(page_id) => vec![*page_id],
+    let pages = match current_page {
+        Some(page_id) => vec![page_id],
         None => pages,
     };
 
@@ -310,7 +584,15 @@ fn main_component() -> Element {
             }
         }
 
-        div { class: if selected_page.read().is_none() { "columns-1 ml:columns-2 2xl:columns-3 gap-4 space-y-4" },
+        if ctx.can_navigate_back() {
+            button {
+                class: "btn btn-ghost btn-sm mb-2",
+                onclick: move |_| ctx.navigate_back(),
+                "Back"
+            }
+        }
+
+        div { class: if current_page.is_none() { "columns-1 ml:columns-2 2xl:columns-3 gap-4 space-y-4" },
             for page_id in pages {
                 {
                     let plugin = ctx.entity_plugin(EntityId::Page(page_id));
@@ -389,6 +671,17 @@ fn events_modal() -> Element {
     let ctx: HostContext = use_context();
     let mut show_events = use_context::<UiContext>().show_events_sidebar;
     let mut new_events = use_context::<UiContext>().new_events;
+    let mut plugin_filter = use_signal(|| None::<PluginId>);
+    let mut level_filter = use_signal(|| None::<NotifyLevel>);
+
+    let mut filter = EventFilter::new();
+    if let Some(plugin_id) = *plugin_filter.read() {
+        filter = filter.with_plugin(plugin_id);
+    }
+    if let Some(level) = level_filter.read().clone() {
+        filter = filter.with_min_level(level);
+    }
+    let events = ctx.query_events(&filter);
 
     let modal_class = if *show_events.read() {
         new_events.set(false);
@@ -401,14 +694,51 @@ fn events_modal() -> Element {
             div { class: "modal-box bg-base-200 w-md flex flex-col h-full",
                 div { class: "flex-none",
                     h3 { class: "font-bold text-lg", "Events" }
+                    div { class: "flex gap-2 mt-2",
+                        select {
+                            class: "select select-sm flex-1",
+                            onchange: move |e| {
+                                let plugin_id = ctx
+                                    .plugin_ids()
+                                    .into_iter()
+                                    .find(|id| id.to_string() == e.value());
+                                plugin_filter.set(plugin_id);
+                            },
+                            option { value: "", "All plugins" }
+                            for plugin in ctx.plugins() {
+                                option {
+                                    key: "{plugin.id()}",
+                                    value: "{plugin.id()}",
+                                    selected: *plugin_filter.read() == Some(plugin.id()),
+                                    "{plugin.name()}"
+                                }
+                            }
+                        }
+                        select {
+                            class: "select select-sm flex-1",
+                            onchange: move |e| {
+                                let level = match e.value().as_str() {
+                                    "info" => Some(NotifyLevel::Info),
+                                    "warn" => Some(NotifyLevel::Warn),
+                                    "error" => Some(NotifyLevel::Error),
+                                    _ => None,
+                                };
+                                level_filter.set(level);
+                            },
+                            option { value: "", "All levels" }
+                            option { value: "info", "Info and above" }
+                            option { value: "warn", "Warnings and above" }
+                            option { value: "error", "Errors" }
+                        }
+                    }
                     div { class: "divider" }
                 }
-                if ctx.events().is_empty() {
-                    p { "Load a plugin to see events" }
+                if events.is_empty() {
+                    p { "No matching events" }
                 }
 
                 ul { class: "flex-1 overflow-auto min-h-0",
-                    for event in ctx.events() {
+                    for event in events {
                         {
                             let ts = event.timestamp.format("%H:%M:%S%.3f");
                             let message: String = event.message.clone();
@@ -439,10 +769,464 @@ fn events_modal() -> Element {
 }
 
 #[component]
-fn plugins_modal() -> Element {
+fn metrics_modal() -> Element {
+    let mut ctx: HostContext = use_context();
+    let mut show_metrics = use_context::<UiContext>().show_metrics_sidebar;
+
+    //? Calls don't notify observers, so the metrics are re-read on each open
+    let metrics = use_memo(move || {
+        let _ = show_metrics.read();
+        ctx.metrics()
+    });
+
+    let modal_class = if *show_metrics.read() {
+        "modal-open"
+    } else {
+        ""
+    };
+    rsx! {
+        dialog { class: "modal modal-start {modal_class}",
+            div { class: "modal-box bg-base-200 w-md flex flex-col h-full",
+                div { class: "flex-none flex items-center justify-between",
+                    h3 { class: "font-bold text-lg", "Metrics" }
+                    button {
+                        class: "btn btn-ghost btn-xs",
+                        onclick: move |_| ctx.reset_metrics(),
+                        "Reset"
+                    }
+                }
+                div { class: "divider" }
+                if metrics.read().is_empty() {
+                    p { "No calls recorded" }
+                }
+
+                div { class: "flex-1 overflow-auto min-h-0",
+                    table { class: "table table-xs",
+                        thead {
+                            tr {
+                                th { "Plugin" }
+                                th { "Method" }
+                                th { "Calls" }
+                                th { "Errors" }
+                                th { "Mean" }
+                                th { "Max" }
+                            }
+                        }
+                        tbody {
+                            for (plugin_id , method , m) in metrics.read().iter().cloned() {
+                                {
+                                    let plugin_name = ctx
+                                        .plugin(plugin_id)
+                                        .map(|p| p.name().to_string())
+                                        .unwrap_or("Unknown Plugin".to_string());
+                                    let error_rate = m.error_rate() * 100.0;
+                                    let mean_ms = m.mean_ms();
+                                    rsx! {
+                                        tr { key: "{plugin_id}-{method}",
+                                            td { style: "color: {plugin_color(&plugin_name)}", "{plugin_name}" }
+                                            td { class: "font-mono", "{method}" }
+                                            td { "{m.calls}" }
+                                            td { "{m.errors} ({error_rate:.0}%)" }
+                                            td { "{mean_ms:.0} ms" }
+                                            td { "{m.max_ms} ms" }
+                                        }
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+            form {
+                method: "dialog",
+                class: "modal-backdrop",
+                onmousedown: move |_| show_metrics.set(false),
+                button { "Close" }
+            }
+        }
+    }
+}
+
+#[component]
+fn transactions_modal() -> Element {
+    let mut ctx: HostContext = use_context();
+    let mut show_transactions = use_context::<UiContext>().show_transactions_sidebar;
+    let transactions = ctx.transactions();
+
+    let modal_class = if *show_transactions.read() {
+        "modal-open"
+    } else {
+        ""
+    };
+    rsx! {
+        dialog { class: "modal modal-start {modal_class}",
+            div { class: "modal-box bg-base-200 w-md flex flex-col h-full",
+                div { class: "flex-none flex items-center justify-between",
+                    h3 { class: "font-bold text-lg", "Transactions" }
+                    button {
+                        class: "btn btn-ghost btn-xs",
+                        onclick: move |_| ctx.clear_finished_transactions(),
+                        "Clear finished"
+                    }
+                }
+                div { class: "divider" }
+                if transactions.is_empty() {
+                    p { "No transactions sent" }
+                }
+
+                div { class: "flex-1 overflow-auto min-h-0 flex flex-col gap-2",
+                    for tx in transactions.into_iter().rev() {
+                        {
+                            let plugin_name = ctx
+                                .plugin(tx.plugin_id)
+                                .map(|p| p.name().to_string())
+                                .unwrap_or("Unknown Plugin".to_string());
+                            let (status, badge) = match tx.status {
+                                TransactionStatus::Pending => ("Pending".to_string(), "badge-info"),
+                                TransactionStatus::Confirmed { block_number } => {
+                                    (format!("Confirmed in {}", block_number), "badge-success")
+                                }
+                                TransactionStatus::Failed { block_number } => {
+                                    (format!("Failed in {}", block_number), "badge-error")
+                                }
+                                TransactionStatus::Replaced => ("Replaced".to_string(), "badge-warning"),
+                            };
+                            let submitted = tx.submitted.format("%H:%M:%S").to_string();
+                            rsx! {
+                                div { key: "{tx.hash}", class: "card bg-base-100 p-2",
+                                    div { class: "flex items-center justify-between",
+                                        span { style: "color: {plugin_color(&plugin_name)}", "{plugin_name}" }
+                                        span { class: "badge {badge}", "{status}" }
+                                    }
+                                    p { class: "font-mono text-xs truncate", "{tx.hash}" }
+                                    p { class: "text-xs opacity-60", "Sent {submitted}" }
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+            form {
+                method: "dialog",
+                class: "modal-backdrop",
+                onmousedown: move |_| show_transactions.set(false),
+                button { "Close" }
+            }
+        }
+    }
+}
+
+#[component]
+fn simulation_modal() -> Element {
+    let mut ctx: HostContext = use_context();
+    let toast_ctx: ToastContext = use_context();
+    let mut show_simulation = use_context::<UiContext>().show_simulation_sidebar;
+    let fork = ctx.simulation_fork();
+    let transactions = ctx.simulated_transactions();
+    let providers: Vec<EthProviderId> = ctx
+        .entity_ids()
+        .into_iter()
+        .filter_map(|id| match id {
+            EntityId::EthProvider(provider_id) => Some(provider_id),
+            _ => None,
+        })
+        .collect();
+    let mut selected = use_signal(|| None::<EthProviderId>);
+
+    let modal_class = if *show_simulation.read() {
+        "modal-open"
+    } else {
+        ""
+    };
+    rsx! {
+        dialog { class: "modal modal-start {modal_class}",
+            div { class: "modal-box bg-base-200 w-md flex flex-col h-full",
+                h3 { class: "flex-none font-bold text-lg", "Simulation" }
+                p { class: "text-sm opacity-60",
+                    "While simulating, transactions are applied to a fork provider instead of being broadcast."
+                }
+                div { class: "divider" }
+                if let Some(fork) = fork {
+                    div { class: "flex items-center justify-between",
+                        span { "Simulating on {ctx.entity_display_name(EntityId::EthProvider(fork))}" }
+                        button {
+                            class: "btn btn-sm",
+                            onclick: move |_| ctx.disable_simulation(),
+                            "Stop"
+                        }
+                    }
+                } else {
+                    fieldset { class: "fieldset",
+                        label { class: "label", "Fork provider" }
+                        select {
+                            class: "select w-full",
+                            onchange: move |e| selected.set(e.value().parse::<EthProviderId>().ok()),
+                            option { value: "", selected: selected.read().is_none(), "Select a provider" }
+                            for provider_id in providers {
+                                option {
+                                    key: "fork-{provider_id}",
+                                    value: "{provider_id}",
+                                    selected: *selected.read() == Some(provider_id),
+                                    "{ctx.entity_display_name(EntityId::EthProvider(provider_id))}"
+                                }
+                            }
+                        }
+                        button {
+                            class: "btn btn-primary btn-sm",
+                            disabled: selected.read().is_none(),
+                            onclick: move |_| {
+                                let Some(fork) = *selected.read() else {
+                                    return;
+                                };
+                                if let Err(e) = ctx.enable_simulation(fork) {
+                                    toast_ctx.push(format!("Failed to start simulation: {}", DomainError::describe(&e)), ToastKind::Error);
+                                }
+                            },
+                            "Start"
+                        }
+                    }
+                }
+                div { class: "divider" }
+                if transactions.is_empty() {
+                    p { "No transactions simulated" }
+                }
+
+                div { class: "flex-1 overflow-auto min-h-0 flex flex-col gap-2",
+                    for tx in transactions.into_iter().rev() {
+                        {
+                            let plugin_name = ctx
+                                .plugin(tx.plugin_id)
+                                .map(|p| p.name().to_string())
+                                .unwrap_or("Unknown Plugin".to_string());
+                            let (status, badge) = match &tx.receipt {
+                                Some(receipt) if receipt.status() => {
+                                    (format!("Succeeded, {} gas", receipt.gas_used), "badge-success")
+                                }
+                                Some(receipt) => (format!("Reverted, {} gas", receipt.gas_used), "badge-error"),
+                                None => ("No receipt".to_string(), "badge-ghost"),
+                            };
+                            let transfers = tx.transfers();
+                            let submitted = tx.submitted.format("%H:%M:%S").to_string();
+                            rsx! {
+                                div { key: "{tx.hash}", class: "card bg-base-100 p-2",
+                                    div { class: "flex items-center justify-between",
+                                        span { style: "color: {plugin_color(&plugin_name)}", "{plugin_name}" }
+                                        span { class: "badge {badge}", "{status}" }
+                                    }
+                                    p { class: "font-mono text-xs truncate", "{tx.hash}" }
+                                    for (i, transfer) in transfers.iter().enumerate() {
+                                        p { key: "{i}", class: "font-mono text-xs break-all",
+                                            "{transfer.value} of {transfer.token}: {transfer.from} → {transfer.to}"
+                                        }
+                                    }
+                                    p { class: "text-xs opacity-60", "Simulated {submitted}" }
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+            form {
+                method: "dialog",
+                class: "modal-backdrop",
+                onmousedown: move |_| show_simulation.set(false),
+                button { "Close" }
+            }
+        }
+    }
+}
+
+#[component]
+fn state_modal() -> Element {
     let ctx: HostContext = use_context();
+    let toast_ctx: ToastContext = use_context();
+    let mut show_state = use_context::<UiContext>().show_state_sidebar;
+    let mut export_password = use_signal(String::new);
+    let mut import_password = use_signal(String::new);
+    let mut import_bytes = use_signal(|| None::<Vec<u8>>);
+
+    let modal_class = if *show_state.read() { "modal-open" } else { "" };
+    let import_encrypted = import_bytes
+        .read()
+        .as_ref()
+        .is_some_and(|b| state_crypto::is_encrypted(b));
+    rsx! {
+        dialog { class: "modal modal-start {modal_class}",
+            div { class: "modal-box bg-base-200 w-md flex flex-col gap-2",
+                h3 { class: "font-bold text-lg", "Host State" }
+                div { class: "divider" }
+                h4 { class: "font-bold", "Save" }
+                p { class: "text-sm opacity-60",
+                    "Saved state includes every plugin's stored data. Set a password to encrypt it."
+                }
+                input {
+                    class: "input input-sm w-full",
+                    r#type: "password",
+                    placeholder: "Password (optional)",
+                    value: "{export_password}",
+                    oninput: move |e| export_password.set(e.value()),
+                }
+                button {
+                    class: "btn btn-primary btn-sm",
+                    onclick: move |_| {
+                        let password = export_password.read().clone();
+                        match save_state(&ctx.state(), &password) {
+                            Ok(()) => export_password.set(String::new()),
+                            Err(e) => toast_ctx.push(format!("Failed to save state: {}", e), ToastKind::Error),
+                        }
+                    },
+                    "Save Host State"
+                }
+                div { class: "divider" }
+                h4 { class: "font-bold", "Load" }
+                input {
+                    class: "file-input file-input-sm w-full",
+                    r#type: "file",
+                    onchange: move |e| async move {
+                        let Some(file) = e.files().into_iter().next() else {
+                            return;
+                        };
+                        match file.read_bytes().await {
+                            Ok(bytes) => import_bytes.set(Some(bytes.to_vec())),
+                            Err(e) => toast_ctx.push(format!("Failed to read file: {:?}", e), ToastKind::Error),
+                        }
+                    },
+                }
+                if import_encrypted {
+                    input {
+                        class: "input input-sm w-full",
+                        r#type: "password",
+                        placeholder: "Password",
+                        value: "{import_password}",
+                        oninput: move |e| import_password.set(e.value()),
+                    }
+                }
+                button {
+                    class: "btn btn-sm",
+                    disabled: import_bytes.read().is_none(),
+                    onclick: move |_| async move {
+                        let Some(bytes) = import_bytes.read().clone() else {
+                            return;
+                        };
+                        let password = import_password.read().clone();
+                        match load_state_bytes(&bytes, &password).await {
+                            Ok(()) => {
+                                import_bytes.set(None);
+                                import_password.set(String::new());
+                                show_state.set(false);
+                            }
+                            Err(e) => toast_ctx.push(format!("Failed to load state: {}", e), ToastKind::Error),
+                        }
+                    },
+                    "Load Host State"
+                }
+            }
+            form {
+                method: "dialog",
+                class: "modal-backdrop",
+                onmousedown: move |_| show_state.set(false),
+                button { "Close" }
+            }
+        }
+    }
+}
+
+#[component]
+fn notifications_modal() -> Element {
+    let mut ctx: HostContext = use_context();
+    let toast_ctx: ToastContext = use_context();
+    let mut show_notifications = use_context::<UiContext>().show_notifications_sidebar;
+
+    let modal_class = if *show_notifications.read() {
+        "modal-open"
+    } else {
+        ""
+    };
+
+    rsx! {
+        dialog { class: "modal modal-start {modal_class}",
+            div { class: "modal-box bg-base-200 w-md flex flex-col h-full",
+                div { class: "flex-none",
+                    h3 { class: "font-bold text-lg", "Notifications" }
+                    div { class: "divider" }
+                }
+                if ctx.notifications().is_empty() {
+                    p { "No notifications" }
+                }
+
+                div { class: "flex-1 overflow-auto min-h-0 flex flex-col gap-2",
+                    for entry in ctx.notifications().into_iter().rev() {
+                        {
+                            let ts = entry.timestamp.format("%H:%M:%S");
+                            let alert_class = match entry.notification.level {
+                                NotifyLevel::Error => "alert-error",
+                                NotifyLevel::Warn => "alert-warning",
+                                _ => "alert-info",
+                            };
+                            let entry_id = entry.id;
+                            rsx! {
+                                div { key: "{entry.id}", class: "alert alert-soft {alert_class} flex flex-col items-start",
+                                    div { class: "text-xs opacity-50",
+                                        "[{ts}] "
+                                        span { style: "color: {plugin_color(&entry.plugin)}", "[{entry.plugin}]" }
+                                    }
+                                    span { "{entry.notification.message}" }
+                                    div { class: "flex gap-2",
+                                        for action in entry.notification.actions.clone() {
+                                            button {
+                                                key: "{action.id}",
+                                                class: "btn btn-sm btn-primary",
+                                                onclick: move |_| {
+                                                    let action = action.clone();
+                                                    async move {
+                                                        show_notifications.set(false);
+                                                        ctx.open_page(Some(action.page_id));
+                                                        ctx.dismiss_notification(entry_id);
+                                                        if let Err(e) = ctx
+                                                            .page_on_update(
+                                                                action.page_id,
+                                                                PageEvent::NotificationAction(action.id.clone()),
+                                                            )
+                                                            .await
+                                                        {
+                                                            toast_ctx.push(format!("Error handling action: {}", DomainError::describe(&e)), ToastKind::Error);
+                                                        }
+                                                    }
+                                                },
+                                                "{action.label}"
+                                            }
+                                        }
+                                        button {
+                                            class: "btn btn-sm btn-ghost",
+                                            onclick: move |_| ctx.dismiss_notification(entry_id),
+                                            "Dismiss"
+                                        }
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+            form {
+                method: "dialog",
+                class: "modal-backdrop",
+                onmousedown: move |_| show_notifications.set(false),
+                button { "Close" }
+            }
+        }
+    }
+}
+
+#[component]
+fn plugins_modal() -> Element {
+    let mut ctx: HostContext = use_context();
     let mut show_plugins = use_context::<UiContext>().show_plugin_registry_sidebar;
     let toast_ctx: ToastContext = use_context();
+    let mut install_url = use_signal(String::new);
+    let mut install_sha256 = use_signal(String::new);
+    let mut publisher_name = use_signal(String::new);
+    let mut publisher_key = use_signal(String::new);
 
     let loaded_plugins = ctx.plugins();
 
@@ -523,6 +1307,107 @@ fn plugins_modal() -> Element {
                             }
                         }
                     }
+
+                    div { class: "divider" }
+                    h3 { class: "font-bold", "Install from URL" }
+                    div { class: "flex flex-col gap-2 pt-2",
+                        input {
+                            class: "input input-sm w-full",
+                            placeholder: "https://example.com/plugin.wasm",
+                            value: "{install_url}",
+                            oninput: move |e| install_url.set(e.value()),
+                        }
+                        input {
+                            class: "input input-sm w-full font-mono",
+                            placeholder: "SHA-256 (hex)",
+                            value: "{install_sha256}",
+                            oninput: move |e| install_sha256.set(e.value()),
+                        }
+                        button {
+                            class: "btn btn-sm",
+                            onclick: move |_| async move {
+                                let url = install_url.read().trim().to_string();
+                                let sha256 = hex::decode(install_sha256.read().trim())
+                                    .ok()
+                                    .and_then(|digest| <[u8; 32]>::try_from(digest).ok());
+                                let Some(sha256) = sha256 else {
+                                    toast_ctx.push("SHA-256 must be 32 hex-encoded bytes", ToastKind::Error);
+                                    return;
+                                };
+
+                                show_plugins.set(false);
+                                match ctx.install_plugin_from_url(&url, sha256).await {
+                                    Ok(id) => {
+                                        info!("Installed plugin {} from {}", id, url);
+                                        toast_ctx.push(format!("Installed plugin from {}", url), ToastKind::Info);
+                                        install_url.set(String::new());
+                                        install_sha256.set(String::new());
+                                    }
+                                    Err(e) => {
+                                        error!("Failed to install plugin from {}: {:?}", url, e);
+                                        toast_ctx.push(format!("Failed to install plugin: {}", e), ToastKind::Error);
+                                    }
+                                }
+                            },
+                            "Install"
+                        }
+                    }
+
+                    div { class: "divider" }
+                    h3 { class: "font-bold", "Trusted Publishers" }
+                    ul {
+                        for publisher in ctx.trusted_publishers() {
+                            {
+                                let key = publisher.key;
+                                let key_hex = hex::encode(key);
+                                rsx! {
+                                    div {
+                                        key: "publisher-{key_hex}",
+                                        class: "py-1.5 w-full flex items-center justify-between gap-2",
+                                        div { class: "min-w-0",
+                                            p { "{publisher.name}" }
+                                            p { class: "font-mono text-xs truncate", "{key_hex}" }
+                                        }
+                                        button {
+                                            class: "btn btn-ghost btn-xs text-error",
+                                            onclick: move |_| ctx.untrust_publisher(key),
+                                            "Remove"
+                                        }
+                                    }
+                                }
+                            }
+                        }
+                    }
+                    div { class: "flex gap-2 pt-2",
+                        input {
+                            class: "input input-sm w-1/3",
+                            placeholder: "Name",
+                            value: "{publisher_name}",
+                            oninput: move |e| publisher_name.set(e.value()),
+                        }
+                        input {
+                            class: "input input-sm grow font-mono",
+                            placeholder: "Public key (hex)",
+                            value: "{publisher_key}",
+                            oninput: move |e| publisher_key.set(e.value()),
+                        }
+                        button {
+                            class: "btn btn-sm",
+                            onclick: move |_| {
+                                let key = hex::decode(publisher_key.read().trim())
+                                    .ok()
+                                    .and_then(|key| <[u8; 32]>::try_from(key).ok());
+                                let Some(key) = key else {
+                                    toast_ctx.push("Public key must be 32 hex-encoded bytes", ToastKind::Error);
+                                    return;
+                                };
+                                ctx.trust_publisher(&publisher_name.read(), key);
+                                publisher_name.set(String::new());
+                                publisher_key.set(String::new());
+                            },
+                            "Add"
+                        }
+                    }
                 }
             }
             form {
@@ -535,6 +1420,123 @@ fn plugins_modal() -> Element {
     )
 }
 
+fn export_vault_history(vault_id: VaultId, format: ExportFormat) {
+    let ctx: HostContext = consume_context();
+    let data = match ctx.export_history(vault_id, format) {
+        Ok(data) => data,
+        Err(e) => {
+            error!("Failed to export history for {}: {:?}", vault_id, e);
+            return;
+        }
+    };
+
+    let filename = format!("{}-history.{}", vault_id, format.extension());
+    if let Err(e) = download_bytes(data.as_bytes(), &filename, format.mime_type()) {
+        error!("Failed to download history: {:?}", e);
+    }
+}
+
+/// Downloads the host's state, encrypted with `password` unless it's empty.
+fn save_state(state: &HostState, password: &str) -> anyhow::Result<()> {
+    let data = serde_json::to_vec_pretty(state)?;
+    if password.is_empty() {
+        return download_bytes(&data, "host-state.json", "application/json");
+    }
+
+    let data = state_crypto::encrypt(&data, password)?;
+    download_bytes(&data, "host-state.tlock", "application/octet-stream")
+}
+
+fn export_audit_log() {
+    let ctx: HostContext = consume_context();
+    let data = match ctx.export_audit_log() {
+        Ok(data) => data,
+        Err(e) => {
+            error!("Failed to export audit log: {:?}", e);
+            return;
+        }
+    };
+
+    if let Err(e) = download_bytes(data.as_bytes(), "audit-log.json", "application/json") {
+        error!("Failed to download audit log: {:?}", e);
+    }
+}
+
+#[component]
+fn chain_switcher() -> Element {
+    let mut ctx: HostContext = use_context();
+    let active_chain = ctx.active_chain();
+    let default_provider = ctx.default_eth_provider(&active_chain);
+    let required = EntityCapabilities::new().with_chain(active_chain.clone());
+    let providers: Vec<EthProviderId> = ctx
+        .entity_ids()
+        .into_iter()
+        .filter(|id| ctx.entity_capabilities(*id).satisfies(&required))
+        .filter_map(|id| match id {
+            EntityId::EthProvider(provider_id) => Some(provider_id),
+            _ => None,
+        })
+        .collect();
+
+    rsx! {
+        fieldset { class: "fieldset mt-2",
+            label { class: "label", "Network" }
+            select {
+                class: "select w-full",
+                onchange: move |e| async move {
+                    let Ok(chain_id) = e.value().parse::<ChainId>() else {
+                        error!("Invalid chain id: {}", e.value());
+                        return;
+                    };
+                    ctx.set_active_chain(chain_id).await;
+                },
+                for chain in CHAINS.iter() {
+                    {
+                        let chain_id = ChainId::new_evm(chain.id);
+                        let is_selected = chain_id == active_chain;
+                        rsx! {
+                            option {
+                                key: "chain-{chain.id}",
+                                value: "{chain_id}",
+                                selected: is_selected,
+                                "{chain.name}"
+                            }
+                        }
+                    }
+                }
+                if !CHAINS.iter().any(|c| ChainId::new_evm(c.id) == active_chain) {
+                    option { value: "{active_chain}", selected: true, "{chain_name(&active_chain)}" }
+                }
+            }
+            label { class: "label", "Default provider" }
+            select {
+                class: "select w-full",
+                onchange: move |e| {
+                    let provider_id = e.value().parse::<EthProviderId>().ok();
+                    ctx.set_default_eth_provider(ctx.active_chain(), provider_id);
+                },
+                option { value: "", selected: default_provider.is_none(), "Ask every time" }
+                for provider_id in providers {
+                    {
+                        let plugin_name = ctx
+                            .entity_plugin(EntityId::EthProvider(provider_id))
+                            .map(|p| p.name().to_string())
+                            .unwrap_or("Unknown Plugin".to_string());
+                        rsx! {
+                            option {
+                                key: "provider-{provider_id}",
+                                value: "{provider_id}",
+                                selected: default_provider == Some(provider_id),
+                                "{ctx.entity_display_name(EntityId::EthProvider(provider_id))} ({plugin_name})"
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
 #[component]
 fn states_dropdown() -> Element {
     let states_folder = asset!("/public/states");
@@ -616,8 +1618,18 @@ async fn handle_load_state(path: &str) -> anyhow::Result<()> {
         .bytes()
         .await
         .map_err(|e| anyhow!("Failed to read response bytes: {:?}", e))?;
+    load_state_bytes(&state_bytes, "").await
+}
+
+/// Replaces the current host with one loaded from a saved state, decrypting
+/// it with `password` if it's encrypted.
+async fn load_state_bytes(bytes: &[u8], password: &str) -> anyhow::Result<()> {
+    let decrypted = match state_crypto::is_encrypted(bytes) {
+        true => Some(state_crypto::decrypt(bytes, password)?),
+        false => None,
+    };
 
-    let state: host::host_state::HostState = serde_json::from_slice(&state_bytes)
+    let state = HostState::from_slice(decrypted.as_deref().unwrap_or(bytes))
         .map_err(|e| anyhow!("Failed to deserialize state JSON: {:?}", e))?;
 
     let host = Host::from_state(state)
@@ -625,6 +1637,7 @@ async fn handle_load_state(path: &str) -> anyhow::Result<()> {
         .map_err(|e| anyhow!("Failed to create host from state: {:?}", e))?;
 
     let mut ctx: HostContext = consume_context();
+    ctx.shutdown().await;
     ctx.set_host(host);
 
     Ok(())
@@ -660,31 +1673,53 @@ fn events_toast_handler() -> Element {
     let ctx: HostContext = use_context();
     let toast_ctx: ToastContext = use_context();
     let mut new_events = use_context::<UiContext>().new_events;
-    let mut last_count = use_signal(|| 0usize);
+    let mut last_seq = use_signal(|| None::<u64>);
 
     use_effect(move || {
-        let events = ctx.events();
-        let new_count = events.len();
-        let old_count = *last_count.peek();
-
-        if new_count <= old_count {
+        let filter = match *last_seq.peek() {
+            Some(seq) => EventFilter::new().after(seq),
+            None => EventFilter::new(),
+        };
+        let events = ctx.query_events(&filter);
+        let Some(latest) = events.last() else {
             return;
-        }
+        };
 
         new_events.set(true);
-        for event in events.iter().skip(old_count) {
+        for event in events.iter() {
             match event.level {
                 NotifyLevel::Trace => {}
                 NotifyLevel::Info => {
                     toast_ctx.push(event.message.clone(), ToastKind::Info);
                 }
+                NotifyLevel::Warn => {
+                    toast_ctx.push(event.message.clone(), ToastKind::Warning);
+                }
                 NotifyLevel::Error => {
                     toast_ctx.push(event.message.clone(), ToastKind::Error);
                 }
             }
         }
 
-        last_count.set(new_count);
+        last_seq.set(Some(latest.seq));
+    });
+
+    use_effect(move || {
+        for (_, toast) in ctx.take_toasts() {
+            let kind = match toast.level {
+                ToastLevel::Info => ToastKind::Info,
+                ToastLevel::Success => ToastKind::Success,
+                ToastLevel::Warning => ToastKind::Warning,
+                ToastLevel::Error => ToastKind::Error,
+            };
+            match toast.duration {
+                Some(duration) => {
+                    let timeout_ms = duration.as_millis().min(u32::MAX as u128) as u32;
+                    toast_ctx.push_for(toast.message, kind, timeout_ms);
+                }
+                None => toast_ctx.push(toast.message, kind),
+            }
+        }
     });
 
     rsx! {}
@@ -700,6 +1735,21 @@ fn get_absolute_url(path: &str) -> String {
     )
 }
 
+fn format_bytes(bytes: usize) -> String {
+    const UNITS: [&str; 4] = ["B", "KiB", "MiB", "GiB"];
+    let mut value = bytes as f64;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+
+    match unit {
+        0 => format!("{} B", bytes),
+        _ => format!("{:.1} {}", value, UNITS[unit]),
+    }
+}
+
 fn plugin_color(plugin_name: &str) -> String {
     let mut hasher = DefaultHasher::new();
     plugin_name.hash(&mut hasher);
@@ -2,14 +2,17 @@
 //!
 //! NOT DESIGNED FOR PRODUCTION USE.
 //!
-//! This is a minimal, very insecure implementation of a Coordinator Plugin. It
-//! stores its private key in memory and in plaintext host storage, does not
-//! authenticate requests, and does not do any validation of incoming data. It
-//! is intended purely for demonstration and testing.
-use std::io::stderr;
+//! This is a minimal, very insecure implementation of a Coordinator Plugin. Its
+//! private key is kept in the host's keystore, but it does not authenticate
+//! requests and does not do any validation of incoming data. It is intended
+//! purely for demonstration and testing.
+use std::{
+    io::stderr,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
 
 use alloy::{
-    primitives::{Address, FixedBytes},
+    primitives::{Address, FixedBytes, TxHash},
     providers::{Provider, ProviderBuilder},
     rpc::types::TransactionRequest,
     signers::local::PrivateKeySigner,
@@ -19,41 +22,62 @@ use erc20s::CHAIN_ID;
 use serde::{Deserialize, Serialize};
 use tlock_alloy::AlloyBridge;
 use tlock_pdk::{
-    runner::PluginRunner,
+    handlers::{CoordinatorPlugin, GlobalPlugin, LifecyclePlugin, PagePlugin},
     state::StateExt,
     tlock_api::{
         RpcMethod,
         alloy::primitives::U256,
         caip::{AccountId, AssetId, AssetType, ChainId},
-        component::{Component, container, heading, text},
+        component::{Component, container, heading, progress, text},
         coordinator,
         domains::Domain,
         entities::{CoordinatorId, EntityId, EthProviderId, PageId, VaultId},
-        global, host, page, plugin, vault,
+        error::{DomainError, ErrorCode},
+        global,
+        host::{self, EntityCapabilities},
+        plugin::PluginManifest,
+        vault,
     },
+    tlock_plugin,
     wasmi_plugin_pdk::{
         rpc_message::{RpcError, RpcErrorContext, ToRpcResult},
         transport::Transport,
     },
 };
-use tracing::{error, info};
+use tracing::{error, info, warn};
 use tracing_subscriber::fmt;
 
+mod simulation;
+
 #[derive(Serialize, Deserialize, Debug, Clone)]
 struct State {
     /// Vault managed by this coordinator
     vault_id: VaultId,
     provider_id: EthProviderId,
     coordinator: Coordinator,
+    /// The coordinator's page. Coordinators created before it was stored
+    /// record it when the page is first loaded.
+    #[serde(default)]
+    page_id: Option<PageId>,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
 struct Coordinator {
     entity_id: EntityId,
-    private_key: FixedBytes<32>,
     account: AccountId,
+    /// The private key of coordinators created before keys moved to the
+    /// host's keystore. Moved there on first use.
+    #[serde(
+        default,
+        rename = "private_key",
+        skip_serializing_if = "Option::is_none"
+    )]
+    legacy_private_key: Option<FixedBytes<32>>,
 }
 
+/// Name of the host secret holding the coordinator's private key
+const PRIVATE_KEY_SECRET: &str = "private_key";
+
 sol! {
     #[sol(rpc)]
     contract ERC20 {
@@ -66,6 +90,10 @@ sol! {
 /// TODO: Dynamically calculate based on bundle complexity
 const REQUIRED_GAS: u128 = 10000000000000000; // 0.01 ETH
 
+/// State key holding the Unix timestamp the open session expires at
+const SESSION_KEY: &str = "session";
+const SESSION_TTL: Duration = Duration::from_secs(10 * 60);
+
 #[derive(Debug)]
 struct ReturnAsset {
     asset: EvmAsset,
@@ -78,107 +106,312 @@ enum EvmAsset {
     Erc20(Address),
 }
 
-async fn ping(transport: Transport, _: ()) -> Result<String, RpcError> {
-    global::Ping.call_async(transport, ()).await?;
-    Ok("pong".to_string())
+#[tlock_plugin(LifecyclePlugin, GlobalPlugin, CoordinatorPlugin, PagePlugin)]
+struct EoaCoordinator;
+
+#[tlock_plugin]
+impl GlobalPlugin for EoaCoordinator {
+    async fn ping(transport: Transport, _: ()) -> Result<String, RpcError> {
+        global::Ping.call_async(transport, ()).await?;
+        Ok("pong".to_string())
+    }
 }
 
-async fn init(transport: Transport, _: ()) -> Result<(), RpcError> {
-    let provider_id =
-        host::RequestEthProvider.call(transport.clone(), ChainId::new_evm(CHAIN_ID))?;
-    let vault_id = host::RequestVault.call(transport.clone(), ())?;
-    let coordinator_id = host::RegisterEntity.call(transport.clone(), Domain::Coordinator)?;
-    host::RegisterEntity.call(transport.clone(), Domain::Page)?;
-
-    let signer = PrivateKeySigner::random();
-    let address = signer.address();
-    let account_id = AccountId::new_evm(CHAIN_ID, address);
-
-    let state = State {
-        vault_id,
-        provider_id,
-        coordinator: Coordinator {
-            entity_id: coordinator_id,
-            private_key: signer.to_bytes(),
-            account: account_id,
-        },
-    };
+#[tlock_plugin]
+impl LifecyclePlugin for EoaCoordinator {
+    async fn get_manifest(_transport: Transport, _params: ()) -> Result<PluginManifest, RpcError> {
+        let manifest = PluginManifest::new()
+            .with_method(host::RequestEthProvider)
+            .with_method(host::RequestVault)
+            .with_method(host::LeaseNonce)
+            .with_method(host::ReleaseNonce)
+            .with_method(vault::GetAssets)
+            .with_method(vault::GetDepositAddress)
+            .with_method(vault::Withdraw)
+            .with_domain(Domain::Coordinator)
+            .with_domain(Domain::Page);
+        Ok(AlloyBridge::manifest(manifest))
+    }
 
-    transport.state().lock_or(|| state)?;
+    async fn init(transport: Transport, _: ()) -> Result<(), RpcError> {
+        let provider_id = host::RequestEthProvider.call(
+            transport.clone(),
+            (Some(ChainId::new_evm(CHAIN_ID)), EntityCapabilities::new()),
+        )?;
+        let vault_id = host::RequestVault.call(
+            transport.clone(),
+            EntityCapabilities::new().with_chain(ChainId::new_evm(CHAIN_ID)),
+        )?;
+        let coordinator_id = host::RegisterEntity.call(transport.clone(), Domain::Coordinator)?;
+        let page_id = match host::RegisterEntity.call(transport.clone(), Domain::Page)? {
+            EntityId::Page(id) => Some(id),
+            _ => None,
+        }
+        .context("Invalid Page ID")?;
+
+        let signer = PrivateKeySigner::random();
+        let address = signer.address();
+        let account_id = AccountId::new_evm(CHAIN_ID, address);
+
+        let state = State {
+            vault_id,
+            provider_id,
+            coordinator: Coordinator {
+                entity_id: coordinator_id,
+                account: account_id.clone(),
+                legacy_private_key: None,
+            },
+            page_id: Some(page_id),
+        };
 
-    Ok(())
+        let state = transport.state().lock_or(|| state)?;
+        //? Only keep the new key if its account is the one that was stored
+        if state.coordinator.account == account_id {
+            host::SecretSet.call(
+                transport.clone(),
+                (PRIVATE_KEY_SECRET.to_string(), signer.to_bytes().to_vec()),
+            )?;
+        }
+
+        Ok(())
+    }
 }
 
-async fn get_session(
-    transport: Transport,
-    params: (CoordinatorId, ChainId, Option<AccountId>),
-) -> Result<AccountId, RpcError> {
-    let state: State = transport.state().read()?;
-    let (coordinator_id, chain_id, maybe_account_id) = params;
+#[tlock_plugin]
+impl CoordinatorPlugin for EoaCoordinator {
+    async fn get_session(
+        transport: Transport,
+        params: (CoordinatorId, ChainId, Option<AccountId>),
+    ) -> Result<coordinator::Session, RpcError> {
+        let state: State = transport.state().read()?;
+        let (coordinator_id, chain_id, maybe_account_id) = params;
+
+        let coordinator_id: EntityId = coordinator_id.into();
+        if coordinator_id != state.coordinator.entity_id {
+            return Err(
+                DomainError::new(ErrorCode::INVALID_ENTITY, "Invalid CoordinatorId").into(),
+            );
+        }
+
+        // TODO: Support arbitrary evm chain IDs
+        if chain_id != ChainId::new_evm(CHAIN_ID) {
+            return Err(DomainError::new(ErrorCode::UNSUPPORTED_CHAIN, "Invalid ChainId").into());
+        }
+
+        if let Some(account_id) = maybe_account_id
+            && account_id != state.coordinator.account
+        {
+            return Err(DomainError::new(ErrorCode::INVALID_ACCOUNT, "Invalid AccountId").into());
+        }
 
-    let coordinator_id: EntityId = coordinator_id.into();
-    if coordinator_id != state.coordinator.entity_id {
-        return Err(RpcError::custom("Invalid CoordinatorId"));
+        let expires_at = get_current_timestamp() + SESSION_TTL.as_secs();
+        transport.state().write_key(SESSION_KEY, expires_at)?;
+
+        Ok(coordinator::Session {
+            account: state.coordinator.account.clone(),
+            ttl: SESSION_TTL,
+        })
     }
 
-    // TODO: Support arbitrary evm chain IDs
-    if chain_id != ChainId::new_evm(CHAIN_ID) {
-        return Err(RpcError::Custom("Invalid ChainId".into()));
+    async fn cancel_session(
+        transport: Transport,
+        params: (CoordinatorId, AccountId),
+    ) -> Result<(), RpcError> {
+        let state: State = transport.state().read()?;
+        let (coordinator_id, account_id) = params;
+
+        let coordinator_id: EntityId = coordinator_id.into();
+        if coordinator_id != state.coordinator.entity_id {
+            return Err(
+                DomainError::new(ErrorCode::INVALID_ENTITY, "Invalid CoordinatorId").into(),
+            );
+        }
+
+        if account_id != state.coordinator.account {
+            return Err(DomainError::new(ErrorCode::INVALID_ACCOUNT, "Invalid AccountId").into());
+        }
+
+        //? Assets are only withdrawn from the vault while a proposal executes, so
+        //? there's nothing held for the session to release
+        transport.state().delete_key(SESSION_KEY)?;
+        Ok(())
     }
 
-    if let Some(account_id) = maybe_account_id
-        && account_id != state.coordinator.account
-    {
-        return Err(RpcError::Custom("Invalid AccountId".into()));
+    async fn get_assets(
+        transport: Transport,
+        params: (CoordinatorId, AccountId),
+    ) -> Result<Vec<(AssetId, U256)>, RpcError> {
+        let state: State = transport.state().read()?;
+        let (coordinator_id, account_id) = params;
+
+        let coordinator_id: EntityId = coordinator_id.into();
+        if coordinator_id != state.coordinator.entity_id {
+            return Err(
+                DomainError::new(ErrorCode::INVALID_ENTITY, "Invalid CoordinatorId").into(),
+            );
+        }
+
+        if account_id != state.coordinator.account {
+            return Err(DomainError::new(ErrorCode::INVALID_ACCOUNT, "Invalid AccountId").into());
+        }
+        require_session(&transport)?;
+
+        // TODO: Filter assets by those on the same chain as the account
+        Ok(vault::GetAssets
+            .call_async(transport.clone(), state.vault_id)
+            .await?)
     }
 
-    Ok(state.coordinator.account.clone())
-}
+    async fn propose(
+        transport: Transport,
+        params: (CoordinatorId, AccountId, coordinator::EvmBundle),
+    ) -> Result<(), RpcError> {
+        info!("Received proposal: {:?}", params);
+        let (coordinator_id, account_id, bundle) = params;
 
-async fn get_assets(
-    transport: Transport,
-    params: (CoordinatorId, AccountId),
-) -> Result<Vec<(AssetId, U256)>, RpcError> {
-    let state: State = transport.state().read()?;
-    let (coordinator_id, account_id) = params;
+        let state: State = transport.state().read()?;
+
+        let coordinator = &state.coordinator;
+
+        let coordinator_id: EntityId = coordinator_id.into();
+        if coordinator_id != coordinator.entity_id {
+            return Err(
+                DomainError::new(ErrorCode::INVALID_ENTITY, "Invalid CoordinatorId").into(),
+            );
+        }
+
+        if account_id != coordinator.account {
+            return Err(DomainError::new(ErrorCode::INVALID_ACCOUNT, "Invalid AccountId").into());
+        }
 
-    let coordinator_id: EntityId = coordinator_id.into();
-    if coordinator_id != state.coordinator.entity_id {
-        return Err(RpcError::Custom("Invalid CoordinatorId".into()));
+        //? Proposing closes the session whether or not the bundle executes
+        require_session(&transport)?;
+        transport.state().delete_key(SESSION_KEY)?;
+
+        execute_proposal(transport, &state, bundle).await
     }
 
-    if account_id != state.coordinator.account {
-        return Err(RpcError::Custom("Invalid AccountId".into()));
+    async fn propose_multichain(
+        transport: Transport,
+        params: (CoordinatorId, Vec<(AccountId, coordinator::EvmBundle)>),
+    ) -> Result<(), RpcError> {
+        info!("Received multichain proposal: {:?}", params);
+        let (coordinator_id, bundles) = params;
+
+        let state: State = transport.state().read()?;
+
+        let entity_id: EntityId = coordinator_id.into();
+        if entity_id != state.coordinator.entity_id {
+            return Err(
+                DomainError::new(ErrorCode::INVALID_ENTITY, "Invalid CoordinatorId").into(),
+            );
+        }
+
+        //? Validate every bundle up-front so a later failure can't leave the
+        //? proposal partially executed
+        for (account_id, bundle) in &bundles {
+            if account_id != &state.coordinator.account {
+                return Err(DomainError::new(
+                    ErrorCode::INVALID_ACCOUNT,
+                    format!(
+                        "Coordinator cannot execute bundles for account {}",
+                        account_id
+                    ),
+                )
+                .into());
+            }
+
+            verify_vault_balance(&transport, &state, bundle).await?;
+            validate_and_get_return_assets(transport.clone(), &state, bundle).await?;
+        }
+
+        require_session(&transport)?;
+        transport.state().delete_key(SESSION_KEY)?;
+
+        for (_, bundle) in bundles {
+            execute_proposal(transport.clone(), &state, bundle).await?;
+        }
+
+        Ok(())
     }
 
-    // TODO: Filter assets by those on the same chain as the account
-    Ok(vault::GetAssets
-        .call_async(transport.clone(), state.vault_id)
-        .await?)
-}
+    async fn simulate(
+        transport: Transport,
+        params: (CoordinatorId, AccountId, coordinator::EvmBundle),
+    ) -> Result<coordinator::SimulationResult, RpcError> {
+        info!("Received simulation request: {:?}", params);
+        let (coordinator_id, account_id, bundle) = params;
 
-async fn propose(
-    transport: Transport,
-    params: (CoordinatorId, AccountId, coordinator::EvmBundle),
-) -> Result<(), RpcError> {
-    info!("Received proposal: {:?}", params);
-    let (coordinator_id, account_id, bundle) = params;
+        let state: State = transport.state().read()?;
 
-    let state: State = transport.state().read()?;
+        let coordinator_id: EntityId = coordinator_id.into();
+        if coordinator_id != state.coordinator.entity_id {
+            return Err(
+                DomainError::new(ErrorCode::INVALID_ENTITY, "Invalid CoordinatorId").into(),
+            );
+        }
+
+        if account_id != state.coordinator.account {
+            return Err(DomainError::new(ErrorCode::INVALID_ACCOUNT, "Invalid AccountId").into());
+        }
+        require_session(&transport)?;
 
-    let coordinator = state.coordinator.clone();
+        let Some(evm_address) = account_id.as_evm_address() else {
+            return Err(RpcError::Custom(
+                "Coordinator account is not an EVM address".into(),
+            ));
+        };
 
-    let coordinator_id: EntityId = coordinator_id.into();
-    if coordinator_id != coordinator.entity_id {
-        return Err(RpcError::custom("Invalid CoordinatorId"));
+        simulation::simulate_bundle(transport.clone(), state.provider_id, evm_address, &bundle)
     }
+}
 
-    if account_id != coordinator.account {
-        return Err(RpcError::custom("Invalid AccountId"));
+/// Loads the coordinator's signer from the host's keystore, first moving a
+/// legacy key out of plugin state if there is one.
+async fn load_signer(transport: Transport) -> Result<PrivateKeySigner, RpcError> {
+    let state: State = transport.state().read()?;
+    if let Some(key) = state.coordinator.legacy_private_key {
+        //? Store the key before clearing it so it can't be lost in between
+        host::SecretSet
+            .call_async(
+                transport.clone(),
+                (PRIVATE_KEY_SECRET.to_string(), key.to_vec()),
+            )
+            .await?;
+        let mut state = transport.state().lock::<State>()?;
+        state.coordinator.legacy_private_key = None;
+        return PrivateKeySigner::from_bytes(&key).context("Invalid private key");
     }
 
-    let signer =
-        PrivateKeySigner::from_bytes(&coordinator.private_key).context("Invalid private key")?;
+    let key = host::SecretGet
+        .call_async(transport, PRIVATE_KEY_SECRET.to_string())
+        .await?
+        .context("Private key missing from keystore")?;
+    PrivateKeySigner::from_slice(&key).context("Invalid private key")
+}
+
+/// Executes a validated bundle from the coordinator's account, returning any
+/// outstanding assets to the vault afterwards. Progress is shown on the
+/// coordinator's page while it runs.
+async fn execute_proposal(
+    transport: Transport,
+    state: &State,
+    bundle: coordinator::EvmBundle,
+) -> Result<(), RpcError> {
+    let result = run_proposal(transport.clone(), state, bundle).await;
+    //? Clear the progress bar whether or not the bundle executed
+    show_progress(&transport, state, None);
+    result
+}
+
+async fn run_proposal(
+    transport: Transport,
+    state: &State,
+    bundle: coordinator::EvmBundle,
+) -> Result<(), RpcError> {
+    let coordinator = &state.coordinator;
+    show_progress(&transport, state, Some(progress("Loading signer", 0)));
+    let signer = load_signer(transport.clone()).await?;
     let provider = ProviderBuilder::new()
         .wallet(signer)
         .connect_client(AlloyBridge::new(transport.clone(), state.provider_id));
@@ -192,52 +425,106 @@ async fn propose(
         }
     };
 
+    show_progress(
+        &transport,
+        state,
+        Some(progress("Checking vault balances", 15)),
+    );
     let initial_native_balance = provider.get_balance(evm_address).await.rpc_err()?;
-    verify_vault_balance(&transport, &state, &bundle).await?;
+    verify_vault_balance(&transport, state, &bundle).await?;
 
-    let return_assets = validate_and_get_return_assets(transport.clone(), &state, &bundle).await?;
+    let return_assets = validate_and_get_return_assets(transport.clone(), state, &bundle).await?;
+    show_progress(&transport, state, Some(progress("Withdrawing gas", 30)));
     withdraw_gas(
         &provider,
         transport.clone(),
-        &state,
+        state,
         &coordinator.account,
         U256::from(REQUIRED_GAS),
     )
     .await?;
-    withdraw_assets(transport.clone(), &state, &coordinator.account, &bundle).await?;
+    show_progress(&transport, state, Some(progress("Withdrawing assets", 45)));
+    withdraw_assets(transport.clone(), state, &coordinator.account, &bundle).await?;
 
     //? We always want to attempt to return assets, even if execution fails,
     //? so defer the error handling
-    let execution_result = execute_bundle(&provider, bundle).await;
+    show_progress(&transport, state, Some(progress("Executing bundle", 60)));
+    let execution_result = execute_bundle(&provider, &transport, state, evm_address, bundle).await;
+    show_progress(&transport, state, Some(progress("Returning assets", 85)));
     return_outstanding_assets(
         &provider,
         transport.clone(),
+        state.provider_id,
         evm_address,
         return_assets,
         initial_native_balance,
     )
     .await?;
 
-    let ui = build_ui(&state);
-    host::SetPage.call(transport.clone(), (PageId::default(), ui))?;
-
     execution_result
 }
 
-async fn on_load(transport: Transport, page_id: PageId) -> Result<(), RpcError> {
-    let state: State = transport.state().read()?;
+//? This coordinator only holds an account on a single chain, so multi-chain
+//? proposals are only accepted when every bundle uses that account.
+/// Errors unless the coordinator's session is open and hasn't expired.
+fn require_session(transport: &Transport) -> Result<(), RpcError> {
+    let expires_at: u64 = transport.state().read_key_or(SESSION_KEY, || 0)?;
+    if expires_at <= get_current_timestamp() {
+        return Err(DomainError::new(ErrorCode::NO_SESSION, "No open session").into());
+    }
+    Ok(())
+}
+
+/// Returns the current Unix timestamp in seconds.
+fn get_current_timestamp() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .expect("System time is before UNIX_EPOCH")
+        .as_secs()
+}
+
+#[tlock_plugin]
+impl PagePlugin for EoaCoordinator {
+    async fn on_load(transport: Transport, page_id: PageId) -> Result<(), RpcError> {
+        let mut state: State = transport.state().read()?;
+        if state.page_id != Some(page_id) {
+            let mut locked = transport.state().try_lock::<State>()?;
+            locked.page_id = Some(page_id);
+            state = (*locked).clone();
+        }
+
+        show_page(&transport, &state, None)
+    }
+}
 
-    let ui = build_ui(&state);
-    host::SetPage.call(transport.clone(), (page_id, ui))?;
+/// Shows `status` on the coordinator's page, or the idle status line if
+/// None. Progress is only informational, so failing to show it is logged
+/// rather than failing the proposal.
+fn show_progress(transport: &Transport, state: &State, status: Option<Component>) {
+    if let Err(e) = show_page(transport, state, status) {
+        warn!("Failed to show progress: {}", e);
+    }
+}
 
+/// Redraws the coordinator's page, showing `status` in place of the idle
+/// status line if given.
+fn show_page(
+    transport: &Transport,
+    state: &State,
+    status: Option<Component>,
+) -> Result<(), RpcError> {
+    let Some(page_id) = state.page_id else {
+        return Ok(());
+    };
+    host::SetPage.call(transport.clone(), (page_id, build_ui(state, status)))?;
     Ok(())
 }
 
-fn build_ui(state: &State) -> Component {
+fn build_ui(state: &State, status: Option<Component>) -> Component {
     let sections = vec![
         heading("Vault Coordinator"),
         text("Execution engine between plugins and vaults"),
-        text("Status: Active"),
+        status.unwrap_or_else(|| text("Status: Active")),
         text(format!("Connected Vault: {}", state.vault_id)),
     ];
 
@@ -260,10 +547,13 @@ async fn verify_vault_balance(
             .unwrap_or(U256::ZERO);
 
         if &vault_amount < amount {
-            return Err(RpcError::Custom(format!(
-                "Insufficient assets ({} < {})",
-                state.vault_id, amount
-            )));
+            let message = format!(
+                "Insufficient {} in {} ({} < {})",
+                asset_id, state.vault_id, vault_amount, amount
+            );
+            return Err(DomainError::new(ErrorCode::INSUFFICIENT_FUNDS, message)
+                .with_data((asset_id, vault_amount, amount))
+                .into());
         }
     }
 
@@ -284,31 +574,7 @@ async fn validate_and_get_return_assets(
         .chain(bundle.outputs.iter().map(|f| f.clone()));
 
     for asset_id in bundled_assets {
-        if (asset_id.chain_id) != ChainId::new_evm(CHAIN_ID) {
-            return Err(RpcError::Custom(format!(
-                "Coordinator cannot return asset {} on chain {}",
-                asset_id, asset_id.chain_id
-            )));
-        }
-
-        let asset = match asset_id.asset {
-            AssetType::Erc20(address) => EvmAsset::Erc20(address),
-            AssetType::Slip44(id) => {
-                if id != 60 {
-                    return Err(RpcError::Custom(format!(
-                        "Coordinator cannot return unsupported slip44 asset {}",
-                        asset_id
-                    )));
-                }
-                EvmAsset::Eth
-            }
-            _ => {
-                return Err(RpcError::Custom(format!(
-                    "Coordinator cannot return unsupported asset {}",
-                    asset_id
-                )));
-            }
-        };
+        let asset = to_evm_asset(&asset_id)?;
 
         let deposit_address = vault::GetDepositAddress
             .call_async(transport.clone(), (state.vault_id, asset_id.clone()))
@@ -329,6 +595,29 @@ async fn validate_and_get_return_assets(
     Ok(return_assets)
 }
 
+fn to_evm_asset(asset_id: &AssetId) -> Result<EvmAsset, RpcError> {
+    if (asset_id.chain_id) != ChainId::new_evm(CHAIN_ID) {
+        let message = format!(
+            "Coordinator cannot return asset {} on chain {}",
+            asset_id, asset_id.chain_id
+        );
+        return Err(DomainError::new(ErrorCode::UNSUPPORTED_CHAIN, message)
+            .with_data(asset_id)
+            .into());
+    }
+
+    match asset_id.asset {
+        AssetType::Erc20(address) => Ok(EvmAsset::Erc20(address)),
+        AssetType::Slip44(60) => Ok(EvmAsset::Eth),
+        _ => Err(DomainError::new(
+            ErrorCode::UNSUPPORTED_ASSET,
+            format!("Coordinator cannot return unsupported asset {}", asset_id),
+        )
+        .with_data(asset_id)
+        .into()),
+    }
+}
+
 async fn withdraw_gas<T: Provider>(
     provider: &T,
     transport: Transport,
@@ -390,6 +679,9 @@ async fn withdraw_assets(
 
 async fn execute_bundle<T: Provider>(
     provider: &T,
+    transport: &Transport,
+    state: &State,
+    from: Address,
     bundle: coordinator::EvmBundle,
 ) -> Result<(), RpcError> {
     for operation in bundle.operations {
@@ -398,22 +690,51 @@ async fn execute_bundle<T: Provider>(
             .to(operation.to)
             .input(operation.data.into())
             .value(operation.value);
-        let tx_hash = provider
-            .send_transaction(tx)
+        let tx_hash =
+            send_with_leased_nonce(provider, transport, state.provider_id, from, tx).await?;
+        info!("Submitted operation with tx_hash {}", tx_hash);
+    }
+
+    Ok(())
+}
+
+/// Sends a transaction with a nonce leased from the host, so it can't collide
+/// with transactions other plugins send from the same account.
+async fn send_with_leased_nonce<T: Provider>(
+    provider: &T,
+    transport: &Transport,
+    provider_id: EthProviderId,
+    from: Address,
+    tx: TransactionRequest,
+) -> Result<TxHash, RpcError> {
+    let nonce = host::LeaseNonce
+        .call_async(transport.clone(), (provider_id, from))
+        .await?;
+    let result = async {
+        provider
+            .send_transaction(tx.nonce(nonce))
             .await
             .rpc_err()?
             .watch()
             .await
-            .rpc_err()?;
-        info!("Submitted operation with tx_hash {}", tx_hash);
+            .rpc_err()
     }
-
-    Ok(())
+    .await;
+
+    //? Releasing is safe even if the transaction was broadcast, since the host
+    //? never re-leases nonces below the pending transaction count
+    if result.is_err() {
+        let _ = host::ReleaseNonce
+            .call_async(transport.clone(), (provider_id, from, nonce))
+            .await;
+    }
+    result
 }
 
 async fn return_outstanding_assets<T: Provider>(
     provider: &T,
     transport: Transport,
+    provider_id: EthProviderId,
     state_account_address: Address,
     return_assets: Vec<ReturnAsset>,
     initial_native_balance: U256,
@@ -424,6 +745,8 @@ async fn return_outstanding_assets<T: Provider>(
             EvmAsset::Eth => {
                 return_eth(
                     provider,
+                    &transport,
+                    provider_id,
                     state_account_address,
                     return_asset.deposit_address,
                     initial_native_balance,
@@ -433,6 +756,8 @@ async fn return_outstanding_assets<T: Provider>(
             EvmAsset::Erc20(address) => {
                 return_erc20(
                     provider,
+                    &transport,
+                    provider_id,
                     state_account_address,
                     return_asset.deposit_address,
                     address,
@@ -462,6 +787,8 @@ async fn return_outstanding_assets<T: Provider>(
 
 async fn return_eth<T: Provider>(
     provider: &T,
+    transport: &Transport,
+    provider_id: EthProviderId,
     state_account_address: Address,
     deposit_address: Address,
     initial_native_balance: U256,
@@ -480,22 +807,11 @@ async fn return_eth<T: Provider>(
         return Ok(());
     }
 
-    let nonce = provider
-        .get_transaction_count(state_account_address)
-        .await
-        .rpc_err()?;
-    let tx_hash = provider
-        .send_transaction(
-            TransactionRequest::default()
-                .to(deposit_address)
-                .value(return_amount)
-                .nonce(nonce),
-        )
-        .await
-        .rpc_err()?
-        .watch()
-        .await
-        .rpc_err()?;
+    let tx = TransactionRequest::default()
+        .to(deposit_address)
+        .value(return_amount);
+    let tx_hash =
+        send_with_leased_nonce(provider, transport, provider_id, state_account_address, tx).await?;
     info!(
         "Returned {} ETH to vault with tx_hash {}",
         return_amount, tx_hash
@@ -505,6 +821,8 @@ async fn return_eth<T: Provider>(
 
 async fn return_erc20<T: Provider>(
     provider: &T,
+    transport: &Transport,
+    provider_id: EthProviderId,
     state_account_address: Address,
     deposit_address: Address,
     erc20_address: Address,
@@ -521,19 +839,11 @@ async fn return_erc20<T: Provider>(
         return Ok(());
     }
 
-    let nonce = provider
-        .get_transaction_count(state_account_address)
-        .await
-        .rpc_err()?;
-    let tx_hash = erc20
+    let tx = erc20
         .transfer(deposit_address, balance)
-        .nonce(nonce)
-        .send()
-        .await
-        .rpc_err()?
-        .watch()
-        .await
-        .rpc_err()?;
+        .into_transaction_request();
+    let tx_hash =
+        send_with_leased_nonce(provider, transport, provider_id, state_account_address, tx).await?;
     info!(
         "Returned {} ERC20 {} to vault with tx_hash {}",
         balance, erc20_address, tx_hash
@@ -550,12 +860,5 @@ fn main() {
         .compact()
         .init();
 
-    PluginRunner::new()
-        .with_method(global::Ping, ping)
-        .with_method(plugin::Init, init)
-        .with_method(coordinator::GetSession, get_session)
-        .with_method(coordinator::GetAssets, get_assets)
-        .with_method(coordinator::Propose, propose)
-        .with_method(page::OnLoad, on_load)
-        .run();
+    EoaCoordinator::runner().run();
 }
@@ -0,0 +1,329 @@
+//! Bundle simulation for the EOA coordinator.
+//!
+//! Replays a bundle on a local revm fork of the coordinator's EthProvider,
+//! mirroring the withdraw / execute / return flow of `propose` without
+//! touching the vault or submitting any transactions.
+use alloy::{
+    eips::BlockId,
+    primitives::{Address, B256, Bytes, I256, TxKind, U256, keccak256},
+    rpc::types::BlockTransactionsKind,
+    sol_types::{SolCall, decode_revert_reason},
+};
+use erc20s::{CHAIN_ID, get_erc20_by_address};
+use revm::{
+    Context, DatabaseRef, ExecuteCommitEvm, ExecuteEvm, MainBuilder, MainContext,
+    context::{BlockEnv, DBErrorMarker, TxEnv, result::ExecutionResult},
+    database::CacheDB,
+    state::{AccountInfo, Bytecode},
+};
+use thiserror::Error;
+use tlock_pdk::{
+    tlock_api::{
+        RpcMethod,
+        caip::AssetId,
+        coordinator::{EvmBundle, SimulationResult},
+        entities::EthProviderId,
+        eth,
+    },
+    wasmi_plugin_pdk::{
+        rpc_message::{RpcError, ToRpcResult},
+        transport::Transport,
+    },
+};
+
+use crate::{ERC20, EvmAsset, to_evm_asset};
+
+#[derive(Debug, Error)]
+#[error("Provider error: {0}")]
+pub struct ProviderDBError(#[from] RpcError);
+
+impl DBErrorMarker for ProviderDBError {}
+
+/// Read-only database backed by an EthProvider at a fixed block.
+struct ProviderDB {
+    transport: Transport,
+    provider_id: EthProviderId,
+    block_id: BlockId,
+}
+
+impl DatabaseRef for ProviderDB {
+    type Error = ProviderDBError;
+
+    fn basic_ref(&self, address: Address) -> Result<Option<AccountInfo>, Self::Error> {
+        let params = (self.provider_id, address, self.block_id);
+        let balance = eth::GetBalance.call(self.transport.clone(), params)?;
+        let nonce = eth::GetTransactionCount.call(self.transport.clone(), params)?;
+        let code = eth::GetCode.call(self.transport.clone(), params)?;
+
+        let bytecode = Bytecode::new_raw(code);
+        let code_hash = bytecode.hash_slow();
+        Ok(Some(AccountInfo::new(balance, nonce, code_hash, bytecode)))
+    }
+
+    fn code_by_hash_ref(&self, _: B256) -> Result<Bytecode, Self::Error> {
+        Err(RpcError::custom("code_by_hash_ref is not supported in ProviderDB").into())
+    }
+
+    fn storage_ref(&self, address: Address, index: U256) -> Result<U256, Self::Error> {
+        let params = (self.provider_id, address, index, self.block_id);
+        Ok(eth::GetStorageAt.call(self.transport.clone(), params)?)
+    }
+
+    fn block_hash_ref(&self, number: u64) -> Result<B256, Self::Error> {
+        let params = (
+            self.provider_id,
+            BlockId::number(number),
+            BlockTransactionsKind::Hashes,
+        );
+        let block = eth::GetBlock.call(self.transport.clone(), params)?;
+        Ok(block.header.hash)
+    }
+}
+
+type SimulationDB = CacheDB<ProviderDB>;
+
+/// A bundled asset tracked across the simulation
+struct TrackedAsset {
+    asset_id: AssetId,
+    asset: EvmAsset,
+    /// Account balance before any vault withdrawals
+    initial: U256,
+    /// Amount withdrawn from the vault as a bundle input
+    withdrawn: U256,
+}
+
+/// Simulates a bundle executed from `account` against the latest block.
+///
+/// Gas is priced at zero while executing so the account doesn't need a gas
+/// balance. The expected cost is reported separately in `gas_cost`.
+pub fn simulate_bundle(
+    transport: Transport,
+    provider_id: EthProviderId,
+    account: Address,
+    bundle: &EvmBundle,
+) -> Result<SimulationResult, RpcError> {
+    let block = eth::GetBlock.call(
+        transport.clone(),
+        (
+            provider_id,
+            BlockId::latest(),
+            BlockTransactionsKind::Hashes,
+        ),
+    )?;
+    let block_env = BlockEnv {
+        number: U256::from(block.header.number),
+        beneficiary: block.header.beneficiary,
+        timestamp: U256::from(block.header.timestamp),
+        gas_limit: block.header.gas_limit,
+        basefee: 0,
+        difficulty: block.header.difficulty,
+        prevrandao: Some(block.header.mix_hash),
+        blob_excess_gas_and_price: None,
+    };
+
+    let mut db = CacheDB::new(ProviderDB {
+        transport: transport.clone(),
+        provider_id,
+        block_id: BlockId::number(block.header.number),
+    });
+
+    //? Withdraw the bundle inputs from the vault into the account
+    let mut tracked: Vec<TrackedAsset> = Vec::new();
+    let bundled_assets = bundle
+        .inputs
+        .iter()
+        .map(|(id, _)| id)
+        .chain(bundle.outputs.iter());
+    for asset_id in bundled_assets {
+        if tracked.iter().any(|t| &t.asset_id == asset_id) {
+            continue;
+        }
+
+        let asset = to_evm_asset(asset_id)?;
+        let initial = balance_of(&mut db, &block_env, account, &asset)?;
+        let withdrawn = bundle
+            .inputs
+            .iter()
+            .filter(|(id, _)| id == asset_id)
+            .fold(U256::ZERO, |acc, (_, amount)| acc + amount);
+
+        if withdrawn > U256::ZERO {
+            set_balance(&mut db, account, &asset, initial + withdrawn)?;
+        }
+
+        tracked.push(TrackedAsset {
+            asset_id: asset_id.clone(),
+            asset,
+            initial,
+            withdrawn,
+        });
+    }
+
+    //? Operations are submitted as separate transactions, so earlier
+    //? operations stay committed when a later one reverts
+    let mut gas_used = 0;
+    let mut revert_reason = None;
+    for operation in &bundle.operations {
+        let tx = call_tx(
+            account,
+            operation.to,
+            operation.value,
+            operation.data.clone().into(),
+            block_env.gas_limit,
+        );
+        let result = transact(&mut db, &block_env, tx, true)?;
+        gas_used += result.gas_used();
+
+        match result {
+            ExecutionResult::Success { .. } => {}
+            ExecutionResult::Revert { output, .. } => {
+                let reason = decode_revert_reason(&output)
+                    .unwrap_or_else(|| format!("Reverted with data {}", output));
+                revert_reason = Some(reason);
+                break;
+            }
+            ExecutionResult::Halt { reason, .. } => {
+                revert_reason = Some(format!("Halted: {:?}", reason));
+                break;
+            }
+        }
+    }
+
+    //? Mirrors `return_outstanding_assets`: ETH above the initial balance and
+    //? the full ERC20 balance are returned to the vault
+    let mut asset_deltas = Vec::new();
+    for t in tracked {
+        let balance = balance_of(&mut db, &block_env, account, &t.asset)?;
+        let returned = match t.asset {
+            EvmAsset::Eth => balance.saturating_sub(t.initial),
+            EvmAsset::Erc20(_) => balance,
+        };
+        asset_deltas.push((t.asset_id, signed_delta(t.withdrawn, returned)));
+    }
+
+    let gas_price = eth::GasPrice.call(transport, provider_id)?;
+    let gas_cost = U256::from(gas_used) * U256::from(gas_price);
+
+    Ok(SimulationResult {
+        asset_deltas,
+        gas_used,
+        gas_cost,
+        revert_reason,
+    })
+}
+
+fn transact(
+    db: &mut SimulationDB,
+    block_env: &BlockEnv,
+    tx: TxEnv,
+    commit: bool,
+) -> Result<ExecutionResult, RpcError> {
+    let mut evm = Context::mainnet()
+        .modify_cfg_chained(|cfg| {
+            cfg.tx_chain_id_check = false;
+            cfg.disable_nonce_check = true;
+            cfg.disable_base_fee = true;
+            cfg.chain_id = CHAIN_ID;
+        })
+        .with_db(db)
+        .with_block(block_env.clone())
+        .build_mainnet();
+
+    let result = if commit {
+        evm.transact_commit(tx)
+    } else {
+        evm.transact(tx).map(|r| r.result)
+    };
+    result.map_err(|e| RpcError::custom(format!("EVM execution error: {}", e)))
+}
+
+fn call_tx(caller: Address, to: Address, value: U256, data: Bytes, gas_limit: u64) -> TxEnv {
+    TxEnv::builder()
+        .caller(caller)
+        .kind(TxKind::Call(to))
+        .value(value)
+        .data(data)
+        .gas_limit(gas_limit)
+        .gas_price(0)
+        .chain_id(Some(CHAIN_ID))
+        .build_fill()
+}
+
+fn balance_of(
+    db: &mut SimulationDB,
+    block_env: &BlockEnv,
+    account: Address,
+    asset: &EvmAsset,
+) -> Result<U256, RpcError> {
+    match asset {
+        EvmAsset::Eth => {
+            let db_account = db.load_account(account).rpc_err()?;
+            Ok(db_account.info.balance)
+        }
+        EvmAsset::Erc20(token) => {
+            let data = ERC20::balanceOfCall { owner: account }.abi_encode();
+            let tx = call_tx(
+                account,
+                *token,
+                U256::ZERO,
+                data.into(),
+                block_env.gas_limit,
+            );
+
+            match transact(db, block_env, tx, false)? {
+                ExecutionResult::Success { output, .. } => {
+                    ERC20::balanceOfCall::abi_decode_returns(output.data()).rpc_err()
+                }
+                _ => Err(RpcError::custom(format!(
+                    "Failed to get balance of ERC20 {}",
+                    token
+                ))),
+            }
+        }
+    }
+}
+
+/// Overrides the account's balance of an asset in the simulation.
+///
+/// ERC20 balances are written directly to the token's balance mapping, so only
+/// tokens with a known balance slot can be simulated.
+fn set_balance(
+    db: &mut SimulationDB,
+    account: Address,
+    asset: &EvmAsset,
+    amount: U256,
+) -> Result<(), RpcError> {
+    match asset {
+        EvmAsset::Eth => {
+            let db_account = db.load_account(account).rpc_err()?;
+            db_account.info.balance = amount;
+        }
+        EvmAsset::Erc20(token) => {
+            let Some(erc20) = get_erc20_by_address(token) else {
+                return Err(RpcError::custom(format!(
+                    "Cannot simulate unknown ERC20 {}",
+                    token
+                )));
+            };
+
+            // Storage key = keccak256(abi.encode(holder, slot))
+            let mut key_preimage = [0u8; 64];
+            key_preimage[12..32].copy_from_slice(account.as_slice());
+            key_preimage[32..64].copy_from_slice(&U256::from(erc20.slot).to_be_bytes::<32>());
+            let storage_key = U256::from_be_bytes(keccak256(key_preimage).0);
+
+            db.insert_account_storage(*token, storage_key, amount)
+                .rpc_err()?;
+        }
+    }
+
+    Ok(())
+}
+
+fn signed_delta(withdrawn: U256, returned: U256) -> I256 {
+    if returned >= withdrawn {
+        I256::from_raw(returned - withdrawn)
+    } else {
+        -I256::from_raw(withdrawn - returned)
+    }
+}
@@ -18,20 +18,24 @@ use erc20s::{CHAIN_ID, ERC20S, get_erc20_by_address};
 use serde::{Deserialize, Serialize};
 use tlock_alloy::AlloyBridge;
 use tlock_pdk::{
-    runner::PluginRunner,
+    handlers::{GlobalPlugin, LifecyclePlugin, PagePlugin, VaultPlugin},
     state::StateExt,
     tlock_api::{
         RpcMethod,
         caip::{AccountId, AssetId, AssetType, ChainId},
         component::{
-            Component, account, asset, button_input, container, form, heading, heading2, hex,
+            Component, account, asset, button_input, container, form, heading, heading2,
             submit_input, text, text_input, unordered_list,
         },
         domains::Domain,
         entities::{EntityId, EthProviderId, PageId, VaultId},
+        error::{DomainError, ErrorCode},
         eth::{self},
-        global, host, page, plugin, vault,
+        host::{self, EntityCapabilities},
+        page,
+        plugin::PluginManifest,
     },
+    tlock_plugin,
     wasmi_plugin_pdk::{
         rpc_message::{RpcError, RpcErrorContext, ToRpcResult},
         transport::Transport,
@@ -49,10 +53,20 @@ struct PluginState {
 #[derive(Serialize, Deserialize, Debug, Clone)]
 struct Vault {
     entity_id: EntityId,
-    private_key: FixedBytes<32>,
     address: Address,
+    /// The private key of vaults created before keys moved to the host's
+    /// keystore. Moved there on first use.
+    #[serde(
+        default,
+        rename = "private_key",
+        skip_serializing_if = "Option::is_none"
+    )]
+    legacy_private_key: Option<FixedBytes<32>>,
 }
 
+/// Name of the host secret holding the vault's private key
+const PRIVATE_KEY_SECRET: &str = "private_key";
+
 //? We can use alloy-generated bindings for creating contract interfaces,
 //? making on-chain calls much easier.
 sol! {
@@ -63,51 +77,158 @@ sol! {
     }
 }
 
-// ---------- Plugin Handlers ----------
+#[tlock_plugin(LifecyclePlugin, GlobalPlugin, VaultPlugin, PagePlugin)]
+struct EoaVault;
 
-async fn init(transport: Transport, _params: ()) -> Result<(), RpcError> {
-    info!("Calling Init on Vault Plugin");
-
-    let provider_id =
-        host::RequestEthProvider.call(transport.clone(), ChainId::Evm(Some(CHAIN_ID)))?;
-    let vault = host::RegisterEntity.call(transport.clone(), Domain::Vault)?;
-
-    let signer = PrivateKeySigner::random();
-    transport.state().write(PluginState {
-        vault: Some(Vault {
-            entity_id: vault,
-            private_key: signer.to_bytes(),
-            address: signer.address(),
-        }),
-        provider_id,
-    })?;
+// ---------- Plugin Handlers ----------
 
-    host::RegisterEntity.call(transport.clone(), Domain::Page)?;
+#[tlock_plugin]
+impl LifecyclePlugin for EoaVault {
+    async fn get_manifest(_transport: Transport, _params: ()) -> Result<PluginManifest, RpcError> {
+        let manifest = PluginManifest::new()
+            .with_method(host::RequestEthProvider)
+            .with_method(host::LeaseNonce)
+            .with_method(host::ReleaseNonce)
+            .with_domain(Domain::Vault)
+            .with_domain(Domain::Page);
+        Ok(AlloyBridge::manifest(manifest))
+    }
 
-    Ok(())
+    async fn init(transport: Transport, _params: ()) -> Result<(), RpcError> {
+        info!("Calling Init on Vault Plugin");
+
+        let provider_id = host::RequestEthProvider.call(
+            transport.clone(),
+            (
+                Some(ChainId::Evm(Some(CHAIN_ID))),
+                EntityCapabilities::new(),
+            ),
+        )?;
+        let vault =
+            host::RegisterEntityWithCapabilities.call(transport.clone(), vault_capabilities())?;
+
+        let signer = PrivateKeySigner::random();
+        host::SecretSet.call(
+            transport.clone(),
+            (PRIVATE_KEY_SECRET.to_string(), signer.to_bytes().to_vec()),
+        )?;
+        transport.state().write(PluginState {
+            vault: Some(Vault {
+                entity_id: vault,
+                address: signer.address(),
+                legacy_private_key: None,
+            }),
+            provider_id,
+        })?;
+
+        host::RegisterEntity.call(transport.clone(), Domain::Page)?;
+
+        Ok(())
+    }
 }
 
-async fn ping(transport: Transport, _params: ()) -> Result<String, RpcError> {
-    let state: PluginState = transport.state().read()?;
+#[tlock_plugin]
+impl GlobalPlugin for EoaVault {
+    async fn ping(transport: Transport, _params: ()) -> Result<String, RpcError> {
+        let state: PluginState = transport.state().read()?;
 
-    let chain_id = eth::ChainId
-        .call_async(transport, state.provider_id)
-        .await?;
-    Ok(format!("Pong! Connected to chain: {}", chain_id))
+        let chain_id = eth::ChainId
+            .call_async(transport, state.provider_id)
+            .await?;
+        Ok(format!("Pong! Connected to chain: {}", chain_id))
+    }
 }
 
 // ---------- Vault Handlers ----------
 
-async fn get_assets(
-    transport: Transport,
-    params: VaultId,
-) -> Result<Vec<(AssetId, U256)>, RpcError> {
-    let vault_id = params;
-    info!("Received get_assets request for vault: {}", vault_id);
+#[tlock_plugin]
+impl VaultPlugin for EoaVault {
+    async fn get_assets(
+        transport: Transport,
+        params: VaultId,
+    ) -> Result<Vec<(AssetId, U256)>, RpcError> {
+        let vault_id = params;
+        info!("Received get_assets request for vault: {}", vault_id);
+
+        let vault = get_vault(transport.clone(), vault_id)?;
+        let assets = get_vault_assets(transport.clone(), &vault).await?;
+        Ok(assets)
+    }
 
-    let vault = get_vault(transport.clone(), vault_id)?;
-    let assets = get_vault_assets(transport.clone(), &vault).await?;
-    Ok(assets)
+    async fn get_deposit_address(
+        transport: Transport,
+        params: (VaultId, AssetId),
+    ) -> Result<AccountId, RpcError> {
+        let (vault_id, asset_id) = params;
+        info!("Received GetDepositAddress request for vault: {}", vault_id);
+
+        validate_chain_id(asset_id.chain_id())?;
+
+        let vault = get_vault(transport.clone(), vault_id)?;
+        let account_id = AccountId::new_evm(CHAIN_ID, vault.address);
+
+        // If the asset is supported, we MUST return a valid address.
+        match &asset_id.asset {
+            AssetType::Slip44(60) => Ok(account_id),
+            AssetType::Erc20(addr) if get_erc20_by_address(addr).is_some() => Ok(account_id),
+            _ => Err(DomainError::new(
+                ErrorCode::UNSUPPORTED_ASSET,
+                "Unsupported asset for deposit address",
+            )
+            .into()),
+        }
+    }
+
+    async fn withdraw(
+        transport: Transport,
+        params: (VaultId, AccountId, AssetId, U256),
+    ) -> Result<(), RpcError> {
+        let (vault_id, to_address, asset_id, amount) = params;
+        info!(
+            "Received Withdraw request for vault: {}, to address: {}, asset: {}, amount: {}",
+            vault_id, to_address, asset_id, amount
+        );
+
+        validate_chain_id(asset_id.chain_id())?;
+        validate_chain_id(to_address.chain_id())?;
+
+        let to_addr = to_address
+            .as_evm_address()
+            .ok_or_else(|| RpcError::Custom("Invalid to address".into()))?;
+
+        let vault = get_vault(transport.clone(), vault_id)?;
+        let signer = load_signer(transport.clone()).await?;
+        let state: PluginState = transport.state().read()?;
+        let provider = ProviderBuilder::new()
+            .wallet(signer)
+            .connect_client(AlloyBridge::new(transport.clone(), state.provider_id));
+
+        //? Lease the nonce from the host so concurrent sends from this account,
+        //? e.g. by a coordinator, don't collide
+        let nonce = host::LeaseNonce
+            .call_async(transport.clone(), (state.provider_id, vault.address))
+            .await?;
+        let result = match &asset_id.asset {
+            AssetType::Slip44(60) => withdraw_eth(&provider, to_addr, amount, nonce).await,
+            AssetType::Erc20(token) => {
+                withdraw_erc20(&provider, *token, to_addr, amount, nonce).await
+            }
+            _ => Err(DomainError::new(
+                ErrorCode::UNSUPPORTED_ASSET,
+                "Unsupported asset type for withdrawal",
+            )
+            .into()),
+        };
+
+        //? Releasing is safe even if the transaction was broadcast, since the host
+        //? never re-leases nonces below the pending transaction count
+        if result.is_err() {
+            let _ = host::ReleaseNonce
+                .call_async(transport.clone(), (state.provider_id, vault.address, nonce))
+                .await;
+        }
+        result
+    }
 }
 
 async fn get_vault_assets(
@@ -141,64 +262,16 @@ async fn get_vault_assets(
     Ok(balances)
 }
 
-async fn get_deposit_address(
-    transport: Transport,
-    params: (VaultId, AssetId),
-) -> Result<AccountId, RpcError> {
-    let (vault_id, asset_id) = params;
-    info!("Received GetDepositAddress request for vault: {}", vault_id);
-
-    validate_chain_id(asset_id.chain_id())?;
-
-    let vault = get_vault(transport.clone(), vault_id)?;
-    let account_id = AccountId::new_evm(CHAIN_ID, vault.address);
-
-    // If the asset is supported, we MUST return a valid address.
-    match &asset_id.asset {
-        AssetType::Slip44(60) => Ok(account_id),
-        AssetType::Erc20(addr) if get_erc20_by_address(addr).is_some() => Ok(account_id),
-        _ => Err(RpcError::Custom(
-            "Unsupported asset for deposit address".into(),
-        )),
-    }
-}
-
-async fn withdraw(
-    transport: Transport,
-    params: (VaultId, AccountId, AssetId, U256),
+async fn withdraw_eth(
+    provider: impl Provider,
+    to: Address,
+    amount: U256,
+    nonce: u64,
 ) -> Result<(), RpcError> {
-    let (vault_id, to_address, asset_id, amount) = params;
-    info!(
-        "Received Withdraw request for vault: {}, to address: {}, asset: {}, amount: {}",
-        vault_id, to_address, asset_id, amount
-    );
-
-    validate_chain_id(asset_id.chain_id())?;
-    validate_chain_id(to_address.chain_id())?;
-
-    let to_addr = to_address
-        .as_evm_address()
-        .ok_or_else(|| RpcError::Custom("Invalid to address".into()))?;
-
-    let vault = get_vault(transport.clone(), vault_id)?;
-    let signer: PrivateKeySigner =
-        PrivateKeySigner::from_bytes(&vault.private_key).context("Invalid private key")?;
-    let state: PluginState = transport.state().read()?;
-    let provider = ProviderBuilder::new()
-        .wallet(signer)
-        .connect_client(AlloyBridge::new(transport.clone(), state.provider_id));
-
-    match &asset_id.asset {
-        AssetType::Slip44(60) => withdraw_eth(&provider, to_addr, amount).await,
-        AssetType::Erc20(token) => withdraw_erc20(&provider, *token, to_addr, amount).await,
-        _ => Err(RpcError::Custom(
-            "Unsupported asset type for withdrawal".into(),
-        )),
-    }
-}
-
-async fn withdraw_eth(provider: impl Provider, to: Address, amount: U256) -> Result<(), RpcError> {
-    let tx = TransactionRequest::default().to(to).with_value(amount);
+    let tx = TransactionRequest::default()
+        .to(to)
+        .with_value(amount)
+        .with_nonce(nonce);
     let tx_hash = provider
         .send_transaction(tx)
         .await
@@ -215,16 +288,21 @@ async fn withdraw_erc20(
     token_address: Address,
     to: Address,
     amount: U256,
+    nonce: u64,
 ) -> Result<(), RpcError> {
     if get_erc20_by_address(&token_address).is_none() {
-        return Err(RpcError::Custom(
-            "Unsupported ERC20 token for withdrawal".into(),
-        ));
+        return Err(DomainError::new(
+            ErrorCode::UNSUPPORTED_ASSET,
+            "Unsupported ERC20 token for withdrawal",
+        )
+        .with_data(token_address)
+        .into());
     }
 
     let contract = ERC20::new(token_address, &provider);
     let tx_hash = contract
         .transfer(to, amount)
+        .nonce(nonce)
         .send()
         .await
         .rpc_err()?
@@ -237,60 +315,63 @@ async fn withdraw_erc20(
 
 // ---------- UI Handlers ----------
 
-async fn on_load(transport: Transport, page_id: PageId) -> Result<(), RpcError> {
-    info!("OnPageLoad called for page: {}", page_id);
-
-    let state: PluginState = transport.state().read()?;
-    let component = build_ui(transport.clone(), &state).await;
-    host::SetPage
-        .call_async(transport.clone(), (page_id, component))
-        .await?;
+#[tlock_plugin]
+impl PagePlugin for EoaVault {
+    async fn on_load(transport: Transport, page_id: PageId) -> Result<(), RpcError> {
+        info!("OnPageLoad called for page: {}", page_id);
 
-    Ok(())
-}
+        let state: PluginState = transport.state().read()?;
+        let component = build_ui(transport.clone(), &state).await;
+        host::SetPage
+            .call_async(transport.clone(), (page_id, component))
+            .await?;
 
-async fn on_update(
-    transport: Transport,
-    params: (PageId, page::PageEvent),
-) -> Result<(), RpcError> {
-    let (page_id, event) = params;
-    info!("Page updated in Vault Plugin: {:?}", event);
-
-    let mut notification = None;
-    match event {
-        page::PageEvent::ButtonClicked(id) if id == "generate_dev_key" => {
-            let signer = PrivateKeySigner::random();
-            handle_new_signer(transport.clone(), signer.clone()).await?;
-        }
-        page::PageEvent::ButtonClicked(id) if id == "refresh_assets" => {
-            // Simply rebuild the UI to refresh asset balances
-            notification = Some("Balances refreshed".to_string());
-        }
-        page::PageEvent::FormSubmitted(id, form_data) if id == "private_key_form" => {
-            handle_dev_private_key(transport.clone(), form_data).await?;
-        }
-        _ => {
-            warn!("Unhandled page event: {:?}", event);
-            return Ok(());
-        }
+        Ok(())
     }
 
-    let state: PluginState = transport.state().read()?;
-    let component = build_ui(transport.clone(), &state).await;
-    host::SetPage
-        .call_async(transport.clone(), (page_id, component))
-        .await?;
+    async fn on_update(
+        transport: Transport,
+        params: (PageId, page::PageEvent),
+    ) -> Result<(), RpcError> {
+        let (page_id, event) = params;
+        info!("Page updated in Vault Plugin: {:?}", event);
+
+        let mut notification = None;
+        match event {
+            page::PageEvent::ButtonClicked(id) if id == "generate_dev_key" => {
+                let signer = PrivateKeySigner::random();
+                handle_new_signer(transport.clone(), signer.clone()).await?;
+            }
+            page::PageEvent::ButtonClicked(id) if id == "refresh_assets" => {
+                // Simply rebuild the UI to refresh asset balances
+                notification = Some("Balances refreshed".to_string());
+            }
+            page::PageEvent::FormSubmitted(id, form_data) if id == "private_key_form" => {
+                handle_dev_private_key(transport.clone(), form_data).await?;
+            }
+            _ => {
+                warn!("Unhandled page event: {:?}", event);
+                return Ok(());
+            }
+        }
 
-    if let Some(notification) = notification {
-        host::Notify
-            .call_async(
-                transport.clone(),
-                (host::NotifyLevel::Info, notification.to_string()),
-            )
+        let state: PluginState = transport.state().read()?;
+        let component = build_ui(transport.clone(), &state).await;
+        host::SetPage
+            .call_async(transport.clone(), (page_id, component))
             .await?;
-    }
 
-    Ok(())
+        if let Some(notification) = notification {
+            host::Notify
+                .call_async(
+                    transport.clone(),
+                    (host::NotifyLevel::Info, notification.to_string()),
+                )
+                .await?;
+        }
+
+        Ok(())
+    }
 }
 
 async fn handle_dev_private_key(
@@ -309,20 +390,59 @@ async fn handle_dev_private_key(
 async fn handle_new_signer(transport: Transport, signer: PrivateKeySigner) -> Result<(), RpcError> {
     let address = signer.address();
 
-    let entity_id = host::RegisterEntity
-        .call_async(transport.clone(), Domain::Vault)
+    let entity_id = host::RegisterEntityWithCapabilities
+        .call_async(transport.clone(), vault_capabilities())
         .await?;
+    store_private_key(transport.clone(), signer.to_bytes()).await?;
 
     let mut state = transport.state().lock::<PluginState>()?;
     state.vault = Some(Vault {
         entity_id,
-        private_key: signer.to_bytes(),
         address,
+        legacy_private_key: None,
     });
 
     Ok(())
 }
 
+async fn store_private_key(transport: Transport, key: FixedBytes<32>) -> Result<(), RpcError> {
+    host::SecretSet
+        .call_async(transport, (PRIVATE_KEY_SECRET.to_string(), key.to_vec()))
+        .await
+}
+
+/// Loads the vault's signer from the host's keystore, first moving a legacy
+/// key out of plugin state if there is one.
+async fn load_signer(transport: Transport) -> Result<PrivateKeySigner, RpcError> {
+    let state: PluginState = transport.state().read()?;
+    if let Some(key) = state.vault.and_then(|v| v.legacy_private_key) {
+        //? Store the key before clearing it so it can't be lost in between
+        store_private_key(transport.clone(), key).await?;
+        let mut state = transport.state().lock::<PluginState>()?;
+        if let Some(vault) = state.vault.as_mut() {
+            vault.legacy_private_key = None;
+        }
+        return PrivateKeySigner::from_bytes(&key).context("Invalid private key");
+    }
+
+    let key = host::SecretGet
+        .call_async(transport, PRIVATE_KEY_SECRET.to_string())
+        .await?
+        .context("Private key missing from keystore")?;
+    PrivateKeySigner::from_slice(&key).context("Invalid private key")
+}
+
+/// The vault holds ETH and the known ERC20s on a single chain
+fn vault_capabilities() -> (Domain, EntityCapabilities) {
+    let capabilities = ERC20S.iter().fold(
+        EntityCapabilities::new()
+            .with_chain(ChainId::new_evm(CHAIN_ID))
+            .with_asset(AssetId::eth(CHAIN_ID)),
+        |capabilities, erc20| capabilities.with_asset(erc20.asset_id.clone()),
+    );
+    (Domain::Vault, capabilities)
+}
+
 async fn build_ui(transport: Transport, state: &PluginState) -> Component {
     let mut sections = vec![
         heading("EOA Vault"),
@@ -345,8 +465,6 @@ async fn build_ui(transport: Transport, state: &PluginState) -> Component {
     sections.push(heading2("Vault Info"));
     sections.push(text("Vault Address:"));
     sections.push(account(AccountId::new_evm(CHAIN_ID, vault.address)));
-    sections.push(text("Private Key:"));
-    sections.push(hex(vault.private_key.as_slice()));
 
     sections.push(heading2("Assets"));
 
@@ -372,17 +490,22 @@ async fn build_ui(transport: Transport, state: &PluginState) -> Component {
 fn validate_chain_id(chain_id: &ChainId) -> Result<(), RpcError> {
     match chain_id {
         ChainId::Evm(Some(id)) if *id == CHAIN_ID => Ok(()),
-        ChainId::Evm(Some(id)) => Err(RpcError::Custom(format!("Unsupported EVM chain: {}", id))),
-        _ => Err(RpcError::Custom("Unsupported chain ID".to_string())),
+        _ => Err(DomainError::new(
+            ErrorCode::UNSUPPORTED_CHAIN,
+            format!("Unsupported chain: {}", chain_id),
+        )
+        .into()),
     }
 }
 
 fn get_vault(transport: Transport, _id: VaultId) -> Result<Vault, RpcError> {
     let state: PluginState = transport.state().read()?;
-    let vault = state
-        .vault
-        .clone()
-        .ok_or_else(|| RpcError::Custom("No vault configured in plugin state".to_string()))?;
+    let vault = state.vault.clone().ok_or_else(|| {
+        DomainError::new(
+            ErrorCode::NOT_CONFIGURED,
+            "No vault configured in plugin state",
+        )
+    })?;
 
     Ok(vault)
 }
@@ -426,13 +549,5 @@ fn main() {
     // - Sets up async runtime
     // - Reads initial host request and routes to handler
     // - Handles bidirectional RPC until final response
-    PluginRunner::new()
-        .with_method(plugin::Init, init)
-        .with_method(global::Ping, ping)
-        .with_method(vault::GetAssets, get_assets)
-        .with_method(vault::Withdraw, withdraw)
-        .with_method(vault::GetDepositAddress, get_deposit_address)
-        .with_method(page::OnLoad, on_load)
-        .with_method(page::OnUpdate, on_update)
-        .run();
+    EoaVault::runner().run();
 }
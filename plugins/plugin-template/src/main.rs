@@ -1,15 +1,22 @@
 use std::io::stderr;
 
 use tlock_pdk::{
-    runner::PluginRunner,
+    handlers::GlobalPlugin,
     tlock_api::{RpcMethod, global},
+    tlock_plugin,
     wasmi_plugin_pdk::{rpc_message::RpcError, transport::Transport},
 };
 use tracing_subscriber::fmt;
 
-async fn ping(transport: Transport, _: ()) -> Result<String, RpcError> {
-    global::Ping.call_async(transport, ()).await?;
-    Ok("pong".to_string())
+#[tlock_plugin(GlobalPlugin)]
+struct Plugin;
+
+#[tlock_plugin]
+impl GlobalPlugin for Plugin {
+    async fn ping(transport: Transport, _: ()) -> Result<String, RpcError> {
+        global::Ping.call_async(transport, ()).await?;
+        Ok("pong".to_string())
+    }
 }
 
 fn main() {
@@ -20,5 +27,5 @@ fn main() {
         .compact()
         .init();
 
-    PluginRunner::new().with_method(global::Ping, ping).run();
+    Plugin::runner().run();
 }
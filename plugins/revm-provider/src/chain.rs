@@ -2,9 +2,9 @@ use std::collections::BTreeMap;
 
 use erc20s::get_erc20_by_address;
 use revm::{
-    Context, DatabaseRef, ExecuteEvm, MainBuilder, MainContext,
+    Context, DatabaseRef, ExecuteEvm, InspectEvm, MainBuilder, MainContext,
     bytecode::LegacyAnalyzedBytecode,
-    context::{BlockEnv, TxEnv, result::ExecutionResult},
+    context::{BlockEnv, CfgEnv, TxEnv, result::ExecutionResult},
     database::{CacheDB as RevmCacheDB, WrapDatabaseRef},
     interpreter::instructions::utility::IntoU256,
     primitives::{Address, B256, U256, address, keccak256},
@@ -35,6 +35,7 @@ use crate::{
     remote_db::{AlloyDBError, RemoteDB},
     rpc::header_to_block_env,
     state::{get_chain_key, get_layer_key},
+    tracer::StructLogger,
 };
 
 /// Represents a forked execution chain for simulating Ethereum transactions.
@@ -71,6 +72,28 @@ pub struct SimulatedBlock {
     pub results: Vec<ExecutionResult>,
 }
 
+/// Everything a call against the chain needs, with overrides applied.
+struct CallEnv {
+    db: RevmCacheDB<Box<dyn DatabaseRef<Error = CacheDBError<AlloyDBError>>>>,
+    block: BlockEnv,
+    tx: TxEnv,
+    chain_id: u64,
+    unconstrained: bool,
+}
+
+impl CallEnv {
+    fn configure(&self, cfg: &mut CfgEnv) {
+        cfg.tx_chain_id_check = false;
+        cfg.disable_nonce_check = true;
+        cfg.chain_id = self.chain_id;
+        if self.unconstrained {
+            cfg.disable_balance_check = true;
+            cfg.disable_base_fee = true;
+            cfg.disable_block_gas_limit = true;
+        }
+    }
+}
+
 #[derive(Error, Debug)]
 pub enum ChainError {
     #[error("RPC Error: {0}")]
@@ -201,12 +224,71 @@ impl Chain {
     /// certain constraints such as gas limits and balance checks.
     pub fn call(
         &self,
-        mut tx: TxEnv,
+        tx: TxEnv,
         block_id: BlockId,
         state_override: Option<StateOverride>,
         block_override: Option<BlockOverrides>,
         unconstrained: bool,
     ) -> Result<ExecutionResult, ChainError> {
+        let (result, _) =
+            self.simulate(tx, block_id, state_override, block_override, unconstrained)?;
+        Ok(result)
+    }
+
+    /// Same as `call`, but also returns the state touched by the call.
+    pub fn simulate(
+        &self,
+        tx: TxEnv,
+        block_id: BlockId,
+        state_override: Option<StateOverride>,
+        block_override: Option<BlockOverrides>,
+        unconstrained: bool,
+    ) -> Result<(ExecutionResult, EvmState), ChainError> {
+        let env = self.call_env(tx, block_id, state_override, block_override, unconstrained)?;
+        let mut evm = Context::mainnet()
+            .modify_cfg_chained(|cfg| env.configure(cfg))
+            .with_db(env.db)
+            .with_block(env.block)
+            .build_mainnet();
+
+        let result = evm
+            .transact(env.tx)
+            .map_err(|e| ChainError::Evm(e.to_string()))?;
+        Ok((result.result, result.state))
+    }
+
+    /// Same as an unconstrained `call`, but records each opcode executed with
+    /// `logger`.
+    pub fn trace(
+        &self,
+        tx: TxEnv,
+        block_id: BlockId,
+        state_override: Option<StateOverride>,
+        block_override: Option<BlockOverrides>,
+        logger: StructLogger,
+    ) -> Result<(ExecutionResult, StructLogger), ChainError> {
+        let env = self.call_env(tx, block_id, state_override, block_override, true)?;
+        let mut evm = Context::mainnet()
+            .modify_cfg_chained(|cfg| env.configure(cfg))
+            .with_db(env.db)
+            .with_block(env.block)
+            .build_mainnet_with_inspector(logger);
+
+        let result = evm
+            .inspect_tx(env.tx)
+            .map_err(|e| ChainError::Evm(e.to_string()))?;
+        Ok((result.result, evm.inspector))
+    }
+
+    /// Prepares the environment for a call against the chain at `block_id`.
+    fn call_env(
+        &self,
+        mut tx: TxEnv,
+        block_id: BlockId,
+        state_override: Option<StateOverride>,
+        block_override: Option<BlockOverrides>,
+        unconstrained: bool,
+    ) -> Result<CallEnv, ChainError> {
         let state = self.clone_state()?;
 
         let mut block_env = match get_blockenv(&state, &block_id) {
@@ -253,26 +335,13 @@ impl Chain {
             tx.caller = address!("0xdeadbeefdeadbeefdeadbeefdeadbeefdeadbeef");
         }
 
-        let mut evm = Context::mainnet()
-            .modify_cfg_chained(|cfg| {
-                cfg.tx_chain_id_check = false;
-                cfg.disable_nonce_check = true;
-                cfg.chain_id = state.chain_id;
-            })
-            .with_db(overlay_db)
-            .with_block(block_env)
-            .build_mainnet();
-
-        if unconstrained {
-            evm.cfg.disable_balance_check = true;
-            evm.cfg.disable_base_fee = true;
-            evm.cfg.disable_block_gas_limit = true;
-        }
-
-        let result = evm
-            .transact(tx)
-            .map_err(|e| ChainError::Evm(e.to_string()))?;
-        Ok(result.result)
+        Ok(CallEnv {
+            db: overlay_db,
+            block: block_env,
+            tx,
+            chain_id: state.chain_id,
+            unconstrained,
+        })
     }
 
     /// Mines a block containing the transaction and updates the chain state.
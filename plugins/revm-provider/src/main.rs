@@ -4,7 +4,9 @@ use erc20s::ERC20S;
 use revm::primitives::{Address, Bytes, alloy_primitives::TxHash, hex};
 use serde::{Deserialize, Serialize};
 use tlock_pdk::{
-    runner::PluginRunner,
+    PluginState,
+    fees::{FEE_HISTORY_BLOCKS, REWARD_PERCENTILES},
+    handlers::{EthProviderPlugin, FeesPlugin, LifecyclePlugin, PagePlugin, TracePlugin},
     state::StateExt,
     tlock_api::{
         RpcMethod,
@@ -13,20 +15,26 @@ use tlock_pdk::{
             eips::{BlockId, BlockNumberOrTag},
             primitives::U256,
             rpc::types::{
-                Block, BlockOverrides, BlockTransactionsKind, Filter, Log, Transaction,
-                TransactionReceipt, TransactionRequest, state::StateOverride,
+                AccessListResult, Block, BlockOverrides, BlockTransactionsKind, Filter, Log,
+                Transaction, TransactionReceipt, TransactionRequest,
+                state::StateOverride,
+                trace::geth::{GethDebugTracingCallOptions, GethTrace},
             },
         },
         caip::AccountId,
         component::{
             Component, button_input, container, dropdown, form, heading, heading2, submit_input,
-            text, text_input, unordered_list,
+            tabs, text, text_input, unordered_list,
         },
         domains::Domain,
         entities::{EntityId, EthProviderId, PageId},
-        eth::{self},
-        host, page, plugin,
+        fees::{self, FeeSuggestion},
+        host::{self, EntityCapabilities},
+        page,
+        plugin::PluginManifest,
+        trace,
     },
+    tlock_plugin,
     wasmi_plugin_pdk::{
         rpc_message::{RpcError, RpcErrorContext},
         transport::Transport,
@@ -38,7 +46,6 @@ use tracing_subscriber::fmt;
 use crate::{
     provider::Provider,
     remote_db::{get_chain_id, get_latest_block_header},
-    state::get_main_key,
 };
 
 mod cache_db;
@@ -48,8 +55,10 @@ mod provider;
 mod remote_db;
 mod rpc;
 mod state;
+mod tracer;
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, PluginState)]
+#[plugin_state(key = "revm_fork_provider/main")]
 struct State {
     page_id: PageId,
 }
@@ -68,252 +77,342 @@ fn get_current_timestamp() -> u64 {
         .as_secs()
 }
 
-async fn init(transport: Transport, _params: ()) -> Result<(), RpcError> {
-    handle_reset_fork(transport.clone())?;
-
-    // Initialize the last reset timestamp
-    transport
-        .state()
-        .write_key(FORK_RESET_TIMESTAMP_KEY, get_current_timestamp())?;
-
-    //? Register the revm entities
-    host::RegisterEntity
-        .call_async(transport.clone(), Domain::EthProvider)
-        .await?;
-    let page_id = host::RegisterEntity
-        .call_async(transport.clone(), Domain::Page)
-        .await?;
-
-    let page_id = match page_id {
-        EntityId::Page(id) => Some(id),
-        _ => None,
+#[tlock_plugin(
+    LifecyclePlugin,
+    PagePlugin,
+    EthProviderPlugin,
+    TracePlugin,
+    FeesPlugin
+)]
+struct RevmProvider;
+
+#[tlock_plugin]
+impl LifecyclePlugin for RevmProvider {
+    async fn get_manifest(_transport: Transport, _params: ()) -> Result<PluginManifest, RpcError> {
+        Ok(PluginManifest::new()
+            .with_method(host::Fetch)
+            .with_origin(RPC_URL)
+            .with_domain(Domain::EthProvider)
+            .with_domain(Domain::Page))
     }
-    .context("Invalid Page ID")?;
 
-    //? Write initial state
-    let state = State { page_id };
-    let state_key = get_main_key(PROVIDER_KEY);
-    transport.state().write_key(state_key, state)?;
+    async fn init(transport: Transport, _params: ()) -> Result<(), RpcError> {
+        handle_reset_fork(transport.clone())?;
 
-    Ok(())
-}
+        // Initialize the last reset timestamp
+        transport
+            .state()
+            .write_key(FORK_RESET_TIMESTAMP_KEY, get_current_timestamp())?;
+
+        //? Register the revm entities
+        let capabilities = EntityCapabilities::new()
+            .with_method(trace::TraceCall)
+            .with_method(fees::Suggest);
+        host::RegisterEntityWithCapabilities
+            .call_async(transport.clone(), (Domain::EthProvider, capabilities))
+            .await?;
+        let page_id = host::RegisterEntity
+            .call_async(transport.clone(), Domain::Page)
+            .await?;
 
-async fn on_load(transport: Transport, page_id: PageId) -> Result<(), RpcError> {
-    let provider = load_provider(transport.clone())?;
+        let page_id = match page_id {
+            EntityId::Page(id) => Some(id),
+            _ => None,
+        }
+        .context("Invalid Page ID")?;
 
-    let component = build_ui(provider)?;
-    host::SetPage
-        .call_async(transport, (page_id, component))
-        .await?;
-    Ok(())
+        //? Write initial state
+        let state = State { page_id };
+        transport.state().save(&state)?;
+
+        Ok(())
+    }
 }
 
-async fn on_update(
-    transport: Transport,
-    params: (PageId, page::PageEvent),
-) -> Result<(), RpcError> {
-    let (page_id, event) = params;
-    info!("Page updated: {:?}", event);
-
-    #[allow(unused_assignments)]
-    let mut notification = None;
-    match event {
-        page::PageEvent::ButtonClicked(button_id) if button_id == "reset_fork" => {
-            handle_reset_fork(transport.clone())?;
-            transport
-                .state()
-                .write_key(FORK_RESET_TIMESTAMP_KEY, get_current_timestamp())?;
-            notification = Some("Fork reset to chain head".to_string());
-        }
-        page::PageEvent::ButtonClicked(button_id) if button_id == "mine_fork" => {
-            handle_mine(transport.clone())?;
-            notification = Some("Mined one block".to_string());
-        }
-        page::PageEvent::FormSubmitted(form_id, form_data) if form_id == "deal_form" => {
-            handle_deal(transport.clone(), form_data)?;
-            notification = Some("Deal executed".to_string());
+#[tlock_plugin]
+impl PagePlugin for RevmProvider {
+    async fn on_load(transport: Transport, page_id: PageId) -> Result<(), RpcError> {
+        let provider = load_provider(transport.clone())?;
+
+        let component = build_ui(provider)?;
+        host::SetPage
+            .call_async(transport, (page_id, component))
+            .await?;
+        Ok(())
+    }
+
+    async fn on_update(
+        transport: Transport,
+        params: (PageId, page::PageEvent),
+    ) -> Result<(), RpcError> {
+        let (page_id, event) = params;
+        info!("Page updated: {:?}", event);
+
+        #[allow(unused_assignments)]
+        let mut notification = None;
+        match event {
+            page::PageEvent::ButtonClicked(button_id) if button_id == "reset_fork" => {
+                handle_reset_fork(transport.clone())?;
+                transport
+                    .state()
+                    .write_key(FORK_RESET_TIMESTAMP_KEY, get_current_timestamp())?;
+                notification = Some("Fork reset to chain head".to_string());
+            }
+            page::PageEvent::ButtonClicked(button_id) if button_id == "mine_fork" => {
+                handle_mine(transport.clone())?;
+                notification = Some("Mined one block".to_string());
+            }
+            page::PageEvent::FormSubmitted(form_id, form_data) if form_id == "deal_form" => {
+                handle_deal(transport.clone(), form_data)?;
+                notification = Some("Deal executed".to_string());
+            }
+            //? The frontend switches tabs itself, there's nothing to re-render
+            page::PageEvent::TabChanged(..) => return Ok(()),
+            _ => {
+                warn!("Unhandled page event: {:?}", event);
+                return Ok(());
+            }
         }
-        _ => {
-            warn!("Unhandled page event: {:?}", event);
-            return Ok(());
+
+        let provider = load_provider(transport.clone())?;
+        let component = build_ui(provider)?;
+        host::SetPage
+            .call_async(transport.clone(), (page_id, component))
+            .await?;
+        if let Some(message) = notification {
+            host::Notify
+                .call_async(
+                    transport,
+                    (tlock_pdk::tlock_api::host::NotifyLevel::Info, message),
+                )
+                .await?;
         }
+
+        Ok(())
     }
+}
 
-    let provider = load_provider(transport.clone())?;
-    let component = build_ui(provider)?;
-    host::SetPage
-        .call_async(transport.clone(), (page_id, component))
-        .await?;
-    if let Some(message) = notification {
-        host::Notify
-            .call_async(
-                transport,
-                (tlock_pdk::tlock_api::host::NotifyLevel::Info, message),
-            )
-            .await?;
+#[tlock_plugin]
+impl EthProviderPlugin for RevmProvider {
+    async fn chain_id(transport: Transport, _: EthProviderId) -> Result<U256, RpcError> {
+        let fork = load_provider(transport.clone())?;
+        Ok(U256::from(fork.state.chain_id))
     }
 
-    Ok(())
-}
+    async fn block_number(transport: Transport, _: EthProviderId) -> Result<u64, RpcError> {
+        let fork = load_provider(transport.clone())?;
+        Ok(fork.block_number()?)
+    }
 
-async fn chain_id(transport: Transport, _: EthProviderId) -> Result<U256, RpcError> {
-    let fork = load_provider(transport.clone())?;
-    Ok(U256::from(fork.state.chain_id))
-}
+    async fn gas_price(transport: Transport, _: EthProviderId) -> Result<u128, RpcError> {
+        let fork = load_provider(transport.clone())?;
+        Ok(fork.gas_price()?)
+    }
 
-async fn block_number(transport: Transport, _: EthProviderId) -> Result<u64, RpcError> {
-    let fork = load_provider(transport.clone())?;
-    Ok(fork.block_number()?)
-}
+    async fn max_priority_fee_per_gas(
+        transport: Transport,
+        _: EthProviderId,
+    ) -> Result<u128, RpcError> {
+        let fork = load_provider(transport.clone())?;
+        Ok(fork.max_priority_fee_per_gas()?)
+    }
 
-async fn gas_price(transport: Transport, _: EthProviderId) -> Result<u128, RpcError> {
-    let fork = load_provider(transport.clone())?;
-    Ok(fork.gas_price()?)
-}
+    async fn blob_base_fee(transport: Transport, _: EthProviderId) -> Result<u128, RpcError> {
+        let fork = load_provider(transport.clone())?;
+        Ok(fork.blob_base_fee()?)
+    }
 
-async fn get_balance(
-    transport: Transport,
-    params: (EthProviderId, Address, BlockId),
-) -> Result<U256, RpcError> {
-    let (_, address, block_id) = params;
-    let fork = load_provider(transport.clone())?;
-    let balance = fork.get_balance(address, block_id)?;
-    Ok(balance)
-}
+    async fn get_balance(
+        transport: Transport,
+        params: (EthProviderId, Address, BlockId),
+    ) -> Result<U256, RpcError> {
+        let (_, address, block_id) = params;
+        let fork = load_provider(transport.clone())?;
+        let balance = fork.get_balance(address, block_id)?;
+        Ok(balance)
+    }
 
-async fn get_block(
-    transport: Transport,
-    params: (EthProviderId, BlockId, BlockTransactionsKind),
-) -> Result<Block, RpcError> {
-    let (_, block_id, tx_kind) = params;
-    let fork = load_provider(transport.clone())?;
-    Ok(fork.get_block(block_id, tx_kind)?)
-}
+    async fn get_block(
+        transport: Transport,
+        params: (EthProviderId, BlockId, BlockTransactionsKind),
+    ) -> Result<Block, RpcError> {
+        let (_, block_id, tx_kind) = params;
+        let fork = load_provider(transport.clone())?;
+        Ok(fork.get_block(block_id, tx_kind)?)
+    }
 
-async fn get_code(
-    transport: Transport,
-    params: (EthProviderId, Address, BlockId),
-) -> Result<Bytes, RpcError> {
-    let (_, address, block_id) = params;
-    let fork = load_provider(transport.clone())?;
-    let code = fork.get_code(address, block_id)?.unwrap_or_default();
-    Ok(code)
-}
+    async fn get_code(
+        transport: Transport,
+        params: (EthProviderId, Address, BlockId),
+    ) -> Result<Bytes, RpcError> {
+        let (_, address, block_id) = params;
+        let fork = load_provider(transport.clone())?;
+        let code = fork.get_code(address, block_id)?.unwrap_or_default();
+        Ok(code)
+    }
 
-async fn get_transaction_count(
-    transport: Transport,
-    params: (EthProviderId, Address, BlockId),
-) -> Result<u64, RpcError> {
-    let (_, address, block_id) = params;
-    let fork = load_provider(transport.clone())?;
-    let transaction_count = fork.get_transaction_count(address, block_id)?;
-    Ok(transaction_count)
-}
+    async fn get_transaction_count(
+        transport: Transport,
+        params: (EthProviderId, Address, BlockId),
+    ) -> Result<u64, RpcError> {
+        let (_, address, block_id) = params;
+        let fork = load_provider(transport.clone())?;
+        let transaction_count = fork.get_transaction_count(address, block_id)?;
+        Ok(transaction_count)
+    }
 
-async fn get_transaction_by_hash(
-    transport: Transport,
-    params: (EthProviderId, TxHash),
-) -> Result<Transaction, RpcError> {
-    let (_, tx_hash) = params;
-    let fork = load_provider(transport.clone())?;
-    Ok(fork.get_transaction_by_hash(tx_hash)?)
-}
+    async fn get_transaction_by_hash(
+        transport: Transport,
+        params: (EthProviderId, TxHash),
+    ) -> Result<Transaction, RpcError> {
+        let (_, tx_hash) = params;
+        let fork = load_provider(transport.clone())?;
+        Ok(fork.get_transaction_by_hash(tx_hash)?)
+    }
 
-async fn get_transaction_receipt(
-    transport: Transport,
-    params: (EthProviderId, TxHash),
-) -> Result<TransactionReceipt, RpcError> {
-    let (_, tx_hash) = params;
-    let fork = load_provider(transport.clone())?;
-    let transaction_receipt = fork
-        .get_transaction_receipt(tx_hash)
-        .ok_or(RpcError::Custom("Transaction receipt not found".into()))?;
-    Ok(transaction_receipt)
-}
+    async fn get_transaction_receipt(
+        transport: Transport,
+        params: (EthProviderId, TxHash),
+    ) -> Result<TransactionReceipt, RpcError> {
+        let (_, tx_hash) = params;
+        let fork = load_provider(transport.clone())?;
+        let transaction_receipt = fork
+            .get_transaction_receipt(tx_hash)
+            .ok_or(RpcError::Custom("Transaction receipt not found".into()))?;
+        Ok(transaction_receipt)
+    }
 
-async fn get_block_receipts(
-    transport: Transport,
-    params: (EthProviderId, BlockId),
-) -> Result<Vec<TransactionReceipt>, RpcError> {
-    let (_, block_id) = params;
-    let fork = load_provider(transport.clone())?;
-    Ok(fork.get_block_receipts(block_id)?)
-}
+    async fn get_block_receipts(
+        transport: Transport,
+        params: (EthProviderId, BlockId),
+    ) -> Result<Vec<TransactionReceipt>, RpcError> {
+        let (_, block_id) = params;
+        let fork = load_provider(transport.clone())?;
+        Ok(fork.get_block_receipts(block_id)?)
+    }
 
-async fn call(
-    transport: Transport,
-    params: (
-        EthProviderId,
-        TransactionRequest,
-        BlockId,
-        Option<StateOverride>,
-        Option<BlockOverrides>,
-    ),
-) -> Result<Bytes, RpcError> {
-    let (_, tx_request, block_id, state_override, block_override) = params;
-    let fork = load_provider(transport.clone())?;
-    let resp = fork.call(tx_request, block_id, state_override, block_override)?;
-    Ok(resp)
-}
+    async fn call(
+        transport: Transport,
+        params: (
+            EthProviderId,
+            TransactionRequest,
+            BlockId,
+            Option<StateOverride>,
+            Option<BlockOverrides>,
+        ),
+    ) -> Result<Bytes, RpcError> {
+        let (_, tx_request, block_id, state_override, block_override) = params;
+        let fork = load_provider(transport.clone())?;
+        let resp = fork.call(tx_request, block_id, state_override, block_override)?;
+        Ok(resp)
+    }
 
-async fn estimate_gas(
-    transport: Transport,
-    params: (
-        EthProviderId,
-        TransactionRequest,
-        BlockId,
-        Option<StateOverride>,
-        Option<BlockOverrides>,
-    ),
-) -> Result<u64, RpcError> {
-    let (_, tx_request, block_id, state_override, block_override) = params;
-    let fork = load_provider(transport.clone())?;
-    let resp = fork.estimate_gas(tx_request, block_id, state_override, block_override)?;
-    Ok(resp)
-}
+    async fn estimate_gas(
+        transport: Transport,
+        params: (
+            EthProviderId,
+            TransactionRequest,
+            BlockId,
+            Option<StateOverride>,
+            Option<BlockOverrides>,
+        ),
+    ) -> Result<u64, RpcError> {
+        let (_, tx_request, block_id, state_override, block_override) = params;
+        let fork = load_provider(transport.clone())?;
+        let resp = fork.estimate_gas(tx_request, block_id, state_override, block_override)?;
+        Ok(resp)
+    }
 
-async fn send_raw_transaction(
-    transport: Transport,
-    params: (EthProviderId, Bytes),
-) -> Result<TxHash, RpcError> {
-    let (_, raw_tx) = params;
-    let fork = load_provider(transport.clone())?;
-    let tx = fork.send_raw_transaction(raw_tx)?;
+    async fn create_access_list(
+        transport: Transport,
+        params: (EthProviderId, TransactionRequest, BlockId),
+    ) -> Result<AccessListResult, RpcError> {
+        let (_, tx_request, block_id) = params;
+        let fork = load_provider(transport.clone())?;
+        Ok(fork.create_access_list(tx_request, block_id)?)
+    }
 
-    info!("Transaction sent");
-    let state_key = get_main_key(PROVIDER_KEY);
-    let state: State = transport.state().read_key(state_key)?;
+    async fn send_raw_transaction(
+        transport: Transport,
+        params: (EthProviderId, Bytes),
+    ) -> Result<TxHash, RpcError> {
+        let (_, raw_tx) = params;
+        let fork = load_provider(transport.clone())?;
+        let tx = fork.send_raw_transaction(raw_tx)?;
+
+        info!("Transaction sent");
+        let state: State = transport
+            .state()
+            .load()?
+            .context("Provider state missing")?;
+
+        host::Notify.call(
+            transport.clone(),
+            (host::NotifyLevel::Info, "Mined".to_string()),
+        )?;
+
+        let provider = load_provider(transport.clone())?;
+        let component = build_ui(provider)?;
+        host::SetPage
+            .call_async(transport.clone(), (state.page_id, component))
+            .await?;
 
-    host::Notify.call(
-        transport.clone(),
-        (host::NotifyLevel::Info, "Mined".to_string()),
-    )?;
+        Ok(tx)
+    }
 
-    let provider = load_provider(transport.clone())?;
-    let component = build_ui(provider)?;
-    host::SetPage
-        .call_async(transport.clone(), (state.page_id, component))
-        .await?;
+    async fn get_logs(
+        transport: Transport,
+        params: (EthProviderId, Filter),
+    ) -> Result<Vec<Log>, RpcError> {
+        let (_, filter) = params;
+        let fork = load_provider(transport.clone())?;
+        Ok(fork.get_logs(filter)?)
+    }
 
-    Ok(tx)
+    async fn fee_history(
+        transport: Transport,
+        params: (EthProviderId, u64, BlockNumberOrTag, Vec<f64>),
+    ) -> Result<alloy::rpc::types::FeeHistory, RpcError> {
+        let (_, block_count, newest_block, reward_percentiles) = params;
+        let fork = load_provider(transport.clone())?;
+        Ok(fork.fee_history(block_count, newest_block, reward_percentiles)?)
+    }
 }
 
-async fn get_logs(
-    transport: Transport,
-    params: (EthProviderId, Filter),
-) -> Result<Vec<Log>, RpcError> {
-    let (_, filter) = params;
-    let fork = load_provider(transport.clone())?;
-    Ok(fork.get_logs(filter)?)
+#[tlock_plugin]
+impl TracePlugin for RevmProvider {
+    async fn trace_call(
+        transport: Transport,
+        params: (
+            EthProviderId,
+            TransactionRequest,
+            BlockId,
+            GethDebugTracingCallOptions,
+        ),
+    ) -> Result<GethTrace, RpcError> {
+        let (_, tx_request, block_id, opts) = params;
+        let fork = load_provider(transport.clone())?;
+        Ok(fork.trace_call(tx_request, block_id, opts)?)
+    }
 }
 
-async fn fee_history(
-    transport: Transport,
-    params: (EthProviderId, u64, BlockNumberOrTag, Vec<f64>),
-) -> Result<alloy::rpc::types::FeeHistory, RpcError> {
-    let (_, block_count, newest_block, reward_percentiles) = params;
-    let fork = load_provider(transport.clone())?;
-    Ok(fork.fee_history(block_count, newest_block, reward_percentiles)?)
+#[tlock_plugin]
+impl FeesPlugin for RevmProvider {
+    async fn suggest(
+        transport: Transport,
+        _provider_id: EthProviderId,
+    ) -> Result<FeeSuggestion, RpcError> {
+        let fork = load_provider(transport.clone())?;
+        let fee_history = fork.fee_history(
+            FEE_HISTORY_BLOCKS,
+            BlockNumberOrTag::Latest,
+            REWARD_PERCENTILES.to_vec(),
+        )?;
+
+        let suggestion =
+            tlock_pdk::fees::suggest(&fee_history).context("Incomplete fee history")?;
+        Ok(suggestion)
+    }
 }
 
 /// Returns a fork provider based on the saved state. Resets the fork
@@ -345,29 +444,22 @@ fn load_provider(transport: Transport) -> Result<Provider, RpcError> {
 }
 
 fn build_ui(provider: Provider) -> Result<Component, RpcError> {
-    let mut sections = vec![
-        heading("REVM Provider"),
-        text("Use a forked Ethereum provided, running locally in your browser"),
-    ];
-
     // Fork info section
     let chain_id = provider.state.chain_id;
     let fork_block = provider.state.fork_block;
     let latest_block = provider.block_number()? - 1;
-    sections.extend(vec![
-        heading2("Fork Information"),
+    let fork_info = vec![
         text(format!("Chain ID: {}", chain_id)),
         text(format!("Fork Block: {:?}", fork_block)),
         text(format!("Current Block: {}", latest_block)),
         button_input("mine_fork", "Mine"),
         button_input("reset_fork", "Reset Fork to Chain Head"),
-    ]);
+    ];
 
     // Cheatcodes section
     let mut asset_symbols = vec!["ETH".to_string()];
     asset_symbols.extend(ERC20S.iter().map(|e| e.symbol.to_string()));
-    sections.extend(vec![
-        heading2("Cheatcodes"),
+    let cheatcodes = vec![
         heading2("Deal"),
         text("Sets the balance of an account"),
         form(
@@ -379,19 +471,17 @@ fn build_ui(provider: Provider) -> Result<Component, RpcError> {
                 submit_input("Execute Deal"),
             ],
         ),
-    ]);
+    ];
 
     // Transactions section
     let tx_count: usize = provider.state.transactions.values().map(|v| v.len()).sum();
-    sections.push(heading2("Blocks"));
-
-    sections.push(text(format!("Total transactions: {}", tx_count)));
+    let mut transactions = vec![text(format!("Total transactions: {}", tx_count))];
 
     // Show transactions by block
     let mut sorted_blocks: Vec<_> = provider.state.transactions.iter().collect();
     sorted_blocks.sort_by_key(|(block_num, _)| *block_num);
     let receipts = provider.state.receipts.clone();
-    sections.push(unordered_list(sorted_blocks.iter().flat_map(
+    transactions.push(unordered_list(sorted_blocks.iter().flat_map(
         |(number, txs)| {
             let block_header = (
                 format!("block_{}", number),
@@ -432,7 +522,18 @@ fn build_ui(provider: Provider) -> Result<Component, RpcError> {
         },
     )));
 
-    Ok(container(sections))
+    Ok(container(vec![
+        heading("REVM Provider"),
+        text("Use a forked Ethereum provided, running locally in your browser"),
+        tabs(
+            "sections",
+            vec![
+                ("Fork", container(fork_info)),
+                ("Cheatcodes", container(cheatcodes)),
+                ("Transactions", container(transactions)),
+            ],
+        ),
+    ]))
 }
 
 fn handle_reset_fork(transport: Transport) -> Result<(), RpcError> {
@@ -512,24 +613,5 @@ fn main() {
         .compact()
         .init();
 
-    PluginRunner::new()
-        .with_method(plugin::Init, init)
-        .with_method(page::OnLoad, on_load)
-        .with_method(page::OnUpdate, on_update)
-        .with_method(eth::ChainId, chain_id)
-        .with_method(eth::BlockNumber, block_number)
-        .with_method(eth::GasPrice, gas_price)
-        .with_method(eth::GetBalance, get_balance)
-        .with_method(eth::GetBlock, get_block)
-        .with_method(eth::GetCode, get_code)
-        .with_method(eth::GetTransactionCount, get_transaction_count)
-        .with_method(eth::GetTransactionByHash, get_transaction_by_hash)
-        .with_method(eth::GetTransactionReceipt, get_transaction_receipt)
-        .with_method(eth::GetBlockReceipts, get_block_receipts)
-        .with_method(eth::Call, call)
-        .with_method(eth::EstimateGas, estimate_gas)
-        .with_method(eth::SendRawTransaction, send_raw_transaction)
-        .with_method(eth::GetLogs, get_logs)
-        .with_method(eth::FeeHistory, fee_history)
-        .run();
+    RevmProvider::runner().run();
 }
@@ -2,8 +2,9 @@ use alloy_consensus::transaction::Recovered;
 use revm::{
     DatabaseRef,
     context::result::{ExecutionResult, HaltReason, Output},
+    precompile::Precompiles,
     primitives::{
-        Address, Bytes, HashMap, U256,
+        Address, B256, Bytes, HashMap, U256,
         alloy_primitives::TxHash,
         hex::{self},
     },
@@ -15,12 +16,17 @@ use tlock_pdk::{
     tlock_api::alloy::{
         self,
         consensus::{TxEnvelope, transaction::SignerRecoverable},
-        eips::{BlockId, BlockNumberOrTag},
+        eips::{
+            BlockId, BlockNumberOrTag,
+            eip2930::{AccessList, AccessListItem},
+        },
         rlp::Decodable,
         rpc::{
             self,
             types::{
-                BlockOverrides, BlockTransactions, BlockTransactionsKind, state::StateOverride,
+                AccessListResult, BlockOverrides, BlockTransactions, BlockTransactionsKind,
+                state::StateOverride,
+                trace::geth::{DefaultFrame, GethDebugTracingCallOptions, GethTrace},
             },
         },
     },
@@ -34,6 +40,7 @@ use crate::{
         result_to_tx_receipt, signed_tx_to_tx_env, simulated_block_to_header, tx_request_to_tx_env,
     },
     state::get_provider_key,
+    tracer::StructLogger,
 };
 
 /// A alloy-style provider backended using REVM. Handles type conversions and
@@ -164,6 +171,17 @@ impl Provider {
         Ok(pending.env.basefee as u128)
     }
 
+    pub fn max_priority_fee_per_gas(&self) -> Result<u128, ProviderError> {
+        //? The fork has no competing transactions, so no tip is needed
+        Ok(0)
+    }
+
+    pub fn blob_base_fee(&self) -> Result<u128, ProviderError> {
+        let pending = self.chain.pending()?;
+        //? 1 wei is the minimum blob base fee
+        Ok(pending.env.blob_gasprice().unwrap_or(1))
+    }
+
     pub fn get_balance(&self, address: Address, block_id: BlockId) -> Result<U256, ProviderError> {
         let account = self
             .chain
@@ -307,6 +325,81 @@ impl Provider {
         }
     }
 
+    /// Computes an access list by simulating the transaction and collecting
+    /// every account and storage slot it loads. The sender, recipient, and
+    /// precompiles are excluded since they're always warm.
+    pub fn create_access_list(
+        &self,
+        tx_request: rpc::types::TransactionRequest,
+        block_id: BlockId,
+    ) -> Result<AccessListResult, ProviderError> {
+        let from = tx_request.from;
+        let to = tx_request.to.and_then(|t| t.to().copied());
+        let tx_env = tx_request_to_tx_env(tx_request);
+
+        let (result, state) = self.chain.simulate(tx_env, block_id, None, None, true)?;
+
+        let precompiles = Precompiles::latest();
+        let items = state
+            .into_iter()
+            .filter(|(address, _)| Some(*address) != from && Some(*address) != to)
+            .filter(|(address, _)| !precompiles.contains(address))
+            .map(|(address, account)| AccessListItem {
+                address,
+                storage_keys: account.storage.keys().map(|k| B256::from(*k)).collect(),
+            })
+            .collect();
+
+        let error = match &result {
+            ExecutionResult::Success { .. } => None,
+            ExecutionResult::Revert { output, .. } => Some(decode_revert_reason(output)),
+            ExecutionResult::Halt { reason, .. } => Some(format!("{:?}", reason)),
+        };
+
+        Ok(AccessListResult {
+            access_list: AccessList(items),
+            gas_used: U256::from(result.gas_used()),
+            error,
+        })
+    }
+
+    /// Traces a call with the default tracer, logging each opcode it executes.
+    ///
+    /// TODO: Support the builtin tracers, like `callTracer`
+    pub fn trace_call(
+        &self,
+        tx_request: rpc::types::TransactionRequest,
+        block_id: BlockId,
+        opts: GethDebugTracingCallOptions,
+    ) -> Result<GethTrace, ProviderError> {
+        if opts.tracing_options.tracer.is_some() {
+            return Err(ProviderError::NotImplemented);
+        }
+
+        let tx_env = tx_request_to_tx_env(tx_request);
+        let logger = StructLogger::new(&opts.tracing_options.config);
+        let (result, logger) = self.chain.trace(
+            tx_env,
+            block_id,
+            opts.state_overrides,
+            opts.block_overrides,
+            logger,
+        )?;
+
+        let (failed, return_value) = match &result {
+            ExecutionResult::Success { output, .. } => (false, output.data().clone()),
+            ExecutionResult::Revert { output, .. } => (true, output.clone()),
+            ExecutionResult::Halt { .. } => (true, Bytes::new()),
+        };
+
+        Ok(GethTrace::Default(DefaultFrame {
+            failed,
+            gas: result.gas_used(),
+            return_value,
+            struct_logs: logger.into_logs(),
+        }))
+    }
+
     /// Sends a raw transaction to the chain, executes it, and returns its hash.
     pub fn send_raw_transaction(&self, raw_tx: Bytes) -> Result<TxHash, ProviderError> {
         let state_key = get_provider_key(&self.key);
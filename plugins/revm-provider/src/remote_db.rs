@@ -220,12 +220,13 @@ fn rpc_call<T: for<'a> Deserialize<'a>>(
         method: "POST".to_string(),
         headers: vec![("Content-Type".to_string(), b"application/json".to_vec())],
         body: Some(body),
+        ..Default::default()
     };
 
     let resp = host::Fetch.call(transport, req)?;
     let resp = resp.map_err(RpcError::custom)?;
 
-    let response: JsonRpcResponse<T> = serde_json::from_slice(&resp)?;
+    let response: JsonRpcResponse<T> = serde_json::from_slice(&resp.body)?;
 
     if let Some(err) = response.error {
         return Err(AlloyDBError::JsonRpcError {
@@ -251,11 +252,12 @@ fn rpc_batch<T: for<'a> Deserialize<'a>>(
         method: "POST".to_string(),
         headers: vec![("Content-Type".to_string(), b"application/json".to_vec())],
         body: Some(body),
+        ..Default::default()
     };
 
     let resp = host::Fetch.call(transport, req)?;
     let resp = resp.map_err(RpcError::custom)?;
 
     // Batch responses are returned directly as an array, no wrapper
-    Ok(serde_json::from_slice(&resp)?)
+    Ok(serde_json::from_slice(&resp.body)?)
 }
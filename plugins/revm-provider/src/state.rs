@@ -3,10 +3,6 @@
 
 use revm::primitives::{Address, U256};
 
-pub fn get_main_key(key: &str) -> String {
-    format!("{}/main", key)
-}
-
 pub fn get_provider_key(key: &str) -> String {
     format!("{}/provider", key)
 }
@@ -0,0 +1,78 @@
+use revm::{
+    Inspector,
+    bytecode::opcode::OpCode,
+    interpreter::{
+        CallInputs, CallOutcome, CreateInputs, CreateOutcome, Interpreter, interpreter_types::Jumps,
+    },
+};
+use tlock_pdk::tlock_api::alloy::rpc::types::trace::geth::{GethDefaultTracingOptions, StructLog};
+
+/// Records every opcode a call executes, as the struct logs of geth's default
+/// tracer. Memory, storage, and return data aren't captured.
+pub struct StructLogger {
+    capture_stack: bool,
+    /// Call depth of the frame being executed, starting at 1
+    depth: u64,
+    /// Gas remaining before the current opcode ran
+    gas_before: u64,
+    logs: Vec<StructLog>,
+}
+
+impl StructLogger {
+    pub fn new(config: &GethDefaultTracingOptions) -> Self {
+        Self {
+            capture_stack: !config.disable_stack.unwrap_or(false),
+            depth: 0,
+            gas_before: 0,
+            logs: Vec::new(),
+        }
+    }
+
+    pub fn into_logs(self) -> Vec<StructLog> {
+        self.logs
+    }
+}
+
+impl<CTX> Inspector<CTX> for StructLogger {
+    fn step(&mut self, interp: &mut Interpreter, _context: &mut CTX) {
+        let op = OpCode::new(interp.bytecode.opcode()).map_or("INVALID", |op| op.as_str());
+        self.gas_before = interp.gas.remaining();
+        self.logs.push(StructLog {
+            pc: interp.bytecode.pc() as u64,
+            op: op.into(),
+            gas: self.gas_before,
+            depth: self.depth,
+            stack: self.capture_stack.then(|| interp.stack.data().clone()),
+            ..Default::default()
+        });
+    }
+
+    fn step_end(&mut self, interp: &mut Interpreter, _context: &mut CTX) {
+        if let Some(log) = self.logs.last_mut() {
+            log.gas_cost = self.gas_before.saturating_sub(interp.gas.remaining());
+        }
+    }
+
+    fn call(&mut self, _context: &mut CTX, _inputs: &mut CallInputs) -> Option<CallOutcome> {
+        self.depth += 1;
+        None
+    }
+
+    fn call_end(&mut self, _context: &mut CTX, _inputs: &CallInputs, _outcome: &mut CallOutcome) {
+        self.depth -= 1;
+    }
+
+    fn create(&mut self, _context: &mut CTX, _inputs: &mut CreateInputs) -> Option<CreateOutcome> {
+        self.depth += 1;
+        None
+    }
+
+    fn create_end(
+        &mut self,
+        _context: &mut CTX,
+        _inputs: &CreateInputs,
+        _outcome: &mut CreateOutcome,
+    ) {
+        self.depth -= 1;
+    }
+}
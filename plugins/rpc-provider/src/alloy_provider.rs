@@ -9,11 +9,16 @@ use alloy::{
     transports::{TransportError, TransportErrorKind, TransportFut},
 };
 use tlock_pdk::{
+    retry::{self, RetryPolicy},
     tlock_api::{RpcMethod, host},
-    wasmi_plugin_pdk::transport::Transport,
+    wasmi_plugin_pdk::{rpc_message::RpcError, transport::Transport},
 };
 use tower_service::Service;
 
+/// Methods that mustn't be sent twice, since one whose response was lost may
+/// still have gone through.
+const NON_IDEMPOTENT_METHODS: &[&str] = &["eth_sendRawTransaction", "eth_sendTransaction"];
+
 //? Helpers to create an alloy provider using the host transport and `Request`
 // instead of a  standard HTTP transport.
 pub fn create_alloy_provider(transport: Transport, url: String) -> impl alloy::providers::Provider {
@@ -53,21 +58,35 @@ impl Service<RequestPacket> for HostTransportService {
                     .map(|(k, v)| (k.to_string(), v.as_bytes().into()))
                     .collect(),
                 body: Some(serde_json::to_vec(&req).map_err(TransportErrorKind::custom)?),
+                ..Default::default()
             };
             params.headers.push((
                 "Content-Type".to_string(),
                 "application/json".as_bytes().into(),
             ));
 
-            let resp = host::Fetch
-                .call_async(transport, params)
+            //? A packet with any non-idempotent request in it is sent once
+            let policy = if is_idempotent(&req) {
+                RetryPolicy::default()
+            } else {
+                RetryPolicy::new(1)
+            };
+            let fetch = || {
+                let transport = transport.clone();
+                let params = params.clone();
+                async move { host::Fetch.call_async(transport, params).await }
+            };
+            let resp = retry::retry_when(transport.clone(), &policy, is_transient, fetch)
                 .await
                 .map_err(TransportErrorKind::custom)?;
 
-            let Ok(body) = resp else {
-                return Err(TransportErrorKind::custom_str(
-                    &resp.err().unwrap_or("Unknown Error".into()),
-                ));
+            let body = match resp {
+                Ok(resp) if resp.is_success() => resp.body,
+                Ok(resp) => {
+                    let body = String::from_utf8_lossy(&resp.body).into_owned();
+                    return Err(TransportErrorKind::http_error(resp.status, body));
+                }
+                Err(e) => return Err(TransportErrorKind::custom_str(&e)),
             };
 
             serde_json::from_slice(&body)
@@ -82,3 +101,19 @@ impl Service<RequestPacket> for HostTransportService {
         Poll::Ready(Ok(()))
     }
 }
+
+fn is_idempotent(req: &RequestPacket) -> bool {
+    req.method_names()
+        .all(|method| !NON_IDEMPOTENT_METHODS.contains(&method))
+}
+
+/// Whether a fetch failed in transport in a way that may pass if it's made
+/// again: the call timed out or was rate limited, or the node was overloaded
+/// or down. Errors the host returns, like an unapproved origin, aren't.
+fn is_transient(result: &Result<Result<host::Response, String>, RpcError>) -> bool {
+    match result {
+        Ok(Ok(resp)) => resp.status == 429 || resp.status >= 500,
+        Ok(Err(_)) => false,
+        Err(err) => retry::is_retryable(err),
+    }
+}
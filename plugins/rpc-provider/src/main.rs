@@ -2,20 +2,35 @@ use std::io::stderr;
 
 use alloy::{
     eips::{BlockId, BlockNumberOrTag},
-    primitives::{Address, Bytes, TxHash, U256},
+    primitives::{Address, B256, Bytes, TxHash, U256},
     providers::Provider,
     rpc::types::{
-        Block, BlockOverrides, BlockTransactionsKind, Filter, Log, Transaction, TransactionReceipt,
-        TransactionRequest, state::StateOverride,
+        AccessListResult, Block, BlockOverrides, BlockTransactionsKind,
+        EIP1186AccountProofResponse, Filter, Log, Transaction, TransactionReceipt,
+        TransactionRequest,
+        state::StateOverride,
+        trace::geth::{GethDebugTracingCallOptions, GethDebugTracingOptions, GethTrace},
     },
 };
 use serde::{Deserialize, Serialize};
 use tlock_pdk::{
-    runner::PluginRunner,
+    fees::{FEE_HISTORY_BLOCKS, REWARD_PERCENTILES},
+    handlers::{EthProviderPlugin, FeesPlugin, GlobalPlugin, LifecyclePlugin, TracePlugin},
     state::StateExt,
-    tlock_api::{RpcMethod, domains::Domain, entities::EthProviderId, eth, global, host, plugin},
+    tlock_api::{
+        RpcMethod,
+        caip::ChainId,
+        domains::Domain,
+        entities::EthProviderId,
+        fees::{self, FeeSuggestion},
+        global,
+        host::{self, EntityCapabilities},
+        plugin::PluginManifest,
+        trace,
+    },
+    tlock_plugin,
     wasmi_plugin_pdk::{
-        rpc_message::{RpcError, ToRpcResult},
+        rpc_message::{RpcError, RpcErrorContext, ToRpcResult},
         transport::Transport,
     },
 };
@@ -31,280 +46,437 @@ struct ProviderState {
     rpc_url: String,
 }
 
-async fn ping(transport: Transport, _params: ()) -> Result<String, RpcError> {
-    global::Ping.call_async(transport.clone(), ()).await?;
-    Ok("pong".to_string())
+#[tlock_plugin(
+    GlobalPlugin,
+    LifecyclePlugin,
+    EthProviderPlugin,
+    TracePlugin,
+    FeesPlugin
+)]
+struct RpcProvider;
+
+#[tlock_plugin]
+impl GlobalPlugin for RpcProvider {
+    async fn ping(transport: Transport, _params: ()) -> Result<String, RpcError> {
+        global::Ping.call_async(transport.clone(), ()).await?;
+        Ok("pong".to_string())
+    }
 }
 
-async fn init(transport: Transport, _params: ()) -> Result<(), RpcError> {
-    info!("Initializing Ethereum Provider Plugin...");
+#[tlock_plugin]
+impl LifecyclePlugin for RpcProvider {
+    async fn get_manifest(_transport: Transport, _params: ()) -> Result<PluginManifest, RpcError> {
+        Ok(PluginManifest::new()
+            .with_method(host::Fetch)
+            .with_origin("https://1rpc.io")
+            .with_domain(Domain::EthProvider))
+    }
 
-    let state = ProviderState {
-        rpc_url: "https://1rpc.io/eth".to_string(),
-    };
-    transport.state().lock_or(|| state)?;
+    async fn init(transport: Transport, _params: ()) -> Result<(), RpcError> {
+        info!("Initializing Ethereum Provider Plugin...");
 
-    host::RegisterEntity
-        .call_async(transport.clone(), Domain::EthProvider)
-        .await?;
+        let state = ProviderState {
+            rpc_url: "https://1rpc.io/eth".to_string(),
+        };
+        transport.state().lock_or(|| state)?;
 
-    Ok(())
+        let capabilities = EntityCapabilities::new()
+            .with_chain(ChainId::new_evm(1))
+            .with_method(trace::TraceTransaction)
+            .with_method(trace::TraceCall)
+            .with_method(fees::Suggest);
+        host::RegisterEntityWithCapabilities
+            .call_async(transport.clone(), (Domain::EthProvider, capabilities))
+            .await?;
+
+        Ok(())
+    }
 }
 
-async fn chain_id(transport: Transport, _params: EthProviderId) -> Result<U256, RpcError> {
-    let state: ProviderState = transport.state().read()?;
+#[tlock_plugin]
+impl EthProviderPlugin for RpcProvider {
+    async fn chain_id(transport: Transport, _params: EthProviderId) -> Result<U256, RpcError> {
+        let state: ProviderState = transport.state().read()?;
 
-    let provider = create_alloy_provider(transport.clone(), state.rpc_url);
-    let chain_id = provider.get_chain_id().await.rpc_err()?;
-    let chain_id = U256::from(chain_id);
+        let provider = create_alloy_provider(transport.clone(), state.rpc_url);
+        let chain_id = provider.get_chain_id().await.rpc_err()?;
+        let chain_id = U256::from(chain_id);
 
-    Ok(chain_id)
-}
+        Ok(chain_id)
+    }
 
-async fn block_number(transport: Transport, _params: EthProviderId) -> Result<u64, RpcError> {
-    let state: ProviderState = transport.state().read()?;
+    async fn block_number(transport: Transport, _params: EthProviderId) -> Result<u64, RpcError> {
+        let state: ProviderState = transport.state().read()?;
 
-    let provider = create_alloy_provider(transport.clone(), state.rpc_url);
-    let block_number = provider.get_block_number().await.rpc_err()?;
-    Ok(block_number)
-}
+        let provider = create_alloy_provider(transport.clone(), state.rpc_url);
+        let block_number = provider.get_block_number().await.rpc_err()?;
+        Ok(block_number)
+    }
 
-async fn call(
-    transport: Transport,
-    params: (
-        EthProviderId,
-        TransactionRequest,
-        BlockId,
-        Option<StateOverride>,
-        Option<BlockOverrides>,
-    ),
-) -> Result<Bytes, RpcError> {
-    let state: ProviderState = transport.state().read()?;
-
-    let (_provider_id, tx, block, state_overrides, block_overrides) = params;
-
-    let provider = create_alloy_provider(transport.clone(), state.rpc_url);
-    let resp = provider
-        .call(tx)
-        .block(block)
-        .overrides_opt(state_overrides)
-        .with_block_overrides_opt(block_overrides)
-        .await
-        .rpc_err()?;
-
-    Ok(resp)
-}
+    async fn call(
+        transport: Transport,
+        params: (
+            EthProviderId,
+            TransactionRequest,
+            BlockId,
+            Option<StateOverride>,
+            Option<BlockOverrides>,
+        ),
+    ) -> Result<Bytes, RpcError> {
+        let state: ProviderState = transport.state().read()?;
+
+        let (_provider_id, tx, block, state_overrides, block_overrides) = params;
+
+        let provider = create_alloy_provider(transport.clone(), state.rpc_url);
+        let resp = provider
+            .call(tx)
+            .block(block)
+            .overrides_opt(state_overrides)
+            .with_block_overrides_opt(block_overrides)
+            .await
+            .rpc_err()?;
+
+        Ok(resp)
+    }
 
-async fn gas_price(transport: Transport, _provider_id: EthProviderId) -> Result<u128, RpcError> {
-    let state: ProviderState = transport.state().read()?;
+    async fn gas_price(
+        transport: Transport,
+        _provider_id: EthProviderId,
+    ) -> Result<u128, RpcError> {
+        let state: ProviderState = transport.state().read()?;
 
-    let provider = create_alloy_provider(transport.clone(), state.rpc_url);
-    let gas_price = provider.get_gas_price().await.rpc_err()?;
+        let provider = create_alloy_provider(transport.clone(), state.rpc_url);
+        let gas_price = provider.get_gas_price().await.rpc_err()?;
 
-    Ok(gas_price)
-}
+        Ok(gas_price)
+    }
 
-async fn get_balance(
-    transport: Transport,
-    params: (EthProviderId, Address, BlockId),
-) -> Result<U256, RpcError> {
-    let state: ProviderState = transport.state().read()?;
-    let (_provider_id, address, block) = params;
-
-    let provider = create_alloy_provider(transport.clone(), state.rpc_url);
-    let balance = provider
-        .get_balance(address)
-        .block_id(block)
-        .await
-        .rpc_err()?;
-    Ok(balance)
-}
+    async fn max_priority_fee_per_gas(
+        transport: Transport,
+        _provider_id: EthProviderId,
+    ) -> Result<u128, RpcError> {
+        let state: ProviderState = transport.state().read()?;
 
-async fn get_block(
-    transport: Transport,
-    params: (EthProviderId, BlockId, BlockTransactionsKind),
-) -> Result<Block, RpcError> {
-    let state: ProviderState = transport.state().read()?;
-    let (_provider_id, block_id, include_transactions) = params;
-
-    let provider = create_alloy_provider(transport.clone(), state.rpc_url);
-    let block = provider
-        .get_block(block_id)
-        .kind(include_transactions)
-        .await
-        .rpc_err()?;
-
-    match block {
-        Some(b) => Ok(b),
-        None => Err(RpcError::Custom("Block not found".into())),
+        let provider = create_alloy_provider(transport.clone(), state.rpc_url);
+        let fee = provider.get_max_priority_fee_per_gas().await.rpc_err()?;
+
+        Ok(fee)
     }
-}
 
-async fn get_block_receipts(
-    transport: Transport,
-    params: (EthProviderId, BlockId),
-) -> Result<Vec<TransactionReceipt>, RpcError> {
-    let state: ProviderState = transport.state().read()?;
-    let (_provider_id, block_id) = params;
+    async fn blob_base_fee(
+        transport: Transport,
+        _provider_id: EthProviderId,
+    ) -> Result<u128, RpcError> {
+        let state: ProviderState = transport.state().read()?;
 
-    let provider = create_alloy_provider(transport.clone(), state.rpc_url);
-    let receipts = provider.get_block_receipts(block_id).await.rpc_err()?;
+        let provider = create_alloy_provider(transport.clone(), state.rpc_url);
+        let fee = provider.get_blob_base_fee().await.rpc_err()?;
 
-    match receipts {
-        Some(r) => Ok(r),
-        None => Ok(vec![]),
+        Ok(fee)
     }
-}
 
-async fn get_code(
-    transport: Transport,
-    params: (EthProviderId, Address, BlockId),
-) -> Result<Bytes, RpcError> {
-    let state: ProviderState = transport.state().read()?;
-    let (_provider_id, address, block_id) = params;
-
-    let provider = create_alloy_provider(transport.clone(), state.rpc_url);
-    let code = provider
-        .get_code_at(address)
-        .block_id(block_id)
-        .await
-        .rpc_err()?;
-
-    Ok(code)
-}
+    async fn get_balance(
+        transport: Transport,
+        params: (EthProviderId, Address, BlockId),
+    ) -> Result<U256, RpcError> {
+        let state: ProviderState = transport.state().read()?;
+        let (_provider_id, address, block) = params;
+
+        let provider = create_alloy_provider(transport.clone(), state.rpc_url);
+        let balance = provider
+            .get_balance(address)
+            .block_id(block)
+            .await
+            .rpc_err()?;
+        Ok(balance)
+    }
 
-async fn get_logs(
-    transport: Transport,
-    params: (EthProviderId, Filter),
-) -> Result<Vec<Log>, RpcError> {
-    let state: ProviderState = transport.state().read()?;
-    let (_provider_id, filter) = params;
+    async fn get_block(
+        transport: Transport,
+        params: (EthProviderId, BlockId, BlockTransactionsKind),
+    ) -> Result<Block, RpcError> {
+        let state: ProviderState = transport.state().read()?;
+        let (_provider_id, block_id, include_transactions) = params;
+
+        let provider = create_alloy_provider(transport.clone(), state.rpc_url);
+        let block = provider
+            .get_block(block_id)
+            .kind(include_transactions)
+            .await
+            .rpc_err()?;
+
+        match block {
+            Some(b) => Ok(b),
+            None => Err(RpcError::Custom("Block not found".into())),
+        }
+    }
 
-    let provider = create_alloy_provider(transport.clone(), state.rpc_url);
-    let logs = provider.get_logs(&filter).await.rpc_err()?;
+    async fn get_block_receipts(
+        transport: Transport,
+        params: (EthProviderId, BlockId),
+    ) -> Result<Vec<TransactionReceipt>, RpcError> {
+        let state: ProviderState = transport.state().read()?;
+        let (_provider_id, block_id) = params;
 
-    Ok(logs)
-}
+        let provider = create_alloy_provider(transport.clone(), state.rpc_url);
+        let receipts = provider.get_block_receipts(block_id).await.rpc_err()?;
+
+        match receipts {
+            Some(r) => Ok(r),
+            None => Ok(vec![]),
+        }
+    }
+
+    async fn get_code(
+        transport: Transport,
+        params: (EthProviderId, Address, BlockId),
+    ) -> Result<Bytes, RpcError> {
+        let state: ProviderState = transport.state().read()?;
+        let (_provider_id, address, block_id) = params;
+
+        let provider = create_alloy_provider(transport.clone(), state.rpc_url);
+        let code = provider
+            .get_code_at(address)
+            .block_id(block_id)
+            .await
+            .rpc_err()?;
+
+        Ok(code)
+    }
 
-async fn get_transaction_by_hash(
-    transport: Transport,
-    params: (EthProviderId, TxHash),
-) -> Result<Transaction, RpcError> {
-    let state: ProviderState = transport.state().read()?;
-    let (_provider_id, tx_hash) = params;
+    async fn get_logs(
+        transport: Transport,
+        params: (EthProviderId, Filter),
+    ) -> Result<Vec<Log>, RpcError> {
+        let state: ProviderState = transport.state().read()?;
+        let (_provider_id, filter) = params;
 
-    let provider = create_alloy_provider(transport.clone(), state.rpc_url);
-    let tx = provider.get_transaction_by_hash(tx_hash).await.rpc_err()?;
+        let provider = create_alloy_provider(transport.clone(), state.rpc_url);
+        let logs = provider.get_logs(&filter).await.rpc_err()?;
 
-    match tx {
-        Some(t) => Ok(t),
-        None => Err(RpcError::Custom("Transaction not found".into())),
+        Ok(logs)
     }
-}
 
-async fn get_transaction_receipt(
-    transport: Transport,
-    params: (EthProviderId, TxHash),
-) -> Result<TransactionReceipt, RpcError> {
-    let state: ProviderState = transport.state().read()?;
-    let (_provider_id, tx_hash) = params;
+    async fn get_transaction_by_hash(
+        transport: Transport,
+        params: (EthProviderId, TxHash),
+    ) -> Result<Transaction, RpcError> {
+        let state: ProviderState = transport.state().read()?;
+        let (_provider_id, tx_hash) = params;
 
-    let provider = create_alloy_provider(transport.clone(), state.rpc_url);
-    let receipt = provider.get_transaction_receipt(tx_hash).await.rpc_err()?;
+        let provider = create_alloy_provider(transport.clone(), state.rpc_url);
+        let tx = provider.get_transaction_by_hash(tx_hash).await.rpc_err()?;
 
-    match receipt {
-        Some(r) => Ok(r),
-        None => Err(RpcError::Custom("Transaction Receipt not Found".into())),
+        match tx {
+            Some(t) => Ok(t),
+            None => Err(RpcError::Custom("Transaction not found".into())),
+        }
     }
-}
 
-async fn get_transaction_count(
-    transport: Transport,
-    params: (EthProviderId, Address, BlockId),
-) -> Result<u64, RpcError> {
-    let state: ProviderState = transport.state().read()?;
-    let (_provider_id, address, block_id) = params;
-
-    let provider = create_alloy_provider(transport.clone(), state.rpc_url);
-    let tx_count = provider
-        .get_transaction_count(address)
-        .block_id(block_id)
-        .await
-        .rpc_err()?;
-
-    Ok(tx_count)
-}
+    async fn get_transaction_receipt(
+        transport: Transport,
+        params: (EthProviderId, TxHash),
+    ) -> Result<TransactionReceipt, RpcError> {
+        let state: ProviderState = transport.state().read()?;
+        let (_provider_id, tx_hash) = params;
 
-async fn send_raw_transaction(
-    transport: Transport,
-    params: (EthProviderId, Bytes),
-) -> Result<TxHash, RpcError> {
-    let state: ProviderState = transport.state().read()?;
-    let (_provider_id, raw_tx) = params;
+        let provider = create_alloy_provider(transport.clone(), state.rpc_url);
+        let receipt = provider.get_transaction_receipt(tx_hash).await.rpc_err()?;
 
-    let provider = create_alloy_provider(transport.clone(), state.rpc_url);
-    let tx = provider.send_raw_transaction(&raw_tx).await.rpc_err()?;
-    let tx_hash = tx.tx_hash();
+        match receipt {
+            Some(r) => Ok(r),
+            None => Err(RpcError::Custom("Transaction Receipt not Found".into())),
+        }
+    }
 
-    Ok(*tx_hash)
-}
+    async fn get_transaction_count(
+        transport: Transport,
+        params: (EthProviderId, Address, BlockId),
+    ) -> Result<u64, RpcError> {
+        let state: ProviderState = transport.state().read()?;
+        let (_provider_id, address, block_id) = params;
+
+        let provider = create_alloy_provider(transport.clone(), state.rpc_url);
+        let tx_count = provider
+            .get_transaction_count(address)
+            .block_id(block_id)
+            .await
+            .rpc_err()?;
+
+        Ok(tx_count)
+    }
 
-async fn estimate_gas(
-    transport: Transport,
-    params: (
-        EthProviderId,
-        TransactionRequest,
-        BlockId,
-        Option<StateOverride>,
-        Option<BlockOverrides>,
-    ),
-) -> Result<u64, RpcError> {
-    let state: ProviderState = transport.state().read()?;
-    let (_provider_id, transaction_request, block_id, state_override, block_override) = params;
-
-    let provider = create_alloy_provider(transport.clone(), state.rpc_url);
-    let gas_estimate = provider
-        .estimate_gas(transaction_request)
-        .block(block_id)
-        .overrides_opt(state_override)
-        .with_block_overrides_opt(block_override)
-        .await
-        .rpc_err()?;
-
-    Ok(gas_estimate)
-}
+    async fn send_raw_transaction(
+        transport: Transport,
+        params: (EthProviderId, Bytes),
+    ) -> Result<TxHash, RpcError> {
+        let state: ProviderState = transport.state().read()?;
+        let (_provider_id, raw_tx) = params;
+
+        let provider = create_alloy_provider(transport.clone(), state.rpc_url);
+        let tx = provider.send_raw_transaction(&raw_tx).await.rpc_err()?;
+        let tx_hash = tx.tx_hash();
+
+        Ok(*tx_hash)
+    }
+
+    async fn estimate_gas(
+        transport: Transport,
+        params: (
+            EthProviderId,
+            TransactionRequest,
+            BlockId,
+            Option<StateOverride>,
+            Option<BlockOverrides>,
+        ),
+    ) -> Result<u64, RpcError> {
+        let state: ProviderState = transport.state().read()?;
+        let (_provider_id, transaction_request, block_id, state_override, block_override) = params;
+
+        let provider = create_alloy_provider(transport.clone(), state.rpc_url);
+        let gas_estimate = provider
+            .estimate_gas(transaction_request)
+            .block(block_id)
+            .overrides_opt(state_override)
+            .with_block_overrides_opt(block_override)
+            .await
+            .rpc_err()?;
+
+        Ok(gas_estimate)
+    }
 
-async fn get_storage_at(
-    transport: Transport,
-    params: (EthProviderId, Address, U256, BlockId),
-) -> Result<U256, RpcError> {
-    let state: ProviderState = transport.state().read()?;
-    let (_provider_id, address, slot, block_id) = params;
-
-    let provider = create_alloy_provider(transport.clone(), state.rpc_url);
-    let storage_value = provider
-        .get_storage_at(address, slot)
-        .block_id(block_id)
-        .await
-        .rpc_err()?;
-
-    Ok(storage_value)
+    async fn get_storage_at(
+        transport: Transport,
+        params: (EthProviderId, Address, U256, BlockId),
+    ) -> Result<U256, RpcError> {
+        let state: ProviderState = transport.state().read()?;
+        let (_provider_id, address, slot, block_id) = params;
+
+        let provider = create_alloy_provider(transport.clone(), state.rpc_url);
+        let storage_value = provider
+            .get_storage_at(address, slot)
+            .block_id(block_id)
+            .await
+            .rpc_err()?;
+
+        Ok(storage_value)
+    }
+
+    async fn create_access_list(
+        transport: Transport,
+        params: (EthProviderId, TransactionRequest, BlockId),
+    ) -> Result<AccessListResult, RpcError> {
+        let state: ProviderState = transport.state().read()?;
+        let (_provider_id, tx, block_id) = params;
+
+        let provider = create_alloy_provider(transport.clone(), state.rpc_url);
+        let access_list = provider
+            .create_access_list(&tx)
+            .block_id(block_id)
+            .await
+            .rpc_err()?;
+
+        Ok(access_list)
+    }
+
+    async fn get_proof(
+        transport: Transport,
+        params: (EthProviderId, Address, Vec<U256>, BlockId),
+    ) -> Result<EIP1186AccountProofResponse, RpcError> {
+        let state: ProviderState = transport.state().read()?;
+        let (_provider_id, address, keys, block_id) = params;
+
+        let provider = create_alloy_provider(transport.clone(), state.rpc_url);
+        let keys = keys.into_iter().map(B256::from).collect();
+        let proof = provider
+            .get_proof(address, keys)
+            .block_id(block_id)
+            .await
+            .rpc_err()?;
+
+        Ok(proof)
+    }
+
+    async fn fee_history(
+        transport: Transport,
+        params: (EthProviderId, u64, BlockNumberOrTag, Vec<f64>),
+    ) -> Result<alloy::rpc::types::FeeHistory, RpcError> {
+        let state: ProviderState = transport.state().read()?;
+        let (_provider_id, block_count, newest_block, reward_percentiles) = params;
+
+        let provider = create_alloy_provider(transport.clone(), state.rpc_url);
+        let fee_history = provider
+            .get_fee_history(block_count, newest_block, &reward_percentiles)
+            .await
+            .rpc_err()?;
+
+        Ok(fee_history)
+    }
 }
 
-async fn fee_history(
-    transport: Transport,
-    params: (EthProviderId, u64, BlockNumberOrTag, Vec<f64>),
-) -> Result<alloy::rpc::types::FeeHistory, RpcError> {
-    let state: ProviderState = transport.state().read()?;
-    let (_provider_id, block_count, newest_block, reward_percentiles) = params;
+//? Alloy's debug API isn't enabled, so trace requests are forwarded raw
+#[tlock_plugin]
+impl TracePlugin for RpcProvider {
+    async fn trace_transaction(
+        transport: Transport,
+        params: (EthProviderId, TxHash, GethDebugTracingOptions),
+    ) -> Result<GethTrace, RpcError> {
+        let state: ProviderState = transport.state().read()?;
+        let (_provider_id, tx_hash, opts) = params;
+
+        let provider = create_alloy_provider(transport.clone(), state.rpc_url);
+        let trace = provider
+            .raw_request("debug_traceTransaction".into(), (tx_hash, opts))
+            .await
+            .rpc_err()?;
+
+        Ok(trace)
+    }
 
-    let provider = create_alloy_provider(transport.clone(), state.rpc_url);
-    let fee_history = provider
-        .get_fee_history(block_count, newest_block, &reward_percentiles)
-        .await
-        .rpc_err()?;
+    async fn trace_call(
+        transport: Transport,
+        params: (
+            EthProviderId,
+            TransactionRequest,
+            BlockId,
+            GethDebugTracingCallOptions,
+        ),
+    ) -> Result<GethTrace, RpcError> {
+        let state: ProviderState = transport.state().read()?;
+        let (_provider_id, tx, block_id, opts) = params;
+
+        let provider = create_alloy_provider(transport.clone(), state.rpc_url);
+        let trace = provider
+            .raw_request("debug_traceCall".into(), (tx, block_id, opts))
+            .await
+            .rpc_err()?;
+
+        Ok(trace)
+    }
+}
 
-    Ok(fee_history)
+#[tlock_plugin]
+impl FeesPlugin for RpcProvider {
+    async fn suggest(
+        transport: Transport,
+        _provider_id: EthProviderId,
+    ) -> Result<FeeSuggestion, RpcError> {
+        let state: ProviderState = transport.state().read()?;
+
+        let provider = create_alloy_provider(transport.clone(), state.rpc_url);
+        let fee_history = provider
+            .get_fee_history(
+                FEE_HISTORY_BLOCKS,
+                BlockNumberOrTag::Latest,
+                &REWARD_PERCENTILES,
+            )
+            .await
+            .rpc_err()?;
+
+        let suggestion =
+            tlock_pdk::fees::suggest(&fee_history).context("Incomplete fee history")?;
+        Ok(suggestion)
+    }
 }
 
 fn main() {
@@ -315,24 +487,5 @@ fn main() {
         .compact()
         .init();
 
-    PluginRunner::new()
-        .with_method(global::Ping, ping)
-        .with_method(plugin::Init, init)
-        .with_method(eth::ChainId, chain_id)
-        .with_method(eth::BlockNumber, block_number)
-        .with_method(eth::Call, call)
-        .with_method(eth::GasPrice, gas_price)
-        .with_method(eth::GetBalance, get_balance)
-        .with_method(eth::GetBlock, get_block)
-        .with_method(eth::GetBlockReceipts, get_block_receipts)
-        .with_method(eth::GetCode, get_code)
-        .with_method(eth::GetLogs, get_logs)
-        .with_method(eth::GetTransactionByHash, get_transaction_by_hash)
-        .with_method(eth::GetTransactionReceipt, get_transaction_receipt)
-        .with_method(eth::GetTransactionCount, get_transaction_count)
-        .with_method(eth::SendRawTransaction, send_raw_transaction)
-        .with_method(eth::EstimateGas, estimate_gas)
-        .with_method(eth::GetStorageAt, get_storage_at)
-        .with_method(eth::FeeHistory, fee_history)
-        .run();
+    RpcProvider::runner().run();
 }
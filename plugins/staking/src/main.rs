@@ -16,18 +16,25 @@ use erc20s::CHAIN_ID;
 use serde::{Deserialize, Serialize};
 use tlock_alloy::AlloyBridge;
 use tlock_pdk::{
-    runner::PluginRunner,
+    handlers::{GlobalPlugin, LifecyclePlugin, PagePlugin, VaultPlugin},
     state::StateExt,
     tlock_api::{
         RpcMethod,
         caip::{AccountId, AssetId, ChainId},
-        component::{asset, container, form, heading, heading2, submit_input, text, text_input},
+        component::{
+            asset, button_input, container, form, heading, heading2, modal, submit_input, text,
+            text_input,
+        },
         domains::Domain,
         entities::{EthProviderId, PageId, VaultId},
-        global, host,
+        error::{DomainError, ErrorCode},
+        global,
+        host::{self, EntityCapabilities},
         page::{self},
-        plugin, vault,
+        plugin::PluginManifest,
+        vault,
     },
+    tlock_plugin,
     wasmi_plugin_pdk::{
         rpc_message::{RpcError, RpcErrorContext, ToRpcResult},
         transport::Transport,
@@ -42,94 +49,150 @@ struct PluginState {
     staked: U256,
     private_key: FixedBytes<32>,
     address: Address,
+    /// Amount the user asked to unstake, awaiting their confirmation
+    #[serde(default)]
+    pending_unstake: Option<f64>,
 }
 
-async fn init(transport: Transport, _params: ()) -> Result<(), RpcError> {
-    info!("Initializing Staking Plugin");
-
-    let provider_id =
-        host::RequestEthProvider.call(transport.clone(), ChainId::new_evm(CHAIN_ID))?;
-    // TODO: Enable me. Disabled for the demo to simplify things
-    // host::RegisterEntity.call(transport.clone(), Domain::Vault)?;
-    host::RegisterEntity.call(transport.clone(), Domain::Page)?;
-
-    let signer = PrivateKeySigner::random();
-    let address = signer.address();
-    let state = PluginState {
-        provider_id,
-        staked: U256::ZERO,
-        private_key: signer.to_bytes(),
-        address,
-    };
+#[tlock_plugin(LifecyclePlugin, GlobalPlugin, PagePlugin, VaultPlugin)]
+struct Staking;
+
+#[tlock_plugin]
+impl LifecyclePlugin for Staking {
+    async fn get_manifest(_transport: Transport, _params: ()) -> Result<PluginManifest, RpcError> {
+        let manifest = PluginManifest::new()
+            .with_method(host::RequestEthProvider)
+            .with_method(host::RequestVault)
+            .with_method(vault::GetDepositAddress)
+            .with_method(vault::Withdraw)
+            .with_domain(Domain::Page);
+        Ok(AlloyBridge::manifest(manifest))
+    }
 
-    transport.state().lock_or(|| state)?;
+    async fn init(transport: Transport, _params: ()) -> Result<(), RpcError> {
+        info!("Initializing Staking Plugin");
 
-    Ok(())
+        let provider_id = host::RequestEthProvider.call(
+            transport.clone(),
+            (Some(ChainId::new_evm(CHAIN_ID)), EntityCapabilities::new()),
+        )?;
+        // TODO: Enable me. Disabled for the demo to simplify things
+        // host::RegisterEntity.call(transport.clone(), Domain::Vault)?;
+        host::RegisterEntity.call(transport.clone(), Domain::Page)?;
+
+        let signer = PrivateKeySigner::random();
+        let address = signer.address();
+        let state = PluginState {
+            provider_id,
+            staked: U256::ZERO,
+            private_key: signer.to_bytes(),
+            address,
+            pending_unstake: None,
+        };
+
+        transport.state().lock_or(|| state)?;
+
+        Ok(())
+    }
 }
 
-async fn ping(transport: Transport, _params: ()) -> Result<String, RpcError> {
-    global::Ping.call(transport, ())?;
-    Ok("pong".to_string())
+#[tlock_plugin]
+impl GlobalPlugin for Staking {
+    async fn ping(transport: Transport, _params: ()) -> Result<String, RpcError> {
+        global::Ping.call(transport, ())?;
+        Ok("pong".to_string())
+    }
 }
 
-// ---------- Page Handlers ----------
+// ---------- Vault Handlers ----------
+
+#[tlock_plugin]
+impl VaultPlugin for Staking {
+    async fn get_deposit_address(
+        transport: Transport,
+        params: (VaultId, AssetId),
+    ) -> Result<AccountId, RpcError> {
+        let (_vault_id, asset_id) = params;
+        let state: PluginState = transport.state().read()?;
+        if asset_id != AssetId::eth(CHAIN_ID) {
+            return Err(DomainError::new(ErrorCode::UNSUPPORTED_ASSET, "Unsupported asset").into());
+        }
+        let account_id = AccountId::new_evm(CHAIN_ID, state.address);
+        Ok(account_id)
+    }
 
-async fn get_deposit_address(
-    transport: Transport,
-    params: (VaultId, AssetId),
-) -> Result<AccountId, RpcError> {
-    let (_vault_id, asset_id) = params;
-    let state: PluginState = transport.state().read()?;
-    if asset_id != AssetId::eth(CHAIN_ID) {
-        return Err(RpcError::custom("Unsupported asset"));
+    async fn get_assets(
+        transport: Transport,
+        _vault_id: VaultId,
+    ) -> Result<Vec<(AssetId, U256)>, RpcError> {
+        let state: PluginState = transport.state().read()?;
+        Ok(vec![(AssetId::eth(CHAIN_ID), state.staked)])
     }
-    let account_id = AccountId::new_evm(CHAIN_ID, state.address);
-    Ok(account_id)
 }
 
-async fn get_assets(
-    transport: Transport,
-    _vault_id: VaultId,
-) -> Result<Vec<(AssetId, U256)>, RpcError> {
-    let state: PluginState = transport.state().read()?;
-    Ok(vec![(AssetId::eth(CHAIN_ID), state.staked)])
-}
+// ---------- Page Handlers ----------
 
-async fn on_load(transport: Transport, page_id: PageId) -> Result<(), RpcError> {
-    info!("Page loaded: {}", page_id);
+#[tlock_plugin]
+impl PagePlugin for Staking {
+    async fn on_load(transport: Transport, page_id: PageId) -> Result<(), RpcError> {
+        info!("Page loaded: {}", page_id);
 
-    let state: PluginState = transport.state().read()?;
-    let component = build_ui(&state);
-    host::SetPage.call(transport.clone(), (page_id, component))?;
+        let state: PluginState = transport.state().read()?;
+        let component = build_ui(&state);
+        host::SetPage.call(transport.clone(), (page_id, component))?;
 
-    Ok(())
-}
-
-async fn on_update(
-    transport: Transport,
-    params: (PageId, page::PageEvent),
-) -> Result<(), RpcError> {
-    let (page_id, event) = params;
-    info!("Page updated: {:?}", event);
+        Ok(())
+    }
 
-    match event {
-        page::PageEvent::FormSubmitted(form_id, form_data) if form_id == "stake_form" => {
-            handle_stake(&transport, form_data)?;
+    async fn on_update(
+        transport: Transport,
+        params: (PageId, page::PageEvent),
+    ) -> Result<(), RpcError> {
+        let (page_id, event) = params;
+        info!("Page updated: {:?}", event);
+
+        match event {
+            page::PageEvent::FormSubmitted(form_id, form_data) if form_id == "stake_form" => {
+                handle_stake(&transport, form_data)?;
+            }
+            page::PageEvent::FormSubmitted(form_id, form_data) if form_id == "unstake_form" => {
+                let amount = form_data.get("amount").context("Missing amount")?;
+                let amount: f64 = amount.parse().context("Invalid amount")?;
+                transport.state().try_lock::<PluginState>()?.pending_unstake = Some(amount);
+            }
+            page::PageEvent::ButtonClicked(id) if id == "confirm_unstake" => {
+                let amount = transport
+                    .state()
+                    .try_lock::<PluginState>()?
+                    .pending_unstake
+                    .take();
+                if let Some(amount) = amount {
+                    handle_unstake(&transport, amount).await?;
+                }
+            }
+            page::PageEvent::ButtonClicked(id) if id == "cancel_unstake" => {
+                transport.state().try_lock::<PluginState>()?.pending_unstake = None;
+            }
+            page::PageEvent::ModalDismissed(id) if id == "confirm_unstake_modal" => {
+                transport.state().try_lock::<PluginState>()?.pending_unstake = None;
+            }
+            _ => {
+                warn!("Unhandled page event: {:?}", event);
+                return Ok(());
+            }
         }
-        page::PageEvent::FormSubmitted(form_id, form_data) if form_id == "unstake_form" => {
-            handle_unstake(&transport, form_data).await?;
-        }
-        _ => {
-            warn!("Unhandled page event: {:?}", event);
-            return Ok(());
-        }
-    }
 
-    let state = transport.state().read()?;
-    let component = build_ui(&state);
-    host::SetPage.call(transport.clone(), (page_id, component))?;
+        let state = transport.state().read()?;
+        let component = build_ui(&state);
+        host::SetPage.call(transport.clone(), (page_id, component))?;
 
-    Ok(())
+        Ok(())
+    }
+}
+
+/// Capabilities required of the vault staked ETH moves to and from
+fn eth_vault() -> EntityCapabilities {
+    EntityCapabilities::new().with_asset(AssetId::eth(CHAIN_ID))
 }
 
 fn handle_stake(transport: &Transport, form_data: HashMap<String, String>) -> Result<(), RpcError> {
@@ -140,7 +203,7 @@ fn handle_stake(transport: &Transport, form_data: HashMap<String, String>) -> Re
     let state: PluginState = transport.state().read()?;
 
     let vault_id = host::RequestVault
-        .call(transport.clone(), ())
+        .call(transport.clone(), eth_vault())
         .context("Failed to request vault")?;
 
     let account_id = AccountId::new_evm(CHAIN_ID, state.address);
@@ -166,21 +229,18 @@ fn handle_stake(transport: &Transport, form_data: HashMap<String, String>) -> Re
     Ok(())
 }
 
-async fn handle_unstake(
-    transport: &Transport,
-    form_data: HashMap<String, String>,
-) -> Result<(), RpcError> {
+async fn handle_unstake(transport: &Transport, amount: f64) -> Result<(), RpcError> {
     let state: PluginState = transport.state().read()?;
 
-    let amount = form_data.get("amount").context("Missing amount")?;
-    let amount: f64 = amount.parse().context("Invalid amount")?;
     let amount_uint = U256::from(amount * 1e18);
     if amount_uint > state.staked {
-        return Err(RpcError::custom("Insufficient staked balance"));
+        let err = DomainError::new(ErrorCode::INSUFFICIENT_FUNDS, "Insufficient staked balance")
+            .with_data((state.staked, amount_uint));
+        return Err(err.into());
     }
 
     let vault_id = host::RequestVault
-        .call(transport.clone(), ())
+        .call(transport.clone(), eth_vault())
         .context("Failed to request vault")?;
 
     let asset_id = AssetId::eth(CHAIN_ID);
@@ -260,6 +320,18 @@ fn build_ui(state: &PluginState) -> tlock_pdk::tlock_api::component::Component {
         ],
     ));
 
+    if let Some(amount) = state.pending_unstake {
+        sections.push(modal(
+            "confirm_unstake_modal",
+            "Confirm Unstake",
+            vec![
+                text(format!("Unstake {:.4} ETH back to your vault?", amount)),
+                button_input("confirm_unstake", "Unstake"),
+                button_input("cancel_unstake", "Cancel"),
+            ],
+        ));
+    }
+
     container(sections)
 }
 
@@ -272,12 +344,5 @@ fn main() {
         .init();
     info!("Starting Staking Plugin...");
 
-    PluginRunner::new()
-        .with_method(plugin::Init, init)
-        .with_method(global::Ping, ping)
-        .with_method(page::OnLoad, on_load)
-        .with_method(page::OnUpdate, on_update)
-        .with_method(vault::GetDepositAddress, get_deposit_address)
-        .with_method(vault::GetAssets, get_assets)
-        .run();
+    Staking::runner().run();
 }
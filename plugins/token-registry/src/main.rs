@@ -0,0 +1,133 @@
+//! Token Registry Plugin
+//!
+//! Provides a `TokenList` entity backed by the tokens in the `erc20s` crate,
+//! plus each supported chain's native asset.
+use std::io::stderr;
+
+use erc20s::{CHAIN_ID, ERC20S};
+use tlock_pdk::{
+    handlers::{GlobalPlugin, LifecyclePlugin, TokenListPlugin},
+    tlock_api::{
+        RpcMethod,
+        caip::{AssetId, ChainId},
+        domains::Domain,
+        entities::TokenListId,
+        global, host,
+        plugin::PluginManifest,
+        tokens::TokenInfo,
+    },
+    tlock_plugin,
+    wasmi_plugin_pdk::{rpc_message::RpcError, transport::Transport},
+};
+use tracing::info;
+use tracing_subscriber::fmt;
+
+const LOGO_BASE_URL: &str =
+    "https://raw.githubusercontent.com/trustwallet/assets/master/blockchains/ethereum";
+
+#[tlock_plugin(LifecyclePlugin, GlobalPlugin, TokenListPlugin)]
+struct TokenRegistry;
+
+// ---------- Plugin Handlers ----------
+
+#[tlock_plugin]
+impl LifecyclePlugin for TokenRegistry {
+    async fn get_manifest(_transport: Transport, _params: ()) -> Result<PluginManifest, RpcError> {
+        Ok(PluginManifest::new().with_domain(Domain::TokenList))
+    }
+
+    async fn init(transport: Transport, _params: ()) -> Result<(), RpcError> {
+        info!("Initializing Token Registry Plugin");
+
+        host::RegisterEntity
+            .call_async(transport, Domain::TokenList)
+            .await?;
+        Ok(())
+    }
+}
+
+#[tlock_plugin]
+impl GlobalPlugin for TokenRegistry {
+    async fn ping(transport: Transport, _params: ()) -> Result<String, RpcError> {
+        global::Ping.call_async(transport, ()).await?;
+        Ok("pong".to_string())
+    }
+}
+
+// ---------- Token List Handlers ----------
+
+#[tlock_plugin]
+impl TokenListPlugin for TokenRegistry {
+    async fn get_list(
+        _transport: Transport,
+        params: (TokenListId, ChainId),
+    ) -> Result<Vec<TokenInfo>, RpcError> {
+        let (_list_id, chain_id) = params;
+
+        let tokens = all_tokens()
+            .into_iter()
+            .filter(|t| t.asset_id.chain_id == chain_id)
+            .collect();
+        Ok(tokens)
+    }
+
+    async fn search(
+        _transport: Transport,
+        params: (TokenListId, String),
+    ) -> Result<Vec<TokenInfo>, RpcError> {
+        let (_list_id, query) = params;
+        let query = query.trim().to_lowercase();
+        if query.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        //? Exact symbol matches first, then partial symbol / name / address matches
+        let (mut exact, partial): (Vec<_>, Vec<_>) = all_tokens()
+            .into_iter()
+            .filter(|t| {
+                t.symbol.to_lowercase().contains(&query)
+                    || t.name.to_lowercase().contains(&query)
+                    || t.asset_id.to_string().to_lowercase().contains(&query)
+            })
+            .partition(|t| t.symbol.to_lowercase() == query);
+        exact.extend(partial);
+        Ok(exact)
+    }
+}
+
+// ---------- Helpers ----------
+
+fn all_tokens() -> Vec<TokenInfo> {
+    let eth = TokenInfo {
+        asset_id: AssetId::eth(CHAIN_ID),
+        symbol: "ETH".to_string(),
+        name: "Ether".to_string(),
+        decimals: 18,
+        logo_url: Some(format!("{}/info/logo.png", LOGO_BASE_URL)),
+    };
+
+    let erc20s = ERC20S.iter().map(|erc20| TokenInfo {
+        asset_id: erc20.asset_id.clone(),
+        symbol: erc20.symbol.to_string(),
+        name: erc20.name.to_string(),
+        decimals: erc20.decimals,
+        //? Asset paths use the checksummed address, which is Address's Display
+        logo_url: Some(format!(
+            "{}/assets/{}/logo.png",
+            LOGO_BASE_URL, erc20.address
+        )),
+    });
+
+    std::iter::once(eth).chain(erc20s).collect()
+}
+
+fn main() {
+    fmt()
+        .with_writer(stderr)
+        .without_time()
+        .with_ansi(false)
+        .compact()
+        .init();
+
+    TokenRegistry::runner().run();
+}
@@ -16,10 +16,10 @@ use erc20s::{CHAIN_ID, ERC20S};
 use serde::{Deserialize, Serialize};
 use tlock_alloy::AlloyBridge;
 use tlock_pdk::{
-    runner::PluginRunner,
+    handlers::{GlobalPlugin, LifecyclePlugin, PagePlugin},
     state::StateExt,
     tlock_api::{
-        RpcMethod,
+        OptionalMethod, RpcMethod,
         caip::{AssetId, AssetType, ChainId},
         component::{
             asset, button_input, container, dropdown, form, heading, submit_input, text, text_input,
@@ -27,8 +27,12 @@ use tlock_pdk::{
         coordinator,
         domains::Domain,
         entities::{CoordinatorId, EthProviderId, PageId},
-        global, host, page, plugin,
+        global,
+        host::{self, EntityCapabilities, Toast, ToastLevel},
+        page,
+        plugin::PluginManifest,
     },
+    tlock_plugin,
     wasmi_plugin_pdk::{
         rpc_message::{RpcError, RpcErrorContext, ToRpcResult},
         transport::Transport,
@@ -95,87 +99,114 @@ sol! {
     }
 }
 
+#[tlock_plugin(LifecyclePlugin, GlobalPlugin, PagePlugin)]
+struct UniswapV2;
+
 // ---------- Plugin Handlers ----------
 
-async fn init(transport: Transport, _params: ()) -> Result<(), RpcError> {
-    info!("Initializing Uniswap V2 Plugin");
+#[tlock_plugin]
+impl LifecyclePlugin for UniswapV2 {
+    async fn get_manifest(_transport: Transport, _params: ()) -> Result<PluginManifest, RpcError> {
+        let manifest = PluginManifest::new()
+            .with_method(host::RequestEthProvider)
+            .with_method(host::RequestCoordinator)
+            .with_method(coordinator::GetSession)
+            .with_method(coordinator::CancelSession)
+            .with_method(coordinator::Simulate)
+            .with_method(coordinator::Propose)
+            .with_domain(Domain::Page);
+        Ok(AlloyBridge::manifest(manifest))
+    }
 
-    let provider_id = host::RequestEthProvider
-        .call_async(transport.clone(), ChainId::new_evm(CHAIN_ID))
-        .await?;
-    let coordinator_id = host::RequestCoordinator
-        .call_async(transport.clone(), ())
-        .await?;
+    async fn init(transport: Transport, _params: ()) -> Result<(), RpcError> {
+        info!("Initializing Uniswap V2 Plugin");
 
-    let page_id = host::RegisterEntity
-        .call_async(transport.clone(), Domain::Page)
-        .await?;
+        let provider_id = host::RequestEthProvider
+            .call_async(
+                transport.clone(),
+                (Some(ChainId::new_evm(CHAIN_ID)), EntityCapabilities::new()),
+            )
+            .await?;
+        let coordinator_id = host::RequestCoordinator
+            .call_async(transport.clone(), ())
+            .await?;
 
-    let page_id = match page_id {
-        tlock_pdk::tlock_api::entities::EntityId::Page(id) => Some(id),
-        _ => None,
-    }
-    .context("Invalid Page ID")?;
-
-    let state = PluginState {
-        coordinator_id,
-        provider_id,
-        page_id,
-        quote: None,
-        last_message: None,
-    };
+        let page_id = host::RegisterEntity
+            .call_async(transport.clone(), Domain::Page)
+            .await?;
 
-    transport.state().lock_or(|| state)?;
+        let page_id = match page_id {
+            tlock_pdk::tlock_api::entities::EntityId::Page(id) => Some(id),
+            _ => None,
+        }
+        .context("Invalid Page ID")?;
 
-    Ok(())
+        let state = PluginState {
+            coordinator_id,
+            provider_id,
+            page_id,
+            quote: None,
+            last_message: None,
+        };
+
+        transport.state().lock_or(|| state)?;
+
+        Ok(())
+    }
 }
 
-async fn ping(transport: Transport, _params: ()) -> Result<String, RpcError> {
-    global::Ping.call_async(transport, ()).await?;
-    Ok("pong".to_string())
+#[tlock_plugin]
+impl GlobalPlugin for UniswapV2 {
+    async fn ping(transport: Transport, _params: ()) -> Result<String, RpcError> {
+        global::Ping.call_async(transport, ()).await?;
+        Ok("pong".to_string())
+    }
 }
 
 // ---------- Page Handlers ----------
 
-async fn on_load(transport: Transport, page_id: PageId) -> Result<(), RpcError> {
-    info!("Page loaded: {}", page_id);
+#[tlock_plugin]
+impl PagePlugin for UniswapV2 {
+    async fn on_load(transport: Transport, page_id: PageId) -> Result<(), RpcError> {
+        info!("Page loaded: {}", page_id);
 
-    let state: PluginState = transport.state().read()?;
-    let component = build_ui(&state);
-    host::SetPage
-        .call_async(transport.clone(), (page_id, component))
-        .await?;
-
-    Ok(())
-}
+        let state: PluginState = transport.state().read()?;
+        let component = build_ui(&state);
+        host::SetPage
+            .call_async(transport.clone(), (page_id, component))
+            .await?;
 
-async fn on_update(
-    transport: Transport,
-    params: (PageId, page::PageEvent),
-) -> Result<(), RpcError> {
-    let (page_id, event) = params;
-    info!("Page updated: {:?}", event);
+        Ok(())
+    }
 
-    let mut state = transport.state().try_lock::<PluginState>()?;
-    match event {
-        page::PageEvent::FormSubmitted(form_id, form_data) if form_id == "swap_form" => {
-            handle_swap_form_update(&transport, &mut state, form_data).await?;
+    async fn on_update(
+        transport: Transport,
+        params: (PageId, page::PageEvent),
+    ) -> Result<(), RpcError> {
+        let (page_id, event) = params;
+        info!("Page updated: {:?}", event);
+
+        let mut state = transport.state().try_lock::<PluginState>()?;
+        match event {
+            page::PageEvent::FormSubmitted(form_id, form_data) if form_id == "swap_form" => {
+                handle_swap_form_update(&transport, &mut state, form_data).await?;
+            }
+            page::PageEvent::ButtonClicked(button_id) if button_id == "execute_swap" => {
+                handle_execute_swap(&transport, &mut state).await?;
+            }
+            _ => {
+                warn!("Unhandled page event: {:?}", event);
+                return Ok(());
+            }
         }
-        page::PageEvent::ButtonClicked(button_id) if button_id == "execute_swap" => {
-            handle_execute_swap(&transport, &mut state).await?;
-        }
-        _ => {
-            warn!("Unhandled page event: {:?}", event);
-            return Ok(());
-        }
-    }
 
-    let component = build_ui(&state);
-    host::SetPage
-        .call_async(transport.clone(), (page_id, component))
-        .await?;
+        let component = build_ui(&state);
+        host::SetPage
+            .call_async(transport.clone(), (page_id, component))
+            .await?;
 
-    Ok(())
+        Ok(())
+    }
 }
 
 // ---------- Event Handler Functions ----------
@@ -332,7 +363,7 @@ async fn handle_execute_swap(
     transport: &Transport,
     state: &mut PluginState,
 ) -> Result<(), RpcError> {
-    state.last_message = Some("Preparing swap...".into());
+    state.last_message = None;
 
     let Some(quote) = &state.quote else {
         state.last_message = Some("No quote available".into());
@@ -349,7 +380,8 @@ async fn handle_execute_swap(
             transport.clone(),
             (coordinator_id, ChainId::new_evm(CHAIN_ID), None),
         )
-        .await?;
+        .await?
+        .account;
 
     // Get account address
     let Some(account_address) = account_id.as_evm_address() else {
@@ -384,6 +416,26 @@ async fn handle_execute_swap(
         operations,
     };
 
+    // Simulate before proposing so reverting swaps never reach the vault.
+    // Coordinators that can't simulate are proposed to directly.
+    let simulation = coordinator::Simulate
+        .call_optional(
+            transport.clone(),
+            (coordinator_id, account_id.clone(), bundle.clone()),
+        )
+        .await?;
+    if simulation.is_none() {
+        info!("Coordinator doesn't support simulation, proposing unsimulated");
+    }
+    if let Some(reason) = simulation.and_then(|s| s.revert_reason) {
+        coordinator::CancelSession
+            .call_async(transport.clone(), (coordinator_id, account_id))
+            .await?;
+        let toast = Toast::new(ToastLevel::Warning, format!("Swap would fail: {}", reason));
+        host::ShowToast.call_async(transport.clone(), toast).await?;
+        return Ok(());
+    }
+
     // Propose to coordinator
     host::Notify
         .call_async(
@@ -395,25 +447,15 @@ async fn handle_execute_swap(
         .call_async(transport.clone(), (coordinator_id, account_id, bundle))
         .await;
     if let Err(err) = proposal {
-        state.last_message = Some(format!("Swap failed: {}", err));
-        host::Notify
-            .call_async(
-                transport.clone(),
-                (host::NotifyLevel::Error, format!("Swap failed")),
-            )
-            .await?;
+        let toast = Toast::new(ToastLevel::Error, format!("Swap failed: {}", err));
+        host::ShowToast.call_async(transport.clone(), toast).await?;
         return Ok(());
     }
 
-    state.last_message = Some("Swap executed".into());
     state.quote = None;
 
-    host::Notify
-        .call_async(
-            transport.clone(),
-            (host::NotifyLevel::Info, format!("Swap executed")),
-        )
-        .await?;
+    let toast = Toast::new(ToastLevel::Success, "Swap executed successfully!");
+    host::ShowToast.call_async(transport.clone(), toast).await?;
 
     Ok(())
 }
@@ -541,10 +583,5 @@ fn main() {
         .init();
     info!("Starting Uniswap V2 Plugin...");
 
-    PluginRunner::new()
-        .with_method(plugin::Init, init)
-        .with_method(global::Ping, ping)
-        .with_method(page::OnLoad, on_load)
-        .with_method(page::OnUpdate, on_update)
-        .run();
+    UniswapV2::runner().run();
 }